@@ -127,6 +127,8 @@ delete Object.prototype.__proto__;
     GPUPipelineLayout: util.nonEnumerable(webgpu.GPUPipelineLayout),
     GPUBindGroup: util.nonEnumerable(webgpu.GPUBindGroup),
     GPUShaderModule: util.nonEnumerable(webgpu.GPUShaderModule),
+    GPUCompilationInfo: util.nonEnumerable(webgpu.GPUCompilationInfo),
+    GPUCompilationMessage: util.nonEnumerable(webgpu.GPUCompilationMessage),
     GPUShaderStage: util.nonEnumerable(webgpu.GPUShaderStage),
     GPUComputePipeline: util.nonEnumerable(webgpu.GPUComputePipeline),
     GPURenderPipeline: util.nonEnumerable(webgpu.GPURenderPipeline),
@@ -140,6 +142,8 @@ delete Object.prototype.__proto__;
     GPUQuerySet: util.nonEnumerable(webgpu.GPUQuerySet),
     GPUOutOfMemoryError: util.nonEnumerable(webgpu.GPUOutOfMemoryError),
     GPUValidationError: util.nonEnumerable(webgpu.GPUValidationError),
+    GPUUncapturedErrorEvent: util.nonEnumerable(webgpu.GPUUncapturedErrorEvent),
+    GPUDeviceLostInfo: util.nonEnumerable(webgpu.GPUDeviceLostInfo),
   };
 
   windowOrWorkerGlobalScope.console.enumerable = false;
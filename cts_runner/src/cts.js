@@ -0,0 +1,36 @@
+// Runs test cases of the WebGPU CTS, and writes their status to a JSON file.
+//
+// Usage: cts.js <CTS out directory URL> <results path> <query>...
+
+const [outUrl, resultsPath, ...queries] = Deno.args;
+
+const { DefaultTestFileLoader } = await import(
+  new URL("common/internal/file_loader.js", outUrl)
+);
+const { parseQuery } = await import(
+  new URL("common/internal/query/parseQuery.js", outUrl)
+);
+const { Logger } = await import(
+  new URL("common/internal/logging/logger.js", outUrl)
+);
+
+const loader = new DefaultTestFileLoader();
+const log = new Logger(false);
+const results = {};
+
+for (const query of queries) {
+  const testcases = await loader.loadCases(parseQuery(query));
+  for (const testcase of testcases) {
+    const name = testcase.query.toString();
+    const [rec, res] = log.record(name);
+    await testcase.run(rec, []);
+    // Warnings don't fail a case.
+    results[name] = res.status === "warn" ? "pass" : res.status;
+    console.log(`[${results[name]}] ${name}`);
+  }
+}
+
+Deno.writeFileSync(
+  resultsPath,
+  new TextEncoder().encode(JSON.stringify(results)),
+);
@@ -0,0 +1,293 @@
+//! Headless mode running test cases of the WebGPU CTS.
+//!
+//! The status of every case is compared with an expectations file, mapping case
+//! names to `"pass"`, `"fail"` or `"skip"`. Only regressions, cases expected to
+//! pass that now fail or are skipped, and cases expected to be skipped that now fail,
+//! are errors, and so are expected cases which didn't run at all. The file is rewritten
+//! with the new statuses when `--update` is given.
+//!
+//! Unless `DENO_WEBGPU_BACKEND` is set, the cases run on the software adapter,
+//! so that the results are the same on every machine.
+
+use std::{
+    collections::BTreeMap,
+    env, fs,
+    path::{Path, PathBuf},
+};
+
+use deno_core::error::anyhow;
+use deno_core::error::AnyError;
+use deno_core::resolve_url_or_path;
+use deno_core::serde_json;
+
+const USAGE: &str = "\
+usage: cts_runner cts --cts-dir <DIR> [--test-list <FILE>] [--expectations <FILE>] [--update] [QUERY]...
+
+Runs the queries of the test list, or the given queries, against the CTS built in <DIR>/out,
+and compares the status of each case with the expectations file.
+The software adapter is used unless DENO_WEBGPU_BACKEND is set.";
+
+type Statuses = BTreeMap<String, String>;
+
+/// Options of the CTS mode.
+pub struct Options {
+    /// Checkout of the `gh-pages` branch of the CTS.
+    cts_dir: PathBuf,
+    /// File listing the queries to run, one per line.
+    test_list: PathBuf,
+    /// Expected status of each case.
+    expectations: PathBuf,
+    /// Rewrite the expectations with the new statuses instead of failing on regressions.
+    update: bool,
+    /// Queries to run instead of the test list.
+    queries: Vec<String>,
+}
+
+impl Options {
+    pub fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, AnyError> {
+        let manifest_dir = Path::new(env!("CARGO_MANIFEST_DIR"));
+        let mut cts_dir = None;
+        let mut options = Options {
+            cts_dir: PathBuf::new(),
+            test_list: manifest_dir.join("test.lst"),
+            expectations: manifest_dir.join("expectations.json"),
+            update: false,
+            queries: Vec::new(),
+        };
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .map(PathBuf::from)
+                    .ok_or_else(|| anyhow!("missing value of {}\n\n{}", arg, USAGE))
+            };
+            match arg.as_str() {
+                "--cts-dir" => cts_dir = Some(value()?),
+                "--test-list" => options.test_list = value()?,
+                "--expectations" => options.expectations = value()?,
+                "--update" => options.update = true,
+                _ if arg.starts_with("--") => {
+                    return Err(anyhow!("unknown option {}\n\n{}", arg, USAGE))
+                }
+                _ => options.queries.push(arg),
+            }
+        }
+
+        options.cts_dir = cts_dir.ok_or_else(|| anyhow!("missing --cts-dir\n\n{}", USAGE))?;
+        Ok(options)
+    }
+
+    fn queries(&self) -> Result<Vec<String>, AnyError> {
+        if !self.queries.is_empty() {
+            return Ok(self.queries.clone());
+        }
+        let list = fs::read_to_string(&self.test_list)
+            .map_err(|err| anyhow!("failed to read {}: {}", self.test_list.display(), err))?;
+        Ok(list
+            .lines()
+            .map(str::trim)
+            .filter(|line| !line.is_empty() && !line.starts_with('#'))
+            .map(String::from)
+            .collect())
+    }
+}
+
+/// Differences between the expected and actual statuses.
+#[derive(Default)]
+struct Comparison<'a> {
+    /// Cases expected to pass, which failed or were skipped,
+    /// and cases expected to be skipped, which failed.
+    regressions: Vec<(&'a str, &'a str)>,
+    /// Cases expected to fail or be skipped, which passed.
+    fixed: Vec<(&'a str, &'a str)>,
+    /// Cases without expectation.
+    new: Vec<&'a str>,
+    /// Cases with an expectation, which didn't run.
+    missing: Vec<(&'a str, &'a str)>,
+}
+
+fn compare<'a>(expectations: &'a Statuses, results: &'a Statuses) -> Comparison<'a> {
+    let mut comparison = Comparison::default();
+    for (name, expected) in expectations {
+        if !results.contains_key(name) {
+            comparison.missing.push((name, expected));
+        }
+    }
+    for (name, status) in results {
+        match expectations.get(name).map(String::as_str) {
+            None => comparison.new.push(name),
+            Some(expected) if expected == status => {}
+            Some(expected) if status == "fail" || expected == "pass" => {
+                comparison.regressions.push((name, expected))
+            }
+            Some(expected) if status == "pass" => comparison.fixed.push((name, expected)),
+            // Failing cases that are now skipped.
+            Some(_) => {}
+        }
+    }
+    comparison
+}
+
+fn read_statuses(path: &Path) -> Result<Statuses, AnyError> {
+    let file =
+        fs::read(path).map_err(|err| anyhow!("failed to read {}: {}", path.display(), err))?;
+    Ok(serde_json::from_slice(&file)?)
+}
+
+pub async fn run(options: Options) -> Result<(), AnyError> {
+    let queries = options.queries()?;
+    if env::var_os("DENO_WEBGPU_BACKEND").is_none() {
+        env::set_var("DENO_WEBGPU_BACKEND", "cpu");
+    }
+    let out_dir = fs::canonicalize(options.cts_dir.join("out")).map_err(|err| {
+        anyhow!(
+            "failed to find the built CTS in {}: {}",
+            options.cts_dir.display(),
+            err
+        )
+    })?;
+    let results_path = env::temp_dir().join(format!("cts_runner_{}.json", std::process::id()));
+
+    let mut args = vec![
+        format!("{}/", resolve_url_or_path(&out_dir.to_string_lossy())?),
+        results_path.to_string_lossy().into_owned(),
+    ];
+    args.extend(queries);
+    let harness = resolve_url_or_path(concat!(env!("CARGO_MANIFEST_DIR"), "/src/cts.js"))?;
+    super::run_module(&harness, args).await?;
+
+    let results = read_statuses(&results_path)?;
+    fs::remove_file(&results_path)?;
+    let expectations = if options.expectations.exists() {
+        read_statuses(&options.expectations)?
+    } else {
+        Statuses::new()
+    };
+
+    let count = |status: &str| results.values().filter(|s| *s == status).count();
+    println!(
+        "{} cases: {} passed, {} failed, {} skipped",
+        results.len(),
+        count("pass"),
+        count("fail"),
+        count("skip")
+    );
+
+    let mut comparison = compare(&expectations, &results);
+    if !options.queries.is_empty() {
+        // Only a part of the test list ran.
+        comparison.missing.clear();
+    }
+    for &(name, expected) in comparison.fixed.iter() {
+        println!(
+            "{} {} (expected {})",
+            super::green_bold("fixed"),
+            name,
+            expected
+        );
+    }
+    for &name in comparison.new.iter() {
+        println!("{} {} ({})", super::yellow_bold("new"), name, results[name]);
+    }
+    for &(name, expected) in comparison.regressions.iter() {
+        println!(
+            "{} {} (expected {})",
+            super::red_bold("regression"),
+            name,
+            expected
+        );
+    }
+    for &(name, expected) in comparison.missing.iter() {
+        println!(
+            "{} {} (expected {})",
+            super::red_bold("missing"),
+            name,
+            expected
+        );
+    }
+    let regressions = comparison.regressions.len();
+    let missing = comparison.missing.len();
+
+    if options.update {
+        // The statuses of the cases that didn't run are kept for a partial run,
+        // and dropped when the whole test list ran.
+        let expectations = if options.queries.is_empty() {
+            results
+        } else {
+            let mut expectations = expectations;
+            expectations.extend(results);
+            expectations
+        };
+        let mut file = serde_json::to_string_pretty(&expectations)?;
+        file.push('\n');
+        fs::write(&options.expectations, file)?;
+        println!("updated {}", options.expectations.display());
+        Ok(())
+    } else if expectations.is_empty() {
+        Err(anyhow!(
+            "{} has no expectations, record them with --update",
+            options.expectations.display()
+        ))
+    } else if regressions != 0 || missing != 0 {
+        Err(anyhow!(
+            "{} regressions and {} missing cases, compared to {}",
+            regressions,
+            missing,
+            options.expectations.display()
+        ))
+    } else {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn statuses(cases: &[(&str, &str)]) -> Statuses {
+        cases
+            .iter()
+            .map(|&(name, status)| (name.to_string(), status.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn compares_statuses() {
+        let expectations = statuses(&[
+            ("pass_to_fail", "pass"),
+            ("pass_to_skip", "pass"),
+            ("skip_to_fail", "skip"),
+            ("skip_to_pass", "skip"),
+            ("fail_to_pass", "fail"),
+            ("fail_to_skip", "fail"),
+            ("unchanged", "pass"),
+            ("removed", "pass"),
+        ]);
+        let results = statuses(&[
+            ("pass_to_fail", "fail"),
+            ("pass_to_skip", "skip"),
+            ("skip_to_fail", "fail"),
+            ("skip_to_pass", "pass"),
+            ("fail_to_pass", "pass"),
+            ("fail_to_skip", "skip"),
+            ("unchanged", "pass"),
+            ("new", "fail"),
+        ]);
+
+        let comparison = compare(&expectations, &results);
+        assert_eq!(
+            comparison.regressions,
+            vec![
+                ("pass_to_fail", "pass"),
+                ("pass_to_skip", "pass"),
+                ("skip_to_fail", "skip"),
+            ]
+        );
+        assert_eq!(
+            comparison.fixed,
+            vec![("fail_to_pass", "fail"), ("skip_to_pass", "skip")]
+        );
+        assert_eq!(comparison.new, vec!["new"]);
+        assert_eq!(comparison.missing, vec![("removed", "pass")]);
+    }
+}
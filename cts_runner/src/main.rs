@@ -11,15 +11,20 @@ use deno_core::resolve_url_or_path;
 use deno_core::serde_json;
 use deno_core::serde_json::json;
 use deno_core::JsRuntime;
+use deno_core::ModuleSpecifier;
 use deno_core::OpState;
 use deno_core::RuntimeOptions;
 use deno_core::ZeroCopyBuf;
 use deno_web::BlobStore;
 use termcolor::Ansi;
+use termcolor::Color::Green;
 use termcolor::Color::Red;
+use termcolor::Color::Yellow;
 use termcolor::ColorSpec;
 use termcolor::WriteColor;
 
+mod cts;
+
 #[tokio::main(flavor = "current_thread")]
 async fn main() {
     unwrap_or_exit(run().await)
@@ -31,8 +36,16 @@ async fn run() -> Result<(), AnyError> {
     let url = args_iter
         .next()
         .ok_or_else(|| anyhow!("missing specifier in first command line argument"))?;
+    if url == "cts" {
+        let options = cts::Options::parse(args_iter)?;
+        return cts::run(options).await;
+    }
     let specifier = resolve_url_or_path(&url)?;
+    run_module(&specifier, args_iter.collect()).await
+}
 
+/// Executes the JS module at `specifier`, with `args` as `Deno.args`.
+async fn run_module(specifier: &ModuleSpecifier, args: Vec<String>) -> Result<(), AnyError> {
     let options = RuntimeOptions {
         module_loader: Some(Rc::new(deno_core::FsModuleLoader)),
         get_error_class_fn: Some(&get_error_class_name),
@@ -48,7 +61,6 @@ async fn run() -> Result<(), AnyError> {
         ..Default::default()
     };
     let mut isolate = JsRuntime::new(options);
-    let args = args_iter.collect::<Vec<String>>();
     let cfg = json!({"args": args, "cwd": env::current_dir().unwrap().to_string_lossy() });
     let bootstrap_script = format!("globalThis.bootstrap({})", serde_json::to_string(&cfg)?);
     isolate.execute_script(&located_script_name!(), &bootstrap_script)?;
@@ -58,7 +70,7 @@ async fn run() -> Result<(), AnyError> {
         .borrow_mut()
         .put(deno_timers::NoTimersPermission);
 
-    let mod_id = isolate.load_module(&specifier, None).await?;
+    let mod_id = isolate.load_module(specifier, None).await?;
     let mod_rx = isolate.mod_evaluate(mod_id);
 
     let rx = tokio::spawn(async move {
@@ -149,3 +161,15 @@ fn red_bold<S: AsRef<str>>(s: S) -> impl fmt::Display {
     style_spec.set_fg(Some(Red)).set_bold(true);
     style(s, style_spec)
 }
+
+fn green_bold<S: AsRef<str>>(s: S) -> impl fmt::Display {
+    let mut style_spec = ColorSpec::new();
+    style_spec.set_fg(Some(Green)).set_bold(true);
+    style(s, style_spec)
+}
+
+fn yellow_bold<S: AsRef<str>>(s: S) -> impl fmt::Display {
+    let mut style_spec = ColorSpec::new();
+    style_spec.set_fg(Some(Yellow)).set_bold(true);
+    style(s, style_spec)
+}
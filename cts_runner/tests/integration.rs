@@ -25,3 +25,12 @@ fn hello_compute_example() {
         .unwrap();
     assert!(output.status.success())
 }
+
+#[test]
+fn cts_mode_requires_cts_dir() {
+    let output = std::process::Command::new(cts_runner_exe_path())
+        .arg("cts")
+        .output()
+        .unwrap();
+    assert!(!output.status.success())
+}
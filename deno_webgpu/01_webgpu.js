@@ -12,24 +12,27 @@
   const core = window.Deno.core;
   const webidl = window.__bootstrap.webidl;
   const eventTarget = window.__bootstrap.eventTarget;
+  const { defineEventHandler } = window.__bootstrap.event;
   const { DOMException } = window.__bootstrap.domException;
   const {
     ArrayBuffer,
     ArrayBufferIsView,
     ArrayIsArray,
     ArrayPrototypeFilter,
+    ArrayPrototypeIncludes,
+    ArrayPrototypeIndexOf,
     ArrayPrototypeMap,
     ArrayPrototypePop,
     ArrayPrototypePush,
+    ArrayPrototypeSlice,
+    ArrayPrototypeSplice,
     Error,
     MathMax,
     ObjectDefineProperty,
     ObjectFreeze,
     Promise,
     PromiseAll,
-    PromisePrototypeCatch,
     PromisePrototypeThen,
-    PromiseReject,
     PromiseResolve,
     Set,
     SetPrototypeEntries,
@@ -172,6 +175,52 @@
     }
   }
 
+  /**
+   * @param {{ type: string, value: string | null }} err
+   * @returns {GPUError}
+   */
+  function createGPUError(err) {
+    switch (err.type) {
+      case "validation":
+        return new GPUValidationError(err.value ?? "validation error");
+      case "out-of-memory":
+        return new GPUOutOfMemoryError();
+    }
+  }
+
+  const _error = Symbol("[[error]]");
+
+  class GPUUncapturedErrorEvent extends Event {
+    /** @type {GPUError} */
+    [_error];
+
+    /**
+     * @param {string} type
+     * @param {GPUUncapturedErrorEventInit} eventInitDict
+     */
+    constructor(type, eventInitDict) {
+      const prefix = "Failed to construct 'GPUUncapturedErrorEvent'";
+      webidl.requiredArguments(arguments.length, 2, { prefix });
+      type = webidl.converters.DOMString(type, {
+        prefix,
+        context: "Argument 1",
+      });
+      eventInitDict = webidl.converters.GPUUncapturedErrorEventInit(
+        eventInitDict,
+        { prefix, context: "Argument 2" },
+      );
+      super(type, eventInitDict);
+      this[webidl.brand] = webidl.brand;
+      this[_error] = eventInitDict.error;
+    }
+
+    /** @returns {GPUError} */
+    get error() {
+      webidl.assertBranded(this, GPUUncapturedErrorEvent);
+      return this[_error];
+    }
+  }
+
   class GPU {
     [webidl.brand] = webidl.brand;
 
@@ -616,12 +665,6 @@
   const _device = Symbol("[[device]]");
   const _queue = Symbol("[[queue]]");
 
-  /**
-   * @typedef ErrorScope
-   * @property {string} filter
-   * @property {Promise<void>[]} operations
-   */
-
   /**
    * @typedef InnerGPUDeviceOptions
    * @property {GPUAdapter} adapter
@@ -647,8 +690,12 @@
     lost;
     /** @type {(info: GPUDeviceLostInfo) => void} */
     resolveLost;
-    /** @type {ErrorScope[]} */
-    errorScopeStack;
+    /** @type {GPUDevice | undefined} */
+    device;
+    /** @type {number[]} */
+    errorScopeIds;
+    /** @type {{ scopeIds: number[], promise: Promise<void> }[]} */
+    pendingErrors;
 
     /**
      * @param {InnerGPUDeviceOptions} options
@@ -659,12 +706,13 @@
       this.features = options.features;
       this.limits = options.limits;
       this.resources = [];
+      this.errorScopeIds = [];
+      this.pendingErrors = [];
       this.isLost = false;
       this.resolveLost = () => {};
       this.lost = new Promise((resolve) => {
         this.resolveLost = resolve;
       });
-      this.errorScopeStack = [];
     }
 
     /** @param {any} resource */
@@ -672,75 +720,78 @@
       ArrayPrototypePush(this.resources, new WeakRef(resource));
     }
 
-    /** @param {{ type: string, value: string | null } | undefined} err */
-    pushError(err) {
-      this.pushErrorPromise(PromiseResolve(err));
+    /**
+     * @param {string | undefined} reason
+     * @param {string} message
+     */
+    lose(reason, message) {
+      if (this.isLost) return;
+      this.isLost = true;
+      this.resolveLost(createGPUDeviceLostInfo(reason, message));
     }
 
-    /** @param {Promise<{ type: string, value: string | null } | undefined>} promise */
-    pushErrorPromise(promise) {
-      const operation = PromisePrototypeThen(promise, (err) => {
-        if (err) {
-          switch (err.type) {
-            case "lost":
-              this.isLost = true;
-              this.resolveLost(
-                createGPUDeviceLostInfo(undefined, "device was lost"),
-              );
-              break;
-            case "validation":
-              return PromiseReject(
-                new GPUValidationError(err.value ?? "validation error"),
-              );
-            case "out-of-memory":
-              return PromiseReject(new GPUOutOfMemoryError());
-          }
-        }
-      });
-
-      const validationStack = ArrayPrototypeFilter(
-        this.errorScopeStack,
-        ({ filter }) => filter == "validation",
-      );
-      const validationScope = validationStack[validationStack.length - 1];
-      const validationFilteredPromise = PromisePrototypeCatch(
-        operation,
-        (err) => {
-          if (err instanceof GPUValidationError) return PromiseReject(err);
-          return PromiseResolve();
-        },
+    /**
+     * Passes the error of an operation to the error scopes of the device,
+     * which are kept by the device resource. Errors that no scope captures
+     * are dispatched as `uncapturederror` events.
+     *
+     * @param {{ type: string, value: string | null } | undefined} err
+     * @param {number[]} scopeIds ids of the error scopes on the stack when the operation was issued
+     */
+    pushError(err, scopeIds = this.errorScopeIds) {
+      if (!err || this.rid === undefined) return;
+      if (err.type === "lost") {
+        this.lose(undefined, "device was lost");
+      }
+      const { err: uncapturedErr } = core.opSync(
+        "op_webgpu_device_handle_error",
+        { deviceRid: this.rid, err, scopeIds },
       );
-      if (validationScope) {
-        ArrayPrototypePush(
-          validationScope.operations,
-          validationFilteredPromise,
+      if (uncapturedErr && this.device) {
+        const error = createGPUError(uncapturedErr);
+        this.device.dispatchEvent(
+          new GPUUncapturedErrorEvent("uncapturederror", { error }),
         );
-      } else {
-        PromisePrototypeCatch(validationFilteredPromise, () => {
-          // TODO(lucacasonato): emit an UncapturedErrorEvent
-        });
       }
-      // prevent uncaptured promise rejections
-      PromisePrototypeCatch(validationFilteredPromise, (_err) => {});
+    }
 
-      const oomStack = ArrayPrototypeFilter(
-        this.errorScopeStack,
-        ({ filter }) => filter == "out-of-memory",
+    /**
+     * The error is passed to the error scopes that are on the stack when
+     * the operation is issued, which aren't popped until it completes.
+     *
+     * @param {Promise<{ type: string, value: string | null } | undefined>} promise
+     */
+    pushErrorPromise(promise) {
+      const scopeIds = ArrayPrototypeSlice(this.errorScopeIds);
+      const settle = () => {
+        ArrayPrototypeSplice(
+          this.pendingErrors,
+          ArrayPrototypeIndexOf(this.pendingErrors, pending),
+          1,
+        );
+      };
+      const pending = {
+        scopeIds,
+        promise: PromisePrototypeThen(promise, (err) => {
+          settle();
+          this.pushError(err, scopeIds);
+        }, settle),
+      };
+      ArrayPrototypePush(this.pendingErrors, pending);
+    }
+
+    /**
+     * Waits for the operations issued within an error scope to complete.
+     *
+     * @param {number} scopeId
+     * @returns {Promise<void[]>}
+     */
+    settleErrorScope(scopeId) {
+      const pending = ArrayPrototypeFilter(
+        this.pendingErrors,
+        (pending) => ArrayPrototypeIncludes(pending.scopeIds, scopeId),
       );
-      const oomScope = oomStack[oomStack.length - 1];
-      const oomFilteredPromise = PromisePrototypeCatch(operation, (err) => {
-        if (err instanceof GPUOutOfMemoryError) return PromiseReject(err);
-        return PromiseResolve();
-      });
-      if (oomScope) {
-        ArrayPrototypePush(oomScope.operations, oomFilteredPromise);
-      } else {
-        PromisePrototypeCatch(oomFilteredPromise, () => {
-          // TODO(lucacasonato): emit an UncapturedErrorEvent
-        });
-      }
-      // prevent uncaptured promise rejections
-      PromisePrototypeCatch(oomFilteredPromise, (_err) => {});
+      return PromiseAll(ArrayPrototypeMap(pending, (pending) => pending.promise));
     }
   }
 
@@ -753,9 +804,11 @@
   function createGPUDevice(label, inner, queue) {
     /** @type {GPUDevice} */
     const device = webidl.createBranded(GPUDevice);
+    eventTarget.setEventTargetData(device);
     device[_label] = label;
     device[_device] = inner;
     device[_queue] = queue;
+    inner.device = device;
     return device;
   }
 
@@ -804,6 +857,7 @@
 
     destroy() {
       webidl.assertBranded(this, GPUDevice);
+      this[_device].lose("destroyed", "device was destroyed");
       this[_cleanup]();
     }
 
@@ -1358,14 +1412,7 @@
 
     get lost() {
       webidl.assertBranded(this, GPUDevice);
-      const device = this[_device];
-      if (!device) {
-        return PromiseResolve(true);
-      }
-      if (device.rid === undefined) {
-        return PromiseResolve(true);
-      }
-      return device.lost;
+      return this[_device].lost;
     }
 
     /**
@@ -1380,33 +1427,36 @@
         context: "Argument 1",
       });
       const device = assertDevice(this, { prefix, context: "this" });
-      ArrayPrototypePush(device.errorScopeStack, { filter, operations: [] });
+      const scopeId = core.opSync("op_webgpu_device_push_error_scope", {
+        deviceRid: device.rid,
+        filter,
+      });
+      ArrayPrototypePush(device.errorScopeIds, scopeId);
     }
 
     /**
      * @returns {Promise<GPUError | null>}
      */
-    // deno-lint-ignore require-await
     async popErrorScope() {
       webidl.assertBranded(this, GPUDevice);
       const prefix = "Failed to execute 'popErrorScope' on 'GPUDevice'";
       const device = assertDevice(this, { prefix, context: "this" });
-      if (device.isLost) {
-        throw new DOMException("Device has been lost.", "OperationError");
-      }
-      const scope = ArrayPrototypePop(device.errorScopeStack);
-      if (!scope) {
+      const scopeId = ArrayPrototypePop(device.errorScopeIds);
+      if (scopeId === undefined) {
         throw new DOMException(
           "There are no error scopes on the error scope stack.",
           "OperationError",
         );
       }
-      const operations = PromiseAll(scope.operations);
-      return PromisePrototypeThen(
-        operations,
-        () => PromiseResolve(null),
-        (err) => PromiseResolve(err),
-      );
+      // The scope is off the stack for new operations, but still captures
+      // the errors of the operations issued within it.
+      await device.settleErrorScope(scopeId);
+      assertDevice(this, { prefix, context: "this" });
+      const { err } = core.opSync("op_webgpu_device_pop_error_scope", {
+        deviceRid: device.rid,
+        scopeId,
+      });
+      return err ? createGPUError(err) : null;
     }
 
     [SymbolFor("Deno.privateCustomInspect")](inspect) {
@@ -1421,6 +1471,7 @@
     }
   }
   GPUObjectBaseMixin("GPUDevice", GPUDevice);
+  defineEventHandler(GPUDevice.prototype, "uncapturederror");
 
   /**
    * @param {string | null} label
@@ -2343,8 +2394,19 @@
       webidl.illegalConstructor();
     }
 
+    /**
+     * @returns {Promise<GPUCompilationInfo>}
+     */
     compilationInfo() {
-      throw new Error("Not yet implemented");
+      webidl.assertBranded(this, GPUShaderModule);
+      const prefix = "Failed to execute 'compilationInfo' on 'GPUShaderModule'";
+      assertDevice(this, { prefix, context: "this" });
+      const shaderModuleRid = assertResource(this, { prefix, context: "this" });
+      const { messages } = core.opSync(
+        "op_webgpu_shader_module_compilation_info",
+        { shaderModuleRid },
+      );
+      return PromiseResolve(createGPUCompilationInfo(messages));
     }
 
     [SymbolFor("Deno.privateCustomInspect")](inspect) {
@@ -2357,6 +2419,151 @@
   }
   GPUObjectBaseMixin("GPUShaderModule", GPUShaderModule);
 
+  const _type = Symbol("[[type]]");
+  const _lineNum = Symbol("[[lineNum]]");
+  const _linePos = Symbol("[[linePos]]");
+  const _offset = Symbol("[[offset]]");
+  const _length = Symbol("[[length]]");
+  const _messages = Symbol("[[messages]]");
+
+  /**
+   * @param {string} message
+   * @param {GPUCompilationMessageType} type
+   * @param {number} lineNum
+   * @param {number} linePos
+   * @param {number} offset
+   * @param {number} length
+   * @returns {GPUCompilationMessage}
+   */
+  function createGPUCompilationMessage(
+    message,
+    type,
+    lineNum,
+    linePos,
+    offset,
+    length,
+  ) {
+    /** @type {GPUCompilationMessage} */
+    const compilationMessage = webidl.createBranded(GPUCompilationMessage);
+    compilationMessage[_message] = message;
+    compilationMessage[_type] = type;
+    compilationMessage[_lineNum] = lineNum;
+    compilationMessage[_linePos] = linePos;
+    compilationMessage[_offset] = offset;
+    compilationMessage[_length] = length;
+    return compilationMessage;
+  }
+
+  class GPUCompilationMessage {
+    /** @type {string} */
+    [_message];
+    /** @type {GPUCompilationMessageType} */
+    [_type];
+    /** @type {number} */
+    [_lineNum];
+    /** @type {number} */
+    [_linePos];
+    /** @type {number} */
+    [_offset];
+    /** @type {number} */
+    [_length];
+
+    constructor() {
+      webidl.illegalConstructor();
+    }
+
+    /** @returns {string} */
+    get message() {
+      webidl.assertBranded(this, GPUCompilationMessage);
+      return this[_message];
+    }
+    /** @returns {GPUCompilationMessageType} */
+    get type() {
+      webidl.assertBranded(this, GPUCompilationMessage);
+      return this[_type];
+    }
+    /** @returns {number} */
+    get lineNum() {
+      webidl.assertBranded(this, GPUCompilationMessage);
+      return this[_lineNum];
+    }
+    /** @returns {number} */
+    get linePos() {
+      webidl.assertBranded(this, GPUCompilationMessage);
+      return this[_linePos];
+    }
+    /** @returns {number} */
+    get offset() {
+      webidl.assertBranded(this, GPUCompilationMessage);
+      return this[_offset];
+    }
+    /** @returns {number} */
+    get length() {
+      webidl.assertBranded(this, GPUCompilationMessage);
+      return this[_length];
+    }
+
+    [SymbolFor("Deno.privateCustomInspect")](inspect) {
+      return `${this.constructor.name} ${
+        inspect({
+          message: this.message,
+          type: this.type,
+          lineNum: this.lineNum,
+          linePos: this.linePos,
+          offset: this.offset,
+          length: this.length,
+        })
+      }`;
+    }
+  }
+
+  /**
+   * @param {{ message: string, type: GPUCompilationMessageType, lineNum: number, linePos: number, offset: number, length: number }[]} messages
+   * @returns {GPUCompilationInfo}
+   */
+  function createGPUCompilationInfo(messages) {
+    /** @type {GPUCompilationInfo} */
+    const compilationInfo = webidl.createBranded(GPUCompilationInfo);
+    compilationInfo[_messages] = ObjectFreeze(
+      ArrayPrototypeMap(
+        messages,
+        ({ message, type, lineNum, linePos, offset, length }) =>
+          createGPUCompilationMessage(
+            message,
+            type,
+            lineNum,
+            linePos,
+            offset,
+            length,
+          ),
+      ),
+    );
+    return compilationInfo;
+  }
+
+  class GPUCompilationInfo {
+    /** @type {GPUCompilationMessage[]} */
+    [_messages];
+
+    constructor() {
+      webidl.illegalConstructor();
+    }
+
+    /** @returns {GPUCompilationMessage[]} */
+    get messages() {
+      webidl.assertBranded(this, GPUCompilationInfo);
+      return this[_messages];
+    }
+
+    [SymbolFor("Deno.privateCustomInspect")](inspect) {
+      return `${this.constructor.name} ${
+        inspect({
+          messages: this.messages,
+        })
+      }`;
+    }
+  }
+
   class GPUShaderStage {
     constructor() {
       webidl.illegalConstructor();
@@ -2725,6 +2932,19 @@
         },
       );
 
+      let occlusionQuerySet;
+      if (descriptor.occlusionQuerySet) {
+        occlusionQuerySet = assertResource(descriptor.occlusionQuerySet, {
+          prefix,
+          context: "occlusion query set",
+        });
+        assertDeviceMatch(device, descriptor.occlusionQuerySet, {
+          prefix,
+          resourceContext: "occlusion query set",
+          selfContext: "this",
+        });
+      }
+
       const { rid } = core.opSync(
         "op_webgpu_command_encoder_begin_render_pass",
         {
@@ -2732,6 +2952,7 @@
           ...descriptor,
           colorAttachments,
           depthStencilAttachment,
+          occlusionQuerySet,
         },
       );
 
@@ -3476,12 +3697,49 @@
       });
     }
 
-    beginOcclusionQuery(_queryIndex) {
-      throw new Error("Not yet implemented");
+    /**
+     * @param {number} queryIndex
+     */
+    beginOcclusionQuery(queryIndex) {
+      webidl.assertBranded(this, GPURenderPassEncoder);
+      const prefix =
+        "Failed to execute 'beginOcclusionQuery' on 'GPURenderPassEncoder'";
+      webidl.requiredArguments(arguments.length, 1, { prefix });
+      queryIndex = webidl.converters.GPUSize32(queryIndex, {
+        prefix,
+        context: "Argument 1",
+      });
+      assertDevice(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      assertResource(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      const renderPassRid = assertResource(this, { prefix, context: "this" });
+      core.opSync("op_webgpu_render_pass_begin_occlusion_query", {
+        renderPassRid,
+        queryIndex,
+      });
     }
 
     endOcclusionQuery() {
-      throw new Error("Not yet implemented");
+      webidl.assertBranded(this, GPURenderPassEncoder);
+      const prefix =
+        "Failed to execute 'endOcclusionQuery' on 'GPURenderPassEncoder'";
+      assertDevice(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      assertResource(this[_encoder], {
+        prefix,
+        context: "encoder referenced by this",
+      });
+      const renderPassRid = assertResource(this, { prefix, context: "this" });
+      core.opSync("op_webgpu_render_pass_end_occlusion_query", {
+        renderPassRid,
+      });
     }
 
     /**
@@ -5106,6 +5364,8 @@
     GPUPipelineLayout,
     GPUBindGroup,
     GPUShaderModule,
+    GPUCompilationMessage,
+    GPUCompilationInfo,
     GPUShaderStage,
     GPUComputePipeline,
     GPURenderPipeline,
@@ -5119,5 +5379,7 @@
     GPUQuerySet,
     GPUOutOfMemoryError,
     GPUValidationError,
+    GPUUncapturedErrorEvent,
+    GPUDeviceLostInfo,
   };
 })(this);
@@ -25,6 +25,8 @@
     GPUPipelineLayout,
     GPUBindGroup,
     GPUShaderModule,
+    GPUCompilationMessage,
+    GPUCompilationInfo,
     GPUShaderStage,
     GPUComputePipeline,
     GPURenderPipeline,
@@ -38,6 +40,7 @@
     GPUQuerySet,
     GPUOutOfMemoryError,
     GPUValidationError,
+    GPUUncapturedErrorEvent,
   } = window.__bootstrap.webgpu;
   const { SymbolIterator, TypeError } = window.__bootstrap.primordials;
 
@@ -133,6 +136,8 @@
       "texture-adapter-specific-format-features",
       "shader-float64",
       "vertex-attribute-64bit",
+      "texture-format-16bit-norm",
+      "texture-format-stencil8",
     ],
   );
 
@@ -350,6 +355,13 @@
       "bc7-rgba-unorm-srgb",
       "depth24unorm-stencil8",
       "depth32float-stencil8",
+      // extended from spec
+      "r16unorm",
+      "r16snorm",
+      "rg16unorm",
+      "rg16snorm",
+      "rgba16unorm",
+      "rgba16snorm",
     ],
   );
 
@@ -868,27 +880,27 @@
       dictMembersGPUShaderModuleDescriptor,
     );
 
-  // // ENUM: GPUCompilationMessageType
-  // webidl.converters["GPUCompilationMessageType"] = webidl.createEnumConverter(
-  //   "GPUCompilationMessageType",
-  //   [
-  //     "error",
-  //     "warning",
-  //     "info",
-  //   ],
-  // );
+  // ENUM: GPUCompilationMessageType
+  webidl.converters["GPUCompilationMessageType"] = webidl.createEnumConverter(
+    "GPUCompilationMessageType",
+    [
+      "error",
+      "warning",
+      "info",
+    ],
+  );
 
-  // // INTERFACE: GPUCompilationMessage
-  // webidl.converters.GPUCompilationMessage = webidl.createInterfaceConverter(
-  //   "GPUCompilationMessage",
-  //   GPUCompilationMessage,
-  // );
+  // INTERFACE: GPUCompilationMessage
+  webidl.converters.GPUCompilationMessage = webidl.createInterfaceConverter(
+    "GPUCompilationMessage",
+    GPUCompilationMessage,
+  );
 
-  // // INTERFACE: GPUCompilationInfo
-  // webidl.converters.GPUCompilationInfo = webidl.createInterfaceConverter(
-  //   "GPUCompilationInfo",
-  //   GPUCompilationInfo,
-  // );
+  // INTERFACE: GPUCompilationInfo
+  webidl.converters.GPUCompilationInfo = webidl.createInterfaceConverter(
+    "GPUCompilationInfo",
+    GPUCompilationInfo,
+  );
 
   // DICTIONARY: GPUPipelineDescriptorBase
   const dictMembersGPUPipelineDescriptorBase = [
@@ -1933,11 +1945,11 @@
   // TYPEDEF: GPUError
   webidl.converters["GPUError"] = webidl.converters.any /** put union here! **/;
 
-  // // INTERFACE: GPUUncapturedErrorEvent
-  // webidl.converters.GPUUncapturedErrorEvent = webidl.createInterfaceConverter(
-  //   "GPUUncapturedErrorEvent",
-  //   GPUUncapturedErrorEvent,
-  // );
+  // INTERFACE: GPUUncapturedErrorEvent
+  webidl.converters.GPUUncapturedErrorEvent = webidl.createInterfaceConverter(
+    "GPUUncapturedErrorEvent",
+    GPUUncapturedErrorEvent,
+  );
 
   // DICTIONARY: GPUUncapturedErrorEventInit
   const dictMembersGPUUncapturedErrorEventInit = [
@@ -89,7 +89,7 @@ pub struct CommandEncoderBeginRenderPassArgs {
     label: Option<String>,
     color_attachments: Vec<GpuRenderPassColorAttachment>,
     depth_stencil_attachment: Option<GpuRenderPassDepthStencilAttachment>,
-    _occlusion_query_set: Option<u32>, // not yet implemented
+    occlusion_query_set: Option<ResourceId>,
 }
 
 pub fn op_webgpu_command_encoder_begin_render_pass(
@@ -180,10 +180,17 @@ pub fn op_webgpu_command_encoder_begin_render_pass(
         });
     }
 
+    let occlusion_query_set = args
+        .occlusion_query_set
+        .map(|rid| state.resource_table.get::<super::WebGpuQuerySet>(rid))
+        .transpose()?
+        .map(|query_set| query_set.0);
+
     let descriptor = wgpu_core::command::RenderPassDescriptor {
         label: args.label.map(Cow::from),
         color_attachments: Cow::from(color_attachments),
         depth_stencil_attachment: depth_stencil_attachment.as_ref(),
+        occlusion_query_set,
     };
 
     let render_pass = wgpu_core::command::RenderPass::new(command_encoder_resource.0, &descriptor);
@@ -1,7 +1,10 @@
 // Copyright 2018-2021 the Deno authors. All rights reserved. MIT license.
 use deno_core::error::AnyError;
 use deno_core::ResourceId;
+use serde::Deserialize;
 use serde::Serialize;
+use std::cell::Cell;
+use std::cell::RefCell;
 use std::convert::From;
 use std::fmt;
 use wgpu_core::binding_model::CreateBindGroupError;
@@ -64,7 +67,7 @@ impl WebGpuResult {
     }
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, Deserialize)]
 #[serde(tag = "type", content = "value")]
 #[serde(rename_all = "kebab-case")]
 pub enum WebGpuError {
@@ -73,6 +76,90 @@ pub enum WebGpuError {
     Validation(String),
 }
 
+#[derive(Deserialize, Clone, Copy, PartialEq)]
+#[serde(rename_all = "kebab-case")]
+pub enum GpuErrorFilter {
+    OutOfMemory,
+    Validation,
+}
+
+struct ErrorScope {
+    id: u32,
+    filter: GpuErrorFilter,
+    error: Option<WebGpuError>,
+}
+
+/// Error scope stack of a device.
+///
+/// Scopes are identified by the id returned when they are pushed, so that errors
+/// of asynchronous operations reach the scopes that were on the stack when the
+/// operation was issued.
+#[derive(Default)]
+pub(crate) struct ErrorScopes {
+    scopes: RefCell<Vec<ErrorScope>>,
+    next_id: Cell<u32>,
+    lost: Cell<bool>,
+}
+
+impl ErrorScopes {
+    /// Pushes a scope, returning its id.
+    pub(crate) fn push(&self, filter: GpuErrorFilter) -> u32 {
+        let id = self.next_id.get();
+        self.next_id.set(id.wrapping_add(1));
+        self.scopes.borrow_mut().push(ErrorScope {
+            id,
+            filter,
+            error: None,
+        });
+        id
+    }
+
+    /// Removes the scope with the given id, returning the error it captured.
+    pub(crate) fn pop(&self, id: u32) -> Result<Option<WebGpuError>, DomExceptionOperationError> {
+        if self.lost.get() {
+            return Err(DomExceptionOperationError::new("Device has been lost."));
+        }
+        let mut scopes = self.scopes.borrow_mut();
+        match scopes.iter().position(|scope| scope.id == id) {
+            Some(index) => Ok(scopes.remove(index).error),
+            None => Err(DomExceptionOperationError::new(
+                "There are no error scopes on the error scope stack.",
+            )),
+        }
+    }
+
+    /// Passes an error to the innermost scope with a matching filter among `scope_ids`,
+    /// the ids of the scopes on the stack when the operation was issued.
+    /// Scopes only keep the first error they capture.
+    ///
+    /// Returns the error if no scope captured it, in which case it has to be
+    /// dispatched as an uncaptured error. Device loss is never captured nor dispatched.
+    pub(crate) fn handle(&self, err: WebGpuError, scope_ids: &[u32]) -> Option<WebGpuError> {
+        let filter = match err {
+            WebGpuError::Lost => {
+                self.lost.set(true);
+                return None;
+            }
+            WebGpuError::OutOfMemory => GpuErrorFilter::OutOfMemory,
+            WebGpuError::Validation(_) => GpuErrorFilter::Validation,
+        };
+        let mut scopes = self.scopes.borrow_mut();
+        match scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.filter == filter && scope_ids.contains(&scope.id))
+        {
+            Some(scope) => {
+                if scope.error.is_none() {
+                    scope.error = Some(err);
+                }
+                None
+            }
+            None => Some(err),
+        }
+    }
+}
+
 impl From<CreateBufferError> for WebGpuError {
     fn from(err: CreateBufferError) -> Self {
         match err {
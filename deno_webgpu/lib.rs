@@ -20,6 +20,9 @@ pub use wgpu_types;
 use wgpu_types::PowerPreference;
 
 use error::DomExceptionOperationError;
+use error::ErrorScopes;
+use error::GpuErrorFilter;
+use error::WebGpuError;
 use error::WebGpuResult;
 
 #[macro_use]
@@ -35,6 +38,8 @@ mod macros {
         wgpu_types::Backend::Dx12 => $global.$method::<wgpu_core::api::Dx12>( $($param),* ),
         #[cfg(all(unix, not(target_os = "macos")))]
         wgpu_types::Backend::Gl => $global.$method::<wgpu_core::api::Gles>( $($param),+ ),
+        #[cfg(feature = "cpu")]
+        wgpu_types::Backend::Cpu => $global.$method::<wgpu_core::api::Cpu>( $($param),* ),
         other => panic!("Unexpected backend {:?}", other),
       }
     };
@@ -91,7 +96,7 @@ impl Resource for WebGpuAdapter {
     }
 }
 
-struct WebGpuDevice(wgpu_core::id::DeviceId);
+struct WebGpuDevice(wgpu_core::id::DeviceId, ErrorScopes);
 impl Resource for WebGpuDevice {
     fn name(&self) -> Cow<str> {
         "webGPUDevice".into()
@@ -130,6 +135,9 @@ fn deserialize_features(features: &wgpu_types::Features) -> Vec<&'static str> {
     if features.contains(wgpu_types::Features::DEPTH_CLAMPING) {
         return_features.push("depth-clamping");
     }
+    if features.contains(wgpu_types::Features::DEPTH32FLOAT_STENCIL8) {
+        return_features.push("depth32float-stencil8");
+    }
     if features.contains(wgpu_types::Features::PIPELINE_STATISTICS_QUERY) {
         return_features.push("pipeline-statistics-query");
     }
@@ -208,6 +216,12 @@ fn deserialize_features(features: &wgpu_types::Features) -> Vec<&'static str> {
     if features.contains(wgpu_types::Features::SHADER_PRIMITIVE_INDEX) {
         return_features.push("shader-primitive-index");
     }
+    if features.contains(wgpu_types::Features::TEXTURE_FORMAT_16BIT_NORM) {
+        return_features.push("texture-format-16bit-norm");
+    }
+    if features.contains(wgpu_types::Features::TEXTURE_FORMAT_STENCIL8) {
+        return_features.push("texture-format-stencil8");
+    }
 
     return_features
 }
@@ -216,6 +230,8 @@ fn deserialize_features(features: &wgpu_types::Features) -> Vec<&'static str> {
 #[serde(rename_all = "camelCase")]
 pub struct RequestAdapterArgs {
     power_preference: Option<wgpu_types::PowerPreference>,
+    #[serde(default)]
+    force_fallback_adapter: bool,
 }
 
 #[derive(Serialize)]
@@ -232,7 +248,25 @@ pub struct GpuAdapterDevice {
     name: Option<String>,
     limits: wgpu_types::Limits,
     features: Vec<&'static str>,
-    is_software: bool,
+    is_fallback_adapter: bool,
+}
+
+/// Backends adapters are requested from, selected with the `DENO_WEBGPU_BACKEND`
+/// environment variable. The software adapter needs the `cpu` feature.
+fn backends_from_env() -> wgpu_types::Backends {
+    match std::env::var("DENO_WEBGPU_BACKEND")
+        .as_deref()
+        .map(str::to_lowercase)
+        .as_deref()
+    {
+        Ok("vulkan") => wgpu_types::Backends::VULKAN,
+        Ok("dx12") => wgpu_types::Backends::DX12,
+        Ok("dx11") => wgpu_types::Backends::DX11,
+        Ok("metal") => wgpu_types::Backends::METAL,
+        Ok("gl") => wgpu_types::Backends::GL,
+        Ok("cpu") => wgpu_types::Backends::CPU,
+        _ => wgpu_types::Backends::PRIMARY,
+    }
 }
 
 pub async fn op_webgpu_request_adapter(
@@ -248,7 +282,7 @@ pub async fn op_webgpu_request_adapter(
         state.put(wgpu_core::hub::Global::new(
             "webgpu",
             wgpu_core::hub::IdentityManagerFactory,
-            wgpu_types::Backends::PRIMARY,
+            backends_from_env() | wgpu_types::Backends::CPU,
         ));
         state.borrow::<Instance>()
     };
@@ -258,14 +292,16 @@ pub async fn op_webgpu_request_adapter(
             Some(power_preference) => power_preference.into(),
             None => PowerPreference::default(),
         },
-        // TODO(lucacasonato): respect forceFallbackAdapter
         compatible_surface: None, // windowless
     };
+    let backends = if args.force_fallback_adapter {
+        wgpu_types::Backends::CPU
+    } else {
+        backends_from_env()
+    };
     let res = instance.request_adapter(
         &descriptor,
-        wgpu_core::instance::AdapterInputs::Mask(wgpu_types::Backends::PRIMARY, |_| {
-            std::marker::PhantomData
-        }),
+        wgpu_core::instance::AdapterInputs::Mask(backends, |_| std::marker::PhantomData),
     );
 
     let adapter = match res {
@@ -276,7 +312,7 @@ pub async fn op_webgpu_request_adapter(
             })
         }
     };
-    let name = gfx_select!(adapter => instance.adapter_get_info(adapter))?.name;
+    let info = gfx_select!(adapter => instance.adapter_get_info(adapter))?;
     let adapter_features = gfx_select!(adapter => instance.adapter_features(adapter))?;
     let features = deserialize_features(&adapter_features);
     let adapter_limits = gfx_select!(adapter => instance.adapter_limits(adapter))?;
@@ -285,10 +321,10 @@ pub async fn op_webgpu_request_adapter(
 
     Ok(GpuAdapterDeviceOrErr::Features(GpuAdapterDevice {
         rid,
-        name: Some(name),
+        name: Some(info.name),
         features,
         limits: adapter_limits,
-        is_software: false,
+        is_fallback_adapter: info.device_type == wgpu_types::DeviceType::Cpu,
     }))
 }
 
@@ -311,6 +347,9 @@ impl From<GpuRequiredFeatures> for wgpu_types::Features {
         if required_features.0.contains("depth-clamping") {
             features.set(wgpu_types::Features::DEPTH_CLAMPING, true);
         }
+        if required_features.0.contains("depth32float-stencil8") {
+            features.set(wgpu_types::Features::DEPTH32FLOAT_STENCIL8, true);
+        }
         if required_features.0.contains("pipeline-statistics-query") {
             features.set(wgpu_types::Features::PIPELINE_STATISTICS_QUERY, true);
         }
@@ -406,6 +445,12 @@ impl From<GpuRequiredFeatures> for wgpu_types::Features {
         if required_features.0.contains("shader-primitive-index") {
             features.set(wgpu_types::Features::SHADER_PRIMITIVE_INDEX, true);
         }
+        if required_features.0.contains("texture-format-16bit-norm") {
+            features.set(wgpu_types::Features::TEXTURE_FORMAT_16BIT_NORM, true);
+        }
+        if required_features.0.contains("texture-format-stencil8") {
+            features.set(wgpu_types::Features::TEXTURE_FORMAT_STENCIL8, true);
+        }
 
         features
     }
@@ -443,7 +488,9 @@ pub async fn op_webgpu_request_device(
     let features = deserialize_features(&device_features);
     let limits = gfx_select!(device => instance.device_limits(device))?;
 
-    let rid = state.resource_table.add(WebGpuDevice(device));
+    let rid = state
+        .resource_table
+        .add(WebGpuDevice(device, ErrorScopes::default()));
 
     Ok(GpuAdapterDevice {
         rid,
@@ -451,7 +498,7 @@ pub async fn op_webgpu_request_device(
         features,
         limits,
         // TODO(lucacasonato): report correctly from wgpu
-        is_software: false,
+        is_fallback_adapter: false,
     })
 }
 
@@ -532,6 +579,62 @@ pub fn op_webgpu_create_query_set(
   ) => state, WebGpuQuerySet)
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicePushErrorScopeArgs {
+    device_rid: ResourceId,
+    filter: GpuErrorFilter,
+}
+
+/// Pushes an error scope, returning its id.
+pub fn op_webgpu_device_push_error_scope(
+    state: &mut OpState,
+    args: DevicePushErrorScopeArgs,
+    _: (),
+) -> Result<u32, AnyError> {
+    let device_resource = state.resource_table.get::<WebGpuDevice>(args.device_rid)?;
+    Ok(device_resource.1.push(args.filter))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DevicePopErrorScopeArgs {
+    device_rid: ResourceId,
+    scope_id: u32,
+}
+
+pub fn op_webgpu_device_pop_error_scope(
+    state: &mut OpState,
+    args: DevicePopErrorScopeArgs,
+    _: (),
+) -> Result<WebGpuResult, AnyError> {
+    let device_resource = state.resource_table.get::<WebGpuDevice>(args.device_rid)?;
+    let maybe_err = device_resource.1.pop(args.scope_id)?;
+
+    Ok(WebGpuResult::maybe_err(maybe_err))
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DeviceHandleErrorArgs {
+    device_rid: ResourceId,
+    err: WebGpuError,
+    scope_ids: Vec<u32>,
+}
+
+/// Passes the error of an operation to the error scopes of the device
+/// that were on the stack when it was issued, returning it back if it is uncaptured.
+pub fn op_webgpu_device_handle_error(
+    state: &mut OpState,
+    args: DeviceHandleErrorArgs,
+    _: (),
+) -> Result<WebGpuResult, AnyError> {
+    let device_resource = state.resource_table.get::<WebGpuDevice>(args.device_rid)?;
+    let maybe_err = device_resource.1.handle(args.err, &args.scope_ids);
+
+    Ok(WebGpuResult::maybe_err(maybe_err))
+}
+
 fn declare_webgpu_ops() -> Vec<(&'static str, Box<OpFn>)> {
     vec![
         // Request device/adapter
@@ -543,6 +646,19 @@ fn declare_webgpu_ops() -> Vec<(&'static str, Box<OpFn>)> {
             "op_webgpu_request_device",
             op_async(op_webgpu_request_device),
         ),
+        // error scopes
+        (
+            "op_webgpu_device_push_error_scope",
+            op_sync(op_webgpu_device_push_error_scope),
+        ),
+        (
+            "op_webgpu_device_pop_error_scope",
+            op_sync(op_webgpu_device_pop_error_scope),
+        ),
+        (
+            "op_webgpu_device_handle_error",
+            op_sync(op_webgpu_device_handle_error),
+        ),
         // Query Set
         (
             "op_webgpu_create_query_set",
@@ -682,6 +798,14 @@ fn declare_webgpu_ops() -> Vec<(&'static str, Box<OpFn>)> {
             "op_webgpu_render_pass_set_stencil_reference",
             op_sync(render_pass::op_webgpu_render_pass_set_stencil_reference),
         ),
+        (
+            "op_webgpu_render_pass_begin_occlusion_query",
+            op_sync(render_pass::op_webgpu_render_pass_begin_occlusion_query),
+        ),
+        (
+            "op_webgpu_render_pass_end_occlusion_query",
+            op_sync(render_pass::op_webgpu_render_pass_end_occlusion_query),
+        ),
         (
             "op_webgpu_render_pass_begin_pipeline_statistics_query",
             op_sync(render_pass::op_webgpu_render_pass_begin_pipeline_statistics_query),
@@ -846,5 +970,9 @@ fn declare_webgpu_ops() -> Vec<(&'static str, Box<OpFn>)> {
             "op_webgpu_create_shader_module",
             op_sync(shader::op_webgpu_create_shader_module),
         ),
+        (
+            "op_webgpu_shader_module_compilation_info",
+            op_sync(shader::op_webgpu_shader_module_compilation_info),
+        ),
     ]
 }
@@ -81,6 +81,7 @@ pub fn op_webgpu_create_compute_pipeline(
             entry_point: Cow::from(args.compute.entry_point),
             // TODO(lucacasonato): support args.compute.constants
         },
+        cache: None,
     };
     let implicit_pipelines = match args.layout {
         Some(_) => None,
@@ -358,6 +359,7 @@ pub fn op_webgpu_create_render_pipeline(
         depth_stencil: args.depth_stencil.map(TryInto::try_into).transpose()?,
         multisample: args.multisample.into(),
         fragment,
+        cache: None,
     };
 
     let implicit_pipelines = match args.layout {
@@ -130,6 +130,52 @@ pub fn op_webgpu_render_pass_set_stencil_reference(
     Ok(WebGpuResult::empty())
 }
 
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderPassBeginOcclusionQueryArgs {
+    render_pass_rid: ResourceId,
+    query_index: u32,
+}
+
+pub fn op_webgpu_render_pass_begin_occlusion_query(
+    state: &mut OpState,
+    args: RenderPassBeginOcclusionQueryArgs,
+    _: (),
+) -> Result<WebGpuResult, AnyError> {
+    let render_pass_resource = state
+        .resource_table
+        .get::<WebGpuRenderPass>(args.render_pass_rid)?;
+
+    wgpu_core::command::render_ffi::wgpu_render_pass_begin_occlusion_query(
+        &mut render_pass_resource.0.borrow_mut(),
+        args.query_index,
+    );
+
+    Ok(WebGpuResult::empty())
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RenderPassEndOcclusionQueryArgs {
+    render_pass_rid: ResourceId,
+}
+
+pub fn op_webgpu_render_pass_end_occlusion_query(
+    state: &mut OpState,
+    args: RenderPassEndOcclusionQueryArgs,
+    _: (),
+) -> Result<WebGpuResult, AnyError> {
+    let render_pass_resource = state
+        .resource_table
+        .get::<WebGpuRenderPass>(args.render_pass_rid)?;
+
+    wgpu_core::command::render_ffi::wgpu_render_pass_end_occlusion_query(
+        &mut render_pass_resource.0.borrow_mut(),
+    );
+
+    Ok(WebGpuResult::empty())
+}
+
 #[derive(Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct RenderPassBeginPipelineStatisticsQueryArgs {
@@ -4,11 +4,16 @@ use deno_core::error::AnyError;
 use deno_core::ResourceId;
 use deno_core::{OpState, Resource};
 use serde::Deserialize;
+use serde::Serialize;
 use std::borrow::Cow;
+use wgpu_core::pipeline::CreateShaderModuleError;
 
 use super::error::WebGpuResult;
 
-pub(crate) struct WebGpuShaderModule(pub(crate) wgpu_core::id::ShaderModuleId);
+pub(crate) struct WebGpuShaderModule(
+    pub(crate) wgpu_core::id::ShaderModuleId,
+    pub(crate) Vec<GpuCompilationMessage>,
+);
 impl Resource for WebGpuShaderModule {
     fn name(&self) -> Cow<str> {
         "webGPUShaderModule".into()
@@ -41,10 +46,91 @@ pub fn op_webgpu_create_shader_module(
         label: args.label.map(Cow::from),
     };
 
-    gfx_put!(device => instance.device_create_shader_module(
-    device,
-    &descriptor,
-    source,
-    std::marker::PhantomData
-  ) => state, WebGpuShaderModule)
+    let (shader_module, maybe_err) = gfx_select!(device => instance.device_create_shader_module(
+      device,
+      &descriptor,
+      source,
+      std::marker::PhantomData
+    ));
+    let messages = maybe_err
+        .as_ref()
+        .and_then(GpuCompilationMessage::from_error)
+        .into_iter()
+        .collect();
+
+    let rid = state
+        .resource_table
+        .add(WebGpuShaderModule(shader_module, messages));
+
+    Ok(WebGpuResult::rid_err(rid, maybe_err))
+}
+
+#[derive(Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct GpuCompilationMessage {
+    message: String,
+    #[serde(rename = "type")]
+    kind: &'static str,
+    line_num: u64,
+    line_pos: u64,
+    offset: u64,
+    length: u64,
+}
+
+impl GpuCompilationMessage {
+    /// Returns the message of a shader module creation error,
+    /// or `None` if the error is not caused by the shader itself.
+    fn from_error(err: &CreateShaderModuleError) -> Option<Self> {
+        let (message, location) = match *err {
+            CreateShaderModuleError::Parsing(ref err) => {
+                (err.diagnostic.message.clone(), err.location())
+            }
+            CreateShaderModuleError::Validation(ref err) => {
+                (err.diagnostic.message.clone(), err.location())
+            }
+            CreateShaderModuleError::Device(_) => return None,
+            ref err => (err.to_string(), None),
+        };
+        // Messages without a location in the source are reported at line 0, as in the spec.
+        let mut message = Self {
+            message,
+            kind: "error",
+            line_num: 0,
+            line_pos: 0,
+            offset: 0,
+            length: 0,
+        };
+        if let Some(location) = location {
+            message.line_num = location.line_number.into();
+            message.line_pos = location.line_position.into();
+            message.offset = location.offset.into();
+            message.length = location.length.into();
+        }
+        Some(message)
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ShaderModuleCompilationInfoArgs {
+    shader_module_rid: ResourceId,
+}
+
+#[derive(Serialize)]
+pub struct GpuCompilationInfo {
+    messages: Vec<GpuCompilationMessage>,
+}
+
+pub fn op_webgpu_shader_module_compilation_info(
+    state: &mut OpState,
+    args: ShaderModuleCompilationInfoArgs,
+    _: (),
+) -> Result<GpuCompilationInfo, AnyError> {
+    let shader_module_resource = state
+        .resource_table
+        .get::<WebGpuShaderModule>(args.shader_module_rid)?;
+
+    Ok(GpuCompilationInfo {
+        messages: shader_module_resource.1.clone(),
+    })
 }
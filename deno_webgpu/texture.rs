@@ -53,6 +53,7 @@ pub fn op_webgpu_create_texture(
         dimension: args.dimension,
         format: args.format,
         usage: wgpu_types::TextureUsages::from_bits_truncate(args.usage),
+        view_formats: Vec::new(),
     };
 
     gfx_put!(device => instance.device_create_texture(
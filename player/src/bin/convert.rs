@@ -0,0 +1,56 @@
+/*! Converts WebGPU traces between the RON and binary formats.
+!*/
+
+use player::{DataSource as _, TraceReader};
+use wgc::device::trace;
+
+use std::{fs, io, path::Path};
+
+/// Re-embeds a data blob into the output trace, returning its new name.
+fn move_data(input: &mut TraceReader, output: &mut trace::Trace, name: &str) -> io::Result<String> {
+    let kind = Path::new(name)
+        .extension()
+        .and_then(|ext| ext.to_str())
+        .unwrap_or("bin");
+    Ok(output.make_binary(kind, &input.load(name)?))
+}
+
+fn main() {
+    env_logger::init();
+
+    let mut args = std::env::args().skip(1);
+    let (input_path, output_path) = match (args.next(), args.next()) {
+        (Some(input), Some(output)) => (input, output),
+        _ => panic!("Provide the input and output trace paths as the parameters"),
+    };
+    let output_path = Path::new(&output_path);
+
+    let mut input = TraceReader::open(Path::new(&input_path)).unwrap();
+    let format = trace::TraceFormat::from_path(output_path);
+    if format == trace::TraceFormat::Ron {
+        fs::create_dir_all(output_path).unwrap();
+    }
+    let mut output = trace::Trace::with_format(output_path, format).unwrap();
+    log::info!(
+        "Converting {:?} trace '{}' into {:?} at '{:?}'",
+        input.format(),
+        input_path,
+        format,
+        output_path
+    );
+
+    let mut count = 0;
+    while let Some(mut action) = input.next_action().unwrap() {
+        match action {
+            trace::Action::CreateShaderModule { ref mut data, .. }
+            | trace::Action::WriteBuffer { ref mut data, .. }
+            | trace::Action::WriteTexture { ref mut data, .. } => {
+                *data = move_data(&mut input, &mut output, data).unwrap();
+            }
+            _ => {}
+        }
+        output.add(action);
+        count += 1;
+    }
+    println!("Converted {} actions", count);
+}
@@ -0,0 +1,406 @@
+/*! Reduces a WebGPU trace to the actions needed to reproduce a failure.
+ *
+ * The failure is a panic of the replay, which is what `GlobalPlay::process`
+ * does on any error. Candidate traces are replayed on a fresh `Global` each,
+ * and kept if they fail with the same message as the original trace.
+ * The result is written in the format of `player/tests/data`.
+!*/
+
+use player::{GlobalPlay as _, IdentityPassThroughFactory, TraceReader};
+use wgc::{device::trace, gfx_select};
+
+use std::{
+    any::Any,
+    cell::Cell,
+    collections::HashMap,
+    panic::{self, AssertUnwindSafe},
+    path::PathBuf,
+};
+
+thread_local! {
+    /// Set while a candidate trace is replayed, which is expected to panic.
+    static REPLAYING: Cell<bool> = Cell::new(false);
+}
+
+const USAGE: &str = "\
+Usage: minimize [OPTIONS] <trace-dir | trace-file> <output.ron>
+
+Options:
+    --backend <name>     replay on vulkan, metal, dx12, dx11, gl, or cpu
+                         instead of the recorded backend
+    --pattern <text>     accept any failure with a message containing the text,
+                         instead of requiring the original message";
+
+struct Options {
+    trace: PathBuf,
+    output: PathBuf,
+    backend: Option<wgt::Backend>,
+    pattern: Option<String>,
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut paths = Vec::new();
+        let mut backend = None;
+        let mut pattern = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for '{}'", arg))
+            };
+            match arg.as_str() {
+                "--backend" => {
+                    let name = value()?;
+                    backend = Some(
+                        player::parse_backend(&name)
+                            .ok_or_else(|| format!("Unknown backend '{}'", name))?,
+                    );
+                }
+                "--pattern" => pattern = Some(value()?),
+                _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+                _ => paths.push(PathBuf::from(arg)),
+            }
+        }
+
+        match (paths.pop(), paths.pop(), paths.is_empty()) {
+            (Some(output), Some(trace), true) => Ok(Self {
+                trace,
+                output,
+                backend,
+                pattern,
+            }),
+            _ => Err("Provide the trace and the output paths as the parameters".to_string()),
+        }
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "Unknown panic".to_string()
+    }
+}
+
+/// Returns the resources an action refers to.
+fn references(action: &trace::Action) -> Vec<player::ResourceKey> {
+    struct Collector(Vec<player::ResourceKey>);
+    impl player::IdVisitor for Collector {
+        fn visit<I: wgc::id::TypedId + Copy>(&mut self, kind: &'static str, id: &mut I) {
+            let (index, epoch, _) = id.unzip();
+            self.0.push((kind, index, epoch));
+        }
+    }
+
+    let mut collector = Collector(Vec::new());
+    player::visit_ids(&mut action.clone(), &mut collector);
+    collector.0
+}
+
+/// A failure of the replay.
+struct Failure {
+    /// Index of the action that failed.
+    index: usize,
+    message: String,
+}
+
+struct Minimizer<'a> {
+    backend: wgt::Backend,
+    desc: wgc::device::DeviceDescriptor<'a>,
+    blobs: HashMap<String, Vec<u8>>,
+    pattern: Option<String>,
+    message: String,
+    attempts: usize,
+}
+
+impl Minimizer<'_> {
+    /// Replays the actions on a new device, returning the failure, if any.
+    fn run(&mut self, actions: &[trace::Action]) -> Option<Failure> {
+        let backend = self.backend;
+        let global = wgc::hub::Global::new("minimizer", IdentityPassThroughFactory, backend.into());
+        let adapter = global
+            .request_adapter(
+                &wgc::instance::RequestAdapterOptions {
+                    power_preference: wgt::PowerPreference::LowPower,
+                    compatible_surface: None,
+                },
+                wgc::instance::AdapterInputs::IdSet(
+                    &[wgc::id::TypedId::zip(0, 0, backend)],
+                    |id| id.backend(),
+                ),
+            )
+            .expect("Unable to find an adapter for selected backend");
+        let device = wgc::id::TypedId::zip(1, 0, backend);
+        let (_, error) = gfx_select!(adapter => global.adapter_request_device(
+            adapter,
+            &self.desc,
+            None,
+            device
+        ));
+        if let Some(e) = error {
+            panic!("{:?}", e);
+        }
+
+        let mut index = 0;
+        let blobs = &mut self.blobs;
+        REPLAYING.with(|replaying| replaying.set(true));
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
+            for action in actions {
+                gfx_select!(device => global.process(device, action.clone(), blobs, &mut command_buffer_id_manager)).unwrap();
+                index += 1;
+            }
+            gfx_select!(device => global.device_poll(device, true)).unwrap();
+        }));
+        // The state may be inconsistent after a failure, so cleaning it up
+        // is allowed to fail as well.
+        let _ = panic::catch_unwind(AssertUnwindSafe(move || drop(global)));
+        REPLAYING.with(|replaying| replaying.set(false));
+
+        result.err().map(|payload| Failure {
+            index,
+            message: panic_message(&*payload),
+        })
+    }
+
+    fn matches(&self, failure: &Failure) -> bool {
+        match self.pattern {
+            Some(ref pattern) => failure.message.contains(pattern.as_str()),
+            None => failure.message == self.message,
+        }
+    }
+
+    /// Checks if the actions still reproduce the failure.
+    fn fails(&mut self, actions: &[trace::Action]) -> bool {
+        self.attempts += 1;
+        match self.run(actions) {
+            Some(failure) if self.matches(&failure) => true,
+            Some(failure) => {
+                log::info!(
+                    "Action {} fails differently with:\n{}",
+                    failure.index,
+                    failure.message
+                );
+                false
+            }
+            None => false,
+        }
+    }
+
+    /// Removes the items not needed for the failure, following the
+    /// "ddmin" delta debugging algorithm.
+    fn reduce<T: Clone>(
+        &mut self,
+        mut items: Vec<T>,
+        mut fails: impl FnMut(&mut Self, &[T]) -> bool,
+    ) -> Vec<T> {
+        let mut granularity = 2;
+        while !items.is_empty() {
+            let chunk_size = (items.len() + granularity - 1) / granularity;
+            let mut reduced = false;
+            for start in (0..items.len()).step_by(chunk_size) {
+                let end = (start + chunk_size).min(items.len());
+                let candidate = items[..start]
+                    .iter()
+                    .chain(items[end..].iter())
+                    .cloned()
+                    .collect::<Vec<_>>();
+                if fails(self, &candidate) {
+                    items = candidate;
+                    granularity = (granularity - 1).max(2);
+                    reduced = true;
+                    break;
+                }
+            }
+            if !reduced {
+                if granularity >= items.len() {
+                    break;
+                }
+                granularity = (granularity * 2).min(items.len());
+            }
+        }
+        items
+    }
+
+    /// Removes the resources that no other action refers to,
+    /// together with all the actions affecting their lifetime.
+    fn drop_unreferenced(
+        &mut self,
+        mut actions: Vec<trace::Action<'static>>,
+    ) -> Vec<trace::Action<'static>> {
+        let created = actions
+            .iter()
+            .filter_map(|action| match player::lifetime(action) {
+                Some((key, player::Lifetime::Create)) => Some(key),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for key in created {
+            let affects = |action: &trace::Action| match player::lifetime(action) {
+                Some((other, _)) => other == key,
+                None => false,
+            };
+            let is_referenced = actions
+                .iter()
+                .any(|action| !affects(action) && references(action).contains(&key));
+            if is_referenced {
+                continue;
+            }
+            let candidate = actions
+                .iter()
+                .filter(|action| !affects(action))
+                .cloned()
+                .collect::<Vec<_>>();
+            if self.fails(&candidate) {
+                actions = candidate;
+            }
+        }
+        actions
+    }
+
+    fn minimize(
+        &mut self,
+        mut actions: Vec<trace::Action<'static>>,
+    ) -> Vec<trace::Action<'static>> {
+        println!("Reducing {} actions", actions.len());
+        actions = self.reduce(actions, |this, candidate| this.fails(candidate));
+
+        for i in 0..actions.len() {
+            let (index, commands) = match actions[i] {
+                trace::Action::Submit(index, ref commands) if !commands.is_empty() => {
+                    (index, commands.clone())
+                }
+                _ => continue,
+            };
+            println!(
+                "Reducing {} commands of submission {}",
+                commands.len(),
+                index
+            );
+            let commands = self.reduce(commands, |this, candidate| {
+                let mut actions = actions.clone();
+                actions[i] = trace::Action::Submit(index, candidate.to_vec());
+                this.fails(&actions)
+            });
+            actions[i] = trace::Action::Submit(index, commands);
+        }
+
+        println!("Dropping unreferenced resources");
+        self.drop_unreferenced(actions)
+    }
+}
+
+fn main() {
+    env_logger::init();
+
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(1);
+        }
+    };
+
+    log::info!("Loading trace '{:?}'", options.trace);
+    let mut trace = TraceReader::open(&options.trace).unwrap();
+    let (desc, recorded_backend) = match trace.next_action().unwrap() {
+        Some(trace::Action::Init { desc, backend }) => (desc, backend),
+        _ => panic!("Expected Action::Init"),
+    };
+    let backend = options.backend.unwrap_or(recorded_backend);
+
+    let mut actions = Vec::new();
+    let mut blobs = HashMap::new();
+    while let Some(mut action) = trace.next_action().unwrap() {
+        match action {
+            // There is no window to present to, and tests can't have one either.
+            trace::Action::ConfigureSurface(..)
+            | trace::Action::GetSurfaceTexture { .. }
+            | trace::Action::Present(_) => continue,
+            _ => {}
+        }
+        if let Some(name) = player::data_name(&action) {
+            let data = player::DataSource::load(&mut trace, name).unwrap();
+            blobs.insert(name.to_string(), data);
+        }
+        if backend != recorded_backend {
+            action = player::change_backend(action, recorded_backend, backend);
+        }
+        actions.push(action);
+    }
+
+    // Replays are noisy when failing on purpose, but other panics are not.
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        if !REPLAYING.with(|replaying| replaying.get()) {
+            default_hook(info);
+        }
+    }));
+
+    let mut minimizer = Minimizer {
+        backend,
+        desc,
+        blobs,
+        pattern: options.pattern,
+        message: String::new(),
+        attempts: 0,
+    };
+    let failure = match minimizer.run(&actions) {
+        Some(failure) => failure,
+        None => {
+            eprintln!("The trace replays without failures");
+            std::process::exit(1);
+        }
+    };
+    if minimizer.pattern.is_some() && !minimizer.matches(&failure) {
+        eprintln!(
+            "The trace fails with a different message:\n{}",
+            failure.message
+        );
+        std::process::exit(1);
+    }
+    println!("Action {} fails with:\n{}", failure.index, failure.message);
+    minimizer.message = failure.message;
+    // Nothing after the failing action is ever replayed.
+    actions.truncate(failure.index + 1);
+
+    let mut actions = minimizer.minimize(actions);
+    println!(
+        "Reduced to {} actions in {} attempts",
+        actions.len(),
+        minimizer.attempts
+    );
+    // Tests expect the failure on the last action, which the reduction
+    // may have moved to an earlier one.
+    let failure = minimizer
+        .run(&actions)
+        .expect("The reduced trace replays without failures");
+    // A failure while waiting on the device has no action to expect it on.
+    let on_action = failure.index < actions.len();
+    actions.truncate(failure.index + 1);
+
+    player::write_test(
+        &options.output,
+        &minimizer.desc,
+        &failure.message,
+        actions,
+        backend,
+        &minimizer.blobs,
+    )
+    .unwrap();
+    if on_action {
+        println!(
+            "Written '{}', add it to 'player/tests/data/all.ron' to make it a test",
+            options.output.display()
+        );
+    } else {
+        println!(
+            "Written '{}', but the failure happens when polling the device after the last action, \
+            which the tests can't express",
+            options.output.display()
+        );
+    }
+}
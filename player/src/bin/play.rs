@@ -1,33 +1,305 @@
 /*! This is a player for WebGPU traces.
 !*/
 
-use player::{GlobalPlay as _, IdentityPassThroughFactory};
+use player::{DataSource, GlobalPlay as _, IdentityPassThroughFactory, TraceReader};
 use wgc::{device::trace, gfx_select};
 
 use std::{
-    fs,
+    collections::{HashMap, HashSet},
+    ops::RangeInclusive,
     path::{Path, PathBuf},
 };
 
+const USAGE: &str = "\
+Usage: play [OPTIONS] <trace-dir | trace-file>
+
+Options:
+    --backend <name>         replay on vulkan, metal, dx12, dx11, gl, or cpu
+                             instead of the recorded backend
+    --stop-at-submit <N>     pause after the N-th submission
+    --stop-at-present <N>    pause after presenting the N-th frame
+    --step                   start paused and advance frames with the keyboard
+    --loop <first>:<last>    replay the frames from `first` to `last` in a loop
+
+Controls:
+    Space                    pause or resume
+    Right                    advance one frame while paused
+    Escape                   quit
+
+Submissions and frames are counted from 1.";
+
+/// Point of the trace to pause the replay at.
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Stop {
+    Submit(usize),
+    Present(usize),
+}
+
+#[derive(Debug)]
+struct Options {
+    trace: PathBuf,
+    backend: Option<wgt::Backend>,
+    stop: Option<Stop>,
+    step: bool,
+    frames: Option<RangeInclusive<usize>>,
+}
+
+fn parse_count(value: &str) -> Result<usize, String> {
+    match value.parse() {
+        Ok(0) | Err(_) => Err(format!("Invalid count '{}'", value)),
+        Ok(count) => Ok(count),
+    }
+}
+
+impl Options {
+    fn parse(mut args: impl Iterator<Item = String>) -> Result<Self, String> {
+        let mut trace = None;
+        let mut backend = None;
+        let mut stop = None;
+        let mut step = false;
+        let mut frames = None;
+
+        while let Some(arg) = args.next() {
+            let mut value = || {
+                args.next()
+                    .ok_or_else(|| format!("Missing value for '{}'", arg))
+            };
+            match arg.as_str() {
+                "--backend" => {
+                    let name = value()?;
+                    backend = Some(
+                        player::parse_backend(&name)
+                            .ok_or_else(|| format!("Unknown backend '{}'", name))?,
+                    );
+                }
+                "--stop-at-submit" => stop = Some(Stop::Submit(parse_count(&value()?)?)),
+                "--stop-at-present" => stop = Some(Stop::Present(parse_count(&value()?)?)),
+                "--step" => step = true,
+                "--loop" => {
+                    let range = value()?;
+                    let (first, last) = match range.find(':') {
+                        Some(pos) => (parse_count(&range[..pos])?, parse_count(&range[pos + 1..])?),
+                        None => return Err(format!("Invalid frame range '{}'", range)),
+                    };
+                    if first > last {
+                        return Err(format!("Invalid frame range '{}'", range));
+                    }
+                    frames = Some(first..=last);
+                }
+                _ if arg.starts_with("--") => return Err(format!("Unknown option '{}'", arg)),
+                _ if Path::new(&arg).exists() => trace = Some(PathBuf::from(arg)),
+                _ => return Err(format!("Trace '{}' does not exist", arg)),
+            }
+        }
+
+        if cfg!(not(feature = "winit")) && (step || frames.is_some()) {
+            return Err(
+                "Stepping and looping require the window of the 'winit' feature".to_string(),
+            );
+        }
+        if cfg!(not(feature = "winit")) && matches!(stop, Some(Stop::Present(_))) {
+            return Err("Presenting frames requires the window of the 'winit' feature".to_string());
+        }
+        Ok(Self {
+            trace: trace.ok_or("Provide the trace dir or binary trace file as the parameter")?,
+            backend,
+            stop,
+            step,
+            frames,
+        })
+    }
+}
+
+/// Frames replayed over and over.
+struct FrameLoop {
+    frames: RangeInclusive<usize>,
+    actions: Vec<trace::Action<'static>>,
+    blobs: HashMap<String, Vec<u8>>,
+    /// Resources created while recording.
+    created: HashSet<player::ResourceKey>,
+    /// Index of the next action to replay, once the recording is done.
+    cursor: Option<usize>,
+}
+
+impl FrameLoop {
+    fn is_recording(&self, frame_count: usize) -> bool {
+        self.cursor.is_none() && self.frames.contains(&(frame_count + 1))
+    }
+
+    /// Records an action of the looped frames.
+    ///
+    /// Returns `false` if the action destroys or frees a resource created before
+    /// the loop, which the following iterations still use, so it must be skipped.
+    fn record(&mut self, action: &trace::Action<'static>) -> bool {
+        match player::lifetime(action) {
+            Some((key, player::Lifetime::Create)) => {
+                self.created.insert(key);
+            }
+            Some((key, _)) if !self.created.contains(&key) => {
+                log::debug!("Skipping the release of {:?} created before the loop", key);
+                return false;
+            }
+            _ => {}
+        }
+        self.actions.push(action.clone());
+        true
+    }
+
+    fn start(&mut self) {
+        // Only the resources both created and freed within the loop can be
+        // re-created on every iteration. Anything else is created before the loop,
+        // or lives past it, so its lifetime actions are only replayed once.
+        let mut freed = HashSet::new();
+        for action in self.actions.iter() {
+            if let Some((key, player::Lifetime::Free)) = player::lifetime(action) {
+                freed.insert(key);
+            }
+        }
+        let created = &self.created;
+        self.actions
+            .retain(|action| match player::lifetime(action) {
+                Some((key, _)) => created.contains(&key) && freed.contains(&key),
+                None => true,
+            });
+        log::info!(
+            "Looping frames {:?} with {} actions",
+            self.frames,
+            self.actions.len()
+        );
+        self.cursor = Some(0);
+    }
+}
+
+/// Result of advancing the replay by an action.
+#[cfg_attr(not(feature = "winit"), allow(dead_code))]
+enum Progress {
+    Processed,
+    /// The surface needs to be configured by the caller.
+    Configure(wgt::SurfaceConfiguration),
+    Presented,
+    Stopped,
+    Finished,
+}
+
+struct Replay {
+    global: wgc::hub::Global<IdentityPassThroughFactory>,
+    device: wgc::id::DeviceId,
+    trace: TraceReader,
+    /// Backend the trace was recorded with, if it differs from the replay one.
+    recorded_backend: Option<wgt::Backend>,
+    command_buffer_id_manager: wgc::hub::IdentityManager,
+    submit_count: usize,
+    frame_count: usize,
+    stop: Option<Stop>,
+    frame_loop: Option<FrameLoop>,
+}
+
+impl Replay {
+    fn fetch(&mut self) -> Option<trace::Action<'static>> {
+        if let Some(ref mut frame_loop) = self.frame_loop {
+            if let Some(ref mut cursor) = frame_loop.cursor {
+                let action = frame_loop.actions[*cursor].clone();
+                *cursor = (*cursor + 1) % frame_loop.actions.len();
+                return Some(action);
+            }
+        }
+
+        loop {
+            let mut action = self.trace.next_action().unwrap()?;
+            if let Some(backend) = self.recorded_backend {
+                action = player::change_backend(action, backend, self.device.backend());
+            }
+            if let Some(ref mut frame_loop) = self.frame_loop {
+                if frame_loop.is_recording(self.frame_count) {
+                    if !frame_loop.record(&action) {
+                        continue;
+                    }
+                    if let Some(name) = player::data_name(&action) {
+                        let data = self.trace.load(name).unwrap();
+                        frame_loop.blobs.insert(name.to_string(), data);
+                    }
+                }
+            }
+            return Some(action);
+        }
+    }
+
+    fn advance(&mut self) -> Progress {
+        let device = self.device;
+        let action = match self.fetch() {
+            Some(action) => action,
+            None => return Progress::Finished,
+        };
+        match action {
+            #[cfg(feature = "winit")]
+            trace::Action::ConfigureSurface(_device_id, config) => Progress::Configure(config),
+            #[cfg(feature = "winit")]
+            trace::Action::Present(id) => {
+                self.frame_count += 1;
+                log::debug!("Presenting frame {}", self.frame_count);
+                let global = &self.global;
+                gfx_select!(device => global.surface_present(id)).unwrap();
+                let stopped = self.stop == Some(Stop::Present(self.frame_count));
+                if let Some(ref mut frame_loop) = self.frame_loop {
+                    if self.frame_count == *frame_loop.frames.end() {
+                        if frame_loop.cursor.is_none() {
+                            frame_loop.start();
+                        }
+                        self.frame_count = frame_loop.frames.start() - 1;
+                    }
+                }
+                if stopped {
+                    Progress::Stopped
+                } else {
+                    Progress::Presented
+                }
+            }
+            action => {
+                let is_submit = matches!(action, trace::Action::Submit(..));
+                let blobs: &mut dyn DataSource = match self.frame_loop {
+                    Some(ref mut frame_loop)
+                        if frame_loop.cursor.is_some()
+                            || frame_loop.is_recording(self.frame_count) =>
+                    {
+                        &mut frame_loop.blobs
+                    }
+                    _ => &mut self.trace,
+                };
+                let global = &self.global;
+                if let Err(e) = gfx_select!(device => global.process(device, action, blobs, &mut self.command_buffer_id_manager))
+                {
+                    eprintln!("Unable to replay the action: {}", e);
+                    std::process::exit(1);
+                }
+                if is_submit {
+                    self.submit_count += 1;
+                    if self.stop == Some(Stop::Submit(self.submit_count)) {
+                        return Progress::Stopped;
+                    }
+                }
+                Progress::Processed
+            }
+        }
+    }
+}
+
 fn main() {
     #[cfg(feature = "winit")]
     use winit::{event_loop::EventLoop, window::WindowBuilder};
 
     env_logger::init();
 
-    //TODO: setting for the backend bits
-    //TODO: setting for the target frame, or controls
-
-    let dir = match std::env::args().nth(1) {
-        Some(arg) if Path::new(&arg).is_dir() => PathBuf::from(arg),
-        _ => panic!("Provide the dir path as the parameter"),
+    let options = match Options::parse(std::env::args().skip(1)) {
+        Ok(options) => options,
+        Err(message) => {
+            eprintln!("{}\n\n{}", message, USAGE);
+            std::process::exit(1);
+        }
     };
 
-    log::info!("Loading trace '{:?}'", dir);
-    let file = fs::File::open(dir.join(trace::FILE_NAME)).unwrap();
-    let mut actions: Vec<trace::Action> = ron::de::from_reader(file).unwrap();
-    actions.reverse(); // allows us to pop from the top
-    log::info!("Found {} actions", actions.len());
+    log::info!("Loading trace '{:?}'", options.trace);
+    let mut trace = TraceReader::open(&options.trace).unwrap();
+    log::info!("Reading {:?} actions", trace.format());
 
     #[cfg(feature = "winit")]
     let event_loop = {
@@ -41,58 +313,92 @@ fn main() {
         .build(&event_loop)
         .unwrap();
 
-    let global = wgc::hub::Global::new("player", IdentityPassThroughFactory, wgt::Backends::all());
-    let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
+    let (desc, recorded_backend) = match trace.next_action().unwrap() {
+        Some(trace::Action::Init { desc, backend }) => (desc, backend),
+        _ => panic!("Expected Action::Init"),
+    };
+    let backend = options.backend.unwrap_or(recorded_backend);
+
+    let global = wgc::hub::Global::new("player", IdentityPassThroughFactory, backend.into());
 
     #[cfg(feature = "winit")]
     let surface =
         global.instance_create_surface(&window, wgc::id::TypedId::zip(0, 1, wgt::Backend::Empty));
 
-    let device = match actions.pop() {
-        Some(trace::Action::Init { desc, backend }) => {
-            log::info!("Initializing the device for backend: {:?}", backend);
-            let adapter = global
-                .request_adapter(
-                    &wgc::instance::RequestAdapterOptions {
-                        power_preference: wgt::PowerPreference::LowPower,
-                        #[cfg(feature = "winit")]
-                        compatible_surface: Some(surface),
-                        #[cfg(not(feature = "winit"))]
-                        compatible_surface: None,
-                    },
-                    wgc::instance::AdapterInputs::IdSet(
-                        &[wgc::id::TypedId::zip(0, 0, backend)],
-                        |id| id.backend(),
-                    ),
-                )
-                .expect("Unable to find an adapter for selected backend");
-
-            let info = gfx_select!(adapter => global.adapter_get_info(adapter)).unwrap();
-            log::info!("Picked '{}'", info.name);
-            let id = wgc::id::TypedId::zip(1, 0, backend);
-            let (_, error) = gfx_select!(adapter => global.adapter_request_device(
-                adapter,
-                &desc,
-                None,
-                id
-            ));
-            if let Some(e) = error {
-                panic!("{:?}", e);
-            }
-            id
-        }
-        _ => panic!("Expected Action::Init"),
+    log::info!(
+        "Initializing the device for backend: {:?}, recorded with {:?}",
+        backend,
+        recorded_backend
+    );
+    let adapter = global
+        .request_adapter(
+            &wgc::instance::RequestAdapterOptions {
+                power_preference: wgt::PowerPreference::LowPower,
+                #[cfg(feature = "winit")]
+                compatible_surface: Some(surface),
+                #[cfg(not(feature = "winit"))]
+                compatible_surface: None,
+            },
+            wgc::instance::AdapterInputs::IdSet(&[wgc::id::TypedId::zip(0, 0, backend)], |id| {
+                id.backend()
+            }),
+        )
+        .expect("Unable to find an adapter for selected backend");
+
+    let info = gfx_select!(adapter => global.adapter_get_info(adapter)).unwrap();
+    log::info!("Picked '{}'", info.name);
+    let device = wgc::id::TypedId::zip(1, 0, backend);
+    let (_, error) = gfx_select!(adapter => global.adapter_request_device(
+        adapter,
+        &desc,
+        None,
+        device
+    ));
+    if let Some(e) = error {
+        panic!("{:?}", e);
+    }
+
+    let mut replay = Replay {
+        global,
+        device,
+        trace,
+        recorded_backend: if backend != recorded_backend {
+            Some(recorded_backend)
+        } else {
+            None
+        },
+        command_buffer_id_manager: wgc::hub::IdentityManager::default(),
+        submit_count: 0,
+        frame_count: 0,
+        stop: options.stop,
+        frame_loop: options.frames.clone().map(|frames| FrameLoop {
+            frames,
+            actions: Vec::new(),
+            blobs: HashMap::new(),
+            created: HashSet::new(),
+            cursor: None,
+        }),
     };
 
     log::info!("Executing actions");
     #[cfg(not(feature = "winit"))]
     {
+        let global = &replay.global;
         gfx_select!(device => global.device_start_capture(device));
 
-        while let Some(action) = actions.pop() {
-            gfx_select!(device => global.process(device, action, &dir, &mut command_buffer_id_manager));
+        loop {
+            match replay.advance() {
+                Progress::Processed | Progress::Presented => {}
+                Progress::Configure(_) => unreachable!(),
+                Progress::Stopped => {
+                    println!("Stopped at submission {}", replay.submit_count);
+                    break;
+                }
+                Progress::Finished => break,
+            }
         }
 
+        let global = &replay.global;
         gfx_select!(device => global.device_stop_capture(device));
         gfx_select!(device => global.device_poll(device, true)).unwrap();
     }
@@ -104,17 +410,28 @@ fn main() {
         };
 
         let mut resize_config = None;
-        let mut frame_count = 0;
+        let mut running = !options.step;
+        let mut pending_frames = 0;
         let mut done = false;
         event_loop.run(move |event, _, control_flow| {
-            *control_flow = ControlFlow::Poll;
+            let active = !done && (running || pending_frames != 0);
             match event {
                 Event::MainEventsCleared => {
-                    window.request_redraw();
+                    if *control_flow != ControlFlow::Exit {
+                        *control_flow = if active {
+                            ControlFlow::Poll
+                        } else {
+                            ControlFlow::Wait
+                        };
+                    }
+                    if active {
+                        window.request_redraw();
+                    }
                 }
-                Event::RedrawRequested(_) if resize_config.is_none() => loop {
-                    match actions.pop() {
-                        Some(trace::Action::ConfigureSurface(_device_id, config)) => {
+                Event::RedrawRequested(_) if active && resize_config.is_none() => loop {
+                    match replay.advance() {
+                        Progress::Processed => {}
+                        Progress::Configure(config) => {
                             log::info!("Configuring the surface");
                             let current_size: (u32, u32) = window.inner_size().into();
                             let size = (config.width, config.height);
@@ -126,26 +443,32 @@ fn main() {
                                 resize_config = Some(config);
                                 break;
                             } else {
+                                let global = &replay.global;
                                 let error = gfx_select!(device => global.surface_configure(surface, device, &config));
                                 if let Some(e) = error {
                                     panic!("{:?}", e);
                                 }
                             }
                         }
-                        Some(trace::Action::Present(id)) => {
-                            frame_count += 1;
-                            log::debug!("Presenting frame {}", frame_count);
-                            gfx_select!(device => global.surface_present(id)).unwrap();
+                        Progress::Presented => {
+                            if !running {
+                                pending_frames -= 1;
+                            }
                             break;
                         }
-                        Some(action) => {
-                            gfx_select!(device => global.process(device, action, &dir, &mut command_buffer_id_manager));
+                        Progress::Stopped => {
+                            println!(
+                                "Stopped at submission {} of frame {}",
+                                replay.submit_count,
+                                replay.frame_count + 1
+                            );
+                            running = false;
+                            pending_frames = 0;
+                            break;
                         }
-                        None => {
-                            if !done {
-                                println!("Finished the end at frame {}", frame_count);
-                                done = true;
-                            }
+                        Progress::Finished => {
+                            println!("Reached the end of the trace at frame {}", replay.frame_count);
+                            done = true;
                             break;
                         }
                     }
@@ -153,6 +476,7 @@ fn main() {
                 Event::WindowEvent { event, .. } => match event {
                     WindowEvent::Resized(_) => {
                         if let Some(config) = resize_config.take() {
+                            let global = &replay.global;
                             let error = gfx_select!(device => global.surface_configure(surface, device, &config));
                             if let Some(e) = error {
                                 panic!("{:?}", e);
@@ -162,19 +486,33 @@ fn main() {
                     WindowEvent::KeyboardInput {
                         input:
                             KeyboardInput {
-                                virtual_keycode: Some(VirtualKeyCode::Escape),
+                                virtual_keycode: Some(key),
                                 state: ElementState::Pressed,
                                 ..
                             },
                         ..
-                    }
-                    | WindowEvent::CloseRequested => {
+                    } => match key {
+                        VirtualKeyCode::Escape => {
+                            *control_flow = ControlFlow::Exit;
+                        }
+                        VirtualKeyCode::Space => {
+                            running = !running;
+                            pending_frames = 0;
+                            log::info!("Replay {}", if running { "resumed" } else { "paused" });
+                        }
+                        VirtualKeyCode::Right if !running => {
+                            pending_frames += 1;
+                        }
+                        _ => {}
+                    },
+                    WindowEvent::CloseRequested => {
                         *control_flow = ControlFlow::Exit;
                     }
                     _ => {}
                 },
                 Event::LoopDestroyed => {
                     log::info!("Closing");
+                    let global = &replay.global;
                     gfx_select!(device => global.device_poll(device, true)).unwrap();
                 }
                 _ => {}
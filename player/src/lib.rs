@@ -8,7 +8,14 @@
 
 use wgc::device::trace;
 
-use std::{borrow::Cow, fmt::Debug, fs, marker::PhantomData, path::Path};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fmt::Debug,
+    fs, io,
+    marker::PhantomData,
+    path::{Path, PathBuf},
+};
 
 #[derive(Debug)]
 pub struct IdentityPassThrough<I>(PhantomData<I>);
@@ -34,19 +41,582 @@ impl<I: Clone + Debug + wgc::id::TypedId> wgc::hub::IdentityHandlerFactory<I>
 }
 impl wgc::hub::GlobalIdentityHandlerFactory for IdentityPassThroughFactory {}
 
+/// Provides the data blobs that trace actions refer to by name.
+pub trait DataSource {
+    fn load(&mut self, name: &str) -> io::Result<Vec<u8>>;
+}
+
+impl DataSource for &Path {
+    fn load(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        fs::read(self.join(name))
+    }
+}
+
+/// Reads the actions of a trace in either of the formats.
+pub enum TraceReader {
+    Ron {
+        dir: PathBuf,
+        actions: std::vec::IntoIter<trace::Action<'static>>,
+    },
+    Binary {
+        reader: trace::BinaryReader<io::BufReader<fs::File>>,
+        data: HashMap<String, Vec<u8>>,
+    },
+}
+
+impl TraceReader {
+    /// Opens either a RON trace directory or a binary trace file.
+    ///
+    /// RON traces are parsed as a whole, while binary ones are streamed.
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if path.is_dir() {
+            let file = fs::File::open(path.join(trace::FILE_NAME))?;
+            let actions: Vec<trace::Action> = ron::de::from_reader(file)
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+            Ok(Self::Ron {
+                dir: path.to_path_buf(),
+                actions: actions.into_iter(),
+            })
+        } else {
+            let file = io::BufReader::new(fs::File::open(path)?);
+            Ok(Self::Binary {
+                reader: trace::BinaryReader::new(file)?,
+                data: HashMap::new(),
+            })
+        }
+    }
+
+    pub fn format(&self) -> trace::TraceFormat {
+        match *self {
+            Self::Ron { .. } => trace::TraceFormat::Ron,
+            Self::Binary { .. } => trace::TraceFormat::Binary,
+        }
+    }
+
+    /// Returns the next action, or `None` at the end of the trace.
+    pub fn next_action(&mut self) -> io::Result<Option<trace::Action<'static>>> {
+        match *self {
+            Self::Ron {
+                ref mut actions, ..
+            } => Ok(actions.next()),
+            Self::Binary {
+                ref mut reader,
+                ref mut data,
+            } => loop {
+                match reader.next_record()? {
+                    Some(trace::Record::Action(action)) => return Ok(Some(action)),
+                    Some(trace::Record::Data { name, data: blob }) => {
+                        data.insert(name, blob);
+                    }
+                    None => return Ok(None),
+                }
+            },
+        }
+    }
+}
+
+impl DataSource for TraceReader {
+    fn load(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        match *self {
+            Self::Ron { ref dir, .. } => fs::read(dir.join(name)),
+            // Every blob is referenced by a single action, so it can be released.
+            Self::Binary { ref mut data, .. } => {
+                data.remove(name).ok_or_else(|| missing_data(name))
+            }
+        }
+    }
+}
+
+impl DataSource for HashMap<String, Vec<u8>> {
+    fn load(&mut self, name: &str) -> io::Result<Vec<u8>> {
+        self.get(name).cloned().ok_or_else(|| missing_data(name))
+    }
+}
+
+fn missing_data(name: &str) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("missing data {} in the trace", name),
+    )
+}
+
+/// Parses a backend name given to the command line tools.
+pub fn parse_backend(name: &str) -> Option<wgt::Backend> {
+    Some(match name.to_lowercase().as_str() {
+        "vulkan" => wgt::Backend::Vulkan,
+        "metal" => wgt::Backend::Metal,
+        "dx12" => wgt::Backend::Dx12,
+        "dx11" => wgt::Backend::Dx11,
+        "gl" => wgt::Backend::Gl,
+        "cpu" => wgt::Backend::Cpu,
+        _ => return None,
+    })
+}
+
+/// Returns the name of the data blob an action refers to.
+pub fn data_name<'a>(action: &'a trace::Action) -> Option<&'a str> {
+    match *action {
+        trace::Action::CreateShaderModule { ref data, .. }
+        | trace::Action::WriteBuffer { ref data, .. }
+        | trace::Action::WriteTexture { ref data, .. } => Some(data),
+        _ => None,
+    }
+}
+
+/// Identifies a resource by its kind, index, and epoch.
+pub type ResourceKey = (&'static str, u32, u32);
+
+/// Effect of an action on a resource lifetime.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Lifetime {
+    Create,
+    Destroy,
+    Free,
+}
+
+/// Returns the resource which lifetime is affected by an action.
+pub fn lifetime(action: &trace::Action) -> Option<(ResourceKey, Lifetime)> {
+    use trace::Action as A;
+
+    fn key<I: wgc::id::TypedId>(kind: &'static str, id: I) -> ResourceKey {
+        let (index, epoch, _) = id.unzip();
+        (kind, index, epoch)
+    }
+
+    Some(match *action {
+        A::CreateBuffer(id, _) => (key("buffer", id), Lifetime::Create),
+        A::DestroyBuffer(id) => (key("buffer", id), Lifetime::Destroy),
+        A::FreeBuffer(id) => (key("buffer", id), Lifetime::Free),
+        A::CreateTexture(id, _) => (key("texture", id), Lifetime::Create),
+        A::DestroyTexture(id) => (key("texture", id), Lifetime::Destroy),
+        A::FreeTexture(id) => (key("texture", id), Lifetime::Free),
+        A::CreateTextureView { id, .. } => (key("view", id), Lifetime::Create),
+        A::DestroyTextureView(id) => (key("view", id), Lifetime::Free),
+        A::CreateSampler(id, _) => (key("sampler", id), Lifetime::Create),
+        A::DestroySampler(id) => (key("sampler", id), Lifetime::Free),
+        A::CreateBindGroupLayout(id, _) => (key("bind group layout", id), Lifetime::Create),
+        A::DestroyBindGroupLayout(id) => (key("bind group layout", id), Lifetime::Free),
+        A::CreatePipelineLayout(id, _) => (key("pipeline layout", id), Lifetime::Create),
+        A::DestroyPipelineLayout(id) => (key("pipeline layout", id), Lifetime::Free),
+        A::CreateBindGroup(id, _) => (key("bind group", id), Lifetime::Create),
+        A::DestroyBindGroup(id) => (key("bind group", id), Lifetime::Free),
+        A::CreateShaderModule { id, .. } => (key("shader module", id), Lifetime::Create),
+        A::DestroyShaderModule(id) => (key("shader module", id), Lifetime::Free),
+        A::CreateComputePipeline { id, .. } => (key("compute pipeline", id), Lifetime::Create),
+        A::DestroyComputePipeline(id) => (key("compute pipeline", id), Lifetime::Free),
+        A::CreateRenderPipeline { id, .. } => (key("render pipeline", id), Lifetime::Create),
+        A::DestroyRenderPipeline(id) => (key("render pipeline", id), Lifetime::Free),
+        A::CreatePipelineCache { id, .. } => (key("pipeline cache", id), Lifetime::Create),
+        A::DestroyPipelineCache(id) => (key("pipeline cache", id), Lifetime::Free),
+        A::CreateRenderBundle { id, .. } => (key("render bundle", id), Lifetime::Create),
+        A::DestroyRenderBundle(id) => (key("render bundle", id), Lifetime::Free),
+        A::CreateQuerySet { id, .. } => (key("query set", id), Lifetime::Create),
+        A::DestroyQuerySet(id) => (key("query set", id), Lifetime::Free),
+        _ => return None,
+    })
+}
+
+/// Visits the IDs of resources, together with the kind of resource they name.
+pub trait IdVisitor {
+    fn visit<I: wgc::id::TypedId + Copy>(&mut self, kind: &'static str, id: &mut I);
+}
+
+/// Visits every ID an action refers to, including the ones it creates or frees.
+pub fn visit_ids(action: &mut trace::Action, visitor: &mut impl IdVisitor) {
+    use trace::Action as A;
+
+    fn stage(stage: &mut wgc::pipeline::ProgrammableStageDescriptor, v: &mut impl IdVisitor) {
+        v.visit("shader module", &mut stage.module);
+    }
+
+    fn implicit_ids(
+        context: &mut Option<wgc::device::ImplicitPipelineContext>,
+        v: &mut impl IdVisitor,
+    ) {
+        if let Some(ref mut context) = *context {
+            v.visit("pipeline layout", &mut context.root_id);
+            for id in context.group_ids.iter_mut() {
+                v.visit("bind group layout", id);
+            }
+        }
+    }
+
+    fn copy_buffer(copy: &mut wgc::command::ImageCopyBuffer, v: &mut impl IdVisitor) {
+        v.visit("buffer", &mut copy.buffer);
+    }
+
+    fn copy_texture(copy: &mut wgc::command::ImageCopyTexture, v: &mut impl IdVisitor) {
+        v.visit("texture", &mut copy.texture);
+    }
+
+    fn render_commands(commands: &mut [wgc::command::RenderCommand], v: &mut impl IdVisitor) {
+        use wgc::command::RenderCommand as C;
+        for command in commands {
+            match *command {
+                C::SetBindGroup {
+                    ref mut bind_group_id,
+                    ..
+                } => v.visit("bind group", bind_group_id),
+                C::SetPipeline(ref mut id) => v.visit("render pipeline", id),
+                C::SetIndexBuffer {
+                    ref mut buffer_id, ..
+                }
+                | C::SetVertexBuffer {
+                    ref mut buffer_id, ..
+                }
+                | C::MultiDrawIndirect {
+                    ref mut buffer_id, ..
+                } => v.visit("buffer", buffer_id),
+                C::MultiDrawIndirectCount {
+                    ref mut buffer_id,
+                    ref mut count_buffer_id,
+                    ..
+                } => {
+                    v.visit("buffer", buffer_id);
+                    v.visit("buffer", count_buffer_id);
+                }
+                C::WriteTimestamp {
+                    ref mut query_set_id,
+                    ..
+                }
+                | C::BeginPipelineStatisticsQuery {
+                    ref mut query_set_id,
+                    ..
+                } => v.visit("query set", query_set_id),
+                C::ExecuteBundle(ref mut id) => v.visit("render bundle", id),
+                C::SetBlendConstant(_)
+                | C::SetStencilReference(_)
+                | C::SetViewport { .. }
+                | C::SetScissor(_)
+                | C::SetPushConstant { .. }
+                | C::Draw { .. }
+                | C::DrawIndexed { .. }
+                | C::PushDebugGroup { .. }
+                | C::PopDebugGroup
+                | C::InsertDebugMarker { .. }
+                | C::EndPipelineStatisticsQuery
+                | C::BeginOcclusionQuery { .. }
+                | C::EndOcclusionQuery => {}
+            }
+        }
+    }
+
+    fn compute_commands(commands: &mut [wgc::command::ComputeCommand], v: &mut impl IdVisitor) {
+        use wgc::command::ComputeCommand as C;
+        for command in commands {
+            match *command {
+                C::SetBindGroup {
+                    ref mut bind_group_id,
+                    ..
+                } => v.visit("bind group", bind_group_id),
+                C::SetPipeline(ref mut id) => v.visit("compute pipeline", id),
+                C::DispatchIndirect {
+                    ref mut buffer_id, ..
+                } => v.visit("buffer", buffer_id),
+                C::WriteTimestamp {
+                    ref mut query_set_id,
+                    ..
+                }
+                | C::BeginPipelineStatisticsQuery {
+                    ref mut query_set_id,
+                    ..
+                } => v.visit("query set", query_set_id),
+                C::SetPushConstant { .. }
+                | C::Dispatch(_)
+                | C::PushDebugGroup { .. }
+                | C::PopDebugGroup
+                | C::InsertDebugMarker { .. }
+                | C::EndPipelineStatisticsQuery => {}
+            }
+        }
+    }
+
+    fn command(command: &mut trace::Command, v: &mut impl IdVisitor) {
+        use trace::Command as C;
+        match *command {
+            C::CopyBufferToBuffer {
+                ref mut src,
+                ref mut dst,
+                ..
+            } => {
+                v.visit("buffer", src);
+                v.visit("buffer", dst);
+            }
+            C::CopyBufferToTexture {
+                ref mut src,
+                ref mut dst,
+                ..
+            } => {
+                copy_buffer(src, v);
+                copy_texture(dst, v);
+            }
+            C::CopyTextureToBuffer {
+                ref mut src,
+                ref mut dst,
+                ..
+            } => {
+                copy_texture(src, v);
+                copy_buffer(dst, v);
+            }
+            C::CopyTextureToTexture {
+                ref mut src,
+                ref mut dst,
+                ..
+            } => {
+                copy_texture(src, v);
+                copy_texture(dst, v);
+            }
+            C::ClearBuffer { ref mut dst, .. } => v.visit("buffer", dst),
+            C::ClearTexture { ref mut dst, .. } => v.visit("texture", dst),
+            C::WriteTimestamp {
+                ref mut query_set_id,
+                ..
+            } => v.visit("query set", query_set_id),
+            C::ResolveQuerySet {
+                ref mut query_set_id,
+                ref mut destination,
+                ..
+            } => {
+                v.visit("query set", query_set_id);
+                v.visit("buffer", destination);
+            }
+            C::RunComputePass { ref mut base } => compute_commands(&mut base.commands, v),
+            C::RunRenderPass {
+                ref mut base,
+                ref mut target_colors,
+                ref mut target_depth_stencil,
+                ref mut occlusion_query_set,
+            } => {
+                render_commands(&mut base.commands, v);
+                for at in target_colors.iter_mut() {
+                    v.visit("view", &mut at.view);
+                    if let Some(ref mut id) = at.resolve_target {
+                        v.visit("view", id);
+                    }
+                }
+                if let Some(ref mut at) = *target_depth_stencil {
+                    v.visit("view", &mut at.view);
+                }
+                if let Some(ref mut id) = *occlusion_query_set {
+                    v.visit("query set", id);
+                }
+            }
+        }
+    }
+
+    match *action {
+        A::Init { .. } => {}
+        A::ConfigureSurface(ref mut id, _) | A::Present(ref mut id) => visitor.visit("surface", id),
+        A::CreateBuffer(ref mut id, _)
+        | A::FreeBuffer(ref mut id)
+        | A::DestroyBuffer(ref mut id)
+        | A::WriteBuffer { ref mut id, .. } => visitor.visit("buffer", id),
+        A::CreateTexture(ref mut id, _)
+        | A::FreeTexture(ref mut id)
+        | A::DestroyTexture(ref mut id) => visitor.visit("texture", id),
+        A::CreateTextureView {
+            ref mut id,
+            ref mut parent_id,
+            ..
+        } => {
+            visitor.visit("view", id);
+            visitor.visit("texture", parent_id);
+        }
+        A::DestroyTextureView(ref mut id) => visitor.visit("view", id),
+        A::CreateSampler(ref mut id, _) | A::DestroySampler(ref mut id) => {
+            visitor.visit("sampler", id)
+        }
+        A::GetSurfaceTexture {
+            ref mut id,
+            ref mut parent_id,
+        } => {
+            visitor.visit("texture", id);
+            visitor.visit("surface", parent_id);
+        }
+        A::CreateBindGroupLayout(ref mut id, _) | A::DestroyBindGroupLayout(ref mut id) => {
+            visitor.visit("bind group layout", id)
+        }
+        A::CreatePipelineLayout(ref mut id, ref mut desc) => {
+            visitor.visit("pipeline layout", id);
+            for id in desc.bind_group_layouts.to_mut() {
+                visitor.visit("bind group layout", id);
+            }
+        }
+        A::DestroyPipelineLayout(ref mut id) => visitor.visit("pipeline layout", id),
+        A::CreateBindGroup(ref mut id, ref mut desc) => {
+            use wgc::binding_model::BindingResource as Br;
+            visitor.visit("bind group", id);
+            visitor.visit("bind group layout", &mut desc.layout);
+            for entry in desc.entries.to_mut() {
+                match entry.resource {
+                    Br::Buffer(ref mut binding) => visitor.visit("buffer", &mut binding.buffer_id),
+                    Br::BufferArray(ref mut bindings) => {
+                        for binding in bindings.to_mut() {
+                            visitor.visit("buffer", &mut binding.buffer_id);
+                        }
+                    }
+                    Br::Sampler(ref mut id) => visitor.visit("sampler", id),
+                    Br::TextureView(ref mut id) => visitor.visit("view", id),
+                    Br::TextureViewArray(ref mut ids) => {
+                        for id in ids.to_mut() {
+                            visitor.visit("view", id);
+                        }
+                    }
+                }
+            }
+        }
+        A::DestroyBindGroup(ref mut id) => visitor.visit("bind group", id),
+        A::CreateShaderModule { ref mut id, .. } | A::DestroyShaderModule(ref mut id) => {
+            visitor.visit("shader module", id)
+        }
+        A::CreateComputePipeline {
+            ref mut id,
+            ref mut desc,
+            ref mut implicit_context,
+        } => {
+            visitor.visit("compute pipeline", id);
+            if let Some(ref mut id) = desc.layout {
+                visitor.visit("pipeline layout", id);
+            }
+            stage(&mut desc.stage, visitor);
+            if let Some(ref mut id) = desc.cache {
+                visitor.visit("pipeline cache", id);
+            }
+            implicit_ids(implicit_context, visitor);
+        }
+        A::DestroyComputePipeline(ref mut id) => visitor.visit("compute pipeline", id),
+        A::CreateRenderPipeline {
+            ref mut id,
+            ref mut desc,
+            ref mut implicit_context,
+        } => {
+            visitor.visit("render pipeline", id);
+            if let Some(ref mut id) = desc.layout {
+                visitor.visit("pipeline layout", id);
+            }
+            stage(&mut desc.vertex.stage, visitor);
+            if let Some(ref mut fragment) = desc.fragment {
+                stage(&mut fragment.stage, visitor);
+            }
+            if let Some(ref mut id) = desc.cache {
+                visitor.visit("pipeline cache", id);
+            }
+            implicit_ids(implicit_context, visitor);
+        }
+        A::DestroyRenderPipeline(ref mut id) => visitor.visit("render pipeline", id),
+        A::CreatePipelineCache { ref mut id, .. } | A::DestroyPipelineCache(ref mut id) => {
+            visitor.visit("pipeline cache", id)
+        }
+        A::CreateRenderBundle {
+            ref mut id,
+            ref mut base,
+            ..
+        } => {
+            visitor.visit("render bundle", id);
+            render_commands(&mut base.commands, visitor);
+        }
+        A::DestroyRenderBundle(ref mut id) => visitor.visit("render bundle", id),
+        A::CreateQuerySet { ref mut id, .. } | A::DestroyQuerySet(ref mut id) => {
+            visitor.visit("query set", id)
+        }
+        A::WriteTexture { ref mut to, .. } => copy_texture(to, visitor),
+        A::Submit(_, ref mut commands) => {
+            for cmd in commands.iter_mut() {
+                command(cmd, visitor);
+            }
+        }
+    }
+}
+
+/// Moves all the IDs referenced by an action from one backend to another,
+/// so that a trace can be replayed on a backend other than the recorded one.
+pub fn change_backend<'a>(
+    mut action: trace::Action<'a>,
+    from: wgt::Backend,
+    to: wgt::Backend,
+) -> trace::Action<'a> {
+    struct ChangeBackend {
+        from: wgt::Backend,
+        to: wgt::Backend,
+    }
+    impl IdVisitor for ChangeBackend {
+        fn visit<I: wgc::id::TypedId + Copy>(&mut self, _kind: &'static str, id: &mut I) {
+            let (index, epoch, backend) = id.unzip();
+            if backend == self.from {
+                *id = I::zip(index, epoch, self.to);
+            }
+        }
+    }
+
+    visit_ids(&mut action, &mut ChangeBackend { from, to });
+    action
+}
+
+/// Writes the actions as a test of `player/tests/data`,
+/// with the data files next to it.
+///
+/// The last action is expected to fail with the `failure` message.
+pub fn write_test(
+    path: &Path,
+    desc: &wgc::device::DeviceDescriptor,
+    failure: &str,
+    actions: Vec<trace::Action>,
+    backend: wgt::Backend,
+    blobs: &HashMap<String, Vec<u8>>,
+) -> io::Result<()> {
+    let dir = path.parent().unwrap_or_else(|| Path::new("."));
+    let stem = path.file_stem().unwrap().to_string_lossy();
+    let config = ron::ser::PrettyConfig::default();
+
+    let mut string = format!(
+        "(\n    features: {},\n    limits: {},\n    failure: Some({}),\n    expectations: [],\n    actions: [\n",
+        ron::ser::to_string(&desc.features).unwrap(),
+        ron::ser::to_string(&desc.limits).unwrap(),
+        ron::ser::to_string(failure).unwrap(),
+    );
+    for action in actions {
+        // Tests are replayed on all the backends, substituting `Empty`.
+        let mut action = change_backend(action, backend, wgt::Backend::Empty);
+        match action {
+            trace::Action::CreateShaderModule { ref mut data, .. }
+            | trace::Action::WriteBuffer { ref mut data, .. }
+            | trace::Action::WriteTexture { ref mut data, .. } => {
+                let name = format!("{}-{}", stem, data);
+                let blob = blobs.get(data.as_str()).ok_or_else(|| missing_data(data))?;
+                fs::write(dir.join(&name), blob)?;
+                *data = name;
+            }
+            _ => {}
+        }
+        let pretty = ron::ser::to_string_pretty(&action, config.clone()).unwrap();
+        for line in pretty.lines() {
+            string.push_str("        ");
+            string.push_str(line);
+            string.push('\n');
+        }
+        string.truncate(string.len() - 1);
+        string.push_str(",\n");
+    }
+    string.push_str("    ],\n)\n");
+    fs::write(path, string)
+}
+
 pub trait GlobalPlay {
     fn encode_commands<A: wgc::hub::HalApi>(
         &self,
         encoder: wgc::id::CommandEncoderId,
         commands: Vec<trace::Command>,
     ) -> wgc::id::CommandBufferId;
+    /// Replays a single action on the device.
+    ///
+    /// Fails if a data blob the action refers to can't be loaded or decoded.
     fn process<A: wgc::hub::HalApi>(
         &self,
         device: wgc::id::DeviceId,
         action: trace::Action,
-        dir: &Path,
+        blobs: &mut dyn DataSource,
         comb_manager: &mut wgc::hub::IdentityManager,
-    );
+    ) -> io::Result<()>;
 }
 
 impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
@@ -116,12 +686,14 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                     base,
                     target_colors,
                     target_depth_stencil,
+                    occlusion_query_set,
                 } => {
                     self.command_encoder_run_render_pass_impl::<A>(
                         encoder,
                         base.as_ref(),
                         &target_colors,
                         target_depth_stencil.as_ref(),
+                        occlusion_query_set,
                     )
                     .unwrap();
                 }
@@ -139,9 +711,9 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
         &self,
         device: wgc::id::DeviceId,
         action: trace::Action,
-        dir: &Path,
+        blobs: &mut dyn DataSource,
         comb_manager: &mut wgc::hub::IdentityManager,
-    ) {
+    ) -> io::Result<()> {
         use wgc::device::trace::Action;
         log::info!("action {:?}", action);
         //TODO: find a way to force ID perishing without excessive `maintain()` calls.
@@ -240,14 +812,19 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
             }
             Action::CreateShaderModule { id, desc, data } => {
                 log::info!("Creating shader from {}", data);
-                let code = fs::read_to_string(dir.join(&data)).unwrap();
+                let code = String::from_utf8(blobs.load(&data)?)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
                 let source = if data.ends_with(".wgsl") {
                     wgc::pipeline::ShaderModuleSource::Wgsl(Cow::Owned(code))
                 } else if data.ends_with(".ron") {
-                    let module = ron::de::from_str(&code).unwrap();
+                    let module = ron::de::from_str(&code)
+                        .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
                     wgc::pipeline::ShaderModuleSource::Naga(module)
                 } else {
-                    panic!("Unknown shader {}", data);
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidInput,
+                        format!("Unknown shader {}", data),
+                    ));
                 };
                 let (_, error) = self.device_create_shader_module::<A>(device, &desc, source, id);
                 if let Some(e) = error {
@@ -301,6 +878,16 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
             Action::DestroyRenderPipeline(id) => {
                 self.render_pipeline_drop::<A>(id);
             }
+            Action::CreatePipelineCache { id, desc } => {
+                let (_, error) =
+                    unsafe { self.device_create_pipeline_cache::<A>(device, &desc, id) };
+                if let Some(e) = error {
+                    panic!("{:?}", e);
+                }
+            }
+            Action::DestroyPipelineCache(id) => {
+                self.pipeline_cache_drop::<A>(id);
+            }
             Action::CreateRenderBundle { id, desc, base } => {
                 let bundle =
                     wgc::command::RenderBundleEncoder::new(&desc, device, Some(base)).unwrap();
@@ -332,7 +919,7 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 range,
                 queued,
             } => {
-                let bin = std::fs::read(dir.join(data)).unwrap();
+                let bin = blobs.load(&data)?;
                 let size = (range.end - range.start) as usize;
                 if queued {
                     self.queue_write_buffer::<A>(device, id, range.start, &bin)
@@ -349,7 +936,7 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 layout,
                 size,
             } => {
-                let bin = std::fs::read(dir.join(data)).unwrap();
+                let bin = blobs.load(&data)?;
                 self.queue_write_texture::<A>(device, &to, &bin, &layout, &size)
                     .unwrap();
             }
@@ -369,5 +956,6 @@ impl GlobalPlay for wgc::hub::Global<IdentityPassThroughFactory> {
                 self.queue_submit::<A>(device, &[cmdbuf]).unwrap();
             }
         }
+        Ok(())
     }
 }
@@ -7,12 +7,14 @@
  *    - all expected buffers have `MAP_READ` usage
  *    - last action is `Submit`
  *    - no swapchain use
+ *    - a test expecting a failure has it on the last action, and no expectations
 !*/
 
 use player::{GlobalPlay, IdentityPassThroughFactory};
 use std::{
     fs::{read_to_string, File},
     io::{Read, Seek, SeekFrom},
+    panic::{self, AssertUnwindSafe},
     path::{Path, PathBuf},
     ptr, slice,
 };
@@ -27,6 +29,8 @@ struct RawId {
 enum ExpectedData {
     Raw(Vec<u8>),
     U64(Vec<u64>),
+    /// Number of `u64` values that have to be non-zero.
+    NonZeroU64(usize),
     File(String, usize),
 }
 
@@ -35,6 +39,7 @@ impl ExpectedData {
         match self {
             ExpectedData::Raw(vec) => vec.len(),
             ExpectedData::U64(vec) => vec.len() * std::mem::size_of::<u64>(),
+            ExpectedData::NonZeroU64(count) => count * std::mem::size_of::<u64>(),
             ExpectedData::File(_, size) => *size,
         }
     }
@@ -51,6 +56,11 @@ struct Expectation {
 #[derive(serde::Deserialize)]
 struct Test<'a> {
     features: wgt::Features,
+    #[serde(default)]
+    limits: wgt::Limits,
+    /// Part of the error message the last action is expected to fail with.
+    #[serde(default)]
+    failure: Option<String>,
     expectations: Vec<Expectation>,
     actions: Vec<wgc::device::trace::Action<'a>>,
 }
@@ -70,9 +80,13 @@ impl Test<'_> {
             wgt::Backend::Dx12 => "Dx12",
             wgt::Backend::Dx11 => "Dx11",
             wgt::Backend::Gl => "Gl",
+            wgt::Backend::Cpu => "Cpu",
             _ => unreachable!(),
         };
-        let string = read_to_string(path).unwrap().replace("Empty", backend_name);
+        // Only the backends of the IDs, as failure messages may mention "Empty" too.
+        let string = read_to_string(path)
+            .unwrap()
+            .replace("Empty)", &format!("{})", backend_name));
         ron::de::from_str(&string).unwrap()
     }
 
@@ -90,19 +104,53 @@ impl Test<'_> {
             &wgt::DeviceDescriptor {
                 label: None,
                 features: self.features | wgt::Features::MAPPABLE_PRIMARY_BUFFERS,
-                limits: wgt::Limits::default(),
+                limits: self.limits,
             },
             None,
             device
         ));
-        if let Some(e) = error {
-            panic!("{:?}", e);
+        match error {
+            Some(wgc::instance::RequestDeviceError::LimitsExceeded(failed)) => {
+                println!("\t\t\tSkipped: {}", failed);
+                return;
+            }
+            Some(e) => panic!("{:?}", e),
+            None => {}
         }
 
         let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
         println!("\t\t\tRunning...");
-        for action in self.actions {
-            wgc::gfx_select!(device => global.process(device, action, dir, &mut command_buffer_id_manager));
+        let mut blobs = dir;
+        let mut actions = self.actions;
+        let failing_action = match self.failure {
+            Some(_) => actions.pop(),
+            None => None,
+        };
+        for action in actions {
+            wgc::gfx_select!(device => global.process(device, action, &mut blobs, &mut command_buffer_id_manager)).unwrap();
+        }
+        if let (Some(action), Some(failure)) = (failing_action, self.failure) {
+            println!("\t\t\tFailing...");
+            let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                wgc::gfx_select!(device => global.process(device, action, &mut blobs, &mut command_buffer_id_manager)).unwrap()
+            }));
+            let payload = match result {
+                Ok(_) => panic!("Test was expected to fail with {:?}", failure),
+                Err(payload) => payload,
+            };
+            let message = payload
+                .downcast_ref::<String>()
+                .map(String::as_str)
+                .or_else(|| payload.downcast_ref::<&str>().copied())
+                .unwrap_or_default();
+            assert!(
+                message.contains(&failure),
+                "Test failed with {:?} instead of {:?}",
+                message,
+                failure
+            );
+            wgc::gfx_select!(device => global.clear_backend(()));
+            return;
         }
         println!("\t\t\tMapping...");
         for expect in &self.expectations {
@@ -112,8 +160,14 @@ impl Test<'_> {
                 expect.offset .. expect.offset+expect.data.len() as wgt::BufferAddress,
                 wgc::resource::BufferMapOperation {
                     host: wgc::device::HostMap::Read,
-                    callback: map_callback,
-                    user_data: ptr::null_mut(),
+                    callback: unsafe {
+                        wgc::resource::BufferMapCallback::from_c(
+                            wgc::resource::BufferMapCallbackC {
+                                callback: map_callback,
+                                user_data: ptr::null_mut(),
+                            },
+                        )
+                    },
                 }
             ))
             .unwrap();
@@ -129,6 +183,19 @@ impl Test<'_> {
                 wgc::gfx_select!(device => global.buffer_get_mapped_range(buffer, expect.offset, Some(expect.data.len() as wgt::BufferAddress)))
                     .unwrap();
             let contents = unsafe { slice::from_raw_parts(ptr, size as usize) };
+            if let ExpectedData::NonZeroU64(_) = expect.data {
+                let zeros = contents
+                    .chunks(std::mem::size_of::<u64>())
+                    .filter(|chunk| chunk.iter().all(|&byte| byte == 0))
+                    .count();
+                if zeros != 0 {
+                    panic!(
+                        "Test expectation is not met!\nBuffer content was:\n{:?}\nbut expected no zero values",
+                        contents
+                    );
+                }
+                continue;
+            }
             let expected_data = match expect.data {
                 ExpectedData::Raw(vec) => vec,
                 ExpectedData::File(name, size) => {
@@ -143,6 +210,7 @@ impl Test<'_> {
                     .into_iter()
                     .flat_map(|u| u.to_ne_bytes().to_vec())
                     .collect::<Vec<u8>>(),
+                ExpectedData::NonZeroU64(_) => unreachable!(),
             };
 
             if &expected_data[..] != contents {
@@ -169,6 +237,7 @@ const BACKENDS: &[wgt::Backend] = &[
     wgt::Backend::Dx12,
     wgt::Backend::Dx11,
     wgt::Backend::Gl,
+    wgt::Backend::Cpu,
 ];
 
 impl Corpus {
@@ -223,3 +292,262 @@ fn test_api() {
 
     Corpus::run_from(PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data/all.ron"))
 }
+
+#[test]
+fn binary_trace_round_trip() {
+    use player::{DataSource as _, TraceReader};
+    use wgc::device::trace;
+
+    let path = std::env::temp_dir().join(format!("wgpu-trace-{}.bin", std::process::id()));
+    let buffer = wgc::id::TypedId::zip(0, 1, wgt::Backend::Empty);
+    let data = [1u8, 2, 3, 4, 5, 6, 7, 8];
+    let mut actions = vec![
+        trace::Action::CreateBuffer(
+            buffer,
+            wgt::BufferDescriptor {
+                label: Some("buffer".into()),
+                size: data.len() as wgt::BufferAddress,
+                usage: wgt::BufferUsages::COPY_DST | wgt::BufferUsages::MAP_READ,
+                mapped_at_creation: false,
+            },
+        ),
+        trace::Action::WriteBuffer {
+            id: buffer,
+            data: String::new(),
+            range: 0..data.len() as wgt::BufferAddress,
+            queued: true,
+        },
+        trace::Action::FreeBuffer(buffer),
+    ];
+    {
+        let mut trace = trace::Trace::with_format(&path, trace::TraceFormat::Binary).unwrap();
+        for action in actions.iter_mut() {
+            if let trace::Action::WriteBuffer {
+                data: ref mut name, ..
+            } = *action
+            {
+                *name = trace.make_binary("bin", &data);
+            }
+            trace.add(action.clone());
+        }
+    }
+
+    let mut reader = TraceReader::open(&path).unwrap();
+    assert_eq!(reader.format(), trace::TraceFormat::Binary);
+    for expected in actions.iter() {
+        let action = reader.next_action().unwrap().unwrap();
+        assert_eq!(
+            ron::ser::to_string(&action).unwrap(),
+            ron::ser::to_string(expected).unwrap()
+        );
+        if let Some(name) = player::data_name(&action) {
+            assert_eq!(reader.load(name).unwrap(), data);
+            assert!(reader.load(name).is_err());
+        }
+    }
+    assert!(reader.next_action().unwrap().is_none());
+
+    // A corrupt chunk size must not be trusted for allocating.
+    let mut bytes = std::fs::read(&path).unwrap();
+    bytes.push(1);
+    bytes.extend_from_slice(&(u64::MAX / 2).to_le_bytes());
+    bytes.extend_from_slice(b"name");
+    std::fs::write(&path, &bytes).unwrap();
+    let mut reader = TraceReader::open(&path).unwrap();
+    let mut count = 0;
+    while reader.next_action().unwrap().is_some() {
+        count += 1;
+    }
+    assert_eq!(count, actions.len());
+
+    std::fs::remove_file(&path).unwrap();
+}
+
+#[test]
+fn missing_blob_is_an_error() {
+    use wgc::device::trace;
+
+    let global = wgc::hub::Global::new("test", IdentityPassThroughFactory, wgt::Backends::all());
+    let adapter = match global.request_adapter(
+        &wgc::instance::RequestAdapterOptions {
+            power_preference: wgt::PowerPreference::LowPower,
+            compatible_surface: None,
+        },
+        wgc::instance::AdapterInputs::IdSet(
+            &BACKENDS
+                .iter()
+                .map(|&backend| wgc::id::TypedId::zip(0, 0, backend))
+                .collect::<Vec<_>>(),
+            |id| id.backend(),
+        ),
+    ) {
+        Ok(adapter) => adapter,
+        Err(_) => {
+            println!("Skipped due to missing adapter");
+            return;
+        }
+    };
+    let backend = adapter.backend();
+    let device = wgc::id::TypedId::zip(0, 0, backend);
+    let (_, error) = wgc::gfx_select!(adapter => global.adapter_request_device(
+        adapter,
+        &wgt::DeviceDescriptor::default(),
+        None,
+        device
+    ));
+    assert!(error.is_none());
+
+    let buffer = wgc::id::TypedId::zip(0, 1, backend);
+    let actions = vec![
+        trace::Action::CreateBuffer(
+            buffer,
+            wgt::BufferDescriptor {
+                label: None,
+                size: 4,
+                usage: wgt::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        ),
+        trace::Action::WriteBuffer {
+            id: buffer,
+            data: "missing.bin".to_string(),
+            range: 0..4,
+            queued: true,
+        },
+    ];
+    let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/data");
+    let mut blobs = dir.as_path();
+    let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
+    let results = actions
+        .into_iter()
+        .map(|action| {
+            wgc::gfx_select!(device => global.process(device, action, &mut blobs, &mut command_buffer_id_manager))
+        })
+        .collect::<Vec<_>>();
+    assert!(results[0].is_ok());
+    assert_eq!(
+        results[1].as_ref().unwrap_err().kind(),
+        std::io::ErrorKind::NotFound
+    );
+
+    wgc::gfx_select!(device => global.clear_backend(()));
+}
+
+#[test]
+fn change_backend_of_ids_only() {
+    use wgc::{device::trace, id::TypedId};
+
+    let layout: wgc::id::BindGroupLayoutId = TypedId::zip(1, 0, wgt::Backend::Vulkan);
+    let action = trace::Action::CreatePipelineLayout(
+        TypedId::zip(2, 0, wgt::Backend::Vulkan),
+        wgc::binding_model::PipelineLayoutDescriptor {
+            label: Some("Id(1,0,Vulkan)".into()),
+            bind_group_layouts: vec![layout].into(),
+            push_constant_ranges: Default::default(),
+        },
+    );
+    match player::change_backend(action, wgt::Backend::Vulkan, wgt::Backend::Gl) {
+        trace::Action::CreatePipelineLayout(id, desc) => {
+            assert_eq!(id.unzip(), (2, 0, wgt::Backend::Gl));
+            assert_eq!(desc.bind_group_layouts[0].unzip(), (1, 0, wgt::Backend::Gl));
+            assert_eq!(desc.label.as_deref(), Some("Id(1,0,Vulkan)"));
+        }
+        _ => unreachable!(),
+    }
+}
+
+#[test]
+fn minimized_trace_round_trip() {
+    use std::collections::HashMap;
+    use wgc::device::trace;
+
+    let global = wgc::hub::Global::new("test", IdentityPassThroughFactory, wgt::Backends::all());
+    let adapter = match global.request_adapter(
+        &wgc::instance::RequestAdapterOptions {
+            power_preference: wgt::PowerPreference::LowPower,
+            compatible_surface: None,
+        },
+        wgc::instance::AdapterInputs::IdSet(
+            &BACKENDS
+                .iter()
+                .map(|&backend| wgc::id::TypedId::zip(0, 0, backend))
+                .collect::<Vec<_>>(),
+            |id| id.backend(),
+        ),
+    ) {
+        Ok(adapter) => adapter,
+        Err(_) => {
+            println!("Skipped due to missing adapter");
+            return;
+        }
+    };
+    let backend = adapter.backend();
+    let desc = wgt::DeviceDescriptor {
+        label: None,
+        features: wgt::Features::empty(),
+        limits: wgt::Limits::downlevel_defaults(),
+    };
+
+    let buffer = wgc::id::TypedId::zip(0, 1, backend);
+    let actions = vec![
+        trace::Action::CreateBuffer(
+            buffer,
+            wgt::BufferDescriptor {
+                label: None,
+                size: 4,
+                usage: wgt::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            },
+        ),
+        trace::Action::WriteBuffer {
+            id: buffer,
+            data: "data.bin".to_string(),
+            range: 0..4,
+            queued: true,
+        },
+        trace::Action::CreateBuffer(
+            wgc::id::TypedId::zip(1, 1, backend),
+            wgt::BufferDescriptor {
+                label: None,
+                size: 4,
+                usage: wgt::BufferUsages::empty(),
+                mapped_at_creation: false,
+            },
+        ),
+    ];
+    let mut blobs = HashMap::new();
+    blobs.insert("data.bin".to_string(), vec![1u8, 2, 3, 4]);
+
+    // Capture the failure the way the minimizer does.
+    let device = wgc::id::TypedId::zip(0, 0, backend);
+    let (_, error) =
+        wgc::gfx_select!(adapter => global.adapter_request_device(adapter, &desc, None, device));
+    assert!(error.is_none());
+    let mut command_buffer_id_manager = wgc::hub::IdentityManager::default();
+    let replay = actions.clone();
+    let mut replay_blobs = blobs.clone();
+    let payload = panic::catch_unwind(AssertUnwindSafe(|| {
+        for action in replay {
+            wgc::gfx_select!(device => global.process(device, action, &mut replay_blobs, &mut command_buffer_id_manager)).unwrap();
+        }
+    }))
+    .unwrap_err();
+    let failure = payload
+        .downcast_ref::<String>()
+        .cloned()
+        .unwrap_or_default();
+    assert!(failure.contains("EmptyUsage"));
+    wgc::gfx_select!(device => global.clear_backend(()));
+
+    let dir = std::env::temp_dir().join(format!("wgpu-minimized-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("minimized.ron");
+    player::write_test(&path, &desc, &failure, actions, backend, &blobs).unwrap();
+
+    let test = Test::load(path, backend);
+    assert_eq!(test.failure.as_deref(), Some(failure.as_str()));
+    assert_eq!(test.limits, desc.limits);
+    test.run(&dir, &global, adapter, 1);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
@@ -12,5 +12,6 @@ fn main() {
         dx12: { all(not(wasm), windows) },
         dx11: { all(false, not(wasm), windows) },
         gl: { all(not(wasm), unix_wo_apple) },
+        cpu: { feature = "cpu" },
     }
 }
@@ -4,13 +4,18 @@ use std::{num::NonZeroU32, ops::Range};
 use crate::device::trace::Command as TraceCommand;
 use crate::{
     command::CommandBuffer,
+    device::{
+        queue::{align_to, get_lowest_common_denom},
+        ZERO_BUFFER_SIZE,
+    },
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Token},
     id::{BufferId, CommandEncoderId, TextureId},
     init_tracker::MemoryInitKind,
+    resource::TextureClearMode,
     track::TextureSelector,
 };
 
-use hal::CommandEncoder as _;
+use hal::{CommandEncoder as _, Device as _};
 use thiserror::Error;
 use wgt::{
     BufferAddress, BufferSize, BufferUsages, ImageSubresourceRange, TextureAspect, TextureUsages,
@@ -191,7 +196,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         };
 
         // Check if texture is supported for clearing
-        if dst_texture.desc.format.describe().sample_type == wgt::TextureSampleType::Depth {
+        if hal::FormatAspects::from(dst_texture.desc.format)
+            .intersects(hal::FormatAspects::DEPTH | hal::FormatAspects::STENCIL)
+        {
             return Err(ClearError::DepthStencilFormatNotSupported);
         }
         if dst_texture.desc.sample_count > 1 {
@@ -227,6 +234,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             });
         }
 
+        let selector = TextureSelector {
+            levels: subresource_range.base_mip_level..subresource_level_end,
+            layers: subresource_range.base_array_layer..subresource_layer_end,
+        };
+
         // query from tracker with usage (and check usage)
         let (dst_texture, dst_pending) = cmd_buf
             .trackers
@@ -234,10 +246,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .use_replace(
                 &*texture_guard,
                 dst,
-                TextureSelector {
-                    levels: subresource_range.base_mip_level..subresource_level_end,
-                    layers: subresource_range.base_array_layer..subresource_layer_end,
-                },
+                selector.clone(),
                 hal::TextureUses::COPY_DST,
             )
             .map_err(ClearError::InvalidTexture)?;
@@ -249,6 +258,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Err(ClearError::MissingCopyDstUsageFlag(None, Some(dst)));
         }
 
+        // Mark dest as initialized, unless only some of the aspects are cleared.
+        if clear_aspects == hal::FormatAspects::from(dst_texture.desc.format) {
+            cmd_buf.texture_memory_init_actions.extend(
+                dst_texture.initialization_status.create_action(
+                    dst,
+                    selector.into(),
+                    MemoryInitKind::ImplicitlyInitialized,
+                ),
+            );
+        }
+
         // actual hal barrier & operation
         let dst_barrier = dst_pending.map(|pending| pending.into_hal(dst_texture));
         let cmd_buf_raw = cmd_buf.encoder.open();
@@ -259,3 +279,223 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(())
     }
 }
+
+/// Zero-fills the selected subresources of a texture by copying from the device's zero buffer.
+///
+/// The texture has to be in the `COPY_DST` state.
+pub(crate) fn clear_texture_via_buffer_copies<A: hal::Api>(
+    texture_desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
+    buffer_copy_pitch: u32,
+    selector: TextureSelector,
+    encoder: &mut A::CommandEncoder,
+    dst_raw: &A::Texture,
+    zero_buffer: &A::Buffer,
+) {
+    let regions = zero_buffer_copy_regions(
+        texture_desc,
+        buffer_copy_pitch,
+        selector,
+        ZERO_BUFFER_SIZE as u32,
+    );
+    unsafe {
+        encoder.copy_buffer_to_texture(zero_buffer, dst_raw, regions.into_iter());
+    }
+}
+
+/// Splits the selected subresources into copies reading at most `zero_buffer_size` bytes.
+fn zero_buffer_copy_regions(
+    texture_desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
+    buffer_copy_pitch: u32,
+    selector: TextureSelector,
+    zero_buffer_size: u32,
+) -> Vec<hal::BufferTextureCopy> {
+    let format_desc = texture_desc.format.describe();
+    let (block_width, block_height) = (
+        format_desc.block_dimensions.0 as u32,
+        format_desc.block_dimensions.1 as u32,
+    );
+    let block_size = format_desc.block_size as u32;
+    let bytes_per_row_alignment = get_lowest_common_denom(buffer_copy_pitch, block_size);
+    // Rows too long for the zero buffer are split into several copies.
+    let max_blocks_per_row =
+        zero_buffer_size / bytes_per_row_alignment * bytes_per_row_alignment / block_size;
+
+    let mut regions = Vec::new();
+    for mip_level in selector.levels {
+        let mip_size = texture_desc
+            .mip_level_size(mip_level)
+            .unwrap()
+            .physical_size(texture_desc.format);
+        let max_width_per_copy = mip_size.width.min(max_blocks_per_row * block_width);
+        let bytes_per_row = align_to(
+            max_width_per_copy / block_width * block_size,
+            bytes_per_row_alignment,
+        );
+        // Each copy takes as many whole block rows as the zero buffer holds.
+        let max_rows_per_copy = zero_buffer_size / bytes_per_row * block_height;
+        let depth = match texture_desc.dimension {
+            wgt::TextureDimension::D3 => mip_size.depth_or_array_layers,
+            wgt::TextureDimension::D1 | wgt::TextureDimension::D2 => 1,
+        };
+
+        for array_layer in selector.layers.clone() {
+            for z in 0..depth {
+                for y in (0..mip_size.height).step_by(max_rows_per_copy as usize) {
+                    for x in (0..mip_size.width).step_by(max_width_per_copy as usize) {
+                        regions.push(hal::BufferTextureCopy {
+                            buffer_layout: wgt::ImageDataLayout {
+                                offset: 0,
+                                bytes_per_row: NonZeroU32::new(bytes_per_row),
+                                rows_per_image: None,
+                            },
+                            texture_base: hal::TextureCopyBase {
+                                mip_level,
+                                array_layer,
+                                origin: wgt::Origin3d { x, y, z },
+                                aspect: hal::FormatAspects::from(texture_desc.format),
+                            },
+                            size: hal::CopyExtent {
+                                width: max_width_per_copy.min(mip_size.width - x),
+                                height: max_rows_per_copy.min(mip_size.height - y),
+                                depth: 1,
+                            },
+                        });
+                    }
+                }
+            }
+        }
+    }
+    regions
+}
+
+/// Zero-fills the selected subresources of a texture with empty render passes.
+///
+/// The texture has to be in the state of `TextureClearMode::RenderPass { is_color }`.
+/// The temporary views are pushed to `clear_views`, and need to be kept alive
+/// until the commands are done executing.
+pub(crate) fn clear_texture_via_render_passes<A: hal::Api>(
+    texture_desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
+    dst_raw: &A::Texture,
+    selector: TextureSelector,
+    is_color: bool,
+    encoder: &mut A::CommandEncoder,
+    device: &A::Device,
+    clear_views: &mut Vec<A::TextureView>,
+) -> Result<(), hal::DeviceError> {
+    let usage = TextureClearMode::RenderPass { is_color }.hal_usage();
+    for mip_level in selector.levels {
+        let extent = texture_desc.mip_level_size(mip_level).unwrap();
+        for layer in selector.layers.clone() {
+            let view_desc = hal::TextureViewDescriptor {
+                label: Some("_ClearView"),
+                format: texture_desc.format,
+                dimension: wgt::TextureViewDimension::D2,
+                usage,
+                range: ImageSubresourceRange {
+                    aspect: TextureAspect::All,
+                    base_mip_level: mip_level,
+                    mip_level_count: NonZeroU32::new(1),
+                    base_array_layer: layer,
+                    array_layer_count: NonZeroU32::new(1),
+                },
+            };
+            let view = unsafe { device.create_texture_view(dst_raw, &view_desc)? };
+            let target = hal::Attachment { view: &view, usage };
+            let (color_attachments, depth_stencil_attachment) = if is_color {
+                let color = hal::ColorAttachment {
+                    target,
+                    resolve_target: None,
+                    ops: hal::AttachmentOps::STORE,
+                    clear_value: wgt::Color::TRANSPARENT,
+                };
+                (vec![color], None)
+            } else {
+                let depth_stencil = hal::DepthStencilAttachment {
+                    target,
+                    depth_ops: hal::AttachmentOps::STORE,
+                    stencil_ops: hal::AttachmentOps::STORE,
+                    clear_value: (0.0, 0),
+                };
+                (Vec::new(), Some(depth_stencil))
+            };
+            unsafe {
+                encoder.begin_render_pass(&hal::RenderPassDescriptor {
+                    label: Some("_Clear"),
+                    extent: wgt::Extent3d {
+                        depth_or_array_layers: 1,
+                        ..extent
+                    },
+                    sample_count: texture_desc.sample_count,
+                    color_attachments: &color_attachments,
+                    depth_stencil_attachment,
+                    occlusion_query_set: None,
+                });
+                encoder.end_render_pass();
+            }
+            drop(color_attachments);
+            clear_views.push(view);
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn covered_texels(
+        format: wgt::TextureFormat,
+        width: u32,
+        height: u32,
+        zero_buffer_size: u32,
+    ) -> u64 {
+        let desc = wgt::TextureDescriptor {
+            label: (),
+            size: wgt::Extent3d {
+                width,
+                height,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgt::TextureDimension::D2,
+            format,
+            usage: TextureUsages::COPY_DST,
+            view_formats: Vec::new(),
+        };
+        let selector = TextureSelector {
+            levels: 0..1,
+            layers: 0..1,
+        };
+        let block_height = format.describe().block_dimensions.1 as u32;
+        zero_buffer_copy_regions(&desc, 256, selector, zero_buffer_size)
+            .iter()
+            .map(|region| {
+                let bytes_per_row = region.buffer_layout.bytes_per_row.unwrap().get();
+                assert!(region.size.height / block_height * bytes_per_row <= zero_buffer_size);
+                assert!(region.texture_base.origin.x + region.size.width <= width);
+                assert!(region.texture_base.origin.y + region.size.height <= height);
+                region.size.width as u64 * region.size.height as u64
+            })
+            .sum()
+    }
+
+    #[test]
+    fn zero_buffer_copies_cover_the_texture() {
+        // Rows fitting into the zero buffer.
+        assert_eq!(
+            covered_texels(wgt::TextureFormat::Rgba8Unorm, 100, 50, 1 << 12),
+            100 * 50
+        );
+        // Rows longer than the zero buffer.
+        assert_eq!(
+            covered_texels(wgt::TextureFormat::Rgba32Float, 1000, 3, 1 << 12),
+            1000 * 3
+        );
+        // Block rows, with fewer of them fitting than the block height.
+        assert_eq!(
+            covered_texels(wgt::TextureFormat::Bc1RgbaUnorm, 1024, 16, 1 << 10),
+            1024 * 16
+        );
+    }
+}
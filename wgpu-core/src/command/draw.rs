@@ -221,5 +221,10 @@ pub enum RenderCommand {
         query_index: u32,
     },
     EndPipelineStatisticsQuery,
+    /// Queries are taken from the occlusion query set of the pass.
+    BeginOcclusionQuery {
+        query_index: u32,
+    },
+    EndOcclusionQuery,
     ExecuteBundle(id::RenderBundleId),
 }
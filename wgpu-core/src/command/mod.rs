@@ -17,14 +17,17 @@ pub use self::query::*;
 pub use self::render::*;
 pub use self::transfer::*;
 
+pub(crate) use self::clear::{clear_texture_via_buffer_copies, clear_texture_via_render_passes};
+
 use crate::error::{ErrorFormatter, PrettyError};
 use crate::FastHashMap;
 use crate::{
+    device::DeviceError,
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
     id,
-    init_tracker::{BufferInitTrackerAction, MemoryInitKind},
-    resource::{Buffer, Texture},
-    track::{BufferState, ResourceTracker, TextureState, TrackerSet},
+    init_tracker::{BufferInitTrackerAction, MemoryInitKind, TextureInitTrackerAction},
+    resource::{Buffer, Texture, TextureClearMode},
+    track::{BufferState, ResourceTracker, TextureSelector, TextureState, TrackerSet},
     Label, Stored,
 };
 
@@ -72,10 +75,16 @@ pub struct BakedCommands<A: hal::Api> {
     pub(crate) list: Vec<A::CommandBuffer>,
     pub(crate) trackers: TrackerSet,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
+    texture_memory_init_actions: Vec<TextureInitTrackerAction>,
 }
 
 pub(crate) struct DestroyedBufferError(pub id::BufferId);
 
+pub(crate) enum TextureInitError {
+    Destroyed(id::TextureId),
+    Device(DeviceError),
+}
+
 impl<A: hal::Api> BakedCommands<A> {
     pub(crate) fn initialize_buffer_memory(
         &mut self,
@@ -151,6 +160,108 @@ impl<A: hal::Api> BakedCommands<A> {
 
         Ok(())
     }
+
+    /// Zero-fills the texture subresources that are read by the command buffer before being written.
+    ///
+    /// Textures cleared with a render pass need temporary views, which are returned in `clear_views`
+    /// and need to be kept alive until the submission is done.
+    #[allow(clippy::too_many_arguments)]
+    pub(crate) fn initialize_texture_memory(
+        &mut self,
+        device: &A::Device,
+        zero_buffer: &A::Buffer,
+        buffer_copy_pitch: u32,
+        device_tracker: &mut TrackerSet,
+        texture_guard: &mut Storage<Texture<A>, id::TextureId>,
+        clear_views: &mut Vec<A::TextureView>,
+    ) -> Result<(), TextureInitError> {
+        // Gather the (mip level, layers) to initialize, in the order of the texture uses.
+        // As with buffers, this is only possible at submission, since previously executed
+        // command buffers change the initialization status.
+        let mut uninitialized_ranges = Vec::new();
+        for texture_use in self.texture_memory_init_actions.drain(..) {
+            let texture = texture_guard
+                .get_mut(texture_use.id)
+                .map_err(|_| TextureInitError::Destroyed(texture_use.id))?;
+
+            let mip_range = texture_use.range.mip_range.clone();
+            let affected_mip_trackers = texture
+                .initialization_status
+                .mips
+                .iter_mut()
+                .enumerate()
+                .skip(mip_range.start as usize)
+                .take((mip_range.end - mip_range.start) as usize);
+            for (mip_level, mip_tracker) in affected_mip_trackers {
+                let uninitialized_layers = mip_tracker.drain(texture_use.range.layer_range.clone());
+                match texture_use.kind {
+                    MemoryInitKind::ImplicitlyInitialized => {
+                        uninitialized_layers.for_each(drop);
+                    }
+                    MemoryInitKind::NeedsInitializedMemory => {
+                        uninitialized_ranges
+                            .extend(uninitialized_layers.map(|layer_range| {
+                                (texture_use.id, mip_level as u32, layer_range)
+                            }));
+                    }
+                }
+            }
+        }
+
+        for (texture_id, mip_level, layer_range) in uninitialized_ranges {
+            let texture = texture_guard
+                .get(texture_id)
+                .map_err(|_| TextureInitError::Destroyed(texture_id))?;
+            let raw_tex = texture
+                .inner
+                .as_raw()
+                .ok_or(TextureInitError::Destroyed(texture_id))?;
+
+            let usage = texture.clear_mode.hal_usage();
+            if usage.is_empty() {
+                continue;
+            }
+            let selector = TextureSelector {
+                levels: mip_level..mip_level + 1,
+                layers: layer_range,
+            };
+            // Don't do use_replace since the texture may already no longer have a ref_count.
+            // However, we *know* that it is currently in use, so the tracker must already know about it.
+            let transition = device_tracker.textures.change_replace_tracked(
+                id::Valid(texture_id),
+                selector.clone(),
+                usage,
+            );
+            unsafe {
+                self.encoder
+                    .transition_textures(transition.map(|pending| pending.into_hal(texture)));
+            }
+
+            match texture.clear_mode {
+                TextureClearMode::BufferCopy => clear_texture_via_buffer_copies::<A>(
+                    &texture.desc,
+                    buffer_copy_pitch,
+                    selector,
+                    &mut self.encoder,
+                    raw_tex,
+                    zero_buffer,
+                ),
+                TextureClearMode::RenderPass { is_color } => clear_texture_via_render_passes::<A>(
+                    &texture.desc,
+                    raw_tex,
+                    selector,
+                    is_color,
+                    &mut self.encoder,
+                    device,
+                    clear_views,
+                )
+                .map_err(|err| TextureInitError::Device(err.into()))?,
+                TextureClearMode::None => {}
+            }
+        }
+
+        Ok(())
+    }
 }
 
 pub struct CommandBuffer<A: hal::Api> {
@@ -159,6 +270,7 @@ pub struct CommandBuffer<A: hal::Api> {
     pub(crate) device_id: Stored<id::DeviceId>,
     pub(crate) trackers: TrackerSet,
     buffer_memory_init_actions: Vec<BufferInitTrackerAction>,
+    texture_memory_init_actions: Vec<TextureInitTrackerAction>,
     limits: wgt::Limits,
     support_clear_buffer_texture: bool,
     #[cfg(feature = "trace")]
@@ -186,6 +298,7 @@ impl<A: HalApi> CommandBuffer<A> {
             device_id,
             trackers: TrackerSet::new(A::VARIANT),
             buffer_memory_init_actions: Default::default(),
+            texture_memory_init_actions: Default::default(),
             limits,
             support_clear_buffer_texture: features.contains(wgt::Features::CLEAR_COMMANDS),
             #[cfg(feature = "trace")]
@@ -252,6 +365,7 @@ impl<A: hal::Api> CommandBuffer<A> {
             list: self.encoder.list,
             trackers: self.trackers,
             buffer_memory_init_actions: self.buffer_memory_init_actions,
+            texture_memory_init_actions: self.texture_memory_init_actions,
         }
     }
 }
@@ -278,7 +392,7 @@ pub struct BasePassRef<'a, C> {
 }
 
 #[doc(hidden)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(
     any(feature = "serial-pass", feature = "trace"),
     derive(serde::Serialize)
@@ -512,6 +626,10 @@ pub enum PassErrorScope {
     BeginPipelineStatisticsQuery,
     #[error("In a end_pipeline_statistics_query command")]
     EndPipelineStatisticsQuery,
+    #[error("In a begin_occlusion_query command")]
+    BeginOcclusionQuery,
+    #[error("In a end_occlusion_query command")]
+    EndOcclusionQuery,
     #[error("In a execute_bundle command")]
     ExecuteBundle,
     #[error("In a dispatch command, indirect:{indirect}")]
@@ -169,14 +169,6 @@ impl<A: HalApi> QuerySet<A> {
         query_index: u32,
         reset_state: Option<&mut QueryResetMap<A>>,
     ) -> Result<&A::QuerySet, QueryUseError> {
-        // We need to defer our resets because we are in a renderpass, add the usage to the reset map.
-        if let Some(reset) = reset_state {
-            let used = reset.use_query_set(query_set_id, self, query_index);
-            if used {
-                return Err(QueryUseError::UsedTwiceInsideRenderpass { query_index });
-            }
-        }
-
         let simple_set_type = SimplifiedQueryType::from(self.desc.ty);
         if simple_set_type != query_type {
             return Err(QueryUseError::IncompatibleType {
@@ -192,6 +184,14 @@ impl<A: HalApi> QuerySet<A> {
             });
         }
 
+        // We need to defer our resets because we are in a renderpass, add the usage to the reset map.
+        if let Some(reset) = reset_state {
+            let used = reset.use_query_set(query_set_id, self, query_index);
+            if used {
+                return Err(QueryUseError::UsedTwiceInsideRenderpass { query_index });
+            }
+        }
+
         Ok(&self.raw)
     }
 
@@ -229,13 +229,45 @@ impl<A: HalApi> QuerySet<A> {
         reset_state: Option<&mut QueryResetMap<A>>,
         active_query: &mut Option<(id::QuerySetId, u32)>,
     ) -> Result<(), QueryUseError> {
-        let needs_reset = reset_state.is_none();
-        let query_set = self.validate_query(
+        self.validate_and_begin_query(
+            raw_encoder,
             query_set_id,
             SimplifiedQueryType::PipelineStatistics,
             query_index,
             reset_state,
-        )?;
+            active_query,
+        )
+    }
+
+    pub(super) fn validate_and_begin_occlusion_query(
+        &self,
+        raw_encoder: &mut A::CommandEncoder,
+        query_set_id: id::QuerySetId,
+        query_index: u32,
+        reset_state: Option<&mut QueryResetMap<A>>,
+        active_query: &mut Option<(id::QuerySetId, u32)>,
+    ) -> Result<(), QueryUseError> {
+        self.validate_and_begin_query(
+            raw_encoder,
+            query_set_id,
+            SimplifiedQueryType::Occlusion,
+            query_index,
+            reset_state,
+            active_query,
+        )
+    }
+
+    fn validate_and_begin_query(
+        &self,
+        raw_encoder: &mut A::CommandEncoder,
+        query_set_id: id::QuerySetId,
+        query_type: SimplifiedQueryType,
+        query_index: u32,
+        reset_state: Option<&mut QueryResetMap<A>>,
+        active_query: &mut Option<(id::QuerySetId, u32)>,
+    ) -> Result<(), QueryUseError> {
+        let needs_reset = reset_state.is_none();
+        let query_set = self.validate_query(query_set_id, query_type, query_index, reset_state)?;
 
         if let Some((_old_id, old_idx)) = active_query.replace((query_set_id, query_index)) {
             return Err(QueryUseError::AlreadyStarted {
@@ -260,6 +292,22 @@ pub(super) fn end_pipeline_statistics_query<A: HalApi>(
     raw_encoder: &mut A::CommandEncoder,
     storage: &Storage<QuerySet<A>, id::QuerySetId>,
     active_query: &mut Option<(id::QuerySetId, u32)>,
+) -> Result<(), QueryUseError> {
+    end_query(raw_encoder, storage, active_query)
+}
+
+pub(super) fn end_occlusion_query<A: HalApi>(
+    raw_encoder: &mut A::CommandEncoder,
+    storage: &Storage<QuerySet<A>, id::QuerySetId>,
+    active_query: &mut Option<(id::QuerySetId, u32)>,
+) -> Result<(), QueryUseError> {
+    end_query(raw_encoder, storage, active_query)
+}
+
+fn end_query<A: HalApi>(
+    raw_encoder: &mut A::CommandEncoder,
+    storage: &Storage<QuerySet<A>, id::QuerySetId>,
+    active_query: &mut Option<(id::QuerySetId, u32)>,
 ) -> Result<(), QueryUseError> {
     if let Some((query_set_id, query_index)) = active_query.take() {
         // We can unwrap here as the validity was validated when the active query was set
@@ -1,10 +1,10 @@
 use crate::{
     binding_model::BindError,
     command::{
-        bind::Binder, end_pipeline_statistics_query, BasePass, BasePassRef, CommandBuffer,
-        CommandEncoderError, CommandEncoderStatus, DrawError, ExecutionError, MapPassErr,
-        PassErrorScope, QueryResetMap, QueryUseError, RenderCommand, RenderCommandError,
-        StateChange,
+        bind::Binder, end_occlusion_query, end_pipeline_statistics_query, BasePass, BasePassRef,
+        CommandBuffer, CommandEncoderError, CommandEncoderStatus, DrawError, ExecutionError,
+        MapPassErr, PassErrorScope, QueryResetMap, QueryUseError, RenderCommand,
+        RenderCommandError, SimplifiedQueryType, StateChange,
     },
     device::{
         AttachmentData, MissingDownlevelFlags, MissingFeatures, RenderPassCompatibilityError,
@@ -15,7 +15,7 @@ use crate::{
     id,
     init_tracker::MemoryInitKind,
     pipeline::PipelineFlags,
-    resource::{Texture, TextureView},
+    resource::{QuerySet, Texture, TextureView},
     track::{StatefulTrackerSubset, TextureSelector, UsageConflict},
     validation::{
         check_buffer_usage, check_texture_usage, MissingBufferUsageError, MissingTextureUsageError,
@@ -150,6 +150,8 @@ pub struct RenderPassDescriptor<'a> {
     pub color_attachments: Cow<'a, [RenderPassColorAttachment]>,
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<&'a RenderPassDepthStencilAttachment>,
+    /// The query set that occlusion queries of the render pass write to, if any.
+    pub occlusion_query_set: Option<id::QuerySetId>,
 }
 
 #[cfg_attr(feature = "serial-pass", derive(Deserialize, Serialize))]
@@ -158,6 +160,7 @@ pub struct RenderPass {
     parent_id: id::CommandEncoderId,
     color_targets: ArrayVec<RenderPassColorAttachment, { hal::MAX_COLOR_TARGETS }>,
     depth_stencil_target: Option<RenderPassDepthStencilAttachment>,
+    occlusion_query_set: Option<id::QuerySetId>,
 }
 
 impl RenderPass {
@@ -167,6 +170,7 @@ impl RenderPass {
             parent_id,
             color_targets: desc.color_attachments.iter().cloned().collect(),
             depth_stencil_target: desc.depth_stencil_attachment.cloned(),
+            occlusion_query_set: desc.occlusion_query_set,
         }
     }
 
@@ -180,6 +184,7 @@ impl RenderPass {
             base: self.base,
             target_colors: self.color_targets.into_iter().collect(),
             target_depth_stencil: self.depth_stencil_target,
+            occlusion_query_set: self.occlusion_query_set,
         }
     }
 
@@ -399,6 +404,8 @@ pub enum RenderPassErrorInner {
     InvalidColorAttachmentFormat(wgt::TextureFormat),
     #[error("attachment format {0:?} is not a depth-stencil format")]
     InvalidDepthStencilAttachmentFormat(wgt::TextureFormat),
+    #[error("attachment view format {0:?} can't be rendered to")]
+    UnsupportedAttachmentViewFormat(wgt::TextureFormat),
     #[error("necessary attachments are missing")]
     MissingAttachments,
     #[error("attachments have differing sizes: {previous:?} is followed by {mismatch:?}")]
@@ -455,6 +462,10 @@ pub enum RenderPassErrorInner {
     Draw(#[from] DrawError),
     #[error(transparent)]
     Bind(#[from] BindError),
+    #[error("occlusion query was used in a render pass without an occlusion query set")]
+    MissingOcclusionQuerySet,
+    #[error("occlusion query {0} is still active at the end of the render pass")]
+    UnendedOcclusionQuery(u32),
     #[error(transparent)]
     QueryUse(#[from] QueryUseError),
 }
@@ -542,8 +553,10 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
         label: Option<&str>,
         color_attachments: &[RenderPassColorAttachment],
         depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
+        occlusion_query_set: Option<&QuerySet<A>>,
         cmd_buf: &mut CommandBuffer<A>,
         view_guard: &'a Storage<TextureView<A>, id::TextureViewId>,
+        texture_guard: &Storage<Texture<A>, id::TextureId>,
     ) -> Result<Self, RenderPassErrorInner> {
         profiling::scope!("start", "RenderPassInfo");
 
@@ -559,6 +572,16 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
         let mut sample_count = 0;
 
         let mut add_view = |view: &TextureView<A>, type_name| {
+            // The view format may support fewer usages than the texture format.
+            if !view
+                .format_features
+                .allowed_usages
+                .contains(TextureUsages::RENDER_ATTACHMENT)
+            {
+                return Err(RenderPassErrorInner::UnsupportedAttachmentViewFormat(
+                    view.desc.format,
+                ));
+            }
             if let Some(ex) = extent {
                 if ex != view.extent {
                     return Err(RenderPassErrorInner::AttachmentsDimensionMismatch {
@@ -581,6 +604,18 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
             Ok(())
         };
 
+        let mut texture_memory_actions = AttachmentDataVec::new();
+        // Attachments that are not cleared need to have their contents initialized.
+        let mut add_init_action = |view: &TextureView<A>, kind| {
+            if let Ok(texture) = texture_guard.get(view.parent_id.value.0) {
+                texture_memory_actions.extend(texture.initialization_status.create_action(
+                    view.parent_id.value.0,
+                    view.selector.clone().into(),
+                    kind,
+                ));
+            }
+        };
+
         let mut colors = ArrayVec::<hal::ColorAttachment<A>, { hal::MAX_COLOR_TARGETS }>::new();
         let mut depth_stencil = None;
 
@@ -606,6 +641,18 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                 hal::TextureUses::DEPTH_STENCIL_WRITE
             };
             render_attachments.push(view.to_render_attachment(usage));
+            let loads_depth =
+                ds_aspects.contains(hal::FormatAspects::DEPTH) && at.depth.load_op == LoadOp::Load;
+            let loads_stencil = ds_aspects.contains(hal::FormatAspects::STENCIL)
+                && at.stencil.load_op == LoadOp::Load;
+            add_init_action(
+                view,
+                if loads_depth || loads_stencil {
+                    MemoryInitKind::NeedsInitializedMemory
+                } else {
+                    MemoryInitKind::ImplicitlyInitialized
+                },
+            );
 
             depth_stencil = Some(hal::DepthStencilAttachment {
                 target: hal::Attachment {
@@ -638,6 +685,13 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
 
             render_attachments
                 .push(color_view.to_render_attachment(hal::TextureUses::COLOR_TARGET));
+            add_init_action(
+                color_view,
+                match at.channel.load_op {
+                    LoadOp::Load => MemoryInitKind::NeedsInitializedMemory,
+                    LoadOp::Clear => MemoryInitKind::ImplicitlyInitialized,
+                },
+            );
 
             let mut hal_resolve_target = None;
             if let Some(resolve_target) = at.resolve_target {
@@ -646,6 +700,15 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
                     .views
                     .use_extend(&*view_guard, resolve_target, (), ())
                     .map_err(|_| RenderPassErrorInner::InvalidAttachment(resolve_target))?;
+                if !resolve_view
+                    .format_features
+                    .allowed_usages
+                    .contains(TextureUsages::RENDER_ATTACHMENT)
+                {
+                    return Err(RenderPassErrorInner::UnsupportedAttachmentViewFormat(
+                        resolve_view.desc.format,
+                    ));
+                }
                 if color_view.extent != resolve_view.extent {
                     return Err(RenderPassErrorInner::AttachmentsDimensionMismatch {
                         previous: (attachment_type_name, extent.unwrap_or_default()),
@@ -661,6 +724,7 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
 
                 render_attachments
                     .push(resolve_view.to_render_attachment(hal::TextureUses::COLOR_TARGET));
+                add_init_action(resolve_view, MemoryInitKind::ImplicitlyInitialized);
 
                 hal_resolve_target = Some(hal::Attachment {
                     view: &resolve_view.raw,
@@ -682,6 +746,9 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
         if sample_count != 1 && sample_count != 4 {
             return Err(RenderPassErrorInner::InvalidSampleCount(sample_count));
         }
+        cmd_buf
+            .texture_memory_init_actions
+            .extend(texture_memory_actions);
 
         let view_data = AttachmentData {
             colors: color_attachments
@@ -707,6 +774,7 @@ impl<'a, A: HalApi> RenderPassInfo<'a, A> {
             sample_count,
             color_attachments: &colors,
             depth_stencil_attachment: depth_stencil,
+            occlusion_query_set: occlusion_query_set.map(|query_set| &query_set.raw),
         };
         unsafe {
             cmd_buf.encoder.raw.begin_render_pass(&hal_desc);
@@ -768,6 +836,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             pass.base.as_ref(),
             &pass.color_targets,
             pass.depth_stencil_target.as_ref(),
+            pass.occlusion_query_set,
         )
     }
 
@@ -778,6 +847,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         base: BasePassRef<RenderCommand>,
         color_attachments: &[RenderPassColorAttachment],
         depth_stencil_attachment: Option<&RenderPassDepthStencilAttachment>,
+        occlusion_query_set_id: Option<id::QuerySetId>,
     ) -> Result<(), RenderPassError> {
         profiling::scope!("run_render_pass", "CommandEncoder");
         let scope = PassErrorScope::Pass(encoder_id);
@@ -802,6 +872,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     base: BasePass::from_ref(base),
                     target_colors: color_attachments.to_vec(),
                     target_depth_stencil: depth_stencil_attachment.cloned(),
+                    occlusion_query_set: occlusion_query_set_id,
                 });
             }
 
@@ -824,12 +895,35 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 encoder_id
             );
 
+            let occlusion_query_set = match occlusion_query_set_id {
+                Some(query_set_id) => {
+                    let query_set = cmd_buf
+                        .trackers
+                        .query_sets
+                        .use_extend(&*query_set_guard, query_set_id, (), ())
+                        .map_err(|_| RenderCommandError::InvalidQuerySet(query_set_id))
+                        .map_pass_err(scope)?;
+                    let set_type = SimplifiedQueryType::from(query_set.desc.ty);
+                    if set_type != SimplifiedQueryType::Occlusion {
+                        return Err(QueryUseError::IncompatibleType {
+                            set_type,
+                            query_type: SimplifiedQueryType::Occlusion,
+                        })
+                        .map_pass_err(scope);
+                    }
+                    Some((query_set_id, query_set))
+                }
+                None => None,
+            };
+
             let mut info = RenderPassInfo::start(
                 base.label,
                 color_attachments,
                 depth_stencil_attachment,
+                occlusion_query_set.map(|(_, query_set)| query_set),
                 cmd_buf,
                 &*view_guard,
+                &*texture_guard,
             )
             .map_pass_err(scope)?;
 
@@ -849,6 +943,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let mut dynamic_offset_count = 0;
             let mut string_offset = 0;
             let mut active_query = None;
+            let mut active_occlusion_query = None;
             let mut query_reset_state = QueryResetMap::new();
 
             for command in base.commands {
@@ -900,6 +995,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                 }
                             }),
                         );
+                        cmd_buf.texture_memory_init_actions.extend(
+                            bind_group.used_texture_ranges.iter().filter_map(|action| {
+                                match texture_guard.get(action.id) {
+                                    Ok(texture) => {
+                                        texture.initialization_status.check_action(action)
+                                    }
+                                    Err(_) => None,
+                                }
+                            }),
+                        );
 
                         let pipeline_layout_id = state.binder.pipeline_layout_id;
                         let entries = state.binder.assign_group(
@@ -1599,6 +1704,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         end_pipeline_statistics_query(raw, &*query_set_guard, &mut active_query)
                             .map_pass_err(scope)?;
                     }
+                    RenderCommand::BeginOcclusionQuery { query_index } => {
+                        let scope = PassErrorScope::BeginOcclusionQuery;
+
+                        let (query_set_id, query_set) = occlusion_query_set
+                            .ok_or(RenderPassErrorInner::MissingOcclusionQuerySet)
+                            .map_pass_err(scope)?;
+
+                        query_set
+                            .validate_and_begin_occlusion_query(
+                                raw,
+                                query_set_id,
+                                query_index,
+                                Some(&mut query_reset_state),
+                                &mut active_occlusion_query,
+                            )
+                            .map_pass_err(scope)?;
+                    }
+                    RenderCommand::EndOcclusionQuery => {
+                        let scope = PassErrorScope::EndOcclusionQuery;
+
+                        end_occlusion_query(raw, &*query_set_guard, &mut active_occlusion_query)
+                            .map_pass_err(scope)?;
+                    }
                     RenderCommand::ExecuteBundle(bundle_id) => {
                         let scope = PassErrorScope::ExecuteBundle;
                         let bundle = cmd_buf
@@ -1630,6 +1758,17 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                                     Err(_) => None,
                                 }),
                         );
+                        cmd_buf.texture_memory_init_actions.extend(
+                            bundle
+                                .texture_memory_init_actions
+                                .iter()
+                                .filter_map(|action| match texture_guard.get(action.id) {
+                                    Ok(texture) => {
+                                        texture.initialization_status.check_action(action)
+                                    }
+                                    Err(_) => None,
+                                }),
+                        );
 
                         unsafe {
                             bundle.execute(
@@ -1666,6 +1805,11 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
             }
 
+            if let Some((_, query_index)) = active_occlusion_query {
+                return Err(RenderPassErrorInner::UnendedOcclusionQuery(query_index))
+                    .map_pass_err(scope);
+            }
+
             log::trace!("Merging {:?} with the render pass", encoder_id);
             let trackers = info.finish(raw, &*texture_guard).map_pass_err(scope)?;
 
@@ -2066,6 +2210,21 @@ pub mod render_ffi {
             .push(RenderCommand::EndPipelineStatisticsQuery);
     }
 
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_begin_occlusion_query(
+        pass: &mut RenderPass,
+        query_index: u32,
+    ) {
+        pass.base
+            .commands
+            .push(RenderCommand::BeginOcclusionQuery { query_index });
+    }
+
+    #[no_mangle]
+    pub extern "C" fn wgpu_render_pass_end_occlusion_query(pass: &mut RenderPass) {
+        pass.base.commands.push(RenderCommand::EndOcclusionQuery);
+    }
+
     /// # Safety
     ///
     /// This function is unsafe as there is no guarantee that the given pointer is
@@ -6,7 +6,7 @@ use crate::{
     error::{ErrorFormatter, PrettyError},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Storage, Token},
     id::{BufferId, CommandEncoderId, TextureId},
-    init_tracker::MemoryInitKind,
+    init_tracker::{has_copy_partial_init_tracker_coverage, MemoryInitKind},
     resource::{Texture, TextureErrorDimension},
     track::TextureSelector,
 };
@@ -179,6 +179,20 @@ pub(crate) fn extract_texture_selector<A: hal::Api>(
     Ok((selector, base, format))
 }
 
+/// Returns the kind of initialization a copy into the texture amounts to.
+/// Subresources that are only partially overwritten need to be initialized first.
+pub(crate) fn copy_dst_init_kind(
+    copy_texture: &ImageCopyTexture,
+    copy_size: &Extent3d,
+    desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
+) -> MemoryInitKind {
+    if has_copy_partial_init_tracker_coverage(copy_size, copy_texture.mip_level, desc) {
+        MemoryInitKind::NeedsInitializedMemory
+    } else {
+        MemoryInitKind::ImplicitlyInitialized
+    }
+}
+
 /// Function copied with some modifications from webgpu standard <https://gpuweb.github.io/gpuweb/#copy-between-buffer-texture>
 /// If successful, returns (number of buffer bytes required for this copy, number of bytes between array layers).
 pub(crate) fn validate_linear_texture_data(
@@ -274,7 +288,7 @@ pub(crate) fn validate_linear_texture_data(
 /// Returns the HAL copy extent and the layer count.
 pub(crate) fn validate_texture_copy_range(
     texture_copy_view: &ImageCopyTexture,
-    desc: &wgt::TextureDescriptor<()>,
+    desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     texture_side: CopySide,
     copy_size: &Extent3d,
 ) -> Result<(hal::CopyExtent, u32), TransferError> {
@@ -535,7 +549,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .use_replace(
                 &*texture_guard,
                 destination.texture,
-                dst_range,
+                dst_range.clone(),
                 hal::TextureUses::COPY_DST,
             )
             .unwrap();
@@ -574,8 +588,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 source.layout.offset..(source.layout.offset + required_buffer_bytes_in_copy),
                 MemoryInitKind::NeedsInitializedMemory,
             ));
+        cmd_buf.texture_memory_init_actions.extend(
+            dst_texture.initialization_status.create_action(
+                destination.texture,
+                dst_range.into(),
+                copy_dst_init_kind(destination, copy_size, &dst_texture.desc),
+            ),
+        );
 
-        if !conv::is_valid_copy_dst_texture_format(dst_texture.desc.format) {
+        if !conv::is_valid_copy_dst_texture_format(dst_texture.desc.format, destination.aspect) {
             return Err(
                 TransferError::CopyToForbiddenTextureFormat(dst_texture.desc.format).into(),
             );
@@ -641,7 +662,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .use_replace(
                 &*texture_guard,
                 source.texture,
-                src_range,
+                src_range.clone(),
                 hal::TextureUses::COPY_SRC,
             )
             .unwrap();
@@ -688,7 +709,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             true,
         )?;
 
-        if !conv::is_valid_copy_src_texture_format(src_texture.desc.format) {
+        if !conv::is_valid_copy_src_texture_format(src_texture.desc.format, source.aspect) {
             return Err(
                 TransferError::CopyFromForbiddenTextureFormat(src_texture.desc.format).into(),
             );
@@ -702,6 +723,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     ..(destination.layout.offset + required_buffer_bytes_in_copy),
                 MemoryInitKind::ImplicitlyInitialized,
             ));
+        cmd_buf.texture_memory_init_actions.extend(
+            src_texture.initialization_status.create_action(
+                source.texture,
+                src_range.into(),
+                MemoryInitKind::NeedsInitializedMemory,
+            ),
+        );
 
         let regions = (0..array_layer_count).map(|rel_array_layer| {
             let mut texture_base = src_base.clone();
@@ -773,7 +801,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .use_replace(
                 &*texture_guard,
                 source.texture,
-                src_range,
+                src_range.clone(),
                 hal::TextureUses::COPY_SRC,
             )
             .unwrap();
@@ -796,7 +824,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .use_replace(
                 &*texture_guard,
                 destination.texture,
-                dst_range,
+                dst_range.clone(),
                 hal::TextureUses::COPY_DST,
             )
             .unwrap();
@@ -820,6 +848,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             copy_size,
         )?;
 
+        cmd_buf.texture_memory_init_actions.extend(
+            src_texture.initialization_status.create_action(
+                source.texture,
+                src_range.into(),
+                MemoryInitKind::NeedsInitializedMemory,
+            ),
+        );
+        cmd_buf.texture_memory_init_actions.extend(
+            dst_texture.initialization_status.create_action(
+                destination.texture,
+                dst_range.into(),
+                copy_dst_init_kind(destination, copy_size, &dst_texture.desc),
+            ),
+        );
+
         let hal_copy_size = hal::CopyExtent {
             width: src_copy_size.width.min(dst_copy_size.width),
             height: src_copy_size.height.min(dst_copy_size.height),
@@ -2,7 +2,9 @@
 use crate::device::trace;
 use crate::{
     device::{
-        queue::{EncoderInFlight, SubmittedWorkDoneClosure, TempResource},
+        queue::{
+            EncoderInFlight, StagingChunk, StagingPool, SubmittedWorkDoneClosure, TempResource,
+        },
         DeviceError,
     },
     hub::{GlobalIdentityHandlerFactory, HalApi, Hub, Token},
@@ -89,6 +91,8 @@ struct NonReferencedResources<A: hal::Api> {
     // Note: we keep the associated ID here in order to be able to check
     // at any point what resources are used in a submission.
     texture_views: Vec<(id::Valid<id::TextureViewId>, A::TextureView)>,
+    // Views created internally, which have no ID.
+    internal_views: Vec<A::TextureView>,
     samplers: Vec<A::Sampler>,
     bind_groups: Vec<A::BindGroup>,
     compute_pipes: Vec<A::ComputePipeline>,
@@ -104,6 +108,7 @@ impl<A: hal::Api> NonReferencedResources<A> {
             buffers: Vec::new(),
             textures: Vec::new(),
             texture_views: Vec::new(),
+            internal_views: Vec::new(),
             samplers: Vec::new(),
             bind_groups: Vec::new(),
             compute_pipes: Vec::new(),
@@ -118,6 +123,7 @@ impl<A: hal::Api> NonReferencedResources<A> {
         self.buffers.extend(other.buffers);
         self.textures.extend(other.textures);
         self.texture_views.extend(other.texture_views);
+        self.internal_views.extend(other.internal_views);
         self.samplers.extend(other.samplers);
         self.bind_groups.extend(other.bind_groups);
         self.compute_pipes.extend(other.compute_pipes);
@@ -137,6 +143,9 @@ impl<A: hal::Api> NonReferencedResources<A> {
         for (_, raw) in self.texture_views.drain(..) {
             device.destroy_texture_view(raw);
         }
+        for raw in self.internal_views.drain(..) {
+            device.destroy_texture_view(raw);
+        }
         for raw in self.samplers.drain(..) {
             device.destroy_sampler(raw);
         }
@@ -166,6 +175,8 @@ struct ActiveSubmission<A: hal::Api> {
     last_resources: NonReferencedResources<A>,
     mapped: Vec<id::Valid<id::BufferId>>,
     encoders: Vec<EncoderInFlight<A>>,
+    /// Chunks of the staging pool used by the writes of the submission.
+    staging_chunks: Vec<StagingChunk<A>>,
     work_done_closures: SmallVec<[SubmittedWorkDoneClosure; 1]>,
 }
 
@@ -223,12 +234,14 @@ impl<A: hal::Api> LifetimeTracker<A> {
         index: SubmissionIndex,
         temp_resources: impl Iterator<Item = TempResource<A>>,
         encoders: Vec<EncoderInFlight<A>>,
+        staging_chunks: Vec<StagingChunk<A>>,
     ) {
         let mut last_resources = NonReferencedResources::new();
         for res in temp_resources {
             match res {
                 TempResource::Buffer(raw) => last_resources.buffers.push(raw),
                 TempResource::Texture(raw) => last_resources.textures.push(raw),
+                TempResource::TextureView(raw) => last_resources.internal_views.push(raw),
             }
         }
 
@@ -237,6 +250,7 @@ impl<A: hal::Api> LifetimeTracker<A> {
             last_resources,
             mapped: Vec::new(),
             encoders,
+            staging_chunks,
             work_done_closures: SmallVec::new(),
         });
     }
@@ -264,6 +278,8 @@ impl<A: hal::Api> LifetimeTracker<A> {
         &mut self,
         last_done: SubmissionIndex,
         command_allocator: &Mutex<super::CommandAllocator<A>>,
+        staging_pool: &Mutex<StagingPool<A>>,
+        device: &A::Device,
     ) -> SmallVec<[SubmittedWorkDoneClosure; 1]> {
         profiling::scope!("triage_submissions");
 
@@ -284,6 +300,7 @@ impl<A: hal::Api> LifetimeTracker<A> {
                 let raw = unsafe { encoder.land() };
                 command_allocator.lock().release_encoder(raw);
             }
+            staging_pool.lock().release(a.staging_chunks, device);
             work_done_closures.extend(a.work_done_closures);
         }
         work_done_closures
@@ -309,18 +326,23 @@ impl<A: hal::Api> LifetimeTracker<A> {
         match temp_resource {
             TempResource::Buffer(raw) => resources.buffers.push(raw),
             TempResource::Texture(raw) => resources.textures.push(raw),
+            TempResource::TextureView(raw) => resources.internal_views.push(raw),
         }
     }
 
-    pub fn add_work_done_closure(&mut self, closure: SubmittedWorkDoneClosure) -> bool {
+    /// Returns the closure back if there is no submission in flight.
+    pub fn add_work_done_closure(
+        &mut self,
+        closure: SubmittedWorkDoneClosure,
+    ) -> Option<SubmittedWorkDoneClosure> {
         match self.active.last_mut() {
             Some(active) => {
                 active.work_done_closures.push(closure);
-                true
+                None
             }
             // Note: we can't immediately invoke the closure, since it assumes
             // nothing is currently locked in the hubs.
-            None => false,
+            None => Some(closure),
         }
     }
 }
@@ -674,7 +696,7 @@ impl<A: HalApi> LifetimeTracker<A> {
                     }
                     _ => panic!("No pending mapping."),
                 };
-                let status = if mapping.range.start != mapping.range.end {
+                let result = if mapping.range.start != mapping.range.end {
                     log::debug!("Buffer {:?} map state -> Active", buffer_id);
                     let host = mapping.op.host;
                     let size = mapping.range.end - mapping.range.start;
@@ -685,17 +707,17 @@ impl<A: HalApi> LifetimeTracker<A> {
                                 range: mapping.range.start..mapping.range.start + size,
                                 host,
                             };
-                            resource::BufferMapAsyncStatus::Success
+                            Ok(())
                         }
                         Err(e) => {
                             log::error!("Mapping failed {:?}", e);
-                            resource::BufferMapAsyncStatus::Error
+                            Err(e)
                         }
                     }
                 } else {
-                    resource::BufferMapAsyncStatus::Success
+                    Ok(())
                 };
-                pending_callbacks.push((mapping.op, status));
+                pending_callbacks.push((mapping.op, result));
             }
         }
         pending_callbacks
@@ -1,9 +1,12 @@
 use crate::{
     binding_model, command, conv,
     device::life::WaitIdleError,
+    diagnostic,
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Hub, Input, InvalidId, Storage, Token},
     id,
-    init_tracker::{BufferInitTracker, BufferInitTrackerAction, MemoryInitKind},
+    init_tracker::{
+        BufferInitTracker, BufferInitTrackerAction, MemoryInitKind, TextureInitTracker,
+    },
     instance, pipeline, present, resource,
     track::{BufferState, TextureSelector, TextureState, TrackerSet, UsageConflict},
     validation::{self, check_buffer_usage, check_texture_usage},
@@ -28,6 +31,8 @@ pub mod trace;
 
 pub const SHADER_STAGE_COUNT: usize = 3;
 const CLEANUP_WAIT_MS: u32 = 5000;
+/// Size of the buffer of zeros that textures are cleared from.
+pub(crate) const ZERO_BUFFER_SIZE: BufferAddress = 512 << 10;
 
 const IMPLICIT_FAILURE: &str = "failed implicit";
 const EP_FAILURE: &str = "EP is invalid";
@@ -110,30 +115,146 @@ impl RenderPassContext {
     }
 }
 
-pub type BufferMapPendingClosure = (resource::BufferMapOperation, resource::BufferMapAsyncStatus);
+pub type BufferMapPendingClosure = (resource::BufferMapOperation, resource::BufferAccessResult);
 
 #[derive(Default)]
 pub struct UserClosures {
     pub mappings: Vec<BufferMapPendingClosure>,
     pub submissions: SmallVec<[queue::SubmittedWorkDoneClosure; 1]>,
+    pub device_lost_invocations: SmallVec<[DeviceLostInvocation; 1]>,
 }
 
 impl UserClosures {
     fn extend(&mut self, other: Self) {
         self.mappings.extend(other.mappings);
         self.submissions.extend(other.submissions);
+        self.device_lost_invocations
+            .extend(other.device_lost_invocations);
     }
 
-    unsafe fn fire(self) {
+    fn fire(self) {
         //Note: this logic is specifically moved out of `handle_mapping()` in order to
         // have nothing locked by the time we execute users callback code.
-        for (operation, status) in self.mappings {
-            (operation.callback)(status, operation.user_data);
+        for (operation, result) in self.mappings {
+            operation.callback.call(result);
         }
         for closure in self.submissions {
-            (closure.callback)(closure.user_data);
+            closure.call();
+        }
+        for invocation in self.device_lost_invocations {
+            invocation.call();
+        }
+    }
+}
+
+pub type DeviceLostCallback = unsafe extern "C" fn(
+    user_data: *mut u8,
+    reason: wgt::DeviceLostReason,
+    message: *const std::os::raw::c_char,
+);
+
+/// C-style form of the closure called when a device is lost, for FFI users.
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct DeviceLostClosureC {
+    pub callback: DeviceLostCallback,
+    pub user_data: *mut u8,
+}
+
+unsafe impl Send for DeviceLostClosureC {}
+
+/// Closure called once when a device is lost, with the reason and a message.
+pub struct DeviceLostClosure {
+    // Wrapped so that only the creation of the C form is unsafe, and calling is not.
+    inner: DeviceLostClosureInner,
+}
+
+enum DeviceLostClosureInner {
+    Rust {
+        callback: Box<dyn FnOnce(wgt::DeviceLostReason, String) + Send + 'static>,
+    },
+    C {
+        inner: DeviceLostClosureC,
+    },
+}
+
+impl DeviceLostClosure {
+    pub fn from_rust(
+        callback: Box<dyn FnOnce(wgt::DeviceLostReason, String) + Send + 'static>,
+    ) -> Self {
+        Self {
+            inner: DeviceLostClosureInner::Rust { callback },
         }
     }
+
+    /// # Safety
+    ///
+    /// - The callback pointer must be valid to call with the provided user_data pointer.
+    /// - Both pointers must point to `'static` data, as the callback may happen at an
+    ///   unspecified time.
+    /// - The message pointer is only valid for the duration of the call.
+    pub unsafe fn from_c(inner: DeviceLostClosureC) -> Self {
+        Self {
+            inner: DeviceLostClosureInner::C { inner },
+        }
+    }
+
+    fn call(self, reason: wgt::DeviceLostReason, message: String) {
+        match self.inner {
+            DeviceLostClosureInner::Rust { callback } => callback(reason, message),
+            // Safety: the contract of the call to from_c says that this call is sound.
+            DeviceLostClosureInner::C { inner } => unsafe {
+                let message = std::ffi::CString::new(message).unwrap_or_default();
+                (inner.callback)(inner.user_data, reason, message.as_ptr())
+            },
+        }
+    }
+}
+
+impl std::fmt::Debug for DeviceLostClosure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.inner {
+            DeviceLostClosureInner::Rust { .. } => f.write_str("DeviceLostClosure::Rust"),
+            DeviceLostClosureInner::C { ref inner } => {
+                write!(f, "DeviceLostClosure::C({:?})", inner)
+            }
+        }
+    }
+}
+
+/// Pending call of a device lost closure, made once nothing is locked.
+#[derive(Debug)]
+pub struct DeviceLostInvocation {
+    closure: DeviceLostClosure,
+    reason: wgt::DeviceLostReason,
+    message: String,
+}
+
+impl DeviceLostInvocation {
+    fn call(self) {
+        self.closure.call(self.reason, self.message);
+    }
+}
+
+/// Whether a device is lost, and who to tell.
+#[derive(Debug, Default)]
+struct DeviceLostState {
+    /// Reason and message of the loss, once the device is lost.
+    lost: Option<(wgt::DeviceLostReason, String)>,
+    closure: Option<DeviceLostClosure>,
+}
+
+impl DeviceLostState {
+    /// Returns the invocation of the closure, if the device is lost and one is set.
+    fn take_invocation(&mut self) -> Option<DeviceLostInvocation> {
+        let (reason, ref message) = *self.lost.as_ref()?;
+        let closure = self.closure.take()?;
+        Some(DeviceLostInvocation {
+            closure,
+            reason,
+            message: message.clone(),
+        })
+    }
 }
 
 fn map_buffer<A: hal::Api>(
@@ -261,6 +382,12 @@ pub struct Device<A: hal::Api> {
     //TODO: move this behind another mutex. This would allow several methods to switch
     // to borrow Device immutably, such as `write_buffer`, `write_texture`, and `buffer_unmap`.
     pending_writes: queue::PendingWrites<A>,
+    /// Buffer of `ZERO_BUFFER_SIZE` zeros, kept in the `COPY_SRC` state after its creation.
+    pub(crate) zero_buffer: A::Buffer,
+    /// Staging memory of `write_buffer` and `write_texture`, recycled across submissions.
+    staging_pool: Mutex<queue::StagingPool<A>>,
+    /// Set once the device is lost, after which operations on it fail with `DeviceError::Lost`.
+    lost: Mutex<DeviceLostState>,
     #[cfg(feature = "trace")]
     pub(crate) trace: Option<Mutex<trace::Trace>>,
 }
@@ -315,7 +442,31 @@ impl<A: HalApi> Device<A> {
         let pending_encoder = com_alloc
             .acquire_encoder(&open.device, &open.queue)
             .map_err(|_| CreateDeviceError::OutOfMemory)?;
-        let pending_writes = queue::PendingWrites::new(pending_encoder);
+        let mut pending_writes = queue::PendingWrites::<A>::new(pending_encoder);
+
+        let zero_buffer = unsafe {
+            open.device.create_buffer(&hal::BufferDescriptor {
+                label: Some("_ZeroBuffer"),
+                size: ZERO_BUFFER_SIZE,
+                usage: hal::BufferUses::COPY_SRC | hal::BufferUses::COPY_DST,
+                memory_flags: hal::MemoryFlags::empty(),
+            })
+        }
+        .map_err(|_| CreateDeviceError::OutOfMemory)?;
+        // The pending writes are submitted before anything else, so the buffer
+        // is filled before any copy out of it.
+        unsafe {
+            let encoder = pending_writes.activate();
+            encoder.transition_buffers(iter::once(hal::BufferBarrier {
+                buffer: &zero_buffer,
+                usage: hal::BufferUses::empty()..hal::BufferUses::COPY_DST,
+            }));
+            encoder.clear_buffer(&zero_buffer, 0..ZERO_BUFFER_SIZE);
+            encoder.transition_buffers(iter::once(hal::BufferBarrier {
+                buffer: &zero_buffer,
+                usage: hal::BufferUses::COPY_DST..hal::BufferUses::COPY_SRC,
+            }));
+        }
 
         Ok(Self {
             raw: open.device,
@@ -347,9 +498,31 @@ impl<A: HalApi> Device<A> {
             features: desc.features,
             downlevel,
             pending_writes,
+            zero_buffer,
+            staging_pool: Mutex::new(queue::StagingPool::new()),
+            lost: Mutex::new(DeviceLostState::default()),
         })
     }
 
+    pub(crate) fn is_lost(&self) -> bool {
+        self.lost.lock().lost.is_some()
+    }
+
+    /// Marks the device as lost, unless it already is.
+    ///
+    /// The closure is not called here, but by the next `maintain`, as locks may be held.
+    pub(crate) fn lose(&self, reason: wgt::DeviceLostReason, message: &str) {
+        let mut state = self.lost.lock();
+        if state.lost.is_none() {
+            log::warn!("Device lost ({:?}): {}", reason, message);
+            state.lost = Some((reason, message.to_string()));
+        }
+    }
+
+    fn lost_reason(&self) -> Option<wgt::DeviceLostReason> {
+        self.lost.lock().lost.as_ref().map(|&(reason, _)| reason)
+    }
+
     fn lock_life<'this, 'token: 'this>(
         &'this self,
         //TODO: fix this - the token has to be borrowed for the lock
@@ -380,30 +553,46 @@ impl<A: HalApi> Device<A> {
         );
         life_tracker.triage_mapped(hub, token);
 
-        let last_done_index = if force_wait {
-            let current_index = self.active_submission_index;
+        let current_index = self.active_submission_index;
+        let fence_value = if self.lost_reason() == Some(wgt::DeviceLostReason::DriverReset) {
+            // Nothing is going to signal the fence anymore.
+            Err(hal::DeviceError::Lost)
+        } else if force_wait {
             unsafe {
                 self.raw
                     .wait(&self.fence, current_index, CLEANUP_WAIT_MS)
-                    .map_err(DeviceError::from)?
-            };
-            current_index
+                    .map(|_| current_index)
+            }
         } else {
-            unsafe {
-                self.raw
-                    .get_fence_value(&self.fence)
-                    .map_err(DeviceError::from)?
+            unsafe { self.raw.get_fence_value(&self.fence) }
+        };
+        let last_done_index = match fence_value {
+            Ok(index) => index,
+            Err(hal::DeviceError::Lost) => {
+                self.lose(
+                    wgt::DeviceLostReason::DriverReset,
+                    "device lost while waiting for submitted work",
+                );
+                // Consider all the work done, so that its resources are freed
+                // and the pending callbacks are called.
+                current_index
             }
+            Err(err) => return Err(DeviceError::from(err).into()),
         };
 
-        let submission_closures =
-            life_tracker.triage_submissions(last_done_index, &self.command_allocator);
+        let submission_closures = life_tracker.triage_submissions(
+            last_done_index,
+            &self.command_allocator,
+            &self.staging_pool,
+            &self.raw,
+        );
         let mapping_closures = life_tracker.handle_mapping(hub, &self.raw, &self.trackers, token);
         life_tracker.cleanup(&self.raw);
 
         Ok(UserClosures {
             mappings: mapping_closures,
             submissions: submission_closures,
+            device_lost_invocations: self.lost.lock().take_invocation().into_iter().collect(),
         })
     }
 
@@ -537,17 +726,42 @@ impl<A: HalApi> Device<A> {
         })
     }
 
+    /// Checks that the texture can be viewed in its view formats.
+    ///
+    /// The usages are checked for each view, as a view format may not support all of them.
+    fn validate_view_formats(
+        &self,
+        desc: &resource::TextureDescriptor,
+    ) -> Result<(), resource::CreateTextureError> {
+        if !desc.view_formats.is_empty() {
+            self.require_downlevel_flags(wgt::DownlevelFlags::VIEW_FORMATS)?;
+        }
+        for &view_format in desc.view_formats.iter() {
+            if !desc.format.is_view_compatible(view_format) {
+                return Err(resource::CreateTextureError::InvalidViewFormat {
+                    texture: desc.format,
+                    view: view_format,
+                });
+            }
+            self.require_features(view_format.describe().required_features)
+                .map_err(|error| {
+                    resource::CreateTextureError::MissingFeatures(view_format, error)
+                })?;
+        }
+        Ok(())
+    }
+
     fn create_texture_from_hal(
         &self,
         hal_texture: A::Texture,
+        hal_usage: hal::TextureUses,
+        clear_mode: resource::TextureClearMode,
         self_id: id::DeviceId,
         adapter: &crate::instance::Adapter<A>,
         desc: &resource::TextureDescriptor,
     ) -> Result<resource::Texture<A>, resource::CreateTextureError> {
         debug_assert_eq!(self_id.backend(), A::VARIANT);
 
-        let hal_usage = conv::map_texture_usage(desc.usage, desc.format.into());
-
         let format_features = self
             .describe_format_features(adapter, desc.format)
             .map_err(|error| resource::CreateTextureError::MissingFeatures(desc.format, error))?;
@@ -589,6 +803,14 @@ impl<A: HalApi> Device<A> {
             return Err(resource::CreateTextureError::InvalidMipLevelCount(mips));
         }
 
+        let mut initialization_status =
+            TextureInitTracker::new(desc.mip_level_count, desc.array_layer_count());
+        if clear_mode == resource::TextureClearMode::None {
+            for mip in initialization_status.mips.iter_mut() {
+                mip.clear(0..desc.array_layer_count());
+            }
+        }
+
         Ok(resource::Texture {
             inner: resource::TextureInner::Native {
                 raw: Some(hal_texture),
@@ -600,6 +822,8 @@ impl<A: HalApi> Device<A> {
             desc: desc.map_label(|_| ()),
             hal_usage,
             format_features,
+            initialization_status,
+            clear_mode,
             full_range: TextureSelector {
                 levels: 0..desc.mip_level_count,
                 layers: 0..desc.array_layer_count(),
@@ -614,7 +838,13 @@ impl<A: HalApi> Device<A> {
         adapter: &crate::instance::Adapter<A>,
         desc: &resource::TextureDescriptor,
     ) -> Result<resource::Texture<A>, resource::CreateTextureError> {
-        let hal_usage = conv::map_texture_usage(desc.usage, desc.format.into());
+        self.validate_view_formats(desc)?;
+
+        // We are required to zero out (initialize) all memory.
+        // This is done on demand, which requires the texture to be usable for clearing!
+        let clear_mode = resource::TextureClearMode::new(&desc.map_label(|_| ()));
+        let hal_usage =
+            conv::map_texture_usage(desc.usage, desc.format.into()) | clear_mode.hal_usage();
         let hal_desc = hal::TextureDescriptor {
             label: desc.label.borrow_option(),
             size: desc.size,
@@ -624,6 +854,7 @@ impl<A: HalApi> Device<A> {
             format: desc.format,
             usage: hal_usage,
             memory_flags: hal::MemoryFlags::empty(),
+            view_formats: &desc.view_formats,
         };
         let raw = unsafe {
             self.raw
@@ -631,11 +862,12 @@ impl<A: HalApi> Device<A> {
                 .map_err(DeviceError::from)?
         };
 
-        self.create_texture_from_hal(raw, self_id, adapter, desc)
+        self.create_texture_from_hal(raw, hal_usage, clear_mode, self_id, adapter, desc)
     }
 
     fn create_texture_view(
         &self,
+        adapter: &crate::instance::Adapter<A>,
         texture: &resource::Texture<A>,
         texture_id: id::TextureId,
         desc: &resource::TextureViewDescriptor,
@@ -755,12 +987,18 @@ impl<A: HalApi> Device<A> {
             extent.depth_or_array_layers = view_layer_count;
         }
         let format = desc.format.unwrap_or(texture.desc.format);
-        if format != texture.desc.format {
+        if format != texture.desc.format && !texture.desc.view_formats.contains(&format) {
             return Err(resource::CreateTextureViewError::FormatReinterpretation {
                 texture: texture.desc.format,
                 view: format,
             });
         }
+        let format_features = if format == texture.desc.format {
+            texture.format_features
+        } else {
+            self.describe_format_features(adapter, format)
+                .map_err(|error| resource::CreateTextureViewError::MissingFeatures(format, error))?
+        };
 
         // filter the usages based on the other criteria
         let usage = {
@@ -781,7 +1019,13 @@ impl<A: HalApi> Device<A> {
             } else {
                 hal::TextureUses::all()
             };
-            texture.hal_usage & mask_copy & mask_dimension & mask_mip_level
+            // a view format may not support all the usages of the texture
+            let mask_format = if format == texture.desc.format {
+                hal::TextureUses::all()
+            } else {
+                conv::map_texture_usage(format_features.allowed_usages, format.into())
+            };
+            texture.hal_usage & mask_copy & mask_dimension & mask_mip_level & mask_format
         };
 
         log::debug!(
@@ -814,7 +1058,7 @@ impl<A: HalApi> Device<A> {
                 dimension: hal_desc.dimension,
                 range: hal_desc.range,
             },
-            format_features: texture.format_features,
+            format_features,
             extent,
             samples: texture.desc.sample_count,
             // once a storage - forever a storage
@@ -908,25 +1152,26 @@ impl<A: HalApi> Device<A> {
         desc: &pipeline::ShaderModuleDescriptor<'a>,
         source: pipeline::ShaderModuleSource<'a>,
     ) -> Result<pipeline::ShaderModule<A>, pipeline::CreateShaderModuleError> {
-        let module = match source {
+        let source_label = desc.label.as_ref().map(|label| label.to_string());
+        let (module, source) = match source {
             pipeline::ShaderModuleSource::Wgsl(code) => {
                 profiling::scope!("naga::wgsl::parse_str");
-                // TODO: refactor the corresponding Naga error to be owned, and then
-                // display it instead of unwrapping
                 match naga::front::wgsl::parse_str(&code) {
-                    Ok(module) => module,
+                    Ok(module) => (module, Some(code.into_owned())),
                     Err(err) => {
                         log::error!("Failed to parse WGSL code for {:?}: {}", desc.label, err);
                         return Err(pipeline::CreateShaderModuleError::Parsing(
-                            pipeline::NagaParseError {
-                                shader_source: code.to_string(),
-                                error: err,
+                            pipeline::ShaderError {
+                                diagnostic: diagnostic::parse_error_diagnostic(&err, &code),
+                                shader_source: Some(code.into_owned()),
+                                label: source_label,
+                                inner: err,
                             },
                         ));
                     }
                 }
             }
-            pipeline::ShaderModuleSource::Naga(module) => module,
+            pipeline::ShaderModuleSource::Naga(module) => (module, None),
         };
 
         use naga::valid::Capabilities as Caps;
@@ -947,7 +1192,13 @@ impl<A: HalApi> Device<A> {
                 .contains(wgt::Features::SHADER_PRIMITIVE_INDEX),
         );
         let info = naga::valid::Validator::new(naga::valid::ValidationFlags::all(), caps)
-            .validate(&module)?;
+            .validate(&module)
+            .map_err(|error| pipeline::ShaderError {
+                diagnostic: diagnostic::validation_error_diagnostic(&error, &module),
+                shader_source: source.clone(),
+                label: source_label.clone(),
+                inner: error,
+            })?;
         let interface = validation::Interface::new(&module, &info, self.features);
         let hal_shader = hal::ShaderInput::Naga(hal::NagaShader { module, info });
 
@@ -976,6 +1227,8 @@ impl<A: HalApi> Device<A> {
                 ref_count: self.life_guard.add_ref(),
             },
             interface: Some(interface),
+            source,
+            source_label,
             #[cfg(debug_assertions)]
             label: desc.label.borrow_or_default().to_string(),
         })
@@ -1015,11 +1268,62 @@ impl<A: HalApi> Device<A> {
                 ref_count: self.life_guard.add_ref(),
             },
             interface: None,
+            source: None,
+            source_label: desc.label.as_ref().map(|label| label.to_string()),
             #[cfg(debug_assertions)]
             label: desc.label.borrow_or_default().to_string(),
         })
     }
 
+    #[allow(unused_unsafe)]
+    unsafe fn create_pipeline_cache(
+        &self,
+        self_id: id::DeviceId,
+        adapter_info: &wgt::AdapterInfo,
+        desc: &pipeline::PipelineCacheDescriptor,
+    ) -> Result<pipeline::PipelineCache<A>, pipeline::CreatePipelineCacheError> {
+        use crate::pipeline_cache;
+
+        self.require_features(wgt::Features::PIPELINE_CACHE)?;
+
+        let data = match desc.data {
+            Some(ref data) => {
+                let validated = match self.raw.pipeline_cache_validation_key() {
+                    Some(key) => pipeline_cache::validate_pipeline_cache(
+                        data,
+                        &pipeline_cache::CacheKey::new(adapter_info, key),
+                    ),
+                    None => Err(pipeline::PipelineCacheValidationError::Unsupported),
+                };
+                match validated {
+                    Ok(data) => Some(data),
+                    Err(error) if desc.fallback => {
+                        log::warn!("Pipeline cache data is rejected: {}", error);
+                        None
+                    }
+                    Err(error) => return Err(error.into()),
+                }
+            }
+            None => None,
+        };
+
+        let hal_desc = hal::PipelineCacheDescriptor {
+            label: desc.label.borrow_option(),
+            data,
+        };
+        let raw =
+            unsafe { self.raw.create_pipeline_cache(&hal_desc) }.map_err(DeviceError::from)?;
+
+        Ok(pipeline::PipelineCache {
+            raw,
+            device_id: Stored {
+                value: id::Valid(self_id),
+                ref_count: self.life_guard.add_ref(),
+            },
+            life_guard: LifeGuard::new(desc.label.borrow_or_default()),
+        })
+    }
+
     fn deduplicate_bind_group_layout(
         self_id: id::DeviceId,
         entry_map: &binding_model::BindEntryMap,
@@ -1366,6 +1670,7 @@ impl<A: HalApi> Device<A> {
         let (sampler_guard, _) = hub.samplers.read(&mut token);
 
         let mut used_buffer_ranges = Vec::new();
+        let mut used_texture_ranges = Vec::new();
         let mut hal_entries = Vec::with_capacity(desc.entries.len());
         let mut hal_buffers = Vec::new();
         let mut hal_samplers = Vec::new();
@@ -1489,6 +1794,11 @@ impl<A: HalApi> Device<A> {
                         .map_err(UsageConflict::from)?;
                     let texture = &texture_guard[view.parent_id.value];
                     check_texture_usage(texture.desc.usage, pub_usage)?;
+                    used_texture_ranges.extend(texture.initialization_status.create_action(
+                        view.parent_id.value.0,
+                        view.selector.clone().into(),
+                        MemoryInitKind::NeedsInitializedMemory,
+                    ));
 
                     let res_index = hal_textures.len();
                     hal_textures.push(hal::TextureBinding {
@@ -1534,6 +1844,11 @@ impl<A: HalApi> Device<A> {
                             .map_err(UsageConflict::from)?;
                         let texture = &texture_guard[view.parent_id.value];
                         check_texture_usage(texture.desc.usage, pub_usage)?;
+                        used_texture_ranges.extend(texture.initialization_status.create_action(
+                            view.parent_id.value.0,
+                            view.selector.clone().into(),
+                            MemoryInitKind::NeedsInitializedMemory,
+                        ));
 
                         hal_textures.push(hal::TextureBinding {
                             view: &view.raw,
@@ -1582,6 +1897,7 @@ impl<A: HalApi> Device<A> {
             life_guard: LifeGuard::new(desc.label.borrow_or_default()),
             used,
             used_buffer_ranges,
+            used_texture_ranges,
             dynamic_binding_info,
         })
     }
@@ -1601,6 +1917,18 @@ impl<A: HalApi> Device<A> {
             return Err(Error::DepthStencilAspect);
         }
         let format_info = view.desc.format.describe();
+        // The view format may support fewer usages than the texture format.
+        let check_view_format_usage = |usage| {
+            if view.format_features.allowed_usages.contains(usage) {
+                Ok(())
+            } else {
+                Err(Error::UnsupportedViewFormatUsage {
+                    binding,
+                    usage,
+                    view_format: view.desc.format,
+                })
+            }
+        };
         match decl.ty {
             wgt::BindingType::Texture {
                 sample_type,
@@ -1643,6 +1971,7 @@ impl<A: HalApi> Device<A> {
                         view_dimension: view.desc.dimension,
                     });
                 }
+                check_view_format_usage(wgt::TextureUsages::TEXTURE_BINDING)?;
                 Ok((
                     wgt::TextureUsages::TEXTURE_BINDING,
                     view.sampled_internal_use,
@@ -1700,6 +2029,7 @@ impl<A: HalApi> Device<A> {
                         hal::TextureUses::STORAGE_WRITE | hal::TextureUses::STORAGE_READ
                     }
                 };
+                check_view_format_usage(wgt::TextureUsages::STORAGE_BINDING)?;
                 Ok((wgt::TextureUsages::STORAGE_BINDING, internal_use))
             }
             _ => Err(Error::WrongBindingType {
@@ -1891,11 +2221,12 @@ impl<A: HalApi> Device<A> {
             ArrayVec::<binding_model::BindEntryMap, { hal::MAX_BIND_GROUPS }>::new();
 
         let io = validation::StageIo::default();
-        let (shader_module_guard, _) = hub.shader_modules.read(&mut token);
+        let (shader_module_guard, mut token) = hub.shader_modules.read(&mut token);
+        let (pipeline_cache_guard, _) = hub.pipeline_caches.read(&mut token);
 
         let shader_module = shader_module_guard
             .get(desc.stage.module)
-            .map_err(|_| validation::StageError::InvalidModule)?;
+            .map_err(|_| pipeline::ShaderError::from(validation::StageError::InvalidModule))?;
 
         {
             let flag = wgt::ShaderStages::COMPUTE;
@@ -1914,13 +2245,17 @@ impl<A: HalApi> Device<A> {
                 }
             };
             if let Some(ref interface) = shader_module.interface {
-                let _ = interface.check_stage(
-                    provided_layouts.as_ref().map(|p| p.as_slice()),
-                    &mut derived_group_layouts,
-                    &desc.stage.entry_point,
-                    flag,
-                    io,
-                )?;
+                let _ = interface
+                    .check_stage(
+                        provided_layouts.as_ref().map(|p| p.as_slice()),
+                        &mut derived_group_layouts,
+                        &desc.stage.entry_point,
+                        flag,
+                        io,
+                    )
+                    .map_err(|error| {
+                        shader_module.stage_error(error, flag, &desc.stage.entry_point)
+                    })?;
             }
         }
 
@@ -1937,6 +2272,11 @@ impl<A: HalApi> Device<A> {
         let layout = pipeline_layout_guard
             .get(pipeline_layout_id)
             .map_err(|_| pipeline::CreateComputePipelineError::InvalidLayout)?;
+        let cache = desc
+            .cache
+            .map(|id| pipeline_cache_guard.get(id).map(|cache| &cache.raw))
+            .transpose()
+            .map_err(|_| pipeline::CreateComputePipelineError::InvalidCache)?;
 
         let pipeline_desc = hal::ComputePipelineDescriptor {
             label: desc.label.borrow_option(),
@@ -1945,6 +2285,7 @@ impl<A: HalApi> Device<A> {
                 entry_point: desc.stage.entry_point.as_ref(),
                 module: &shader_module.raw,
             },
+            cache,
         };
 
         let raw =
@@ -2185,7 +2526,8 @@ impl<A: HalApi> Device<A> {
             sc
         };
 
-        let (shader_module_guard, _) = hub.shader_modules.read(&mut token);
+        let (shader_module_guard, mut token) = hub.shader_modules.read(&mut token);
+        let (pipeline_cache_guard, _) = hub.pipeline_caches.read(&mut token);
 
         let vertex_stage = {
             let stage = &desc.vertex.stage;
@@ -2194,7 +2536,7 @@ impl<A: HalApi> Device<A> {
             let shader_module = shader_module_guard.get(stage.module).map_err(|_| {
                 pipeline::CreateRenderPipelineError::Stage {
                     stage: flag,
-                    error: validation::StageError::InvalidModule,
+                    error: validation::StageError::InvalidModule.into(),
                 }
             })?;
 
@@ -2222,7 +2564,7 @@ impl<A: HalApi> Device<A> {
                     )
                     .map_err(|error| pipeline::CreateRenderPipelineError::Stage {
                         stage: flag,
-                        error,
+                        error: shader_module.stage_error(error, flag, &stage.entry_point),
                     })?;
                 validated_stages |= flag;
             }
@@ -2242,7 +2584,7 @@ impl<A: HalApi> Device<A> {
                         .get(fragment.stage.module)
                         .map_err(|_| pipeline::CreateRenderPipelineError::Stage {
                             stage: flag,
-                            error: validation::StageError::InvalidModule,
+                            error: validation::StageError::InvalidModule.into(),
                         })?;
 
                 let provided_layouts = match desc.layout {
@@ -2267,7 +2609,11 @@ impl<A: HalApi> Device<A> {
                             )
                             .map_err(|error| pipeline::CreateRenderPipelineError::Stage {
                                 stage: flag,
-                                error,
+                                error: shader_module.stage_error(
+                                    error,
+                                    flag,
+                                    &fragment.stage.entry_point,
+                                ),
                             })?;
                         validated_stages |= flag;
                     }
@@ -2329,6 +2675,11 @@ impl<A: HalApi> Device<A> {
         let layout = pipeline_layout_guard
             .get(pipeline_layout_id)
             .map_err(|_| pipeline::CreateRenderPipelineError::InvalidLayout)?;
+        let cache = desc
+            .cache
+            .map(|id| pipeline_cache_guard.get(id).map(|cache| &cache.raw))
+            .transpose()
+            .map_err(|_| pipeline::CreateRenderPipelineError::InvalidCache)?;
 
         let pipeline_desc = hal::RenderPipelineDescriptor {
             label: desc.label.borrow_option(),
@@ -2340,6 +2691,7 @@ impl<A: HalApi> Device<A> {
             multisample: desc.multisample,
             fragment_stage,
             color_targets,
+            cache,
         };
         let raw =
             unsafe { self.raw.create_render_pipeline(&pipeline_desc) }.map_err(
@@ -2439,9 +2791,12 @@ impl<A: HalApi> Device<A> {
                     .wait(&self.fence, submission_index, !0)
                     .map_err(DeviceError::from)?
             };
-            let closures = self
-                .lock_life(token)
-                .triage_submissions(submission_index, &self.command_allocator);
+            let closures = self.lock_life(token).triage_submissions(
+                submission_index,
+                &self.command_allocator,
+                &self.staging_pool,
+                &self.raw,
+            );
             assert!(
                 closures.is_empty(),
                 "wait_for_submit is not expected to work with closures"
@@ -2518,7 +2873,12 @@ impl<A: hal::Api> Device<A> {
         if let Err(error) = unsafe { self.raw.wait(&self.fence, current_index, CLEANUP_WAIT_MS) } {
             log::error!("failed to wait for the device: {:?}", error);
         }
-        let _ = life_tracker.triage_submissions(current_index, &self.command_allocator);
+        let _ = life_tracker.triage_submissions(
+            current_index,
+            &self.command_allocator,
+            &self.staging_pool,
+            &self.raw,
+        );
         life_tracker.cleanup(&self.raw);
         #[cfg(feature = "trace")]
         {
@@ -2528,6 +2888,10 @@ impl<A: hal::Api> Device<A> {
 
     pub(crate) fn dispose(self) {
         self.pending_writes.dispose(&self.raw);
+        unsafe {
+            self.raw.destroy_buffer(self.zero_buffer);
+        }
+        self.staging_pool.into_inner().dispose(&self.raw);
         self.command_allocator.into_inner().dispose(&self.raw);
         unsafe {
             self.raw.destroy_fence(self.fence);
@@ -2604,6 +2968,22 @@ impl<G: GlobalIdentityHandlerFactory> ImplicitPipelineIds<'_, G> {
     }
 }
 
+/// Estimates the memory used by a texture from its descriptor, ignoring
+/// any alignment and padding the backend may add.
+fn estimate_texture_size(desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>) -> u64 {
+    let format_desc = desc.format.describe();
+    let (block_width, block_height) = format_desc.block_dimensions;
+    let layer_size = (0..desc.mip_level_count)
+        .filter_map(|level| desc.mip_level_size(level))
+        .map(|size| {
+            let width_blocks = (size.width + block_width as u32 - 1) / block_width as u32;
+            let height_blocks = (size.height + block_height as u32 - 1) / block_height as u32;
+            width_blocks as u64 * height_blocks as u64 * size.depth_or_array_layers as u64
+        })
+        .sum::<u64>();
+    layer_size * format_desc.block_size as u64 * desc.sample_count as u64
+}
+
 impl<G: GlobalIdentityHandlerFactory> Global<G> {
     pub fn adapter_is_surface_supported<A: HalApi>(
         &self,
@@ -2679,6 +3059,49 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         Ok(device.downlevel.clone())
     }
 
+    pub fn device_memory_report<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+    ) -> Result<wgt::MemoryReport, InvalidDevice> {
+        use crate::hub::Resource as _;
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+        let (buffer_guard, mut token) = hub.buffers.read(&mut token);
+        let (texture_guard, _) = hub.textures.read(&mut token);
+
+        let mut report = wgt::MemoryReport {
+            staging: device.staging_pool.lock().usage(),
+            allocator: device.raw.allocator_report(),
+            ..Default::default()
+        };
+        for (_, buffer) in buffer_guard.iter(device_id.backend()) {
+            if buffer.device_id.value.0 != device_id || buffer.raw.is_none() {
+                continue;
+            }
+            report
+                .buffers
+                .add(buffer.label(), buffer.usage, buffer.size);
+        }
+        for (_, texture) in texture_guard.iter(device_id.backend()) {
+            if texture.device_id.value.0 != device_id {
+                continue;
+            }
+            // Surface textures are owned by the swapchain.
+            if let resource::TextureInner::Native { raw: Some(_) } = texture.inner {
+                report.textures.add(
+                    texture.label(),
+                    texture.desc.usage,
+                    estimate_texture_size(&texture.desc),
+                );
+            }
+        }
+
+        Ok(report)
+    }
+
     pub fn device_create_buffer<A: HalApi>(
         &self,
         device_id: id::DeviceId,
@@ -2694,6 +3117,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3038,6 +3462,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3094,6 +3519,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3106,12 +3532,24 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     .add(trace::Action::CreateTexture(fid.id(), desc.clone()));
             }
 
+            if let Err(error) = device.validate_view_formats(desc) {
+                break error;
+            }
+
             let adapter = &adapter_guard[device.adapter_id.value];
-            let texture =
-                match device.create_texture_from_hal(hal_texture, device_id, adapter, desc) {
-                    Ok(texture) => texture,
-                    Err(error) => break error,
-                };
+            // The contents of the raw texture are not known, so it's considered initialized.
+            let hal_usage = conv::map_texture_usage(desc.usage, desc.format.into());
+            let texture = match device.create_texture_from_hal(
+                hal_texture,
+                hal_usage,
+                resource::TextureClearMode::None,
+                device_id,
+                adapter,
+                desc,
+            ) {
+                Ok(texture) => texture,
+                Err(error) => break error,
+            };
             let num_levels = texture.full_range.levels.end;
             let num_layers = texture.full_range.layers.end;
             let ref_count = texture.life_guard.add_ref();
@@ -3242,6 +3680,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut token = Token::root();
         let fid = hub.texture_views.prepare(id_in);
 
+        let (adapter_guard, mut token) = hub.adapters.read(&mut token);
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let (texture_guard, mut token) = hub.textures.read(&mut token);
         let error = loop {
@@ -3250,6 +3689,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Err(_) => break resource::CreateTextureViewError::InvalidTexture,
             };
             let device = &device_guard[texture.device_id.value];
+            let adapter = &adapter_guard[device.adapter_id.value];
             #[cfg(feature = "trace")]
             if let Some(ref trace) = device.trace {
                 trace.lock().add(trace::Action::CreateTextureView {
@@ -3259,7 +3699,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 });
             }
 
-            let view = match device.create_texture_view(texture, texture_id, desc) {
+            let view = match device.create_texture_view(adapter, texture, texture_id, desc) {
                 Ok(view) => view,
                 Err(e) => break e,
             };
@@ -3348,6 +3788,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3429,6 +3870,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let error = 'outer: loop {
             let (device_guard, mut token) = hub.devices.read(&mut token);
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3524,6 +3966,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3602,6 +4045,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3696,6 +4140,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3756,6 +4201,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -3824,6 +4270,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost,
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid,
             };
@@ -3999,6 +4446,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -4084,6 +4532,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -4217,6 +4666,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (device_guard, mut token) = hub.devices.read(&mut token);
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -4327,6 +4777,115 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .push(layout_id);
     }
 
+    #[allow(unused_unsafe)]
+    /// # Safety
+    ///
+    /// The `data` of the descriptor must have been returned by `pipeline_cache_get_data`.
+    /// Its header is validated, but the contents are passed to the driver as-is.
+    pub unsafe fn device_create_pipeline_cache<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        desc: &pipeline::PipelineCacheDescriptor,
+        id_in: Input<G, id::PipelineCacheId>,
+    ) -> (
+        id::PipelineCacheId,
+        Option<pipeline::CreatePipelineCacheError>,
+    ) {
+        profiling::scope!("create_pipeline_cache", "Device");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let fid = hub.pipeline_caches.prepare(id_in);
+
+        let (adapter_guard, mut token) = hub.adapters.read(&mut token);
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let error = loop {
+            let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
+                Ok(device) => device,
+                Err(_) => break DeviceError::Invalid.into(),
+            };
+            #[cfg(feature = "trace")]
+            if let Some(ref trace) = device.trace {
+                // The cache contents only make sense on this machine, so they aren't recorded.
+                trace.lock().add(trace::Action::CreatePipelineCache {
+                    id: fid.id(),
+                    desc: pipeline::PipelineCacheDescriptor {
+                        label: desc.label.clone(),
+                        data: None,
+                        fallback: desc.fallback,
+                    },
+                });
+            }
+
+            let adapter = &adapter_guard[device.adapter_id.value];
+            let cache = match device.create_pipeline_cache(device_id, &adapter.raw.info, desc) {
+                Ok(cache) => cache,
+                Err(e) => break e,
+            };
+            let id = fid.assign(cache, &mut token);
+            return (id.0, None);
+        };
+
+        let id = fid.assign_error(desc.label.borrow_or_default(), &mut token);
+        (id, Some(error))
+    }
+
+    /// Returns the contents of the pipeline cache, prefixed with a header
+    /// identifying the adapter and driver.
+    ///
+    /// Returns `None` if the backend doesn't support retrieving the data.
+    pub fn pipeline_cache_get_data<A: HalApi>(
+        &self,
+        pipeline_cache_id: id::PipelineCacheId,
+    ) -> Option<Vec<u8>> {
+        use crate::pipeline_cache;
+        profiling::scope!("get_data", "PipelineCache");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (adapter_guard, mut token) = hub.adapters.read(&mut token);
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (cache_guard, _) = hub.pipeline_caches.read(&mut token);
+        let cache = cache_guard.get(pipeline_cache_id).ok()?;
+        let device = &device_guard[cache.device_id.value];
+        let adapter = &adapter_guard[device.adapter_id.value];
+
+        let key = device.raw.pipeline_cache_validation_key()?;
+        let data = unsafe { device.raw.pipeline_cache_get_data(&cache.raw) }?;
+        Some(pipeline_cache::add_cache_header(
+            &data,
+            &pipeline_cache::CacheKey::new(&adapter.raw.info, key),
+        ))
+    }
+
+    pub fn pipeline_cache_label<A: HalApi>(&self, id: id::PipelineCacheId) -> String {
+        A::hub(self).pipeline_caches.label_for_resource(id)
+    }
+
+    pub fn pipeline_cache_drop<A: HalApi>(&self, pipeline_cache_id: id::PipelineCacheId) {
+        profiling::scope!("drop", "PipelineCache");
+
+        let hub = A::hub(self);
+        let mut token = Token::root();
+        let (device_guard, mut token) = hub.devices.read(&mut token);
+        let (cache, _) = hub
+            .pipeline_caches
+            .unregister(pipeline_cache_id, &mut token);
+        if let Some(cache) = cache {
+            let device = &device_guard[cache.device_id.value];
+            #[cfg(feature = "trace")]
+            if let Some(ref trace) = device.trace {
+                trace
+                    .lock()
+                    .add(trace::Action::DestroyPipelineCache(pipeline_cache_id));
+            }
+            unsafe {
+                device.raw.destroy_pipeline_cache(cache.raw);
+            }
+        }
+    }
+
     pub fn surface_configure<A: HalApi>(
         &self,
         surface_id: id::SurfaceId,
@@ -4387,6 +4946,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
         let error = loop {
             let device = match device_guard.get(device_id) {
+                Ok(device) if device.is_lost() => break DeviceError::Lost.into(),
                 Ok(device) => device,
                 Err(_) => break DeviceError::Invalid.into(),
             };
@@ -4425,11 +4985,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     depth_or_array_layers: 1,
                 },
                 usage: conv::map_texture_usage(config.usage, hal::FormatAspects::COLOR),
+                view_formats: config.view_formats.clone(),
             };
 
             if let Err(error) = validate_surface_configuraiton(&mut hal_config, &caps) {
                 break error;
             }
+            if !config.view_formats.is_empty() {
+                if let Err(error) =
+                    device.require_downlevel_flags(wgt::DownlevelFlags::VIEW_FORMATS)
+                {
+                    break error.into();
+                }
+            }
+            if let Some(&view_format) = config
+                .view_formats
+                .iter()
+                .find(|&&view_format| !config.format.is_view_compatible(view_format))
+            {
+                break E::InvalidViewFormat {
+                    surface: config.format,
+                    view: view_format,
+                };
+            }
 
             match unsafe {
                 A::get_surface_mut(surface)
@@ -4506,9 +5084,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 .map_err(|_| DeviceError::Invalid)?
                 .maintain(hub, force_wait, &mut token)?
         };
-        unsafe {
-            closures.fire();
-        }
+        closures.fire();
         Ok(())
     }
 
@@ -4548,11 +5124,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         {
             self.poll_devices::<hal::api::Dx11>(force_wait, &mut closures)?;
         }
-
-        unsafe {
-            closures.fire();
+        #[cfg(cpu)]
+        {
+            self.poll_devices::<hal::api::Cpu>(force_wait, &mut closures)?;
         }
 
+        closures.fire();
+
         Ok(())
     }
 
@@ -4578,6 +5156,51 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         }
     }
 
+    /// Sets the closure called once the device is lost, replacing the previous one.
+    ///
+    /// The closure is called right away if the device is already lost.
+    pub fn device_set_device_lost_closure<A: HalApi>(
+        &self,
+        device_id: id::DeviceId,
+        closure: DeviceLostClosure,
+    ) -> Result<(), InvalidDevice> {
+        let invocation = {
+            let hub = A::hub(self);
+            let mut token = Token::root();
+            let (device_guard, _) = hub.devices.read(&mut token);
+            let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+            let mut state = device.lost.lock();
+            state.closure = Some(closure);
+            state.take_invocation()
+        };
+        if let Some(invocation) = invocation {
+            invocation.call();
+        }
+        Ok(())
+    }
+
+    /// Loses the device with `DeviceLostReason::Destroyed`.
+    ///
+    /// Operations on the device and its resources fail from now on, but the work already
+    /// submitted runs to completion, and the resources are freed when dropped.
+    pub fn device_destroy<A: HalApi>(&self, device_id: id::DeviceId) -> Result<(), InvalidDevice> {
+        profiling::scope!("destroy", "Device");
+
+        let invocation = {
+            let hub = A::hub(self);
+            let mut token = Token::root();
+            let (device_guard, _) = hub.devices.read(&mut token);
+            let device = device_guard.get(device_id).map_err(|_| InvalidDevice)?;
+            device.lose(wgt::DeviceLostReason::Destroyed, "device destroyed");
+            let invocation = device.lost.lock().take_invocation();
+            invocation
+        };
+        if let Some(invocation) = invocation {
+            invocation.call();
+        }
+        Ok(())
+    }
+
     pub fn device_drop<A: HalApi>(&self, device_id: id::DeviceId) {
         profiling::scope!("drop", "Device");
 
@@ -4605,6 +5228,20 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         range: Range<BufferAddress>,
         op: resource::BufferMapOperation,
     ) -> Result<(), resource::BufferAccessError> {
+        // The callback is told about errors too, once nothing is locked.
+        if let Err((op, err)) = self.buffer_map_async_inner::<A>(buffer_id, range, op) {
+            op.callback.call(Err(err.clone()));
+            return Err(err);
+        }
+        Ok(())
+    }
+
+    fn buffer_map_async_inner<A: HalApi>(
+        &self,
+        buffer_id: id::BufferId,
+        range: Range<BufferAddress>,
+        op: resource::BufferMapOperation,
+    ) -> Result<(), (resource::BufferMapOperation, resource::BufferAccessError)> {
         profiling::scope!("map_async", "Buffer");
 
         let hub = A::hub(self);
@@ -4616,23 +5253,28 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         };
 
         if range.start % wgt::MAP_ALIGNMENT != 0 || range.end % wgt::COPY_BUFFER_ALIGNMENT != 0 {
-            return Err(resource::BufferAccessError::UnalignedRange);
+            return Err((op, resource::BufferAccessError::UnalignedRange));
         }
 
         let (device_id, ref_count) = {
             let (mut buffer_guard, _) = hub.buffers.write(&mut token);
-            let buffer = buffer_guard
-                .get_mut(buffer_id)
-                .map_err(|_| resource::BufferAccessError::Invalid)?;
+            let buffer = match buffer_guard.get_mut(buffer_id) {
+                Ok(buffer) => buffer,
+                Err(_) => return Err((op, resource::BufferAccessError::Invalid)),
+            };
+            if device_guard[buffer.device_id.value].is_lost() {
+                return Err((op, DeviceError::Lost.into()));
+            }
+            if let Err(e) = check_buffer_usage(buffer.usage, pub_usage) {
+                return Err((op, e.into()));
+            }
 
-            check_buffer_usage(buffer.usage, pub_usage)?;
             buffer.map_state = match buffer.map_state {
                 resource::BufferMapState::Init { .. } | resource::BufferMapState::Active { .. } => {
-                    return Err(resource::BufferAccessError::AlreadyMapped);
+                    return Err((op, resource::BufferAccessError::AlreadyMapped));
                 }
                 resource::BufferMapState::Waiting(_) => {
-                    op.call_error();
-                    return Ok(());
+                    return Err((op, resource::BufferAccessError::MapAlreadyPending));
                 }
                 resource::BufferMapState::Idle => {
                     resource::BufferMapState::Waiting(resource::BufferPendingMapping {
@@ -4806,7 +5448,10 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 return Err(resource::BufferAccessError::NotMapped);
             }
             resource::BufferMapState::Waiting(pending) => {
-                return Ok(Some((pending.op, resource::BufferMapAsyncStatus::Aborted)));
+                return Ok(Some((
+                    pending.op,
+                    Err(resource::BufferAccessError::MapAborted),
+                )));
             }
             resource::BufferMapState::Active { ptr, range, host } => {
                 if host == HostMap::Write {
@@ -4843,10 +5488,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), resource::BufferAccessError> {
         //Note: outside inner function so no locks are held when calling the callback
         let closure = self.buffer_unmap_inner::<A>(buffer_id)?;
-        if let Some((operation, status)) = closure {
-            unsafe {
-                (operation.callback)(status, operation.user_data);
-            }
+        if let Some((operation, result)) = closure {
+            operation.callback.call(result);
         }
         Ok(())
     }
@@ -2,14 +2,16 @@
 use crate::device::trace::Action;
 use crate::{
     command::{
+        clear_texture_via_buffer_copies, clear_texture_via_render_passes, copy_dst_init_kind,
         extract_texture_selector, validate_linear_texture_data, validate_texture_copy_range,
-        CommandBuffer, CopySide, ImageCopyTexture, TransferError,
+        CommandBuffer, CopySide, ImageCopyTexture, TextureInitError, TransferError,
     },
     conv,
-    device::{DeviceError, WaitIdleError},
+    device::{DeviceError, UserClosures, WaitIdleError},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Token},
     id,
-    resource::{BufferAccessError, BufferMapState, TextureInner},
+    init_tracker::MemoryInitKind,
+    resource::{BufferAccessError, BufferMapState, TextureClearMode, TextureInner},
     track, FastHashSet,
 };
 
@@ -25,43 +27,230 @@ use thiserror::Error;
 /// without a concrete moment of when it can be cleared.
 const WRITE_COMMAND_BUFFERS_PER_POOL: usize = 64;
 
+/// Size of the chunks of the staging pool. Writes larger than that get a dedicated chunk.
+const STAGING_CHUNK_SIZE: wgt::BufferAddress = 1 << 20;
+
+/// Number of free chunks the staging pool keeps for reuse, the others are destroyed,
+/// along with the dedicated chunks of large writes.
+const MAX_FREE_STAGING_CHUNKS: usize = 16;
+
 pub type OnSubmittedWorkDoneCallback = unsafe extern "C" fn(user_data: *mut u8);
+
+/// C-style form of the closure called when submitted work is done, for FFI users.
 #[repr(C)]
 #[derive(Clone, Copy, Debug)]
-pub struct SubmittedWorkDoneClosure {
+pub struct SubmittedWorkDoneClosureC {
     pub callback: OnSubmittedWorkDoneCallback,
     pub user_data: *mut u8,
 }
 
-unsafe impl Send for SubmittedWorkDoneClosure {}
-unsafe impl Sync for SubmittedWorkDoneClosure {}
+unsafe impl Send for SubmittedWorkDoneClosureC {}
+unsafe impl Sync for SubmittedWorkDoneClosureC {}
+
+/// Closure called when the work submitted so far is done.
+pub struct SubmittedWorkDoneClosure {
+    // Wrapped so that only the creation of the C form is unsafe, and calling is not.
+    inner: SubmittedWorkDoneClosureInner,
+}
+
+enum SubmittedWorkDoneClosureInner {
+    Rust {
+        callback: Box<dyn FnOnce() + Send + 'static>,
+    },
+    C {
+        inner: SubmittedWorkDoneClosureC,
+    },
+}
+
+impl SubmittedWorkDoneClosure {
+    pub fn from_rust(callback: Box<dyn FnOnce() + Send + 'static>) -> Self {
+        Self {
+            inner: SubmittedWorkDoneClosureInner::Rust { callback },
+        }
+    }
+
+    /// # Safety
+    ///
+    /// - The callback pointer must be valid to call with the provided user_data pointer.
+    /// - Both pointers must point to `'static` data, as the callback may happen at an
+    ///   unspecified time.
+    pub unsafe fn from_c(inner: SubmittedWorkDoneClosureC) -> Self {
+        Self {
+            inner: SubmittedWorkDoneClosureInner::C { inner },
+        }
+    }
+
+    pub(crate) fn call(self) {
+        match self.inner {
+            SubmittedWorkDoneClosureInner::Rust { callback } => callback(),
+            // Safety: the contract of the call to from_c says that this call is sound.
+            SubmittedWorkDoneClosureInner::C { inner } => unsafe {
+                (inner.callback)(inner.user_data)
+            },
+        }
+    }
+}
 
-struct StagingData<A: hal::Api> {
+impl std::fmt::Debug for SubmittedWorkDoneClosure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.inner {
+            SubmittedWorkDoneClosureInner::Rust { .. } => {
+                f.write_str("SubmittedWorkDoneClosure::Rust")
+            }
+            SubmittedWorkDoneClosureInner::C { ref inner } => {
+                write!(f, "SubmittedWorkDoneClosure::C({:?})", inner)
+            }
+        }
+    }
+}
+
+/// Buffer of the staging pool, which writes are sub-allocated from.
+#[derive(Debug)]
+pub(super) struct StagingChunk<A: hal::Api> {
     buffer: A::Buffer,
+    size: wgt::BufferAddress,
+    /// End of the last allocation.
+    cursor: wgt::BufferAddress,
 }
 
-impl<A: hal::Api> StagingData<A> {
+/// Range of a staging chunk allocated for a write.
+#[derive(Clone, Copy, Debug)]
+struct StagingData {
+    /// Index of the chunk in the active chunks of the pool.
+    chunk: usize,
+    offset: wgt::BufferAddress,
+    size: wgt::BufferAddress,
+}
+
+/// Pool of staging buffers for `write_buffer` and `write_texture`.
+///
+/// Writes are sub-allocated from the active chunks, which are handed over to the
+/// lifetime tracker on submission, and returned to the pool once that submission is done.
+#[derive(Debug)]
+pub(super) struct StagingPool<A: hal::Api> {
+    /// Chunks used by the pending writes.
+    active: Vec<StagingChunk<A>>,
+    /// Chunks not used by the GPU, ready to be reused.
+    free: Vec<StagingChunk<A>>,
+    /// All the chunks of the pool, including those in flight.
+    usage: wgt::MemoryUsage,
+}
+
+impl<A: hal::Api> StagingPool<A> {
+    pub fn new() -> Self {
+        Self {
+            active: Vec::new(),
+            free: Vec::new(),
+            usage: wgt::MemoryUsage::default(),
+        }
+    }
+
+    pub fn usage(&self) -> wgt::MemoryUsage {
+        self.usage
+    }
+
+    fn allocate(
+        &mut self,
+        device: &A::Device,
+        size: wgt::BufferAddress,
+        alignment: wgt::BufferAddress,
+    ) -> Result<StagingData, DeviceError> {
+        for (index, chunk) in self.active.iter_mut().enumerate() {
+            let offset = align_to_address(chunk.cursor, alignment);
+            if offset + size <= chunk.size {
+                chunk.cursor = offset + size;
+                return Ok(StagingData {
+                    chunk: index,
+                    offset,
+                    size,
+                });
+            }
+        }
+
+        let mut chunk = match self.free.iter().position(|chunk| chunk.size >= size) {
+            Some(index) => self.free.swap_remove(index),
+            None => {
+                profiling::scope!("create staging chunk");
+                let chunk_size = size.max(STAGING_CHUNK_SIZE);
+                let desc = hal::BufferDescriptor {
+                    label: Some("_Staging"),
+                    size: chunk_size,
+                    usage: hal::BufferUses::MAP_WRITE | hal::BufferUses::COPY_SRC,
+                    memory_flags: hal::MemoryFlags::empty(),
+                };
+                let buffer = unsafe { device.create_buffer(&desc)? };
+                self.usage.add(chunk_size);
+                StagingChunk {
+                    buffer,
+                    size: chunk_size,
+                    cursor: 0,
+                }
+            }
+        };
+        chunk.cursor = size;
+        self.active.push(chunk);
+        Ok(StagingData {
+            chunk: self.active.len() - 1,
+            offset: 0,
+            size,
+        })
+    }
+
+    fn buffer(&self, stage: StagingData) -> &A::Buffer {
+        &self.active[stage.chunk].buffer
+    }
+
     unsafe fn write(
         &self,
         device: &A::Device,
-        offset: wgt::BufferAddress,
+        stage: StagingData,
         data: &[u8],
     ) -> Result<(), hal::DeviceError> {
-        let mapping = device.map_buffer(&self.buffer, offset..offset + data.len() as u64)?;
+        let buffer = self.buffer(stage);
+        let range = stage.offset..stage.offset + data.len() as u64;
+        let mapping = device.map_buffer(buffer, range.clone())?;
         ptr::copy_nonoverlapping(data.as_ptr(), mapping.ptr.as_ptr(), data.len());
         if !mapping.is_coherent {
-            device
-                .flush_mapped_ranges(&self.buffer, iter::once(offset..offset + data.len() as u64));
+            device.flush_mapped_ranges(buffer, iter::once(range));
         }
-        device.unmap_buffer(&self.buffer)?;
+        device.unmap_buffer(buffer)?;
         Ok(())
     }
+
+    /// Takes the chunks used by the pending writes, on submission.
+    pub fn take_active(&mut self) -> Vec<StagingChunk<A>> {
+        mem::take(&mut self.active)
+    }
+
+    /// Returns chunks whose submission is done to the pool.
+    pub fn release(&mut self, chunks: Vec<StagingChunk<A>>, device: &A::Device) {
+        for mut chunk in chunks {
+            if chunk.size <= STAGING_CHUNK_SIZE && self.free.len() < MAX_FREE_STAGING_CHUNKS {
+                chunk.cursor = 0;
+                self.free.push(chunk);
+            } else {
+                self.usage.remove(chunk.size);
+                unsafe {
+                    device.destroy_buffer(chunk.buffer);
+                }
+            }
+        }
+    }
+
+    pub fn dispose(self, device: &A::Device) {
+        for chunk in self.active.into_iter().chain(self.free) {
+            unsafe {
+                device.destroy_buffer(chunk.buffer);
+            }
+        }
+    }
 }
 
 #[derive(Debug)]
 pub enum TempResource<A: hal::Api> {
     Buffer(A::Buffer),
     Texture(A::Texture),
+    TextureView(A::TextureView),
 }
 
 /// A queue execution for a particular command encoder.
@@ -117,6 +306,9 @@ impl<A: hal::Api> PendingWrites<A> {
                 TempResource::Texture(texture) => unsafe {
                     device.destroy_texture(texture);
                 },
+                TempResource::TextureView(view) => unsafe {
+                    device.destroy_texture_view(view);
+                },
             }
         }
     }
@@ -125,10 +317,6 @@ impl<A: hal::Api> PendingWrites<A> {
         self.temp_resources.push(resource);
     }
 
-    fn consume(&mut self, stage: StagingData<A>) {
-        self.temp_resources.push(TempResource::Buffer(stage.buffer));
-    }
-
     #[must_use]
     fn pre_submit(&mut self) -> Option<&A::CommandBuffer> {
         self.dst_buffers.clear();
@@ -186,20 +374,6 @@ impl<A: hal::Api> PendingWrites<A> {
     }
 }
 
-impl<A: hal::Api> super::Device<A> {
-    fn prepare_stage(&mut self, size: wgt::BufferAddress) -> Result<StagingData<A>, DeviceError> {
-        profiling::scope!("prepare_stage");
-        let stage_desc = hal::BufferDescriptor {
-            label: Some("_Staging"),
-            size,
-            usage: hal::BufferUses::MAP_WRITE | hal::BufferUses::COPY_SRC,
-            memory_flags: hal::MemoryFlags::TRANSIENT,
-        };
-        let buffer = unsafe { self.raw.create_buffer(&stage_desc)? };
-        Ok(StagingData { buffer })
-    }
-}
-
 #[derive(Clone, Debug, Error)]
 #[error("queue is invalid")]
 pub struct InvalidQueue;
@@ -248,6 +422,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let device = device_guard
             .get_mut(queue_id)
             .map_err(|_| DeviceError::Invalid)?;
+        if device.is_lost() {
+            return Err(DeviceError::Lost.into());
+        }
         let (buffer_guard, _) = hub.buffers.read(&mut token);
 
         #[cfg(feature = "trace")]
@@ -268,23 +445,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Ok(());
         }
 
-        let stage = device.prepare_stage(data_size)?;
-        unsafe { stage.write(&device.raw, 0, data) }.map_err(DeviceError::from)?;
-
-        let mut trackers = device.trackers.lock();
-        let (dst, transition) = trackers
-            .buffers
-            .use_replace(&*buffer_guard, buffer_id, (), hal::BufferUses::COPY_DST)
-            .map_err(TransferError::InvalidBuffer)?;
-        let dst_raw = dst
-            .raw
-            .as_ref()
-            .ok_or(TransferError::InvalidBuffer(buffer_id))?;
+        // Validate before allocating, so that invalid writes don't take staging memory.
+        let dst = buffer_guard
+            .get(buffer_id)
+            .map_err(|_| TransferError::InvalidBuffer(buffer_id))?;
+        if dst.raw.is_none() {
+            return Err(TransferError::InvalidBuffer(buffer_id).into());
+        }
         if !dst.usage.contains(wgt::BufferUsages::COPY_DST) {
             return Err(TransferError::MissingCopyDstUsageFlag(Some(buffer_id), None).into());
         }
-        dst.life_guard.use_at(device.active_submission_index + 1);
-
         if data_size % wgt::COPY_BUFFER_ALIGNMENT != 0 {
             return Err(TransferError::UnalignedCopySize(data_size).into());
         }
@@ -301,23 +471,38 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             .into());
         }
 
+        let staging_pool = device.staging_pool.get_mut();
+        let stage = staging_pool.allocate(&device.raw, data_size, wgt::COPY_BUFFER_ALIGNMENT)?;
+        unsafe { staging_pool.write(&device.raw, stage, data) }.map_err(DeviceError::from)?;
+
+        let mut trackers = device.trackers.lock();
+        let (dst, transition) = trackers
+            .buffers
+            .use_replace(&*buffer_guard, buffer_id, (), hal::BufferUses::COPY_DST)
+            .map_err(TransferError::InvalidBuffer)?;
+        let dst_raw = dst
+            .raw
+            .as_ref()
+            .ok_or(TransferError::InvalidBuffer(buffer_id))?;
+        dst.life_guard.use_at(device.active_submission_index + 1);
+
         let region = wgt::BufferSize::new(data.len() as u64).map(|size| hal::BufferCopy {
-            src_offset: 0,
+            src_offset: stage.offset,
             dst_offset: buffer_offset,
             size,
         });
+        let stage_buffer = device.staging_pool.get_mut().buffer(stage);
         let barriers = iter::once(hal::BufferBarrier {
-            buffer: &stage.buffer,
+            buffer: stage_buffer,
             usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
         })
         .chain(transition.map(|pending| pending.into_hal(dst)));
         let encoder = device.pending_writes.activate();
         unsafe {
             encoder.transition_buffers(barriers);
-            encoder.copy_buffer_to_buffer(&stage.buffer, dst_raw, region.into_iter());
+            encoder.copy_buffer_to_buffer(stage_buffer, dst_raw, region.into_iter());
         }
 
-        device.pending_writes.consume(stage);
         device.pending_writes.dst_buffers.insert(buffer_id);
 
         // Ensure the overwritten bytes are marked as initialized so they don't need to be nulled prior to mapping or binding.
@@ -349,6 +534,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let device = device_guard
             .get_mut(queue_id)
             .map_err(|_| DeviceError::Invalid)?;
+        if device.is_lost() {
+            return Err(DeviceError::Lost.into());
+        }
 
         #[cfg(feature = "trace")]
         if let Some(ref trace) = device.trace {
@@ -367,7 +555,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             return Ok(());
         }
 
-        let (texture_guard, _) = hub.textures.read(&mut token);
+        let (mut texture_guard, _) = hub.textures.write(&mut token);
         let (selector, dst_base, texture_format) =
             extract_texture_selector(destination, size, &*texture_guard)?;
         let format_desc = texture_format.describe();
@@ -381,7 +569,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             false,
         )?;
 
-        if !conv::is_valid_copy_dst_texture_format(texture_format) {
+        if !conv::is_valid_copy_dst_texture_format(texture_format, destination.aspect) {
             return Err(TransferError::CopyToForbiddenTextureFormat(texture_format).into());
         }
         let (block_width, block_height) = format_desc.block_dimensions;
@@ -408,31 +596,29 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let block_rows_in_copy =
             (size.depth_or_array_layers - 1) * block_rows_per_image + height_blocks;
         let stage_size = stage_bytes_per_row as u64 * block_rows_in_copy as u64;
-        let stage = device.prepare_stage(stage_size)?;
-
-        let mut trackers = device.trackers.lock();
-        let (dst, transition) = trackers
-            .textures
-            .use_replace(
-                &*texture_guard,
-                destination.texture,
-                selector,
-                hal::TextureUses::COPY_DST,
-            )
-            .unwrap();
-        let dst_raw = dst
-            .inner
-            .as_raw()
-            .ok_or(TransferError::InvalidTexture(destination.texture))?;
-
+        let dst = texture_guard.get_mut(destination.texture).unwrap();
         if !dst.desc.usage.contains(wgt::TextureUsages::COPY_DST) {
             return Err(
                 TransferError::MissingCopyDstUsageFlag(None, Some(destination.texture)).into(),
             );
         }
+        if dst.inner.as_raw().is_none() {
+            return Err(TransferError::InvalidTexture(destination.texture).into());
+        }
         let (hal_copy_size, array_layer_count) =
             validate_texture_copy_range(destination, &dst.desc, CopySide::Destination, size)?;
-        dst.life_guard.use_at(device.active_submission_index + 1);
+
+        // Copies from buffers to textures need offsets aligned to the texel block size.
+        let stage_alignment = get_lowest_common_denom(
+            device.alignments.buffer_copy_offset.get() as u32,
+            get_lowest_common_denom(
+                format_desc.block_size as u32,
+                wgt::COPY_BUFFER_ALIGNMENT as u32,
+            ),
+        );
+        let staging_pool = device.staging_pool.get_mut();
+        let stage = staging_pool.allocate(&device.raw, stage_size, stage_alignment as u64)?;
+        let stage_buffer = staging_pool.buffer(stage);
 
         let bytes_per_row = if let Some(bytes_per_row) = data_layout.bytes_per_row {
             bytes_per_row.get()
@@ -440,7 +626,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             width_blocks * format_desc.block_size as u32
         };
 
-        let mapping = unsafe { device.raw.map_buffer(&stage.buffer, 0..stage_size) }
+        let stage_range = stage.offset..stage.offset + stage.size;
+        let mapping = unsafe { device.raw.map_buffer(stage_buffer, stage_range.clone()) }
             .map_err(DeviceError::from)?;
         unsafe {
             profiling::scope!("copy");
@@ -475,20 +662,101 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             if !mapping.is_coherent {
                 device
                     .raw
-                    .flush_mapped_ranges(&stage.buffer, iter::once(0..stage_size));
+                    .flush_mapped_ranges(stage_buffer, iter::once(stage_range));
             }
             device
                 .raw
-                .unmap_buffer(&stage.buffer)
+                .unmap_buffer(stage_buffer)
                 .map_err(DeviceError::from)?;
         }
 
+        // Written subresources that are only partially overwritten need to be zero-filled
+        // before the copy. They are marked as initialized once the copy is recorded.
+        let dst = texture_guard.get(destination.texture).unwrap();
+        let uninitialized_layers = dst.initialization_status.mips[destination.mip_level as usize]
+            .clone()
+            .drain(selector.layers.clone())
+            .collect::<Vec<_>>();
+        let clear_layers = match copy_dst_init_kind(destination, size, &dst.desc) {
+            MemoryInitKind::NeedsInitializedMemory => uninitialized_layers,
+            MemoryInitKind::ImplicitlyInitialized => Vec::new(),
+        };
+        let clear_usage = dst.clear_mode.hal_usage();
+
+        let mut trackers = device.trackers.lock();
+        let encoder = device.pending_writes.activate();
+        let mut clear_views = Vec::new();
+        for layer_range in clear_layers {
+            let clear_selector = track::TextureSelector {
+                levels: destination.mip_level..destination.mip_level + 1,
+                layers: layer_range,
+            };
+            let (dst, transition) = trackers
+                .textures
+                .use_replace(
+                    &*texture_guard,
+                    destination.texture,
+                    clear_selector.clone(),
+                    clear_usage,
+                )
+                .unwrap();
+            let dst_raw = dst.inner.as_raw().unwrap();
+            unsafe {
+                encoder.transition_textures(transition.map(|pending| pending.into_hal(dst)));
+            }
+            match dst.clear_mode {
+                TextureClearMode::BufferCopy => clear_texture_via_buffer_copies::<A>(
+                    &dst.desc,
+                    device.alignments.buffer_copy_pitch.get() as u32,
+                    clear_selector,
+                    encoder,
+                    dst_raw,
+                    &device.zero_buffer,
+                ),
+                TextureClearMode::RenderPass { is_color } => {
+                    let result = clear_texture_via_render_passes::<A>(
+                        &dst.desc,
+                        dst_raw,
+                        clear_selector,
+                        is_color,
+                        encoder,
+                        &device.raw,
+                        &mut clear_views,
+                    );
+                    if let Err(err) = result {
+                        device
+                            .pending_writes
+                            .temp_resources
+                            .extend(clear_views.drain(..).map(TempResource::TextureView));
+                        return Err(DeviceError::from(err).into());
+                    }
+                }
+                TextureClearMode::None => {}
+            }
+        }
+        device
+            .pending_writes
+            .temp_resources
+            .extend(clear_views.into_iter().map(TempResource::TextureView));
+
+        let (dst, transition) = trackers
+            .textures
+            .use_replace(
+                &*texture_guard,
+                destination.texture,
+                selector.clone(),
+                hal::TextureUses::COPY_DST,
+            )
+            .unwrap();
+        let dst_raw = dst.inner.as_raw().unwrap();
+        dst.life_guard.use_at(device.active_submission_index + 1);
+
         let regions = (0..array_layer_count).map(|rel_array_layer| {
             let mut texture_base = dst_base.clone();
             texture_base.array_layer += rel_array_layer;
             hal::BufferTextureCopy {
                 buffer_layout: wgt::ImageDataLayout {
-                    offset: rel_array_layer as u64 * bytes_per_array_layer,
+                    offset: stage.offset + rel_array_layer as u64 * bytes_per_array_layer,
                     bytes_per_row: NonZeroU32::new(stage_bytes_per_row),
                     rows_per_image: NonZeroU32::new(block_rows_per_image),
                 },
@@ -497,7 +765,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             }
         });
         let barrier = hal::BufferBarrier {
-            buffer: &stage.buffer,
+            buffer: stage_buffer,
             usage: hal::BufferUses::MAP_WRITE..hal::BufferUses::COPY_SRC,
         };
 
@@ -505,10 +773,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         unsafe {
             encoder.transition_buffers(iter::once(barrier));
             encoder.transition_textures(transition.map(|pending| pending.into_hal(dst)));
-            encoder.copy_buffer_to_texture(&stage.buffer, dst_raw, regions);
+            encoder.copy_buffer_to_texture(stage_buffer, dst_raw, regions);
         }
 
-        device.pending_writes.consume(stage);
+        texture_guard
+            .get_mut(destination.texture)
+            .unwrap()
+            .initialization_status
+            .mips[destination.mip_level as usize]
+            .clear(selector.layers);
+
         device
             .pending_writes
             .dst_textures
@@ -524,6 +798,37 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
     ) -> Result<(), QueueSubmitError> {
         profiling::scope!("submit", "Queue");
 
+        match self.queue_submit_impl::<A>(queue_id, command_buffer_ids) {
+            Ok(callbacks) => {
+                // the closures should execute with nothing locked!
+                callbacks.fire();
+                Ok(())
+            }
+            Err(err) => {
+                // A failed submission loses the device, and the next calls
+                // have to know about it without waiting for a `maintain`.
+                let invocation = {
+                    let hub = A::hub(self);
+                    let mut token = Token::root();
+                    let (device_guard, _) = hub.devices.read(&mut token);
+                    device_guard
+                        .get(queue_id)
+                        .ok()
+                        .and_then(|device| device.lost.lock().take_invocation())
+                };
+                if let Some(invocation) = invocation {
+                    invocation.call();
+                }
+                Err(err)
+            }
+        }
+    }
+
+    fn queue_submit_impl<A: HalApi>(
+        &self,
+        queue_id: id::QueueId,
+        command_buffer_ids: &[id::CommandBufferId],
+    ) -> Result<UserClosures, QueueSubmitError> {
         let callbacks = {
             let hub = A::hub(self);
             let mut token = Token::root();
@@ -532,11 +837,15 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             let device = device_guard
                 .get_mut(queue_id)
                 .map_err(|_| DeviceError::Invalid)?;
+            if device.is_lost() {
+                return Err(DeviceError::Lost.into());
+            }
             device.temp_suspected.clear();
             device.active_submission_index += 1;
             let submit_index = device.active_submission_index;
             let mut active_executions = Vec::new();
             let mut used_surface_textures = track::ResourceTracker::new(A::VARIANT);
+            let mut clear_views = Vec::new();
 
             {
                 let (mut command_buffer_guard, mut token) = hub.command_buffers.write(&mut token);
@@ -723,6 +1032,21 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         baked
                             .initialize_buffer_memory(&mut *trackers, &mut *buffer_guard)
                             .map_err(|err| QueueSubmitError::DestroyedBuffer(err.0))?;
+                        baked
+                            .initialize_texture_memory(
+                                &device.raw,
+                                &device.zero_buffer,
+                                device.alignments.buffer_copy_pitch.get() as u32,
+                                &mut *trackers,
+                                &mut *texture_guard,
+                                &mut clear_views,
+                            )
+                            .map_err(|err| match err {
+                                TextureInitError::Destroyed(id) => {
+                                    QueueSubmitError::DestroyedTexture(id)
+                                }
+                                TextureInitError::Device(err) => QueueSubmitError::Queue(err),
+                            })?;
                         //Note: stateless trackers are not merged:
                         // device already knows these resources exist.
                         CommandBuffer::insert_barriers(
@@ -778,6 +1102,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                     ref mut fence,
                     ..
                 } = *device;
+                pending_writes
+                    .temp_resources
+                    .extend(clear_views.drain(..).map(TempResource::TextureView));
                 let refs = pending_writes
                     .pre_submit()
                     .into_iter()
@@ -787,10 +1114,16 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                             .flat_map(|pool_execution| pool_execution.cmd_buffers.iter()),
                     )
                     .collect::<Vec<_>>();
-                unsafe {
-                    queue
-                        .submit(&refs, Some((fence, submit_index)))
-                        .map_err(DeviceError::from)?;
+                if let Err(err) = unsafe { queue.submit(&refs, Some((fence, submit_index))) } {
+                    // The trackers already account for this submission,
+                    // so the state of the device can't be trusted anymore.
+                    let reason = match err {
+                        hal::DeviceError::Lost => wgt::DeviceLostReason::DriverReset,
+                        hal::DeviceError::OutOfMemory => wgt::DeviceLostReason::OutOfMemory,
+                    };
+                    device.lose(reason, &format!("queue submission failed: {}", err));
+                    // The reason is reported by the device lost closure.
+                    return Err(DeviceError::Lost.into());
                 }
             }
 
@@ -805,10 +1138,12 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
 
             // this will register the new submission to the life time tracker
             let mut pending_write_resources = mem::take(&mut device.pending_writes.temp_resources);
+            let staging_chunks = device.staging_pool.get_mut().take_active();
             device.lock_life(&mut token).track_submission(
                 submit_index,
                 pending_write_resources.drain(..),
                 active_executions,
+                staging_chunks,
             );
 
             // This will schedule destruction of all resources that are no longer needed
@@ -826,11 +1161,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             closures
         };
 
-        // the closures should execute with nothing locked!
-        unsafe {
-            callbacks.fire();
-        }
-        Ok(())
+        Ok(callbacks)
     }
 
     pub fn queue_get_timestamp_period<A: HalApi>(
@@ -852,7 +1183,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         closure: SubmittedWorkDoneClosure,
     ) -> Result<(), InvalidQueue> {
         //TODO: flush pending writes
-        let added = {
+        let not_added = {
             let hub = A::hub(self);
             let mut token = Token::root();
             let (device_guard, mut token) = hub.devices.read(&mut token);
@@ -861,16 +1192,14 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 Err(_) => return Err(InvalidQueue),
             }
         };
-        if !added {
-            unsafe {
-                (closure.callback)(closure.user_data);
-            }
+        if let Some(closure) = not_added {
+            closure.call();
         }
         Ok(())
     }
 }
 
-fn get_lowest_common_denom(a: u32, b: u32) -> u32 {
+pub(crate) fn get_lowest_common_denom(a: u32, b: u32) -> u32 {
     let gcd = if a >= b {
         get_greatest_common_divisor(a, b)
     } else {
@@ -892,7 +1221,17 @@ fn get_greatest_common_divisor(mut a: u32, mut b: u32) -> u32 {
     }
 }
 
-fn align_to(value: u32, alignment: u32) -> u32 {
+pub(crate) fn align_to(value: u32, alignment: u32) -> u32 {
+    match value % alignment {
+        0 => value,
+        other => value - other + alignment,
+    }
+}
+
+fn align_to_address(
+    value: wgt::BufferAddress,
+    alignment: wgt::BufferAddress,
+) -> wgt::BufferAddress {
     match value % alignment {
         0 => value,
         other => value - other + alignment,
@@ -913,3 +1252,65 @@ fn test_gcd() {
     assert_eq!(get_greatest_common_divisor(6, 4), 2);
     assert_eq!(get_greatest_common_divisor(7, 7), 7);
 }
+
+#[test]
+fn test_staging_pool_reuse_and_eviction() {
+    use hal::Instance as _;
+
+    type Api = hal::api::Empty;
+    // The empty backend uses the same type for its instance and devices.
+    let device = unsafe {
+        <Api as hal::Api>::Instance::init(&hal::InstanceDescriptor {
+            name: "test",
+            flags: hal::InstanceFlags::empty(),
+        })
+    }
+    .unwrap();
+    let mut pool = StagingPool::<Api>::new();
+
+    // Small writes share a chunk, large ones get a dedicated one.
+    pool.allocate(&device, 16, 4).unwrap();
+    let second = pool.allocate(&device, 16, 4).unwrap();
+    assert_eq!((second.chunk, second.offset), (0, 16));
+    let large = pool.allocate(&device, 3 * STAGING_CHUNK_SIZE, 4).unwrap();
+    assert_eq!(large.chunk, 1);
+    assert_eq!(
+        pool.usage(),
+        wgt::MemoryUsage {
+            count: 2,
+            bytes: 4 * STAGING_CHUNK_SIZE,
+        }
+    );
+
+    // Dedicated chunks aren't kept for reuse.
+    let active = pool.take_active();
+    pool.release(active, &device);
+    assert_eq!(pool.free.len(), 1);
+    assert_eq!(
+        pool.usage(),
+        wgt::MemoryUsage {
+            count: 1,
+            bytes: STAGING_CHUNK_SIZE,
+        }
+    );
+
+    // Free chunks are reused before new ones are created, up to the pool capacity.
+    let chunk_count = MAX_FREE_STAGING_CHUNKS + 2;
+    for _ in 0..chunk_count {
+        pool.allocate(&device, STAGING_CHUNK_SIZE, 4).unwrap();
+    }
+    assert!(pool.free.is_empty());
+    assert_eq!(pool.usage().count, chunk_count);
+    let active = pool.take_active();
+    pool.release(active, &device);
+    assert_eq!(pool.free.len(), MAX_FREE_STAGING_CHUNKS);
+    assert_eq!(
+        pool.usage(),
+        wgt::MemoryUsage {
+            count: MAX_FREE_STAGING_CHUNKS,
+            bytes: MAX_FREE_STAGING_CHUNKS as u64 * STAGING_CHUNK_SIZE,
+        }
+    );
+
+    pool.dispose(&device);
+}
@@ -1,13 +1,45 @@
 use crate::id;
-use std::ops::Range;
+#[cfg(feature = "replay")]
+use std::io::Read;
 #[cfg(feature = "trace")]
-use std::{borrow::Cow, io::Write as _};
+use std::{borrow::Cow, io::Write};
+use std::{ops::Range, path::Path};
 
 //TODO: consider a readable Id that doesn't include the backend
 
 type FileName = String;
 
 pub const FILE_NAME: &str = "trace.ron";
+/// Extension of the single-file binary traces.
+pub const BINARY_EXTENSION: &str = "trace";
+
+/// Every binary trace starts with these bytes, followed by `BINARY_VERSION`.
+const BINARY_MAGIC: &[u8; 8] = b"wgpu-trc";
+const BINARY_VERSION: u32 = 5;
+const RECORD_ACTION: u8 = 0;
+const RECORD_DATA: u8 = 1;
+
+/// Layout of a trace on disk.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TraceFormat {
+    /// A directory with a pretty-printed `trace.ron` and loose data files.
+    Ron,
+    /// A single file with a stream of binary records. Data blobs are
+    /// embedded right before the actions that refer to them, so the trace
+    /// can be written and replayed incrementally.
+    Binary,
+}
+
+impl TraceFormat {
+    /// Derives the format from the trace path: a file with `BINARY_EXTENSION`
+    /// is a binary trace, anything else is a RON trace directory.
+    pub fn from_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext == BINARY_EXTENSION && !path.is_dir() => Self::Binary,
+            _ => Self::Ron,
+        }
+    }
+}
 
 #[cfg(feature = "trace")]
 pub(crate) fn new_render_bundle_encoder_descriptor<'a>(
@@ -31,7 +63,7 @@ pub(crate) fn new_render_bundle_encoder_descriptor<'a>(
 }
 
 #[allow(clippy::large_enum_variant)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "trace", derive(serde::Serialize))]
 #[cfg_attr(feature = "replay", derive(serde::Deserialize))]
 pub enum Action<'a> {
@@ -94,6 +126,11 @@ pub enum Action<'a> {
         implicit_context: Option<super::ImplicitPipelineContext>,
     },
     DestroyRenderPipeline(id::RenderPipelineId),
+    CreatePipelineCache {
+        id: id::PipelineCacheId,
+        desc: crate::pipeline::PipelineCacheDescriptor<'a>,
+    },
+    DestroyPipelineCache(id::PipelineCacheId),
     CreateRenderBundle {
         id: id::RenderBundleId,
         desc: crate::command::RenderBundleEncoderDescriptor<'a>,
@@ -120,7 +157,7 @@ pub enum Action<'a> {
     Submit(crate::SubmissionIndex, Vec<Command>),
 }
 
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 #[cfg_attr(feature = "trace", derive(serde::Serialize))]
 #[cfg_attr(feature = "replay", derive(serde::Deserialize))]
 pub enum Command {
@@ -173,28 +210,66 @@ pub enum Command {
         base: crate::command::BasePass<crate::command::RenderCommand>,
         target_colors: Vec<crate::command::RenderPassColorAttachment>,
         target_depth_stencil: Option<crate::command::RenderPassDepthStencilAttachment>,
+        #[cfg_attr(feature = "replay", serde(default))]
+        occlusion_query_set: Option<id::QuerySetId>,
+    },
+}
+
+#[cfg(feature = "trace")]
+#[derive(Debug)]
+enum Sink {
+    Ron {
+        file: std::fs::File,
+        config: ron::ser::PrettyConfig,
     },
+    Binary(std::io::BufWriter<std::fs::File>),
 }
 
 #[cfg(feature = "trace")]
 #[derive(Debug)]
 pub struct Trace {
     path: std::path::PathBuf,
-    file: std::fs::File,
-    config: ron::ser::PrettyConfig,
+    sink: Sink,
     binary_id: usize,
 }
 
+#[cfg(feature = "trace")]
+fn write_record(writer: &mut impl Write, tag: u8, chunks: &[&[u8]]) -> Result<(), std::io::Error> {
+    writer.write_all(&[tag])?;
+    for chunk in chunks {
+        writer.write_all(&(chunk.len() as u64).to_le_bytes())?;
+        writer.write_all(chunk)?;
+    }
+    Ok(())
+}
+
 #[cfg(feature = "trace")]
 impl Trace {
-    pub fn new(path: &std::path::Path) -> Result<Self, std::io::Error> {
-        log::info!("Tracing into '{:?}'", path);
-        let mut file = std::fs::File::create(path.join(FILE_NAME))?;
-        file.write_all(b"[\n")?;
+    pub fn new(path: &Path) -> Result<Self, std::io::Error> {
+        Self::with_format(path, TraceFormat::from_path(path))
+    }
+
+    pub fn with_format(path: &Path, format: TraceFormat) -> Result<Self, std::io::Error> {
+        log::info!("Tracing into '{:?}' as {:?}", path, format);
+        let sink = match format {
+            TraceFormat::Ron => {
+                let mut file = std::fs::File::create(path.join(FILE_NAME))?;
+                file.write_all(b"[\n")?;
+                Sink::Ron {
+                    file,
+                    config: ron::ser::PrettyConfig::default(),
+                }
+            }
+            TraceFormat::Binary => {
+                let mut writer = std::io::BufWriter::new(std::fs::File::create(path)?);
+                writer.write_all(BINARY_MAGIC)?;
+                writer.write_all(&BINARY_VERSION.to_le_bytes())?;
+                Sink::Binary(writer)
+            }
+        };
         Ok(Self {
             path: path.to_path_buf(),
-            file,
-            config: ron::ser::PrettyConfig::default(),
+            sink,
             binary_id: 0,
         })
     }
@@ -202,17 +277,48 @@ impl Trace {
     pub fn make_binary(&mut self, kind: &str, data: &[u8]) -> String {
         self.binary_id += 1;
         let name = format!("data{}.{}", self.binary_id, kind);
-        let _ = std::fs::write(self.path.join(&name), data);
+        match self.sink {
+            Sink::Ron { .. } => {
+                let _ = std::fs::write(self.path.join(&name), data);
+            }
+            Sink::Binary(ref mut writer) => {
+                if let Err(e) = write_record(writer, RECORD_DATA, &[name.as_bytes(), data]) {
+                    log::warn!("Binary trace write failure: {:?}", e);
+                }
+            }
+        }
         name
     }
 
-    pub(crate) fn add(&mut self, action: Action) {
-        match ron::ser::to_string_pretty(&action, self.config.clone()) {
-            Ok(string) => {
-                let _ = writeln!(self.file, "{},", string);
-            }
-            Err(e) => {
-                log::warn!("RON serialization failure: {:?}", e);
+    pub fn add(&mut self, action: Action) {
+        match self.sink {
+            Sink::Ron {
+                ref mut file,
+                ref config,
+            } => match ron::ser::to_string_pretty(&action, config.clone()) {
+                Ok(string) => {
+                    let _ = writeln!(file, "{},", string);
+                }
+                Err(e) => {
+                    log::warn!("RON serialization failure: {:?}", e);
+                }
+            },
+            Sink::Binary(ref mut writer) => {
+                let bytes = match bincode::serialize(&action) {
+                    Ok(bytes) => bytes,
+                    Err(e) => {
+                        log::warn!("Binary serialization failure: {:?}", e);
+                        return;
+                    }
+                };
+                let mut result = write_record(writer, RECORD_ACTION, &[&bytes]);
+                // Keep whole frames on disk in case the application crashes.
+                if let Action::Submit(..) | Action::Present(_) = action {
+                    result = result.and_then(|()| writer.flush());
+                }
+                if let Err(e) = result {
+                    log::warn!("Binary trace write failure: {:?}", e);
+                }
             }
         }
     }
@@ -221,6 +327,99 @@ impl Trace {
 #[cfg(feature = "trace")]
 impl Drop for Trace {
     fn drop(&mut self) {
-        let _ = self.file.write_all(b"]");
+        match self.sink {
+            Sink::Ron { ref mut file, .. } => {
+                let _ = file.write_all(b"]");
+            }
+            Sink::Binary(ref mut writer) => {
+                let _ = writer.flush();
+            }
+        }
+    }
+}
+
+/// A record of a binary trace.
+#[cfg(feature = "replay")]
+#[derive(Debug)]
+pub enum Record {
+    Action(Action<'static>),
+    Data { name: FileName, data: Vec<u8> },
+}
+
+/// Incremental reader of binary traces.
+#[cfg(feature = "replay")]
+#[derive(Debug)]
+pub struct BinaryReader<R> {
+    reader: R,
+}
+
+#[cfg(feature = "replay")]
+fn invalid_data<E: Into<Box<dyn std::error::Error + Send + Sync>>>(error: E) -> std::io::Error {
+    std::io::Error::new(std::io::ErrorKind::InvalidData, error)
+}
+
+#[cfg(feature = "replay")]
+impl<R: Read> BinaryReader<R> {
+    pub fn new(mut reader: R) -> Result<Self, std::io::Error> {
+        let mut magic = [0u8; 8];
+        reader.read_exact(&mut magic)?;
+        if &magic != BINARY_MAGIC {
+            return Err(invalid_data("not a binary wgpu trace"));
+        }
+        let mut version = [0u8; 4];
+        reader.read_exact(&mut version)?;
+        match u32::from_le_bytes(version) {
+            BINARY_VERSION => Ok(Self { reader }),
+            other => Err(invalid_data(format!(
+                "unsupported binary trace version {}",
+                other
+            ))),
+        }
+    }
+
+    fn read_chunk(&mut self) -> Result<Vec<u8>, std::io::Error> {
+        let mut size = [0u8; 8];
+        self.reader.read_exact(&mut size)?;
+        let size = u64::from_le_bytes(size);
+        // The size comes from the file, so it can't be trusted for an upfront
+        // allocation: the chunk only grows as far as the data actually goes.
+        let mut chunk = Vec::new();
+        self.reader.by_ref().take(size).read_to_end(&mut chunk)?;
+        if (chunk.len() as u64) < size {
+            return Err(std::io::ErrorKind::UnexpectedEof.into());
+        }
+        Ok(chunk)
+    }
+
+    /// Reads the next record, returning `None` at the end of the stream.
+    ///
+    /// A record cut short, i.e. by the traced application crashing,
+    /// is also treated as the end of the stream.
+    pub fn next_record(&mut self) -> Result<Option<Record>, std::io::Error> {
+        let mut tag = [0u8; 1];
+        if self.reader.read(&mut tag)? == 0 {
+            return Ok(None);
+        }
+        let result = match tag[0] {
+            RECORD_ACTION => self.read_chunk().and_then(|bytes| {
+                bincode::deserialize(&bytes)
+                    .map(Record::Action)
+                    .map_err(invalid_data)
+            }),
+            RECORD_DATA => self.read_chunk().and_then(|name| {
+                let name = String::from_utf8(name).map_err(invalid_data)?;
+                let data = self.read_chunk()?;
+                Ok(Record::Data { name, data })
+            }),
+            other => Err(invalid_data(format!("unknown record tag {}", other))),
+        };
+        match result {
+            Ok(record) => Ok(Some(record)),
+            Err(e) if e.kind() == std::io::ErrorKind::UnexpectedEof => {
+                log::warn!("Binary trace is truncated");
+                Ok(None)
+            }
+            Err(e) => Err(e),
+        }
     }
 }
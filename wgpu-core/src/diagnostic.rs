@@ -0,0 +1,217 @@
+/*! Diagnostics of shader errors, pointing into the shader source.
+ *
+ * Diagnostics are rendered as annotated snippets of the source with `codespan-reporting`,
+ * like the errors of the Naga WGSL front-end.
+!*/
+
+use std::{error::Error, ops::Range};
+
+use codespan_reporting::{
+    diagnostic::{Diagnostic, Label},
+    files::SimpleFile,
+    term::{self, termcolor::NoColor},
+};
+
+/// Location of a span in a shader source, as reported by `GPUCompilationMessage`.
+///
+/// Positions and lengths are counted in UTF-16 code units, as in the WebGPU specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SourceLocation {
+    /// 1-based line number.
+    pub line_number: u32,
+    /// 1-based position in the line.
+    pub line_position: u32,
+    /// 0-based offset from the start of the source.
+    pub offset: u32,
+    /// Length of the span.
+    pub length: u32,
+}
+
+/// Diagnostic of a shader error, with labelled spans of the source.
+#[derive(Clone, Debug, Default)]
+pub struct ShaderDiagnostic {
+    pub message: String,
+    /// Byte ranges of the source, with their messages. The first one is the primary label.
+    pub labels: Vec<(Range<usize>, String)>,
+    pub notes: Vec<String>,
+}
+
+impl ShaderDiagnostic {
+    /// Creates a diagnostic without labels, noting the sources of `error`.
+    pub fn from_error(error: &dyn Error) -> Self {
+        let mut notes = Vec::new();
+        let mut source = error.source();
+        while let Some(error) = source {
+            notes.push(error.to_string());
+            source = error.source();
+        }
+        Self {
+            message: error.to_string(),
+            labels: Vec::new(),
+            notes,
+        }
+    }
+
+    /// Adds a label to the span, ignoring undefined spans.
+    pub(crate) fn with_label(mut self, span: naga::Span, message: impl Into<String>) -> Self {
+        if let Some(range) = span.to_range() {
+            self.labels.push((range, message.into()));
+        }
+        self
+    }
+
+    /// Returns the location of the primary label in `source`.
+    pub fn location(&self, source: &str) -> Option<SourceLocation> {
+        let range = self.labels.first()?.0.clone();
+        let utf16_len = |text: &str| text.encode_utf16().count() as u32;
+        let before = source.get(..range.start)?;
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+        Some(SourceLocation {
+            line_number: before.matches('\n').count() as u32 + 1,
+            line_position: utf16_len(&before[line_start..]) + 1,
+            offset: utf16_len(before),
+            length: utf16_len(source.get(range)?),
+        })
+    }
+
+    /// Renders the diagnostic as annotated snippets of `source`, named `file_name`.
+    pub fn emit_to_string(&self, source: &str, file_name: &str) -> String {
+        let files = SimpleFile::new(file_name, source);
+        let diagnostic = Diagnostic::error()
+            .with_message(self.message.clone())
+            .with_labels(
+                self.labels
+                    .iter()
+                    .enumerate()
+                    .map(|(index, &(ref range, ref message))| {
+                        let label = if index == 0 {
+                            Label::primary((), range.clone())
+                        } else {
+                            Label::secondary((), range.clone())
+                        };
+                        label.with_message(message.clone())
+                    })
+                    .collect(),
+            )
+            .with_notes(
+                self.notes
+                    .iter()
+                    .map(|note| format!("note: {}", note))
+                    .collect(),
+            );
+
+        let config = term::Config::default();
+        let mut writer = NoColor::new(Vec::new());
+        term::emit(&mut writer, &config, &files, &diagnostic).expect("cannot write error");
+        String::from_utf8(writer.into_inner()).unwrap()
+    }
+}
+
+/// Span of the body of a function, as the function itself has no span.
+pub(crate) fn function_span(function: &naga::Function) -> naga::Span {
+    naga::Span::total_span(
+        function
+            .expressions
+            .iter()
+            .map(|(handle, _)| function.expressions.get_span(handle)),
+    )
+}
+
+/// Returns the diagnostic of a WGSL parsing error.
+pub(crate) fn parse_error_diagnostic(
+    error: &naga::front::wgsl::ParseError,
+    source: &str,
+) -> ShaderDiagnostic {
+    let (line, column) = error.location(source);
+    let start = source
+        .split_inclusive('\n')
+        .take(line - 1)
+        .map(str::len)
+        .sum::<usize>()
+        + source
+            .lines()
+            .nth(line - 1)
+            .and_then(|text| text.char_indices().nth(column - 1))
+            .map_or(0, |(offset, _)| offset);
+    // The label covers the token at the location of the error.
+    let rest = &source[start.min(source.len())..];
+    let length = match rest.find(|c: char| !(c.is_alphanumeric() || c == '_')) {
+        Some(0) => rest.chars().next().map_or(0, char::len_utf8),
+        Some(length) => length,
+        None => rest.len(),
+    };
+    ShaderDiagnostic {
+        message: error.to_string(),
+        labels: vec![(start..start + length, String::new())],
+        notes: Vec::new(),
+    }
+}
+
+/// Returns the diagnostic of a validation error of `module`.
+pub(crate) fn validation_error_diagnostic(
+    error: &naga::valid::ValidationError,
+    module: &naga::Module,
+) -> ShaderDiagnostic {
+    use naga::valid::ValidationError as Ve;
+
+    let diagnostic = ShaderDiagnostic::from_error(error);
+    match *error {
+        Ve::Constant { handle, .. } => {
+            diagnostic.with_label(module.constants.get_span(handle), "invalid constant")
+        }
+        Ve::GlobalVariable { handle, .. } => diagnostic.with_label(
+            module.global_variables.get_span(handle),
+            "invalid global variable",
+        ),
+        Ve::Function { handle, .. } => {
+            diagnostic.with_label(module.functions.get_span(handle), "invalid function")
+        }
+        Ve::EntryPoint {
+            stage, ref name, ..
+        } => match module
+            .entry_points
+            .iter()
+            .find(|ep| ep.stage == stage && ep.name == *name)
+        {
+            Some(ep) => diagnostic.with_label(function_span(&ep.function), "invalid entry point"),
+            None => diagnostic,
+        },
+        _ => diagnostic,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn parse_error_location() {
+        let source = "// comment\nfn main() {\n    let x = ;\n}\n";
+        let error = naga::front::wgsl::parse_str(source).unwrap_err();
+        let diagnostic = parse_error_diagnostic(&error, source);
+
+        let location = diagnostic.location(source).unwrap();
+        assert_eq!(location.line_number, 3);
+        assert_eq!(location.line_position, 13);
+        assert_eq!(location.offset, 35);
+        assert_eq!(location.length, 1);
+
+        let rendered = diagnostic.emit_to_string(source, "shader.wgsl");
+        assert!(rendered.contains("shader.wgsl:3:13"));
+    }
+
+    #[test]
+    fn utf16_location() {
+        let source = "// é\u{1F600}\nfn";
+        let diagnostic = ShaderDiagnostic {
+            message: String::new(),
+            labels: vec![(source.find("fn").unwrap()..source.len(), String::new())],
+            notes: Vec::new(),
+        };
+        let location = diagnostic.location(source).unwrap();
+        assert_eq!(location.line_number, 2);
+        assert_eq!(location.line_position, 1);
+        assert_eq!(location.offset, 7);
+        assert_eq!(location.length, 2);
+    }
+}
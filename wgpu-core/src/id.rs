@@ -61,6 +61,7 @@ impl<T> Id<T> {
             3 => Backend::Dx12,
             4 => Backend::Dx11,
             5 => Backend::Gl,
+            7 => Backend::Cpu,
             _ => unreachable!(),
         }
     }
@@ -153,6 +154,7 @@ pub type BindGroupId = Id<crate::binding_model::BindGroup<Dummy>>;
 pub type ShaderModuleId = Id<crate::pipeline::ShaderModule<Dummy>>;
 pub type RenderPipelineId = Id<crate::pipeline::RenderPipeline<Dummy>>;
 pub type ComputePipelineId = Id<crate::pipeline::ComputePipeline<Dummy>>;
+pub type PipelineCacheId = Id<crate::pipeline::PipelineCache<Dummy>>;
 // Command
 pub type CommandEncoderId = CommandBufferId;
 pub type CommandBufferId = Id<crate::command::CommandBuffer<Dummy>>;
@@ -171,6 +173,7 @@ fn test_id_backend() {
         Backend::Dx12,
         Backend::Dx11,
         Backend::Gl,
+        Backend::Cpu,
     ] {
         let id: Id<()> = Id::zip(1, 0, b);
         assert_eq!(id.backend(), b);
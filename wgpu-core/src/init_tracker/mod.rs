@@ -17,10 +17,12 @@ use smallvec::SmallVec;
 use std::{iter, ops::Range};
 
 mod buffer;
-//mod texture;
+mod texture;
 
 pub(crate) use buffer::{BufferInitTracker, BufferInitTrackerAction};
-//pub(crate) use texture::{TextureInitRange, TextureInitTracker, TextureInitTrackerAction};
+pub(crate) use texture::{
+    has_copy_partial_init_tracker_coverage, TextureInitTracker, TextureInitTrackerAction,
+};
 
 #[derive(Debug, Clone, Copy)]
 pub(crate) enum MemoryInitKind {
@@ -0,0 +1,151 @@
+use super::{InitTracker, MemoryInitKind};
+use crate::{id::TextureId, track::TextureSelector};
+use arrayvec::ArrayVec;
+use std::ops::Range;
+
+#[derive(Debug, Clone)]
+pub(crate) struct TextureInitRange {
+    pub(crate) mip_range: Range<u32>,
+    pub(crate) layer_range: Range<u32>,
+}
+
+// Returns true if a copy operation doesn't fully cover the texture init tracking granularity.
+// I.e. if this function returns true for a pending copy operation, the target texture needs to be ensured to be initialized first!
+pub(crate) fn has_copy_partial_init_tracker_coverage(
+    copy_size: &wgt::Extent3d,
+    mip_level: u32,
+    desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
+) -> bool {
+    let target_size = desc.mip_level_size(mip_level).unwrap();
+    copy_size.width != target_size.width
+        || copy_size.height != target_size.height
+        || (desc.dimension == wgt::TextureDimension::D3
+            && copy_size.depth_or_array_layers != target_size.depth_or_array_layers)
+}
+
+impl From<TextureSelector> for TextureInitRange {
+    fn from(selector: TextureSelector) -> Self {
+        TextureInitRange {
+            mip_range: selector.levels,
+            layer_range: selector.layers,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub(crate) struct TextureInitTrackerAction {
+    pub(crate) id: TextureId,
+    pub(crate) range: TextureInitRange,
+    pub(crate) kind: MemoryInitKind,
+}
+
+pub(crate) type TextureLayerInitTracker = InitTracker<u32>;
+
+/// Tracks the initialization status of every mip level of a texture, per array layer.
+#[derive(Debug)]
+pub(crate) struct TextureInitTracker {
+    pub mips: ArrayVec<TextureLayerInitTracker, { hal::MAX_MIP_LEVELS as usize }>,
+}
+
+impl TextureInitTracker {
+    pub(crate) fn new(mip_level_count: u32, depth_or_array_layers: u32) -> Self {
+        TextureInitTracker {
+            mips: std::iter::repeat(TextureLayerInitTracker::new(depth_or_array_layers))
+                .take(mip_level_count as usize)
+                .collect(),
+        }
+    }
+
+    /// Checks if an action has/requires any effect on the initialization status and shrinks its range if possible.
+    pub(crate) fn check_action(
+        &self,
+        action: &TextureInitTrackerAction,
+    ) -> Option<TextureInitTrackerAction> {
+        let mut mip_range_start = std::usize::MAX;
+        let mut mip_range_end = std::usize::MIN;
+        let mut layer_range_start = std::u32::MAX;
+        let mut layer_range_end = std::u32::MIN;
+
+        for (i, mip_tracker) in self
+            .mips
+            .iter()
+            .enumerate()
+            .take(action.range.mip_range.end as usize)
+            .skip(action.range.mip_range.start as usize)
+        {
+            if let Some(uninitialized_layer_range) =
+                mip_tracker.check(action.range.layer_range.clone())
+            {
+                mip_range_start = mip_range_start.min(i);
+                mip_range_end = i + 1;
+                layer_range_start = layer_range_start.min(uninitialized_layer_range.start);
+                layer_range_end = layer_range_end.max(uninitialized_layer_range.end);
+            };
+        }
+
+        if mip_range_start < mip_range_end && layer_range_start < layer_range_end {
+            Some(TextureInitTrackerAction {
+                id: action.id,
+                range: TextureInitRange {
+                    mip_range: mip_range_start as u32..mip_range_end as u32,
+                    layer_range: layer_range_start..layer_range_end,
+                },
+                kind: action.kind,
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Creates an action if it would have any effect on the initialization status and shrinks the range if possible.
+    pub(crate) fn create_action(
+        &self,
+        id: TextureId,
+        range: TextureInitRange,
+        kind: MemoryInitKind,
+    ) -> Option<TextureInitTrackerAction> {
+        self.check_action(&TextureInitTrackerAction { id, range, kind })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::{TextureInitRange, TextureInitTracker, TextureInitTrackerAction};
+    use crate::{id::TypedId as _, init_tracker::MemoryInitKind};
+
+    fn action(
+        mip_range: std::ops::Range<u32>,
+        layer_range: std::ops::Range<u32>,
+    ) -> TextureInitTrackerAction {
+        TextureInitTrackerAction {
+            id: crate::id::TextureId::zip(1, 0, wgt::Backend::Empty),
+            range: TextureInitRange {
+                mip_range,
+                layer_range,
+            },
+            kind: MemoryInitKind::NeedsInitializedMemory,
+        }
+    }
+
+    #[test]
+    fn check_action_for_newly_created_tracker() {
+        let tracker = TextureInitTracker::new(3, 4);
+        let checked = tracker.check_action(&action(1..3, 2..4)).unwrap();
+        assert_eq!(checked.range.mip_range, 1..3);
+        assert_eq!(checked.range.layer_range, 2..4);
+    }
+
+    #[test]
+    fn check_action_shrinks_to_uninitialized_subresources() {
+        let mut tracker = TextureInitTracker::new(3, 4);
+        tracker.mips[0].clear(0..4);
+        tracker.mips[1].clear(0..2);
+        tracker.mips[2].clear(0..4);
+
+        assert!(tracker.check_action(&action(0..1, 0..4)).is_none());
+        assert!(tracker.check_action(&action(1..2, 0..2)).is_none());
+        let checked = tracker.check_action(&action(0..3, 0..4)).unwrap();
+        assert_eq!(checked.range.mip_range, 1..2);
+        assert_eq!(checked.range.layer_range, 2..4);
+    }
+}
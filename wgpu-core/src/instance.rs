@@ -90,6 +90,8 @@ pub struct Instance {
     pub dx11: Option<HalInstance<hal::api::Dx11>>,
     #[cfg(gl)]
     pub gl: Option<HalInstance<hal::api::Gles>>,
+    #[cfg(cpu)]
+    pub cpu: Option<HalInstance<hal::api::Cpu>>,
 }
 
 impl Instance {
@@ -123,6 +125,8 @@ impl Instance {
             dx11: init::<hal::api::Dx11>(backends),
             #[cfg(gl)]
             gl: init::<hal::api::Gles>(backends),
+            #[cfg(cpu)]
+            cpu: init::<hal::api::Cpu>(backends),
         }
     }
 
@@ -146,6 +150,8 @@ impl Instance {
             map((surface.dx11, &self.dx11)),
             #[cfg(gl)]
             map((surface.gl, &self.gl)),
+            #[cfg(cpu)]
+            map((surface.cpu, &self.cpu)),
         }
     }
 }
@@ -162,6 +168,8 @@ pub struct Surface {
     pub dx11: Option<HalSurface<hal::api::Dx11>>,
     #[cfg(gl)]
     pub gl: Option<HalSurface<hal::api::Gles>>,
+    #[cfg(cpu)]
+    pub cpu: Option<HalSurface<hal::api::Cpu>>,
 }
 
 impl crate::hub::Resource for Surface {
@@ -464,6 +472,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             dx11: init(hal::api::Dx11, &self.instance.dx11, handle),
             #[cfg(gl)]
             gl: init(hal::api::Gles, &self.instance.gl, handle),
+            // the CPU backend has nothing to present to
+            #[cfg(cpu)]
+            cpu: None,
         };
 
         let mut token = Token::root();
@@ -489,6 +500,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 },
                 //acquired_texture: None,
             }),
+            #[cfg(cpu)]
+            cpu: None,
         };
 
         let mut token = Token::root();
@@ -537,6 +550,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             map((&instance.dx11, Backend::Dx11, "Dx11")),
             #[cfg(gl)]
             map((&instance.gl, Backend::Gl, "GL")),
+            #[cfg(cpu)]
+            map((&instance.cpu, Backend::Cpu, "CPU")),
         }
 
         adapters
@@ -567,6 +582,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let mut id_dx12 = inputs.find(Backend::Dx12);
         let mut id_dx11 = inputs.find(Backend::Dx11);
         let mut id_gl = inputs.find(Backend::Gl);
+        let mut id_cpu = inputs.find(Backend::Cpu);
 
         backends_map! {
             let map = |(instance_backend, id_backend, surface_backend)| {
@@ -622,6 +638,13 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                 }
                 surface_gl
             }));
+            #[cfg(cpu)]
+            let adapters_cpu = map((&instance.cpu, &id_cpu, {
+                fn surface_cpu(surf: &Surface) -> Option<&HalSurface<hal::api::Cpu>> {
+                    surf.cpu.as_ref()
+                }
+                surface_cpu
+            }));
         }
 
         if device_types.is_empty() {
@@ -681,6 +704,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             map(("Dx11", &mut id_dx11, adapters_dx11)),
             #[cfg(gl)]
             map(("GL", &mut id_gl, adapters_gl)),
+            #[cfg(cpu)]
+            map(("CPU", &mut id_cpu, adapters_cpu)),
         }
 
         let _ = (
@@ -690,6 +715,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             id_dx12.take(),
             id_dx11.take(),
             id_gl.take(),
+            id_cpu.take(),
         );
         log::warn!("Some adapters are present, but enumerating them failed!");
         Err(RequestAdapterError::NotFound)
@@ -719,6 +745,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
             Backend::Dx11 => fid.assign(Adapter::new(hal_adapter), &mut token).0,
             #[cfg(gl)]
             Backend::Gl => fid.assign(Adapter::new(hal_adapter), &mut token).0,
+            #[cfg(cpu)]
+            Backend::Cpu => fid.assign(Adapter::new(hal_adapter), &mut token).0,
             _ => unreachable!(),
         }
     }
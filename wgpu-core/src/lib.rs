@@ -34,12 +34,14 @@ pub mod binding_model;
 pub mod command;
 mod conv;
 pub mod device;
+pub mod diagnostic;
 pub mod error;
 pub mod hub;
 pub mod id;
 mod init_tracker;
 pub mod instance;
 pub mod pipeline;
+mod pipeline_cache;
 pub mod present;
 pub mod resource;
 mod track;
@@ -213,12 +215,33 @@ macro_rules! gfx_select {
             //wgt::Backend::Dx11 => $global.$method::<$crate::api::Dx11>( $($param),* ),
             #[cfg(all(not(target_arch = "wasm32"), unix, not(any(target_os = "ios", target_os = "macos"))))]
             wgt::Backend::Gl => $global.$method::<$crate::api::Gles>( $($param),+ ),
+            wgt::Backend::Cpu => $crate::gfx_select_cpu!($global.$method( $($param),* )),
             other => panic!("Unexpected backend {:?}", other),
 
         }
     };
 }
 
+// The CPU backend is opt-in, so the feature check has to happen in this crate,
+// instead of the crate invoking `gfx_select`.
+#[cfg(feature = "cpu")]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! gfx_select_cpu {
+    ($global:ident.$method:ident( $($param:expr),* )) => {
+        $global.$method::<$crate::api::Cpu>( $($param),* )
+    };
+}
+
+#[cfg(not(feature = "cpu"))]
+#[doc(hidden)]
+#[macro_export]
+macro_rules! gfx_select_cpu {
+    ($global:ident.$method:ident( $($param:expr),* )) => {
+        panic!("Unexpected backend {:?}", wgt::Backend::Cpu)
+    };
+}
+
 /// Fast hash map used internally.
 type FastHashMap<K, V> =
     std::collections::HashMap<K, V, std::hash::BuildHasherDefault<fxhash::FxHasher>>;
@@ -1,13 +1,16 @@
 use crate::{
     binding_model::{CreateBindGroupLayoutError, CreatePipelineLayoutError},
     device::{DeviceError, MissingDownlevelFlags, MissingFeatures, RenderPassContext},
+    diagnostic::{ShaderDiagnostic, SourceLocation},
     hub::Resource,
-    id::{DeviceId, PipelineLayoutId, ShaderModuleId},
+    id::{DeviceId, PipelineCacheId, PipelineLayoutId, ShaderModuleId},
     validation, Label, LifeGuard, Stored,
 };
-use std::borrow::Cow;
+use std::{borrow::Cow, error, fmt};
 use thiserror::Error;
 
+pub use crate::pipeline_cache::PipelineCacheValidationError;
+
 pub enum ShaderModuleSource<'a> {
     Wgsl(Cow<'a, str>),
     Naga(naga::Module),
@@ -25,10 +28,36 @@ pub struct ShaderModule<A: hal::Api> {
     pub(crate) raw: A::ShaderModule,
     pub(crate) device_id: Stored<DeviceId>,
     pub(crate) interface: Option<validation::Interface>,
+    /// Source of the module if it was created from WGSL, to point errors into it.
+    pub(crate) source: Option<String>,
+    pub(crate) source_label: Option<String>,
     #[cfg(debug_assertions)]
     pub(crate) label: String,
 }
 
+impl<A: hal::Api> ShaderModule<A> {
+    /// Attaches the diagnostic of a stage error to it.
+    pub(crate) fn stage_error(
+        &self,
+        error: validation::StageError,
+        stage_bit: wgt::ShaderStages,
+        entry_point_name: &str,
+    ) -> ShaderError<validation::StageError> {
+        let diagnostic = match self.interface {
+            Some(ref interface) => {
+                interface.stage_error_diagnostic(&error, stage_bit, entry_point_name)
+            }
+            None => ShaderDiagnostic::from_error(&error),
+        };
+        ShaderError {
+            shader_source: self.source.clone(),
+            label: self.source_label.clone(),
+            diagnostic,
+            inner: error,
+        }
+    }
+}
+
 impl<A: hal::Api> Resource for ShaderModule<A> {
     const TYPE: &'static str = "ShaderModule";
 
@@ -44,31 +73,67 @@ impl<A: hal::Api> Resource for ShaderModule<A> {
     }
 }
 
-#[derive(Clone, Debug, Error)]
-pub struct NagaParseError {
-    pub shader_source: String,
-    pub error: naga::front::wgsl::ParseError,
+/// Error in a shader module, with a diagnostic pointing into its source.
+#[derive(Clone, Debug)]
+pub struct ShaderError<E> {
+    /// Source of the module, if it was created from WGSL.
+    pub shader_source: Option<String>,
+    /// Label of the module, used as the file name of the source.
+    pub label: Option<String>,
+    pub diagnostic: ShaderDiagnostic,
+    pub inner: E,
+}
+
+impl<E: error::Error> From<E> for ShaderError<E> {
+    fn from(inner: E) -> Self {
+        Self {
+            shader_source: None,
+            label: None,
+            diagnostic: ShaderDiagnostic::from_error(&inner),
+            inner,
+        }
+    }
 }
-impl std::fmt::Display for NagaParseError {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(
-            f,
-            "\nShader error:\n{}",
-            self.error.emit_to_string(&self.shader_source)
-        )
+
+impl<E> ShaderError<E> {
+    /// Returns the location of the diagnostic in the source.
+    pub fn location(&self) -> Option<SourceLocation> {
+        self.diagnostic.location(self.shader_source.as_ref()?)
+    }
+}
+
+impl<E> fmt::Display for ShaderError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.shader_source {
+            Some(ref source) => {
+                let file_name = self.label.as_deref().unwrap_or("wgsl");
+                write!(
+                    f,
+                    "\nShader error:\n{}",
+                    self.diagnostic.emit_to_string(source, file_name)
+                )
+            }
+            None => write!(f, "{}", self.diagnostic.message),
+        }
+    }
+}
+
+impl<E: error::Error + 'static> error::Error for ShaderError<E> {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        Some(&self.inner)
     }
 }
 
 #[derive(Clone, Debug, Error)]
 pub enum CreateShaderModuleError {
     #[error("Failed to parse a shader")]
-    Parsing(#[from] NagaParseError),
+    Parsing(#[from] ShaderError<naga::front::wgsl::ParseError>),
     #[error("Failed to generate the backend-specific code")]
     Generation,
     #[error(transparent)]
     Device(#[from] DeviceError),
-    #[error(transparent)]
-    Validation(#[from] naga::valid::ValidationError),
+    #[error("Failed to validate a shader")]
+    Validation(#[from] ShaderError<naga::valid::ValidationError>),
     #[error(transparent)]
     MissingFeatures(#[from] MissingFeatures),
 }
@@ -110,6 +175,9 @@ pub struct ComputePipelineDescriptor<'a> {
     pub layout: Option<PipelineLayoutId>,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStageDescriptor<'a>,
+    /// The pipeline cache to use when creating this pipeline.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub cache: Option<PipelineCacheId>,
 }
 
 #[derive(Clone, Debug, Error)]
@@ -118,10 +186,12 @@ pub enum CreateComputePipelineError {
     Device(#[from] DeviceError),
     #[error("pipeline layout is invalid")]
     InvalidLayout,
+    #[error("pipeline cache is invalid")]
+    InvalidCache,
     #[error("unable to derive an implicit layout")]
     Implicit(#[from] ImplicitLayoutError),
     #[error("error matching shader requirements against the pipeline")]
-    Stage(#[from] validation::StageError),
+    Stage(#[from] ShaderError<validation::StageError>),
     #[error("Internal error: {0}")]
     Internal(String),
     #[error(transparent)]
@@ -201,6 +271,9 @@ pub struct RenderPipelineDescriptor<'a> {
     pub multisample: wgt::MultisampleState,
     /// The fragment processing state for this pipeline.
     pub fragment: Option<FragmentState<'a>>,
+    /// The pipeline cache to use when creating this pipeline.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub cache: Option<PipelineCacheId>,
 }
 
 #[derive(Clone, Debug, Error)]
@@ -238,6 +311,8 @@ pub enum CreateRenderPipelineError {
     Device(#[from] DeviceError),
     #[error("pipeline layout is invalid")]
     InvalidLayout,
+    #[error("pipeline cache is invalid")]
+    InvalidCache,
     #[error("unable to derive an implicit layout")]
     Implicit(#[from] ImplicitLayoutError),
     #[error("color state [{0}] is invalid")]
@@ -277,7 +352,7 @@ pub enum CreateRenderPipelineError {
     Stage {
         stage: wgt::ShaderStages,
         #[source]
-        error: validation::StageError,
+        error: ShaderError<validation::StageError>,
     },
     #[error("Internal error in {stage:?} shader: {error}")]
     Internal {
@@ -314,3 +389,41 @@ impl<A: hal::Api> Resource for RenderPipeline<A> {
         &self.life_guard
     }
 }
+
+/// Describes a pipeline cache.
+#[derive(Clone, Debug)]
+#[cfg_attr(feature = "trace", derive(serde::Serialize))]
+#[cfg_attr(feature = "replay", derive(serde::Deserialize))]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// Data previously returned by `pipeline_cache_get_data`, if any.
+    #[cfg_attr(any(feature = "replay", feature = "trace"), serde(default))]
+    pub data: Option<Cow<'a, [u8]>>,
+    /// If the data is rejected, create an empty cache instead of failing.
+    pub fallback: bool,
+}
+
+#[derive(Clone, Debug, Error)]
+pub enum CreatePipelineCacheError {
+    #[error(transparent)]
+    Device(#[from] DeviceError),
+    #[error("pipeline cache data is rejected")]
+    Validation(#[from] PipelineCacheValidationError),
+    #[error(transparent)]
+    MissingFeatures(#[from] MissingFeatures),
+}
+
+#[derive(Debug)]
+pub struct PipelineCache<A: hal::Api> {
+    pub(crate) raw: A::PipelineCache,
+    pub(crate) device_id: Stored<DeviceId>,
+    pub(crate) life_guard: LifeGuard,
+}
+
+impl<A: hal::Api> Resource for PipelineCache<A> {
+    const TYPE: &'static str = "PipelineCache";
+
+    fn life_guard(&self) -> &LifeGuard {
+        &self.life_guard
+    }
+}
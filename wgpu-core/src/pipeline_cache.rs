@@ -0,0 +1,190 @@
+// Pipeline cache data returned by the backend is prefixed with a header identifying
+// the wgpu release, adapter and driver that produced it, so that stale or foreign data
+// is rejected instead of being handed over to the driver.
+
+use std::convert::TryInto;
+use thiserror::Error;
+
+const MAGIC: [u8; 8] = *b"WGPUPLCH";
+const HEADER_VERSION: u32 = 2;
+const HEADER_LENGTH: usize = 8 + 4 + 8 + 4 + 8 + 8 + 16 + 8 + 8;
+
+#[derive(Clone, Debug, Error)]
+pub enum PipelineCacheValidationError {
+    #[error("the pipeline cache data is truncated")]
+    Truncated,
+    #[error("the pipeline cache data is longer than recorded")]
+    Extended,
+    #[error("the pipeline cache data is corrupted")]
+    Corrupted,
+    #[error("the pipeline cache data was produced by a different version of wgpu")]
+    Outdated,
+    #[error("the pipeline cache data was produced for a different adapter or driver")]
+    WrongDevice,
+    #[error("pipeline caches are not supported by this device")]
+    Unsupported,
+}
+
+/// Identifies the adapter and driver the cache data belongs to.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) struct CacheKey {
+    pub backend: wgt::Backend,
+    pub vendor: u64,
+    pub device: u64,
+    pub validation_key: [u8; 16],
+}
+
+impl CacheKey {
+    pub fn new(info: &wgt::AdapterInfo, validation_key: [u8; 16]) -> Self {
+        Self {
+            backend: info.backend,
+            vendor: info.vendor as u64,
+            device: info.device as u64,
+            validation_key,
+        }
+    }
+}
+
+/// FNV-1a, good enough to catch corrupted files.
+fn hash(data: &[u8]) -> u64 {
+    data.iter().fold(0xcbf2_9ce4_8422_2325, |hash, &byte| {
+        (hash ^ byte as u64).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// Identifies the wgpu release writing and reading the cache data.
+fn wgpu_version() -> u64 {
+    hash(env!("CARGO_PKG_VERSION").as_bytes())
+}
+
+/// Prefixes the backend data with a header for `key`.
+pub(crate) fn add_cache_header(data: &[u8], key: &CacheKey) -> Vec<u8> {
+    let mut out = Vec::with_capacity(HEADER_LENGTH + data.len());
+    out.extend_from_slice(&MAGIC);
+    out.extend_from_slice(&HEADER_VERSION.to_le_bytes());
+    out.extend_from_slice(&wgpu_version().to_le_bytes());
+    out.extend_from_slice(&(key.backend as u32).to_le_bytes());
+    out.extend_from_slice(&key.vendor.to_le_bytes());
+    out.extend_from_slice(&key.device.to_le_bytes());
+    out.extend_from_slice(&key.validation_key);
+    out.extend_from_slice(&(data.len() as u64).to_le_bytes());
+    out.extend_from_slice(&hash(data).to_le_bytes());
+    debug_assert_eq!(out.len(), HEADER_LENGTH);
+    out.extend_from_slice(data);
+    out
+}
+
+/// Checks the header of `cache_data` against `key`, returning the backend data.
+pub(crate) fn validate_pipeline_cache<'d>(
+    cache_data: &'d [u8],
+    key: &CacheKey,
+) -> Result<&'d [u8], PipelineCacheValidationError> {
+    use PipelineCacheValidationError as E;
+
+    if cache_data.len() < HEADER_LENGTH {
+        return Err(E::Truncated);
+    }
+    let (header, data) = cache_data.split_at(HEADER_LENGTH);
+    let u32_at = |offset: usize| u32::from_le_bytes(header[offset..offset + 4].try_into().unwrap());
+    let u64_at = |offset: usize| u64::from_le_bytes(header[offset..offset + 8].try_into().unwrap());
+
+    if header[..8] != MAGIC {
+        return Err(E::Corrupted);
+    }
+    if u32_at(8) != HEADER_VERSION || u64_at(12) != wgpu_version() {
+        return Err(E::Outdated);
+    }
+    if u32_at(20) != key.backend as u32
+        || u64_at(24) != key.vendor
+        || u64_at(32) != key.device
+        || header[40..56] != key.validation_key
+    {
+        return Err(E::WrongDevice);
+    }
+    let data_size = u64_at(56);
+    if (data.len() as u64) < data_size {
+        return Err(E::Truncated);
+    }
+    if (data.len() as u64) > data_size {
+        return Err(E::Extended);
+    }
+    if u64_at(64) != hash(data) {
+        return Err(E::Corrupted);
+    }
+    Ok(data)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const KEY: CacheKey = CacheKey {
+        backend: wgt::Backend::Vulkan,
+        vendor: 0x10de,
+        device: 0x1234,
+        validation_key: [7; 16],
+    };
+
+    #[test]
+    fn round_trip() {
+        let data = [1, 2, 3, 4, 5];
+        let cache = add_cache_header(&data, &KEY);
+        assert_eq!(validate_pipeline_cache(&cache, &KEY).unwrap(), &data[..]);
+    }
+
+    #[test]
+    fn rejects_invalid_data() {
+        let data = [1, 2, 3, 4, 5];
+        let cache = add_cache_header(&data, &KEY);
+
+        let result = validate_pipeline_cache(&cache[..cache.len() - 1], &KEY);
+        assert!(matches!(
+            result,
+            Err(PipelineCacheValidationError::Truncated)
+        ));
+
+        let mut extended = cache.clone();
+        extended.push(0);
+        let result = validate_pipeline_cache(&extended, &KEY);
+        assert!(matches!(
+            result,
+            Err(PipelineCacheValidationError::Extended)
+        ));
+
+        let mut corrupted = cache.clone();
+        *corrupted.last_mut().unwrap() ^= 0xFF;
+        let result = validate_pipeline_cache(&corrupted, &KEY);
+        assert!(matches!(
+            result,
+            Err(PipelineCacheValidationError::Corrupted)
+        ));
+
+        let mut other_release = cache.clone();
+        other_release[12] ^= 0xFF;
+        let result = validate_pipeline_cache(&other_release, &KEY);
+        assert!(matches!(
+            result,
+            Err(PipelineCacheValidationError::Outdated)
+        ));
+
+        let other_backend = CacheKey {
+            backend: wgt::Backend::Dx12,
+            ..KEY
+        };
+        let result = validate_pipeline_cache(&cache, &other_backend);
+        assert!(matches!(
+            result,
+            Err(PipelineCacheValidationError::WrongDevice)
+        ));
+
+        let other_driver = CacheKey {
+            validation_key: [8; 16],
+            ..KEY
+        };
+        let result = validate_pipeline_cache(&cache, &other_driver);
+        assert!(matches!(
+            result,
+            Err(PipelineCacheValidationError::WrongDevice)
+        ));
+    }
+}
@@ -13,9 +13,10 @@
 use crate::device::trace::Action;
 use crate::{
     conv,
-    device::DeviceError,
+    device::{DeviceError, MissingDownlevelFlags},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Input, Token},
     id::{DeviceId, SurfaceId, TextureId, Valid},
+    init_tracker::TextureInitTracker,
     resource,
     track::TextureSelector,
     LifeGuard, Stored,
@@ -75,6 +76,13 @@ pub enum ConfigureSurfaceError {
     },
     #[error("requested usage is not supported")]
     UnsupportedUsage,
+    #[error("Surface of format {surface:?} can't be viewed as {view:?}")]
+    InvalidViewFormat {
+        surface: wgt::TextureFormat,
+        view: wgt::TextureFormat,
+    },
+    #[error(transparent)]
+    MissingDownlevelFlags(#[from] MissingDownlevelFlags),
 }
 
 #[repr(C)]
@@ -124,6 +132,9 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
         let (texture_id, status) = match unsafe { suf.raw.acquire_texture(FRAME_TIMEOUT_MS) } {
             Ok(Some(ast)) => {
                 let present = surface.presentation.as_mut().unwrap();
+                // Surface textures are never cleared by us.
+                let mut initialization_status = TextureInitTracker::new(1, 1);
+                initialization_status.mips[0].clear(0..1);
                 let texture = resource::Texture {
                     inner: resource::TextureInner::Surface {
                         raw: ast.texture,
@@ -143,6 +154,7 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         format: config.format,
                         dimension: wgt::TextureDimension::D2,
                         usage: config.usage,
+                        view_formats: config.view_formats.clone(),
                     },
                     hal_usage: conv::map_texture_usage(config.usage, config.format.into()),
                     format_features: wgt::TextureFormatFeatures {
@@ -150,6 +162,8 @@ impl<G: GlobalIdentityHandlerFactory> Global<G> {
                         flags: wgt::TextureFormatFeatureFlags::empty(),
                         filterable: false,
                     },
+                    initialization_status,
+                    clear_mode: resource::TextureClearMode::None,
                     full_range: TextureSelector {
                         layers: 0..1,
                         levels: 0..1,
@@ -1,8 +1,8 @@
 use crate::{
-    device::{DeviceError, HostMap, MissingFeatures},
+    device::{DeviceError, HostMap, MissingDownlevelFlags, MissingFeatures},
     hub::{Global, GlobalIdentityHandlerFactory, HalApi, Resource, Token},
     id::{DeviceId, SurfaceId, TextureId, Valid},
-    init_tracker::BufferInitTracker,
+    init_tracker::{BufferInitTracker, TextureInitTracker},
     track::{TextureSelector, DUMMY_SELECTOR},
     validation::MissingBufferUsageError,
     Label, LifeGuard, RefCount, Stored,
@@ -45,29 +45,89 @@ pub(crate) enum BufferMapState<A: hal::Api> {
 unsafe impl<A: hal::Api> Send for BufferMapState<A> {}
 unsafe impl<A: hal::Api> Sync for BufferMapState<A> {}
 
-pub type BufferMapCallback = unsafe extern "C" fn(status: BufferMapAsyncStatus, userdata: *mut u8);
+pub type BufferMapCallbackFn =
+    unsafe extern "C" fn(status: BufferMapAsyncStatus, user_data: *mut u8);
 
+/// C-style form of the callback of a buffer mapping, for FFI users.
 #[repr(C)]
 #[derive(Debug)]
-pub struct BufferMapOperation {
-    pub host: HostMap,
-    pub callback: BufferMapCallback,
+pub struct BufferMapCallbackC {
+    pub callback: BufferMapCallbackFn,
     pub user_data: *mut u8,
 }
 
-//TODO: clarify if/why this is needed here
-unsafe impl Send for BufferMapOperation {}
-unsafe impl Sync for BufferMapOperation {}
+unsafe impl Send for BufferMapCallbackC {}
+unsafe impl Sync for BufferMapCallbackC {}
+
+/// Callback called with the outcome of a buffer mapping.
+pub struct BufferMapCallback {
+    // Wrapped so that only the creation of the C form is unsafe, and calling is not.
+    inner: BufferMapCallbackInner,
+}
+
+enum BufferMapCallbackInner {
+    Rust {
+        callback: Box<dyn FnOnce(BufferAccessResult) + Send + 'static>,
+    },
+    C {
+        inner: BufferMapCallbackC,
+    },
+}
+
+impl BufferMapCallback {
+    pub fn from_rust(callback: Box<dyn FnOnce(BufferAccessResult) + Send + 'static>) -> Self {
+        Self {
+            inner: BufferMapCallbackInner::Rust { callback },
+        }
+    }
+
+    /// # Safety
+    ///
+    /// - The callback pointer must be valid to call with the provided user_data pointer.
+    /// - Both pointers must point to `'static` data, as the callback may happen at an
+    ///   unspecified time.
+    pub unsafe fn from_c(inner: BufferMapCallbackC) -> Self {
+        Self {
+            inner: BufferMapCallbackInner::C { inner },
+        }
+    }
+
+    pub(crate) fn call(self, result: BufferAccessResult) {
+        match self.inner {
+            BufferMapCallbackInner::Rust { callback } => callback(result),
+            // Safety: the contract of the call to from_c says that this call is sound.
+            BufferMapCallbackInner::C { inner } => unsafe {
+                let status = match result {
+                    Ok(()) => BufferMapAsyncStatus::Success,
+                    Err(BufferAccessError::MapAborted) => BufferMapAsyncStatus::Aborted,
+                    Err(BufferAccessError::Device(DeviceError::Lost)) => {
+                        BufferMapAsyncStatus::ContextLost
+                    }
+                    Err(_) => BufferMapAsyncStatus::Error,
+                };
+                (inner.callback)(status, inner.user_data)
+            },
+        }
+    }
+}
 
-impl BufferMapOperation {
-    pub(crate) fn call_error(self) {
-        log::error!("wgpu_buffer_map_async failed: buffer mapping is pending");
-        unsafe {
-            (self.callback)(BufferMapAsyncStatus::Error, self.user_data);
+impl std::fmt::Debug for BufferMapCallback {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.inner {
+            BufferMapCallbackInner::Rust { .. } => f.write_str("BufferMapCallback::Rust"),
+            BufferMapCallbackInner::C { ref inner } => {
+                write!(f, "BufferMapCallback::C({:?})", inner)
+            }
         }
     }
 }
 
+#[derive(Debug)]
+pub struct BufferMapOperation {
+    pub host: HostMap,
+    pub callback: BufferMapCallback,
+}
+
 #[derive(Clone, Debug, Error)]
 pub enum BufferAccessError {
     #[error(transparent)]
@@ -102,8 +162,14 @@ pub enum BufferAccessError {
         index: wgt::BufferAddress,
         max: wgt::BufferAddress,
     },
+    #[error("buffer map is already pending")]
+    MapAlreadyPending,
+    #[error("buffer map was aborted by an unmap")]
+    MapAborted,
 }
 
+pub type BufferAccessResult = Result<(), BufferAccessError>;
+
 #[derive(Debug)]
 pub(crate) struct BufferPendingMapping {
     pub range: Range<wgt::BufferAddress>,
@@ -154,7 +220,7 @@ impl<A: hal::Api> Borrow<()> for Buffer<A> {
     }
 }
 
-pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>>;
+pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>, Vec<wgt::TextureFormat>>;
 
 #[derive(Debug)]
 pub(crate) enum TextureInner<A: hal::Api> {
@@ -178,13 +244,62 @@ impl<A: hal::Api> TextureInner<A> {
     }
 }
 
+/// The way a texture is zero-filled before its contents are first used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub(crate) enum TextureClearMode {
+    /// By copying from the device's zero buffer, which requires the `COPY_DST` usage.
+    BufferCopy,
+    /// With a render pass clearing the attachment, which requires the render target usage.
+    /// Used for the depth/stencil and multisampled textures, which can't be copied into,
+    /// and for the 2D render targets that aren't created with `COPY_DST`.
+    RenderPass { is_color: bool },
+    /// The texture is never cleared and is considered initialized from the start.
+    /// This is the case for surface textures and textures created from raw handles.
+    None,
+}
+
+impl TextureClearMode {
+    pub(crate) fn new(desc: &wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>) -> Self {
+        let is_color = hal::FormatAspects::from(desc.format).contains(hal::FormatAspects::COLOR);
+        if !is_color || desc.sample_count > 1 {
+            return Self::RenderPass { is_color };
+        }
+        // Compressed formats can't be rendered to, and not all backends can clear them
+        // in place (e.g. `vkCmdClearColorImage` doesn't accept BC formats).
+        if desc.format.describe().block_dimensions != (1, 1) {
+            return Self::BufferCopy;
+        }
+        // Avoid adding an internal `COPY_DST` usage if the texture can be cleared as is.
+        if desc.dimension == wgt::TextureDimension::D2
+            && desc.usage.contains(wgt::TextureUsages::RENDER_ATTACHMENT)
+            && !desc.usage.contains(wgt::TextureUsages::COPY_DST)
+        {
+            Self::RenderPass { is_color }
+        } else {
+            Self::BufferCopy
+        }
+    }
+
+    /// Internal usages that the texture needs to be created with to be cleared.
+    pub(crate) fn hal_usage(self) -> hal::TextureUses {
+        match self {
+            Self::BufferCopy => hal::TextureUses::COPY_DST,
+            Self::RenderPass { is_color: true } => hal::TextureUses::COLOR_TARGET,
+            Self::RenderPass { is_color: false } => hal::TextureUses::DEPTH_STENCIL_WRITE,
+            Self::None => hal::TextureUses::empty(),
+        }
+    }
+}
+
 #[derive(Debug)]
 pub struct Texture<A: hal::Api> {
     pub(crate) inner: TextureInner<A>,
     pub(crate) device_id: Stored<DeviceId>,
-    pub(crate) desc: wgt::TextureDescriptor<()>,
+    pub(crate) desc: wgt::TextureDescriptor<(), Vec<wgt::TextureFormat>>,
     pub(crate) hal_usage: hal::TextureUses,
     pub(crate) format_features: wgt::TextureFormatFeatures,
+    pub(crate) initialization_status: TextureInitTracker,
+    pub(crate) clear_mode: TextureClearMode,
     pub(crate) full_range: TextureSelector,
     pub(crate) life_guard: LifeGuard,
 }
@@ -247,6 +362,13 @@ pub enum CreateTextureError {
     InvalidUsages(wgt::TextureUsages, wgt::TextureFormat),
     #[error("Texture format {0:?} can't be used")]
     MissingFeatures(wgt::TextureFormat, #[source] MissingFeatures),
+    #[error("Texture of format {texture:?} can't be viewed as {view:?}")]
+    InvalidViewFormat {
+        texture: wgt::TextureFormat,
+        view: wgt::TextureFormat,
+    },
+    #[error(transparent)]
+    MissingDownlevelFlags(#[from] MissingDownlevelFlags),
 }
 
 impl<A: hal::Api> Resource for Texture<A> {
@@ -271,7 +393,7 @@ pub struct TextureViewDescriptor<'a> {
     /// Debug label of the texture view. This will show up in graphics debuggers for easy identification.
     pub label: Label<'a>,
     /// Format of the texture view, or `None` for the same format as the texture itself.
-    /// At this time, it must be the same the underlying format of the texture.
+    /// It must be either the format of the texture or one of its `view_formats`.
     pub format: Option<wgt::TextureFormat>,
     /// The dimension of the texture view. For 1D textures, this must be `1D`. For 2D textures it must be one of
     /// `D2`, `D2Array`, `Cube`, and `CubeArray`. For 3D textures it must be `3D`
@@ -344,6 +466,8 @@ pub enum CreateTextureViewError {
         texture: wgt::TextureFormat,
         view: wgt::TextureFormat,
     },
+    #[error("Texture format {0:?} can't be used")]
+    MissingFeatures(wgt::TextureFormat, #[source] MissingFeatures),
 }
 
 #[derive(Clone, Debug, Error)]
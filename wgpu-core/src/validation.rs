@@ -1,4 +1,8 @@
-use crate::{binding_model::BindEntryMap, FastHashMap, FastHashSet};
+use crate::{
+    binding_model::BindEntryMap,
+    diagnostic::{self, ShaderDiagnostic},
+    FastHashMap, FastHashSet,
+};
 use naga::valid::GlobalUse;
 use std::{collections::hash_map::Entry, fmt};
 use thiserror::Error;
@@ -98,6 +102,7 @@ struct SpecializationConstant {
 
 #[derive(Debug, Default)]
 struct EntryPoint {
+    span: naga::Span,
     inputs: Vec<Varying>,
     outputs: Vec<Varying>,
     resources: Vec<(naga::Handle<Resource>, GlobalUse)>,
@@ -647,14 +652,20 @@ impl NumericType {
         use wgt::TextureFormat as Tf;
 
         let (dim, kind) = match format {
-            Tf::R8Unorm | Tf::R8Snorm | Tf::R16Float | Tf::R32Float => {
-                (NumericDimension::Scalar, Sk::Float)
-            }
+            Tf::R8Unorm
+            | Tf::R8Snorm
+            | Tf::R16Unorm
+            | Tf::R16Snorm
+            | Tf::R16Float
+            | Tf::R32Float => (NumericDimension::Scalar, Sk::Float),
             Tf::R8Uint | Tf::R16Uint | Tf::R32Uint => (NumericDimension::Scalar, Sk::Uint),
             Tf::R8Sint | Tf::R16Sint | Tf::R32Sint => (NumericDimension::Scalar, Sk::Sint),
-            Tf::Rg8Unorm | Tf::Rg8Snorm | Tf::Rg16Float | Tf::Rg32Float => {
-                (NumericDimension::Vector(Vs::Bi), Sk::Float)
-            }
+            Tf::Rg8Unorm
+            | Tf::Rg8Snorm
+            | Tf::Rg16Unorm
+            | Tf::Rg16Snorm
+            | Tf::Rg16Float
+            | Tf::Rg32Float => (NumericDimension::Vector(Vs::Bi), Sk::Float),
             Tf::Rg8Uint | Tf::Rg16Uint | Tf::Rg32Uint => {
                 (NumericDimension::Vector(Vs::Bi), Sk::Uint)
             }
@@ -667,6 +678,8 @@ impl NumericType {
             | Tf::Bgra8Unorm
             | Tf::Bgra8UnormSrgb
             | Tf::Rgb10a2Unorm
+            | Tf::Rgba16Unorm
+            | Tf::Rgba16Snorm
             | Tf::Rgba16Float
             | Tf::Rgba32Float => (NumericDimension::Vector(Vs::Quad), Sk::Float),
             Tf::Rgba8Uint | Tf::Rgba16Uint | Tf::Rgba32Uint => {
@@ -676,7 +689,12 @@ impl NumericType {
                 (NumericDimension::Vector(Vs::Quad), Sk::Sint)
             }
             Tf::Rg11b10Float => (NumericDimension::Vector(Vs::Tri), Sk::Float),
-            Tf::Depth32Float | Tf::Depth24Plus | Tf::Depth24PlusStencil8 => {
+            Tf::Stencil8 => (NumericDimension::Scalar, Sk::Uint),
+            Tf::Depth16Unorm
+            | Tf::Depth32Float
+            | Tf::Depth32FloatStencil8
+            | Tf::Depth24Plus
+            | Tf::Depth24PlusStencil8 => {
                 panic!("Unexpected depth format")
             }
             Tf::Rgb9e5Ufloat => (NumericDimension::Vector(Vs::Tri), Sk::Float),
@@ -888,7 +906,7 @@ impl Interface {
                     ty,
                     class: var.class,
                 },
-                Default::default(),
+                module.global_variables.get_span(var_handle),
             );
             resource_mapping.insert(var_handle, handle);
         }
@@ -897,7 +915,10 @@ impl Interface {
         entry_points.reserve(module.entry_points.len());
         for (index, entry_point) in (&module.entry_points).iter().enumerate() {
             let info = info.get_entry_point(index);
-            let mut ep = EntryPoint::default();
+            let mut ep = EntryPoint {
+                span: diagnostic::function_span(&entry_point.function),
+                ..Default::default()
+            };
             for arg in entry_point.function.arguments.iter() {
                 Self::populate(&mut ep.inputs, arg.binding.as_ref(), arg.ty, &module.types);
             }
@@ -930,6 +951,58 @@ impl Interface {
         }
     }
 
+    fn shader_stage(stage_bit: wgt::ShaderStages) -> naga::ShaderStage {
+        match stage_bit {
+            wgt::ShaderStages::VERTEX => naga::ShaderStage::Vertex,
+            wgt::ShaderStages::FRAGMENT => naga::ShaderStage::Fragment,
+            wgt::ShaderStages::COMPUTE => naga::ShaderStage::Compute,
+            _ => unreachable!(),
+        }
+    }
+
+    fn resource_span(&self, bind: &naga::ResourceBinding) -> naga::Span {
+        self.resources
+            .iter()
+            .find(|&(_, res)| res.bind == *bind)
+            .map_or_else(naga::Span::default, |(handle, _)| {
+                self.resources.get_span(handle)
+            })
+    }
+
+    /// Returns the diagnostic of an error of `check_stage`, pointing at the
+    /// resources and entry point involved.
+    pub fn stage_error_diagnostic(
+        &self,
+        error: &StageError,
+        stage_bit: wgt::ShaderStages,
+        entry_point_name: &str,
+    ) -> ShaderDiagnostic {
+        let diagnostic = ShaderDiagnostic::from_error(error);
+        match *error {
+            StageError::InvalidModule | StageError::MissingEntryPoint(_) => diagnostic,
+            StageError::Binding(ref bind, _) => {
+                diagnostic.with_label(self.resource_span(bind), "declared here")
+            }
+            StageError::Filtering {
+                ref texture,
+                ref sampler,
+                ..
+            } => diagnostic
+                .with_label(self.resource_span(texture), "texture declared here")
+                .with_label(self.resource_span(sampler), "sampler declared here"),
+            StageError::Input { location, .. } => {
+                let pair = (Self::shader_stage(stage_bit), entry_point_name.to_string());
+                match self.entry_points.get(&pair) {
+                    Some(ep) => diagnostic.with_label(
+                        ep.span,
+                        format!("input at location {} is used here", location),
+                    ),
+                    None => diagnostic,
+                }
+            }
+        }
+    }
+
     pub fn check_stage(
         &self,
         given_layouts: Option<&[&BindEntryMap]>,
@@ -940,12 +1013,7 @@ impl Interface {
     ) -> Result<StageIo, StageError> {
         // Since a shader module can have multiple entry points with the same name,
         // we need to look for one with the right execution model.
-        let shader_stage = match stage_bit {
-            wgt::ShaderStages::VERTEX => naga::ShaderStage::Vertex,
-            wgt::ShaderStages::FRAGMENT => naga::ShaderStage::Fragment,
-            wgt::ShaderStages::COMPUTE => naga::ShaderStage::Compute,
-            _ => unreachable!(),
-        };
+        let shader_stage = Self::shader_stage(stage_bit);
         let pair = (shader_stage, entry_point_name.to_string());
         let entry_point = self
             .entry_points
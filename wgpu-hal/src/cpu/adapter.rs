@@ -0,0 +1,212 @@
+use std::time::{Duration, Instant};
+
+impl crate::Instance<super::Api> for super::Instance {
+    unsafe fn init(desc: &crate::InstanceDescriptor) -> Result<Self, crate::InstanceError> {
+        let loop_timeout = match std::env::var("WGPU_CPU_LOOP_TIMEOUT_MS") {
+            Ok(value) => match value.parse::<u64>() {
+                Ok(0) => None,
+                Ok(ms) => Some(Duration::from_millis(ms)),
+                Err(_) => {
+                    log::warn!("Invalid WGPU_CPU_LOOP_TIMEOUT_MS value {:?}", value);
+                    Some(super::DEFAULT_LOOP_TIMEOUT)
+                }
+            },
+            Err(_) => Some(super::DEFAULT_LOOP_TIMEOUT),
+        };
+        Ok(Self {
+            flags: desc.flags,
+            loop_timeout,
+        })
+    }
+
+    unsafe fn create_surface(
+        &self,
+        _has_handle: &impl raw_window_handle::HasRawWindowHandle,
+    ) -> Result<super::Surface, crate::InstanceError> {
+        Err(crate::InstanceError)
+    }
+
+    unsafe fn destroy_surface(&self, _surface: super::Surface) {}
+
+    unsafe fn enumerate_adapters(&self) -> Vec<crate::ExposedAdapter<super::Api>> {
+        if self.flags.contains(crate::InstanceFlags::VALIDATION) {
+            log::info!("Validation is not supported by the CPU backend");
+        }
+
+        let features = wgt::Features::DEPTH_CLAMPING
+            | wgt::Features::MAPPABLE_PRIMARY_BUFFERS
+            | wgt::Features::MULTI_DRAW_INDIRECT
+            | wgt::Features::MULTI_DRAW_INDIRECT_COUNT
+            | wgt::Features::PUSH_CONSTANTS
+            | wgt::Features::ADDRESS_MODE_CLAMP_TO_BORDER
+            | wgt::Features::TIMESTAMP_QUERY
+            | wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
+            | wgt::Features::VERTEX_WRITABLE_STORAGE
+            | wgt::Features::CLEAR_COMMANDS
+            | wgt::Features::SHADER_PRIMITIVE_INDEX
+            | wgt::Features::TEXTURE_FORMAT_16BIT_NORM
+            | wgt::Features::TEXTURE_FORMAT_STENCIL8;
+
+        let capabilities = crate::Capabilities {
+            limits: wgt::Limits {
+                max_push_constant_size: super::MAX_PUSH_CONSTANT_SIZE,
+                ..wgt::Limits::default()
+            },
+            alignments: crate::Alignments {
+                buffer_copy_offset: wgt::BufferSize::new(4).unwrap(),
+                buffer_copy_pitch: wgt::BufferSize::new(4).unwrap(),
+            },
+            downlevel: wgt::DownlevelCapabilities::default(),
+        };
+
+        vec![crate::ExposedAdapter {
+            adapter: super::Adapter {
+                loop_timeout: self.loop_timeout,
+            },
+            info: wgt::AdapterInfo {
+                name: "CPU".to_string(),
+                vendor: 0,
+                device: 0,
+                device_type: wgt::DeviceType::Cpu,
+                backend: wgt::Backend::Cpu,
+            },
+            features,
+            capabilities,
+        }]
+    }
+}
+
+impl crate::Surface<super::Api> for super::Surface {
+    unsafe fn configure(
+        &mut self,
+        _device: &super::Device,
+        _config: &crate::SurfaceConfiguration,
+    ) -> Result<(), crate::SurfaceError> {
+        Err(crate::SurfaceError::Lost)
+    }
+
+    unsafe fn unconfigure(&mut self, _device: &super::Device) {}
+
+    unsafe fn acquire_texture(
+        &mut self,
+        _timeout_ms: u32,
+    ) -> Result<Option<crate::AcquiredSurfaceTexture<super::Api>>, crate::SurfaceError> {
+        Err(crate::SurfaceError::Lost)
+    }
+
+    unsafe fn discard_texture(&mut self, _texture: super::Texture) {}
+}
+
+impl crate::Adapter<super::Api> for super::Adapter {
+    unsafe fn open(
+        &self,
+        _features: wgt::Features,
+    ) -> Result<crate::OpenDevice<super::Api>, crate::DeviceError> {
+        Ok(crate::OpenDevice {
+            device: super::Device {
+                loop_timeout: self.loop_timeout,
+            },
+            queue: super::Queue {
+                start_time: Instant::now(),
+                workers: parking_lot::Mutex::new(super::queue::WorkerPool::new()),
+            },
+        })
+    }
+
+    unsafe fn texture_format_capabilities(
+        &self,
+        format: wgt::TextureFormat,
+    ) -> crate::TextureFormatCapabilities {
+        use crate::TextureFormatCapabilities as Tfc;
+        use wgt::TextureFormat as Tf;
+
+        let copy = Tfc::COPY_SRC | Tfc::COPY_DST;
+        let unfiltered_color = copy | Tfc::SAMPLED | Tfc::COLOR_ATTACHMENT;
+        let filtered_color = unfiltered_color | Tfc::SAMPLED_LINEAR | Tfc::COLOR_ATTACHMENT_BLEND;
+        let storage = Tfc::STORAGE | Tfc::STORAGE_READ_WRITE;
+        let depth = copy | Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT;
+        match format {
+            Tf::R8Unorm | Tf::R8Snorm | Tf::Rg8Unorm | Tf::Rg8Snorm => filtered_color,
+            Tf::R8Uint | Tf::R8Sint | Tf::Rg8Uint | Tf::Rg8Sint => unfiltered_color,
+            Tf::R16Uint | Tf::R16Sint | Tf::Rg16Uint | Tf::Rg16Sint => unfiltered_color,
+            Tf::R16Float | Tf::Rg16Float => filtered_color,
+            Tf::R16Unorm | Tf::Rg16Unorm | Tf::Rgba16Unorm => filtered_color,
+            Tf::R16Snorm | Tf::Rg16Snorm | Tf::Rgba16Snorm => {
+                copy | Tfc::SAMPLED | Tfc::SAMPLED_LINEAR
+            }
+            Tf::R32Uint | Tf::R32Sint => unfiltered_color | storage | Tfc::STORAGE_ATOMIC,
+            Tf::R32Float | Tf::Rg32Float | Tf::Rgba32Float => filtered_color | storage,
+            Tf::Rg32Uint | Tf::Rg32Sint | Tf::Rgba32Uint | Tf::Rgba32Sint => {
+                unfiltered_color | storage
+            }
+            Tf::Rgba8Unorm | Tf::Rgba8Snorm | Tf::Rgba16Float => filtered_color | storage,
+            Tf::Rgba8Uint | Tf::Rgba8Sint | Tf::Rgba16Uint | Tf::Rgba16Sint => {
+                unfiltered_color | storage
+            }
+            Tf::Rgba8UnormSrgb | Tf::Bgra8Unorm | Tf::Bgra8UnormSrgb => filtered_color,
+            Tf::Rgb10a2Unorm | Tf::Rg11b10Float => filtered_color,
+            Tf::Rgb9e5Ufloat => copy | Tfc::SAMPLED | Tfc::SAMPLED_LINEAR,
+            Tf::Depth32Float | Tf::Depth24Plus | Tf::Depth24PlusStencil8 => depth,
+            Tf::Depth16Unorm | Tf::Stencil8 => depth,
+            // The depth aspect alone fills the texel, leaving no room for the stencil.
+            Tf::Depth32FloatStencil8 => Tfc::empty(),
+            Tf::Bc1RgbaUnorm
+            | Tf::Bc1RgbaUnormSrgb
+            | Tf::Bc2RgbaUnorm
+            | Tf::Bc2RgbaUnormSrgb
+            | Tf::Bc3RgbaUnorm
+            | Tf::Bc3RgbaUnormSrgb
+            | Tf::Bc4RUnorm
+            | Tf::Bc4RSnorm
+            | Tf::Bc5RgUnorm
+            | Tf::Bc5RgSnorm
+            | Tf::Bc6hRgbSfloat
+            | Tf::Bc6hRgbUfloat
+            | Tf::Bc7RgbaUnorm
+            | Tf::Bc7RgbaUnormSrgb
+            | Tf::Etc2RgbUnorm
+            | Tf::Etc2RgbUnormSrgb
+            | Tf::Etc2RgbA1Unorm
+            | Tf::Etc2RgbA1UnormSrgb
+            | Tf::EacRUnorm
+            | Tf::EacRSnorm
+            | Tf::EacRgUnorm
+            | Tf::EacRgSnorm
+            | Tf::Astc4x4RgbaUnorm
+            | Tf::Astc4x4RgbaUnormSrgb
+            | Tf::Astc5x4RgbaUnorm
+            | Tf::Astc5x4RgbaUnormSrgb
+            | Tf::Astc5x5RgbaUnorm
+            | Tf::Astc5x5RgbaUnormSrgb
+            | Tf::Astc6x5RgbaUnorm
+            | Tf::Astc6x5RgbaUnormSrgb
+            | Tf::Astc6x6RgbaUnorm
+            | Tf::Astc6x6RgbaUnormSrgb
+            | Tf::Astc8x5RgbaUnorm
+            | Tf::Astc8x5RgbaUnormSrgb
+            | Tf::Astc8x6RgbaUnorm
+            | Tf::Astc8x6RgbaUnormSrgb
+            | Tf::Astc10x5RgbaUnorm
+            | Tf::Astc10x5RgbaUnormSrgb
+            | Tf::Astc10x6RgbaUnorm
+            | Tf::Astc10x6RgbaUnormSrgb
+            | Tf::Astc8x8RgbaUnorm
+            | Tf::Astc8x8RgbaUnormSrgb
+            | Tf::Astc10x8RgbaUnorm
+            | Tf::Astc10x8RgbaUnormSrgb
+            | Tf::Astc10x10RgbaUnorm
+            | Tf::Astc10x10RgbaUnormSrgb
+            | Tf::Astc12x10RgbaUnorm
+            | Tf::Astc12x10RgbaUnormSrgb
+            | Tf::Astc12x12RgbaUnorm
+            | Tf::Astc12x12RgbaUnormSrgb => Tfc::empty(),
+        }
+    }
+
+    unsafe fn surface_capabilities(
+        &self,
+        _surface: &super::Surface,
+    ) -> Option<crate::SurfaceCapabilities> {
+        None
+    }
+}
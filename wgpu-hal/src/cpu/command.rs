@@ -0,0 +1,369 @@
+use super::{Command as C, DrawCount};
+use std::{mem, ops::Range, sync::Arc};
+
+impl super::CommandEncoder {
+    fn push(&mut self, command: super::Command) {
+        self.cmd_buffer.commands.push(command);
+    }
+}
+
+impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
+    unsafe fn begin_encoding(&mut self, _label: crate::Label) -> Result<(), crate::DeviceError> {
+        self.cmd_buffer.commands.clear();
+        Ok(())
+    }
+    unsafe fn discard_encoding(&mut self) {
+        self.cmd_buffer.commands.clear();
+    }
+    unsafe fn end_encoding(&mut self) -> Result<super::CommandBuffer, crate::DeviceError> {
+        Ok(mem::take(&mut self.cmd_buffer))
+    }
+    unsafe fn reset_all<I>(&mut self, _command_buffers: I) {}
+
+    unsafe fn transition_buffers<'a, T>(&mut self, _barriers: T)
+    where
+        T: Iterator<Item = crate::BufferBarrier<'a, super::Api>>,
+    {
+    }
+
+    unsafe fn transition_textures<'a, T>(&mut self, _barriers: T)
+    where
+        T: Iterator<Item = crate::TextureBarrier<'a, super::Api>>,
+    {
+    }
+
+    unsafe fn clear_buffer(&mut self, buffer: &super::Buffer, range: crate::MemoryRange) {
+        self.push(C::ClearBuffer {
+            dst: Arc::clone(&buffer.memory),
+            range,
+        });
+    }
+
+    unsafe fn clear_texture(
+        &mut self,
+        texture: &super::Texture,
+        subresource_range: &wgt::ImageSubresourceRange,
+    ) {
+        self.push(C::ClearTexture {
+            dst: Arc::clone(&texture.image),
+            range: subresource_range.clone(),
+        });
+    }
+
+    unsafe fn copy_buffer_to_buffer<T>(
+        &mut self,
+        src: &super::Buffer,
+        dst: &super::Buffer,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::BufferCopy>,
+    {
+        for copy in regions {
+            self.push(C::CopyBufferToBuffer {
+                src: Arc::clone(&src.memory),
+                dst: Arc::clone(&dst.memory),
+                copy,
+            });
+        }
+    }
+
+    unsafe fn copy_texture_to_texture<T>(
+        &mut self,
+        src: &super::Texture,
+        _src_usage: crate::TextureUses,
+        dst: &super::Texture,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::TextureCopy>,
+    {
+        for copy in regions {
+            self.push(C::CopyTextureToTexture {
+                src: Arc::clone(&src.image),
+                dst: Arc::clone(&dst.image),
+                copy,
+            });
+        }
+    }
+
+    unsafe fn copy_buffer_to_texture<T>(
+        &mut self,
+        src: &super::Buffer,
+        dst: &super::Texture,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::BufferTextureCopy>,
+    {
+        for copy in regions {
+            self.push(C::CopyBufferToTexture {
+                src: Arc::clone(&src.memory),
+                dst: Arc::clone(&dst.image),
+                copy,
+            });
+        }
+    }
+
+    unsafe fn copy_texture_to_buffer<T>(
+        &mut self,
+        src: &super::Texture,
+        _src_usage: crate::TextureUses,
+        dst: &super::Buffer,
+        regions: T,
+    ) where
+        T: Iterator<Item = crate::BufferTextureCopy>,
+    {
+        for copy in regions {
+            self.push(C::CopyTextureToBuffer {
+                src: Arc::clone(&src.image),
+                dst: Arc::clone(&dst.memory),
+                copy,
+            });
+        }
+    }
+
+    unsafe fn begin_query(&mut self, set: &super::QuerySet, index: u32) {
+        self.push(C::BeginQuery(Arc::clone(&set.results), index));
+    }
+    unsafe fn end_query(&mut self, _set: &super::QuerySet, _index: u32) {
+        self.push(C::EndQuery);
+    }
+    unsafe fn write_timestamp(&mut self, set: &super::QuerySet, index: u32) {
+        self.push(C::WriteTimestamp(Arc::clone(&set.results), index));
+    }
+    unsafe fn reset_queries(&mut self, set: &super::QuerySet, range: Range<u32>) {
+        self.push(C::ResetQueries(Arc::clone(&set.results), range));
+    }
+    unsafe fn copy_query_results(
+        &mut self,
+        set: &super::QuerySet,
+        range: Range<u32>,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        stride: wgt::BufferSize,
+    ) {
+        self.push(C::CopyQueryResults {
+            results: Arc::clone(&set.results),
+            range,
+            dst: Arc::clone(&buffer.memory),
+            offset,
+            stride: stride.get(),
+        });
+    }
+
+    // render
+
+    unsafe fn begin_render_pass(&mut self, desc: &crate::RenderPassDescriptor<super::Api>) {
+        let color_targets = desc
+            .color_attachments
+            .iter()
+            .map(|cat| super::ColorTarget {
+                view: cat.target.view.clone(),
+                resolve_view: cat.resolve_target.as_ref().map(|rat| rat.view.clone()),
+                ops: cat.ops,
+                clear_value: cat.clear_value,
+            })
+            .collect();
+        let depth_stencil_target =
+            desc.depth_stencil_attachment
+                .as_ref()
+                .map(|dsat| super::DepthStencilTarget {
+                    view: dsat.target.view.clone(),
+                    depth_ops: dsat.depth_ops,
+                    stencil_ops: dsat.stencil_ops,
+                    clear_value: dsat.clear_value,
+                });
+        self.push(C::BeginRenderPass(super::RenderPassDesc {
+            extent: desc.extent,
+            color_targets,
+            depth_stencil_target,
+        }));
+    }
+    unsafe fn end_render_pass(&mut self) {
+        self.push(C::EndRenderPass);
+    }
+
+    unsafe fn set_bind_group(
+        &mut self,
+        _layout: &super::PipelineLayout,
+        index: u32,
+        group: &super::BindGroup,
+        dynamic_offsets: &[wgt::DynamicOffset],
+    ) {
+        self.push(C::SetBindGroup {
+            index,
+            entries: Arc::clone(&group.entries),
+            dynamic_offsets: dynamic_offsets.to_vec(),
+        });
+    }
+    unsafe fn set_push_constants(
+        &mut self,
+        _layout: &super::PipelineLayout,
+        _stages: wgt::ShaderStages,
+        offset: u32,
+        data: &[u32],
+    ) {
+        self.push(C::SetPushConstants {
+            offset,
+            data: data.to_vec(),
+        });
+    }
+
+    unsafe fn insert_debug_marker(&mut self, _label: &str) {}
+    unsafe fn begin_debug_marker(&mut self, _group_label: &str) {}
+    unsafe fn end_debug_marker(&mut self) {}
+
+    unsafe fn set_render_pipeline(&mut self, pipeline: &super::RenderPipeline) {
+        self.push(C::SetRenderPipeline(Arc::clone(&pipeline.inner)));
+    }
+
+    unsafe fn set_index_buffer<'a>(
+        &mut self,
+        binding: crate::BufferBinding<'a, super::Api>,
+        format: wgt::IndexFormat,
+    ) {
+        self.push(C::SetIndexBuffer {
+            memory: Arc::clone(&binding.buffer.memory),
+            offset: binding.offset,
+            format,
+        });
+    }
+    unsafe fn set_vertex_buffer<'a>(
+        &mut self,
+        index: u32,
+        binding: crate::BufferBinding<'a, super::Api>,
+    ) {
+        self.push(C::SetVertexBuffer {
+            index,
+            memory: Arc::clone(&binding.buffer.memory),
+            offset: binding.offset,
+        });
+    }
+    unsafe fn set_viewport(&mut self, rect: &crate::Rect<f32>, depth_range: Range<f32>) {
+        self.push(C::SetViewport {
+            rect: rect.clone(),
+            depth: depth_range,
+        });
+    }
+    unsafe fn set_scissor_rect(&mut self, rect: &crate::Rect<u32>) {
+        self.push(C::SetScissor(rect.clone()));
+    }
+    unsafe fn set_stencil_reference(&mut self, value: u32) {
+        self.push(C::SetStencilReference(value));
+    }
+    unsafe fn set_blend_constants(&mut self, color: &[f32; 4]) {
+        self.push(C::SetBlendConstants(*color));
+    }
+
+    unsafe fn draw(
+        &mut self,
+        start_vertex: u32,
+        vertex_count: u32,
+        start_instance: u32,
+        instance_count: u32,
+    ) {
+        self.push(C::Draw {
+            start_vertex,
+            vertex_count,
+            start_instance,
+            instance_count,
+        });
+    }
+    unsafe fn draw_indexed(
+        &mut self,
+        start_index: u32,
+        index_count: u32,
+        base_vertex: i32,
+        start_instance: u32,
+        instance_count: u32,
+    ) {
+        self.push(C::DrawIndexed {
+            start_index,
+            index_count,
+            base_vertex,
+            start_instance,
+            instance_count,
+        });
+    }
+    unsafe fn draw_indirect(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        draw_count: u32,
+    ) {
+        self.push(C::DrawIndirect {
+            memory: Arc::clone(&buffer.memory),
+            offset,
+            count: DrawCount::Direct(draw_count),
+            indexed: false,
+        });
+    }
+    unsafe fn draw_indexed_indirect(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        draw_count: u32,
+    ) {
+        self.push(C::DrawIndirect {
+            memory: Arc::clone(&buffer.memory),
+            offset,
+            count: DrawCount::Direct(draw_count),
+            indexed: true,
+        });
+    }
+    unsafe fn draw_indirect_count(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        count_buffer: &super::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    ) {
+        self.push(C::DrawIndirect {
+            memory: Arc::clone(&buffer.memory),
+            offset,
+            count: DrawCount::Indirect {
+                memory: Arc::clone(&count_buffer.memory),
+                offset: count_offset,
+                max_count,
+            },
+            indexed: false,
+        });
+    }
+    unsafe fn draw_indexed_indirect_count(
+        &mut self,
+        buffer: &super::Buffer,
+        offset: wgt::BufferAddress,
+        count_buffer: &super::Buffer,
+        count_offset: wgt::BufferAddress,
+        max_count: u32,
+    ) {
+        self.push(C::DrawIndirect {
+            memory: Arc::clone(&buffer.memory),
+            offset,
+            count: DrawCount::Indirect {
+                memory: Arc::clone(&count_buffer.memory),
+                offset: count_offset,
+                max_count,
+            },
+            indexed: true,
+        });
+    }
+
+    // compute
+
+    unsafe fn begin_compute_pass(&mut self, _desc: &crate::ComputePassDescriptor) {}
+    unsafe fn end_compute_pass(&mut self) {}
+
+    unsafe fn set_compute_pipeline(&mut self, pipeline: &super::ComputePipeline) {
+        self.push(C::SetComputePipeline(Arc::clone(&pipeline.program)));
+    }
+
+    unsafe fn dispatch(&mut self, count: [u32; 3]) {
+        self.push(C::Dispatch(count));
+    }
+    unsafe fn dispatch_indirect(&mut self, buffer: &super::Buffer, offset: wgt::BufferAddress) {
+        self.push(C::DispatchIndirect {
+            memory: Arc::clone(&buffer.memory),
+            offset,
+        });
+    }
+}
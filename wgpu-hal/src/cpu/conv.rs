@@ -0,0 +1,597 @@
+//! Conversions between the stored texel/vertex data and the values seen by the shaders.
+//!
+//! A texel is represented by 4 raw 32-bit components, which are interpreted
+//! according to the sample type of the format: as `f32` bits for the float formats,
+//! as `u32` for the unsigned and as `i32` for the signed integer ones.
+
+use wgt::TextureFormat as Tf;
+
+pub type Texel = [u32; 4];
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ScalarKind {
+    Float,
+    Uint,
+    Sint,
+}
+
+#[derive(Clone, Copy)]
+enum Component {
+    Unorm8,
+    Snorm8,
+    Uint8,
+    Sint8,
+    Unorm16,
+    Snorm16,
+    Uint16,
+    Sint16,
+    Float16,
+    Uint32,
+    Sint32,
+    Float32,
+}
+
+impl Component {
+    fn size(self) -> usize {
+        match self {
+            Self::Unorm8 | Self::Snorm8 | Self::Uint8 | Self::Sint8 => 1,
+            Self::Unorm16 | Self::Snorm16 | Self::Uint16 | Self::Sint16 | Self::Float16 => 2,
+            Self::Uint32 | Self::Sint32 | Self::Float32 => 4,
+        }
+    }
+
+    fn kind(self) -> ScalarKind {
+        match self {
+            Self::Uint8 | Self::Uint16 | Self::Uint32 => ScalarKind::Uint,
+            Self::Sint8 | Self::Sint16 | Self::Sint32 => ScalarKind::Sint,
+            _ => ScalarKind::Float,
+        }
+    }
+
+    fn decode(self, bytes: &[u8]) -> u32 {
+        let raw = match self.size() {
+            1 => bytes[0] as u32,
+            2 => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+            _ => u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
+        };
+        match self {
+            Self::Unorm8 => (raw as f32 / 255.0).to_bits(),
+            Self::Snorm8 => (raw as u8 as i8 as f32 / 127.0).max(-1.0).to_bits(),
+            Self::Unorm16 => (raw as f32 / 65535.0).to_bits(),
+            Self::Snorm16 => (raw as u16 as i16 as f32 / 32767.0).max(-1.0).to_bits(),
+            Self::Sint8 => raw as u8 as i8 as i32 as u32,
+            Self::Sint16 => raw as u16 as i16 as i32 as u32,
+            Self::Float16 => f16_to_f32(raw as u16).to_bits(),
+            Self::Uint8 | Self::Uint16 | Self::Uint32 | Self::Sint32 | Self::Float32 => raw,
+        }
+    }
+
+    fn encode(self, value: u32, bytes: &mut [u8]) {
+        let float = f32::from_bits(value);
+        let raw = match self {
+            Self::Unorm8 => (float.max(0.0).min(1.0) * 255.0 + 0.5) as u32,
+            Self::Snorm8 => (float.max(-1.0).min(1.0) * 127.0).round() as i32 as u32,
+            Self::Unorm16 => (float.max(0.0).min(1.0) * 65535.0 + 0.5) as u32,
+            Self::Snorm16 => (float.max(-1.0).min(1.0) * 32767.0).round() as i32 as u32,
+            Self::Float16 => f32_to_f16(float) as u32,
+            Self::Uint8 | Self::Sint8 | Self::Uint16 | Self::Sint16 => value,
+            Self::Uint32 | Self::Sint32 | Self::Float32 => value,
+        };
+        let size = self.size();
+        bytes[..size].copy_from_slice(&raw.to_le_bytes()[..size]);
+    }
+}
+
+enum Layout {
+    Plain(Component, usize),
+    Bgra8Unorm,
+    Rgb10a2Unorm,
+    Rg11b10Float,
+    Rgb9e5Ufloat,
+    /// Depth is stored as `f32`.
+    DepthFloat,
+    /// Depth is stored as a 16-bit normalized integer.
+    Depth16Unorm,
+    /// Depth is stored in the low 24 bits as normalized integer,
+    /// stencil is stored in the high 8 bits.
+    Depth24Stencil8,
+    /// Stencil is stored as `u8`, there is no depth.
+    Stencil8,
+    Compressed,
+}
+
+fn layout(format: wgt::TextureFormat) -> Layout {
+    use Component as C;
+    match format {
+        Tf::R8Unorm => Layout::Plain(C::Unorm8, 1),
+        Tf::R8Snorm => Layout::Plain(C::Snorm8, 1),
+        Tf::R8Uint => Layout::Plain(C::Uint8, 1),
+        Tf::R8Sint => Layout::Plain(C::Sint8, 1),
+        Tf::R16Uint => Layout::Plain(C::Uint16, 1),
+        Tf::R16Sint => Layout::Plain(C::Sint16, 1),
+        Tf::R16Unorm => Layout::Plain(C::Unorm16, 1),
+        Tf::R16Snorm => Layout::Plain(C::Snorm16, 1),
+        Tf::R16Float => Layout::Plain(C::Float16, 1),
+        Tf::Rg8Unorm => Layout::Plain(C::Unorm8, 2),
+        Tf::Rg8Snorm => Layout::Plain(C::Snorm8, 2),
+        Tf::Rg8Uint => Layout::Plain(C::Uint8, 2),
+        Tf::Rg8Sint => Layout::Plain(C::Sint8, 2),
+        Tf::R32Uint => Layout::Plain(C::Uint32, 1),
+        Tf::R32Sint => Layout::Plain(C::Sint32, 1),
+        Tf::R32Float => Layout::Plain(C::Float32, 1),
+        Tf::Rg16Uint => Layout::Plain(C::Uint16, 2),
+        Tf::Rg16Sint => Layout::Plain(C::Sint16, 2),
+        Tf::Rg16Unorm => Layout::Plain(C::Unorm16, 2),
+        Tf::Rg16Snorm => Layout::Plain(C::Snorm16, 2),
+        Tf::Rg16Float => Layout::Plain(C::Float16, 2),
+        Tf::Rgba8Unorm | Tf::Rgba8UnormSrgb => Layout::Plain(C::Unorm8, 4),
+        Tf::Rgba8Snorm => Layout::Plain(C::Snorm8, 4),
+        Tf::Rgba8Uint => Layout::Plain(C::Uint8, 4),
+        Tf::Rgba8Sint => Layout::Plain(C::Sint8, 4),
+        Tf::Bgra8Unorm | Tf::Bgra8UnormSrgb => Layout::Bgra8Unorm,
+        Tf::Rgb10a2Unorm => Layout::Rgb10a2Unorm,
+        Tf::Rg11b10Float => Layout::Rg11b10Float,
+        Tf::Rg32Uint => Layout::Plain(C::Uint32, 2),
+        Tf::Rg32Sint => Layout::Plain(C::Sint32, 2),
+        Tf::Rg32Float => Layout::Plain(C::Float32, 2),
+        Tf::Rgba16Uint => Layout::Plain(C::Uint16, 4),
+        Tf::Rgba16Sint => Layout::Plain(C::Sint16, 4),
+        Tf::Rgba16Unorm => Layout::Plain(C::Unorm16, 4),
+        Tf::Rgba16Snorm => Layout::Plain(C::Snorm16, 4),
+        Tf::Rgba16Float => Layout::Plain(C::Float16, 4),
+        Tf::Rgba32Uint => Layout::Plain(C::Uint32, 4),
+        Tf::Rgba32Sint => Layout::Plain(C::Sint32, 4),
+        Tf::Rgba32Float => Layout::Plain(C::Float32, 4),
+        Tf::Rgb9e5Ufloat => Layout::Rgb9e5Ufloat,
+        Tf::Depth32Float | Tf::Depth24Plus | Tf::Depth32FloatStencil8 => Layout::DepthFloat,
+        Tf::Depth24PlusStencil8 => Layout::Depth24Stencil8,
+        Tf::Depth16Unorm => Layout::Depth16Unorm,
+        Tf::Stencil8 => Layout::Stencil8,
+        _ => Layout::Compressed,
+    }
+}
+
+/// Returns the kind of values produced by the format.
+pub fn format_kind(format: wgt::TextureFormat) -> ScalarKind {
+    match layout(format) {
+        Layout::Plain(component, _) => component.kind(),
+        _ => ScalarKind::Float,
+    }
+}
+
+/// Returns the default texel, with all components but the alpha set to zero.
+pub fn default_texel(kind: ScalarKind) -> Texel {
+    let one = match kind {
+        ScalarKind::Float => 1f32.to_bits(),
+        ScalarKind::Uint | ScalarKind::Sint => 1,
+    };
+    [0, 0, 0, one]
+}
+
+/// Decodes a texel of a color format, or the depth aspect of a depth format.
+pub fn decode_texel(format: wgt::TextureFormat, bytes: &[u8]) -> Texel {
+    let mut texel = default_texel(format_kind(format));
+    match layout(format) {
+        Layout::Plain(component, count) => {
+            let size = component.size();
+            for (i, value) in texel.iter_mut().take(count).enumerate() {
+                *value = component.decode(&bytes[i * size..]);
+            }
+        }
+        Layout::Bgra8Unorm => {
+            for (i, &swizzle) in [2, 1, 0, 3].iter().enumerate() {
+                texel[swizzle] = Component::Unorm8.decode(&bytes[i..]);
+            }
+        }
+        Layout::Rgb10a2Unorm => {
+            let packed = read_u32(bytes);
+            for (i, value) in texel.iter_mut().take(3).enumerate() {
+                *value = (((packed >> (i * 10)) & 0x3ff) as f32 / 1023.0).to_bits();
+            }
+            texel[3] = ((packed >> 30) as f32 / 3.0).to_bits();
+        }
+        Layout::Rg11b10Float => {
+            let packed = read_u32(bytes);
+            texel[0] = small_float_to_f32(packed & 0x7ff, 6).to_bits();
+            texel[1] = small_float_to_f32((packed >> 11) & 0x7ff, 6).to_bits();
+            texel[2] = small_float_to_f32(packed >> 22, 5).to_bits();
+        }
+        Layout::Rgb9e5Ufloat => {
+            let packed = read_u32(bytes);
+            let scale = 2f32.powi((packed >> 27) as i32 - 15 - 9);
+            for (i, value) in texel.iter_mut().take(3).enumerate() {
+                *value = (((packed >> (i * 9)) & 0x1ff) as f32 * scale).to_bits();
+            }
+        }
+        Layout::DepthFloat | Layout::Depth16Unorm | Layout::Depth24Stencil8 => {
+            texel[0] = read_depth(format, bytes).to_bits();
+        }
+        Layout::Stencil8 => {
+            texel[0] = read_stencil(format, bytes);
+        }
+        Layout::Compressed => {
+            log::warn!("Compressed format {:?} can't be decoded", format);
+        }
+    }
+    if format.describe().srgb {
+        for value in texel.iter_mut().take(3) {
+            *value = srgb_to_linear(f32::from_bits(*value)).to_bits();
+        }
+    }
+    texel
+}
+
+/// Encodes a texel of a color format, or the depth aspect of a depth format.
+pub fn encode_texel(format: wgt::TextureFormat, mut texel: Texel, bytes: &mut [u8]) {
+    if format.describe().srgb {
+        for value in texel.iter_mut().take(3) {
+            *value = linear_to_srgb(f32::from_bits(*value)).to_bits();
+        }
+    }
+    let float = |i: usize| f32::from_bits(texel[i]);
+    match layout(format) {
+        Layout::Plain(component, count) => {
+            let size = component.size();
+            for (i, &value) in texel.iter().take(count).enumerate() {
+                component.encode(value, &mut bytes[i * size..]);
+            }
+        }
+        Layout::Bgra8Unorm => {
+            for (i, &swizzle) in [2, 1, 0, 3].iter().enumerate() {
+                Component::Unorm8.encode(texel[swizzle], &mut bytes[i..]);
+            }
+        }
+        Layout::Rgb10a2Unorm => {
+            let unorm = |value: f32, max: f32| (value.max(0.0).min(1.0) * max + 0.5) as u32;
+            let packed = unorm(float(0), 1023.0)
+                | unorm(float(1), 1023.0) << 10
+                | unorm(float(2), 1023.0) << 20
+                | unorm(float(3), 3.0) << 30;
+            bytes[..4].copy_from_slice(&packed.to_le_bytes());
+        }
+        Layout::Rg11b10Float => {
+            let packed = f32_to_small_float(float(0), 6)
+                | f32_to_small_float(float(1), 6) << 11
+                | f32_to_small_float(float(2), 5) << 22;
+            bytes[..4].copy_from_slice(&packed.to_le_bytes());
+        }
+        Layout::Rgb9e5Ufloat => {
+            let packed = encode_rgb9e5([float(0), float(1), float(2)]);
+            bytes[..4].copy_from_slice(&packed.to_le_bytes());
+        }
+        Layout::DepthFloat | Layout::Depth16Unorm | Layout::Depth24Stencil8 => {
+            write_depth(format, float(0), bytes)
+        }
+        Layout::Stencil8 => write_stencil(format, texel[0], bytes),
+        Layout::Compressed => {
+            log::warn!("Compressed format {:?} can't be encoded", format);
+        }
+    }
+}
+
+/// Converts a clear color into a texel of the given format.
+pub fn clear_texel(format: wgt::TextureFormat, color: wgt::Color) -> Texel {
+    let components = [color.r, color.g, color.b, color.a];
+    let mut texel = [0; 4];
+    for (value, &component) in texel.iter_mut().zip(components.iter()) {
+        *value = match format_kind(format) {
+            ScalarKind::Float => (component as f32).to_bits(),
+            ScalarKind::Uint => component as u32,
+            ScalarKind::Sint => component as i32 as u32,
+        };
+    }
+    texel
+}
+
+fn read_u32(bytes: &[u8]) -> u32 {
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+pub fn read_depth(format: wgt::TextureFormat, bytes: &[u8]) -> f32 {
+    match layout(format) {
+        Layout::Depth16Unorm => u16::from_le_bytes([bytes[0], bytes[1]]) as f32 / 65535.0,
+        Layout::Depth24Stencil8 => (read_u32(bytes) & 0xff_ffff) as f32 / 16_777_215.0,
+        Layout::Stencil8 => 0.0,
+        _ => f32::from_bits(read_u32(bytes)),
+    }
+}
+
+pub fn write_depth(format: wgt::TextureFormat, depth: f32, bytes: &mut [u8]) {
+    let packed = match layout(format) {
+        Layout::Depth16Unorm => {
+            let unorm = (depth.max(0.0).min(1.0) * 65535.0 + 0.5) as u16;
+            bytes[..2].copy_from_slice(&unorm.to_le_bytes());
+            return;
+        }
+        Layout::Stencil8 => return,
+        Layout::Depth24Stencil8 => {
+            let unorm = (depth.max(0.0).min(1.0) * 16_777_215.0 + 0.5) as u32;
+            (read_u32(bytes) & 0xff00_0000) | unorm
+        }
+        _ => depth.to_bits(),
+    };
+    bytes[..4].copy_from_slice(&packed.to_le_bytes());
+}
+
+pub fn read_stencil(format: wgt::TextureFormat, bytes: &[u8]) -> u32 {
+    match layout(format) {
+        Layout::Depth24Stencil8 => bytes[3] as u32,
+        Layout::Stencil8 => bytes[0] as u32,
+        _ => 0,
+    }
+}
+
+pub fn write_stencil(format: wgt::TextureFormat, stencil: u32, bytes: &mut [u8]) {
+    match layout(format) {
+        Layout::Depth24Stencil8 => bytes[3] = stencil as u8,
+        Layout::Stencil8 => bytes[0] = stencil as u8,
+        _ => {}
+    }
+}
+
+/// Returns the offset and the size in bytes of an aspect within a texel,
+/// as seen by the copy operations.
+pub fn aspect_bytes(format: wgt::TextureFormat, aspect: crate::FormatAspects) -> (usize, usize) {
+    let block_size = format.describe().block_size as usize;
+    match layout(format) {
+        Layout::Depth24Stencil8 if aspect == crate::FormatAspects::STENCIL => (3, 1),
+        _ => (0, block_size),
+    }
+}
+
+fn srgb_to_linear(value: f32) -> f32 {
+    if value <= 0.04045 {
+        value / 12.92
+    } else {
+        ((value + 0.055) / 1.055).powf(2.4)
+    }
+}
+
+fn linear_to_srgb(value: f32) -> f32 {
+    if value <= 0.003_130_8 {
+        value * 12.92
+    } else {
+        1.055 * value.powf(1.0 / 2.4) - 0.055
+    }
+}
+
+pub fn f16_to_f32(half: u16) -> f32 {
+    let sign = ((half >> 15) as u32) << 31;
+    let exponent = ((half >> 10) & 0x1f) as u32;
+    let mantissa = (half & 0x3ff) as u32;
+    let bits = match exponent {
+        0 if mantissa == 0 => sign,
+        0 => {
+            // subnormal, the unit is 2^-24
+            let value = mantissa as f32 / 16_777_216.0;
+            return if sign != 0 { -value } else { value };
+        }
+        0x1f => sign | 0x7f80_0000 | (mantissa << 13),
+        _ => sign | ((exponent + 112) << 23) | (mantissa << 13),
+    };
+    f32::from_bits(bits)
+}
+
+/// Converts to half-precision, rounding to the nearest even.
+pub fn f32_to_f16(value: f32) -> u16 {
+    let bits = value.to_bits();
+    let sign = ((bits >> 16) & 0x8000) as u16;
+    let exponent = ((bits >> 23) & 0xff) as i32;
+    let mantissa = bits & 0x7f_ffff;
+    if exponent == 0xff {
+        let nan = if mantissa != 0 { 0x200 } else { 0 };
+        return sign | 0x7c00 | nan;
+    }
+    let half_exponent = exponent - 127 + 15;
+    if half_exponent >= 0x1f {
+        return sign | 0x7c00;
+    }
+    let (half, remainder, halfway) = if half_exponent <= 0 {
+        if half_exponent < -10 {
+            return sign;
+        }
+        let full = mantissa | 0x80_0000;
+        let shift = (14 - half_exponent) as u32;
+        (full >> shift, full & ((1 << shift) - 1), 1 << (shift - 1))
+    } else {
+        (
+            ((half_exponent as u32) << 10) | (mantissa >> 13),
+            mantissa & 0x1fff,
+            0x1000,
+        )
+    };
+    let round_up = remainder > halfway || (remainder == halfway && half & 1 != 0);
+    // rounding may carry into the exponent, which is the correct behavior
+    sign | (half + round_up as u32) as u16
+}
+
+/// Decodes an unsigned float with a 5-bit exponent.
+fn small_float_to_f32(bits: u32, mantissa_bits: u32) -> f32 {
+    let exponent = bits >> mantissa_bits;
+    let mantissa = bits & ((1 << mantissa_bits) - 1);
+    let scale = (1 << mantissa_bits) as f32;
+    match exponent {
+        0 => mantissa as f32 / scale * 2f32.powi(-14),
+        0x1f if mantissa == 0 => f32::INFINITY,
+        0x1f => f32::NAN,
+        _ => (1.0 + mantissa as f32 / scale) * 2f32.powi(exponent as i32 - 15),
+    }
+}
+
+/// Encodes an unsigned float with a 5-bit exponent, truncating the mantissa.
+fn f32_to_small_float(value: f32, mantissa_bits: u32) -> u32 {
+    if value.is_nan() {
+        return (0x1f << mantissa_bits) | 1;
+    }
+    let half = f32_to_f16(value.max(0.0)) as u32;
+    half >> (10 - mantissa_bits)
+}
+
+fn encode_rgb9e5(rgb: [f32; 3]) -> u32 {
+    const MANTISSA_BITS: i32 = 9;
+    const BIAS: i32 = 15;
+    const MAX_EXPONENT: i32 = 31;
+    let max_value = (((1 << MANTISSA_BITS) - 1) as f32 / (1 << MANTISSA_BITS) as f32)
+        * 2f32.powi(MAX_EXPONENT - BIAS);
+    let clamped = [
+        rgb[0].max(0.0).min(max_value),
+        rgb[1].max(0.0).min(max_value),
+        rgb[2].max(0.0).min(max_value),
+    ];
+    let max_channel = clamped[0].max(clamped[1]).max(clamped[2]);
+    let mut exponent = (max_channel.log2().floor() as i32).max(-BIAS - 1) + 1 + BIAS;
+    let mut scale = 2f32.powi(exponent - BIAS - MANTISSA_BITS);
+    if (max_channel / scale + 0.5) as i32 == 1 << MANTISSA_BITS {
+        exponent += 1;
+        scale *= 2.0;
+    }
+    let mantissa = |value: f32| ((value / scale + 0.5) as u32).min((1 << MANTISSA_BITS) - 1);
+    mantissa(clamped[0])
+        | mantissa(clamped[1]) << 9
+        | mantissa(clamped[2]) << 18
+        | (exponent.max(0) as u32) << 27
+}
+
+/// Decodes a vertex attribute into raw components,
+/// interpreted in the same way as the texel components.
+pub fn decode_vertex(format: wgt::VertexFormat, bytes: &[u8]) -> (Texel, ScalarKind) {
+    use wgt::VertexFormat as Vf;
+    use Component as C;
+    let (component, count) = match format {
+        Vf::Uint8x2 => (C::Uint8, 2),
+        Vf::Uint8x4 => (C::Uint8, 4),
+        Vf::Sint8x2 => (C::Sint8, 2),
+        Vf::Sint8x4 => (C::Sint8, 4),
+        Vf::Unorm8x2 => (C::Unorm8, 2),
+        Vf::Unorm8x4 => (C::Unorm8, 4),
+        Vf::Snorm8x2 => (C::Snorm8, 2),
+        Vf::Snorm8x4 => (C::Snorm8, 4),
+        Vf::Uint16x2 => (C::Uint16, 2),
+        Vf::Uint16x4 => (C::Uint16, 4),
+        Vf::Sint16x2 => (C::Sint16, 2),
+        Vf::Sint16x4 => (C::Sint16, 4),
+        Vf::Unorm16x2 => (C::Unorm16, 2),
+        Vf::Unorm16x4 => (C::Unorm16, 4),
+        Vf::Snorm16x2 => (C::Snorm16, 2),
+        Vf::Snorm16x4 => (C::Snorm16, 4),
+        Vf::Float16x2 => (C::Float16, 2),
+        Vf::Float16x4 => (C::Float16, 4),
+        Vf::Float32 => (C::Float32, 1),
+        Vf::Float32x2 => (C::Float32, 2),
+        Vf::Float32x3 => (C::Float32, 3),
+        Vf::Float32x4 => (C::Float32, 4),
+        Vf::Uint32 => (C::Uint32, 1),
+        Vf::Uint32x2 => (C::Uint32, 2),
+        Vf::Uint32x3 => (C::Uint32, 3),
+        Vf::Uint32x4 => (C::Uint32, 4),
+        Vf::Sint32 => (C::Sint32, 1),
+        Vf::Sint32x2 => (C::Sint32, 2),
+        Vf::Sint32x3 => (C::Sint32, 3),
+        Vf::Sint32x4 => (C::Sint32, 4),
+        Vf::Float64 | Vf::Float64x2 | Vf::Float64x3 | Vf::Float64x4 => {
+            // 64-bit attributes are narrowed down to `f32`
+            let mut texel = default_texel(ScalarKind::Float);
+            let count = (format.size() / 8) as usize;
+            for (i, value) in texel.iter_mut().take(count).enumerate() {
+                let mut raw = [0u8; 8];
+                raw.copy_from_slice(&bytes[i * 8..i * 8 + 8]);
+                *value = (f64::from_le_bytes(raw) as f32).to_bits();
+            }
+            return (texel, ScalarKind::Float);
+        }
+    };
+    let mut texel = default_texel(component.kind());
+    let size = component.size();
+    for (i, value) in texel.iter_mut().take(count).enumerate() {
+        *value = component.decode(&bytes[i * size..]);
+    }
+    (texel, component.kind())
+}
+
+pub fn compare(function: wgt::CompareFunction, reference: f32, value: f32) -> bool {
+    use wgt::CompareFunction as Cf;
+    match function {
+        Cf::Never => false,
+        Cf::Less => reference < value,
+        Cf::Equal => reference == value,
+        Cf::LessEqual => reference <= value,
+        Cf::Greater => reference > value,
+        Cf::NotEqual => reference != value,
+        Cf::GreaterEqual => reference >= value,
+        Cf::Always => true,
+    }
+}
+
+pub fn stencil_op(op: wgt::StencilOperation, value: u32, reference: u32) -> u32 {
+    use wgt::StencilOperation as So;
+    match op {
+        So::Keep => value,
+        So::Zero => 0,
+        So::Replace => reference,
+        So::Invert => !value & 0xff,
+        So::IncrementClamp => (value + 1).min(0xff),
+        So::DecrementClamp => value.saturating_sub(1),
+        So::IncrementWrap => (value + 1) & 0xff,
+        So::DecrementWrap => value.wrapping_sub(1) & 0xff,
+    }
+}
+
+fn blend_factor(
+    factor: wgt::BlendFactor,
+    src: [f32; 4],
+    dst: [f32; 4],
+    constant: [f32; 4],
+    channel: usize,
+) -> f32 {
+    use wgt::BlendFactor as Bf;
+    match factor {
+        Bf::Zero => 0.0,
+        Bf::One => 1.0,
+        Bf::Src => src[channel],
+        Bf::OneMinusSrc => 1.0 - src[channel],
+        Bf::SrcAlpha => src[3],
+        Bf::OneMinusSrcAlpha => 1.0 - src[3],
+        Bf::Dst => dst[channel],
+        Bf::OneMinusDst => 1.0 - dst[channel],
+        Bf::DstAlpha => dst[3],
+        Bf::OneMinusDstAlpha => 1.0 - dst[3],
+        Bf::SrcAlphaSaturated if channel == 3 => 1.0,
+        Bf::SrcAlphaSaturated => src[3].min(1.0 - dst[3]),
+        Bf::Constant => constant[channel],
+        Bf::OneMinusConstant => 1.0 - constant[channel],
+    }
+}
+
+pub fn blend(
+    state: &wgt::BlendState,
+    src: [f32; 4],
+    dst: [f32; 4],
+    constant: [f32; 4],
+) -> [f32; 4] {
+    let mut result = [0.0; 4];
+    for (channel, value) in result.iter_mut().enumerate() {
+        let component = if channel == 3 {
+            &state.alpha
+        } else {
+            &state.color
+        };
+        let s = src[channel] * blend_factor(component.src_factor, src, dst, constant, channel);
+        let d = dst[channel] * blend_factor(component.dst_factor, src, dst, constant, channel);
+        *value = match component.operation {
+            wgt::BlendOperation::Add => s + d,
+            wgt::BlendOperation::Subtract => s - d,
+            wgt::BlendOperation::ReverseSubtract => d - s,
+            wgt::BlendOperation::Min => src[channel].min(dst[channel]),
+            wgt::BlendOperation::Max => src[channel].max(dst[channel]),
+        };
+    }
+    result
+}
+
+pub fn map_border_color(color: wgt::SamplerBorderColor) -> [f32; 4] {
+    match color {
+        wgt::SamplerBorderColor::TransparentBlack => [0.0; 4],
+        wgt::SamplerBorderColor::OpaqueBlack => [0.0, 0.0, 0.0, 1.0],
+        wgt::SamplerBorderColor::OpaqueWhite => [1.0; 4],
+    }
+}
@@ -0,0 +1,294 @@
+use super::interp;
+use std::sync::Arc;
+
+type DeviceResult<T> = Result<T, crate::DeviceError>;
+
+impl crate::Device<super::Api> for super::Device {
+    unsafe fn exit(self, _queue: super::Queue) {}
+
+    unsafe fn create_buffer(&self, desc: &crate::BufferDescriptor) -> DeviceResult<super::Buffer> {
+        Ok(super::Buffer {
+            memory: Arc::new(super::Memory::new(desc.size as usize)),
+        })
+    }
+    unsafe fn destroy_buffer(&self, _buffer: super::Buffer) {}
+
+    unsafe fn map_buffer(
+        &self,
+        buffer: &super::Buffer,
+        range: crate::MemoryRange,
+    ) -> DeviceResult<crate::BufferMapping> {
+        let ptr = buffer.memory.ptr.as_ptr().add(range.start as usize);
+        Ok(crate::BufferMapping {
+            ptr: std::ptr::NonNull::new_unchecked(ptr),
+            is_coherent: true,
+        })
+    }
+    unsafe fn unmap_buffer(&self, _buffer: &super::Buffer) -> DeviceResult<()> {
+        Ok(())
+    }
+    unsafe fn flush_mapped_ranges<I>(&self, _buffer: &super::Buffer, _ranges: I) {}
+    unsafe fn invalidate_mapped_ranges<I>(&self, _buffer: &super::Buffer, _ranges: I) {}
+
+    unsafe fn create_texture(
+        &self,
+        desc: &crate::TextureDescriptor,
+    ) -> DeviceResult<super::Texture> {
+        Ok(super::Texture {
+            image: Arc::new(super::Image::new(desc)),
+        })
+    }
+    unsafe fn destroy_texture(&self, _texture: super::Texture) {}
+
+    unsafe fn create_texture_view(
+        &self,
+        texture: &super::Texture,
+        desc: &crate::TextureViewDescriptor,
+    ) -> DeviceResult<super::TextureView> {
+        let image = &texture.image;
+        let mip_end = match desc.range.mip_level_count {
+            Some(count) => desc.range.base_mip_level + count.get(),
+            None => image.mip_level_count,
+        };
+        let array_layers = match image.dimension {
+            wgt::TextureDimension::D3 => 0..1,
+            _ => {
+                let end = match desc.range.array_layer_count {
+                    Some(count) => desc.range.base_array_layer + count.get(),
+                    None => image.size.depth_or_array_layers,
+                };
+                desc.range.base_array_layer..end
+            }
+        };
+        Ok(super::TextureView {
+            image: Arc::clone(image),
+            format: desc.format,
+            dimension: desc.dimension,
+            aspects: crate::FormatAspects::from(desc.format)
+                & crate::FormatAspects::from(desc.range.aspect),
+            mip_levels: desc.range.base_mip_level..mip_end,
+            array_layers,
+        })
+    }
+    unsafe fn destroy_texture_view(&self, _view: super::TextureView) {}
+
+    unsafe fn create_sampler(
+        &self,
+        desc: &crate::SamplerDescriptor,
+    ) -> DeviceResult<super::Sampler> {
+        if desc.anisotropy_clamp.is_some() {
+            log::debug!("Anisotropic filtering is ignored by the CPU backend");
+        }
+        Ok(super::Sampler {
+            address_modes: desc.address_modes,
+            mag_filter: desc.mag_filter,
+            min_filter: desc.min_filter,
+            mipmap_filter: desc.mipmap_filter,
+            lod_clamp: desc.lod_clamp.clone().unwrap_or(0.0..f32::MAX),
+            compare: desc.compare,
+            border_color: desc
+                .border_color
+                .unwrap_or(wgt::SamplerBorderColor::TransparentBlack),
+        })
+    }
+    unsafe fn destroy_sampler(&self, _sampler: super::Sampler) {}
+
+    unsafe fn create_command_encoder(
+        &self,
+        _desc: &crate::CommandEncoderDescriptor<super::Api>,
+    ) -> DeviceResult<super::CommandEncoder> {
+        Ok(super::CommandEncoder {
+            cmd_buffer: super::CommandBuffer::default(),
+        })
+    }
+    unsafe fn destroy_command_encoder(&self, _encoder: super::CommandEncoder) {}
+
+    unsafe fn create_bind_group_layout(
+        &self,
+        desc: &crate::BindGroupLayoutDescriptor,
+    ) -> DeviceResult<super::BindGroupLayout> {
+        Ok(super::BindGroupLayout {
+            entries: Arc::from(desc.entries),
+        })
+    }
+    unsafe fn destroy_bind_group_layout(&self, _bg_layout: super::BindGroupLayout) {}
+
+    unsafe fn create_pipeline_layout(
+        &self,
+        _desc: &crate::PipelineLayoutDescriptor<super::Api>,
+    ) -> DeviceResult<super::PipelineLayout> {
+        Ok(super::PipelineLayout { _private: () })
+    }
+    unsafe fn destroy_pipeline_layout(&self, _pipeline_layout: super::PipelineLayout) {}
+
+    unsafe fn create_bind_group(
+        &self,
+        desc: &crate::BindGroupDescriptor<super::Api>,
+    ) -> DeviceResult<super::BindGroup> {
+        let mut entries = Vec::with_capacity(desc.entries.len());
+        for entry in desc.entries {
+            let layout = desc
+                .layout
+                .entries
+                .iter()
+                .find(|layout| layout.binding == entry.binding)
+                .unwrap();
+            let binding = match layout.ty {
+                wgt::BindingType::Buffer {
+                    has_dynamic_offset, ..
+                } => {
+                    let bb = &desc.buffers[entry.resource_index as usize];
+                    super::Binding::Buffer {
+                        memory: Arc::clone(&bb.buffer.memory),
+                        offset: bb.offset,
+                        size: match bb.size {
+                            Some(size) => size.get(),
+                            None => bb.buffer.memory.size as wgt::BufferAddress - bb.offset,
+                        },
+                        dynamic: has_dynamic_offset,
+                    }
+                }
+                wgt::BindingType::Sampler { .. } => {
+                    let sampler = desc.samplers[entry.resource_index as usize];
+                    super::Binding::Sampler(sampler.clone())
+                }
+                wgt::BindingType::Texture { .. } | wgt::BindingType::StorageTexture { .. } => {
+                    let view = desc.textures[entry.resource_index as usize].view;
+                    super::Binding::Texture(view.clone())
+                }
+            };
+            entries.push((entry.binding, binding));
+        }
+        entries.sort_by_key(|&(binding, _)| binding);
+
+        Ok(super::BindGroup {
+            entries: Arc::from(entries),
+        })
+    }
+    unsafe fn destroy_bind_group(&self, _group: super::BindGroup) {}
+
+    unsafe fn create_shader_module(
+        &self,
+        _desc: &crate::ShaderModuleDescriptor,
+        shader: crate::ShaderInput,
+    ) -> Result<super::ShaderModule, crate::ShaderError> {
+        Ok(super::ShaderModule {
+            naga: match shader {
+                crate::ShaderInput::SpirV(_) => {
+                    return Err(crate::ShaderError::Compilation(
+                        "SPIR-V input is not supported by the CPU backend".to_string(),
+                    ));
+                }
+                crate::ShaderInput::Naga(naga) => Arc::new(naga),
+            },
+        })
+    }
+    unsafe fn destroy_shader_module(&self, _module: super::ShaderModule) {}
+
+    unsafe fn create_render_pipeline(
+        &self,
+        desc: &crate::RenderPipelineDescriptor<super::Api>,
+    ) -> Result<super::RenderPipeline, crate::PipelineError> {
+        let vertex = interp::Program::new(
+            &desc.vertex_stage.module.naga,
+            desc.vertex_stage.entry_point,
+            naga::ShaderStage::Vertex,
+            self.loop_timeout,
+        )?;
+        let fragment = match desc.fragment_stage {
+            Some(ref stage) => Some(Arc::new(interp::Program::new(
+                &stage.module.naga,
+                stage.entry_point,
+                naga::ShaderStage::Fragment,
+                self.loop_timeout,
+            )?)),
+            None => None,
+        };
+        let vertex_buffers = desc
+            .vertex_buffers
+            .iter()
+            .map(|vb| super::VertexBufferDesc {
+                stride: vb.array_stride,
+                step: vb.step_mode,
+                attributes: vb.attributes.into(),
+            })
+            .collect();
+
+        Ok(super::RenderPipeline {
+            inner: Arc::new(super::RenderPipelineInner {
+                vertex: Arc::new(vertex),
+                fragment,
+                vertex_buffers,
+                primitive: desc.primitive,
+                depth_stencil: desc.depth_stencil.clone(),
+                color_targets: desc.color_targets.into(),
+            }),
+        })
+    }
+    unsafe fn destroy_render_pipeline(&self, _pipeline: super::RenderPipeline) {}
+
+    unsafe fn create_compute_pipeline(
+        &self,
+        desc: &crate::ComputePipelineDescriptor<super::Api>,
+    ) -> Result<super::ComputePipeline, crate::PipelineError> {
+        let program = interp::Program::new(
+            &desc.stage.module.naga,
+            desc.stage.entry_point,
+            naga::ShaderStage::Compute,
+            self.loop_timeout,
+        )?;
+        Ok(super::ComputePipeline {
+            program: Arc::new(program),
+        })
+    }
+    unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
+
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        Ok(())
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: ()) {}
+    unsafe fn pipeline_cache_get_data(&self, _cache: &()) -> Option<Vec<u8>> {
+        None
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        None
+    }
+
+    unsafe fn create_query_set(
+        &self,
+        desc: &wgt::QuerySetDescriptor<crate::Label>,
+    ) -> DeviceResult<super::QuerySet> {
+        Ok(super::QuerySet {
+            results: Arc::new(parking_lot::Mutex::new(vec![0; desc.count as usize])),
+        })
+    }
+    unsafe fn destroy_query_set(&self, _set: super::QuerySet) {}
+
+    unsafe fn create_fence(&self) -> DeviceResult<super::Fence> {
+        Ok(super::Fence { value: 0 })
+    }
+    unsafe fn destroy_fence(&self, _fence: super::Fence) {}
+    unsafe fn get_fence_value(&self, fence: &super::Fence) -> DeviceResult<crate::FenceValue> {
+        Ok(fence.value)
+    }
+    unsafe fn wait(
+        &self,
+        fence: &super::Fence,
+        wait_value: crate::FenceValue,
+        _timeout_ms: u32,
+    ) -> DeviceResult<bool> {
+        // All the work is done by the time `submit` returns.
+        Ok(fence.value >= wait_value)
+    }
+
+    unsafe fn start_capture(&self) -> bool {
+        false
+    }
+    unsafe fn stop_capture(&self) {}
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
+}
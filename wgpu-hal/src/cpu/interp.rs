@@ -0,0 +1,2367 @@
+//! Interpreter of the naga IR.
+//!
+//! All the variables live in byte-addressed memory, laid out according to the
+//! naga `Layouter`, so that pointers can be represented by a memory space and an offset.
+//! Expression results are kept in a per-function cache, which is filled by `Emit`
+//! statements in the order the IR dictates.
+
+use super::{conv, texture::SampleQuery, Binding, Memory, Sampler, TextureView};
+use naga::{proc::Layouter, Handle, TypeInner as Ti};
+use parking_lot::{Condvar, Mutex};
+use std::{
+    ptr,
+    sync::Arc,
+    time::{Duration, Instant},
+};
+
+/// Number of loop iterations between the checks of the loop timeout.
+const LOOP_TIMEOUT_CHECK_INTERVAL: u32 = 1 << 10;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Scalar {
+    Sint(i32),
+    Uint(u32),
+    Float(f32),
+    Bool(bool),
+}
+
+impl Scalar {
+    fn zero(kind: naga::ScalarKind) -> Self {
+        Self::from_bits(kind, 0)
+    }
+
+    pub fn from_bits(kind: naga::ScalarKind, bits: u32) -> Self {
+        match kind {
+            naga::ScalarKind::Sint => Self::Sint(bits as i32),
+            naga::ScalarKind::Uint => Self::Uint(bits),
+            naga::ScalarKind::Float => Self::Float(f32::from_bits(bits)),
+            naga::ScalarKind::Bool => Self::Bool(bits != 0),
+        }
+    }
+
+    pub fn bits(self) -> u32 {
+        match self {
+            Self::Sint(v) => v as u32,
+            Self::Uint(v) => v,
+            Self::Float(v) => v.to_bits(),
+            Self::Bool(v) => v as u32,
+        }
+    }
+
+    pub fn as_f32(self) -> f32 {
+        match self {
+            Self::Sint(v) => v as f32,
+            Self::Uint(v) => v as f32,
+            Self::Float(v) => v,
+            Self::Bool(v) => v as u32 as f32,
+        }
+    }
+
+    pub fn as_i32(self) -> i32 {
+        match self {
+            Self::Sint(v) => v,
+            Self::Uint(v) => v as i32,
+            Self::Float(v) => v as i32,
+            Self::Bool(v) => v as i32,
+        }
+    }
+
+    pub fn as_u32(self) -> u32 {
+        match self {
+            Self::Sint(v) => v as u32,
+            Self::Uint(v) => v,
+            Self::Float(v) => v as u32,
+            Self::Bool(v) => v as u32,
+        }
+    }
+
+    pub fn as_bool(self) -> bool {
+        match self {
+            Self::Bool(v) => v,
+            other => other.bits() != 0,
+        }
+    }
+
+    fn convert(self, kind: naga::ScalarKind) -> Self {
+        match kind {
+            naga::ScalarKind::Sint => Self::Sint(self.as_i32()),
+            naga::ScalarKind::Uint => Self::Uint(self.as_u32()),
+            naga::ScalarKind::Float => Self::Float(self.as_f32()),
+            naga::ScalarKind::Bool => Self::Bool(self.as_bool()),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum Space {
+    /// Local variables of a function, identified by the depth of the call stack.
+    Function(usize),
+    Private,
+    WorkGroup,
+    /// Uniform, storage, and push constant globals.
+    Global(Handle<naga::GlobalVariable>),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pointer {
+    space: Space,
+    offset: usize,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Value {
+    Scalar(Scalar),
+    /// Vectors, matrices (as a list of columns), arrays, and structures.
+    Composite(Vec<Value>),
+    Pointer(Pointer),
+    /// Image or sampler global.
+    Handle(Handle<naga::GlobalVariable>),
+}
+
+impl Value {
+    pub fn scalar(&self) -> Scalar {
+        match *self {
+            Self::Scalar(scalar) => scalar,
+            Self::Composite(ref components) if !components.is_empty() => components[0].scalar(),
+            _ => Scalar::Uint(0),
+        }
+    }
+
+    /// Returns all the scalars of the value, in order.
+    pub fn flatten(&self) -> Vec<Scalar> {
+        let mut scalars = Vec::new();
+        self.flatten_into(&mut scalars);
+        scalars
+    }
+
+    fn flatten_into(&self, scalars: &mut Vec<Scalar>) {
+        match *self {
+            Self::Scalar(scalar) => scalars.push(scalar),
+            Self::Composite(ref components) => {
+                for component in components {
+                    component.flatten_into(scalars);
+                }
+            }
+            Self::Pointer(_) | Self::Handle(_) => {}
+        }
+    }
+
+    pub fn floats(&self) -> Vec<f32> {
+        self.flatten().into_iter().map(Scalar::as_f32).collect()
+    }
+
+    pub fn from_floats(values: &[f32]) -> Self {
+        if values.len() == 1 {
+            Self::Scalar(Scalar::Float(values[0]))
+        } else {
+            Self::Composite(
+                values
+                    .iter()
+                    .map(|&v| Self::Scalar(Scalar::Float(v)))
+                    .collect(),
+            )
+        }
+    }
+
+    fn map(&self, fun: &impl Fn(Scalar) -> Scalar) -> Self {
+        match *self {
+            Self::Scalar(scalar) => Self::Scalar(fun(scalar)),
+            Self::Composite(ref components) => {
+                Self::Composite(components.iter().map(|c| c.map(fun)).collect())
+            }
+            ref other => other.clone(),
+        }
+    }
+
+    /// Applies a function component-wise, broadcasting scalars.
+    fn zip(&self, other: &Self, fun: &impl Fn(Scalar, Scalar) -> Scalar) -> Self {
+        match (self, other) {
+            (&Self::Scalar(a), &Self::Scalar(b)) => Self::Scalar(fun(a, b)),
+            (&Self::Composite(ref a), &Self::Composite(ref b)) => {
+                Self::Composite(a.iter().zip(b.iter()).map(|(a, b)| a.zip(b, fun)).collect())
+            }
+            (&Self::Composite(ref a), &Self::Scalar(_)) => {
+                Self::Composite(a.iter().map(|a| a.zip(other, fun)).collect())
+            }
+            (&Self::Scalar(_), &Self::Composite(ref b)) => {
+                Self::Composite(b.iter().map(|b| self.zip(b, fun)).collect())
+            }
+            _ => self.clone(),
+        }
+    }
+
+    fn zip3(&self, b: &Self, c: &Self, fun: &impl Fn(Scalar, Scalar, Scalar) -> Scalar) -> Self {
+        let component = |value: &Self, i: usize| match *value {
+            Self::Composite(ref components) => components[i].clone(),
+            ref other => other.clone(),
+        };
+        let len = [self, b, c]
+            .iter()
+            .filter_map(|value| match **value {
+                Self::Composite(ref components) => Some(components.len()),
+                _ => None,
+            })
+            .next();
+        match len {
+            Some(len) => Self::Composite(
+                (0..len)
+                    .map(|i| component(self, i).zip3(&component(b, i), &component(c, i), fun))
+                    .collect(),
+            ),
+            None => Self::Scalar(fun(self.scalar(), b.scalar(), c.scalar())),
+        }
+    }
+
+    fn component(&self, index: usize) -> Self {
+        match *self {
+            Self::Composite(ref components) => match components.get(index) {
+                Some(component) => component.clone(),
+                None => {
+                    log::warn!("Component index {} is out of bounds", index);
+                    components
+                        .last()
+                        .cloned()
+                        .unwrap_or(Self::Scalar(Scalar::Uint(0)))
+                }
+            },
+            ref other => other.clone(),
+        }
+    }
+}
+
+/// Raw memory region, which values are read from and written to.
+#[derive(Clone, Copy)]
+struct Region {
+    ptr: *mut u8,
+    len: usize,
+}
+
+impl Region {
+    fn from_slice(slice: &mut [u8]) -> Self {
+        Self {
+            ptr: slice.as_mut_ptr(),
+            len: slice.len(),
+        }
+    }
+
+    unsafe fn read_scalar(self, offset: usize, kind: naga::ScalarKind, width: u8) -> Scalar {
+        let width = width as usize;
+        if offset + width > self.len {
+            return Scalar::zero(kind);
+        }
+        let mut raw = [0u8; 8];
+        ptr::copy_nonoverlapping(self.ptr.add(offset), raw.as_mut_ptr(), width);
+        match (kind, width) {
+            (naga::ScalarKind::Float, 8) => Scalar::Float(f64::from_le_bytes(raw) as f32),
+            (naga::ScalarKind::Bool, _) => Scalar::Bool(raw[0] != 0),
+            _ => Scalar::from_bits(kind, u32::from_le_bytes([raw[0], raw[1], raw[2], raw[3]])),
+        }
+    }
+
+    unsafe fn write_scalar(self, offset: usize, scalar: Scalar, width: u8) {
+        let width = width as usize;
+        if offset + width > self.len {
+            return;
+        }
+        let raw = match (scalar, width) {
+            (Scalar::Float(v), 8) => (v as f64).to_le_bytes(),
+            (Scalar::Bool(v), _) => [v as u8, 0, 0, 0, 0, 0, 0, 0],
+            (other, _) => {
+                let bits = other.bits().to_le_bytes();
+                [bits[0], bits[1], bits[2], bits[3], 0, 0, 0, 0]
+            }
+        };
+        ptr::copy_nonoverlapping(raw.as_ptr(), self.ptr.add(offset), width);
+    }
+}
+
+/// Resource bound to a global variable.
+pub enum Resource {
+    Buffer {
+        memory: Arc<Memory>,
+        offset: usize,
+        size: usize,
+    },
+    Texture(TextureView),
+    Sampler(Sampler),
+}
+
+/// All the resources used by a draw or a dispatch.
+pub struct Resources {
+    globals: Vec<Option<Resource>>,
+    /// Serializes the atomic operations across the invocations running in parallel.
+    atomic_lock: Mutex<()>,
+}
+
+impl Resources {
+    fn texture(&self, handle: Handle<naga::GlobalVariable>) -> Option<&TextureView> {
+        match self.globals[handle.index()] {
+            Some(Resource::Texture(ref view)) => Some(view),
+            _ => None,
+        }
+    }
+
+    fn sampler(&self, handle: Handle<naga::GlobalVariable>) -> Option<&Sampler> {
+        match self.globals[handle.index()] {
+            Some(Resource::Sampler(ref sampler)) => Some(sampler),
+            _ => None,
+        }
+    }
+}
+
+/// Memory layout of the local variables of a function.
+struct LocalLayout {
+    offsets: Vec<usize>,
+    /// Initial contents, with the initializers applied.
+    init: Vec<u8>,
+}
+
+/// An entry point, prepared for execution.
+pub struct Program {
+    shader: Arc<crate::NagaShader>,
+    entry_point: usize,
+    layouter: Layouter,
+    global_offsets: Vec<usize>,
+    private_init: Vec<u8>,
+    workgroup_memory_size: usize,
+    entry_layout: LocalLayout,
+    function_layouts: Vec<LocalLayout>,
+    /// True if the entry point may need to synchronize invocations.
+    pub uses_barriers: bool,
+    /// Time after which a loop is considered to never finish.
+    ///
+    /// GPU drivers reset the device instead of hanging on such shaders, and so does
+    /// the interpreter, by panicking, which the queue reports as a lost device.
+    loop_timeout: Option<Duration>,
+}
+
+fn round_up(value: usize, alignment: usize) -> usize {
+    (value + alignment - 1) / alignment * alignment
+}
+
+fn block_has_barriers(block: &naga::Block) -> bool {
+    block.iter().any(|statement| match *statement {
+        naga::Statement::Barrier(_) => true,
+        naga::Statement::Block(ref block) => block_has_barriers(block),
+        naga::Statement::If {
+            ref accept,
+            ref reject,
+            ..
+        } => block_has_barriers(accept) || block_has_barriers(reject),
+        naga::Statement::Switch {
+            ref cases,
+            ref default,
+            ..
+        } => cases.iter().any(|case| block_has_barriers(&case.body)) || block_has_barriers(default),
+        naga::Statement::Loop {
+            ref body,
+            ref continuing,
+        } => block_has_barriers(body) || block_has_barriers(continuing),
+        _ => false,
+    })
+}
+
+impl Program {
+    pub fn new(
+        shader: &Arc<crate::NagaShader>,
+        entry_point: &str,
+        stage: naga::ShaderStage,
+        loop_timeout: Option<Duration>,
+    ) -> Result<Self, crate::PipelineError> {
+        let module = &shader.module;
+        let ep_index = module
+            .entry_points
+            .iter()
+            .position(|ep| ep.stage == stage && ep.name == entry_point)
+            .ok_or(crate::PipelineError::EntryPoint(stage))?;
+
+        let mut layouter = Layouter::default();
+        layouter
+            .update(&module.types, &module.constants)
+            .map_err(|e| {
+                let stages = match stage {
+                    naga::ShaderStage::Vertex => wgt::ShaderStages::VERTEX,
+                    naga::ShaderStage::Fragment => wgt::ShaderStages::FRAGMENT,
+                    naga::ShaderStage::Compute => wgt::ShaderStages::COMPUTE,
+                };
+                crate::PipelineError::Linkage(stages, format!("{:?}", e))
+            })?;
+
+        let mut program = Self {
+            shader: Arc::clone(shader),
+            entry_point: ep_index,
+            layouter,
+            global_offsets: vec![0; module.global_variables.len()],
+            private_init: Vec::new(),
+            workgroup_memory_size: 0,
+            entry_layout: LocalLayout {
+                offsets: Vec::new(),
+                init: Vec::new(),
+            },
+            function_layouts: Vec::new(),
+            uses_barriers: false,
+            loop_timeout,
+        };
+
+        let mut private_size = 0;
+        let mut workgroup_size = 0;
+        let mut private_inits = Vec::new();
+        for (handle, var) in module.global_variables.iter() {
+            let layout = &program.layouter[var.ty];
+            let alignment = layout.alignment.get() as usize;
+            let size = match var.class {
+                naga::StorageClass::Private => &mut private_size,
+                naga::StorageClass::WorkGroup => &mut workgroup_size,
+                _ => continue,
+            };
+            let offset = round_up(*size, alignment);
+            *size = offset + layout.size as usize;
+            program.global_offsets[handle.index()] = offset;
+            if let (naga::StorageClass::Private, Some(init)) = (var.class, var.init) {
+                private_inits.push((offset, var.ty, init));
+            }
+        }
+        let mut private_init = vec![0; private_size];
+        for (offset, ty, init) in private_inits {
+            let value = program.constant_value(init);
+            unsafe {
+                program.write_value(
+                    Region::from_slice(&mut private_init),
+                    offset,
+                    &module.types[ty].inner,
+                    &value,
+                );
+            }
+        }
+        program.private_init = private_init;
+        program.workgroup_memory_size = workgroup_size;
+
+        let ep = &module.entry_points[ep_index];
+        program.entry_layout = program.local_layout(&ep.function);
+        program.function_layouts = module
+            .functions
+            .iter()
+            .map(|(_, fun)| program.local_layout(fun))
+            .collect();
+        program.uses_barriers = block_has_barriers(&ep.function.body)
+            || module
+                .functions
+                .iter()
+                .any(|(_, fun)| block_has_barriers(&fun.body));
+        Ok(program)
+    }
+
+    fn module(&self) -> &naga::Module {
+        &self.shader.module
+    }
+
+    pub fn entry_point(&self) -> &naga::EntryPoint {
+        &self.shader.module.entry_points[self.entry_point]
+    }
+
+    pub fn workgroup_size(&self) -> [u32; 3] {
+        self.entry_point().workgroup_size
+    }
+
+    fn local_layout(&self, fun: &naga::Function) -> LocalLayout {
+        let mut size = 0;
+        let mut offsets = Vec::with_capacity(fun.local_variables.len());
+        for (_, var) in fun.local_variables.iter() {
+            let layout = &self.layouter[var.ty];
+            let offset = round_up(size, layout.alignment.get() as usize);
+            size = offset + layout.size as usize;
+            offsets.push(offset);
+        }
+        let mut init = vec![0; size];
+        for ((_, var), &offset) in fun.local_variables.iter().zip(offsets.iter()) {
+            if let Some(constant) = var.init {
+                let value = self.constant_value(constant);
+                unsafe {
+                    self.write_value(
+                        Region::from_slice(&mut init),
+                        offset,
+                        &self.module().types[var.ty].inner,
+                        &value,
+                    );
+                }
+            }
+        }
+        LocalLayout { offsets, init }
+    }
+
+    /// Resolves the resources of all the globals used by the program.
+    pub fn bind(
+        &self,
+        groups: &[Option<Arc<[(u32, Binding)]>>],
+        push_constants: &Arc<Memory>,
+    ) -> Resources {
+        let globals = self
+            .module()
+            .global_variables
+            .iter()
+            .map(|(_, var)| {
+                if let naga::StorageClass::PushConstant = var.class {
+                    return Some(Resource::Buffer {
+                        memory: Arc::clone(push_constants),
+                        offset: 0,
+                        size: push_constants.size,
+                    });
+                }
+                let res_binding = var.binding.as_ref()?;
+                let entries = groups.get(res_binding.group as usize)?.as_ref()?;
+                let index = entries
+                    .binary_search_by_key(&res_binding.binding, |&(binding, _)| binding)
+                    .ok()?;
+                Some(match entries[index].1 {
+                    Binding::Buffer {
+                        ref memory,
+                        offset,
+                        size,
+                        dynamic: _,
+                    } => Resource::Buffer {
+                        memory: Arc::clone(memory),
+                        offset: offset as usize,
+                        size: size as usize,
+                    },
+                    Binding::Texture(ref view) => Resource::Texture(view.clone()),
+                    Binding::Sampler(ref sampler) => Resource::Sampler(sampler.clone()),
+                })
+            })
+            .collect();
+        Resources {
+            globals,
+            atomic_lock: Mutex::new(()),
+        }
+    }
+
+    /// Allocates the workgroup memory shared by the invocations of a workgroup.
+    pub fn create_workgroup_memory(&self) -> Memory {
+        Memory::new(self.workgroup_memory_size)
+    }
+
+    fn constant_value(&self, handle: Handle<naga::Constant>) -> Value {
+        match self.module().constants[handle].inner {
+            naga::ConstantInner::Scalar { value, width: _ } => Value::Scalar(match value {
+                naga::ScalarValue::Sint(v) => Scalar::Sint(v as i32),
+                naga::ScalarValue::Uint(v) => Scalar::Uint(v as u32),
+                naga::ScalarValue::Float(v) => Scalar::Float(v as f32),
+                naga::ScalarValue::Bool(v) => Scalar::Bool(v),
+            }),
+            naga::ConstantInner::Composite {
+                ref components,
+                ty: _,
+            } => Value::Composite(components.iter().map(|&c| self.constant_value(c)).collect()),
+        }
+    }
+
+    fn array_length(&self, size: naga::ArraySize) -> Option<usize> {
+        match size {
+            naga::ArraySize::Constant(handle) => {
+                Some(self.constant_value(handle).scalar().as_u32() as usize)
+            }
+            naga::ArraySize::Dynamic => None,
+        }
+    }
+
+    /// Returns a zero value of the given type.
+    pub fn zero_value(&self, inner: &Ti) -> Value {
+        let types = &self.module().types;
+        match *inner {
+            Ti::Scalar { kind, .. } | Ti::Atomic { kind, .. } => Value::Scalar(Scalar::zero(kind)),
+            Ti::Vector { size, kind, .. } => {
+                Value::Composite(vec![Value::Scalar(Scalar::zero(kind)); size as usize])
+            }
+            Ti::Matrix { columns, rows, .. } => Value::Composite(vec![
+                Value::Composite(vec![
+                        Value::Scalar(Scalar::Float(0.0));
+                        rows as usize
+                    ]);
+                columns as usize
+            ]),
+            Ti::Array { base, size, .. } => {
+                let count = self.array_length(size).unwrap_or(0);
+                Value::Composite(vec![self.zero_value(&types[base].inner); count])
+            }
+            Ti::Struct { ref members, .. } => Value::Composite(
+                members
+                    .iter()
+                    .map(|member| self.zero_value(&types[member.ty].inner))
+                    .collect(),
+            ),
+            _ => Value::Scalar(Scalar::Uint(0)),
+        }
+    }
+
+    /// Returns the offset between the columns of a matrix.
+    fn column_stride(rows: naga::VectorSize, width: u8) -> usize {
+        let aligned_rows = if rows > naga::VectorSize::Bi { 4 } else { 2 };
+        aligned_rows * width as usize
+    }
+
+    unsafe fn read_value(&self, region: Region, offset: usize, inner: &Ti) -> Value {
+        let types = &self.module().types;
+        match *inner {
+            Ti::Scalar { kind, width } | Ti::Atomic { kind, width } => {
+                Value::Scalar(region.read_scalar(offset, kind, width))
+            }
+            Ti::Vector { size, kind, width } => Value::Composite(
+                (0..size as usize)
+                    .map(|i| {
+                        Value::Scalar(region.read_scalar(offset + i * width as usize, kind, width))
+                    })
+                    .collect(),
+            ),
+            Ti::Matrix {
+                columns,
+                rows,
+                width,
+            } => {
+                let stride = Self::column_stride(rows, width);
+                let column = Ti::Vector {
+                    size: rows,
+                    kind: naga::ScalarKind::Float,
+                    width,
+                };
+                Value::Composite(
+                    (0..columns as usize)
+                        .map(|i| self.read_value(region, offset + i * stride, &column))
+                        .collect(),
+                )
+            }
+            Ti::Array { base, size, stride } => {
+                let count = match self.array_length(size) {
+                    Some(count) => count,
+                    None => region.len.saturating_sub(offset) / stride as usize,
+                };
+                Value::Composite(
+                    (0..count)
+                        .map(|i| {
+                            self.read_value(
+                                region,
+                                offset + i * stride as usize,
+                                &types[base].inner,
+                            )
+                        })
+                        .collect(),
+                )
+            }
+            Ti::Struct { ref members, .. } => Value::Composite(
+                members
+                    .iter()
+                    .map(|member| {
+                        self.read_value(
+                            region,
+                            offset + member.offset as usize,
+                            &types[member.ty].inner,
+                        )
+                    })
+                    .collect(),
+            ),
+            ref other => {
+                log::warn!("Unable to load a value of type {:?}", other);
+                Value::Scalar(Scalar::Uint(0))
+            }
+        }
+    }
+
+    unsafe fn write_value(&self, region: Region, offset: usize, inner: &Ti, value: &Value) {
+        let types = &self.module().types;
+        match *inner {
+            Ti::Scalar { width, .. } | Ti::Atomic { width, .. } => {
+                region.write_scalar(offset, value.scalar(), width)
+            }
+            Ti::Vector { size, width, .. } => {
+                for i in 0..size as usize {
+                    region.write_scalar(
+                        offset + i * width as usize,
+                        value.component(i).scalar(),
+                        width,
+                    );
+                }
+            }
+            Ti::Matrix {
+                columns,
+                rows,
+                width,
+            } => {
+                let stride = Self::column_stride(rows, width);
+                for i in 0..columns as usize {
+                    let column = value.component(i);
+                    for j in 0..rows as usize {
+                        region.write_scalar(
+                            offset + i * stride + j * width as usize,
+                            column.component(j).scalar(),
+                            width,
+                        );
+                    }
+                }
+            }
+            Ti::Array { base, stride, .. } => {
+                if let Value::Composite(ref elements) = *value {
+                    for (i, element) in elements.iter().enumerate() {
+                        self.write_value(
+                            region,
+                            offset + i * stride as usize,
+                            &types[base].inner,
+                            element,
+                        );
+                    }
+                }
+            }
+            Ti::Struct { ref members, .. } => {
+                for (i, member) in members.iter().enumerate() {
+                    self.write_value(
+                        region,
+                        offset + member.offset as usize,
+                        &types[member.ty].inner,
+                        &value.component(i),
+                    );
+                }
+            }
+            ref other => log::warn!("Unable to store a value of type {:?}", other),
+        }
+    }
+
+    /// Builds the arguments of the entry point, asking `fetch` for the value of each binding.
+    pub fn entry_arguments(
+        &self,
+        mut fetch: impl FnMut(&naga::Binding, &Ti) -> Value,
+    ) -> Vec<Value> {
+        let types = &self.module().types;
+        self.entry_point()
+            .function
+            .arguments
+            .iter()
+            .map(|arg| {
+                let inner = &types[arg.ty].inner;
+                match (arg.binding.as_ref(), inner) {
+                    (Some(binding), _) => fetch(binding, inner),
+                    (None, &Ti::Struct { ref members, .. }) => Value::Composite(
+                        members
+                            .iter()
+                            .map(|member| match member.binding {
+                                Some(ref binding) => fetch(binding, &types[member.ty].inner),
+                                None => self.zero_value(&types[member.ty].inner),
+                            })
+                            .collect(),
+                    ),
+                    (None, _) => self.zero_value(inner),
+                }
+            })
+            .collect()
+    }
+
+    /// Passes each output of the entry point to `store`.
+    pub fn entry_outputs(&self, result: Value, mut store: impl FnMut(&naga::Binding, Value)) {
+        let types = &self.module().types;
+        let fun_result = match self.entry_point().function.result {
+            Some(ref result) => result,
+            None => return,
+        };
+        match (fun_result.binding.as_ref(), &types[fun_result.ty].inner) {
+            (Some(binding), _) => store(binding, result),
+            (None, &Ti::Struct { ref members, .. }) => {
+                if let Value::Composite(values) = result {
+                    for (member, value) in members.iter().zip(values) {
+                        if let Some(ref binding) = member.binding {
+                            store(binding, value);
+                        }
+                    }
+                }
+            }
+            (None, _) => {}
+        }
+    }
+}
+
+enum Flow {
+    Next,
+    Break,
+    Continue,
+    Return(Option<Value>),
+    Kill,
+}
+
+struct Frame<'a> {
+    function: &'a naga::Function,
+    info: &'a naga::valid::FunctionInfo,
+    layout: &'a LocalLayout,
+    depth: usize,
+    arguments: Vec<Value>,
+    values: Vec<Option<Value>>,
+}
+
+#[derive(Default)]
+struct BarrierState {
+    arrived: usize,
+    generation: u64,
+    poisoned: bool,
+}
+
+/// Barrier shared by the invocations of a workgroup.
+///
+/// Unlike `std::sync::Barrier`, it can be poisoned when an invocation panics,
+/// which releases the invocations waiting at it instead of leaving them blocked forever.
+pub struct WorkgroupBarrier {
+    count: usize,
+    state: Mutex<BarrierState>,
+    condvar: Condvar,
+}
+
+impl WorkgroupBarrier {
+    pub fn new(count: usize) -> Self {
+        Self {
+            count,
+            state: Mutex::new(BarrierState::default()),
+            condvar: Condvar::new(),
+        }
+    }
+
+    /// Blocks until all the invocations reach the barrier.
+    /// Returns `false` if the barrier got poisoned.
+    pub fn wait(&self) -> bool {
+        let mut state = self.state.lock();
+        if state.poisoned {
+            return false;
+        }
+        state.arrived += 1;
+        if state.arrived == self.count {
+            state.arrived = 0;
+            state.generation += 1;
+            self.condvar.notify_all();
+            return true;
+        }
+        let generation = state.generation;
+        while state.generation == generation && !state.poisoned {
+            self.condvar.wait(&mut state);
+        }
+        !state.poisoned
+    }
+
+    /// Releases all the current and future waiters.
+    pub fn poison(&self) {
+        self.state.lock().poisoned = true;
+        self.condvar.notify_all();
+    }
+}
+
+/// State of a single shader invocation.
+pub struct Invocation<'a> {
+    program: &'a Program,
+    resources: &'a Resources,
+    workgroup: &'a Memory,
+    barrier: Option<&'a WorkgroupBarrier>,
+    private: Vec<u8>,
+    frames: Vec<Vec<u8>>,
+}
+
+impl<'a> Invocation<'a> {
+    pub fn new(
+        program: &'a Program,
+        resources: &'a Resources,
+        workgroup: &'a Memory,
+        barrier: Option<&'a WorkgroupBarrier>,
+    ) -> Self {
+        Self {
+            program,
+            resources,
+            workgroup,
+            barrier,
+            private: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    /// Runs the entry point. Returns `None` if the invocation was killed.
+    pub fn run(&mut self, arguments: Vec<Value>) -> Option<Option<Value>> {
+        let program = self.program;
+        self.private.clear();
+        self.private.extend_from_slice(&program.private_init);
+        self.frames.clear();
+        let ep = program.entry_point();
+        let info = program.shader.info.get_entry_point(program.entry_point);
+        match self.call(&ep.function, info, &program.entry_layout, arguments) {
+            Flow::Kill => None,
+            Flow::Return(value) => Some(value),
+            _ => Some(None),
+        }
+    }
+
+    fn call(
+        &mut self,
+        function: &'a naga::Function,
+        info: &'a naga::valid::FunctionInfo,
+        layout: &'a LocalLayout,
+        arguments: Vec<Value>,
+    ) -> Flow {
+        let depth = self.frames.len();
+        self.frames.push(layout.init.clone());
+        let mut frame = Frame {
+            function,
+            info,
+            layout,
+            depth,
+            arguments,
+            values: vec![None; function.expressions.len()],
+        };
+        let flow = self.execute(&mut frame, &function.body);
+        self.frames.pop();
+        flow
+    }
+
+    fn region(&mut self, space: Space) -> Region {
+        match space {
+            Space::Function(depth) => Region::from_slice(&mut self.frames[depth]),
+            Space::Private => Region::from_slice(&mut self.private),
+            Space::WorkGroup => Region {
+                ptr: self.workgroup.ptr.as_ptr(),
+                len: self.workgroup.size,
+            },
+            Space::Global(handle) => match self.resources.globals[handle.index()] {
+                Some(Resource::Buffer {
+                    ref memory,
+                    offset,
+                    size,
+                }) => Region {
+                    ptr: unsafe { memory.ptr.as_ptr().add(offset) },
+                    len: size.min(memory.size - offset),
+                },
+                _ => {
+                    log::warn!("Global {:?} is not bound to a buffer", handle);
+                    Region {
+                        ptr: ptr::null_mut(),
+                        len: 0,
+                    }
+                }
+            },
+        }
+    }
+
+    fn type_of<'f>(&self, frame: &'f Frame<'a>, expr: Handle<naga::Expression>) -> &'f Ti
+    where
+        'a: 'f,
+    {
+        frame.info[expr]
+            .ty
+            .inner_with(&self.program.shader.module.types)
+    }
+
+    fn execute(&mut self, frame: &mut Frame<'a>, block: &'a naga::Block) -> Flow {
+        use naga::Statement as S;
+        for statement in block.iter() {
+            let flow = match *statement {
+                S::Emit(ref range) => {
+                    for expr in range.clone() {
+                        let value = self.evaluate(frame, expr);
+                        frame.values[expr.index()] = Some(value);
+                    }
+                    Flow::Next
+                }
+                S::Block(ref block) => self.execute(frame, block),
+                S::If {
+                    condition,
+                    ref accept,
+                    ref reject,
+                } => {
+                    if self.operand(frame, condition).scalar().as_bool() {
+                        self.execute(frame, accept)
+                    } else {
+                        self.execute(frame, reject)
+                    }
+                }
+                S::Switch {
+                    selector,
+                    ref cases,
+                    ref default,
+                } => {
+                    let value = self.operand(frame, selector).scalar().as_i32();
+                    let mut flow = Flow::Next;
+                    let mut run_default = true;
+                    if let Some(start) = cases.iter().position(|case| case.value == value) {
+                        run_default = false;
+                        for case in cases[start..].iter() {
+                            flow = self.execute(frame, &case.body);
+                            if let Flow::Next = flow {
+                                if case.fall_through {
+                                    run_default = true;
+                                    continue;
+                                }
+                            }
+                            run_default = false;
+                            break;
+                        }
+                    }
+                    if run_default {
+                        flow = self.execute(frame, default);
+                    }
+                    match flow {
+                        Flow::Break => Flow::Next,
+                        other => other,
+                    }
+                }
+                S::Loop {
+                    ref body,
+                    ref continuing,
+                } => {
+                    let start = Instant::now();
+                    let mut iterations = 0u32;
+                    loop {
+                        iterations = iterations.wrapping_add(1);
+                        if let Some(timeout) = self.program.loop_timeout {
+                            if iterations % LOOP_TIMEOUT_CHECK_INTERVAL == 0
+                                && start.elapsed() > timeout
+                            {
+                                panic!("Loop didn't finish in {:?}", timeout);
+                            }
+                        }
+                        match self.execute(frame, body) {
+                            Flow::Break => break Flow::Next,
+                            Flow::Next | Flow::Continue => {}
+                            other => break other,
+                        }
+                        match self.execute(frame, continuing) {
+                            Flow::Break => break Flow::Next,
+                            Flow::Next | Flow::Continue => {}
+                            other => break other,
+                        }
+                    }
+                }
+                S::Break => Flow::Break,
+                S::Continue => Flow::Continue,
+                S::Return { value } => Flow::Return(value.map(|expr| self.operand(frame, expr))),
+                S::Kill => Flow::Kill,
+                S::Barrier(_) => match self.barrier {
+                    // Another invocation of the workgroup failed, give up on this one.
+                    Some(barrier) if !barrier.wait() => Flow::Kill,
+                    _ => Flow::Next,
+                },
+                S::Store { pointer, value } => {
+                    let target = self.operand(frame, pointer);
+                    let value = self.operand(frame, value);
+                    self.store(frame, pointer, target, &value);
+                    Flow::Next
+                }
+                S::ImageStore {
+                    image,
+                    coordinate,
+                    array_index,
+                    value,
+                } => {
+                    self.image_store(frame, image, coordinate, array_index, value);
+                    Flow::Next
+                }
+                S::Atomic {
+                    pointer,
+                    ref fun,
+                    value,
+                    result,
+                } => {
+                    let old = self.atomic(frame, pointer, fun, value);
+                    let result_value = match *self.type_of(frame, result) {
+                        Ti::Vector { kind, .. } => {
+                            let exchanged = match *fun {
+                                naga::AtomicFunction::Exchange {
+                                    compare: Some(compare),
+                                } => old == self.operand(frame, compare).scalar(),
+                                _ => false,
+                            };
+                            Value::Composite(vec![
+                                Value::Scalar(old),
+                                Value::Scalar(Scalar::Uint(exchanged as u32).convert(kind)),
+                            ])
+                        }
+                        _ => Value::Scalar(old),
+                    };
+                    frame.values[result.index()] = Some(result_value);
+                    Flow::Next
+                }
+                S::Call {
+                    function,
+                    ref arguments,
+                    result,
+                } => {
+                    let program = self.program;
+                    let args = arguments
+                        .iter()
+                        .map(|&arg| self.operand(frame, arg))
+                        .collect();
+                    let flow = self.call(
+                        &program.shader.module.functions[function],
+                        &program.shader.info[function],
+                        &program.function_layouts[function.index()],
+                        args,
+                    );
+                    match flow {
+                        Flow::Kill => Flow::Kill,
+                        Flow::Return(value) => {
+                            if let (Some(result), Some(value)) = (result, value) {
+                                frame.values[result.index()] = Some(value);
+                            }
+                            Flow::Next
+                        }
+                        _ => Flow::Next,
+                    }
+                }
+            };
+            match flow {
+                Flow::Next => {}
+                other => return other,
+            }
+        }
+        Flow::Next
+    }
+
+    fn store(
+        &mut self,
+        frame: &Frame<'a>,
+        pointer_expr: Handle<naga::Expression>,
+        pointer: Value,
+        value: &Value,
+    ) {
+        let pointer = match pointer {
+            Value::Pointer(pointer) => pointer,
+            _ => return,
+        };
+        let program = self.program;
+        let types = &program.shader.module.types;
+        let region = self.region(pointer.space);
+        unsafe {
+            match *self.type_of(frame, pointer_expr) {
+                Ti::Pointer { base, .. } => {
+                    program.write_value(region, pointer.offset, &types[base].inner, value)
+                }
+                Ti::ValuePointer {
+                    size: Some(size),
+                    kind,
+                    width,
+                    ..
+                } => program.write_value(
+                    region,
+                    pointer.offset,
+                    &Ti::Vector { size, kind, width },
+                    value,
+                ),
+                Ti::ValuePointer {
+                    size: None, width, ..
+                } => region.write_scalar(pointer.offset, value.scalar(), width),
+                _ => {}
+            }
+        }
+    }
+
+    fn atomic(
+        &mut self,
+        frame: &mut Frame<'a>,
+        pointer_expr: Handle<naga::Expression>,
+        fun: &naga::AtomicFunction,
+        value: Handle<naga::Expression>,
+    ) -> Scalar {
+        let pointer = match self.operand(frame, pointer_expr) {
+            Value::Pointer(pointer) => pointer,
+            _ => return Scalar::Uint(0),
+        };
+        let operand = self.operand(frame, value).scalar();
+        let compare = match *fun {
+            naga::AtomicFunction::Exchange {
+                compare: Some(compare),
+            } => Some(self.operand(frame, compare).scalar()),
+            _ => None,
+        };
+        let (kind, width) = match *self.type_of(frame, pointer_expr) {
+            Ti::Pointer { base, .. } => match self.program.shader.module.types[base].inner {
+                Ti::Atomic { kind, width } | Ti::Scalar { kind, width } => (kind, width),
+                _ => (naga::ScalarKind::Uint, 4),
+            },
+            _ => (naga::ScalarKind::Uint, 4),
+        };
+        let region = self.region(pointer.space);
+        let resources = self.resources;
+        let _guard = resources.atomic_lock.lock();
+        unsafe {
+            let old = region.read_scalar(pointer.offset, kind, width);
+            let new = match *fun {
+                naga::AtomicFunction::Add => binary_scalar(naga::BinaryOperator::Add, old, operand),
+                naga::AtomicFunction::Subtract => {
+                    binary_scalar(naga::BinaryOperator::Subtract, old, operand)
+                }
+                naga::AtomicFunction::And => binary_scalar(naga::BinaryOperator::And, old, operand),
+                naga::AtomicFunction::ExclusiveOr => {
+                    binary_scalar(naga::BinaryOperator::ExclusiveOr, old, operand)
+                }
+                naga::AtomicFunction::InclusiveOr => {
+                    binary_scalar(naga::BinaryOperator::InclusiveOr, old, operand)
+                }
+                naga::AtomicFunction::Min => min_scalar(old, operand),
+                naga::AtomicFunction::Max => max_scalar(old, operand),
+                naga::AtomicFunction::Exchange { .. } => match compare {
+                    Some(compare) if compare != old => old,
+                    _ => operand,
+                },
+            };
+            region.write_scalar(pointer.offset, new, width);
+            old
+        }
+    }
+
+    /// Returns the value of an operand, evaluating it if it's not emitted.
+    fn operand(&mut self, frame: &mut Frame<'a>, expr: Handle<naga::Expression>) -> Value {
+        if let Some(ref value) = frame.values[expr.index()] {
+            return value.clone();
+        }
+        let value = self.evaluate(frame, expr);
+        frame.values[expr.index()] = Some(value.clone());
+        value
+    }
+
+    fn pointer_offset(&self, pointee: &Ti, index: usize) -> usize {
+        match *pointee {
+            Ti::Vector { width, .. } | Ti::ValuePointer { width, .. } => index * width as usize,
+            Ti::Matrix { rows, width, .. } => index * Program::column_stride(rows, width),
+            Ti::Array { stride, .. } => index * stride as usize,
+            Ti::Struct { ref members, .. } => members[index].offset as usize,
+            _ => 0,
+        }
+    }
+
+    fn access(
+        &mut self,
+        frame: &Frame<'a>,
+        base_expr: Handle<naga::Expression>,
+        base: Value,
+        index: usize,
+    ) -> Value {
+        match base {
+            Value::Pointer(pointer) => {
+                let types = &self.program.shader.module.types;
+                let offset = match *self.type_of(frame, base_expr) {
+                    Ti::Pointer { base, .. } => self.pointer_offset(&types[base].inner, index),
+                    ref value_pointer => self.pointer_offset(value_pointer, index),
+                };
+                Value::Pointer(Pointer {
+                    space: pointer.space,
+                    offset: pointer.offset + offset,
+                })
+            }
+            other => other.component(index),
+        }
+    }
+
+    fn evaluate(&mut self, frame: &mut Frame<'a>, expr: Handle<naga::Expression>) -> Value {
+        use naga::Expression as E;
+        let program = self.program;
+        let module = &program.shader.module;
+        match frame.function.expressions[expr] {
+            E::Access { base, index } => {
+                let base_value = self.operand(frame, base);
+                let index = self.operand(frame, index).scalar().as_u32() as usize;
+                self.access(frame, base, base_value, index)
+            }
+            E::AccessIndex { base, index } => {
+                let base_value = self.operand(frame, base);
+                self.access(frame, base, base_value, index as usize)
+            }
+            E::Constant(handle) => program.constant_value(handle),
+            E::Splat { size, value } => {
+                let value = self.operand(frame, value);
+                Value::Composite(vec![value; size as usize])
+            }
+            E::Swizzle {
+                size,
+                vector,
+                pattern,
+            } => {
+                let vector = self.operand(frame, vector);
+                Value::Composite(
+                    pattern[..size as usize]
+                        .iter()
+                        .map(|component| vector.component(component.index() as usize))
+                        .collect(),
+                )
+            }
+            E::Compose { ty, ref components } => {
+                let values = components
+                    .iter()
+                    .map(|&component| self.operand(frame, component))
+                    .collect::<Vec<_>>();
+                match module.types[ty].inner {
+                    // vectors can be composed out of other vectors
+                    Ti::Vector { .. } => Value::Composite(
+                        values
+                            .iter()
+                            .flat_map(|value| value.flatten())
+                            .map(Value::Scalar)
+                            .collect(),
+                    ),
+                    _ => Value::Composite(values),
+                }
+            }
+            E::FunctionArgument(index) => frame.arguments[index as usize].clone(),
+            E::GlobalVariable(handle) => {
+                let var = &module.global_variables[handle];
+                let space = match var.class {
+                    naga::StorageClass::Handle => return Value::Handle(handle),
+                    naga::StorageClass::Private => Space::Private,
+                    naga::StorageClass::WorkGroup => Space::WorkGroup,
+                    _ => Space::Global(handle),
+                };
+                Value::Pointer(Pointer {
+                    space,
+                    offset: program.global_offsets[handle.index()],
+                })
+            }
+            E::LocalVariable(handle) => Value::Pointer(Pointer {
+                space: Space::Function(frame.depth),
+                offset: frame.layout.offsets[handle.index()],
+            }),
+            E::Load { pointer } => match self.operand(frame, pointer) {
+                Value::Pointer(pointer) => {
+                    let inner = self.type_of(frame, expr);
+                    let region = self.region(pointer.space);
+                    unsafe { program.read_value(region, pointer.offset, inner) }
+                }
+                other => other,
+            },
+            E::ImageSample {
+                image,
+                sampler,
+                coordinate,
+                array_index,
+                offset,
+                level,
+                depth_ref,
+            } => self.image_sample(
+                frame,
+                expr,
+                image,
+                sampler,
+                coordinate,
+                array_index,
+                offset,
+                level,
+                depth_ref,
+            ),
+            E::ImageLoad {
+                image,
+                coordinate,
+                array_index,
+                index,
+            } => {
+                let view = match self.operand(frame, image) {
+                    Value::Handle(handle) => self.resources.texture(handle),
+                    _ => None,
+                };
+                let coords = self.operand(frame, coordinate).flatten();
+                let mut coordinate = [0i32; 3];
+                for (c, value) in coordinate.iter_mut().zip(coords) {
+                    *c = value.as_i32();
+                }
+                let layer = match array_index {
+                    Some(expr) => self.operand(frame, expr).scalar().as_u32(),
+                    None => 0,
+                };
+                let level = match (index, view) {
+                    (Some(expr), Some(view)) if view.sample_count() == 1 => {
+                        self.operand(frame, expr).scalar().as_u32()
+                    }
+                    _ => 0,
+                };
+                let texel = match view {
+                    Some(view) => unsafe { view.load(coordinate, layer, level) },
+                    None => [0; 4],
+                };
+                texel_to_value(texel, self.type_of(frame, expr))
+            }
+            E::ImageQuery { image, query } => {
+                let view = match self.operand(frame, image) {
+                    Value::Handle(handle) => self.resources.texture(handle),
+                    _ => None,
+                };
+                let view = match view {
+                    Some(view) => view,
+                    None => return program.zero_value(self.type_of(frame, expr)),
+                };
+                let result = match query {
+                    naga::ImageQuery::Size { level } => {
+                        let level = match level {
+                            Some(expr) => self.operand(frame, expr).scalar().as_u32(),
+                            None => 0,
+                        };
+                        let size = view.size(level);
+                        let count = match *self.type_of(frame, expr) {
+                            Ti::Vector { size, .. } => size as usize,
+                            _ => 1,
+                        };
+                        size[..count].to_vec()
+                    }
+                    naga::ImageQuery::NumLevels => vec![view.level_count()],
+                    naga::ImageQuery::NumLayers => {
+                        let layers = view.size(0)[2];
+                        match view.dimension {
+                            wgt::TextureViewDimension::Cube
+                            | wgt::TextureViewDimension::CubeArray => vec![layers / 6],
+                            _ => vec![layers],
+                        }
+                    }
+                    naga::ImageQuery::NumSamples => vec![view.sample_count()],
+                };
+                let kind = match *self.type_of(frame, expr) {
+                    Ti::Scalar { kind, .. } | Ti::Vector { kind, .. } => kind,
+                    _ => naga::ScalarKind::Sint,
+                };
+                let values = result
+                    .into_iter()
+                    .map(|v| Value::Scalar(Scalar::Uint(v).convert(kind)))
+                    .collect::<Vec<_>>();
+                if values.len() == 1 {
+                    values.into_iter().next().unwrap()
+                } else {
+                    Value::Composite(values)
+                }
+            }
+            E::Unary { op, expr } => {
+                let value = self.operand(frame, expr);
+                value.map(&|scalar| match (op, scalar) {
+                    (naga::UnaryOperator::Negate, Scalar::Sint(v)) => {
+                        Scalar::Sint(v.wrapping_neg())
+                    }
+                    (naga::UnaryOperator::Negate, Scalar::Uint(v)) => {
+                        Scalar::Uint(v.wrapping_neg())
+                    }
+                    (naga::UnaryOperator::Negate, Scalar::Float(v)) => Scalar::Float(-v),
+                    (naga::UnaryOperator::Not, Scalar::Sint(v)) => Scalar::Sint(!v),
+                    (naga::UnaryOperator::Not, Scalar::Uint(v)) => Scalar::Uint(!v),
+                    (_, Scalar::Bool(v)) => Scalar::Bool(!v),
+                    (_, other) => other,
+                })
+            }
+            E::Binary { op, left, right } => {
+                let a = self.operand(frame, left);
+                let b = self.operand(frame, right);
+                if let naga::BinaryOperator::Multiply = op {
+                    let left_ty = self.type_of(frame, left);
+                    let right_ty = self.type_of(frame, right);
+                    match (left_ty, right_ty) {
+                        (&Ti::Matrix { .. }, &Ti::Vector { .. })
+                        | (&Ti::Matrix { .. }, &Ti::Matrix { .. }) => {
+                            return multiply_matrix(&a, &b)
+                        }
+                        (&Ti::Vector { .. }, &Ti::Matrix { .. }) => {
+                            return multiply_vector_matrix(&a, &b)
+                        }
+                        _ => {}
+                    }
+                }
+                a.zip(&b, &|a, b| binary_scalar(op, a, b))
+            }
+            E::Select {
+                condition,
+                accept,
+                reject,
+            } => {
+                let condition = self.operand(frame, condition);
+                let accept = self.operand(frame, accept);
+                let reject = self.operand(frame, reject);
+                match condition {
+                    Value::Composite(_) => condition.zip3(&accept, &reject, &|c, a, r| {
+                        if c.as_bool() {
+                            a
+                        } else {
+                            r
+                        }
+                    }),
+                    _ if condition.scalar().as_bool() => accept,
+                    _ => reject,
+                }
+            }
+            // Derivatives are not tracked, since the fragments are shaded one by one.
+            E::Derivative { expr, .. } => self.operand(frame, expr).map(&|_| Scalar::Float(0.0)),
+            E::Relational { fun, argument } => {
+                let value = self.operand(frame, argument);
+                match fun {
+                    naga::RelationalFunction::All => {
+                        Value::Scalar(Scalar::Bool(value.flatten().iter().all(|s| s.as_bool())))
+                    }
+                    naga::RelationalFunction::Any => {
+                        Value::Scalar(Scalar::Bool(value.flatten().iter().any(|s| s.as_bool())))
+                    }
+                    naga::RelationalFunction::IsNan => {
+                        value.map(&|s| Scalar::Bool(s.as_f32().is_nan()))
+                    }
+                    naga::RelationalFunction::IsInf => {
+                        value.map(&|s| Scalar::Bool(s.as_f32().is_infinite()))
+                    }
+                    naga::RelationalFunction::IsFinite => {
+                        value.map(&|s| Scalar::Bool(s.as_f32().is_finite()))
+                    }
+                    naga::RelationalFunction::IsNormal => {
+                        value.map(&|s| Scalar::Bool(s.as_f32().is_normal()))
+                    }
+                }
+            }
+            E::Math {
+                fun,
+                arg,
+                arg1,
+                arg2,
+                arg3,
+            } => {
+                let arg = self.operand(frame, arg);
+                match (fun, arg1) {
+                    (naga::MathFunction::Modf, Some(pointer_expr))
+                    | (naga::MathFunction::Frexp, Some(pointer_expr)) => {
+                        return self.decompose(frame, fun, &arg, pointer_expr)
+                    }
+                    _ => {}
+                }
+                let arg1 = arg1.map(|expr| self.operand(frame, expr));
+                let arg2 = arg2.map(|expr| self.operand(frame, expr));
+                let arg3 = arg3.map(|expr| self.operand(frame, expr));
+                math(fun, arg, arg1, arg2, arg3)
+                    .unwrap_or_else(|| program.zero_value(self.type_of(frame, expr)))
+            }
+            E::As {
+                expr: operand,
+                kind,
+                convert,
+            } => {
+                let value = self.operand(frame, operand);
+                match convert {
+                    Some(_) => value.map(&|s| s.convert(kind)),
+                    None => value.map(&|s| Scalar::from_bits(kind, s.bits())),
+                }
+            }
+            E::ArrayLength(pointer_expr) => {
+                let pointer = match self.operand(frame, pointer_expr) {
+                    Value::Pointer(pointer) => pointer,
+                    _ => return Value::Scalar(Scalar::Uint(0)),
+                };
+                let stride = match *self.type_of(frame, pointer_expr) {
+                    Ti::Pointer { base, .. } => match module.types[base].inner {
+                        Ti::Array { stride, .. } => stride as usize,
+                        _ => 1,
+                    },
+                    _ => 1,
+                };
+                let region = self.region(pointer.space);
+                Value::Scalar(Scalar::Uint(
+                    (region.len.saturating_sub(pointer.offset) / stride) as u32,
+                ))
+            }
+            E::CallResult(_) | E::AtomicResult { .. } => {
+                log::warn!("Result {:?} is used before being produced", expr);
+                program.zero_value(self.type_of(frame, expr))
+            }
+        }
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    /// Evaluates `Modf` or `Frexp`. Like in SPIR-V, the whole part or the exponent
+    /// is written through the pointer argument, and the other part is returned.
+    fn decompose(
+        &mut self,
+        frame: &mut Frame<'a>,
+        fun: naga::MathFunction,
+        arg: &Value,
+        pointer_expr: Handle<naga::Expression>,
+    ) -> Value {
+        let program = self.program;
+        let types = &program.shader.module.types;
+        let kind = match *self.type_of(frame, pointer_expr) {
+            Ti::Pointer { base, .. } => match types[base].inner {
+                Ti::Scalar { kind, .. } | Ti::Vector { kind, .. } => kind,
+                _ => naga::ScalarKind::Float,
+            },
+            Ti::ValuePointer { kind, .. } => kind,
+            _ => naga::ScalarKind::Float,
+        };
+        let (result, other) = match fun {
+            naga::MathFunction::Modf => (
+                arg.map(&|s| Scalar::Float(s.as_f32().fract())),
+                arg.map(&|s| Scalar::Float(s.as_f32().trunc())),
+            ),
+            _ => (
+                arg.map(&|s| Scalar::Float(frexp(s.as_f32()).0)),
+                arg.map(&|s| Scalar::Sint(frexp(s.as_f32()).1)),
+            ),
+        };
+        let pointer = self.operand(frame, pointer_expr);
+        self.store(
+            frame,
+            pointer_expr,
+            pointer,
+            &other.map(&|s| s.convert(kind)),
+        );
+        result
+    }
+
+    fn image_sample(
+        &mut self,
+        frame: &mut Frame<'a>,
+        expr: Handle<naga::Expression>,
+        image: Handle<naga::Expression>,
+        sampler: Handle<naga::Expression>,
+        coordinate: Handle<naga::Expression>,
+        array_index: Option<Handle<naga::Expression>>,
+        offset: Option<Handle<naga::Constant>>,
+        level: naga::SampleLevel,
+        depth_ref: Option<Handle<naga::Expression>>,
+    ) -> Value {
+        let program = self.program;
+        let resources = self.resources;
+        let view = match self.operand(frame, image) {
+            Value::Handle(handle) => resources.texture(handle),
+            _ => None,
+        };
+        let sampler = match self.operand(frame, sampler) {
+            Value::Handle(handle) => resources.sampler(handle),
+            _ => None,
+        };
+        let (view, sampler) = match (view, sampler) {
+            (Some(view), Some(sampler)) => (view, sampler),
+            _ => return program.zero_value(self.type_of(frame, expr)),
+        };
+
+        let mut coords = [0.0; 3];
+        for (c, value) in coords
+            .iter_mut()
+            .zip(self.operand(frame, coordinate).floats())
+        {
+            *c = value;
+        }
+        let mut offsets = [0; 3];
+        if let Some(constant) = offset {
+            for (o, value) in offsets
+                .iter_mut()
+                .zip(program.constant_value(constant).flatten())
+            {
+                *o = value.as_i32();
+            }
+        }
+        // Implicit level of detail would require derivatives, so the base level is used.
+        let lod = match level {
+            naga::SampleLevel::Auto | naga::SampleLevel::Zero => 0.0,
+            naga::SampleLevel::Exact(expr) | naga::SampleLevel::Bias(expr) => {
+                self.operand(frame, expr).scalar().as_f32()
+            }
+            naga::SampleLevel::Gradient { .. } => 0.0,
+        };
+        let query = SampleQuery {
+            coords,
+            array_index: match array_index {
+                Some(expr) => self.operand(frame, expr).scalar().as_u32(),
+                None => 0,
+            },
+            lod,
+            offset: offsets,
+            depth_ref: depth_ref.map(|expr| self.operand(frame, expr).scalar().as_f32()),
+        };
+        let result = unsafe { view.sample(sampler, &query) };
+        let texel = [
+            result[0].to_bits(),
+            result[1].to_bits(),
+            result[2].to_bits(),
+            result[3].to_bits(),
+        ];
+        texel_to_value(texel, self.type_of(frame, expr))
+    }
+
+    fn image_store(
+        &mut self,
+        frame: &mut Frame<'a>,
+        image: Handle<naga::Expression>,
+        coordinate: Handle<naga::Expression>,
+        array_index: Option<Handle<naga::Expression>>,
+        value: Handle<naga::Expression>,
+    ) {
+        let resources = self.resources;
+        let view = match self.operand(frame, image) {
+            Value::Handle(handle) => resources.texture(handle),
+            _ => None,
+        };
+        let view = match view {
+            Some(view) => view,
+            None => return,
+        };
+        let mut coords = [0i32; 3];
+        for (c, value) in coords
+            .iter_mut()
+            .zip(self.operand(frame, coordinate).flatten())
+        {
+            *c = value.as_i32();
+        }
+        let layer = match array_index {
+            Some(expr) => self.operand(frame, expr).scalar().as_u32(),
+            None => 0,
+        };
+        let mut texel = [0u32; 4];
+        for (t, scalar) in texel.iter_mut().zip(self.operand(frame, value).flatten()) {
+            *t = scalar.bits();
+        }
+        unsafe { view.store(coords, layer, texel) };
+    }
+}
+
+/// Converts a texel into a value of the given type,
+/// which is either a scalar or a vector.
+pub fn texel_to_value(texel: conv::Texel, inner: &Ti) -> Value {
+    match *inner {
+        Ti::Vector { size, kind, .. } => Value::Composite(
+            texel[..size as usize]
+                .iter()
+                .map(|&bits| Value::Scalar(Scalar::from_bits(kind, bits)))
+                .collect(),
+        ),
+        Ti::Scalar { kind, .. } => Value::Scalar(Scalar::from_bits(kind, texel[0])),
+        _ => Value::Scalar(Scalar::Uint(0)),
+    }
+}
+
+/// Converts raw texel components of the given kind into a value of the given type.
+pub fn components_to_value(texel: conv::Texel, source: conv::ScalarKind, inner: &Ti) -> Value {
+    let scalar = |bits: u32, kind: naga::ScalarKind| {
+        let source = match source {
+            conv::ScalarKind::Float => Scalar::Float(f32::from_bits(bits)),
+            conv::ScalarKind::Uint => Scalar::Uint(bits),
+            conv::ScalarKind::Sint => Scalar::Sint(bits as i32),
+        };
+        Value::Scalar(source.convert(kind))
+    };
+    match *inner {
+        Ti::Vector { size, kind, .. } => Value::Composite(
+            texel[..size as usize]
+                .iter()
+                .map(|&bits| scalar(bits, kind))
+                .collect(),
+        ),
+        Ti::Scalar { kind, .. } => scalar(texel[0], kind),
+        _ => Value::Scalar(Scalar::Uint(0)),
+    }
+}
+
+/// Converts a list of scalars into a value of the given scalar or vector type.
+pub fn scalars_to_value(scalars: &[Scalar], inner: &Ti) -> Value {
+    let convert = |i: usize, kind| match scalars.get(i) {
+        Some(&scalar) => Value::Scalar(scalar.convert(kind)),
+        None => Value::Scalar(Scalar::zero(kind)),
+    };
+    match *inner {
+        Ti::Vector { size, kind, .. } => {
+            Value::Composite((0..size as usize).map(|i| convert(i, kind)).collect())
+        }
+        Ti::Scalar { kind, .. } => convert(0, kind),
+        _ => Value::Scalar(Scalar::Uint(0)),
+    }
+}
+
+fn binary_scalar(op: naga::BinaryOperator, a: Scalar, b: Scalar) -> Scalar {
+    use naga::BinaryOperator as Bo;
+    match op {
+        Bo::Equal => return Scalar::Bool(a == b),
+        Bo::NotEqual => return Scalar::Bool(a != b),
+        Bo::LogicalAnd => return Scalar::Bool(a.as_bool() && b.as_bool()),
+        Bo::LogicalOr => return Scalar::Bool(a.as_bool() || b.as_bool()),
+        _ => {}
+    }
+    match a {
+        Scalar::Float(a) => {
+            let b = b.as_f32();
+            match op {
+                Bo::Add => Scalar::Float(a + b),
+                Bo::Subtract => Scalar::Float(a - b),
+                Bo::Multiply => Scalar::Float(a * b),
+                Bo::Divide => Scalar::Float(a / b),
+                Bo::Modulo => Scalar::Float(a % b),
+                Bo::Less => Scalar::Bool(a < b),
+                Bo::LessEqual => Scalar::Bool(a <= b),
+                Bo::Greater => Scalar::Bool(a > b),
+                Bo::GreaterEqual => Scalar::Bool(a >= b),
+                _ => Scalar::Float(a),
+            }
+        }
+        Scalar::Sint(a) => {
+            let shift = b.as_u32() & 31;
+            let b = b.as_i32();
+            match op {
+                Bo::Add => Scalar::Sint(a.wrapping_add(b)),
+                Bo::Subtract => Scalar::Sint(a.wrapping_sub(b)),
+                Bo::Multiply => Scalar::Sint(a.wrapping_mul(b)),
+                Bo::Divide if b == 0 => Scalar::Sint(a),
+                Bo::Divide => Scalar::Sint(a.wrapping_div(b)),
+                Bo::Modulo if b == 0 => Scalar::Sint(0),
+                Bo::Modulo => Scalar::Sint(a.wrapping_rem(b)),
+                Bo::Less => Scalar::Bool(a < b),
+                Bo::LessEqual => Scalar::Bool(a <= b),
+                Bo::Greater => Scalar::Bool(a > b),
+                Bo::GreaterEqual => Scalar::Bool(a >= b),
+                Bo::And => Scalar::Sint(a & b),
+                Bo::ExclusiveOr => Scalar::Sint(a ^ b),
+                Bo::InclusiveOr => Scalar::Sint(a | b),
+                Bo::ShiftLeft => Scalar::Sint(a << shift),
+                Bo::ShiftRight => Scalar::Sint(a >> shift),
+                _ => Scalar::Sint(a),
+            }
+        }
+        Scalar::Uint(a) => {
+            let b = b.as_u32();
+            match op {
+                Bo::Add => Scalar::Uint(a.wrapping_add(b)),
+                Bo::Subtract => Scalar::Uint(a.wrapping_sub(b)),
+                Bo::Multiply => Scalar::Uint(a.wrapping_mul(b)),
+                Bo::Divide if b == 0 => Scalar::Uint(a),
+                Bo::Divide => Scalar::Uint(a / b),
+                Bo::Modulo if b == 0 => Scalar::Uint(0),
+                Bo::Modulo => Scalar::Uint(a % b),
+                Bo::Less => Scalar::Bool(a < b),
+                Bo::LessEqual => Scalar::Bool(a <= b),
+                Bo::Greater => Scalar::Bool(a > b),
+                Bo::GreaterEqual => Scalar::Bool(a >= b),
+                Bo::And => Scalar::Uint(a & b),
+                Bo::ExclusiveOr => Scalar::Uint(a ^ b),
+                Bo::InclusiveOr => Scalar::Uint(a | b),
+                Bo::ShiftLeft => Scalar::Uint(a << (b & 31)),
+                Bo::ShiftRight => Scalar::Uint(a >> (b & 31)),
+                _ => Scalar::Uint(a),
+            }
+        }
+        Scalar::Bool(a) => {
+            let b = b.as_bool();
+            match op {
+                Bo::And => Scalar::Bool(a & b),
+                Bo::InclusiveOr => Scalar::Bool(a | b),
+                Bo::ExclusiveOr => Scalar::Bool(a ^ b),
+                _ => Scalar::Bool(a),
+            }
+        }
+    }
+}
+
+fn min_scalar(a: Scalar, b: Scalar) -> Scalar {
+    match a {
+        Scalar::Float(v) => Scalar::Float(v.min(b.as_f32())),
+        Scalar::Sint(v) => Scalar::Sint(v.min(b.as_i32())),
+        Scalar::Uint(v) => Scalar::Uint(v.min(b.as_u32())),
+        Scalar::Bool(v) => Scalar::Bool(v && b.as_bool()),
+    }
+}
+
+fn max_scalar(a: Scalar, b: Scalar) -> Scalar {
+    match a {
+        Scalar::Float(v) => Scalar::Float(v.max(b.as_f32())),
+        Scalar::Sint(v) => Scalar::Sint(v.max(b.as_i32())),
+        Scalar::Uint(v) => Scalar::Uint(v.max(b.as_u32())),
+        Scalar::Bool(v) => Scalar::Bool(v || b.as_bool()),
+    }
+}
+
+/// Multiplies a matrix by a vector or another matrix.
+fn multiply_matrix(matrix: &Value, other: &Value) -> Value {
+    let columns = match *matrix {
+        Value::Composite(ref columns) => columns,
+        _ => return matrix.clone(),
+    };
+    let multiply_vector = |vector: &Value| -> Value {
+        let weights = vector.floats();
+        let rows = columns.first().map_or(0, |column| column.floats().len());
+        let mut result = vec![0.0; rows];
+        for (column, &weight) in columns.iter().zip(weights.iter()) {
+            for (r, value) in result.iter_mut().zip(column.floats()) {
+                *r += value * weight;
+            }
+        }
+        Value::from_floats(&result)
+    };
+    match *other {
+        // matrix * matrix
+        Value::Composite(ref elements)
+            if matches!(elements.first(), Some(&Value::Composite(_))) =>
+        {
+            Value::Composite(elements.iter().map(multiply_vector).collect())
+        }
+        ref vector => multiply_vector(vector),
+    }
+}
+
+/// Multiplies a row vector by a matrix.
+fn multiply_vector_matrix(vector: &Value, matrix: &Value) -> Value {
+    let weights = vector.floats();
+    match *matrix {
+        Value::Composite(ref columns) => Value::from_floats(
+            &columns
+                .iter()
+                .map(|column| dot(&column.floats(), &weights))
+                .collect::<Vec<_>>(),
+        ),
+        _ => vector.clone(),
+    }
+}
+
+fn dot(a: &[f32], b: &[f32]) -> f32 {
+    a.iter().zip(b.iter()).map(|(a, b)| a * b).sum()
+}
+
+fn determinant(m: &[Vec<f32>]) -> f32 {
+    match m.len() {
+        1 => m[0][0],
+        2 => m[0][0] * m[1][1] - m[1][0] * m[0][1],
+        3 => {
+            m[0][0] * (m[1][1] * m[2][2] - m[2][1] * m[1][2])
+                - m[1][0] * (m[0][1] * m[2][2] - m[2][1] * m[0][2])
+                + m[2][0] * (m[0][1] * m[1][2] - m[1][1] * m[0][2])
+        }
+        4 => (0..4)
+            .map(|i| {
+                let minor = (1..4)
+                    .map(|column| {
+                        (0..4)
+                            .filter(|&row| row != i)
+                            .map(|row| m[column][row])
+                            .collect::<Vec<_>>()
+                    })
+                    .collect::<Vec<_>>();
+                let sign = if i % 2 == 0 { 1.0 } else { -1.0 };
+                sign * m[0][i] * determinant(&minor)
+            })
+            .sum(),
+        _ => 0.0,
+    }
+}
+
+/// Inverts a matrix given as a list of columns, through its adjugate.
+fn inverse(m: &[Vec<f32>]) -> Vec<Vec<f32>> {
+    let det = determinant(m);
+    (0..m.len())
+        .map(|column| {
+            (0..m.len())
+                .map(|row| {
+                    // cofactor of the transposed element
+                    let minor = m
+                        .iter()
+                        .enumerate()
+                        .filter(|&(c, _)| c != row)
+                        .map(|(_, values)| {
+                            values
+                                .iter()
+                                .enumerate()
+                                .filter(|&(r, _)| r != column)
+                                .map(|(_, &v)| v)
+                                .collect::<Vec<_>>()
+                        })
+                        .collect::<Vec<_>>();
+                    let sign = if (row + column) % 2 == 0 { 1.0 } else { -1.0 };
+                    sign * determinant(&minor) / det
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Splits a float into a significand in `[0.5, 1)` and a power of two.
+fn frexp(value: f32) -> (f32, i32) {
+    if value == 0.0 || !value.is_finite() {
+        return (value, 0);
+    }
+    let bits = value.to_bits();
+    let exponent = (bits >> 23 & 0xff) as i32;
+    if exponent == 0 {
+        // normalize the subnormals first
+        let (significand, exponent) = frexp(value * (1 << 25) as f32);
+        return (significand, exponent - 25);
+    }
+    (
+        f32::from_bits(bits & 0x807f_ffff | 0x3f00_0000),
+        exponent - 126,
+    )
+}
+
+fn pack_unorm(value: f32, max: f32) -> u32 {
+    (value.max(0.0).min(1.0) * max + 0.5) as u32
+}
+
+fn pack_snorm(value: f32, max: f32) -> u32 {
+    ((value.max(-1.0).min(1.0) * max).round() as i32) as u32
+}
+
+/// Evaluates a math function. Returns `None` if the function is not supported.
+fn math(
+    fun: naga::MathFunction,
+    arg: Value,
+    arg1: Option<Value>,
+    arg2: Option<Value>,
+    arg3: Option<Value>,
+) -> Option<Value> {
+    use naga::MathFunction as Mf;
+    let float = |f: fn(f32) -> f32| arg.map(&|s| Scalar::Float(f(s.as_f32())));
+    let float2 = |f: fn(f32, f32) -> f32| -> Option<Value> {
+        Some(arg.zip(arg1.as_ref()?, &|a, b| {
+            Scalar::Float(f(a.as_f32(), b.as_f32()))
+        }))
+    };
+    Some(match fun {
+        Mf::Abs => arg.map(&|s| match s {
+            Scalar::Sint(v) => Scalar::Sint(v.wrapping_abs()),
+            Scalar::Float(v) => Scalar::Float(v.abs()),
+            other => other,
+        }),
+        Mf::Min => arg.zip(arg1.as_ref()?, &min_scalar),
+        Mf::Max => arg.zip(arg1.as_ref()?, &max_scalar),
+        Mf::Clamp => arg.zip3(arg1.as_ref()?, arg2.as_ref()?, &|v, low, high| {
+            min_scalar(max_scalar(v, low), high)
+        }),
+        Mf::Cos => float(f32::cos),
+        Mf::Cosh => float(f32::cosh),
+        Mf::Sin => float(f32::sin),
+        Mf::Sinh => float(f32::sinh),
+        Mf::Tan => float(f32::tan),
+        Mf::Tanh => float(f32::tanh),
+        Mf::Acos => float(f32::acos),
+        Mf::Asin => float(f32::asin),
+        Mf::Atan => float(f32::atan),
+        Mf::Atan2 => float2(f32::atan2)?,
+        Mf::Asinh => float(f32::asinh),
+        Mf::Acosh => float(f32::acosh),
+        Mf::Atanh => float(f32::atanh),
+        Mf::Ceil => float(f32::ceil),
+        Mf::Floor => float(f32::floor),
+        Mf::Round => float(|v| {
+            // round half to even
+            let rounded = v.round();
+            if (v - v.trunc()).abs() == 0.5 && rounded % 2.0 != 0.0 {
+                rounded - v.signum()
+            } else {
+                rounded
+            }
+        }),
+        Mf::Fract => float(|v| v - v.floor()),
+        Mf::Trunc => float(f32::trunc),
+        Mf::Ldexp => arg.zip(arg1.as_ref()?, &|a, b| {
+            Scalar::Float(a.as_f32() * 2f32.powi(b.as_i32()))
+        }),
+        Mf::Exp => float(f32::exp),
+        Mf::Exp2 => float(f32::exp2),
+        Mf::Log => float(f32::ln),
+        Mf::Log2 => float(f32::log2),
+        Mf::Pow => float2(f32::powf)?,
+        Mf::Dot => {
+            let b = arg1?;
+            match arg.scalar() {
+                Scalar::Float(_) => Value::Scalar(Scalar::Float(dot(&arg.floats(), &b.floats()))),
+                _ => {
+                    let products = arg.zip(&b, &|a, b| {
+                        binary_scalar(naga::BinaryOperator::Multiply, a, b)
+                    });
+                    let flat = products.flatten();
+                    let first = flat.first().cloned().unwrap_or(Scalar::Uint(0));
+                    Value::Scalar(flat.iter().skip(1).fold(first, |sum, &v| {
+                        binary_scalar(naga::BinaryOperator::Add, sum, v)
+                    }))
+                }
+            }
+        }
+        Mf::Outer => {
+            let a = arg.floats();
+            let b = arg1?.floats();
+            Value::Composite(
+                b.iter()
+                    .map(|&column| {
+                        Value::from_floats(&a.iter().map(|&v| v * column).collect::<Vec<_>>())
+                    })
+                    .collect(),
+            )
+        }
+        Mf::Cross => {
+            let a = arg.floats();
+            let b = arg1?.floats();
+            Value::from_floats(&[
+                a[1] * b[2] - a[2] * b[1],
+                a[2] * b[0] - a[0] * b[2],
+                a[0] * b[1] - a[1] * b[0],
+            ])
+        }
+        Mf::Distance => {
+            let a = arg.floats();
+            let b = arg1?.floats();
+            let diff = a
+                .iter()
+                .zip(b.iter())
+                .map(|(a, b)| a - b)
+                .collect::<Vec<_>>();
+            Value::Scalar(Scalar::Float(dot(&diff, &diff).sqrt()))
+        }
+        Mf::Length => {
+            let a = arg.floats();
+            Value::Scalar(Scalar::Float(dot(&a, &a).sqrt()))
+        }
+        Mf::Normalize => {
+            let a = arg.floats();
+            let length = dot(&a, &a).sqrt();
+            Value::from_floats(&a.iter().map(|v| v / length).collect::<Vec<_>>())
+        }
+        Mf::FaceForward => {
+            let n = arg.floats();
+            let i = arg1?.floats();
+            let nref = arg2?.floats();
+            let sign = if dot(&nref, &i) < 0.0 { 1.0 } else { -1.0 };
+            Value::from_floats(&n.iter().map(|v| v * sign).collect::<Vec<_>>())
+        }
+        Mf::Reflect => {
+            let i = arg.floats();
+            let n = arg1?.floats();
+            let d = 2.0 * dot(&n, &i);
+            Value::from_floats(
+                &i.iter()
+                    .zip(n.iter())
+                    .map(|(i, n)| i - d * n)
+                    .collect::<Vec<_>>(),
+            )
+        }
+        Mf::Refract => {
+            let i = arg.floats();
+            let n = arg1?.floats();
+            let eta = arg2?.scalar().as_f32();
+            let d = dot(&n, &i);
+            let k = 1.0 - eta * eta * (1.0 - d * d);
+            let result = if k < 0.0 {
+                vec![0.0; i.len()]
+            } else {
+                i.iter()
+                    .zip(n.iter())
+                    .map(|(i, n)| eta * i - (eta * d + k.sqrt()) * n)
+                    .collect()
+            };
+            Value::from_floats(&result)
+        }
+        Mf::Sign => arg.map(&|s| match s {
+            Scalar::Sint(v) => Scalar::Sint(v.signum()),
+            Scalar::Float(v) if v == 0.0 => Scalar::Float(0.0),
+            Scalar::Float(v) => Scalar::Float(v.signum()),
+            other => other,
+        }),
+        Mf::Fma => arg.zip3(arg1.as_ref()?, arg2.as_ref()?, &|a, b, c| {
+            Scalar::Float(a.as_f32().mul_add(b.as_f32(), c.as_f32()))
+        }),
+        Mf::Mix => arg.zip3(arg1.as_ref()?, arg2.as_ref()?, &|a, b, t| {
+            let t = t.as_f32();
+            Scalar::Float(a.as_f32() * (1.0 - t) + b.as_f32() * t)
+        }),
+        Mf::Step => arg.zip(arg1.as_ref()?, &|edge, x| {
+            Scalar::Float(if x.as_f32() < edge.as_f32() { 0.0 } else { 1.0 })
+        }),
+        Mf::SmoothStep => arg.zip3(arg1.as_ref()?, arg2.as_ref()?, &|low, high, x| {
+            let (low, high) = (low.as_f32(), high.as_f32());
+            let t = ((x.as_f32() - low) / (high - low)).max(0.0).min(1.0);
+            Scalar::Float(t * t * (3.0 - 2.0 * t))
+        }),
+        Mf::Sqrt => float(f32::sqrt),
+        Mf::InverseSqrt => float(|v| 1.0 / v.sqrt()),
+        Mf::Transpose => {
+            let columns = match arg {
+                Value::Composite(ref columns) => {
+                    columns.iter().map(Value::floats).collect::<Vec<_>>()
+                }
+                _ => return None,
+            };
+            let rows = columns.first().map_or(0, Vec::len);
+            Value::Composite(
+                (0..rows)
+                    .map(|r| Value::from_floats(&columns.iter().map(|c| c[r]).collect::<Vec<_>>()))
+                    .collect(),
+            )
+        }
+        Mf::Determinant => {
+            let columns = match arg {
+                Value::Composite(ref columns) => {
+                    columns.iter().map(Value::floats).collect::<Vec<_>>()
+                }
+                _ => return None,
+            };
+            Value::Scalar(Scalar::Float(determinant(&columns)))
+        }
+        Mf::CountOneBits => arg.map(&|s| match s {
+            Scalar::Sint(v) => Scalar::Sint(v.count_ones() as i32),
+            other => Scalar::Uint(other.as_u32().count_ones()),
+        }),
+        Mf::ReverseBits => arg.map(&|s| match s {
+            Scalar::Sint(v) => Scalar::Sint(v.reverse_bits()),
+            other => Scalar::Uint(other.as_u32().reverse_bits()),
+        }),
+        Mf::ExtractBits => {
+            let offset = arg1?.scalar().as_u32().min(32);
+            let count = arg2?.scalar().as_u32().min(32 - offset);
+            arg.map(&|s| {
+                if count == 0 {
+                    return Scalar::from_bits(kind_of(s), 0);
+                }
+                match s {
+                    // sign extend the extracted bits
+                    Scalar::Sint(v) => Scalar::Sint((v << (32 - offset - count)) >> (32 - count)),
+                    other => {
+                        Scalar::Uint((other.as_u32() << (32 - offset - count)) >> (32 - count))
+                    }
+                }
+            })
+        }
+        Mf::InsertBits => {
+            let offset = arg2?.scalar().as_u32().min(32);
+            let count = arg3?.scalar().as_u32().min(32 - offset);
+            let mask = if count == 32 {
+                !0
+            } else {
+                ((1u32 << count) - 1) << offset
+            };
+            arg.zip(&arg1?, &|e, newbits| {
+                let bits = (e.bits() & !mask) | ((newbits.bits() << offset) & mask);
+                Scalar::from_bits(kind_of(e), bits)
+            })
+        }
+        Mf::Pack4x8snorm => {
+            let v = arg.floats();
+            Value::Scalar(Scalar::Uint((0..4).fold(0, |acc, i| {
+                acc | (pack_snorm(v[i], 127.0) & 0xff) << (i * 8)
+            })))
+        }
+        Mf::Pack4x8unorm => {
+            let v = arg.floats();
+            Value::Scalar(Scalar::Uint(
+                (0..4).fold(0, |acc, i| acc | pack_unorm(v[i], 255.0) << (i * 8)),
+            ))
+        }
+        Mf::Pack2x16snorm => {
+            let v = arg.floats();
+            Value::Scalar(Scalar::Uint(
+                (pack_snorm(v[0], 32767.0) & 0xffff) | pack_snorm(v[1], 32767.0) << 16,
+            ))
+        }
+        Mf::Pack2x16unorm => {
+            let v = arg.floats();
+            Value::Scalar(Scalar::Uint(
+                pack_unorm(v[0], 65535.0) | pack_unorm(v[1], 65535.0) << 16,
+            ))
+        }
+        Mf::Pack2x16float => {
+            let v = arg.floats();
+            Value::Scalar(Scalar::Uint(
+                conv::f32_to_f16(v[0]) as u32 | (conv::f32_to_f16(v[1]) as u32) << 16,
+            ))
+        }
+        Mf::Unpack4x8snorm => {
+            let v = arg.scalar().as_u32();
+            Value::from_floats(
+                &(0..4)
+                    .map(|i| ((v >> (i * 8)) as u8 as i8 as f32 / 127.0).max(-1.0))
+                    .collect::<Vec<_>>(),
+            )
+        }
+        Mf::Unpack4x8unorm => {
+            let v = arg.scalar().as_u32();
+            Value::from_floats(
+                &(0..4)
+                    .map(|i| ((v >> (i * 8)) & 0xff) as f32 / 255.0)
+                    .collect::<Vec<_>>(),
+            )
+        }
+        Mf::Unpack2x16snorm => {
+            let v = arg.scalar().as_u32();
+            Value::from_floats(&[
+                (v as u16 as i16 as f32 / 32767.0).max(-1.0),
+                ((v >> 16) as u16 as i16 as f32 / 32767.0).max(-1.0),
+            ])
+        }
+        Mf::Unpack2x16unorm => {
+            let v = arg.scalar().as_u32();
+            Value::from_floats(&[(v & 0xffff) as f32 / 65535.0, (v >> 16) as f32 / 65535.0])
+        }
+        Mf::Unpack2x16float => {
+            let v = arg.scalar().as_u32();
+            Value::from_floats(&[
+                conv::f16_to_f32(v as u16),
+                conv::f16_to_f32((v >> 16) as u16),
+            ])
+        }
+        Mf::Inverse => {
+            let columns = match arg {
+                Value::Composite(ref columns) => {
+                    columns.iter().map(Value::floats).collect::<Vec<_>>()
+                }
+                _ => return None,
+            };
+            Value::Composite(
+                inverse(&columns)
+                    .into_iter()
+                    .map(|column| Value::from_floats(&column))
+                    .collect(),
+            )
+        }
+        // These write through a pointer, see `Invocation::decompose`.
+        Mf::Modf | Mf::Frexp => {
+            log::warn!("Math function {:?} is missing its pointer argument", fun);
+            return None;
+        }
+    })
+}
+
+fn kind_of(scalar: Scalar) -> naga::ScalarKind {
+    match scalar {
+        Scalar::Sint(_) => naga::ScalarKind::Sint,
+        Scalar::Uint(_) => naga::ScalarKind::Uint,
+        Scalar::Float(_) => naga::ScalarKind::Float,
+        Scalar::Bool(_) => naga::ScalarKind::Bool,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{frexp, inverse, WorkgroupBarrier};
+    use std::{sync::Arc, thread};
+
+    #[test]
+    fn frexp_splits_floats() {
+        assert_eq!(frexp(1.0), (0.5, 1));
+        assert_eq!(frexp(-12.0), (-0.75, 4));
+        assert_eq!(frexp(0.0), (0.0, 0));
+        let (significand, exponent) = frexp(f32::from_bits(1));
+        assert_eq!((significand, exponent), (0.5, -148));
+    }
+
+    #[test]
+    fn inverse_of_matrices() {
+        let m = vec![vec![4.0, 2.0], vec![7.0, 6.0]];
+        assert_eq!(inverse(&m), vec![vec![0.6, -0.2], vec![-0.7, 0.4]]);
+        let m = vec![
+            vec![2.0, 0.0, 0.0, 0.0],
+            vec![0.0, 4.0, 0.0, 0.0],
+            vec![0.0, 0.0, 0.5, 0.0],
+            vec![3.0, 2.0, 1.0, 1.0],
+        ];
+        let expected = vec![
+            vec![0.5, 0.0, 0.0, 0.0],
+            vec![0.0, 0.25, 0.0, 0.0],
+            vec![0.0, 0.0, 2.0, 0.0],
+            vec![-1.5, -0.5, -2.0, 1.0],
+        ];
+        assert_eq!(inverse(&m), expected);
+    }
+
+    #[test]
+    fn poisoned_barrier_releases_waiters() {
+        let barrier = Arc::new(WorkgroupBarrier::new(3));
+        let waiter = {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || barrier.wait())
+        };
+        // The third invocation never arrives.
+        barrier.poison();
+        assert!(!waiter.join().unwrap());
+        assert!(!barrier.wait());
+    }
+
+    #[test]
+    fn barrier_is_reusable() {
+        let barrier = Arc::new(WorkgroupBarrier::new(2));
+        let other = {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || barrier.wait() && barrier.wait())
+        };
+        assert!(barrier.wait() && barrier.wait());
+        assert!(other.join().unwrap());
+    }
+}
@@ -0,0 +1,425 @@
+/*!
+# Software CPU implementation.
+
+This backend doesn't talk to any GPU driver. It exists so that the rest of the stack
+can be exercised on machines without a graphics device, such as CI runners.
+
+## Memory
+
+Buffers and textures are plain host allocations. Every buffer is mappable,
+and mapping is persistent and coherent, since the "device" is the same CPU.
+
+Textures are stored linearly, mip level after mip level. Within a level the
+array layers (or depth slices for 3D textures) follow each other, and texel
+blocks are tightly packed in rows.
+Multi-sampled textures are stored with a single sample, and resolving them
+is a plain copy.
+
+## Execution
+
+Command encoders record the commands into a list, in the same way the GLES backend
+does it, and the queue executes the list in `submit`. All the work is finished by the
+time `submit` returns, so fences are signaled right away.
+
+## Shaders
+
+Shaders are not translated into anything. Instead, the naga IR is interpreted
+directly, see `interp.rs`. Compute workgroups are executed one invocation at a time,
+unless the entry point issues control barriers: then all invocations of a workgroup
+run on separate threads, which meet at the barriers. These threads are kept by the queue
+and reused by the following workgroups. If an invocation panics, the
+other invocations of its workgroup are released from the barriers, and `submit`
+reports the device as lost. Panicking vertex and fragment invocations lose the device too,
+and so do loops running for too long, like the watchdog of GPU drivers would.
+The time a single loop may run for defaults to a few seconds, and is read from the
+`WGPU_CPU_LOOP_TIMEOUT_MS` environment variable when the instance is created.
+A value of 0 disables the watchdog.
+
+## Rasterization
+
+Rasterization is a straightforward scanline-free implementation over the bounding
+box of each triangle, with the top-left fill rule, perspective-correct interpolation,
+clipping to the near plane, and the fixed-function depth, stencil and blending stages.
+Derivatives are not computed, so implicit level-of-detail sampling always uses
+the base level of a view.
+*/
+
+mod adapter;
+mod command;
+mod conv;
+mod device;
+mod interp;
+mod queue;
+mod raster;
+mod texture;
+
+use parking_lot::Mutex;
+use std::{ops::Range, ptr::NonNull, sync::Arc, time::Duration};
+
+#[derive(Clone)]
+pub struct Api;
+
+const MAX_PUSH_CONSTANT_SIZE: u32 = 128;
+/// Time after which a loop is considered to never finish, unless overridden
+/// by `WGPU_CPU_LOOP_TIMEOUT_MS`.
+const DEFAULT_LOOP_TIMEOUT: Duration = Duration::from_secs(2);
+
+impl crate::Api for Api {
+    type Instance = Instance;
+    type Surface = Surface;
+    type Adapter = Adapter;
+    type Device = Device;
+
+    type Queue = Queue;
+    type CommandEncoder = CommandEncoder;
+    type CommandBuffer = CommandBuffer;
+
+    type Buffer = Buffer;
+    type Texture = Texture;
+    type SurfaceTexture = Texture;
+    type TextureView = TextureView;
+    type Sampler = Sampler;
+    type QuerySet = QuerySet;
+    type Fence = Fence;
+
+    type BindGroupLayout = BindGroupLayout;
+    type BindGroup = BindGroup;
+    type PipelineLayout = PipelineLayout;
+    type ShaderModule = ShaderModule;
+    type RenderPipeline = RenderPipeline;
+    type ComputePipeline = ComputePipeline;
+    type PipelineCache = ();
+}
+
+pub struct Instance {
+    flags: crate::InstanceFlags,
+    loop_timeout: Option<Duration>,
+}
+
+/// The CPU backend can't present, so surfaces are never created.
+pub struct Surface {
+    _private: (),
+}
+
+pub struct Adapter {
+    loop_timeout: Option<Duration>,
+}
+
+pub struct Device {
+    loop_timeout: Option<Duration>,
+}
+
+pub struct Queue {
+    /// Reference point for the timestamp queries.
+    start_time: std::time::Instant,
+    workers: Mutex<queue::WorkerPool>,
+}
+
+/// Host memory block, backing either a buffer or a texture.
+///
+/// The contents are accessed through raw pointers, both by the mappings
+/// and by the queue. Synchronization between them is the responsibility of
+/// the user, just like with any other mapped memory.
+#[derive(Debug)]
+struct Memory {
+    ptr: NonNull<u8>,
+    size: usize,
+}
+
+unsafe impl Send for Memory {}
+unsafe impl Sync for Memory {}
+
+impl Memory {
+    fn new(size: usize) -> Self {
+        let data = vec![0u8; size].into_boxed_slice();
+        Self {
+            // never null, even for an empty slice
+            ptr: NonNull::new(Box::into_raw(data) as *mut u8).unwrap(),
+            size,
+        }
+    }
+
+    /// Returns the bytes in `range`.
+    ///
+    /// # Safety
+    /// The range has to be within the block, and nobody should be writing to it.
+    unsafe fn slice(&self, range: Range<usize>) -> &[u8] {
+        debug_assert!(range.start <= range.end && range.end <= self.size);
+        std::slice::from_raw_parts(self.ptr.as_ptr().add(range.start), range.end - range.start)
+    }
+
+    /// Returns the bytes in `range` for writing.
+    ///
+    /// # Safety
+    /// The range has to be within the block, and nobody else should be accessing it.
+    #[allow(clippy::mut_from_ref)]
+    unsafe fn slice_mut(&self, range: Range<usize>) -> &mut [u8] {
+        debug_assert!(range.start <= range.end && range.end <= self.size);
+        std::slice::from_raw_parts_mut(self.ptr.as_ptr().add(range.start), range.end - range.start)
+    }
+
+    /// Copies `size` bytes between two blocks, which may be the same block.
+    unsafe fn copy(src: &Self, src_offset: usize, dst: &Self, dst_offset: usize, size: usize) {
+        debug_assert!(src_offset + size <= src.size && dst_offset + size <= dst.size);
+        std::ptr::copy(
+            src.ptr.as_ptr().add(src_offset),
+            dst.ptr.as_ptr().add(dst_offset),
+            size,
+        );
+    }
+}
+
+impl Drop for Memory {
+    fn drop(&mut self) {
+        unsafe {
+            let slice = std::ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.size);
+            drop(Box::from_raw(slice));
+        }
+    }
+}
+
+#[derive(Debug)]
+pub struct Buffer {
+    memory: Arc<Memory>,
+}
+
+/// Storage of a texture, shared between the texture, its views, and the commands.
+#[derive(Debug)]
+struct Image {
+    memory: Memory,
+    format: wgt::TextureFormat,
+    dimension: wgt::TextureDimension,
+    size: wgt::Extent3d,
+    mip_level_count: u32,
+    sample_count: u32,
+    /// Offset of each mip level in the memory block.
+    mip_offsets: Box<[usize]>,
+}
+
+#[derive(Debug)]
+pub struct Texture {
+    image: Arc<Image>,
+}
+
+#[derive(Clone, Debug)]
+pub struct TextureView {
+    image: Arc<Image>,
+    format: wgt::TextureFormat,
+    dimension: wgt::TextureViewDimension,
+    aspects: crate::FormatAspects,
+    mip_levels: Range<u32>,
+    array_layers: Range<u32>,
+}
+
+#[derive(Clone, Debug)]
+pub struct Sampler {
+    address_modes: [wgt::AddressMode; 3],
+    mag_filter: wgt::FilterMode,
+    min_filter: wgt::FilterMode,
+    mipmap_filter: wgt::FilterMode,
+    lod_clamp: Range<f32>,
+    compare: Option<wgt::CompareFunction>,
+    border_color: wgt::SamplerBorderColor,
+}
+
+#[derive(Debug)]
+pub struct QuerySet {
+    results: Arc<Mutex<Vec<u64>>>,
+}
+
+#[derive(Debug)]
+pub struct Fence {
+    value: crate::FenceValue,
+}
+
+pub struct BindGroupLayout {
+    entries: Arc<[wgt::BindGroupLayoutEntry]>,
+}
+
+pub struct PipelineLayout {
+    _private: (),
+}
+
+#[derive(Clone, Debug)]
+enum Binding {
+    Buffer {
+        memory: Arc<Memory>,
+        offset: wgt::BufferAddress,
+        size: wgt::BufferAddress,
+        dynamic: bool,
+    },
+    Texture(TextureView),
+    Sampler(Sampler),
+}
+
+#[derive(Debug)]
+pub struct BindGroup {
+    /// Resources, sorted by their binding index.
+    entries: Arc<[(u32, Binding)]>,
+}
+
+#[derive(Debug)]
+pub struct ShaderModule {
+    naga: Arc<crate::NagaShader>,
+}
+
+#[derive(Clone, Debug)]
+struct VertexBufferDesc {
+    stride: wgt::BufferAddress,
+    step: wgt::VertexStepMode,
+    attributes: Box<[wgt::VertexAttribute]>,
+}
+
+struct RenderPipelineInner {
+    vertex: Arc<interp::Program>,
+    fragment: Option<Arc<interp::Program>>,
+    vertex_buffers: Box<[VertexBufferDesc]>,
+    primitive: wgt::PrimitiveState,
+    depth_stencil: Option<wgt::DepthStencilState>,
+    color_targets: Box<[wgt::ColorTargetState]>,
+}
+
+pub struct RenderPipeline {
+    inner: Arc<RenderPipelineInner>,
+}
+
+pub struct ComputePipeline {
+    program: Arc<interp::Program>,
+}
+
+#[derive(Clone)]
+struct ColorTarget {
+    view: TextureView,
+    resolve_view: Option<TextureView>,
+    ops: crate::AttachmentOps,
+    clear_value: wgt::Color,
+}
+
+#[derive(Clone)]
+struct DepthStencilTarget {
+    view: TextureView,
+    depth_ops: crate::AttachmentOps,
+    stencil_ops: crate::AttachmentOps,
+    clear_value: (f32, u32),
+}
+
+struct RenderPassDesc {
+    extent: wgt::Extent3d,
+    color_targets: Vec<ColorTarget>,
+    depth_stencil_target: Option<DepthStencilTarget>,
+}
+
+enum Command {
+    ClearBuffer {
+        dst: Arc<Memory>,
+        range: crate::MemoryRange,
+    },
+    ClearTexture {
+        dst: Arc<Image>,
+        range: wgt::ImageSubresourceRange,
+    },
+    CopyBufferToBuffer {
+        src: Arc<Memory>,
+        dst: Arc<Memory>,
+        copy: crate::BufferCopy,
+    },
+    CopyTextureToTexture {
+        src: Arc<Image>,
+        dst: Arc<Image>,
+        copy: crate::TextureCopy,
+    },
+    CopyBufferToTexture {
+        src: Arc<Memory>,
+        dst: Arc<Image>,
+        copy: crate::BufferTextureCopy,
+    },
+    CopyTextureToBuffer {
+        src: Arc<Image>,
+        dst: Arc<Memory>,
+        copy: crate::BufferTextureCopy,
+    },
+    BeginQuery(Arc<Mutex<Vec<u64>>>, u32),
+    EndQuery,
+    WriteTimestamp(Arc<Mutex<Vec<u64>>>, u32),
+    ResetQueries(Arc<Mutex<Vec<u64>>>, Range<u32>),
+    CopyQueryResults {
+        results: Arc<Mutex<Vec<u64>>>,
+        range: Range<u32>,
+        dst: Arc<Memory>,
+        offset: wgt::BufferAddress,
+        stride: wgt::BufferAddress,
+    },
+    BeginRenderPass(RenderPassDesc),
+    EndRenderPass,
+    SetBindGroup {
+        index: u32,
+        entries: Arc<[(u32, Binding)]>,
+        dynamic_offsets: Vec<wgt::DynamicOffset>,
+    },
+    SetPushConstants {
+        offset: u32,
+        data: Vec<u32>,
+    },
+    SetRenderPipeline(Arc<RenderPipelineInner>),
+    SetIndexBuffer {
+        memory: Arc<Memory>,
+        offset: wgt::BufferAddress,
+        format: wgt::IndexFormat,
+    },
+    SetVertexBuffer {
+        index: u32,
+        memory: Arc<Memory>,
+        offset: wgt::BufferAddress,
+    },
+    SetViewport {
+        rect: crate::Rect<f32>,
+        depth: Range<f32>,
+    },
+    SetScissor(crate::Rect<u32>),
+    SetStencilReference(u32),
+    SetBlendConstants([f32; 4]),
+    Draw {
+        start_vertex: u32,
+        vertex_count: u32,
+        start_instance: u32,
+        instance_count: u32,
+    },
+    DrawIndexed {
+        start_index: u32,
+        index_count: u32,
+        base_vertex: i32,
+        start_instance: u32,
+        instance_count: u32,
+    },
+    DrawIndirect {
+        memory: Arc<Memory>,
+        offset: wgt::BufferAddress,
+        count: DrawCount,
+        indexed: bool,
+    },
+    SetComputePipeline(Arc<interp::Program>),
+    Dispatch([u32; 3]),
+    DispatchIndirect {
+        memory: Arc<Memory>,
+        offset: wgt::BufferAddress,
+    },
+}
+
+/// Number of draws issued by an indirect draw command.
+enum DrawCount {
+    Direct(u32),
+    Indirect {
+        memory: Arc<Memory>,
+        offset: wgt::BufferAddress,
+        max_count: u32,
+    },
+}
+
+#[derive(Default)]
+pub struct CommandBuffer {
+    commands: Vec<Command>,
+}
+
+pub struct CommandEncoder {
+    cmd_buffer: CommandBuffer,
+}
@@ -0,0 +1,652 @@
+use super::{conv, interp, raster, texture, Binding, Command as C, DrawCount, Memory};
+use parking_lot::Mutex;
+use std::{
+    ops::Range,
+    panic::{self, AssertUnwindSafe},
+    sync::{mpsc, Arc},
+    thread,
+};
+
+/// Work of a worker thread, returning `false` if the invocation panicked.
+type Job = Box<dyn FnOnce() -> bool + Send>;
+
+struct Worker {
+    jobs: mpsc::Sender<Job>,
+    thread: thread::JoinHandle<()>,
+}
+
+/// Threads running the invocations of the workgroups that use control barriers.
+///
+/// The invocations of such a workgroup have to run concurrently to meet at the barriers,
+/// so the pool grows to a thread per invocation of the largest workgroup dispatched.
+/// The threads are reused by all the following workgroups.
+pub struct WorkerPool {
+    workers: Vec<Worker>,
+    result_sender: mpsc::Sender<bool>,
+    results: mpsc::Receiver<bool>,
+}
+
+impl WorkerPool {
+    pub fn new() -> Self {
+        let (result_sender, results) = mpsc::channel();
+        Self {
+            workers: Vec::new(),
+            result_sender,
+            results,
+        }
+    }
+
+    /// Runs each job on its own thread, and waits for all of them.
+    /// Returns `false` if any of the jobs failed.
+    fn run(&mut self, jobs: Vec<Job>) -> Result<bool, crate::DeviceError> {
+        while self.workers.len() < jobs.len() {
+            let (sender, receiver) = mpsc::channel::<Job>();
+            let results = self.result_sender.clone();
+            let thread = thread::Builder::new()
+                .name(format!("wgpu-cpu-worker-{}", self.workers.len()))
+                .spawn(move || {
+                    for job in receiver {
+                        if results.send(job()).is_err() {
+                            break;
+                        }
+                    }
+                })
+                .map_err(|e| {
+                    log::error!("Unable to spawn a worker thread: {}", e);
+                    crate::DeviceError::OutOfMemory
+                })?;
+            self.workers.push(Worker {
+                jobs: sender,
+                thread,
+            });
+        }
+
+        let count = jobs.len();
+        for (worker, job) in self.workers.iter().zip(jobs) {
+            if worker.jobs.send(job).is_err() {
+                return Err(crate::DeviceError::Lost);
+            }
+        }
+        // Wait for all the jobs, even after a failure.
+        let mut succeeded = true;
+        for _ in 0..count {
+            succeeded &= self.results.recv().map_err(|_| crate::DeviceError::Lost)?;
+        }
+        Ok(succeeded)
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        for worker in self.workers.drain(..) {
+            // closing the channel stops the thread
+            drop(worker.jobs);
+            let _ = worker.thread.join();
+        }
+    }
+}
+
+/// Reads a `u32` from the memory, returning zero if it's out of bounds.
+unsafe fn read_u32(memory: &Memory, offset: wgt::BufferAddress) -> u32 {
+    let offset = offset as usize;
+    if offset + 4 > memory.size {
+        return 0;
+    }
+    let bytes = memory.slice(offset..offset + 4);
+    u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+}
+
+/// Bindings and dynamic state of the command buffer being executed.
+struct State {
+    bind_groups: Vec<Option<Arc<[(u32, Binding)]>>>,
+    push_constants: Arc<Memory>,
+    render_pipeline: Option<Arc<super::RenderPipelineInner>>,
+    compute_program: Option<Arc<interp::Program>>,
+    index_buffer: Option<(Arc<Memory>, wgt::BufferAddress, wgt::IndexFormat)>,
+    vertex_buffers: Vec<Option<(Arc<Memory>, wgt::BufferAddress)>>,
+    render_pass: Option<super::RenderPassDesc>,
+    viewport: crate::Rect<f32>,
+    depth_range: Range<f32>,
+    scissor: crate::Rect<u32>,
+    stencil_reference: u32,
+    blend_constants: [f32; 4],
+    /// Occlusion query being recorded, and the number of samples passed so far.
+    occlusion_query: Option<(Arc<Mutex<Vec<u64>>>, u32, u64)>,
+}
+
+impl State {
+    fn new() -> Self {
+        Self {
+            bind_groups: Vec::new(),
+            push_constants: Arc::new(Memory::new(super::MAX_PUSH_CONSTANT_SIZE as usize)),
+            render_pipeline: None,
+            compute_program: None,
+            index_buffer: None,
+            vertex_buffers: Vec::new(),
+            render_pass: None,
+            viewport: crate::Rect {
+                x: 0.0,
+                y: 0.0,
+                w: 0.0,
+                h: 0.0,
+            },
+            depth_range: 0.0..1.0,
+            scissor: crate::Rect {
+                x: 0,
+                y: 0,
+                w: 0,
+                h: 0,
+            },
+            stencil_reference: 0,
+            blend_constants: [0.0; 4],
+            occlusion_query: None,
+        }
+    }
+}
+
+impl super::Queue {
+    unsafe fn execute(&self, state: &mut State, command: &C) -> Result<(), crate::DeviceError> {
+        match *command {
+            C::ClearBuffer { ref dst, ref range } => {
+                dst.slice_mut(range.start as usize..range.end as usize)
+                    .fill(0);
+            }
+            C::ClearTexture { ref dst, ref range } => dst.clear(range),
+            C::CopyBufferToBuffer {
+                ref src,
+                ref dst,
+                ref copy,
+            } => Memory::copy(
+                src,
+                copy.src_offset as usize,
+                dst,
+                copy.dst_offset as usize,
+                copy.size.get() as usize,
+            ),
+            C::CopyTextureToTexture {
+                ref src,
+                ref dst,
+                ref copy,
+            } => texture::copy_texture_to_texture(src, dst, copy),
+            C::CopyBufferToTexture {
+                ref src,
+                ref dst,
+                ref copy,
+            } => texture::copy_buffer_to_texture(src, dst, copy),
+            C::CopyTextureToBuffer {
+                ref src,
+                ref dst,
+                ref copy,
+            } => texture::copy_texture_to_buffer(src, dst, copy),
+            C::BeginQuery(ref results, index) => {
+                state.occlusion_query = Some((Arc::clone(results), index, 0));
+            }
+            C::EndQuery => {
+                if let Some((results, index, count)) = state.occlusion_query.take() {
+                    results.lock()[index as usize] = count;
+                }
+            }
+            C::WriteTimestamp(ref results, index) => {
+                results.lock()[index as usize] = self.start_time.elapsed().as_nanos() as u64;
+            }
+            C::ResetQueries(ref results, ref range) => {
+                for result in results.lock()[range.start as usize..range.end as usize].iter_mut() {
+                    *result = 0;
+                }
+            }
+            C::CopyQueryResults {
+                ref results,
+                ref range,
+                ref dst,
+                offset,
+                stride,
+            } => {
+                let results = results.lock();
+                for (i, query) in (range.start..range.end).enumerate() {
+                    let start = (offset + i as wgt::BufferAddress * stride) as usize;
+                    dst.slice_mut(start..start + 8)
+                        .copy_from_slice(&results[query as usize].to_le_bytes());
+                }
+            }
+            C::BeginRenderPass(ref desc) => {
+                self.begin_render_pass(desc);
+                state.viewport = crate::Rect {
+                    x: 0.0,
+                    y: 0.0,
+                    w: desc.extent.width as f32,
+                    h: desc.extent.height as f32,
+                };
+                state.depth_range = 0.0..1.0;
+                state.scissor = crate::Rect {
+                    x: 0,
+                    y: 0,
+                    w: desc.extent.width,
+                    h: desc.extent.height,
+                };
+                state.render_pass = Some(super::RenderPassDesc {
+                    extent: desc.extent,
+                    color_targets: desc.color_targets.clone(),
+                    depth_stencil_target: desc.depth_stencil_target.clone(),
+                });
+            }
+            C::EndRenderPass => {
+                if let Some(desc) = state.render_pass.take() {
+                    for target in desc.color_targets.iter() {
+                        if let Some(ref resolve_view) = target.resolve_view {
+                            super::Image::resolve(&target.view, resolve_view);
+                        }
+                    }
+                }
+            }
+            C::SetBindGroup {
+                index,
+                ref entries,
+                ref dynamic_offsets,
+            } => {
+                let index = index as usize;
+                if state.bind_groups.len() <= index {
+                    state.bind_groups.resize(index + 1, None);
+                }
+                let entries = if dynamic_offsets.is_empty() {
+                    Arc::clone(entries)
+                } else {
+                    // dynamic offsets apply to the dynamic buffers in the binding order
+                    let mut dynamic_offsets = dynamic_offsets.iter();
+                    entries
+                        .iter()
+                        .map(|&(binding, ref resource)| {
+                            let resource = match *resource {
+                                Binding::Buffer {
+                                    ref memory,
+                                    offset,
+                                    size,
+                                    dynamic: true,
+                                } => Binding::Buffer {
+                                    memory: Arc::clone(memory),
+                                    offset: offset
+                                        + dynamic_offsets.next().cloned().unwrap_or(0)
+                                            as wgt::BufferAddress,
+                                    size,
+                                    dynamic: true,
+                                },
+                                ref other => other.clone(),
+                            };
+                            (binding, resource)
+                        })
+                        .collect()
+                };
+                state.bind_groups[index] = Some(entries);
+            }
+            C::SetPushConstants { offset, ref data } => {
+                let start = offset as usize;
+                let bytes = state
+                    .push_constants
+                    .slice_mut(start..start + data.len() * 4);
+                for (chunk, value) in bytes.chunks_exact_mut(4).zip(data.iter()) {
+                    chunk.copy_from_slice(&value.to_le_bytes());
+                }
+            }
+            C::SetRenderPipeline(ref pipeline) => {
+                state.render_pipeline = Some(Arc::clone(pipeline));
+            }
+            C::SetIndexBuffer {
+                ref memory,
+                offset,
+                format,
+            } => {
+                state.index_buffer = Some((Arc::clone(memory), offset, format));
+            }
+            C::SetVertexBuffer {
+                index,
+                ref memory,
+                offset,
+            } => {
+                let index = index as usize;
+                if state.vertex_buffers.len() <= index {
+                    state.vertex_buffers.resize(index + 1, None);
+                }
+                state.vertex_buffers[index] = Some((Arc::clone(memory), offset));
+            }
+            C::SetViewport {
+                ref rect,
+                ref depth,
+            } => {
+                state.viewport = rect.clone();
+                state.depth_range = depth.clone();
+            }
+            C::SetScissor(ref rect) => state.scissor = rect.clone(),
+            C::SetStencilReference(value) => state.stencil_reference = value,
+            C::SetBlendConstants(color) => state.blend_constants = color,
+            C::Draw {
+                start_vertex,
+                vertex_count,
+                start_instance,
+                instance_count,
+            } => {
+                let vertices = (start_vertex..start_vertex + vertex_count)
+                    .map(Some)
+                    .collect::<Vec<_>>();
+                self.draw(
+                    state,
+                    &vertices,
+                    start_instance..start_instance + instance_count,
+                )?;
+            }
+            C::DrawIndexed {
+                start_index,
+                index_count,
+                base_vertex,
+                start_instance,
+                instance_count,
+            } => {
+                let vertices = self.fetch_indices(state, start_index, index_count, base_vertex);
+                self.draw(
+                    state,
+                    &vertices,
+                    start_instance..start_instance + instance_count,
+                )?;
+            }
+            C::DrawIndirect {
+                ref memory,
+                offset,
+                ref count,
+                indexed,
+            } => {
+                let draw_count = match *count {
+                    DrawCount::Direct(count) => count,
+                    DrawCount::Indirect {
+                        memory: ref count_memory,
+                        offset: count_offset,
+                        max_count,
+                    } => read_u32(count_memory, count_offset).min(max_count),
+                };
+                let stride = if indexed { 20 } else { 16 };
+                for i in 0..draw_count as wgt::BufferAddress {
+                    let args = offset + i * stride;
+                    let arg = |index: wgt::BufferAddress| read_u32(memory, args + index * 4);
+                    if indexed {
+                        let vertices = self.fetch_indices(state, arg(2), arg(0), arg(3) as i32);
+                        self.draw(state, &vertices, arg(4)..arg(4) + arg(1))?;
+                    } else {
+                        let vertices = (arg(2)..arg(2) + arg(0)).map(Some).collect::<Vec<_>>();
+                        self.draw(state, &vertices, arg(3)..arg(3) + arg(1))?;
+                    }
+                }
+            }
+            C::SetComputePipeline(ref program) => {
+                state.compute_program = Some(Arc::clone(program));
+            }
+            C::Dispatch(count) => self.dispatch(state, count)?,
+            C::DispatchIndirect { ref memory, offset } => {
+                let count = [
+                    read_u32(memory, offset),
+                    read_u32(memory, offset + 4),
+                    read_u32(memory, offset + 8),
+                ];
+                self.dispatch(state, count)?;
+            }
+        }
+        Ok(())
+    }
+
+    unsafe fn begin_render_pass(&self, desc: &super::RenderPassDesc) {
+        for target in desc.color_targets.iter() {
+            if target.ops.contains(crate::AttachmentOps::LOAD) {
+                continue;
+            }
+            let view = &target.view;
+            let texel = conv::clear_texel(view.format, target.clear_value);
+            let [width, height, _] = view.size(0);
+            for layer in 0..view.array_layers.end - view.array_layers.start {
+                for y in 0..height {
+                    for x in 0..width {
+                        view.store([x as i32, y as i32, 0], layer, texel);
+                    }
+                }
+            }
+        }
+        if let Some(ref target) = desc.depth_stencil_target {
+            let view = &target.view;
+            let clear_depth = view.aspects.contains(crate::FormatAspects::DEPTH)
+                && !target.depth_ops.contains(crate::AttachmentOps::LOAD);
+            let clear_stencil = view.aspects.contains(crate::FormatAspects::STENCIL)
+                && !target.stencil_ops.contains(crate::AttachmentOps::LOAD);
+            if !clear_depth && !clear_stencil {
+                return;
+            }
+            let (depth, stencil) = target.clear_value;
+            let [width, height, _] = view.size(0);
+            for layer in view.array_layers.clone() {
+                for y in 0..height {
+                    for x in 0..width {
+                        let bytes = view.image.texel_bytes(view.mip_levels.start, layer, x, y);
+                        if clear_depth {
+                            conv::write_depth(view.image.format, depth, bytes);
+                        }
+                        if clear_stencil {
+                            conv::write_stencil(view.image.format, stencil, bytes);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Reads the index buffer, applying the base vertex and the primitive restart.
+    unsafe fn fetch_indices(
+        &self,
+        state: &State,
+        start_index: u32,
+        index_count: u32,
+        base_vertex: i32,
+    ) -> Vec<Option<u32>> {
+        let (memory, offset, format) = match state.index_buffer {
+            Some((ref memory, offset, format)) => (memory, offset, format),
+            None => return Vec::new(),
+        };
+        let strip_index_format = state
+            .render_pipeline
+            .as_ref()
+            .and_then(|pipeline| pipeline.primitive.strip_index_format);
+        let (index_size, restart) = match format {
+            wgt::IndexFormat::Uint16 => (2, 0xFFFF),
+            wgt::IndexFormat::Uint32 => (4, !0),
+        };
+        (start_index..start_index + index_count)
+            .map(|i| {
+                let start = offset as usize + i as usize * index_size;
+                if start + index_size > memory.size {
+                    return None;
+                }
+                let bytes = memory.slice(start..start + index_size);
+                let index = match format {
+                    wgt::IndexFormat::Uint16 => u16::from_le_bytes([bytes[0], bytes[1]]) as u32,
+                    wgt::IndexFormat::Uint32 => {
+                        u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]])
+                    }
+                };
+                if strip_index_format.is_some() && index == restart {
+                    None
+                } else {
+                    Some((index as i32 + base_vertex) as u32)
+                }
+            })
+            .collect()
+    }
+
+    /// Rasterizes the primitives of the vertices, once per instance.
+    ///
+    /// A panicking invocation stops the draw and is reported as a lost device.
+    unsafe fn draw(
+        &self,
+        state: &mut State,
+        vertices: &[Option<u32>],
+        instances: Range<u32>,
+    ) -> Result<(), crate::DeviceError> {
+        let (pipeline, pass) = match (&state.render_pipeline, &state.render_pass) {
+            (&Some(ref pipeline), &Some(ref pass)) => (pipeline, pass),
+            _ => return Ok(()),
+        };
+        let vertex_resources = pipeline
+            .vertex
+            .bind(&state.bind_groups, &state.push_constants);
+        let fragment_resources = pipeline
+            .fragment
+            .as_ref()
+            .map(|program| program.bind(&state.bind_groups, &state.push_constants));
+        let draw = raster::Draw {
+            pipeline,
+            vertex_resources: &vertex_resources,
+            fragment_resources: fragment_resources.as_ref(),
+            vertex_buffers: &state.vertex_buffers,
+            pass,
+            viewport: &state.viewport,
+            depth_range: &state.depth_range,
+            scissor: &state.scissor,
+            stencil_reference: state.stencil_reference,
+            blend_constants: state.blend_constants,
+        };
+        let mut occlusion_count = 0;
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            draw.run(vertices, instances, &mut occlusion_count)
+        }));
+        if result.is_err() {
+            log::error!("Draw invocation panicked");
+            return Err(crate::DeviceError::Lost);
+        }
+        if let Some((_, _, ref mut count)) = state.occlusion_query {
+            *count += occlusion_count;
+        }
+        Ok(())
+    }
+
+    /// Runs the compute program over `count` workgroups.
+    ///
+    /// A panicking invocation stops the dispatch and is reported as a lost device.
+    unsafe fn dispatch(&self, state: &State, count: [u32; 3]) -> Result<(), crate::DeviceError> {
+        let program = match state.compute_program {
+            Some(ref program) => program,
+            None => return Ok(()),
+        };
+        let resources = Arc::new(program.bind(&state.bind_groups, &state.push_constants));
+        let size = program.workgroup_size();
+        let invocation_count = (size[0] * size[1] * size[2]) as usize;
+
+        let arguments = move |group: [u32; 3], local_index: u32| {
+            let local = [
+                local_index % size[0],
+                local_index / size[0] % size[1],
+                local_index / (size[0] * size[1]),
+            ];
+            let uvec3 = |v: [u32; 3]| {
+                interp::Value::Composite(
+                    v.iter()
+                        .map(|&c| interp::Value::Scalar(interp::Scalar::Uint(c)))
+                        .collect(),
+                )
+            };
+            move |binding: &naga::Binding, _: &naga::TypeInner| match *binding {
+                naga::Binding::BuiltIn(naga::BuiltIn::GlobalInvocationId) => uvec3([
+                    group[0] * size[0] + local[0],
+                    group[1] * size[1] + local[1],
+                    group[2] * size[2] + local[2],
+                ]),
+                naga::Binding::BuiltIn(naga::BuiltIn::LocalInvocationId) => uvec3(local),
+                naga::Binding::BuiltIn(naga::BuiltIn::LocalInvocationIndex) => {
+                    interp::Value::Scalar(interp::Scalar::Uint(local_index))
+                }
+                naga::Binding::BuiltIn(naga::BuiltIn::WorkGroupId) => uvec3(group),
+                naga::Binding::BuiltIn(naga::BuiltIn::NumWorkGroups) => uvec3(count),
+                naga::Binding::BuiltIn(naga::BuiltIn::WorkGroupSize) => uvec3(size),
+                _ => interp::Value::Scalar(interp::Scalar::Uint(0)),
+            }
+        };
+
+        for z in 0..count[2] {
+            for y in 0..count[1] {
+                for x in 0..count[0] {
+                    let group = [x, y, z];
+                    let workgroup = Arc::new(program.create_workgroup_memory());
+                    let succeeded = if program.uses_barriers {
+                        // Invocations have to run concurrently to meet at the barriers.
+                        let barrier = Arc::new(interp::WorkgroupBarrier::new(invocation_count));
+                        let jobs = (0..invocation_count as u32)
+                            .map(|local_index| {
+                                let program = Arc::clone(program);
+                                let resources = Arc::clone(&resources);
+                                let workgroup = Arc::clone(&workgroup);
+                                let barrier = Arc::clone(&barrier);
+                                Box::new(move || {
+                                    let result = panic::catch_unwind(AssertUnwindSafe(|| {
+                                        let mut invocation = interp::Invocation::new(
+                                            &program,
+                                            &resources,
+                                            &workgroup,
+                                            Some(&barrier),
+                                        );
+                                        let args =
+                                            program.entry_arguments(arguments(group, local_index));
+                                        invocation.run(args);
+                                    }));
+                                    if result.is_err() {
+                                        // Release the other invocations waiting for this one.
+                                        barrier.poison();
+                                    }
+                                    result.is_ok()
+                                }) as Job
+                            })
+                            .collect();
+                        self.workers.lock().run(jobs)?
+                    } else {
+                        panic::catch_unwind(AssertUnwindSafe(|| {
+                            let mut invocation =
+                                interp::Invocation::new(program, &resources, &workgroup, None);
+                            for local_index in 0..invocation_count as u32 {
+                                let args = program.entry_arguments(arguments(group, local_index));
+                                invocation.run(args);
+                            }
+                        }))
+                        .is_ok()
+                    };
+                    if !succeeded {
+                        log::error!("Compute invocation panicked in workgroup {:?}", group);
+                        return Err(crate::DeviceError::Lost);
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl crate::Queue<super::Api> for super::Queue {
+    unsafe fn submit(
+        &mut self,
+        command_buffers: &[&super::CommandBuffer],
+        signal_fence: Option<(&mut super::Fence, crate::FenceValue)>,
+    ) -> Result<(), crate::DeviceError> {
+        for cmd_buf in command_buffers.iter() {
+            let mut state = State::new();
+            for command in cmd_buf.commands.iter() {
+                self.execute(&mut state, command)?;
+            }
+        }
+
+        if let Some((fence, value)) = signal_fence {
+            fence.value = value;
+        }
+        Ok(())
+    }
+
+    unsafe fn present(
+        &mut self,
+        _surface: &mut super::Surface,
+        _texture: super::Texture,
+    ) -> Result<(), crate::SurfaceError> {
+        Err(crate::SurfaceError::Lost)
+    }
+
+    unsafe fn get_timestamp_period(&self) -> f32 {
+        1.0
+    }
+}
@@ -0,0 +1,815 @@
+//! Vertex processing, primitive assembly, and rasterization.
+
+use super::{
+    conv,
+    interp::{self, Invocation, Program, Resources, Scalar, Value},
+    Memory, RenderPassDesc, RenderPipelineInner, TextureView,
+};
+use naga::TypeInner as Ti;
+use std::{collections::HashMap, ops::Range, sync::Arc};
+
+/// Guard against the division by zero when clipping to the near plane.
+const MIN_W: f32 = 1.0e-6;
+
+#[derive(Clone, Copy, PartialEq)]
+enum Interpolation {
+    Perspective,
+    Linear,
+    Flat,
+}
+
+#[derive(Clone)]
+struct Varying {
+    location: u32,
+    interpolation: Interpolation,
+    values: Vec<Scalar>,
+}
+
+/// Vertex after the vertex shader, in clip space.
+#[derive(Clone)]
+struct ClipVertex {
+    position: [f32; 4],
+    varyings: Vec<Varying>,
+}
+
+impl ClipVertex {
+    fn lerp(&self, other: &Self, t: f32) -> Self {
+        let mut position = [0.0; 4];
+        for (i, p) in position.iter_mut().enumerate() {
+            *p = self.position[i] + (other.position[i] - self.position[i]) * t;
+        }
+        let varyings = self
+            .varyings
+            .iter()
+            .zip(other.varyings.iter())
+            .map(|(a, b)| Varying {
+                location: a.location,
+                interpolation: a.interpolation,
+                values: a
+                    .values
+                    .iter()
+                    .zip(b.values.iter())
+                    .map(|(&va, &vb)| match (a.interpolation, va, vb) {
+                        (Interpolation::Flat, _, _) => va,
+                        (_, Scalar::Float(fa), Scalar::Float(fb)) => {
+                            Scalar::Float(fa + (fb - fa) * t)
+                        }
+                        _ => va,
+                    })
+                    .collect(),
+            })
+            .collect();
+        Self { position, varyings }
+    }
+}
+
+/// Vertex after the viewport transformation.
+struct ScreenVertex {
+    x: f32,
+    y: f32,
+    z: f32,
+    inv_w: f32,
+    varyings: Vec<Varying>,
+}
+
+/// Everything needed to execute a draw call.
+pub struct Draw<'a> {
+    pub pipeline: &'a RenderPipelineInner,
+    pub vertex_resources: &'a Resources,
+    pub fragment_resources: Option<&'a Resources>,
+    pub vertex_buffers: &'a [Option<(Arc<Memory>, wgt::BufferAddress)>],
+    pub pass: &'a RenderPassDesc,
+    pub viewport: &'a crate::Rect<f32>,
+    pub depth_range: &'a Range<f32>,
+    pub scissor: &'a crate::Rect<u32>,
+    pub stencil_reference: u32,
+    pub blend_constants: [f32; 4],
+}
+
+struct Fragment {
+    x: u32,
+    y: u32,
+    depth: f32,
+    inv_w: f32,
+    front_facing: bool,
+    primitive_index: u32,
+    varyings: Vec<(u32, Vec<Scalar>)>,
+}
+
+impl<'a> Draw<'a> {
+    /// Draws the primitives formed by the vertex indices, where `None` restarts a strip.
+    pub unsafe fn run(
+        &self,
+        vertices: &[Option<u32>],
+        instances: Range<u32>,
+        occlusion_count: &mut u64,
+    ) {
+        let empty_workgroup = Memory::new(0);
+        let mut vertex_invocation = Invocation::new(
+            &self.pipeline.vertex,
+            self.vertex_resources,
+            &empty_workgroup,
+            None,
+        );
+        let mut fragment_invocation = match (&self.pipeline.fragment, self.fragment_resources) {
+            (&Some(ref program), Some(resources)) => {
+                Some(Invocation::new(program, resources, &empty_workgroup, None))
+            }
+            _ => None,
+        };
+
+        for instance in instances {
+            let mut cache = HashMap::new();
+            let mut shaded = Vec::with_capacity(vertices.len());
+            for &vertex in vertices {
+                shaded.push(vertex.map(|index| {
+                    cache
+                        .entry(index)
+                        .or_insert_with(|| {
+                            self.shade_vertex(&mut vertex_invocation, index, instance)
+                        })
+                        .clone()
+                }));
+            }
+
+            let mut primitive_index = 0;
+            for strip in shaded.split(Option::is_none) {
+                let strip = strip.iter().flatten().collect::<Vec<_>>();
+                match self.pipeline.primitive.topology {
+                    wgt::PrimitiveTopology::PointList => {
+                        for vertex in strip {
+                            self.rasterize_point(
+                                vertex,
+                                primitive_index,
+                                &mut fragment_invocation,
+                                occlusion_count,
+                            );
+                            primitive_index += 1;
+                        }
+                    }
+                    wgt::PrimitiveTopology::LineList => {
+                        for pair in strip.chunks_exact(2) {
+                            self.rasterize_line(
+                                pair[0],
+                                pair[1],
+                                primitive_index,
+                                &mut fragment_invocation,
+                                occlusion_count,
+                            );
+                            primitive_index += 1;
+                        }
+                    }
+                    wgt::PrimitiveTopology::LineStrip => {
+                        for pair in strip.windows(2) {
+                            self.rasterize_line(
+                                pair[0],
+                                pair[1],
+                                primitive_index,
+                                &mut fragment_invocation,
+                                occlusion_count,
+                            );
+                            primitive_index += 1;
+                        }
+                    }
+                    wgt::PrimitiveTopology::TriangleList => {
+                        for triangle in strip.chunks_exact(3) {
+                            self.rasterize_triangle(
+                                [triangle[0], triangle[1], triangle[2]],
+                                primitive_index,
+                                &mut fragment_invocation,
+                                occlusion_count,
+                            );
+                            primitive_index += 1;
+                        }
+                    }
+                    wgt::PrimitiveTopology::TriangleStrip => {
+                        for i in 2..strip.len() {
+                            // keep the winding order of the odd triangles consistent
+                            let triangle = if i % 2 == 0 {
+                                [strip[i - 2], strip[i - 1], strip[i]]
+                            } else {
+                                [strip[i - 1], strip[i - 2], strip[i]]
+                            };
+                            self.rasterize_triangle(
+                                triangle,
+                                primitive_index,
+                                &mut fragment_invocation,
+                                occlusion_count,
+                            );
+                            primitive_index += 1;
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    unsafe fn fetch_attribute(
+        &self,
+        location: u32,
+        vertex: u32,
+        instance: u32,
+        inner: &Ti,
+    ) -> Option<Value> {
+        for (desc, buffer) in self
+            .pipeline
+            .vertex_buffers
+            .iter()
+            .zip(self.vertex_buffers.iter())
+        {
+            let attribute = match desc
+                .attributes
+                .iter()
+                .find(|attribute| attribute.shader_location == location)
+            {
+                Some(attribute) => attribute,
+                None => continue,
+            };
+            let &(ref memory, offset) = buffer.as_ref()?;
+            let index = match desc.step {
+                wgt::VertexStepMode::Vertex => vertex,
+                wgt::VertexStepMode::Instance => instance,
+            };
+            let start =
+                (offset + index as wgt::BufferAddress * desc.stride + attribute.offset) as usize;
+            let end = start + attribute.format.size() as usize;
+            if end > memory.size {
+                return None;
+            }
+            let (texel, kind) = conv::decode_vertex(attribute.format, memory.slice(start..end));
+            return Some(interp::components_to_value(texel, kind, inner));
+        }
+        None
+    }
+
+    unsafe fn shade_vertex(
+        &self,
+        invocation: &mut Invocation,
+        vertex: u32,
+        instance: u32,
+    ) -> ClipVertex {
+        let program: &Program = &self.pipeline.vertex;
+        let arguments = program.entry_arguments(|binding, inner| match *binding {
+            naga::Binding::BuiltIn(naga::BuiltIn::VertexIndex) => {
+                Value::Scalar(Scalar::Uint(vertex))
+            }
+            naga::Binding::BuiltIn(naga::BuiltIn::InstanceIndex) => {
+                Value::Scalar(Scalar::Uint(instance))
+            }
+            naga::Binding::Location { location, .. } => self
+                .fetch_attribute(location, vertex, instance, inner)
+                .unwrap_or_else(|| program.zero_value(inner)),
+            _ => program.zero_value(inner),
+        });
+
+        let mut output = ClipVertex {
+            position: [0.0, 0.0, 0.0, 1.0],
+            varyings: Vec::new(),
+        };
+        if let Some(Some(result)) = invocation.run(arguments) {
+            program.entry_outputs(result, |binding, value| match *binding {
+                naga::Binding::BuiltIn(naga::BuiltIn::Position) => {
+                    for (p, v) in output.position.iter_mut().zip(value.floats()) {
+                        *p = v;
+                    }
+                }
+                naga::Binding::Location {
+                    location,
+                    interpolation,
+                    ..
+                } => {
+                    let values = value.flatten();
+                    let is_float = matches!(values.first(), Some(&Scalar::Float(_)));
+                    output.varyings.push(Varying {
+                        location,
+                        interpolation: match interpolation {
+                            _ if !is_float => Interpolation::Flat,
+                            Some(naga::Interpolation::Flat) => Interpolation::Flat,
+                            Some(naga::Interpolation::Linear) => Interpolation::Linear,
+                            Some(naga::Interpolation::Perspective) | None => {
+                                Interpolation::Perspective
+                            }
+                        },
+                        values,
+                    });
+                }
+                _ => {}
+            });
+        }
+        output
+    }
+
+    fn to_screen(&self, vertex: &ClipVertex) -> ScreenVertex {
+        let [x, y, z, w] = vertex.position;
+        let inv_w = 1.0 / w;
+        let viewport = self.viewport;
+        let depth = self.depth_range;
+        ScreenVertex {
+            x: viewport.x + (x * inv_w + 1.0) * 0.5 * viewport.w,
+            y: viewport.y + (1.0 - y * inv_w) * 0.5 * viewport.h,
+            z: depth.start + z * inv_w * (depth.end - depth.start),
+            inv_w,
+            varyings: vertex.varyings.clone(),
+        }
+    }
+
+    /// Returns the area of pixels that can be touched by the draw.
+    fn bounds(&self) -> (u32, u32, u32, u32) {
+        let extent = &self.pass.extent;
+        let x0 = self.scissor.x.max(self.viewport.x.max(0.0) as u32);
+        let y0 = self.scissor.y.max(self.viewport.y.max(0.0) as u32);
+        let x1 = (self.scissor.x + self.scissor.w)
+            .min((self.viewport.x + self.viewport.w).ceil().max(0.0) as u32)
+            .min(extent.width);
+        let y1 = (self.scissor.y + self.scissor.h)
+            .min((self.viewport.y + self.viewport.h).ceil().max(0.0) as u32)
+            .min(extent.height);
+        (x0, y0, x1, y1)
+    }
+
+    /// Clips a triangle in clip space, returning a convex polygon.
+    fn clip(&self, triangle: [&ClipVertex; 3]) -> Vec<ClipVertex> {
+        let mut polygon = triangle.iter().map(|&v| v.clone()).collect::<Vec<_>>();
+        let mut planes: Vec<fn(&[f32; 4]) -> f32> = vec![|p| p[3] - MIN_W];
+        if !self.pipeline.primitive.clamp_depth {
+            planes.push(|p| p[2]);
+            planes.push(|p| p[3] - p[2]);
+        }
+        for plane in planes {
+            if polygon.is_empty() {
+                break;
+            }
+            let mut clipped = Vec::with_capacity(polygon.len() + 1);
+            for i in 0..polygon.len() {
+                let a = &polygon[i];
+                let b = &polygon[(i + 1) % polygon.len()];
+                let da = plane(&a.position);
+                let db = plane(&b.position);
+                if da >= 0.0 {
+                    clipped.push(a.clone());
+                }
+                if (da >= 0.0) != (db >= 0.0) {
+                    clipped.push(a.lerp(b, da / (da - db)));
+                }
+            }
+            polygon = clipped;
+        }
+        polygon
+    }
+
+    unsafe fn rasterize_triangle(
+        &self,
+        triangle: [&ClipVertex; 3],
+        primitive_index: u32,
+        fragment_invocation: &mut Option<Invocation>,
+        occlusion_count: &mut u64,
+    ) {
+        let polygon = self.clip(triangle);
+        if polygon.len() < 3 {
+            return;
+        }
+        let screen = polygon
+            .iter()
+            .map(|v| self.to_screen(v))
+            .collect::<Vec<_>>();
+        for i in 1..screen.len() - 1 {
+            self.rasterize_screen_triangle(
+                [&screen[0], &screen[i], &screen[i + 1]],
+                primitive_index,
+                fragment_invocation,
+                occlusion_count,
+            );
+        }
+    }
+
+    unsafe fn rasterize_screen_triangle(
+        &self,
+        v: [&ScreenVertex; 3],
+        primitive_index: u32,
+        fragment_invocation: &mut Option<Invocation>,
+        occlusion_count: &mut u64,
+    ) {
+        fn edge(a: &ScreenVertex, b: &ScreenVertex, x: f32, y: f32) -> f32 {
+            (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x)
+        }
+        fn is_top_left(a: &ScreenVertex, b: &ScreenVertex) -> bool {
+            let (dx, dy) = (b.x - a.x, b.y - a.y);
+            (dy == 0.0 && dx > 0.0) || dy < 0.0
+        }
+
+        let area = edge(v[0], v[1], v[2].x, v[2].y);
+        if area == 0.0 || !area.is_finite() {
+            return;
+        }
+        // The Y axis points down in the screen space,
+        // so the counter-clockwise triangles have a negative area.
+        let front_facing = match self.pipeline.primitive.front_face {
+            wgt::FrontFace::Ccw => area < 0.0,
+            wgt::FrontFace::Cw => area > 0.0,
+        };
+        match self.pipeline.primitive.cull_mode {
+            Some(wgt::Face::Front) if front_facing => return,
+            Some(wgt::Face::Back) if !front_facing => return,
+            _ => {}
+        }
+        // Make the winding consistent for the edge functions.
+        let (a, b, c, area) = if area > 0.0 {
+            (v[0], v[1], v[2], area)
+        } else {
+            (v[0], v[2], v[1], -area)
+        };
+
+        let (bx0, by0, bx1, by1) = self.bounds();
+        let min_x = (a.x.min(b.x).min(c.x).floor().max(0.0) as u32).max(bx0);
+        let min_y = (a.y.min(b.y).min(c.y).floor().max(0.0) as u32).max(by0);
+        let max_x = (a.x.max(b.x).max(c.x).ceil().max(0.0) as u32).min(bx1);
+        let max_y = (a.y.max(b.y).max(c.y).ceil().max(0.0) as u32).min(by1);
+
+        let depth_bias = self.depth_bias(a, b, c, area);
+        let edges = [(b, c), (c, a), (a, b)];
+        let top_left = [is_top_left(b, c), is_top_left(c, a), is_top_left(a, b)];
+
+        for y in min_y..max_y {
+            for x in min_x..max_x {
+                let (px, py) = (x as f32 + 0.5, y as f32 + 0.5);
+                let mut weights = [0.0; 3];
+                let mut inside = true;
+                for (i, &(e0, e1)) in edges.iter().enumerate() {
+                    let w = edge(e0, e1, px, py);
+                    if w < 0.0 || (w == 0.0 && !top_left[i]) {
+                        inside = false;
+                        break;
+                    }
+                    weights[i] = w / area;
+                }
+                if !inside {
+                    continue;
+                }
+                let vertices = [a, b, c];
+                let depth = weights
+                    .iter()
+                    .zip(vertices.iter())
+                    .map(|(w, v)| w * v.z)
+                    .sum::<f32>()
+                    + depth_bias;
+                let inv_w = weights
+                    .iter()
+                    .zip(vertices.iter())
+                    .map(|(w, v)| w * v.inv_w)
+                    .sum::<f32>();
+                let perspective = [
+                    weights[0] * a.inv_w / inv_w,
+                    weights[1] * b.inv_w / inv_w,
+                    weights[2] * c.inv_w / inv_w,
+                ];
+                // The provoking vertex is the first one of the original triangle.
+                let varyings = v[0]
+                    .varyings
+                    .iter()
+                    .enumerate()
+                    .map(|(i, provoking)| {
+                        let values = match provoking.interpolation {
+                            Interpolation::Flat => provoking.values.clone(),
+                            interpolation => {
+                                let factors = match interpolation {
+                                    Interpolation::Linear => &weights,
+                                    _ => &perspective,
+                                };
+                                (0..provoking.values.len())
+                                    .map(|j| {
+                                        let value = vertices
+                                            .iter()
+                                            .zip(factors.iter())
+                                            .map(|(v, f)| v.varyings[i].values[j].as_f32() * f)
+                                            .sum::<f32>();
+                                        Scalar::Float(value)
+                                    })
+                                    .collect()
+                            }
+                        };
+                        (provoking.location, values)
+                    })
+                    .collect();
+                self.process_fragment(
+                    Fragment {
+                        x,
+                        y,
+                        depth,
+                        inv_w,
+                        front_facing,
+                        primitive_index,
+                        varyings,
+                    },
+                    fragment_invocation,
+                    occlusion_count,
+                );
+            }
+        }
+    }
+
+    fn depth_bias(&self, a: &ScreenVertex, b: &ScreenVertex, c: &ScreenVertex, area: f32) -> f32 {
+        let bias = match self.pipeline.depth_stencil {
+            Some(ref ds) if ds.bias.is_enabled() => ds.bias,
+            _ => return 0.0,
+        };
+        // slope of the depth in the screen space
+        let dzdx = ((b.z - a.z) * (c.y - a.y) - (c.z - a.z) * (b.y - a.y)) / area;
+        let dzdy = ((c.z - a.z) * (b.x - a.x) - (b.z - a.z) * (c.x - a.x)) / area;
+        let slope = dzdx.abs().max(dzdy.abs());
+        let value = bias.constant as f32 / (1 << 24) as f32 + bias.slope_scale * slope;
+        if bias.clamp > 0.0 {
+            value.min(bias.clamp)
+        } else if bias.clamp < 0.0 {
+            value.max(bias.clamp)
+        } else {
+            value
+        }
+    }
+
+    unsafe fn rasterize_point(
+        &self,
+        vertex: &ClipVertex,
+        primitive_index: u32,
+        fragment_invocation: &mut Option<Invocation>,
+        occlusion_count: &mut u64,
+    ) {
+        if vertex.position[3] < MIN_W {
+            return;
+        }
+        let v = self.to_screen(vertex);
+        let (x0, y0, x1, y1) = self.bounds();
+        if v.x < x0 as f32 || v.y < y0 as f32 || v.x >= x1 as f32 || v.y >= y1 as f32 {
+            return;
+        }
+        let varyings = v
+            .varyings
+            .iter()
+            .map(|varying| (varying.location, varying.values.clone()))
+            .collect();
+        self.process_fragment(
+            Fragment {
+                x: v.x as u32,
+                y: v.y as u32,
+                depth: v.z,
+                inv_w: v.inv_w,
+                front_facing: true,
+                primitive_index,
+                varyings,
+            },
+            fragment_invocation,
+            occlusion_count,
+        );
+    }
+
+    unsafe fn rasterize_line(
+        &self,
+        start: &ClipVertex,
+        end: &ClipVertex,
+        primitive_index: u32,
+        fragment_invocation: &mut Option<Invocation>,
+        occlusion_count: &mut u64,
+    ) {
+        if start.position[3] < MIN_W || end.position[3] < MIN_W {
+            return;
+        }
+        let a = self.to_screen(start);
+        let b = self.to_screen(end);
+        let (x0, y0, x1, y1) = self.bounds();
+        let steps = (b.x - a.x).abs().max((b.y - a.y).abs()).ceil().max(1.0) as u32;
+        for step in 0..steps {
+            let t = (step as f32 + 0.5) / steps as f32;
+            let x = a.x + (b.x - a.x) * t;
+            let y = a.y + (b.y - a.y) * t;
+            if x < x0 as f32 || y < y0 as f32 || x >= x1 as f32 || y >= y1 as f32 {
+                continue;
+            }
+            let inv_w = a.inv_w + (b.inv_w - a.inv_w) * t;
+            let perspective = t * b.inv_w / inv_w;
+            let varyings = a
+                .varyings
+                .iter()
+                .zip(b.varyings.iter())
+                .map(|(va, vb)| {
+                    let factor = match va.interpolation {
+                        Interpolation::Flat => 0.0,
+                        Interpolation::Linear => t,
+                        Interpolation::Perspective => perspective,
+                    };
+                    let values = va
+                        .values
+                        .iter()
+                        .zip(vb.values.iter())
+                        .map(|(&sa, &sb)| match (sa, sb) {
+                            (Scalar::Float(fa), Scalar::Float(fb)) if factor != 0.0 => {
+                                Scalar::Float(fa + (fb - fa) * factor)
+                            }
+                            _ => sa,
+                        })
+                        .collect();
+                    (va.location, values)
+                })
+                .collect();
+            self.process_fragment(
+                Fragment {
+                    x: x as u32,
+                    y: y as u32,
+                    depth: a.z + (b.z - a.z) * t,
+                    inv_w,
+                    front_facing: true,
+                    primitive_index,
+                    varyings,
+                },
+                fragment_invocation,
+                occlusion_count,
+            );
+        }
+    }
+
+    unsafe fn process_fragment(
+        &self,
+        fragment: Fragment,
+        fragment_invocation: &mut Option<Invocation>,
+        occlusion_count: &mut u64,
+    ) {
+        let mut depth = fragment.depth;
+        let mut colors = Vec::new();
+
+        if let Some(ref mut invocation) = *fragment_invocation {
+            let program: &Program = self.pipeline.fragment.as_ref().unwrap();
+            let arguments = program.entry_arguments(|binding, inner| match *binding {
+                naga::Binding::BuiltIn(naga::BuiltIn::Position) => Value::from_floats(&[
+                    fragment.x as f32 + 0.5,
+                    fragment.y as f32 + 0.5,
+                    fragment.depth,
+                    fragment.inv_w,
+                ]),
+                naga::Binding::BuiltIn(naga::BuiltIn::FrontFacing) => {
+                    Value::Scalar(Scalar::Bool(fragment.front_facing))
+                }
+                naga::Binding::BuiltIn(naga::BuiltIn::PrimitiveIndex) => {
+                    Value::Scalar(Scalar::Uint(fragment.primitive_index))
+                }
+                naga::Binding::BuiltIn(naga::BuiltIn::SampleMask) => {
+                    Value::Scalar(Scalar::Uint(!0))
+                }
+                naga::Binding::Location { location, .. } => {
+                    match fragment.varyings.iter().find(|&&(l, _)| l == location) {
+                        Some(&(_, ref values)) => interp::scalars_to_value(values, inner),
+                        None => program.zero_value(inner),
+                    }
+                }
+                _ => program.zero_value(inner),
+            });
+            match invocation.run(arguments) {
+                // discarded
+                None => return,
+                Some(Some(result)) => {
+                    program.entry_outputs(result, |binding, value| match *binding {
+                        naga::Binding::BuiltIn(naga::BuiltIn::FragDepth) => {
+                            depth = value.scalar().as_f32();
+                        }
+                        naga::Binding::Location { location, .. } => {
+                            colors.push((location, value.flatten()));
+                        }
+                        _ => {}
+                    });
+                }
+                Some(None) => {}
+            }
+        }
+
+        if self.pipeline.primitive.clamp_depth {
+            depth = depth.max(self.depth_range.start).min(self.depth_range.end);
+        }
+        if !self.depth_stencil_test(&fragment, depth) {
+            return;
+        }
+        *occlusion_count += 1;
+
+        for (location, values) in colors {
+            let (target, state) = match (
+                self.pass.color_targets.get(location as usize),
+                self.pipeline.color_targets.get(location as usize),
+            ) {
+                (Some(target), Some(state)) => (target, state),
+                _ => continue,
+            };
+            self.write_color(&target.view, state, fragment.x, fragment.y, &values);
+        }
+    }
+
+    /// Runs the depth and stencil tests, updating the attachment.
+    /// Returns true if the fragment passes.
+    unsafe fn depth_stencil_test(&self, fragment: &Fragment, depth: f32) -> bool {
+        let (state, target) = match (
+            &self.pipeline.depth_stencil,
+            &self.pass.depth_stencil_target,
+        ) {
+            (&Some(ref state), &Some(ref target)) => (state, target),
+            _ => return true,
+        };
+        let view: &TextureView = &target.view;
+        let bytes = view.image.texel_bytes(
+            view.mip_levels.start,
+            view.array_layers.start,
+            fragment.x,
+            fragment.y,
+        );
+        let format = view.image.format;
+
+        let depth_passed = if view.aspects.contains(crate::FormatAspects::DEPTH) {
+            conv::compare(state.depth_compare, depth, conv::read_depth(format, bytes))
+        } else {
+            true
+        };
+
+        if view.aspects.contains(crate::FormatAspects::STENCIL) && state.stencil.is_enabled() {
+            let face = if fragment.front_facing {
+                &state.stencil.front
+            } else {
+                &state.stencil.back
+            };
+            let stored = conv::read_stencil(format, bytes);
+            let read_mask = state.stencil.read_mask;
+            let stencil_passed = conv::compare(
+                face.compare,
+                (self.stencil_reference & read_mask) as f32,
+                (stored & read_mask) as f32,
+            );
+            let op = if !stencil_passed {
+                face.fail_op
+            } else if !depth_passed {
+                face.depth_fail_op
+            } else {
+                face.pass_op
+            };
+            let value = conv::stencil_op(op, stored, self.stencil_reference);
+            let write_mask = state.stencil.write_mask;
+            conv::write_stencil(format, (stored & !write_mask) | (value & write_mask), bytes);
+            if !stencil_passed {
+                return false;
+            }
+        }
+
+        if !depth_passed {
+            return false;
+        }
+        if state.depth_write_enabled && view.aspects.contains(crate::FormatAspects::DEPTH) {
+            conv::write_depth(format, depth, bytes);
+        }
+        true
+    }
+
+    unsafe fn write_color(
+        &self,
+        view: &TextureView,
+        state: &wgt::ColorTargetState,
+        x: u32,
+        y: u32,
+        values: &[Scalar],
+    ) {
+        let coords = [x as i32, y as i32, 0];
+        let kind = view.kind();
+        let mut texel = conv::default_texel(kind);
+        for (t, &value) in texel.iter_mut().zip(values.iter()) {
+            *t = match kind {
+                conv::ScalarKind::Float => value.as_f32().to_bits(),
+                conv::ScalarKind::Uint => value.as_u32(),
+                conv::ScalarKind::Sint => value.as_i32() as u32,
+            };
+        }
+
+        let full_mask = state.write_mask == wgt::ColorWrites::ALL;
+        if state.blend.is_none() && full_mask {
+            view.store(coords, 0, texel);
+            return;
+        }
+        let old = view.load(coords, 0, 0);
+        if let (Some(ref blend), conv::ScalarKind::Float) = (state.blend, kind) {
+            let result = conv::blend(
+                blend,
+                texel_floats(texel),
+                texel_floats(old),
+                self.blend_constants,
+            );
+            for (t, r) in texel.iter_mut().zip(result.iter()) {
+                *t = r.to_bits();
+            }
+        }
+        for (i, t) in texel.iter_mut().enumerate() {
+            if state.write_mask.bits() & (1 << i) == 0 {
+                *t = old[i];
+            }
+        }
+        view.store(coords, 0, texel);
+    }
+}
+
+fn texel_floats(texel: conv::Texel) -> [f32; 4] {
+    [
+        f32::from_bits(texel[0]),
+        f32::from_bits(texel[1]),
+        f32::from_bits(texel[2]),
+        f32::from_bits(texel[3]),
+    ]
+}
@@ -0,0 +1,572 @@
+//! Texture storage, copies, and sampling.
+
+use super::{
+    conv::{self, Texel},
+    Image, Memory, Sampler, TextureView,
+};
+
+impl Image {
+    pub fn new(desc: &crate::TextureDescriptor) -> Self {
+        let mut image = Self {
+            memory: Memory::new(0),
+            format: desc.format,
+            dimension: desc.dimension,
+            size: desc.size,
+            mip_level_count: desc.mip_level_count,
+            sample_count: desc.sample_count,
+            mip_offsets: Box::new([]),
+        };
+        let mut offset = 0;
+        image.mip_offsets = (0..desc.mip_level_count)
+            .map(|level| {
+                let level_offset = offset;
+                offset += image.slice_pitch(level) * image.mip_size(level)[2] as usize;
+                level_offset
+            })
+            .collect();
+        image.memory = Memory::new(offset);
+        image
+    }
+
+    /// Returns the size of a mip level: width, height, and the number of slices,
+    /// which are the depth of 3D textures or the array layers of the others.
+    pub fn mip_size(&self, level: u32) -> [u32; 3] {
+        let width = (self.size.width >> level).max(1);
+        let height = match self.dimension {
+            wgt::TextureDimension::D1 => 1,
+            _ => (self.size.height >> level).max(1),
+        };
+        let slices = match self.dimension {
+            wgt::TextureDimension::D3 => (self.size.depth_or_array_layers >> level).max(1),
+            _ => self.size.depth_or_array_layers,
+        };
+        [width, height, slices]
+    }
+
+    fn block_info(&self) -> (u32, u32, usize) {
+        let info = self.format.describe();
+        (
+            info.block_dimensions.0 as u32,
+            info.block_dimensions.1 as u32,
+            info.block_size as usize,
+        )
+    }
+
+    fn row_pitch(&self, level: u32) -> usize {
+        let (block_width, _, block_size) = self.block_info();
+        let [width, _, _] = self.mip_size(level);
+        ((width + block_width - 1) / block_width) as usize * block_size
+    }
+
+    fn slice_pitch(&self, level: u32) -> usize {
+        let (_, block_height, _) = self.block_info();
+        let [_, height, _] = self.mip_size(level);
+        ((height + block_height - 1) / block_height) as usize * self.row_pitch(level)
+    }
+
+    /// Returns the offset of the texel block containing the texel at `x` and `y`.
+    fn block_offset(&self, level: u32, slice: u32, x: u32, y: u32) -> usize {
+        let (block_width, block_height, block_size) = self.block_info();
+        self.mip_offsets[level as usize]
+            + slice as usize * self.slice_pitch(level)
+            + (y / block_height) as usize * self.row_pitch(level)
+            + (x / block_width) as usize * block_size
+    }
+
+    /// Returns the bytes of a texel block.
+    ///
+    /// # Safety
+    /// The coordinates have to be in bounds, and the caller must not hold any other
+    /// reference to the same block.
+    #[allow(clippy::mut_from_ref)]
+    pub unsafe fn texel_bytes(&self, level: u32, slice: u32, x: u32, y: u32) -> &mut [u8] {
+        let offset = self.block_offset(level, slice, x, y);
+        self.memory.slice_mut(offset..offset + self.block_info().2)
+    }
+
+    /// Resets the contents of a subresource range to zero.
+    pub unsafe fn clear(&self, range: &wgt::ImageSubresourceRange) {
+        let level_end = match range.mip_level_count {
+            Some(count) => range.base_mip_level + count.get(),
+            None => self.mip_level_count,
+        };
+        let aspects =
+            crate::FormatAspects::from(self.format) & crate::FormatAspects::from(range.aspect);
+        let whole_texel = aspects == crate::FormatAspects::from(self.format);
+        for level in range.base_mip_level..level_end {
+            let [width, height, slices] = self.mip_size(level);
+            let layers = match self.dimension {
+                wgt::TextureDimension::D3 => 0..slices,
+                _ => {
+                    let end = match range.array_layer_count {
+                        Some(count) => range.base_array_layer + count.get(),
+                        None => slices,
+                    };
+                    range.base_array_layer..end
+                }
+            };
+            for slice in layers {
+                if whole_texel {
+                    let start = self.block_offset(level, slice, 0, 0);
+                    self.memory
+                        .slice_mut(start..start + self.slice_pitch(level))
+                        .fill(0);
+                    continue;
+                }
+                for y in 0..height {
+                    for x in 0..width {
+                        let bytes = self.texel_bytes(level, slice, x, y);
+                        if aspects.contains(crate::FormatAspects::DEPTH) {
+                            conv::write_depth(self.format, 0.0, bytes);
+                        }
+                        if aspects.contains(crate::FormatAspects::STENCIL) {
+                            conv::write_stencil(self.format, 0, bytes);
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Resolves a multi-sampled image into `dst`.
+    ///
+    /// Since only a single sample is stored, this is a plain copy.
+    pub unsafe fn resolve(src: &TextureView, dst: &TextureView) {
+        let [width, height, _] = src.image.mip_size(src.mip_levels.start);
+        let block_size = src.image.block_info().2;
+        for (src_layer, dst_layer) in src.array_layers.clone().zip(dst.array_layers.clone()) {
+            for y in 0..height {
+                for x in 0..width {
+                    let texel = conv::decode_texel(
+                        src.format,
+                        src.image.texel_bytes(src.mip_levels.start, src_layer, x, y),
+                    );
+                    let dst_bytes = dst.image.texel_bytes(dst.mip_levels.start, dst_layer, x, y);
+                    conv::encode_texel(dst.format, texel, &mut dst_bytes[..block_size]);
+                }
+            }
+        }
+    }
+}
+
+/// Walks over the rows of texel blocks of a buffer-texture copy,
+/// calling `copy_row` with the buffer offset, the texture offset,
+/// and the number of blocks in the row.
+unsafe fn walk_buffer_texture_copy(
+    image: &Image,
+    copy: &crate::BufferTextureCopy,
+    aspect_size: usize,
+    mut copy_row: impl FnMut(usize, usize, usize),
+) {
+    let (block_width, block_height, _) = image.block_info();
+    let blocks_per_row = (copy.size.width / block_width) as usize;
+    let rows = copy.size.height / block_height;
+    let bytes_per_row = match copy.buffer_layout.bytes_per_row {
+        Some(bytes) => bytes.get() as usize,
+        None => blocks_per_row * aspect_size,
+    };
+    let rows_per_image = match copy.buffer_layout.rows_per_image {
+        Some(count) => (count.get() / block_height) as usize,
+        None => rows as usize,
+    };
+    let base = &copy.texture_base;
+    for z in 0..copy.size.depth {
+        let slice = base.array_layer + base.origin.z + z;
+        for row in 0..rows {
+            let buffer_offset = copy.buffer_layout.offset as usize
+                + (z as usize * rows_per_image + row as usize) * bytes_per_row;
+            let texture_offset = image.block_offset(
+                base.mip_level,
+                slice,
+                base.origin.x,
+                base.origin.y + row * block_height,
+            );
+            copy_row(buffer_offset, texture_offset, blocks_per_row);
+        }
+    }
+}
+
+pub unsafe fn copy_buffer_to_texture(src: &Memory, dst: &Image, copy: &crate::BufferTextureCopy) {
+    let block_size = dst.block_info().2;
+    let (aspect_offset, aspect_size) = conv::aspect_bytes(dst.format, copy.texture_base.aspect);
+    walk_buffer_texture_copy(
+        dst,
+        copy,
+        aspect_size,
+        |buffer_offset, texture_offset, blocks| {
+            if aspect_size == block_size {
+                Memory::copy(
+                    src,
+                    buffer_offset,
+                    &dst.memory,
+                    texture_offset,
+                    blocks * block_size,
+                );
+            } else {
+                for i in 0..blocks {
+                    Memory::copy(
+                        src,
+                        buffer_offset + i * aspect_size,
+                        &dst.memory,
+                        texture_offset + i * block_size + aspect_offset,
+                        aspect_size,
+                    );
+                }
+            }
+        },
+    );
+}
+
+pub unsafe fn copy_texture_to_buffer(src: &Image, dst: &Memory, copy: &crate::BufferTextureCopy) {
+    let block_size = src.block_info().2;
+    let (aspect_offset, aspect_size) = conv::aspect_bytes(src.format, copy.texture_base.aspect);
+    walk_buffer_texture_copy(
+        src,
+        copy,
+        aspect_size,
+        |buffer_offset, texture_offset, blocks| {
+            if aspect_size == block_size {
+                Memory::copy(
+                    &src.memory,
+                    texture_offset,
+                    dst,
+                    buffer_offset,
+                    blocks * block_size,
+                );
+            } else {
+                for i in 0..blocks {
+                    Memory::copy(
+                        &src.memory,
+                        texture_offset + i * block_size + aspect_offset,
+                        dst,
+                        buffer_offset + i * aspect_size,
+                        aspect_size,
+                    );
+                }
+            }
+        },
+    );
+}
+
+pub unsafe fn copy_texture_to_texture(src: &Image, dst: &Image, copy: &crate::TextureCopy) {
+    let (block_width, block_height, block_size) = src.block_info();
+    let row_size = (copy.size.width / block_width) as usize * block_size;
+    for z in 0..copy.size.depth {
+        let src_slice = copy.src_base.array_layer + copy.src_base.origin.z + z;
+        let dst_slice = copy.dst_base.array_layer + copy.dst_base.origin.z + z;
+        for row in 0..copy.size.height / block_height {
+            let src_offset = src.block_offset(
+                copy.src_base.mip_level,
+                src_slice,
+                copy.src_base.origin.x,
+                copy.src_base.origin.y + row * block_height,
+            );
+            let dst_offset = dst.block_offset(
+                copy.dst_base.mip_level,
+                dst_slice,
+                copy.dst_base.origin.x,
+                copy.dst_base.origin.y + row * block_height,
+            );
+            Memory::copy(&src.memory, src_offset, &dst.memory, dst_offset, row_size);
+        }
+    }
+}
+
+/// Applies the address mode to a texel coordinate.
+/// Returns `None` if the border color has to be used instead.
+fn address(mode: wgt::AddressMode, coord: i32, size: u32) -> Option<u32> {
+    let size = size as i32;
+    Some(match mode {
+        wgt::AddressMode::ClampToEdge => coord.max(0).min(size - 1),
+        wgt::AddressMode::Repeat => coord.rem_euclid(size),
+        wgt::AddressMode::MirrorRepeat => {
+            let period = coord.rem_euclid(2 * size);
+            if period < size {
+                period
+            } else {
+                2 * size - 1 - period
+            }
+        }
+        wgt::AddressMode::ClampToBorder => {
+            if coord < 0 || coord >= size {
+                return None;
+            }
+            coord
+        }
+    } as u32)
+}
+
+/// Maps a cube direction to the face index and the 2D coordinates within the face.
+fn cube_face(direction: [f32; 3]) -> (u32, [f32; 2]) {
+    let [x, y, z] = direction;
+    let (ax, ay, az) = (x.abs(), y.abs(), z.abs());
+    let (face, sc, tc, ma) = if ax >= ay && ax >= az {
+        if x >= 0.0 {
+            (0, -z, -y, ax)
+        } else {
+            (1, z, -y, ax)
+        }
+    } else if ay >= az {
+        if y >= 0.0 {
+            (2, x, z, ay)
+        } else {
+            (3, x, -z, ay)
+        }
+    } else if z >= 0.0 {
+        (4, x, -y, az)
+    } else {
+        (5, -x, -y, az)
+    };
+    let ma = if ma == 0.0 { 1.0 } else { ma };
+    (face, [(sc / ma + 1.0) * 0.5, (tc / ma + 1.0) * 0.5])
+}
+
+/// Parameters of a single sampling operation, in the terms of the view.
+pub struct SampleQuery {
+    pub coords: [f32; 3],
+    pub array_index: u32,
+    pub lod: f32,
+    pub offset: [i32; 3],
+    /// Reference value for the comparison samplers.
+    pub depth_ref: Option<f32>,
+}
+
+impl TextureView {
+    fn is_3d(&self) -> bool {
+        self.dimension == wgt::TextureViewDimension::D3
+    }
+
+    fn is_cube(&self) -> bool {
+        match self.dimension {
+            wgt::TextureViewDimension::Cube | wgt::TextureViewDimension::CubeArray => true,
+            _ => false,
+        }
+    }
+
+    /// Returns the size of a level of the view, with the array layer count
+    /// in place of the depth for non-3D textures.
+    pub fn size(&self, level: u32) -> [u32; 3] {
+        let [width, height, depth] = self.image.mip_size(self.mip_levels.start + level);
+        if self.is_3d() {
+            [width, height, depth]
+        } else {
+            [
+                width,
+                height,
+                self.array_layers.end - self.array_layers.start,
+            ]
+        }
+    }
+
+    pub fn level_count(&self) -> u32 {
+        self.mip_levels.end - self.mip_levels.start
+    }
+
+    pub fn sample_count(&self) -> u32 {
+        self.image.sample_count
+    }
+
+    pub fn kind(&self) -> conv::ScalarKind {
+        conv::format_kind(self.format)
+    }
+
+    fn texel_location(&self, coords: [i32; 3], layer: u32, level: u32) -> Option<(u32, u32)> {
+        if level >= self.level_count() {
+            return None;
+        }
+        let [width, height, depth] = self.size(level);
+        let slice = if self.is_3d() {
+            if coords[2] < 0 || coords[2] as u32 >= depth {
+                return None;
+            }
+            coords[2] as u32
+        } else {
+            if layer >= self.array_layers.end - self.array_layers.start {
+                return None;
+            }
+            self.array_layers.start + layer
+        };
+        if coords[0] < 0 || coords[0] as u32 >= width || coords[1] < 0 || coords[1] as u32 >= height
+        {
+            return None;
+        }
+        Some((self.mip_levels.start + level, slice))
+    }
+
+    /// Loads a texel, returning zero for out of bounds accesses.
+    pub unsafe fn load(&self, coords: [i32; 3], layer: u32, level: u32) -> Texel {
+        match self.texel_location(coords, layer, level) {
+            Some((level, slice)) => {
+                let bytes =
+                    self.image
+                        .texel_bytes(level, slice, coords[0] as u32, coords[1] as u32);
+                if self.aspects == crate::FormatAspects::STENCIL {
+                    [conv::read_stencil(self.format, bytes), 0, 0, 1]
+                } else {
+                    conv::decode_texel(self.format, bytes)
+                }
+            }
+            None => [0; 4],
+        }
+    }
+
+    /// Stores a texel, ignoring out of bounds accesses.
+    pub unsafe fn store(&self, coords: [i32; 3], layer: u32, texel: Texel) {
+        if let Some((level, slice)) = self.texel_location(coords, layer, 0) {
+            let bytes = self
+                .image
+                .texel_bytes(level, slice, coords[0] as u32, coords[1] as u32);
+            conv::encode_texel(self.format, texel, bytes);
+        }
+    }
+
+    /// Fetches a single texel for filtering, after applying the address modes.
+    unsafe fn fetch(
+        &self,
+        sampler: &Sampler,
+        coords: [i32; 3],
+        layer: u32,
+        level: u32,
+        depth_ref: Option<f32>,
+    ) -> [f32; 4] {
+        let size = self.size(level);
+        let mut wrapped = [0i32; 3];
+        let dims = if self.is_3d() { 3 } else { 2 };
+        for i in 0..dims {
+            match address(sampler.address_modes[i], coords[i], size[i]) {
+                Some(coord) => wrapped[i] = coord as i32,
+                None => return conv::map_border_color(sampler.border_color),
+            }
+        }
+        let texel = self.load(wrapped, layer, level);
+        let value = [
+            f32::from_bits(texel[0]),
+            f32::from_bits(texel[1]),
+            f32::from_bits(texel[2]),
+            f32::from_bits(texel[3]),
+        ];
+        match (depth_ref, sampler.compare) {
+            (Some(reference), Some(function)) => {
+                let passed = conv::compare(function, reference, value[0]) as u32 as f32;
+                [passed, passed, passed, 1.0]
+            }
+            _ => value,
+        }
+    }
+
+    unsafe fn sample_level(
+        &self,
+        sampler: &Sampler,
+        filter: wgt::FilterMode,
+        coords: [f32; 3],
+        layer: u32,
+        level: u32,
+        offset: [i32; 3],
+        depth_ref: Option<f32>,
+    ) -> [f32; 4] {
+        let size = self.size(level);
+        let dims = if self.is_3d() { 3 } else { 2 };
+        match filter {
+            wgt::FilterMode::Nearest => {
+                let mut texel = [0i32; 3];
+                for i in 0..dims {
+                    texel[i] = (coords[i] * size[i] as f32).floor() as i32 + offset[i];
+                }
+                self.fetch(sampler, texel, layer, level, depth_ref)
+            }
+            wgt::FilterMode::Linear => {
+                let mut base = [0i32; 3];
+                let mut fraction = [0f32; 3];
+                for i in 0..dims {
+                    let coord = coords[i] * size[i] as f32 - 0.5;
+                    base[i] = coord.floor() as i32 + offset[i];
+                    fraction[i] = coord - coord.floor();
+                }
+                let mut result = [0f32; 4];
+                let corners = 1 << dims;
+                for corner in 0..corners {
+                    let mut texel = base;
+                    let mut weight = 1.0;
+                    for i in 0..dims {
+                        if corner & (1 << i) != 0 {
+                            texel[i] += 1;
+                            weight *= fraction[i];
+                        } else {
+                            weight *= 1.0 - fraction[i];
+                        }
+                    }
+                    let value = self.fetch(sampler, texel, layer, level, depth_ref);
+                    for (r, v) in result.iter_mut().zip(value.iter()) {
+                        *r += weight * v;
+                    }
+                }
+                result
+            }
+        }
+    }
+
+    /// Samples the view with a filtering sampler. Only valid for float formats.
+    pub unsafe fn sample(&self, sampler: &Sampler, query: &SampleQuery) -> [f32; 4] {
+        let (coords, layer) = if self.is_cube() {
+            let (face, coords) = cube_face(query.coords);
+            ([coords[0], coords[1], 0.0], query.array_index * 6 + face)
+        } else {
+            (query.coords, query.array_index)
+        };
+
+        let max_level = (self.level_count() - 1) as f32;
+        let lod = query
+            .lod
+            .max(sampler.lod_clamp.start)
+            .min(sampler.lod_clamp.end)
+            .max(0.0)
+            .min(max_level);
+        let filter = if query.lod <= 0.0 {
+            sampler.mag_filter
+        } else {
+            sampler.min_filter
+        };
+
+        match sampler.mipmap_filter {
+            wgt::FilterMode::Linear if lod.fract() != 0.0 => {
+                let level = lod.floor() as u32;
+                let weight = lod.fract();
+                let a = self.sample_level(
+                    sampler,
+                    filter,
+                    coords,
+                    layer,
+                    level,
+                    query.offset,
+                    query.depth_ref,
+                );
+                let b = self.sample_level(
+                    sampler,
+                    filter,
+                    coords,
+                    layer,
+                    level + 1,
+                    query.offset,
+                    query.depth_ref,
+                );
+                let mut result = [0.0; 4];
+                for i in 0..4 {
+                    result[i] = a[i] * (1.0 - weight) + b[i] * weight;
+                }
+                result
+            }
+            _ => {
+                let level = (lod + 0.5).floor() as u32;
+                self.sample_level(
+                    sampler,
+                    filter,
+                    coords,
+                    layer,
+                    level.min(max_level as u32),
+                    query.offset,
+                    query.depth_ref,
+                )
+            }
+        }
+    }
+}
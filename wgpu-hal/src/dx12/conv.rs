@@ -15,6 +15,8 @@ pub(super) fn map_texture_format(format: wgt::TextureFormat) -> dxgiformat::DXGI
         Tf::R8Sint => DXGI_FORMAT_R8_SINT,
         Tf::R16Uint => DXGI_FORMAT_R16_UINT,
         Tf::R16Sint => DXGI_FORMAT_R16_SINT,
+        Tf::R16Unorm => DXGI_FORMAT_R16_UNORM,
+        Tf::R16Snorm => DXGI_FORMAT_R16_SNORM,
         Tf::R16Float => DXGI_FORMAT_R16_FLOAT,
         Tf::Rg8Unorm => DXGI_FORMAT_R8G8_UNORM,
         Tf::Rg8Snorm => DXGI_FORMAT_R8G8_SNORM,
@@ -25,6 +27,8 @@ pub(super) fn map_texture_format(format: wgt::TextureFormat) -> dxgiformat::DXGI
         Tf::R32Float => DXGI_FORMAT_R32_FLOAT,
         Tf::Rg16Uint => DXGI_FORMAT_R16G16_UINT,
         Tf::Rg16Sint => DXGI_FORMAT_R16G16_SINT,
+        Tf::Rg16Unorm => DXGI_FORMAT_R16G16_UNORM,
+        Tf::Rg16Snorm => DXGI_FORMAT_R16G16_SNORM,
         Tf::Rg16Float => DXGI_FORMAT_R16G16_FLOAT,
         Tf::Rgba8Unorm => DXGI_FORMAT_R8G8B8A8_UNORM,
         Tf::Rgba8UnormSrgb => DXGI_FORMAT_R8G8B8A8_UNORM_SRGB,
@@ -40,6 +44,8 @@ pub(super) fn map_texture_format(format: wgt::TextureFormat) -> dxgiformat::DXGI
         Tf::Rg32Float => DXGI_FORMAT_R32G32_FLOAT,
         Tf::Rgba16Uint => DXGI_FORMAT_R16G16B16A16_UINT,
         Tf::Rgba16Sint => DXGI_FORMAT_R16G16B16A16_SINT,
+        Tf::Rgba16Unorm => DXGI_FORMAT_R16G16B16A16_UNORM,
+        Tf::Rgba16Snorm => DXGI_FORMAT_R16G16B16A16_SNORM,
         Tf::Rgba16Float => DXGI_FORMAT_R16G16B16A16_FLOAT,
         Tf::Rgba32Uint => DXGI_FORMAT_R32G32B32A32_UINT,
         Tf::Rgba32Sint => DXGI_FORMAT_R32G32B32A32_SINT,
@@ -47,6 +53,10 @@ pub(super) fn map_texture_format(format: wgt::TextureFormat) -> dxgiformat::DXGI
         Tf::Depth32Float => DXGI_FORMAT_D32_FLOAT,
         Tf::Depth24Plus => DXGI_FORMAT_D24_UNORM_S8_UINT,
         Tf::Depth24PlusStencil8 => DXGI_FORMAT_D24_UNORM_S8_UINT,
+        Tf::Depth32FloatStencil8 => DXGI_FORMAT_D32_FLOAT_S8X24_UINT,
+        Tf::Depth16Unorm => DXGI_FORMAT_D16_UNORM,
+        // There is no stencil-only format, so `Stencil8` is not exposed.
+        Tf::Stencil8 => DXGI_FORMAT_UNKNOWN,
         Tf::Rgb9e5Ufloat => DXGI_FORMAT_R9G9B9E5_SHAREDEXP,
         Tf::Bc1RgbaUnorm => DXGI_FORMAT_BC1_UNORM,
         Tf::Bc1RgbaUnormSrgb => DXGI_FORMAT_BC1_UNORM_SRGB,
@@ -115,10 +125,14 @@ pub fn map_texture_format_nosrgb(format: wgt::TextureFormat) -> dxgiformat::DXGI
 //TODO: stencil views?
 pub fn map_texture_format_nodepth(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
     match format {
+        wgt::TextureFormat::Depth16Unorm => dxgiformat::DXGI_FORMAT_R16_UNORM,
         wgt::TextureFormat::Depth32Float => dxgiformat::DXGI_FORMAT_R32_FLOAT,
         wgt::TextureFormat::Depth24Plus | wgt::TextureFormat::Depth24PlusStencil8 => {
             dxgiformat::DXGI_FORMAT_R24_UNORM_X8_TYPELESS
         }
+        wgt::TextureFormat::Depth32FloatStencil8 => {
+            dxgiformat::DXGI_FORMAT_R32_FLOAT_X8X24_TYPELESS
+        }
         _ => {
             assert_eq!(
                 crate::FormatAspects::from(format),
@@ -131,14 +145,42 @@ pub fn map_texture_format_nodepth(format: wgt::TextureFormat) -> dxgiformat::DXG
 
 pub fn map_texture_format_depth_typeless(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
     match format {
+        wgt::TextureFormat::Depth16Unorm => dxgiformat::DXGI_FORMAT_R16_TYPELESS,
         wgt::TextureFormat::Depth32Float => dxgiformat::DXGI_FORMAT_R32_TYPELESS,
         wgt::TextureFormat::Depth24Plus | wgt::TextureFormat::Depth24PlusStencil8 => {
             dxgiformat::DXGI_FORMAT_R24G8_TYPELESS
         }
+        wgt::TextureFormat::Depth32FloatStencil8 => dxgiformat::DXGI_FORMAT_R32G8X24_TYPELESS,
         _ => unreachable!(),
     }
 }
 
+/// Returns the typeless format of the family, for the textures viewed as
+/// both the sRGB and the linear variants of the format.
+pub fn map_texture_format_typeless(format: wgt::TextureFormat) -> dxgiformat::DXGI_FORMAT {
+    match format {
+        wgt::TextureFormat::Rgba8Unorm | wgt::TextureFormat::Rgba8UnormSrgb => {
+            dxgiformat::DXGI_FORMAT_R8G8B8A8_TYPELESS
+        }
+        wgt::TextureFormat::Bgra8Unorm | wgt::TextureFormat::Bgra8UnormSrgb => {
+            dxgiformat::DXGI_FORMAT_B8G8R8A8_TYPELESS
+        }
+        wgt::TextureFormat::Bc1RgbaUnorm | wgt::TextureFormat::Bc1RgbaUnormSrgb => {
+            dxgiformat::DXGI_FORMAT_BC1_TYPELESS
+        }
+        wgt::TextureFormat::Bc2RgbaUnorm | wgt::TextureFormat::Bc2RgbaUnormSrgb => {
+            dxgiformat::DXGI_FORMAT_BC2_TYPELESS
+        }
+        wgt::TextureFormat::Bc3RgbaUnorm | wgt::TextureFormat::Bc3RgbaUnormSrgb => {
+            dxgiformat::DXGI_FORMAT_BC3_TYPELESS
+        }
+        wgt::TextureFormat::Bc7RgbaUnorm | wgt::TextureFormat::Bc7RgbaUnormSrgb => {
+            dxgiformat::DXGI_FORMAT_BC7_TYPELESS
+        }
+        _ => map_texture_format(format),
+    }
+}
+
 pub fn map_index_format(format: wgt::IndexFormat) -> dxgiformat::DXGI_FORMAT {
     match format {
         wgt::IndexFormat::Uint16 => dxgiformat::DXGI_FORMAT_R16_UINT,
@@ -396,19 +396,23 @@ impl crate::Device<super::Api> for super::Device {
             Height: desc.size.height,
             DepthOrArraySize: desc.size.depth_or_array_layers as u16,
             MipLevels: desc.mip_level_count as u16,
-            Format: if crate::FormatAspects::from(desc.format).contains(crate::FormatAspects::COLOR)
-                || !desc.usage.intersects(
+            Format: if !crate::FormatAspects::from(desc.format)
+                .contains(crate::FormatAspects::COLOR)
+                && desc.usage.intersects(
                     crate::TextureUses::RESOURCE
                         | crate::TextureUses::STORAGE_READ
                         | crate::TextureUses::STORAGE_WRITE,
                 ) {
-                conv::map_texture_format(desc.format)
-            } else {
                 // This branch is needed if it's a depth texture, and it's ever needed to be viewed as SRV or UAV,
                 // because then we'd create a non-depth format view of it.
                 // Note: we can skip this branch if
                 // `D3D12_FEATURE_D3D12_OPTIONS3::CastingFullyTypedFormatSupported`
                 conv::map_texture_format_depth_typeless(desc.format)
+            } else if !desc.view_formats.is_empty() {
+                // The views reinterpret the typeless resource with the format they are created with.
+                conv::map_texture_format_typeless(desc.format)
+            } else {
+                conv::map_texture_format(desc.format)
             },
             SampleDesc: dxgitype::DXGI_SAMPLE_DESC {
                 Count: desc.sample_count,
@@ -1396,6 +1400,20 @@ impl crate::Device<super::Api> for super::Device {
         pipeline.raw.destroy();
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        Ok(())
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: ()) {}
+    unsafe fn pipeline_cache_get_data(&self, _cache: &()) -> Option<Vec<u8>> {
+        None
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        None
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1489,4 +1507,7 @@ impl crate::Device<super::Api> for super::Device {
         self.render_doc
             .end_frame_capture(self.raw.as_mut_ptr() as *mut _, ptr::null_mut())
     }
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
 }
@@ -7,6 +7,20 @@ const GL_UNMASKED_VENDOR_WEBGL: u32 = 0x9245;
 const GL_UNMASKED_RENDERER_WEBGL: u32 = 0x9246;
 
 impl super::Adapter {
+    /// Returns the uniform buffer limit reported for the driver limit of a shader stage.
+    ///
+    /// One of the uniform blocks is reserved for the emulated push constants, unless
+    /// that would go below the limit of [`wgt::Limits::downlevel_defaults`]. On such drivers,
+    /// pipelines using push constants fail to link if a stage also uses all the uniform buffers.
+    fn max_uniform_buffers_per_shader_stage(driver_limit: u32) -> u32 {
+        let required = wgt::Limits::downlevel_defaults().max_uniform_buffers_per_shader_stage;
+        if driver_limit > required {
+            driver_limit - 1
+        } else {
+            driver_limit
+        }
+    }
+
     /// According to the OpenGL specification, the version information is
     /// expected to follow the following syntax:
     ///
@@ -230,12 +244,27 @@ impl super::Adapter {
             vertex_shader_storage_textures.min(fragment_shader_storage_textures)
         };
 
+        // Texture views aren't in any GLES version, only in extensions of GLES 3.1.
+        let texture_view_name = if ver < (3, 1) {
+            None
+        } else if extensions.contains("GL_OES_texture_view") {
+            Some("glTextureViewOES")
+        } else if extensions.contains("GL_EXT_texture_view") {
+            Some("glTextureViewEXT")
+        } else {
+            None
+        };
+        let texture_view = texture_view_name
+            .and_then(|name| context.get_proc_address(name))
+            .map(|function| std::mem::transmute::<_, super::TextureViewFn>(function));
+
         let mut downlevel_flags = wgt::DownlevelFlags::empty()
             | wgt::DownlevelFlags::DEVICE_LOCAL_IMAGE_COPIES
             | wgt::DownlevelFlags::NON_POWER_OF_TWO_MIPMAPPED_TEXTURES
             | wgt::DownlevelFlags::CUBE_ARRAY_TEXTURES
             | wgt::DownlevelFlags::COMPARISON_SAMPLERS;
         downlevel_flags.set(wgt::DownlevelFlags::COMPUTE_SHADERS, ver >= (3, 1));
+        downlevel_flags.set(wgt::DownlevelFlags::VIEW_FORMATS, texture_view.is_some());
         downlevel_flags.set(
             wgt::DownlevelFlags::FRAGMENT_WRITABLE_STORAGE,
             ver >= (3, 1) && max_storage_block_size != 0,
@@ -258,11 +287,21 @@ impl super::Adapter {
         let mut features = wgt::Features::empty()
             | wgt::Features::TEXTURE_COMPRESSION_ETC2
             | wgt::Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-            | wgt::Features::CLEAR_COMMANDS;
+            | wgt::Features::CLEAR_COMMANDS
+            | wgt::Features::PUSH_CONSTANTS
+            | wgt::Features::DEPTH32FLOAT_STENCIL8;
         features.set(
             wgt::Features::DEPTH_CLAMPING,
             extensions.contains("GL_EXT_depth_clamp"),
         );
+        features.set(
+            wgt::Features::TEXTURE_FORMAT_16BIT_NORM,
+            extensions.contains("GL_EXT_texture_norm16"),
+        );
+        features.set(
+            wgt::Features::TEXTURE_FORMAT_STENCIL8,
+            ver >= (3, 2) || extensions.contains("GL_OES_texture_stencil8"),
+        );
         features.set(
             wgt::Features::VERTEX_WRITABLE_STORAGE,
             downlevel_flags.contains(wgt::DownlevelFlags::VERTEX_STORAGE)
@@ -294,9 +333,10 @@ impl super::Adapter {
         } else {
             256
         };
-        let max_uniform_buffers_per_shader_stage =
+        let max_uniform_buffers_per_shader_stage = Self::max_uniform_buffers_per_shader_stage(
             gl.get_parameter_i32(glow::MAX_VERTEX_UNIFORM_BLOCKS)
-                .min(gl.get_parameter_i32(glow::MAX_FRAGMENT_UNIFORM_BLOCKS)) as u32;
+                .min(gl.get_parameter_i32(glow::MAX_FRAGMENT_UNIFORM_BLOCKS)) as u32,
+        );
 
         let limits = wgt::Limits {
             max_texture_dimension_1d: max_texture_size,
@@ -334,7 +374,7 @@ impl super::Adapter {
             } else {
                 !0
             },
-            max_push_constant_size: 0,
+            max_push_constant_size: super::MAX_PUSH_CONSTANTS as u32 * 4,
             min_uniform_buffer_offset_alignment,
             min_storage_buffer_offset_alignment,
         };
@@ -367,6 +407,7 @@ impl super::Adapter {
                     private_caps,
                     workarounds,
                     shading_language_version,
+                    texture_view,
                 }),
             },
             info: Self::make_info(vendor, renderer),
@@ -435,6 +476,16 @@ impl crate::Adapter<super::Api> for super::Adapter {
         let zeroes = vec![0u8; super::ZERO_BUFFER_SIZE];
         gl.buffer_data_u8_slice(glow::COPY_READ_BUFFER, &zeroes, glow::STATIC_DRAW);
 
+        let push_constant_buffer = gl
+            .create_buffer()
+            .map_err(|_| crate::DeviceError::OutOfMemory)?;
+        gl.bind_buffer(glow::UNIFORM_BUFFER, Some(push_constant_buffer));
+        gl.buffer_data_size(
+            glow::UNIFORM_BUFFER,
+            super::MAX_PUSH_CONSTANTS as i32 * 4,
+            glow::DYNAMIC_DRAW,
+        );
+
         // Compile the shader program we use for doing manual clears to work around Mesa fastclear
         // bug.
         let (shader_clear_program, shader_clear_program_color_uniform_location) =
@@ -459,6 +510,7 @@ impl crate::Adapter<super::Api> for super::Adapter {
                 shader_clear_program,
                 shader_clear_program_color_uniform_location,
                 zero_buffer,
+                push_constant_buffer,
                 temp_query_results: Vec::new(),
                 draw_buffer_count: 1,
             },
@@ -479,6 +531,8 @@ impl crate::Adapter<super::Api> for super::Adapter {
             Tf::R8Unorm | Tf::R8Snorm => filtered_color,
             Tf::R8Uint | Tf::R8Sint | Tf::R16Uint | Tf::R16Sint => unfiltered_color,
             Tf::R16Float | Tf::Rg8Unorm | Tf::Rg8Snorm => filtered_color,
+            Tf::R16Unorm | Tf::Rg16Unorm | Tf::Rgba16Unorm => filtered_color,
+            Tf::R16Snorm | Tf::Rg16Snorm | Tf::Rgba16Snorm => Tfc::SAMPLED | Tfc::SAMPLED_LINEAR,
             Tf::Rg8Uint | Tf::Rg8Sint | Tf::R32Uint | Tf::R32Sint => {
                 unfiltered_color | Tfc::STORAGE
             }
@@ -496,7 +550,11 @@ impl crate::Adapter<super::Api> for super::Adapter {
             Tf::Rgba32Float => unfiltered_color | Tfc::STORAGE,
             Tf::Depth32Float => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
             Tf::Depth24Plus => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
-            Tf::Depth24PlusStencil8 => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
+            Tf::Depth24PlusStencil8 | Tf::Depth32FloatStencil8 => {
+                Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT
+            }
+            Tf::Depth16Unorm => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
+            Tf::Stencil8 => Tfc::SAMPLED | Tfc::DEPTH_STENCIL_ATTACHMENT,
             Tf::Rgb9e5Ufloat
             | Tf::Bc1RgbaUnorm
             | Tf::Bc1RgbaUnormSrgb
@@ -593,6 +651,15 @@ impl crate::Adapter<super::Api> for super::Adapter {
 mod tests {
     use super::super::Adapter;
 
+    #[test]
+    fn test_max_uniform_buffers() {
+        // The GLES 3.0 minimum is what the downlevel limits require.
+        assert_eq!(Adapter::max_uniform_buffers_per_shader_stage(12), 12);
+        assert_eq!(Adapter::max_uniform_buffers_per_shader_stage(13), 12);
+        assert_eq!(Adapter::max_uniform_buffers_per_shader_stage(14), 13);
+        assert_eq!(Adapter::max_uniform_buffers_per_shader_stage(0), 0);
+    }
+
     #[test]
     fn test_version_parse() {
         let error = Err(crate::InstanceError);
@@ -43,6 +43,14 @@ impl super::CommandBuffer {
         self.data_bytes.extend(marker.as_bytes());
         start..self.data_bytes.len() as u32
     }
+
+    fn add_push_constant_data(&mut self, data: &[u32]) -> Range<u32> {
+        let start = self.data_bytes.len() as u32;
+        for value in data {
+            self.data_bytes.extend(&value.to_ne_bytes());
+        }
+        start..self.data_bytes.len() as u32
+    }
 }
 
 impl super::CommandEncoder {
@@ -148,8 +156,11 @@ impl super::CommandEncoder {
     fn set_pipeline_inner(&mut self, inner: &super::PipelineInner) {
         self.cmd_buffer.commands.push(C::SetProgram(inner.program));
 
-        //TODO: push constants
-        let _ = &inner.uniforms;
+        if let Some(slot) = inner.push_constant_slot {
+            self.cmd_buffer
+                .commands
+                .push(C::BindPushConstantBuffer(slot as u32));
+        }
 
         // rebind textures, if needed
         let mut dirty_textures = 0u32;
@@ -594,10 +605,14 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         &mut self,
         _layout: &super::PipelineLayout,
         _stages: wgt::ShaderStages,
-        _offset: u32,
-        _data: &[u32],
+        offset: u32,
+        data: &[u32],
     ) {
-        unimplemented!()
+        let range = self.cmd_buffer.add_push_constant_data(data);
+        self.cmd_buffer.commands.push(C::SetPushConstants {
+            offset,
+            data: range,
+        });
     }
 
     unsafe fn insert_debug_marker(&mut self, label: &str) {
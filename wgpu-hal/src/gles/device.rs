@@ -21,7 +21,8 @@ impl CompilationContext<'_> {
         module: &naga::Module,
         ep_info: &naga::valid::FunctionInfo,
         reflection_info: naga::back::glsl::ReflectionInfo,
-    ) {
+        naga_stage: naga::ShaderStage,
+    ) -> Result<(), crate::PipelineError> {
         for (handle, var) in module.global_variables.iter() {
             if ep_info[handle].is_empty() {
                 continue;
@@ -33,7 +34,17 @@ impl CompilationContext<'_> {
             };
 
             let br = var.binding.as_ref().unwrap();
-            let slot = self.layout.get_slot(br);
+            let slot = if *br == super::PUSH_CONSTANT_BINDING {
+                self.layout.push_constant_slot.ok_or_else(|| {
+                    crate::PipelineError::Linkage(
+                        map_naga_stage(naga_stage),
+                        "the shader uses push constants, but the pipeline layout has no push constant ranges"
+                            .to_string(),
+                    )
+                })?
+            } else {
+                self.layout.get_slot(br)
+            };
 
             let name = match reflection_info.uniforms.get(&handle) {
                 Some(name) => name.clone(),
@@ -73,9 +84,155 @@ impl CompilationContext<'_> {
                 self.sampler_map[texture_linear_index as usize] = Some(sampler_linear_index);
             }
         }
+        Ok(())
     }
 }
 
+/// Turns the push constant blocks into uniform blocks, which are backed
+/// by a uniform buffer updated with the values of the push constants.
+fn emulate_push_constants(
+    shader: crate::NagaShader,
+) -> Result<crate::NagaShader, crate::ShaderError> {
+    let mut module = shader.module;
+    let mut has_push_constants = false;
+    for (_, var) in module.global_variables.iter_mut() {
+        if var.class != naga::StorageClass::PushConstant {
+            continue;
+        }
+        match module.types[var.ty].inner {
+            naga::TypeInner::Struct {
+                top_level: true, ..
+            } => {}
+            _ => {
+                return Err(crate::ShaderError::Compilation(
+                    "Push constants are only supported in block structures".to_string(),
+                ))
+            }
+        }
+        var.class = naga::StorageClass::Uniform;
+        var.binding = Some(super::PUSH_CONSTANT_BINDING);
+        has_push_constants = true;
+    }
+    if !has_push_constants {
+        return Ok(crate::NagaShader {
+            module,
+            info: shader.info,
+        });
+    }
+
+    // The uniform blocks are laid out with `std140` in GLSL, while the values
+    // are written with the layout of the shader, so these have to match.
+    for (_, var) in module.global_variables.iter() {
+        if var.binding == Some(super::PUSH_CONSTANT_BINDING) {
+            std140_layout(&module, var.ty).map_err(|e| {
+                crate::ShaderError::Compilation(format!(
+                    "Push constants can't be emulated with a uniform block: {}",
+                    e
+                ))
+            })?;
+        }
+    }
+
+    let info = naga::valid::Validator::new(
+        naga::valid::ValidationFlags::all(),
+        naga::valid::Capabilities::all(),
+    )
+    .validate(&module)
+    .map_err(|e| {
+        crate::ShaderError::Compilation(format!(
+            "Push constants can't be emulated with a uniform block: {}",
+            e
+        ))
+    })?;
+    Ok(crate::NagaShader { module, info })
+}
+
+/// Returns the size and alignment of a type in the `std140` layout,
+/// checking that the offsets and strides given by the shader follow it.
+fn std140_layout(
+    module: &naga::Module,
+    ty: naga::Handle<naga::Type>,
+) -> Result<(u32, u32), String> {
+    use naga::TypeInner as Ti;
+
+    let round_up = |value: u32, alignment: u32| (value + alignment - 1) / alignment * alignment;
+    let vector_alignment = |size: naga::VectorSize, width: u32| match size {
+        naga::VectorSize::Bi => 2 * width,
+        naga::VectorSize::Tri | naga::VectorSize::Quad => 4 * width,
+    };
+
+    Ok(match module.types[ty].inner {
+        Ti::Scalar { width, .. } => (width as u32, width as u32),
+        Ti::Vector { size, width, .. } => (
+            size as u32 * width as u32,
+            vector_alignment(size, width as u32),
+        ),
+        // Matrices are laid out like arrays of their columns,
+        // which have to be 16 byte aligned in `std140`.
+        Ti::Matrix {
+            columns,
+            rows,
+            width,
+        } => {
+            let column_stride = vector_alignment(rows, width as u32);
+            if column_stride % 16 != 0 {
+                return Err(format!(
+                    "matrix column stride is {} instead of a multiple of 16",
+                    column_stride
+                ));
+            }
+            (columns as u32 * column_stride, column_stride)
+        }
+        Ti::Array {
+            base,
+            size: naga::ArraySize::Constant(handle),
+            stride,
+        } => {
+            let (base_size, base_alignment) = std140_layout(module, base)?;
+            let alignment = round_up(base_alignment, 16);
+            let expected_stride = round_up(base_size, alignment);
+            if stride != expected_stride {
+                return Err(format!(
+                    "array stride is {} instead of {}",
+                    stride, expected_stride
+                ));
+            }
+            let count = match module.constants[handle].inner {
+                naga::ConstantInner::Scalar {
+                    value: naga::ScalarValue::Uint(count),
+                    ..
+                } => count as u32,
+                naga::ConstantInner::Scalar {
+                    value: naga::ScalarValue::Sint(count),
+                    ..
+                } => count as u32,
+                _ => return Err("array size is not an integer".to_string()),
+            };
+            (count * stride, alignment)
+        }
+        Ti::Struct { ref members, .. } => {
+            let mut alignment = 16;
+            let mut end = 0;
+            for member in members {
+                let (size, member_alignment) = std140_layout(module, member.ty)?;
+                let expected_offset = round_up(end, member_alignment);
+                if member.offset != expected_offset {
+                    return Err(format!(
+                        "member {:?} is at offset {} instead of {}",
+                        member.name.as_deref().unwrap_or_default(),
+                        member.offset,
+                        expected_offset
+                    ));
+                }
+                end = member.offset + size;
+                alignment = alignment.max(member_alignment);
+            }
+            (round_up(end, alignment), alignment)
+        }
+        ref other => return Err(format!("{:?} can't be in a uniform block", other)),
+    })
+}
+
 impl super::Device {
     unsafe fn compile_shader(
         gl: &glow::Context,
@@ -160,7 +317,8 @@ impl super::Device {
             &shader.module,
             shader.info.get_entry_point(entry_point_index),
             reflection_info,
-        );
+            naga_stage,
+        )?;
 
         unsafe { Self::compile_shader(gl, &output, naga_stage, stage.module.label.as_deref()) }
     }
@@ -263,35 +421,10 @@ impl super::Device {
             }
         }
 
-        let uniforms = {
-            let count = gl.get_active_uniforms(program);
-            let mut offset = 0;
-            let mut uniforms = Vec::new();
-
-            for uniform in 0..count {
-                let glow::ActiveUniform { size, utype, name } =
-                    gl.get_active_uniform(program, uniform).unwrap();
-
-                if let Some(location) = gl.get_uniform_location(program, &name) {
-                    // Sampler2D won't show up in UniformLocation and the only other uniforms
-                    // should be push constants
-                    uniforms.push(super::UniformDesc {
-                        location,
-                        offset,
-                        utype,
-                    });
-
-                    offset += size as u32;
-                }
-            }
-
-            uniforms.into_boxed_slice()
-        };
-
         Ok(super::PipelineInner {
             program,
             sampler_map,
-            uniforms,
+            push_constant_slot: layout.push_constant_slot,
         })
     }
 }
@@ -303,6 +436,7 @@ impl crate::Device<super::Api> for super::Device {
         gl.delete_framebuffer(queue.draw_fbo);
         gl.delete_framebuffer(queue.copy_fbo);
         gl.delete_buffer(queue.zero_buffer);
+        gl.delete_buffer(queue.push_constant_buffer);
     }
 
     unsafe fn create_buffer(
@@ -435,9 +569,11 @@ impl crate::Device<super::Api> for super::Device {
             depth: 1,
         };
 
+        // Renderbuffers can't be viewed in other formats.
         let inner = if render_usage.contains(desc.usage)
             && desc.dimension == wgt::TextureDimension::D2
             && desc.size.depth_or_array_layers == 1
+            && desc.view_formats.is_empty()
         {
             let raw = gl.create_renderbuffer().unwrap();
             gl.bind_renderbuffer(glow::RENDERBUFFER, Some(raw));
@@ -597,18 +733,59 @@ impl crate::Device<super::Api> for super::Device {
             Some(count) => desc.range.base_mip_level + count.get(),
             None => texture.mip_level_count,
         };
+        let (inner, owns_texture) = match texture.inner {
+            // The sRGB decoding and encoding is a property of the texture object,
+            // so views in another format need one of their own, sharing the storage.
+            // It covers the whole texture, for the ranges to stay relative to the original.
+            super::TextureInner::Texture { raw, target } if desc.format != texture.format => {
+                let texture_view = self
+                    .shared
+                    .texture_view
+                    .expect("View formats require DownlevelFlags::VIEW_FORMATS");
+                let format_desc = self.shared.describe_texture_format(desc.format);
+                let gl = &self.shared.context.lock();
+                let view_raw = gl.create_texture().unwrap();
+                texture_view(
+                    mem::transmute(view_raw),
+                    target,
+                    mem::transmute(raw),
+                    format_desc.internal,
+                    0,
+                    texture.mip_level_count,
+                    0,
+                    texture.array_layer_count,
+                );
+                if let Some(label) = desc.label {
+                    if gl.supports_debug() {
+                        gl.object_label(glow::TEXTURE, mem::transmute(view_raw), Some(label));
+                    }
+                }
+                let inner = super::TextureInner::Texture {
+                    raw: view_raw,
+                    target,
+                };
+                (inner, true)
+            }
+            ref inner => (inner.clone(), false),
+        };
         Ok(super::TextureView {
             //TODO: use `conv::map_view_dimension(desc.dimension)`?
-            inner: texture.inner.clone(),
-            sample_type: texture.format.describe().sample_type,
-            aspects: crate::FormatAspects::from(texture.format)
+            inner,
+            sample_type: desc.format.describe().sample_type,
+            aspects: crate::FormatAspects::from(desc.format)
                 & crate::FormatAspects::from(desc.range.aspect),
             mip_levels: desc.range.base_mip_level..end_mip_level,
             array_layers: desc.range.base_array_layer..end_array_layer,
-            format: texture.format,
+            format: desc.format,
+            owns_texture,
         })
     }
-    unsafe fn destroy_texture_view(&self, _view: super::TextureView) {}
+    unsafe fn destroy_texture_view(&self, view: super::TextureView) {
+        if let (true, super::TextureInner::Texture { raw, .. }) = (view.owns_texture, view.inner) {
+            let gl = &self.shared.context.lock();
+            gl.delete_texture(raw);
+        }
+    }
 
     unsafe fn create_sampler(
         &self,
@@ -770,8 +947,16 @@ impl crate::Device<super::Api> for super::Device {
             });
         }
 
+        let push_constant_slot = if desc.push_constant_ranges.is_empty() {
+            None
+        } else {
+            binding_map.insert(super::PUSH_CONSTANT_BINDING, num_uniform_buffers);
+            Some(num_uniform_buffers)
+        };
+
         Ok(super::PipelineLayout {
             group_infos: group_infos.into_boxed_slice(),
+            push_constant_slot,
             naga_options: glsl::Options {
                 version: self.shared.shading_language_version,
                 writer_flags,
@@ -865,7 +1050,7 @@ impl crate::Device<super::Api> for super::Device {
                 crate::ShaderInput::SpirV(_) => {
                     panic!("`Features::SPIRV_SHADER_PASSTHROUGH` is not enabled")
                 }
-                crate::ShaderInput::Naga(naga) => naga,
+                crate::ShaderInput::Naga(naga) => emulate_push_constants(naga)?,
             },
             label: desc.label.map(|str| str.to_string()),
         })
@@ -959,6 +1144,20 @@ impl crate::Device<super::Api> for super::Device {
         gl.delete_program(pipeline.inner.program);
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        Ok(())
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: ()) {}
+    unsafe fn pipeline_cache_get_data(&self, _cache: &()) -> Option<Vec<u8>> {
+        None
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        None
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1053,4 +1252,69 @@ impl crate::Device<super::Api> for super::Device {
         self.render_doc
             .end_frame_capture(ptr::null_mut(), ptr::null_mut())
     }
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::std140_layout;
+
+    fn push_constant_layout(source: &str) -> Result<(u32, u32), String> {
+        let module = naga::front::wgsl::parse_str(source).unwrap();
+        let (_, var) = module
+            .global_variables
+            .iter()
+            .find(|(_, var)| var.class == naga::StorageClass::PushConstant)
+            .unwrap();
+        std140_layout(&module, var.ty)
+    }
+
+    #[test]
+    fn std140_push_constants() {
+        let layout = push_constant_layout(
+            "
+            [[block]] struct PushConstants { color: vec4<f32>; offset: vec3<f32>; scale: f32; };
+            var<push_constant> pc: PushConstants;
+            ",
+        );
+        assert_eq!(layout, Ok((32, 16)));
+
+        let layout = push_constant_layout(
+            "
+            [[block]] struct PushConstants { transform: mat4x4<f32>; ids: array<vec4<u32>, 2>; };
+            var<push_constant> pc: PushConstants;
+            ",
+        );
+        assert_eq!(layout, Ok((96, 16)));
+    }
+
+    #[test]
+    fn non_std140_push_constants() {
+        let layout = push_constant_layout(
+            "
+            [[block]] struct PushConstants { weights: array<f32, 4>; };
+            var<push_constant> pc: PushConstants;
+            ",
+        );
+        assert!(layout.is_err());
+
+        let layout = push_constant_layout(
+            "
+            [[block]] struct PushConstants { rotation: mat2x2<f32>; };
+            var<push_constant> pc: PushConstants;
+            ",
+        );
+        assert!(layout.is_err());
+
+        let layout = push_constant_layout(
+            "
+            struct Inner { value: f32; };
+            [[block]] struct PushConstants { inner: Inner; value: f32; };
+            var<push_constant> pc: PushConstants;
+            ",
+        );
+        assert!(layout.is_err());
+    }
 }
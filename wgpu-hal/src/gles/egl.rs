@@ -312,6 +312,11 @@ impl AdapterContext {
             egl_display: self.egl_display,
         }
     }
+
+    /// Returns the address of a GL function that the [`glow::Context`] doesn't expose.
+    pub fn get_proc_address(&self, name: &str) -> Option<extern "system" fn()> {
+        self.egl.get_proc_address(name)
+    }
 }
 
 #[derive(Debug)]
@@ -823,6 +828,13 @@ impl crate::Surface<super::Api> for Surface {
     ) -> Result<(), crate::SurfaceError> {
         use raw_window_handle::RawWindowHandle as Rwh;
 
+        // The surface textures are renderbuffers, which have no views.
+        if !config.view_formats.is_empty() {
+            return Err(crate::SurfaceError::Other(
+                "surface textures can't be viewed in other formats",
+            ));
+        }
+
         let (surface, wl_window) = match self.unconfigure_impl(device) {
             Some(pair) => pair,
             None => {
@@ -83,6 +83,13 @@ const MAX_TEXTURE_SLOTS: usize = 16;
 const MAX_SAMPLERS: usize = 16;
 const MAX_VERTEX_ATTRIBUTES: usize = 16;
 const ZERO_BUFFER_SIZE: usize = 256 << 10;
+const MAX_PUSH_CONSTANTS: usize = 64;
+/// Push constants are emulated with a uniform buffer, which is
+/// bound to the shaders with this resource binding.
+const PUSH_CONSTANT_BINDING: naga::ResourceBinding = naga::ResourceBinding {
+    group: !0,
+    binding: 0,
+};
 
 impl crate::Api for Api {
     type Instance = Instance;
@@ -108,6 +115,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = ();
 }
 
 bitflags::bitflags! {
@@ -140,6 +148,18 @@ bitflags::bitflags! {
 
 type BindTarget = u32;
 
+/// `glTextureView` of `GL_EXT_texture_view` or `GL_OES_texture_view`, which glow doesn't expose.
+type TextureViewFn = unsafe extern "system" fn(
+    texture: u32,
+    target: u32,
+    orig_texture: u32,
+    internal_format: u32,
+    min_level: u32,
+    num_levels: u32,
+    min_layer: u32,
+    num_layers: u32,
+);
+
 #[derive(Debug, Clone, Copy)]
 enum VertexAttribKind {
     Float, // glVertexAttribPointer
@@ -165,6 +185,9 @@ struct AdapterShared {
     private_caps: PrivateCapabilities,
     workarounds: Workarounds,
     shading_language_version: naga::back::glsl::Version,
+    /// Creates textures sharing the storage of another one in a different format,
+    /// if available. `DownlevelFlags::VIEW_FORMATS` is exposed when it is.
+    texture_view: Option<TextureViewFn>,
 }
 
 pub struct Adapter {
@@ -191,6 +214,8 @@ pub struct Queue {
     /// Keep a reasonably large buffer filled with zeroes, so that we can implement `ClearBuffer` of
     /// zeroes by copying from it.
     zero_buffer: glow::Buffer,
+    /// Uniform buffer holding the emulated push constants.
+    push_constant_buffer: glow::Buffer,
     temp_query_results: Vec<u64>,
     draw_buffer_count: u8,
 }
@@ -241,6 +266,8 @@ pub struct TextureView {
     mip_levels: Range<u32>,
     array_layers: Range<u32>,
     format: wgt::TextureFormat,
+    /// The view has its own texture object, created with `glTextureView`.
+    owns_texture: bool,
 }
 
 #[derive(Debug)]
@@ -264,10 +291,13 @@ struct BindGroupLayoutInfo {
 
 pub struct PipelineLayout {
     group_infos: Box<[BindGroupLayoutInfo]>,
+    /// Uniform buffer slot of the push constants, following the ones of the bind groups.
+    push_constant_slot: Option<u8>,
     naga_options: naga::back::glsl::Options,
 }
 
 impl PipelineLayout {
+    /// Returns the slot of a binding of the bind groups.
     fn get_slot(&self, br: &naga::ResourceBinding) -> u8 {
         let group_info = &self.group_infos[br.group as usize];
         group_info.binding_to_slot[br.binding as usize]
@@ -345,13 +375,6 @@ struct VertexBufferDesc {
     stride: u32,
 }
 
-#[derive(Clone)]
-struct UniformDesc {
-    location: glow::UniformLocation,
-    offset: u32,
-    utype: u32,
-}
-
 /// For each texture in the pipeline layout, store the index of the only
 /// sampler (in this layout) that the texture is used with.
 type SamplerBindMap = [Option<u8>; MAX_TEXTURE_SLOTS];
@@ -359,7 +382,7 @@ type SamplerBindMap = [Option<u8>; MAX_TEXTURE_SLOTS];
 struct PipelineInner {
     program: glow::Program,
     sampler_map: SamplerBindMap,
-    uniforms: Box<[UniformDesc]>,
+    push_constant_slot: Option<u8>,
 }
 
 #[derive(Clone, Debug)]
@@ -654,6 +677,11 @@ enum Command {
         slot: u32,
         binding: ImageBinding,
     },
+    SetPushConstants {
+        offset: u32,
+        data: Range<u32>,
+    },
+    BindPushConstantBuffer(u32),
     InsertDebugMarker(Range<u32>),
     PushDebugGroup(Range<u32>),
     PopDebugGroup,
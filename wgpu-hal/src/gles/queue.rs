@@ -923,6 +923,17 @@ impl super::Queue {
                     binding.format,
                 );
             }
+            C::SetPushConstants { offset, ref data } => {
+                gl.bind_buffer(glow::UNIFORM_BUFFER, Some(self.push_constant_buffer));
+                gl.buffer_sub_data_u8_slice(
+                    glow::UNIFORM_BUFFER,
+                    offset as i32,
+                    &data_bytes[data.start as usize..data.end as usize],
+                );
+            }
+            C::BindPushConstantBuffer(slot) => {
+                gl.bind_buffer_base(glow::UNIFORM_BUFFER, slot, Some(self.push_constant_buffer));
+            }
             C::InsertDebugMarker(ref range) => {
                 let marker = extract_marker(data_bytes, range);
                 gl.debug_message_insert(
@@ -46,6 +46,8 @@ compile_error!("Metal API enabled on non-Apple OS. If your project is not using
 #[cfg(all(feature = "dx12", not(windows)))]
 compile_error!("DX12 API enabled on non-Windows OS. If your project is not using resolver=\"2\" in Cargo.toml, it should.");
 
+#[cfg(feature = "cpu")]
+mod cpu;
 #[cfg(all(feature = "dx12", windows))]
 mod dx12;
 mod empty;
@@ -58,6 +60,8 @@ mod vulkan;
 
 pub mod auxil;
 pub mod api {
+    #[cfg(feature = "cpu")]
+    pub use super::cpu::Api as Cpu;
     #[cfg(feature = "dx12")]
     pub use super::dx12::Api as Dx12;
     pub use super::empty::Api as Empty;
@@ -158,6 +162,7 @@ pub trait Api: Clone + Sized {
     type ShaderModule: fmt::Debug + Send + Sync;
     type RenderPipeline: Send + Sync;
     type ComputePipeline: Send + Sync;
+    type PipelineCache: fmt::Debug + Send + Sync;
 }
 
 pub trait Instance<A: Api>: Sized + Send + Sync {
@@ -277,6 +282,24 @@ pub trait Device<A: Api>: Send + Sync {
         desc: &ComputePipelineDescriptor<A>,
     ) -> Result<A::ComputePipeline, PipelineError>;
     unsafe fn destroy_compute_pipeline(&self, pipeline: A::ComputePipeline);
+    /// Creates a pipeline cache, optionally pre-populated with `desc.data`.
+    ///
+    /// The data must have been returned by `pipeline_cache_get_data` on a device
+    /// with the same `pipeline_cache_validation_key`.
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &PipelineCacheDescriptor,
+    ) -> Result<A::PipelineCache, DeviceError>;
+    unsafe fn destroy_pipeline_cache(&self, cache: A::PipelineCache);
+    /// Returns the serialized contents of the cache.
+    ///
+    /// `None` means the backend doesn't support retrieving it.
+    unsafe fn pipeline_cache_get_data(&self, cache: &A::PipelineCache) -> Option<Vec<u8>>;
+    /// Returns a key identifying the driver that produced the pipeline cache data.
+    ///
+    /// The cache data is only compatible with devices returning the same key.
+    /// `None` means pipeline caches are not supported.
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]>;
 
     unsafe fn create_query_set(
         &self,
@@ -295,6 +318,11 @@ pub trait Device<A: Api>: Send + Sync {
 
     unsafe fn start_capture(&self) -> bool;
     unsafe fn stop_capture(&self);
+
+    /// Returns the statistics of the memory allocator.
+    ///
+    /// `None` means the backend doesn't provide them.
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport>;
 }
 
 pub trait Queue<A: Api>: Send + Sync {
@@ -577,8 +605,13 @@ impl From<wgt::TextureAspect> for FormatAspects {
 impl From<wgt::TextureFormat> for FormatAspects {
     fn from(format: wgt::TextureFormat) -> Self {
         match format {
-            wgt::TextureFormat::Depth32Float | wgt::TextureFormat::Depth24Plus => Self::DEPTH,
-            wgt::TextureFormat::Depth24PlusStencil8 => Self::DEPTH | Self::STENCIL,
+            wgt::TextureFormat::Depth16Unorm
+            | wgt::TextureFormat::Depth32Float
+            | wgt::TextureFormat::Depth24Plus => Self::DEPTH,
+            wgt::TextureFormat::Depth24PlusStencil8 | wgt::TextureFormat::Depth32FloatStencil8 => {
+                Self::DEPTH | Self::STENCIL
+            }
+            wgt::TextureFormat::Stencil8 => Self::STENCIL,
             _ => Self::COLOR,
         }
     }
@@ -760,12 +793,14 @@ pub struct TextureDescriptor<'a> {
     pub format: wgt::TextureFormat,
     pub usage: TextureUses,
     pub memory_flags: MemoryFlags,
+    /// Formats that views of the texture may use, other than `format`.
+    pub view_formats: &'a [wgt::TextureFormat],
 }
 
 /// TextureView descriptor.
 ///
 /// Valid usage:
-///. - `format` has to be the same as `TextureDescriptor::format`
+///. - `format` has to be `TextureDescriptor::format` or one of `TextureDescriptor::view_formats`
 ///. - `dimension` has to be compatible with `TextureDescriptor::dimension`
 ///. - `usage` has to be a subset of `TextureDescriptor::usage`
 ///. - `range` has to be a subset of parent texture
@@ -928,6 +963,8 @@ pub struct ComputePipelineDescriptor<'a, A: Api> {
     pub layout: &'a A::PipelineLayout,
     /// The compiled compute stage and its entry point.
     pub stage: ProgrammableStage<'a, A>,
+    /// The cache to look up and store the compiled pipeline in.
+    pub cache: Option<&'a A::PipelineCache>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -961,6 +998,15 @@ pub struct RenderPipelineDescriptor<'a, A: Api> {
     pub fragment_stage: Option<ProgrammableStage<'a, A>>,
     /// The effect of draw calls on the color aspect of the output target.
     pub color_targets: &'a [wgt::ColorTargetState],
+    /// The cache to look up and store the compiled pipeline in.
+    pub cache: Option<&'a A::PipelineCache>,
+}
+
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    pub label: Label<'a>,
+    /// Data previously returned by `pipeline_cache_get_data`.
+    pub data: Option<&'a [u8]>,
 }
 
 /// Specifies how the alpha channel of the textures should be handled during (martin mouv i step)
@@ -1000,6 +1046,8 @@ pub struct SurfaceConfiguration {
     pub extent: wgt::Extent3d,
     /// Allowed usage of surface textures,
     pub usage: TextureUses,
+    /// Formats that views of the surface textures may use, other than `format`.
+    pub view_formats: Vec<wgt::TextureFormat>,
 }
 
 #[derive(Debug, Clone)]
@@ -1114,6 +1162,8 @@ pub struct RenderPassDescriptor<'a, A: Api> {
     pub sample_count: u32,
     pub color_attachments: &'a [ColorAttachment<'a, A>],
     pub depth_stencil_attachment: Option<DepthStencilAttachment<'a, A>>,
+    /// Query set that occlusion queries started within the pass write to.
+    pub occlusion_query_set: Option<&'a A::QuerySet>,
 }
 
 #[derive(Clone, Debug)]
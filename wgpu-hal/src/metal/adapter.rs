@@ -72,6 +72,14 @@ impl crate::Adapter<super::Api> for super::Adapter {
                     | Tfc::COLOR_ATTACHMENT
                     | Tfc::COLOR_ATTACHMENT_BLEND
             }
+            Tf::R16Unorm
+            | Tf::R16Snorm
+            | Tf::Rg16Unorm
+            | Tf::Rg16Snorm
+            | Tf::Rgba16Unorm
+            | Tf::Rgba16Snorm => {
+                Tfc::SAMPLED_LINEAR | Tfc::COLOR_ATTACHMENT | Tfc::COLOR_ATTACHMENT_BLEND
+            }
             Tf::Rg8Unorm | Tf::Rg8Snorm => {
                 Tfc::SAMPLED_LINEAR
                     | Tfc::STORAGE
@@ -189,6 +197,21 @@ impl crate::Adapter<super::Api> for super::Adapter {
             Tf::Depth24Plus | Tf::Depth24PlusStencil8 => {
                 Tfc::DEPTH_STENCIL_ATTACHMENT | Tfc::SAMPLED_LINEAR
             }
+            Tf::Depth32FloatStencil8 => {
+                if pc.format_depth32_stencil8_filter {
+                    Tfc::DEPTH_STENCIL_ATTACHMENT | Tfc::SAMPLED_LINEAR
+                } else {
+                    Tfc::DEPTH_STENCIL_ATTACHMENT
+                }
+            }
+            Tf::Depth16Unorm => {
+                if pc.format_depth16unorm {
+                    Tfc::DEPTH_STENCIL_ATTACHMENT | Tfc::SAMPLED_LINEAR
+                } else {
+                    Tfc::DEPTH_STENCIL_ATTACHMENT
+                }
+            }
+            Tf::Stencil8 => Tfc::DEPTH_STENCIL_ATTACHMENT,
             Tf::Rgb9e5Ufloat => Tfc::SAMPLED_LINEAR,
             Tf::Bc1RgbaUnorm
             | Tf::Bc1RgbaUnormSrgb
@@ -865,7 +888,10 @@ impl super::PrivateCapabilities {
             | F::VERTEX_WRITABLE_STORAGE
             | F::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
             | F::POLYGON_MODE_LINE
-            | F::CLEAR_COMMANDS;
+            | F::CLEAR_COMMANDS
+            | F::DEPTH32FLOAT_STENCIL8
+            | F::TEXTURE_FORMAT_16BIT_NORM
+            | F::TEXTURE_FORMAT_STENCIL8;
 
         features.set(F::DEPTH_CLAMPING, self.supports_depth_clamping);
 
@@ -952,6 +978,8 @@ impl super::PrivateCapabilities {
             Tf::R8Sint => R8Sint,
             Tf::R16Uint => R16Uint,
             Tf::R16Sint => R16Sint,
+            Tf::R16Unorm => R16Unorm,
+            Tf::R16Snorm => R16Snorm,
             Tf::R16Float => R16Float,
             Tf::Rg8Unorm => RG8Unorm,
             Tf::Rg8Snorm => RG8Snorm,
@@ -962,6 +990,8 @@ impl super::PrivateCapabilities {
             Tf::R32Float => R32Float,
             Tf::Rg16Uint => RG16Uint,
             Tf::Rg16Sint => RG16Sint,
+            Tf::Rg16Unorm => RG16Unorm,
+            Tf::Rg16Snorm => RG16Snorm,
             Tf::Rg16Float => RG16Float,
             Tf::Rgba8Unorm => RGBA8Unorm,
             Tf::Rgba8UnormSrgb => RGBA8Unorm_sRGB,
@@ -977,6 +1007,8 @@ impl super::PrivateCapabilities {
             Tf::Rg32Float => RG32Float,
             Tf::Rgba16Uint => RGBA16Uint,
             Tf::Rgba16Sint => RGBA16Sint,
+            Tf::Rgba16Unorm => RGBA16Unorm,
+            Tf::Rgba16Snorm => RGBA16Snorm,
             Tf::Rgba16Float => RGBA16Float,
             Tf::Rgba32Uint => RGBA32Uint,
             Tf::Rgba32Sint => RGBA32Sint,
@@ -996,6 +1028,9 @@ impl super::PrivateCapabilities {
                     Depth32Float_Stencil8
                 }
             }
+            Tf::Depth32FloatStencil8 => Depth32Float_Stencil8,
+            Tf::Depth16Unorm => Depth16Unorm,
+            Tf::Stencil8 => Stencil8,
             Tf::Rgb9e5Ufloat => RGB9E5Float,
             Tf::Bc1RgbaUnorm => BC1_RGBA,
             Tf::Bc1RgbaUnormSrgb => BC1_RGBA_sRGB,
@@ -389,7 +389,9 @@ impl crate::CommandEncoder<super::Api> for super::CommandEncoder {
         self.state.index = None;
 
         let descriptor = mtl::RenderPassDescriptor::new();
-        //TODO: set visibility results buffer
+        if let Some(query_set) = desc.occlusion_query_set {
+            descriptor.set_visibility_result_buffer(Some(&query_set.raw_buffer));
+        }
 
         for (i, at) in desc.color_attachments.iter().enumerate() {
             let at_descriptor = descriptor.color_attachments().object_at(i as u64).unwrap();
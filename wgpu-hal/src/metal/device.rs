@@ -274,7 +274,11 @@ impl crate::Device<super::Api> for super::Device {
         descriptor.set_height(desc.size.height as u64);
         descriptor.set_mipmap_level_count(desc.mip_level_count as u64);
         descriptor.set_pixel_format(mtl_format);
-        descriptor.set_usage(conv::map_texture_usage(desc.usage));
+        let mut mtl_usage = conv::map_texture_usage(desc.usage);
+        if !desc.view_formats.is_empty() {
+            mtl_usage |= mtl::MTLTextureUsage::PixelFormatView;
+        }
+        descriptor.set_usage(mtl_usage);
         descriptor.set_storage_mode(mtl::MTLStorageMode::Private);
 
         let raw = self.shared.device.lock().new_texture(&descriptor);
@@ -951,6 +955,20 @@ impl crate::Device<super::Api> for super::Device {
     }
     unsafe fn destroy_compute_pipeline(&self, _pipeline: super::ComputePipeline) {}
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<(), crate::DeviceError> {
+        Ok(())
+    }
+    unsafe fn destroy_pipeline_cache(&self, _cache: ()) {}
+    unsafe fn pipeline_cache_get_data(&self, _cache: &()) -> Option<Vec<u8>> {
+        None
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        None
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1045,4 +1063,7 @@ impl crate::Device<super::Api> for super::Device {
         }
         shared_capture_manager.stop_capture();
     }
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        None
+    }
 }
@@ -216,7 +216,9 @@ impl crate::Surface<super::Api> for super::Surface {
         self.extent = config.extent;
 
         let render_layer = self.render_layer.lock();
-        let framebuffer_only = config.usage == crate::TextureUses::COLOR_TARGET;
+        // Views of the drawables in other formats need them to be regular textures.
+        let framebuffer_only =
+            config.usage == crate::TextureUses::COLOR_TARGET && config.view_formats.is_empty();
         let display_sync = config.present_mode != wgt::PresentMode::Immediate;
         let drawable_size = CGSize::new(config.extent.width as f64, config.extent.height as f64);
 
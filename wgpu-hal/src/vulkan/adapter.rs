@@ -241,7 +241,8 @@ impl PhysicalDeviceFeatures {
             | F::TIMESTAMP_QUERY
             | F::PIPELINE_STATISTICS_QUERY
             | F::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES
-            | F::CLEAR_COMMANDS;
+            | F::CLEAR_COMMANDS
+            | F::PIPELINE_CACHE;
         let mut dl_flags = Df::all();
 
         dl_flags.set(Df::CUBE_ARRAY_TEXTURES, self.core.image_cube_array != 0);
@@ -426,6 +427,12 @@ impl PhysicalDeviceCapabilities {
             .any(|ep| unsafe { CStr::from_ptr(ep.extension_name.as_ptr()) } == extension)
     }
 
+    fn supports_swapchain_mutable_format(&self) -> bool {
+        self.supports_extension(vk::KhrSwapchainMutableFormatFn::name())
+            && (self.properties.api_version >= vk::API_VERSION_1_2
+                || self.supports_extension(vk::KhrImageFormatListFn::name()))
+    }
+
     /// Map `requested_features` to the list of Vulkan extension strings required to create the logical device.
     fn get_required_extensions(&self, requested_features: wgt::Features) -> Vec<&'static CStr> {
         let mut extensions = Vec::new();
@@ -471,6 +478,18 @@ impl PhysicalDeviceCapabilities {
             extensions.push(vk::ExtConservativeRasterizationFn::name());
         }
 
+        // Lists the formats of the views of mutable format images.
+        if self.properties.api_version < vk::API_VERSION_1_2
+            && self.supports_extension(vk::KhrImageFormatListFn::name())
+            && !extensions.contains(&vk::KhrImageFormatListFn::name())
+        {
+            extensions.push(vk::KhrImageFormatListFn::name());
+        }
+
+        if self.supports_swapchain_mutable_format() {
+            extensions.push(vk::KhrSwapchainMutableFormatFn::name());
+        }
+
         extensions
     }
 
@@ -626,7 +645,52 @@ impl super::Instance {
             backend: wgt::Backend::Vulkan,
         };
 
-        let (available_features, downlevel_flags) = phd_features.to_wgpu(&phd_capabilities);
+        let (mut available_features, downlevel_flags) = phd_features.to_wgpu(&phd_capabilities);
+        let supports_format = |format, features| unsafe {
+            self.shared
+                .raw
+                .get_physical_device_format_properties(phd, format)
+                .optimal_tiling_features
+                .contains(features)
+        };
+        available_features.set(
+            wgt::Features::DEPTH32FLOAT_STENCIL8,
+            supports_format(
+                vk::Format::D32_SFLOAT_S8_UINT,
+                vk::FormatFeatureFlags::SAMPLED_IMAGE
+                    | vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+            ),
+        );
+        available_features.set(
+            wgt::Features::TEXTURE_FORMAT_16BIT_NORM,
+            [
+                vk::Format::R16_UNORM,
+                vk::Format::R16G16_UNORM,
+                vk::Format::R16G16B16A16_UNORM,
+            ]
+            .iter()
+            .all(|&format| {
+                supports_format(
+                    format,
+                    vk::FormatFeatureFlags::SAMPLED_IMAGE
+                        | vk::FormatFeatureFlags::COLOR_ATTACHMENT,
+                )
+            }) && [
+                vk::Format::R16_SNORM,
+                vk::Format::R16G16_SNORM,
+                vk::Format::R16G16B16A16_SNORM,
+            ]
+            .iter()
+            .all(|&format| supports_format(format, vk::FormatFeatureFlags::SAMPLED_IMAGE)),
+        );
+        available_features.set(
+            wgt::Features::TEXTURE_FORMAT_STENCIL8,
+            supports_format(
+                vk::Format::S8_UINT,
+                vk::FormatFeatureFlags::SAMPLED_IMAGE
+                    | vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+            ),
+        );
         let mut workarounds = super::Workarounds::empty();
         {
             use crate::auxil::db;
@@ -683,20 +747,17 @@ impl super::Instance {
                 Some(features) => features.timeline_semaphore == vk::TRUE,
                 None => phd_capabilities.supports_extension(vk::KhrTimelineSemaphoreFn::name()),
             },
-            texture_d24: unsafe {
-                self.shared
-                    .raw
-                    .get_physical_device_format_properties(phd, vk::Format::X8_D24_UNORM_PACK32)
-                    .optimal_tiling_features
-                    .contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
-            },
-            texture_d24_s8: unsafe {
-                self.shared
-                    .raw
-                    .get_physical_device_format_properties(phd, vk::Format::D24_UNORM_S8_UINT)
-                    .optimal_tiling_features
-                    .contains(vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT)
-            },
+            texture_d24: supports_format(
+                vk::Format::X8_D24_UNORM_PACK32,
+                vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+            ),
+            texture_d24_s8: supports_format(
+                vk::Format::D24_UNORM_S8_UINT,
+                vk::FormatFeatureFlags::DEPTH_STENCIL_ATTACHMENT,
+            ),
+            image_format_list: phd_capabilities.properties.api_version >= vk::API_VERSION_1_2
+                || phd_capabilities.supports_extension(vk::KhrImageFormatListFn::name()),
+            swapchain_mutable_format: phd_capabilities.supports_swapchain_mutable_format(),
             non_coherent_map_mask: phd_capabilities.properties.limits.non_coherent_atom_size - 1,
             can_present: true,
             //TODO: make configurable
@@ -875,12 +936,23 @@ impl super::Adapter {
                 timeline_semaphore: timeline_semaphore_fn,
             },
             vendor_id: self.phd_capabilities.properties.vendor_id,
+            pipeline_cache_uuid: self.phd_capabilities.properties.pipeline_cache_uuid,
             downlevel_flags: self.downlevel_flags,
             private_caps: self.private_caps.clone(),
             workarounds: self.workarounds,
             timestamp_period: self.phd_capabilities.properties.limits.timestamp_period,
             render_passes: Mutex::new(Default::default()),
             framebuffers: Mutex::new(Default::default()),
+            memory_stats: Mutex::new(super::MemoryStats {
+                blocks: Default::default(),
+                types: memory_types
+                    .iter()
+                    .map(|mem| super::MemoryTypeStats {
+                        flags: mem.property_flags,
+                        ..Default::default()
+                    })
+                    .collect(),
+            }),
         });
         let queue = super::Queue {
             raw: raw_queue,
@@ -12,6 +12,8 @@ impl super::PrivateCapabilities {
             Tf::R8Sint => F::R8_SINT,
             Tf::R16Uint => F::R16_UINT,
             Tf::R16Sint => F::R16_SINT,
+            Tf::R16Unorm => F::R16_UNORM,
+            Tf::R16Snorm => F::R16_SNORM,
             Tf::R16Float => F::R16_SFLOAT,
             Tf::Rg8Unorm => F::R8G8_UNORM,
             Tf::Rg8Snorm => F::R8G8_SNORM,
@@ -22,6 +24,8 @@ impl super::PrivateCapabilities {
             Tf::R32Float => F::R32_SFLOAT,
             Tf::Rg16Uint => F::R16G16_UINT,
             Tf::Rg16Sint => F::R16G16_SINT,
+            Tf::Rg16Unorm => F::R16G16_UNORM,
+            Tf::Rg16Snorm => F::R16G16_SNORM,
             Tf::Rg16Float => F::R16G16_SFLOAT,
             Tf::Rgba8Unorm => F::R8G8B8A8_UNORM,
             Tf::Rgba8UnormSrgb => F::R8G8B8A8_SRGB,
@@ -37,6 +41,8 @@ impl super::PrivateCapabilities {
             Tf::Rg32Float => F::R32G32_SFLOAT,
             Tf::Rgba16Uint => F::R16G16B16A16_UINT,
             Tf::Rgba16Sint => F::R16G16B16A16_SINT,
+            Tf::Rgba16Unorm => F::R16G16B16A16_UNORM,
+            Tf::Rgba16Snorm => F::R16G16B16A16_SNORM,
             Tf::Rgba16Float => F::R16G16B16A16_SFLOAT,
             Tf::Rgba32Uint => F::R32G32B32A32_UINT,
             Tf::Rgba32Sint => F::R32G32B32A32_SINT,
@@ -56,6 +62,9 @@ impl super::PrivateCapabilities {
                     F::D32_SFLOAT_S8_UINT
                 }
             }
+            Tf::Depth32FloatStencil8 => F::D32_SFLOAT_S8_UINT,
+            Tf::Depth16Unorm => F::D16_UNORM,
+            Tf::Stencil8 => F::S8_UINT,
             Tf::Rgb9e5Ufloat => F::E5B9G9R9_UFLOAT_PACK32,
             Tf::Bc1RgbaUnorm => F::BC1_RGBA_UNORM_BLOCK,
             Tf::Bc1RgbaUnormSrgb => F::BC1_RGBA_SRGB_BLOCK,
@@ -268,7 +268,12 @@ impl gpu_alloc::MemoryDevice<vk::DeviceMemory> for super::DeviceShared {
         }
 
         match self.raw.allocate_memory(&info, None) {
-            Ok(memory) => Ok(memory),
+            Ok(memory) => {
+                let mut stats = self.memory_stats.lock();
+                stats.types[memory_type as usize].blocks.add(size);
+                stats.blocks.insert(memory, (memory_type, size));
+                Ok(memory)
+            }
             Err(vk::Result::ERROR_OUT_OF_DEVICE_MEMORY) => {
                 Err(gpu_alloc::OutOfMemory::OutOfDeviceMemory)
             }
@@ -281,6 +286,11 @@ impl gpu_alloc::MemoryDevice<vk::DeviceMemory> for super::DeviceShared {
     }
 
     unsafe fn deallocate_memory(&self, memory: vk::DeviceMemory) {
+        let mut stats = self.memory_stats.lock();
+        if let Some((memory_type, size)) = stats.blocks.remove(&memory) {
+            stats.types[memory_type as usize].blocks.remove(size);
+        }
+        drop(stats);
         self.raw.free_memory(memory, None);
     }
 
@@ -480,8 +490,21 @@ impl super::Device {
             None => vk::SwapchainKHR::null(),
         };
 
-        let info = vk::SwapchainCreateInfoKHR::builder()
-            .flags(vk::SwapchainCreateFlagsKHR::empty())
+        let mut raw_flags = vk::SwapchainCreateFlagsKHR::empty();
+        let mut raw_view_formats = Vec::new();
+        if !config.view_formats.is_empty() {
+            raw_flags |= vk::SwapchainCreateFlagsKHR::MUTABLE_FORMAT;
+            // The list has to include the swapchain format itself.
+            raw_view_formats = std::iter::once(config.format)
+                .chain(config.view_formats.iter().copied())
+                .map(|format| self.shared.private_caps.map_texture_format(format))
+                .collect::<Vec<_>>();
+        }
+        let mut format_list_info =
+            vk::ImageFormatListCreateInfo::builder().view_formats(&raw_view_formats);
+
+        let mut info = vk::SwapchainCreateInfoKHR::builder()
+            .flags(raw_flags)
             .surface(surface.raw)
             .min_image_count(config.swap_chain_size)
             .image_format(self.shared.private_caps.map_texture_format(config.format))
@@ -498,6 +521,9 @@ impl super::Device {
             .present_mode(conv::map_present_mode(config.present_mode))
             .clipped(true)
             .old_swapchain(old_swapchain);
+        if !raw_view_formats.is_empty() {
+            info = info.push_next(&mut format_list_info);
+        }
 
         let result = functor.create_swapchain(&info, None);
 
@@ -670,6 +696,10 @@ impl crate::Device<super::Api> for super::Device {
                 memory_types: req.memory_type_bits & self.valid_ash_memory_types,
             },
         )?;
+        self.shared
+            .memory_stats
+            .lock()
+            .add_allocation(block.memory_type(), block.size());
 
         self.shared
             .raw
@@ -687,9 +717,12 @@ impl crate::Device<super::Api> for super::Device {
     }
     unsafe fn destroy_buffer(&self, buffer: super::Buffer) {
         self.shared.raw.destroy_buffer(buffer.raw, None);
-        self.mem_allocator
+        let block = buffer.block.into_inner();
+        self.shared
+            .memory_stats
             .lock()
-            .dealloc(&*self.shared, buffer.block.into_inner());
+            .remove_allocation(block.memory_type(), block.size());
+        self.mem_allocator.lock().dealloc(&*self.shared, block);
     }
 
     unsafe fn map_buffer(
@@ -746,8 +779,21 @@ impl crate::Device<super::Api> for super::Device {
         if desc.dimension == wgt::TextureDimension::D2 && desc.size.depth_or_array_layers % 6 == 0 {
             raw_flags |= vk::ImageCreateFlags::CUBE_COMPATIBLE;
         }
+        let mut raw_view_formats = Vec::new();
+        if !desc.view_formats.is_empty() {
+            raw_flags |= vk::ImageCreateFlags::MUTABLE_FORMAT;
+            if self.shared.private_caps.image_format_list {
+                // The list has to include the texture format itself.
+                raw_view_formats = std::iter::once(desc.format)
+                    .chain(desc.view_formats.iter().copied())
+                    .map(|format| self.shared.private_caps.map_texture_format(format))
+                    .collect::<Vec<_>>();
+            }
+        }
+        let mut format_list_info =
+            vk::ImageFormatListCreateInfo::builder().view_formats(&raw_view_formats);
 
-        let vk_info = vk::ImageCreateInfo::builder()
+        let mut vk_info = vk::ImageCreateInfo::builder()
             .flags(raw_flags)
             .image_type(conv::map_texture_dimension(desc.dimension))
             .format(self.shared.private_caps.map_texture_format(desc.format))
@@ -763,6 +809,9 @@ impl crate::Device<super::Api> for super::Device {
             .usage(conv::map_texture_usage(desc.usage))
             .sharing_mode(vk::SharingMode::EXCLUSIVE)
             .initial_layout(vk::ImageLayout::UNDEFINED);
+        if !raw_view_formats.is_empty() {
+            vk_info = vk_info.push_next(&mut format_list_info);
+        }
 
         let raw = self.shared.raw.create_image(&vk_info, None)?;
         let req = self.shared.raw.get_image_memory_requirements(raw);
@@ -776,6 +825,10 @@ impl crate::Device<super::Api> for super::Device {
                 memory_types: req.memory_type_bits & self.valid_ash_memory_types,
             },
         )?;
+        self.shared
+            .memory_stats
+            .lock()
+            .add_allocation(block.memory_type(), block.size());
 
         self.shared
             .raw
@@ -802,6 +855,10 @@ impl crate::Device<super::Api> for super::Device {
             self.shared.raw.destroy_image(texture.raw, None);
         }
         if let Some(block) = texture.block {
+            self.shared
+                .memory_stats
+                .lock()
+                .remove_allocation(block.memory_type(), block.size());
             self.mem_allocator.lock().dealloc(&*self.shared, block);
         }
     }
@@ -1396,10 +1453,14 @@ impl crate::Device<super::Api> for super::Device {
                 .build()
         }];
 
+        let pipeline_cache = desc
+            .cache
+            .map_or(vk::PipelineCache::null(), |cache| cache.raw);
+
         let mut raw_vec = self
             .shared
             .raw
-            .create_graphics_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+            .create_graphics_pipelines(pipeline_cache, &vk_infos, None)
             .map_err(|(_, e)| crate::DeviceError::from(e))?;
 
         let raw = raw_vec.pop().unwrap();
@@ -1438,10 +1499,14 @@ impl crate::Device<super::Api> for super::Device {
                 .build()
         }];
 
+        let pipeline_cache = desc
+            .cache
+            .map_or(vk::PipelineCache::null(), |cache| cache.raw);
+
         let mut raw_vec = self
             .shared
             .raw
-            .create_compute_pipelines(vk::PipelineCache::null(), &vk_infos, None)
+            .create_compute_pipelines(pipeline_cache, &vk_infos, None)
             .map_err(|(_, e)| crate::DeviceError::from(e))?;
 
         let raw = raw_vec.pop().unwrap();
@@ -1460,6 +1525,31 @@ impl crate::Device<super::Api> for super::Device {
         self.shared.raw.destroy_pipeline(pipeline.raw, None);
     }
 
+    unsafe fn create_pipeline_cache(
+        &self,
+        desc: &crate::PipelineCacheDescriptor,
+    ) -> Result<super::PipelineCache, crate::DeviceError> {
+        let mut info = vk::PipelineCacheCreateInfo::builder();
+        if let Some(data) = desc.data {
+            info = info.initial_data(data);
+        }
+        let raw = self.shared.raw.create_pipeline_cache(&info, None)?;
+        if let Some(label) = desc.label {
+            self.shared
+                .set_object_name(vk::ObjectType::PIPELINE_CACHE, raw, label);
+        }
+        Ok(super::PipelineCache { raw })
+    }
+    unsafe fn destroy_pipeline_cache(&self, cache: super::PipelineCache) {
+        self.shared.raw.destroy_pipeline_cache(cache.raw, None);
+    }
+    unsafe fn pipeline_cache_get_data(&self, cache: &super::PipelineCache) -> Option<Vec<u8>> {
+        self.shared.raw.get_pipeline_cache_data(cache.raw).ok()
+    }
+    fn pipeline_cache_validation_key(&self) -> Option<[u8; 16]> {
+        Some(self.shared.pipeline_cache_uuid)
+    }
+
     unsafe fn create_query_set(
         &self,
         desc: &wgt::QuerySetDescriptor<crate::Label>,
@@ -1613,6 +1703,24 @@ impl crate::Device<super::Api> for super::Device {
             ptr::null_mut(),
         )
     }
+
+    fn allocator_report(&self) -> Option<wgt::AllocatorReport> {
+        let stats = self.shared.memory_stats.lock();
+        let memory_types = stats
+            .types
+            .iter()
+            .enumerate()
+            .filter(|&(_, ty)| ty.blocks.count != 0)
+            .map(|(index, ty)| wgt::MemoryTypeReport {
+                index: index as u32,
+                device_local: ty.flags.contains(vk::MemoryPropertyFlags::DEVICE_LOCAL),
+                host_visible: ty.flags.contains(vk::MemoryPropertyFlags::HOST_VISIBLE),
+                blocks: ty.blocks,
+                allocations: ty.allocations,
+            })
+            .collect();
+        Some(wgt::AllocatorReport { memory_types })
+    }
 }
 
 impl From<gpu_alloc::AllocationError> for crate::DeviceError {
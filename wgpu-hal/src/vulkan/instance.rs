@@ -638,6 +638,11 @@ impl crate::Surface<super::Api> for super::Surface {
         device: &super::Device,
         config: &crate::SurfaceConfiguration,
     ) -> Result<(), crate::SurfaceError> {
+        if !config.view_formats.is_empty() && !device.shared.private_caps.swapchain_mutable_format {
+            return Err(crate::SurfaceError::Other(
+                "swapchain images can't be viewed in other formats",
+            ));
+        }
         let old = self
             .swapchain
             .take()
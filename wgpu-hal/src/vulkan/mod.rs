@@ -72,6 +72,7 @@ impl crate::Api for Api {
     type ShaderModule = ShaderModule;
     type RenderPipeline = RenderPipeline;
     type ComputePipeline = ComputePipeline;
+    type PipelineCache = PipelineCache;
 }
 
 struct DebugUtils {
@@ -159,6 +160,10 @@ struct PrivateCapabilities {
     timeline_semaphores: bool,
     texture_d24: bool,
     texture_d24_s8: bool,
+    /// The view formats of mutable format images can be listed, with `VK_KHR_image_format_list`/1.2+.
+    image_format_list: bool,
+    /// Swapchain images can be viewed in other formats, with `VK_KHR_swapchain_mutable_format`.
+    swapchain_mutable_format: bool,
     /// Ability to present contents to any screen. Only needed to work around broken platform configurations.
     can_present: bool,
     non_coherent_map_mask: wgt::BufferAddress,
@@ -226,18 +231,45 @@ struct FramebufferKey {
     sample_count: u32,
 }
 
+/// Memory allocated by `gpu-alloc` of a given memory type.
+#[derive(Debug, Default)]
+struct MemoryTypeStats {
+    flags: vk::MemoryPropertyFlags,
+    blocks: wgt::MemoryUsage,
+    allocations: wgt::MemoryUsage,
+}
+
+#[derive(Debug, Default)]
+struct MemoryStats {
+    /// Memory objects allocated by `gpu-alloc`, with their type and size.
+    blocks: fxhash::FxHashMap<vk::DeviceMemory, (u32, u64)>,
+    types: Vec<MemoryTypeStats>,
+}
+
+impl MemoryStats {
+    fn add_allocation(&mut self, memory_type: u32, size: u64) {
+        self.types[memory_type as usize].allocations.add(size);
+    }
+
+    fn remove_allocation(&mut self, memory_type: u32, size: u64) {
+        self.types[memory_type as usize].allocations.remove(size);
+    }
+}
+
 struct DeviceShared {
     raw: ash::Device,
     handle_is_owned: bool,
     instance: Arc<InstanceShared>,
     extension_fns: DeviceExtensionFunctions,
     vendor_id: u32,
+    pipeline_cache_uuid: [u8; vk::UUID_SIZE],
     timestamp_period: f32,
     downlevel_flags: wgt::DownlevelFlags,
     private_caps: PrivateCapabilities,
     workarounds: Workarounds,
     render_passes: Mutex<fxhash::FxHashMap<RenderPassKey, vk::RenderPass>>,
     framebuffers: Mutex<fxhash::FxHashMap<FramebufferKey, vk::Framebuffer>>,
+    memory_stats: Mutex<MemoryStats>,
 }
 
 pub struct Device {
@@ -384,6 +416,11 @@ pub struct ComputePipeline {
     raw: vk::Pipeline,
 }
 
+#[derive(Debug)]
+pub struct PipelineCache {
+    raw: vk::PipelineCache,
+}
+
 #[derive(Debug)]
 pub struct QuerySet {
     raw: vk::QueryPool,
@@ -583,3 +620,22 @@ impl From<vk::Result> for crate::DeviceError {
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn memory_stats_saturate() {
+        let mut stats = MemoryStats {
+            blocks: Default::default(),
+            types: vec![MemoryTypeStats::default()],
+        };
+        stats.add_allocation(0, 256);
+        stats.remove_allocation(0, 256);
+        assert_eq!(stats.types[0].allocations, wgt::MemoryUsage::default());
+        // An allocation that wasn't accounted for doesn't wrap the usage around.
+        stats.remove_allocation(0, 256);
+        assert_eq!(stats.types[0].allocations, wgt::MemoryUsage::default());
+    }
+}
@@ -0,0 +1,253 @@
+//! Comparison of two capability reports.
+
+use crate::report::{AdapterReport, Report};
+use std::{collections::BTreeMap, fmt::Display};
+
+/// Differences between the capabilities of two adapters.
+#[derive(Default)]
+struct AdapterDiff {
+    lines: Vec<String>,
+}
+
+impl AdapterDiff {
+    fn value<T: Display + PartialEq>(&mut self, path: &str, old: T, new: T) {
+        if old != new {
+            self.lines.push(format!("{}: {} -> {}", path, old, new));
+        }
+    }
+
+    fn names(&mut self, path: &str, old: &[String], new: &[String]) {
+        let removed = old.iter().filter(|name| !new.contains(name));
+        let added = new.iter().filter(|name| !old.contains(name));
+        let changes = removed
+            .map(|name| format!("-{}", name))
+            .chain(added.map(|name| format!("+{}", name)))
+            .collect::<Vec<_>>();
+        if !changes.is_empty() {
+            self.lines.push(format!("{}: {}", path, changes.join(" ")));
+        }
+    }
+
+    fn map<T>(
+        &mut self,
+        path: &str,
+        old: &BTreeMap<String, T>,
+        new: &BTreeMap<String, T>,
+        mut compare: impl FnMut(&mut Self, &str, &T, &T),
+    ) {
+        for (key, old_value) in old {
+            let key_path = format!("{}.{}", path, key);
+            match new.get(key) {
+                Some(new_value) => compare(self, &key_path, old_value, new_value),
+                None => self.lines.push(format!("{}: removed", key_path)),
+            }
+        }
+        for key in new.keys().filter(|key| !old.contains_key(*key)) {
+            self.lines.push(format!("{}.{}: added", path, key));
+        }
+    }
+
+    fn compare(old: &AdapterReport, new: &AdapterReport) -> Self {
+        let mut diff = AdapterDiff::default();
+        diff.value("info.name", &old.info.name, &new.info.name);
+        diff.value("info.vendor", old.info.vendor, new.info.vendor);
+        diff.value("info.device", old.info.device, new.info.device);
+        diff.value(
+            "info.device_type",
+            &old.info.device_type,
+            &new.info.device_type,
+        );
+        diff.value("info.backend", &old.info.backend, &new.info.backend);
+        diff.names("features", &old.features, &new.features);
+        diff.map(
+            "limits",
+            &old.limits,
+            &new.limits,
+            |diff, path, old, new| diff.value(path, old, new),
+        );
+        diff.value(
+            "downlevel.compliant",
+            old.downlevel.compliant,
+            new.downlevel.compliant,
+        );
+        diff.value(
+            "downlevel.shader_model",
+            &old.downlevel.shader_model,
+            &new.downlevel.shader_model,
+        );
+        diff.names(
+            "downlevel.flags",
+            &old.downlevel.flags,
+            &new.downlevel.flags,
+        );
+        diff.map(
+            "texture_formats",
+            &old.texture_formats,
+            &new.texture_formats,
+            |diff, path, old, new| {
+                diff.names(
+                    &format!("{}.allowed_usages", path),
+                    &old.allowed_usages,
+                    &new.allowed_usages,
+                );
+                diff.names(&format!("{}.flags", path), &old.flags, &new.flags);
+            },
+        );
+        diff
+    }
+}
+
+fn describe(adapter: &AdapterReport) -> String {
+    format!("{:?} on {}", adapter.info.name, adapter.info.backend)
+}
+
+/// Appends the differences between two adapters to `lines`.
+fn adapter_diff(lines: &mut Vec<String>, title: &str, old: &AdapterReport, new: &AdapterReport) {
+    let diff = AdapterDiff::compare(old, new);
+    if diff.lines.is_empty() {
+        return;
+    }
+    lines.push(format!("Adapter {}:", title));
+    lines.extend(diff.lines.into_iter().map(|line| format!("\t{}", line)));
+}
+
+/// Lists the differences between the reports, one output line per entry.
+///
+/// Adapters are matched by name and backend. If both reports have a single
+/// adapter, these are compared to each other regardless, which allows
+/// checking a machine against a reference report.
+pub fn diff(old: &Report, new: &Report) -> Vec<String> {
+    let mut lines = Vec::new();
+    if let ([old_adapter], [new_adapter]) = (&old.adapters[..], &new.adapters[..]) {
+        let title = format!("{} -> {}", describe(old_adapter), describe(new_adapter));
+        adapter_diff(&mut lines, &title, old_adapter, new_adapter);
+        return lines;
+    }
+
+    let is_same_adapter = |a: &AdapterReport, b: &AdapterReport| {
+        a.info.name == b.info.name && a.info.backend == b.info.backend
+    };
+    for old_adapter in old.adapters.iter() {
+        match new
+            .adapters
+            .iter()
+            .find(|a| is_same_adapter(a, old_adapter))
+        {
+            Some(new_adapter) => {
+                adapter_diff(&mut lines, &describe(old_adapter), old_adapter, new_adapter);
+            }
+            None => lines.push(format!("Adapter {}: removed", describe(old_adapter))),
+        }
+    }
+    for new_adapter in new.adapters.iter() {
+        if !old.adapters.iter().any(|a| is_same_adapter(a, new_adapter)) {
+            lines.push(format!("Adapter {}: added", describe(new_adapter)));
+        }
+    }
+    lines
+}
+
+/// Prints the differences between the reports, returning `true` if there are any.
+pub fn print_diff(old: &Report, new: &Report) -> bool {
+    let lines = diff(old, new);
+    for line in lines.iter() {
+        println!("{}", line);
+    }
+    !lines.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::diff;
+    use crate::report::{
+        AdapterInfoReport, AdapterReport, DownlevelReport, Report, TextureFormatReport,
+    };
+    use std::collections::BTreeMap;
+
+    fn adapter(name: &str, backend: &str) -> AdapterReport {
+        let mut limits = BTreeMap::new();
+        limits.insert("max_bind_groups".to_string(), 4);
+        let mut texture_formats = BTreeMap::new();
+        texture_formats.insert(
+            "Rgba8Unorm".to_string(),
+            TextureFormatReport {
+                allowed_usages: vec!["COPY_SRC".to_string(), "COPY_DST".to_string()],
+                flags: Vec::new(),
+            },
+        );
+        AdapterReport {
+            info: AdapterInfoReport {
+                name: name.to_string(),
+                vendor: 0,
+                device: 0,
+                device_type: "DiscreteGpu".to_string(),
+                backend: backend.to_string(),
+            },
+            features: vec!["DEPTH_CLAMPING".to_string()],
+            limits,
+            downlevel: DownlevelReport {
+                compliant: true,
+                shader_model: "Sm5".to_string(),
+                flags: vec!["COMPUTE_SHADERS".to_string()],
+            },
+            texture_formats,
+        }
+    }
+
+    #[test]
+    fn identical_reports() {
+        let report = Report {
+            adapters: vec![adapter("A", "Vulkan"), adapter("B", "Gl")],
+        };
+        assert!(diff(&report, &report).is_empty());
+    }
+
+    #[test]
+    fn changed_capabilities() {
+        let old = Report {
+            adapters: vec![adapter("A", "Vulkan")],
+        };
+        let mut new_adapter = adapter("A", "Vulkan");
+        new_adapter.features = vec!["TEXTURE_COMPRESSION_BC".to_string()];
+        new_adapter.limits.insert("max_bind_groups".to_string(), 8);
+        new_adapter.downlevel.compliant = false;
+        new_adapter
+            .texture_formats
+            .get_mut("Rgba8Unorm")
+            .unwrap()
+            .allowed_usages
+            .pop();
+        let new = Report {
+            adapters: vec![new_adapter],
+        };
+
+        assert_eq!(
+            diff(&old, &new),
+            [
+                "Adapter \"A\" on Vulkan -> \"A\" on Vulkan:",
+                "\tfeatures: -DEPTH_CLAMPING +TEXTURE_COMPRESSION_BC",
+                "\tlimits.max_bind_groups: 4 -> 8",
+                "\tdownlevel.compliant: true -> false",
+                "\ttexture_formats.Rgba8Unorm.allowed_usages: -COPY_DST",
+            ]
+        );
+    }
+
+    #[test]
+    fn matched_adapters() {
+        let old = Report {
+            adapters: vec![adapter("A", "Vulkan"), adapter("B", "Gl")],
+        };
+        let new = Report {
+            adapters: vec![adapter("A", "Vulkan"), adapter("C", "Dx12")],
+        };
+
+        assert_eq!(
+            diff(&old, &new),
+            [
+                "Adapter \"B\" on Gl: removed",
+                "Adapter \"C\" on Dx12: added",
+            ]
+        );
+    }
+}
@@ -1,4 +1,8 @@
+mod diff;
+mod report;
+
 use std::{
+    fs,
     mem::size_of,
     process::{exit, Command},
     time::Instant,
@@ -88,71 +92,222 @@ fn print_info_from_adapter(adapter: &wgpu::Adapter, idx: usize) {
     }
 }
 
+fn read_report(path: &str) -> report::Report {
+    let file = fs::File::open(path).unwrap_or_else(|e| {
+        eprintln!("Unable to open '{}': {}", path, e);
+        exit(1)
+    });
+    serde_json::from_reader(file).unwrap_or_else(|e| {
+        eprintln!("Unable to parse '{}': {}", path, e);
+        exit(1)
+    })
+}
+
+/// What wgpu-info was asked to do.
+#[derive(Debug, PartialEq)]
+enum Subcommand {
+    /// List the adapters in a human readable form.
+    List,
+    /// Print the adapter report as JSON.
+    Json,
+    /// Compare two JSON reports.
+    Diff { old: String, new: String },
+    /// Run a program once for every adapter.
+    Run { program: String, args: Vec<String> },
+}
+
+const USAGE: &str = "\
+Usage:
+    wgpu-info                          list the adapters
+    wgpu-info --json                   print the adapters as JSON
+    wgpu-info diff <old.json> <new.json>
+                                       compare two JSON reports
+    wgpu-info [--] <program> [args..]  run a program for every adapter";
+
+/// Parses the command line, without the program name.
+///
+/// The program to run may be preceded by `--`, which is needed when it is
+/// called `diff` or starts with a dash.
+fn parse_args(mut args: Vec<String>) -> Result<Subcommand, String> {
+    let first = match args.first() {
+        Some(first) => first.as_str(),
+        None => return Ok(Subcommand::List),
+    };
+    match first {
+        "--json" => {
+            if args.len() != 1 {
+                return Err("`--json` takes no arguments".to_string());
+            }
+            Ok(Subcommand::Json)
+        }
+        "diff" => {
+            if args.len() != 3 {
+                return Err("`diff` takes exactly two reports".to_string());
+            }
+            let new = args.pop().unwrap();
+            let old = args.pop().unwrap();
+            Ok(Subcommand::Diff { old, new })
+        }
+        "--" => {
+            if args.len() == 1 {
+                return Err("missing the program to run after `--`".to_string());
+            }
+            args.remove(0);
+            let program = args.remove(0);
+            Ok(Subcommand::Run { program, args })
+        }
+        flag if flag.starts_with('-') => Err(format!("unknown option `{}`", flag)),
+        _ => {
+            let program = args.remove(0);
+            Ok(Subcommand::Run { program, args })
+        }
+    }
+}
+
 fn main() {
     env_logger::init();
 
-    let args: Vec<_> = std::env::args().skip(1).collect();
+    let subcommand = parse_args(std::env::args().skip(1).collect()).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, USAGE);
+        exit(1)
+    });
 
+    match subcommand {
+        Subcommand::List => {
+            let instance = wgpu::Instance::new(wgpu::Backends::all());
+            let adapters = instance.enumerate_adapters(wgpu::Backends::all());
+            for (idx, adapter) in adapters.enumerate() {
+                print_info_from_adapter(&adapter, idx)
+            }
+        }
+        Subcommand::Json => {
+            let instance = wgpu::Instance::new(wgpu::Backends::all());
+            let report = report::Report {
+                adapters: instance
+                    .enumerate_adapters(wgpu::Backends::all())
+                    .map(|adapter| report::AdapterReport::from_adapter(&adapter))
+                    .collect(),
+            };
+            println!("{}", serde_json::to_string_pretty(&report).unwrap());
+        }
+        Subcommand::Diff { old, new } => {
+            let old = read_report(&old);
+            let new = read_report(&new);
+            if diff::print_diff(&old, &new) {
+                exit(1);
+            }
+            println!("No differences");
+        }
+        Subcommand::Run { program, args } => run_for_every_adapter(&program, &args),
+    }
+}
+
+fn run_for_every_adapter(program: &str, args: &[String]) {
     let instance = wgpu::Instance::new(wgpu::Backends::all());
     let adapters: Vec<_> = instance.enumerate_adapters(wgpu::Backends::all()).collect();
     let adapter_count = adapters.len();
+    let all_start = Instant::now();
 
-    if args.is_empty() {
-        for (idx, adapter) in adapters.into_iter().enumerate() {
-            print_info_from_adapter(&adapter, idx)
-        }
-    } else {
-        let all_start = Instant::now();
+    for (idx, adapter) in adapters.into_iter().enumerate() {
+        let adapter_start_time = Instant::now();
+        let idx = idx + 1;
+        let info = adapter.get_info();
+        println!(
+            "=========== TESTING {} on {:?} ({} of {}) ===========",
+            info.name, info.backend, idx, adapter_count
+        );
+        let exit_status = Command::new(program)
+            .args(args)
+            .env("WGPU_ADAPTER_NAME", &info.name)
+            .env(
+                "WGPU_BACKEND",
+                match info.backend {
+                    wgpu::Backend::Empty => unreachable!(),
+                    wgpu::Backend::Vulkan => "vulkan",
+                    wgpu::Backend::Metal => "metal",
+                    wgpu::Backend::Dx12 => "dx12",
+                    wgpu::Backend::Dx11 => "dx11",
+                    wgpu::Backend::Gl => "gl",
+                    wgpu::Backend::BrowserWebGpu => "webgpu",
+                    wgpu::Backend::Cpu => "cpu",
+                },
+            )
+            .spawn()
+            .unwrap()
+            .wait()
+            .unwrap();
+
+        let adapter_time = adapter_start_time.elapsed().as_secs_f32();
 
-        for (idx, adapter) in adapters.into_iter().enumerate() {
-            let adapter_start_time = Instant::now();
-            let idx = idx + 1;
-            let info = adapter.get_info();
+        if exit_status.success() {
             println!(
-                "=========== TESTING {} on {:?} ({} of {}) ===========",
-                info.name, info.backend, idx, adapter_count
+                "=========== PASSED! {} on {:?} ({} of {}) in {:.3}s ===========",
+                info.name, info.backend, idx, adapter_count, adapter_time
             );
-            let exit_status = Command::new(&args[0])
-                .args(&args[1..])
-                .env("WGPU_ADAPTER_NAME", &info.name)
-                .env(
-                    "WGPU_BACKEND",
-                    match info.backend {
-                        wgpu::Backend::Empty => unreachable!(),
-                        wgpu::Backend::Vulkan => "vulkan",
-                        wgpu::Backend::Metal => "metal",
-                        wgpu::Backend::Dx12 => "dx12",
-                        wgpu::Backend::Dx11 => "dx11",
-                        wgpu::Backend::Gl => "gl",
-                        wgpu::Backend::BrowserWebGpu => "webgpu",
-                    },
-                )
-                .spawn()
-                .unwrap()
-                .wait()
-                .unwrap();
-
-            let adapter_time = adapter_start_time.elapsed().as_secs_f32();
-
-            if exit_status.success() {
-                println!(
-                    "=========== PASSED! {} on {:?} ({} of {}) in {:.3}s ===========",
-                    info.name, info.backend, idx, adapter_count, adapter_time
-                );
-            } else {
-                println!(
-                    "=========== FAILED! {} on {:?} ({} of {}) in {:.3}s ===========",
-                    info.name, info.backend, idx, adapter_count, adapter_time
-                );
-                exit(1);
-            }
+        } else {
+            println!(
+                "=========== FAILED! {} on {:?} ({} of {}) in {:.3}s ===========",
+                info.name, info.backend, idx, adapter_count, adapter_time
+            );
+            exit(1);
         }
+    }
 
-        let all_time = all_start.elapsed().as_secs_f32();
+    let all_time = all_start.elapsed().as_secs_f32();
 
-        println!(
-            "=========== {} adapters PASSED in {:.3}s ===========",
-            adapter_count, all_time
+    println!(
+        "=========== {} adapters PASSED in {:.3}s ===========",
+        adapter_count, all_time
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_args, Subcommand};
+
+    fn parse(args: &[&str]) -> Result<Subcommand, String> {
+        parse_args(args.iter().map(|arg| arg.to_string()).collect())
+    }
+
+    #[test]
+    fn subcommands() {
+        assert_eq!(parse(&[]), Ok(Subcommand::List));
+        assert_eq!(parse(&["--json"]), Ok(Subcommand::Json));
+        assert_eq!(
+            parse(&["diff", "old.json", "new.json"]),
+            Ok(Subcommand::Diff {
+                old: "old.json".to_string(),
+                new: "new.json".to_string(),
+            })
+        );
+        assert!(parse(&["diff", "old.json"]).is_err());
+        assert!(parse(&["--json", "report.json"]).is_err());
+        assert!(parse(&["--jsn"]).is_err());
+    }
+
+    #[test]
+    fn programs() {
+        assert_eq!(
+            parse(&["cargo", "test", "--json"]),
+            Ok(Subcommand::Run {
+                program: "cargo".to_string(),
+                args: vec!["test".to_string(), "--json".to_string()],
+            })
+        );
+        assert_eq!(
+            parse(&["--", "diff", "a", "b", "c"]),
+            Ok(Subcommand::Run {
+                program: "diff".to_string(),
+                args: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            })
+        );
+        assert_eq!(
+            parse(&["--", "--json"]),
+            Ok(Subcommand::Run {
+                program: "--json".to_string(),
+                args: Vec::new(),
+            })
         );
+        assert!(parse(&["--"]).is_err());
     }
 }
@@ -0,0 +1,283 @@
+//! Machine-readable report of the adapter capabilities.
+
+use serde::{Deserialize, Serialize};
+use std::{collections::BTreeMap, mem::size_of};
+
+/// All the texture formats queried for their features.
+const TEXTURE_FORMATS: &[wgpu::TextureFormat] = {
+    use wgpu::TextureFormat as Tf;
+    &[
+        Tf::R8Unorm,
+        Tf::R8Snorm,
+        Tf::R8Uint,
+        Tf::R8Sint,
+        Tf::R16Uint,
+        Tf::R16Sint,
+        Tf::R16Unorm,
+        Tf::R16Snorm,
+        Tf::R16Float,
+        Tf::Rg8Unorm,
+        Tf::Rg8Snorm,
+        Tf::Rg8Uint,
+        Tf::Rg8Sint,
+        Tf::R32Uint,
+        Tf::R32Sint,
+        Tf::R32Float,
+        Tf::Rg16Uint,
+        Tf::Rg16Sint,
+        Tf::Rg16Unorm,
+        Tf::Rg16Snorm,
+        Tf::Rg16Float,
+        Tf::Rgba8Unorm,
+        Tf::Rgba8UnormSrgb,
+        Tf::Rgba8Snorm,
+        Tf::Rgba8Uint,
+        Tf::Rgba8Sint,
+        Tf::Bgra8Unorm,
+        Tf::Bgra8UnormSrgb,
+        Tf::Rgb10a2Unorm,
+        Tf::Rg11b10Float,
+        Tf::Rg32Uint,
+        Tf::Rg32Sint,
+        Tf::Rg32Float,
+        Tf::Rgba16Uint,
+        Tf::Rgba16Sint,
+        Tf::Rgba16Unorm,
+        Tf::Rgba16Snorm,
+        Tf::Rgba16Float,
+        Tf::Rgba32Uint,
+        Tf::Rgba32Sint,
+        Tf::Rgba32Float,
+        Tf::Depth32Float,
+        Tf::Depth24Plus,
+        Tf::Depth24PlusStencil8,
+        Tf::Depth32FloatStencil8,
+        Tf::Depth16Unorm,
+        Tf::Stencil8,
+        Tf::Rgb9e5Ufloat,
+        Tf::Bc1RgbaUnorm,
+        Tf::Bc1RgbaUnormSrgb,
+        Tf::Bc2RgbaUnorm,
+        Tf::Bc2RgbaUnormSrgb,
+        Tf::Bc3RgbaUnorm,
+        Tf::Bc3RgbaUnormSrgb,
+        Tf::Bc4RUnorm,
+        Tf::Bc4RSnorm,
+        Tf::Bc5RgUnorm,
+        Tf::Bc5RgSnorm,
+        Tf::Bc6hRgbUfloat,
+        Tf::Bc6hRgbSfloat,
+        Tf::Bc7RgbaUnorm,
+        Tf::Bc7RgbaUnormSrgb,
+        Tf::Etc2RgbUnorm,
+        Tf::Etc2RgbUnormSrgb,
+        Tf::Etc2RgbA1Unorm,
+        Tf::Etc2RgbA1UnormSrgb,
+        Tf::EacRUnorm,
+        Tf::EacRSnorm,
+        Tf::EacRgUnorm,
+        Tf::EacRgSnorm,
+        Tf::Astc4x4RgbaUnorm,
+        Tf::Astc4x4RgbaUnormSrgb,
+        Tf::Astc5x4RgbaUnorm,
+        Tf::Astc5x4RgbaUnormSrgb,
+        Tf::Astc5x5RgbaUnorm,
+        Tf::Astc5x5RgbaUnormSrgb,
+        Tf::Astc6x5RgbaUnorm,
+        Tf::Astc6x5RgbaUnormSrgb,
+        Tf::Astc6x6RgbaUnorm,
+        Tf::Astc6x6RgbaUnormSrgb,
+        Tf::Astc8x5RgbaUnorm,
+        Tf::Astc8x5RgbaUnormSrgb,
+        Tf::Astc8x6RgbaUnorm,
+        Tf::Astc8x6RgbaUnormSrgb,
+        Tf::Astc10x5RgbaUnorm,
+        Tf::Astc10x5RgbaUnormSrgb,
+        Tf::Astc10x6RgbaUnorm,
+        Tf::Astc10x6RgbaUnormSrgb,
+        Tf::Astc8x8RgbaUnorm,
+        Tf::Astc8x8RgbaUnormSrgb,
+        Tf::Astc10x8RgbaUnorm,
+        Tf::Astc10x8RgbaUnormSrgb,
+        Tf::Astc10x10RgbaUnorm,
+        Tf::Astc10x10RgbaUnormSrgb,
+        Tf::Astc12x10RgbaUnorm,
+        Tf::Astc12x10RgbaUnormSrgb,
+        Tf::Astc12x12RgbaUnorm,
+        Tf::Astc12x12RgbaUnormSrgb,
+    ]
+};
+
+/// Lists the names of the flags set in a bitflags value.
+///
+/// Names are used instead of the bits, so that the reports stay comparable
+/// when the bits get reassigned between versions.
+macro_rules! flag_names {
+    ($ty:ty, $value:expr) => {{
+        let value = $value;
+        (0..(size_of::<$ty>() * 8))
+            .filter_map(|i| <$ty>::from_bits(1 << i))
+            .filter(|&bit| value.contains(bit))
+            .map(|bit| format!("{:?}", bit))
+            .collect::<Vec<_>>()
+    }};
+}
+
+/// Maps the names of the limits to their values.
+macro_rules! limit_map {
+    ($limits:expr, [$($name:ident),* $(,)?]) => {{
+        let wgpu::Limits { $($name),* } = $limits;
+        let mut map = BTreeMap::new();
+        $(
+            map.insert(stringify!($name).to_string(), $name);
+        )*
+        map
+    }};
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Report {
+    pub adapters: Vec<AdapterReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdapterReport {
+    pub info: AdapterInfoReport,
+    pub features: Vec<String>,
+    pub limits: BTreeMap<String, u32>,
+    pub downlevel: DownlevelReport,
+    pub texture_formats: BTreeMap<String, TextureFormatReport>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AdapterInfoReport {
+    pub name: String,
+    pub vendor: usize,
+    pub device: usize,
+    pub device_type: String,
+    pub backend: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DownlevelReport {
+    pub compliant: bool,
+    pub shader_model: String,
+    pub flags: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+pub struct TextureFormatReport {
+    pub allowed_usages: Vec<String>,
+    pub flags: Vec<String>,
+}
+
+impl AdapterReport {
+    pub fn from_adapter(adapter: &wgpu::Adapter) -> Self {
+        let info = adapter.get_info();
+        let downlevel = adapter.get_downlevel_properties();
+
+        let limits = limit_map!(
+            adapter.limits(),
+            [
+                max_texture_dimension_1d,
+                max_texture_dimension_2d,
+                max_texture_dimension_3d,
+                max_texture_array_layers,
+                max_bind_groups,
+                max_dynamic_uniform_buffers_per_pipeline_layout,
+                max_dynamic_storage_buffers_per_pipeline_layout,
+                max_sampled_textures_per_shader_stage,
+                max_samplers_per_shader_stage,
+                max_storage_buffers_per_shader_stage,
+                max_storage_textures_per_shader_stage,
+                max_uniform_buffers_per_shader_stage,
+                max_uniform_buffer_binding_size,
+                max_storage_buffer_binding_size,
+                max_vertex_buffers,
+                max_vertex_attributes,
+                max_vertex_buffer_array_stride,
+                max_push_constant_size,
+                min_uniform_buffer_offset_alignment,
+                min_storage_buffer_offset_alignment,
+            ]
+        );
+
+        let texture_formats = TEXTURE_FORMATS
+            .iter()
+            .map(|&format| {
+                let features = adapter.get_texture_format_features(format);
+                let report = TextureFormatReport {
+                    allowed_usages: flag_names!(wgpu::TextureUsages, features.allowed_usages),
+                    flags: flag_names!(wgpu::TextureFormatFeatureFlags, features.flags),
+                };
+                (format!("{:?}", format), report)
+            })
+            .collect();
+
+        AdapterReport {
+            info: AdapterInfoReport {
+                name: info.name,
+                vendor: info.vendor,
+                device: info.device,
+                device_type: format!("{:?}", info.device_type),
+                backend: format!("{:?}", info.backend),
+            },
+            features: flag_names!(wgpu::Features, adapter.features()),
+            limits,
+            downlevel: DownlevelReport {
+                compliant: downlevel.is_webgpu_compliant(),
+                shader_model: format!("{:?}", downlevel.shader_model),
+                flags: flag_names!(wgpu::DownlevelFlags, downlevel.flags),
+            },
+            texture_formats,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Report;
+
+    #[test]
+    fn flag_names() {
+        let features = wgpu::Features::DEPTH_CLAMPING | wgpu::Features::TIMESTAMP_QUERY;
+        assert_eq!(
+            flag_names!(wgpu::Features, features),
+            ["DEPTH_CLAMPING", "TIMESTAMP_QUERY"]
+        );
+        assert!(flag_names!(wgpu::Features, wgpu::Features::empty()).is_empty());
+    }
+
+    #[test]
+    fn json_round_trip() {
+        let json = serde_json::json!({
+            "adapters": [{
+                "info": {
+                    "name": "A",
+                    "vendor": 4098,
+                    "device": 29695,
+                    "device_type": "DiscreteGpu",
+                    "backend": "Vulkan",
+                },
+                "features": ["DEPTH_CLAMPING"],
+                "limits": { "max_bind_groups": 4 },
+                "downlevel": {
+                    "compliant": true,
+                    "shader_model": "Sm5",
+                    "flags": ["COMPUTE_SHADERS"],
+                },
+                "texture_formats": {
+                    "Rgba8Unorm": {
+                        "allowed_usages": ["COPY_SRC", "COPY_DST"],
+                        "flags": [],
+                    },
+                },
+            }],
+        });
+
+        let report: Report = serde_json::from_value(json.clone()).unwrap();
+        assert_eq!(report.adapters[0].info.name, "A");
+        assert_eq!(report.adapters[0].limits["max_bind_groups"], 4);
+        assert_eq!(serde_json::to_value(&report).unwrap(), json);
+    }
+}
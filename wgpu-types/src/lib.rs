@@ -67,6 +67,8 @@ pub enum Backend {
     Gl = 5,
     /// WebGPU in the browser
     BrowserWebGpu = 6,
+    /// Software implementation running on the CPU
+    Cpu = 7,
 }
 
 /// Power Preference when choosing a physical adapter.
@@ -104,6 +106,9 @@ bitflags::bitflags! {
         const DX11 = 1 << Backend::Dx11 as u32;
         /// Supported when targeting the web through webassembly
         const BROWSER_WEBGPU = 1 << Backend::BrowserWebGpu as u32;
+        /// Software implementation, supported everywhere with the `cpu` feature enabled.
+        /// Not a part of either `PRIMARY` or `SECONDARY`, but included in `Backends::all()`.
+        const CPU = 1 << Backend::Cpu as u32;
         /// All the apis that wgpu offers first tier of support for.
         ///
         /// Vulkan + Metal + DX12 + Browser WebGPU
@@ -223,6 +228,28 @@ bitflags::bitflags! {
         ///
         /// This is a web and native feature.
         const PIPELINE_STATISTICS_QUERY = 1 << 3;
+        /// Allows for usage of the [`TextureFormat::Depth32FloatStencil8`] depth/stencil format.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (mostly)
+        /// - DX12
+        /// - Metal
+        /// - GLES
+        ///
+        /// This is a web and native feature.
+        const DEPTH32FLOAT_STENCIL8 = 1 << 4;
+        /// Allows for usage of the [`TextureFormat::Stencil8`] stencil-only format.
+        ///
+        /// Only exposed where the backend has a native stencil-only format, so the texture
+        /// is never silently backed by a combined depth/stencil format.
+        ///
+        /// Supported platforms:
+        /// - Vulkan (with `VK_FORMAT_S8_UINT` support)
+        /// - Metal
+        /// - GLES 3.2 or with `OES_texture_stencil8`
+        ///
+        /// This is a web and native feature.
+        const TEXTURE_FORMAT_STENCIL8 = 1 << 5;
         /// Webgpu only allows the MAP_READ and MAP_WRITE buffer usage to be matched with
         /// COPY_DST and COPY_SRC respectively. This removes this requirement.
         ///
@@ -378,7 +405,10 @@ bitflags::bitflags! {
         /// - Vulkan
         /// - Metal
         /// - DX11 (emulated with uniforms)
-        /// - OpenGL (emulated with uniforms)
+        /// - OpenGL (emulated with a uniform buffer, which takes one of the
+        ///   [`Limits::max_uniform_buffers_per_shader_stage`] when the driver has more than
+        ///   the downlevel default, and requires the push constant blocks to be laid out
+        ///   like `std140` uniform blocks)
         ///
         /// This is a native only feature.
         const PUSH_CONSTANTS = 1 << 25;
@@ -523,6 +553,26 @@ bitflags::bitflags! {
         ///
         /// This is a native only feature.
         const SHADER_PRIMITIVE_INDEX = 1 << 38;
+        /// Enables creation of pipeline caches, which can be serialized and used on the
+        /// next run to speed up pipeline creation.
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        ///
+        /// This is a native only feature.
+        const PIPELINE_CACHE = 1 << 39;
+        /// Enables normalized 16-bit texture formats: [`TextureFormat::R16Unorm`],
+        /// [`TextureFormat::R16Snorm`], [`TextureFormat::Rg16Unorm`], [`TextureFormat::Rg16Snorm`],
+        /// [`TextureFormat::Rgba16Unorm`] and [`TextureFormat::Rgba16Snorm`].
+        ///
+        /// Supported platforms:
+        /// - Vulkan
+        /// - DX12
+        /// - Metal
+        /// - GLES with `EXT_texture_norm16`
+        ///
+        /// This is a native only feature.
+        const TEXTURE_FORMAT_16BIT_NORM = 1 << 40;
     }
 }
 
@@ -804,6 +854,13 @@ bitflags::bitflags! {
         /// WebGPU, the implementation is allowed to completely ignore aniso clamp. This flag is
         /// here for native backends so they can comunicate to the user of aniso is enabled.
         const ANISOTROPIC_FILTERING = 1 << 11;
+
+        /// Supports views of a texture in the formats listed in its
+        /// [`TextureDescriptor::view_formats`], or in [`SurfaceConfiguration::view_formats`].
+        ///
+        /// The GL backend supports it with `GL_EXT_texture_view` or `GL_OES_texture_view`,
+        /// except for surfaces.
+        const VIEW_FORMATS = 1 << 12;
     }
 }
 
@@ -896,6 +953,123 @@ impl<L> DeviceDescriptor<L> {
     }
 }
 
+/// Reason why a [`Device`] was lost.
+#[repr(C)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "trace", derive(Serialize))]
+#[cfg_attr(feature = "replay", derive(Deserialize))]
+pub enum DeviceLostReason {
+    /// The device was explicitly destroyed.
+    Destroyed,
+    /// The driver reset or removed the device.
+    DriverReset,
+    /// The device ran out of memory while executing submitted work.
+    OutOfMemory,
+}
+
+/// Number and total size of a set of resources.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct MemoryUsage {
+    /// Number of resources.
+    pub count: usize,
+    /// Total size of the resources, in bytes.
+    pub bytes: u64,
+}
+
+impl MemoryUsage {
+    /// Accounts for one more resource of `bytes` size.
+    pub fn add(&mut self, bytes: u64) {
+        self.count += 1;
+        self.bytes += bytes;
+    }
+
+    /// Accounts for one less resource of `bytes` size.
+    ///
+    /// Saturates at zero, so that removing a resource that wasn't accounted
+    /// for can't wrap the usage around.
+    pub fn remove(&mut self, bytes: u64) {
+        self.count = self.count.saturating_sub(1);
+        self.bytes = self.bytes.saturating_sub(bytes);
+    }
+}
+
+/// Memory used by one kind of resources, with usage flags `U`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceMemoryReport<U: Ord> {
+    /// Memory used by all the resources of this kind.
+    pub total: MemoryUsage,
+    /// Memory used by the resources with each label.
+    ///
+    /// Labels are only retained in debug builds, otherwise all the resources
+    /// are reported under the empty label.
+    pub by_label: std::collections::BTreeMap<String, MemoryUsage>,
+    /// Memory used by the resources with each combination of usage flags.
+    pub by_usage: std::collections::BTreeMap<U, MemoryUsage>,
+}
+
+impl<U: Ord> Default for ResourceMemoryReport<U> {
+    fn default() -> Self {
+        Self {
+            total: MemoryUsage::default(),
+            by_label: Default::default(),
+            by_usage: Default::default(),
+        }
+    }
+}
+
+impl<U: Ord> ResourceMemoryReport<U> {
+    /// Accounts for a resource of `bytes` size.
+    pub fn add(&mut self, label: &str, usage: U, bytes: u64) {
+        self.total.add(bytes);
+        self.by_label
+            .entry(label.to_string())
+            .or_default()
+            .add(bytes);
+        self.by_usage.entry(usage).or_default().add(bytes);
+    }
+}
+
+/// Statistics of the memory allocator for one memory type.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryTypeReport {
+    /// Index of the memory type, as reported by the backend API.
+    pub index: u32,
+    /// Memory of this type is local to the device (VRAM).
+    pub device_local: bool,
+    /// Memory of this type can be mapped on the host.
+    pub host_visible: bool,
+    /// Memory blocks allocated from the driver, which resources are sub-allocated from.
+    pub blocks: MemoryUsage,
+    /// Sub-allocations made by resources in the blocks.
+    pub allocations: MemoryUsage,
+}
+
+/// Statistics of the memory allocator of the backend.
+///
+/// These are estimates, based on the sizes requested from the driver, which
+/// may reserve more memory for alignment and its own bookkeeping.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AllocatorReport {
+    /// Statistics for every memory type in use.
+    pub memory_types: Vec<MemoryTypeReport>,
+}
+
+/// Memory used by the resources of a [`Device`].
+///
+/// All the values are estimates, and are not meant to match the memory usage
+/// reported by the operating system or the driver.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct MemoryReport {
+    /// Memory used by buffers.
+    pub buffers: ResourceMemoryReport<BufferUsages>,
+    /// Memory used by textures, estimated from their size and format.
+    pub textures: ResourceMemoryReport<TextureUsages>,
+    /// Staging memory used to upload the data of `Queue::write_buffer` and `Queue::write_texture`.
+    pub staging: MemoryUsage,
+    /// Statistics of the memory allocator, if the backend provides them.
+    pub allocator: Option<AllocatorReport>,
+}
+
 bitflags::bitflags! {
     /// Describes the shader stages that a binding will be visible from.
     ///
@@ -1390,6 +1564,16 @@ pub enum TextureFormat {
     /// Red channel only. 16 bit integer per channel. Signed in shader.
     #[cfg_attr(feature = "serde", serde(rename = "r16sint"))]
     R16Sint,
+    /// Red channel only. 16 bit integer per channel. [0, 65535] converted to/from float [0, 1] in shader.
+    ///
+    /// [`Features::TEXTURE_FORMAT_16BIT_NORM`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "r16unorm"))]
+    R16Unorm,
+    /// Red channel only. 16 bit integer per channel. [-32767, 32767] converted to/from float [-1, 1] in shader.
+    ///
+    /// [`Features::TEXTURE_FORMAT_16BIT_NORM`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "r16snorm"))]
+    R16Snorm,
     /// Red channel only. 16 bit float per channel. Float in shader.
     #[cfg_attr(feature = "serde", serde(rename = "r16float"))]
     R16Float,
@@ -1422,6 +1606,16 @@ pub enum TextureFormat {
     /// Red and green channels. 16 bit integer per channel. Signed in shader.
     #[cfg_attr(feature = "serde", serde(rename = "rg16sint"))]
     Rg16Sint,
+    /// Red and green channels. 16 bit integer per channel. [0, 65535] converted to/from float [0, 1] in shader.
+    ///
+    /// [`Features::TEXTURE_FORMAT_16BIT_NORM`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "rg16unorm"))]
+    Rg16Unorm,
+    /// Red and green channels. 16 bit integer per channel. [-32767, 32767] converted to/from float [-1, 1] in shader.
+    ///
+    /// [`Features::TEXTURE_FORMAT_16BIT_NORM`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "rg16snorm"))]
+    Rg16Snorm,
     /// Red and green channels. 16 bit float per channel. Float in shader.
     #[cfg_attr(feature = "serde", serde(rename = "rg16float"))]
     Rg16Float,
@@ -1471,6 +1665,16 @@ pub enum TextureFormat {
     /// Red, green, blue, and alpha channels. 16 bit integer per channel. Signed in shader.
     #[cfg_attr(feature = "serde", serde(rename = "rgba16sint"))]
     Rgba16Sint,
+    /// Red, green, blue, and alpha channels. 16 bit integer per channel. [0, 65535] converted to/from float [0, 1] in shader.
+    ///
+    /// [`Features::TEXTURE_FORMAT_16BIT_NORM`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "rgba16unorm"))]
+    Rgba16Unorm,
+    /// Red, green, blue, and alpha channels. 16 bit integer per channel. [-32767, 32767] converted to/from float [-1, 1] in shader.
+    ///
+    /// [`Features::TEXTURE_FORMAT_16BIT_NORM`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "rgba16snorm"))]
+    Rgba16Snorm,
     /// Red, green, blue, and alpha channels. 16 bit float per channel. Float in shader.
     #[cfg_attr(feature = "serde", serde(rename = "rgba16float"))]
     Rgba16Float,
@@ -1496,6 +1700,19 @@ pub enum TextureFormat {
     /// Special depth/stencil format with at least 24 bit integer depth and 8 bits integer stencil.
     #[cfg_attr(feature = "serde", serde(rename = "depth24plus-stencil8"))]
     Depth24PlusStencil8,
+    /// Special depth/stencil format with 32 bit floating point depth and 8 bits integer stencil.
+    ///
+    /// [`Features::DEPTH32FLOAT_STENCIL8`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "depth32float-stencil8"))]
+    Depth32FloatStencil8,
+    /// Special depth format with 16 bit integer depth.
+    #[cfg_attr(feature = "serde", serde(rename = "depth16unorm"))]
+    Depth16Unorm,
+    /// Special stencil format with 8 bits integer stencil.
+    ///
+    /// [`Features::TEXTURE_FORMAT_STENCIL8`] must be enabled to use this texture format.
+    #[cfg_attr(feature = "serde", serde(rename = "stencil8"))]
+    Stencil8,
 
     // Packed uncompressed texture formats
     /// Packed unsigned float with 9 bits mantisa for each RGB component, then a common 5 bits exponent
@@ -1813,6 +2030,9 @@ impl TextureFormat {
         let bc = Features::TEXTURE_COMPRESSION_BC;
         let etc2 = Features::TEXTURE_COMPRESSION_ETC2;
         let astc_ldr = Features::TEXTURE_COMPRESSION_ASTC_LDR;
+        let norm16bit = Features::TEXTURE_FORMAT_16BIT_NORM;
+        let d32_s8 = Features::DEPTH32FLOAT_STENCIL8;
+        let s8 = Features::TEXTURE_FORMAT_STENCIL8;
 
         // Sample Types
         let uint = TextureSampleType::Uint;
@@ -1831,6 +2051,8 @@ impl TextureFormat {
         let attachment = basic | TextureUsages::RENDER_ATTACHMENT;
         let storage = basic | TextureUsages::STORAGE_BINDING;
         let all_flags = TextureUsages::all();
+        // The aspects of a packed depth/stencil format can't be copied separately yet.
+        let packed_attachment = TextureUsages::TEXTURE_BINDING | TextureUsages::RENDER_ATTACHMENT;
 
         // See <https://gpuweb.github.io/gpuweb/#texture-format-caps> for reference
         let (required_features, sample_type, srgb, block_dimensions, block_size, allowed_usages) =
@@ -1844,6 +2066,8 @@ impl TextureFormat {
                 // Normal 16 bit textures
                 Self::R16Uint => (native, uint, linear, (1, 1), 2, attachment),
                 Self::R16Sint => (native, sint, linear, (1, 1), 2, attachment),
+                Self::R16Unorm => (norm16bit, float, linear, (1, 1), 2, attachment),
+                Self::R16Snorm => (norm16bit, float, linear, (1, 1), 2, basic),
                 Self::R16Float => (native, float, linear, (1, 1), 2, attachment),
                 Self::Rg8Unorm => (native, float, linear, (1, 1), 2, attachment),
                 Self::Rg8Snorm => (native, float, linear, (1, 1), 2, attachment),
@@ -1856,6 +2080,8 @@ impl TextureFormat {
                 Self::R32Float => (native, nearest, linear, (1, 1), 4, all_flags),
                 Self::Rg16Uint => (native, uint, linear, (1, 1), 4, attachment),
                 Self::Rg16Sint => (native, sint, linear, (1, 1), 4, attachment),
+                Self::Rg16Unorm => (norm16bit, float, linear, (1, 1), 4, attachment),
+                Self::Rg16Snorm => (norm16bit, float, linear, (1, 1), 4, basic),
                 Self::Rg16Float => (native, float, linear, (1, 1), 4, attachment),
                 Self::Rgba8Unorm => (native, float, linear, (1, 1), 4, all_flags),
                 Self::Rgba8UnormSrgb => (native, float, srgb, (1, 1), 4, attachment),
@@ -1875,6 +2101,8 @@ impl TextureFormat {
                 Self::Rg32Float => (native, nearest, linear, (1, 1), 8, all_flags),
                 Self::Rgba16Uint => (native, uint, linear, (1, 1), 8, all_flags),
                 Self::Rgba16Sint => (native, sint, linear, (1, 1), 8, all_flags),
+                Self::Rgba16Unorm => (norm16bit, float, linear, (1, 1), 8, attachment),
+                Self::Rgba16Snorm => (norm16bit, float, linear, (1, 1), 8, basic),
                 Self::Rgba16Float => (native, float, linear, (1, 1), 8, all_flags),
 
                 // Packed 32 bit textures
@@ -1886,6 +2114,9 @@ impl TextureFormat {
                 Self::Depth32Float => (native, depth, linear, (1, 1), 4, attachment),
                 Self::Depth24Plus => (native, depth, linear, (1, 1), 4, attachment),
                 Self::Depth24PlusStencil8 => (native, depth, linear, (1, 1), 4, attachment),
+                Self::Depth32FloatStencil8 => (d32_s8, depth, linear, (1, 1), 4, packed_attachment),
+                Self::Depth16Unorm => (native, depth, linear, (1, 1), 2, attachment),
+                Self::Stencil8 => (s8, uint, linear, (1, 1), 1, attachment),
 
                 // Packed uncompressed
                 Self::Rgb9e5Ufloat => (native, float, linear, (1, 1), 4, basic),
@@ -1962,6 +2193,47 @@ impl TextureFormat {
             },
         }
     }
+
+    /// Returns the non-sRGB counterpart of the format, or the format itself
+    /// if it isn't an sRGB format.
+    pub fn remove_srgb_suffix(&self) -> TextureFormat {
+        match *self {
+            Self::Rgba8UnormSrgb => Self::Rgba8Unorm,
+            Self::Bgra8UnormSrgb => Self::Bgra8Unorm,
+            Self::Bc1RgbaUnormSrgb => Self::Bc1RgbaUnorm,
+            Self::Bc2RgbaUnormSrgb => Self::Bc2RgbaUnorm,
+            Self::Bc3RgbaUnormSrgb => Self::Bc3RgbaUnorm,
+            Self::Bc7RgbaUnormSrgb => Self::Bc7RgbaUnorm,
+            Self::Etc2RgbUnormSrgb => Self::Etc2RgbUnorm,
+            Self::Etc2RgbA1UnormSrgb => Self::Etc2RgbA1Unorm,
+            Self::Astc4x4RgbaUnormSrgb => Self::Astc4x4RgbaUnorm,
+            Self::Astc5x4RgbaUnormSrgb => Self::Astc5x4RgbaUnorm,
+            Self::Astc5x5RgbaUnormSrgb => Self::Astc5x5RgbaUnorm,
+            Self::Astc6x5RgbaUnormSrgb => Self::Astc6x5RgbaUnorm,
+            Self::Astc6x6RgbaUnormSrgb => Self::Astc6x6RgbaUnorm,
+            Self::Astc8x5RgbaUnormSrgb => Self::Astc8x5RgbaUnorm,
+            Self::Astc8x6RgbaUnormSrgb => Self::Astc8x6RgbaUnorm,
+            Self::Astc10x5RgbaUnormSrgb => Self::Astc10x5RgbaUnorm,
+            Self::Astc10x6RgbaUnormSrgb => Self::Astc10x6RgbaUnorm,
+            Self::Astc8x8RgbaUnormSrgb => Self::Astc8x8RgbaUnorm,
+            Self::Astc10x8RgbaUnormSrgb => Self::Astc10x8RgbaUnorm,
+            Self::Astc10x10RgbaUnormSrgb => Self::Astc10x10RgbaUnorm,
+            Self::Astc12x10RgbaUnormSrgb => Self::Astc12x10RgbaUnorm,
+            Self::Astc12x12RgbaUnormSrgb => Self::Astc12x12RgbaUnorm,
+            other => other,
+        }
+    }
+
+    /// Returns `true` if a texture of this format can be viewed as `view_format`.
+    ///
+    /// The formats must only differ in being sRGB or not, which keeps the
+    /// size and the dimensions of the blocks the same.
+    pub fn is_view_compatible(&self, view_format: TextureFormat) -> bool {
+        let (info, view_info) = (self.describe(), view_format.describe());
+        self.remove_srgb_suffix() == view_format.remove_srgb_suffix()
+            && info.block_size == view_info.block_size
+            && info.block_dimensions == view_info.block_dimensions
+    }
 }
 
 bitflags::bitflags! {
@@ -2512,6 +2784,12 @@ pub struct SurfaceConfiguration {
     /// Presentation mode of the swap chain. FIFO is the only guaranteed to be supported, though
     /// other formats will automatically fall back to FIFO.
     pub present_mode: PresentMode,
+    /// Formats that views of the surface textures may use, in addition to `format` itself.
+    ///
+    /// A view format must be compatible with `format`, see [`TextureFormat::is_view_compatible`].
+    /// Listing formats here requires [`DownlevelFlags::VIEW_FORMATS`].
+    #[cfg_attr(feature = "replay", serde(default))]
+    pub view_formats: Vec<TextureFormat>,
 }
 
 /// Status of the recieved surface image.
@@ -2732,7 +3010,7 @@ impl Extent3d {
 #[derive(Clone, Debug, PartialEq, Eq, Hash)]
 #[cfg_attr(feature = "trace", derive(Serialize))]
 #[cfg_attr(feature = "replay", derive(Deserialize))]
-pub struct TextureDescriptor<L> {
+pub struct TextureDescriptor<L, V> {
     /// Debug label of the texture. This will show up in graphics debuggers for easy identification.
     pub label: L,
     /// Size of the texture. All components must be greater than zero. For a
@@ -2749,19 +3027,37 @@ pub struct TextureDescriptor<L> {
     pub format: TextureFormat,
     /// Allowed usages of the texture. If used in other ways, the operation will panic.
     pub usage: TextureUsages,
+    /// Formats that views of the texture may use, in addition to `format` itself.
+    ///
+    /// A view format must be compatible with `format`, see [`TextureFormat::is_view_compatible`].
+    /// Listing formats here requires [`DownlevelFlags::VIEW_FORMATS`].
+    #[cfg_attr(feature = "replay", serde(default))]
+    pub view_formats: V,
 }
 
-impl<L> TextureDescriptor<L> {
+impl<L, V: Clone> TextureDescriptor<L, V> {
     ///
-    pub fn map_label<K>(&self, fun: impl FnOnce(&L) -> K) -> TextureDescriptor<K> {
+    pub fn map_label<K>(&self, fun: impl FnOnce(&L) -> K) -> TextureDescriptor<K, V> {
+        self.map_label_and_view_formats(fun, |view_formats| view_formats.clone())
+    }
+}
+
+impl<L, V> TextureDescriptor<L, V> {
+    ///
+    pub fn map_label_and_view_formats<K, M>(
+        &self,
+        l_fun: impl FnOnce(&L) -> K,
+        v_fun: impl FnOnce(&V) -> M,
+    ) -> TextureDescriptor<K, M> {
         TextureDescriptor {
-            label: fun(&self.label),
+            label: l_fun(&self.label),
             size: self.size,
             mip_level_count: self.mip_level_count,
             sample_count: self.sample_count,
             dimension: self.dimension,
             format: self.format,
             usage: self.usage,
+            view_formats: v_fun(&self.view_formats),
         }
     }
 
@@ -2782,6 +3078,7 @@ impl<L> TextureDescriptor<L> {
     ///   dimension: wgpu::TextureDimension::D3,
     ///   format: wgpu::TextureFormat::Rgba8Sint,
     ///   usage: wgpu::TextureUsages::empty(),
+    ///   view_formats: &[] as &[wgpu::TextureFormat],
     /// };
     ///
     /// assert_eq!(desc.mip_level_size(0), Some(wgpu::Extent3d { width: 100, height: 60, depth_or_array_layers: 1 }));
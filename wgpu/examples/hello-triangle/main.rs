@@ -62,6 +62,7 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
         primitive: wgpu::PrimitiveState::default(),
         depth_stencil: None,
         multisample: wgpu::MultisampleState::default(),
+        cache: None,
     });
 
     let mut config = wgpu::SurfaceConfiguration {
@@ -70,6 +71,7 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
         width: size.width,
         height: size.height,
         present_mode: wgpu::PresentMode::Mailbox,
+        view_formats: Vec::new(),
     };
 
     surface.configure(&device, &config);
@@ -113,6 +115,7 @@ async fn run(event_loop: EventLoop<()>, window: Window) {
                             },
                         }],
                         depth_stencil_attachment: None,
+                        occlusion_query_set: None,
                     });
                     rpass.set_pipeline(&render_pipeline);
                     rpass.draw(0..3, 0..1);
@@ -35,6 +35,7 @@ impl ViewportDesc {
             width: size.width,
             height: size.height,
             present_mode: wgpu::PresentMode::Fifo,
+            view_formats: Vec::new(),
         };
 
         self.surface.configure(device, &config);
@@ -129,6 +130,7 @@ async fn run(event_loop: EventLoop<()>, viewports: Vec<(Window, wgpu::Color)>) {
                                 },
                             }],
                             depth_stencil_attachment: None,
+                            occlusion_query_set: None,
                         });
                     }
 
@@ -72,6 +72,7 @@ impl Example {
                 count: sample_count,
                 ..Default::default()
             },
+            cache: None,
         });
         let mut encoder =
             device.create_render_bundle_encoder(&wgpu::RenderBundleEncoderDescriptor {
@@ -106,6 +107,7 @@ impl Example {
             format: config.format,
             usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
             label: None,
+            view_formats: &[],
         };
 
         device
@@ -271,6 +273,7 @@ impl framework::Example for Example {
                     label: None,
                     color_attachments: &[rpass_color_attachment],
                     depth_stencil_attachment: None,
+                    occlusion_query_set: None,
                 })
                 .execute_bundles(iter::once(&self.bundle));
         }
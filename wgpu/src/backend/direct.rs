@@ -2,9 +2,10 @@ use crate::{
     backend::native_gpu_future, AdapterInfo, BindGroupDescriptor, BindGroupLayoutDescriptor,
     BindingResource, BufferBinding, CommandEncoderDescriptor, ComputePassDescriptor,
     ComputePipelineDescriptor, DownlevelCapabilities, Features, Label, Limits, LoadOp, MapMode,
-    Operations, PipelineLayoutDescriptor, RenderBundleEncoderDescriptor, RenderPipelineDescriptor,
-    SamplerDescriptor, ShaderModuleDescriptor, ShaderModuleDescriptorSpirV, ShaderSource,
-    SurfaceStatus, TextureDescriptor, TextureFormat, TextureViewDescriptor,
+    Operations, PipelineCacheDescriptor, PipelineLayoutDescriptor, RenderBundleEncoderDescriptor,
+    RenderPipelineDescriptor, SamplerDescriptor, ShaderModuleDescriptor,
+    ShaderModuleDescriptorSpirV, ShaderSource, SurfaceStatus, TextureDescriptor, TextureFormat,
+    TextureViewDescriptor,
 };
 
 use arrayvec::ArrayVec;
@@ -82,11 +83,7 @@ impl Context {
         if let Some(err) = error {
             self.handle_error_fatal(err, "Adapter::create_device_from_hal");
         }
-        let device = Device {
-            id: device_id,
-            error_sink: Arc::new(Mutex::new(ErrorSinkRaw::new())),
-            features: desc.features,
-        };
+        let device = self.create_device(device_id, desc.features);
         Ok((device, device_id))
     }
 
@@ -100,7 +97,7 @@ impl Context {
         let (id, error) = global.create_texture_from_hal::<A>(
             hal_texture,
             device.id,
-            &desc.map_label(|l| l.map(Borrowed)),
+            &desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec()),
             PhantomData,
         );
         if let Some(cause) = error {
@@ -146,38 +143,48 @@ impl Context {
         }
     }
 
-    fn handle_error(
+    fn make_error(
         &self,
-        sink_mutex: &Mutex<ErrorSinkRaw>,
         cause: impl Error + Send + Sync + 'static,
         label_key: &'static str,
         label: Label,
         string: &'static str,
-    ) {
+    ) -> crate::Error {
         let error = wgc::error::ContextError {
             string,
             cause: Box::new(cause),
             label: label.unwrap_or_default().to_string(),
             label_key,
         };
-        let sink = sink_mutex.lock();
         let mut source_opt: Option<&(dyn Error + 'static)> = Some(&error);
         while let Some(source) = source_opt {
             if let Some(wgc::device::DeviceError::OutOfMemory) =
                 source.downcast_ref::<wgc::device::DeviceError>()
             {
-                return sink.handle_error(crate::Error::OutOfMemoryError {
+                return crate::Error::OutOfMemoryError {
                     source: Box::new(error),
-                });
+                };
             }
             source_opt = source.source();
         }
 
         // Otherwise, it is a validation error
-        sink.handle_error(crate::Error::ValidationError {
+        crate::Error::ValidationError {
             description: self.format_error(&error),
             source: Box::new(error),
-        });
+        }
+    }
+
+    fn handle_error(
+        &self,
+        sink_mutex: &Mutex<ErrorSinkRaw>,
+        cause: impl Error + Send + Sync + 'static,
+        label_key: &'static str,
+        label: Label,
+        string: &'static str,
+    ) {
+        let error = self.make_error(cause, label_key, label, string);
+        sink_mutex.lock().handle_error(error);
     }
 
     fn handle_error_nolabel(
@@ -189,6 +196,33 @@ impl Context {
         self.handle_error(sink_mutex, cause, "", None, string)
     }
 
+    /// Wraps a new device, with an error sink that learns when the device is lost.
+    fn create_device(&self, id: wgc::id::DeviceId, features: Features) -> Device {
+        let error_sink = Arc::new(Mutex::new(ErrorSinkRaw::new()));
+        let sink = Arc::clone(&error_sink);
+        let closure =
+            wgc::device::DeviceLostClosure::from_rust(Box::new(move |reason, message| {
+                let callback = {
+                    let mut sink = sink.lock();
+                    sink.lost = Some((reason, message.clone()));
+                    sink.device_lost_callback.take()
+                };
+                if let Some(callback) = callback {
+                    callback(reason, message);
+                }
+            }));
+        let global = &self.0;
+        if let Err(err) = wgc::gfx_select!(id => global.device_set_device_lost_closure(id, closure))
+        {
+            self.handle_error_fatal(err, "Device::set_device_lost_callback");
+        }
+        Device {
+            id,
+            error_sink,
+            features,
+        }
+    }
+
     fn handle_error_fatal(
         &self,
         cause: impl Error + Send + Sync + 'static,
@@ -494,6 +528,14 @@ mod pass_impl {
             wgpu_render_pass_end_pipeline_statistics_query(self)
         }
 
+        fn begin_occlusion_query(&mut self, query_index: u32) {
+            wgpu_render_pass_begin_occlusion_query(self, query_index)
+        }
+
+        fn end_occlusion_query(&mut self) {
+            wgpu_render_pass_end_occlusion_query(self)
+        }
+
         fn execute_bundles<'a, I: Iterator<Item = &'a wgc::id::RenderBundleId>>(
             &mut self,
             render_bundles: I,
@@ -720,6 +762,31 @@ pub(crate) struct CommandEncoder {
     open: bool,
 }
 
+#[cfg(feature = "spirv")]
+type ShaderParseError = naga::front::spv::Error;
+#[cfg(not(feature = "spirv"))]
+type ShaderParseError = std::convert::Infallible;
+
+/// Converts the source of a shader module, parsing SPIR-V into Naga IR.
+fn shader_module_source<'a>(
+    desc: &'a ShaderModuleDescriptor,
+) -> Result<wgc::pipeline::ShaderModuleSource<'a>, ShaderParseError> {
+    Ok(match desc.source {
+        #[cfg(feature = "spirv")]
+        ShaderSource::SpirV(ref spv) => {
+            // Parse the given shader code and store its representation.
+            let options = naga::front::spv::Options {
+                adjust_coordinate_space: false, // we require NDC_Y_UP feature
+                strict_capabilities: true,
+                block_ctx_dump_prefix: None,
+            };
+            let parser = naga::front::spv::Parser::new(spv.iter().cloned(), &options);
+            wgc::pipeline::ShaderModuleSource::Naga(parser.parse()?)
+        }
+        ShaderSource::Wgsl(ref code) => wgc::pipeline::ShaderModuleSource::Wgsl(Borrowed(code)),
+    })
+}
+
 impl crate::Context for Context {
     type AdapterId = wgc::id::AdapterId;
     type DeviceId = Device;
@@ -735,6 +802,7 @@ impl crate::Context for Context {
     type PipelineLayoutId = wgc::id::PipelineLayoutId;
     type RenderPipelineId = wgc::id::RenderPipelineId;
     type ComputePipelineId = wgc::id::ComputePipelineId;
+    type PipelineCacheId = wgc::id::PipelineCacheId;
     type CommandEncoderId = CommandEncoder;
     type ComputePassId = wgc::command::ComputePass;
     type RenderPassId = wgc::command::RenderPass;
@@ -751,6 +819,7 @@ impl crate::Context for Context {
         Ready<Result<(Self::DeviceId, Self::QueueId), crate::RequestDeviceError>>;
     type MapAsyncFuture = native_gpu_future::GpuFuture<Result<(), crate::BufferAsyncError>>;
     type OnSubmittedWorkDoneFuture = native_gpu_future::GpuFuture<()>;
+    type PopErrorScopeFuture = Ready<Option<crate::Error>>;
 
     fn init(backends: wgt::Backends) -> Self {
         Self(wgc::hub::Global::new(
@@ -809,11 +878,7 @@ impl crate::Context for Context {
             log::error!("Error in Adapter::request_device: {}", err);
             return ready(Err(crate::RequestDeviceError));
         }
-        let device = Device {
-            id: device_id,
-            error_sink: Arc::new(Mutex::new(ErrorSinkRaw::new())),
-            features: desc.features,
-        };
+        let device = self.create_device(device_id, desc.features);
         ready(Ok((device, device_id)))
     }
 
@@ -967,6 +1032,14 @@ impl crate::Context for Context {
         }
     }
 
+    fn device_memory_report(&self, device: &Self::DeviceId) -> wgt::MemoryReport {
+        let global = &self.0;
+        match wgc::gfx_select!(device.id => global.device_memory_report(device.id)) {
+            Ok(report) => report,
+            Err(err) => self.handle_error_fatal(err, "Device::memory_report"),
+        }
+    }
+
     fn device_create_shader_module(
         &self,
         device: &Self::DeviceId,
@@ -976,21 +1049,7 @@ impl crate::Context for Context {
         let descriptor = wgc::pipeline::ShaderModuleDescriptor {
             label: desc.label.map(Borrowed),
         };
-        let source = match desc.source {
-            #[cfg(feature = "spirv")]
-            ShaderSource::SpirV(ref spv) => {
-                // Parse the given shader code and store its representation.
-                let options = naga::front::spv::Options {
-                    adjust_coordinate_space: false, // we require NDC_Y_UP feature
-                    strict_capabilities: true,
-                    block_ctx_dump_prefix: None,
-                };
-                let parser = naga::front::spv::Parser::new(spv.iter().cloned(), &options);
-                let module = parser.parse().unwrap();
-                wgc::pipeline::ShaderModuleSource::Naga(module)
-            }
-            ShaderSource::Wgsl(ref code) => wgc::pipeline::ShaderModuleSource::Wgsl(Borrowed(code)),
-        };
+        let source = shader_module_source(desc).unwrap();
         let (id, error) = wgc::gfx_select!(
             device.id => global.device_create_shader_module(device.id, &descriptor, source, PhantomData)
         );
@@ -1006,6 +1065,30 @@ impl crate::Context for Context {
         id
     }
 
+    fn device_try_create_shader_module(
+        &self,
+        device: &Self::DeviceId,
+        desc: &ShaderModuleDescriptor,
+    ) -> Result<Self::ShaderModuleId, crate::Error> {
+        let global = &self.0;
+        let descriptor = wgc::pipeline::ShaderModuleDescriptor {
+            label: desc.label.map(Borrowed),
+        };
+        let source = shader_module_source(desc).map_err(|cause| {
+            self.make_error(cause, LABEL, desc.label, "Device::try_create_shader_module")
+        })?;
+        let (id, error) = wgc::gfx_select!(
+            device.id => global.device_create_shader_module(device.id, &descriptor, source, PhantomData)
+        );
+        match error {
+            Some(cause) => {
+                wgc::gfx_select!(id => global.shader_module_drop(id));
+                Err(self.make_error(cause, LABEL, desc.label, "Device::try_create_shader_module"))
+            }
+            None => Ok(id),
+        }
+    }
+
     unsafe fn device_create_shader_module_spirv(
         &self,
         device: &Self::DeviceId,
@@ -1234,6 +1317,7 @@ impl crate::Context for Context {
                 },
                 targets: Borrowed(frag.targets),
             }),
+            cache: desc.cache.map(|c| c.id),
         };
 
         let global = &self.0;
@@ -1280,6 +1364,7 @@ impl crate::Context for Context {
                 module: desc.module.id,
                 entry_point: Borrowed(desc.entry_point),
             },
+            cache: desc.cache.map(|c| c.id),
         };
 
         let global = &self.0;
@@ -1309,6 +1394,35 @@ impl crate::Context for Context {
         id
     }
 
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        desc: &PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId {
+        let descriptor = wgc::pipeline::PipelineCacheDescriptor {
+            label: desc.label.map(Borrowed),
+            data: desc.data.map(Borrowed),
+            fallback: desc.fallback,
+        };
+
+        let global = &self.0;
+        let (id, error) = wgc::gfx_select!(device.id => global.device_create_pipeline_cache(
+            device.id,
+            &descriptor,
+            PhantomData
+        ));
+        if let Some(cause) = error {
+            self.handle_error(
+                &device.error_sink,
+                cause,
+                LABEL,
+                desc.label,
+                "Device::create_pipeline_cache",
+            );
+        }
+        id
+    }
+
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -1343,7 +1457,7 @@ impl crate::Context for Context {
         let global = &self.0;
         let (id, error) = wgc::gfx_select!(device.id => global.device_create_texture(
             device.id,
-            &desc.map_label(|l| l.map(Borrowed)),
+            &desc.map_label_and_view_formats(|l| l.map(Borrowed), |v| v.to_vec()),
             PhantomData
         ));
         if let Some(cause) = error {
@@ -1503,6 +1617,48 @@ impl crate::Context for Context {
         error_sink.uncaptured_handler = Box::new(handler);
     }
 
+    fn device_set_device_lost_callback(
+        &self,
+        device: &Self::DeviceId,
+        callback: Box<dyn FnOnce(wgt::DeviceLostReason, String) + Send + 'static>,
+    ) {
+        let mut error_sink = device.error_sink.lock();
+        match error_sink.lost.clone() {
+            Some((reason, message)) => {
+                drop(error_sink);
+                callback(reason, message);
+            }
+            None => error_sink.device_lost_callback = Some(callback),
+        }
+    }
+
+    fn device_destroy(&self, device: &Self::DeviceId) {
+        let global = &self.0;
+        if let Err(err) = wgc::gfx_select!(device.id => global.device_destroy(device.id)) {
+            self.handle_error_fatal(err, "Device::destroy");
+        }
+    }
+
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
+        let mut error_sink = device.error_sink.lock();
+        error_sink.scopes.push(ErrorScope {
+            error: None,
+            filter,
+        });
+    }
+
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture {
+        let mut error_sink = device.error_sink.lock();
+        match error_sink.scopes.pop() {
+            Some(scope) => ready(scope.error),
+            // WebGPU rejects the promise in that case, which is reported as an error here.
+            None => ready(Some(crate::Error::ValidationError {
+                description: EmptyErrorScopeStack.to_string(),
+                source: Box::new(EmptyErrorScopeStack),
+            })),
+        }
+    }
+
     fn buffer_map_async(
         &self,
         buffer: &Self::BufferId,
@@ -1511,25 +1667,14 @@ impl crate::Context for Context {
     ) -> Self::MapAsyncFuture {
         let (future, completion) = native_gpu_future::new_gpu_future();
 
-        extern "C" fn buffer_map_future_wrapper(
-            status: wgc::resource::BufferMapAsyncStatus,
-            user_data: *mut u8,
-        ) {
-            let completion =
-                unsafe { native_gpu_future::GpuFutureCompletion::from_raw(user_data as _) };
-            completion.complete(match status {
-                wgc::resource::BufferMapAsyncStatus::Success => Ok(()),
-                _ => Err(crate::BufferAsyncError),
-            })
-        }
-
         let operation = wgc::resource::BufferMapOperation {
             host: match mode {
                 MapMode::Read => wgc::device::HostMap::Read,
                 MapMode::Write => wgc::device::HostMap::Write,
             },
-            callback: buffer_map_future_wrapper,
-            user_data: completion.into_raw() as _,
+            callback: wgc::resource::BufferMapCallback::from_rust(Box::new(move |result| {
+                completion.complete(result.map_err(|_| crate::BufferAsyncError))
+            })),
         };
 
         let global = &self.0;
@@ -1688,6 +1833,14 @@ impl crate::Context for Context {
         let global = &self.0;
         wgc::gfx_select!(*pipeline => global.render_pipeline_drop(*pipeline))
     }
+    fn pipeline_cache_drop(&self, cache: &Self::PipelineCacheId) {
+        let global = &self.0;
+        wgc::gfx_select!(*cache => global.pipeline_cache_drop(*cache))
+    }
+    fn pipeline_cache_get_data(&self, cache: &Self::PipelineCacheId) -> Option<Vec<u8>> {
+        let global = &self.0;
+        wgc::gfx_select!(*cache => global.pipeline_cache_get_data(*cache))
+    }
 
     fn compute_pipeline_get_bind_group_layout(
         &self,
@@ -1914,6 +2067,7 @@ impl crate::Context for Context {
                 label: desc.label.map(Borrowed),
                 color_attachments: Borrowed(&colors),
                 depth_stencil_attachment: depth_stencil.as_ref(),
+                occlusion_query_set: desc.occlusion_query_set.map(|query_set| query_set.id),
             },
         )
     }
@@ -2047,6 +2201,8 @@ impl crate::Context for Context {
             *queue => global.queue_write_buffer(*queue, buffer.id, offset, data)
         ) {
             Ok(()) => (),
+            // Reported by the device lost callback.
+            Err(wgc::device::queue::QueueWriteError::Queue(wgc::device::DeviceError::Lost)) => (),
             Err(err) => self.handle_error_fatal(err, "Queue::write_buffer"),
         }
     }
@@ -2068,6 +2224,8 @@ impl crate::Context for Context {
             &size
         )) {
             Ok(()) => (),
+            // Reported by the device lost callback.
+            Err(wgc::device::queue::QueueWriteError::Queue(wgc::device::DeviceError::Lost)) => (),
             Err(err) => self.handle_error_fatal(err, "Queue::write_texture"),
         }
     }
@@ -2082,6 +2240,8 @@ impl crate::Context for Context {
         let global = &self.0;
         match wgc::gfx_select!(*queue => global.queue_submit(*queue, &temp_command_buffers)) {
             Ok(()) => (),
+            // Reported by the device lost callback.
+            Err(wgc::device::queue::QueueSubmitError::Queue(wgc::device::DeviceError::Lost)) => (),
             Err(err) => self.handle_error_fatal(err, "Queue::submit"),
         }
     }
@@ -2105,16 +2265,10 @@ impl crate::Context for Context {
     ) -> Self::OnSubmittedWorkDoneFuture {
         let (future, completion) = native_gpu_future::new_gpu_future();
 
-        extern "C" fn submitted_work_done_future_wrapper(user_data: *mut u8) {
-            let completion =
-                unsafe { native_gpu_future::GpuFutureCompletion::from_raw(user_data as _) };
-            completion.complete(())
-        }
-
-        let closure = wgc::device::queue::SubmittedWorkDoneClosure {
-            callback: submitted_work_done_future_wrapper,
-            user_data: completion.into_raw() as _,
-        };
+        let closure =
+            wgc::device::queue::SubmittedWorkDoneClosure::from_rust(Box::new(move || {
+                completion.complete(())
+            }));
 
         let global = &self.0;
         let res = wgc::gfx_select!(queue => global.queue_on_submitted_work_done(*queue, closure));
@@ -2142,19 +2296,67 @@ pub(crate) struct SurfaceOutputDetail {
 
 type ErrorSink = Arc<Mutex<ErrorSinkRaw>>;
 
+struct ErrorScope {
+    error: Option<crate::Error>,
+    filter: crate::ErrorFilter,
+}
+
+/// Error of popping an error scope without a matching push.
+#[derive(Clone, Debug)]
+struct EmptyErrorScopeStack;
+
+impl fmt::Display for EmptyErrorScopeStack {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("Device::pop_error_scope called without a matching push")
+    }
+}
+
+impl Error for EmptyErrorScopeStack {}
+
 struct ErrorSinkRaw {
+    scopes: Vec<ErrorScope>,
     uncaptured_handler: Box<dyn crate::UncapturedErrorHandler>,
+    /// Reason and message of the loss of the device, once it is lost.
+    lost: Option<(wgt::DeviceLostReason, String)>,
+    device_lost_callback: Option<Box<dyn FnOnce(wgt::DeviceLostReason, String) + Send + 'static>>,
 }
 
 impl ErrorSinkRaw {
     fn new() -> ErrorSinkRaw {
         ErrorSinkRaw {
+            scopes: Vec::new(),
             uncaptured_handler: Box::from(default_error_handler),
+            lost: None,
+            device_lost_callback: None,
         }
     }
 
-    fn handle_error(&self, err: crate::Error) {
-        (self.uncaptured_handler)(err);
+    fn handle_error(&mut self, err: crate::Error) {
+        // Operations on a lost device fail silently, the loss itself is
+        // reported by the device lost callback.
+        if self.lost.is_some() {
+            log::debug!("Ignoring error of a lost device: {}", err);
+            return;
+        }
+        let filter = match err {
+            crate::Error::OutOfMemoryError { .. } => crate::ErrorFilter::OutOfMemory,
+            crate::Error::ValidationError { .. } => crate::ErrorFilter::Validation,
+        };
+        // The innermost scope with a matching filter captures the error,
+        // but only keeps the first one it sees.
+        match self
+            .scopes
+            .iter_mut()
+            .rev()
+            .find(|scope| scope.filter == filter)
+        {
+            Some(scope) => {
+                if scope.error.is_none() {
+                    scope.error = Some(err);
+                }
+            }
+            None => (self.uncaptured_handler)(err),
+        }
     }
 }
 
@@ -7,7 +7,7 @@ use std::{
     pin::Pin,
     task::{self, Poll},
 };
-use wasm_bindgen::prelude::*;
+use wasm_bindgen::{prelude::*, JsCast};
 
 // We need to make a wrapper for some of the handle types returned by the web backend to make them
 // implement `Send` and `Sync` to match native.
@@ -124,11 +124,15 @@ impl crate::ComputePassInner<Context> for ComputePass {
             .dispatch_indirect_with_f64(&indirect_buffer.0, indirect_offset as f64);
     }
 
-    fn write_timestamp(&mut self, _query_set: &(), _query_index: u32) {
+    fn write_timestamp(&mut self, _query_set: &Sendable<web_sys::GpuQuerySet>, _query_index: u32) {
         // Not available in gecko yet
     }
 
-    fn begin_pipeline_statistics_query(&mut self, _query_set: &(), _query_index: u32) {
+    fn begin_pipeline_statistics_query(
+        &mut self,
+        _query_set: &Sendable<web_sys::GpuQuerySet>,
+        _query_index: u32,
+    ) {
         // Not available in gecko yet
     }
 
@@ -444,17 +448,29 @@ impl crate::RenderPassInner<Context> for RenderPass {
         self.0.execute_bundles(&mapped);
     }
 
-    fn write_timestamp(&mut self, _query_set: &(), _query_index: u32) {
+    fn write_timestamp(&mut self, _query_set: &Sendable<web_sys::GpuQuerySet>, _query_index: u32) {
         // Not available in gecko yet
     }
 
-    fn begin_pipeline_statistics_query(&mut self, _query_set: &(), _query_index: u32) {
+    fn begin_pipeline_statistics_query(
+        &mut self,
+        _query_set: &Sendable<web_sys::GpuQuerySet>,
+        _query_index: u32,
+    ) {
         // Not available in gecko yet
     }
 
     fn end_pipeline_statistics_query(&mut self) {
         // Not available in gecko yet
     }
+
+    fn begin_occlusion_query(&mut self, query_index: u32) {
+        self.0.begin_occlusion_query(query_index);
+    }
+
+    fn end_occlusion_query(&mut self) {
+        self.0.end_occlusion_query();
+    }
 }
 
 fn map_texture_format(texture_format: wgt::TextureFormat) -> web_sys::GpuTextureFormat {
@@ -499,10 +515,26 @@ fn map_texture_format(texture_format: wgt::TextureFormat) -> web_sys::GpuTexture
         TextureFormat::Depth32Float => tf::Depth32float,
         TextureFormat::Depth24Plus => tf::Depth24plus,
         TextureFormat::Depth24PlusStencil8 => tf::Depth24plusStencil8,
+        TextureFormat::Depth32FloatStencil8 => tf::Depth32floatStencil8,
+        TextureFormat::Depth16Unorm => tf::Depth16unorm,
+        TextureFormat::Stencil8 => tf::Stencil8,
         _ => unimplemented!(),
     }
 }
 
+/// Sets the `viewFormats` member of a texture or canvas descriptor,
+/// which the web-sys bindings don't have a setter for.
+fn set_view_formats(desc: &js_sys::Object, view_formats: &[wgt::TextureFormat]) {
+    if view_formats.is_empty() {
+        return;
+    }
+    let mapped = view_formats
+        .iter()
+        .map(|&format| JsValue::from(map_texture_format(format)))
+        .collect::<js_sys::Array>();
+    js_sys::Reflect::set(desc, &JsValue::from_str("viewFormats"), &mapped).unwrap();
+}
+
 fn map_texture_format_from_web_sys(
     texture_format: web_sys::GpuTextureFormat,
 ) -> wgt::TextureFormat {
@@ -547,6 +579,9 @@ fn map_texture_format_from_web_sys(
         tf::Depth32float => TextureFormat::Depth32Float,
         tf::Depth24plus => TextureFormat::Depth24Plus,
         tf::Depth24plusStencil8 => TextureFormat::Depth24PlusStencil8,
+        tf::Depth32floatStencil8 => TextureFormat::Depth32FloatStencil8,
+        tf::Depth16unorm => TextureFormat::Depth16Unorm,
+        tf::Stencil8 => TextureFormat::Stencil8,
         _ => unimplemented!(),
     }
 }
@@ -887,6 +922,47 @@ fn future_map_async(result: JsFutureResult) -> Result<(), crate::BufferAsyncErro
     result.map(|_| ()).map_err(|_| crate::BufferAsyncError)
 }
 
+/// Error reported by the browser, carrying its message.
+#[derive(Debug)]
+struct WebError(String);
+
+impl std::error::Error for WebError {}
+
+impl fmt::Display for WebError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+fn future_pop_error_scope(result: JsFutureResult) -> Option<crate::Error> {
+    // The promise is rejected when there is no error scope to pop.
+    let js_value = match result {
+        Ok(js_value) => js_value,
+        Err(_) => {
+            let description = "Device::pop_error_scope called without a matching push".to_string();
+            return Some(crate::Error::ValidationError {
+                source: Box::new(WebError(description.clone())),
+                description,
+            });
+        }
+    };
+    if js_value.is_null() {
+        return None;
+    }
+    Some(match js_value.dyn_into::<web_sys::GpuValidationError>() {
+        Ok(validation_error) => {
+            let description = validation_error.message();
+            crate::Error::ValidationError {
+                source: Box::new(WebError(description.clone())),
+                description,
+            }
+        }
+        Err(_) => crate::Error::OutOfMemoryError {
+            source: Box::new(WebError("Out of Memory".to_string())),
+        },
+    })
+}
+
 impl Context {
     pub fn instance_create_surface_from_canvas(
         &self,
@@ -922,10 +998,11 @@ impl crate::Context for Context {
     type SamplerId = Sendable<web_sys::GpuSampler>;
     type BufferId = Sendable<web_sys::GpuBuffer>;
     type TextureId = Sendable<web_sys::GpuTexture>;
-    type QuerySetId = (); //TODO!
+    type QuerySetId = Sendable<web_sys::GpuQuerySet>;
     type PipelineLayoutId = Sendable<web_sys::GpuPipelineLayout>;
     type RenderPipelineId = Sendable<web_sys::GpuRenderPipeline>;
     type ComputePipelineId = Sendable<web_sys::GpuComputePipeline>;
+    type PipelineCacheId = ();
     type CommandEncoderId = web_sys::GpuCommandEncoder;
     type ComputePassId = ComputePass;
     type RenderPassId = RenderPass;
@@ -950,6 +1027,8 @@ impl crate::Context for Context {
     >;
     type OnSubmittedWorkDoneFuture =
         MakeSendFuture<wasm_bindgen_futures::JsFuture, fn(JsFutureResult) -> ()>;
+    type PopErrorScopeFuture =
+        MakeSendFuture<wasm_bindgen_futures::JsFuture, fn(JsFutureResult) -> Option<crate::Error>>;
 
     fn init(_backends: wgt::Backends) -> Self {
         Context(web_sys::window().unwrap().navigator().gpu())
@@ -1028,7 +1107,10 @@ impl crate::Context for Context {
         let possible_features = [
             (wgt::Features::DEPTH_CLAMPING, Gfn::DepthClamping),
             // TODO (_, Gfn::Depth24unormStencil8),
-            // TODO (_, Gfn::Depth32floatStencil8),
+            (
+                wgt::Features::DEPTH32FLOAT_STENCIL8,
+                Gfn::Depth32floatStencil8,
+            ),
             (
                 wgt::Features::PIPELINE_STATISTICS_QUERY,
                 Gfn::PipelineStatisticsQuery,
@@ -1067,7 +1149,8 @@ impl crate::Context for Context {
     fn adapter_features(&self, adapter: &Self::AdapterId) -> wgt::Features {
         // TODO
         let _features = adapter.0.features();
-        wgt::Features::empty()
+        // `stencil8` is a core WebGPU format, so it needs no feature name.
+        wgt::Features::TEXTURE_FORMAT_STENCIL8
     }
 
     fn adapter_limits(&self, adapter: &Self::AdapterId) -> wgt::Limits {
@@ -1141,6 +1224,7 @@ impl crate::Context for Context {
         let mut mapped =
             web_sys::GpuCanvasConfiguration::new(&device.0, map_texture_format(config.format));
         mapped.usage(config.usage.bits());
+        set_view_formats(&mapped, &config.view_formats);
         surface.0.configure(&mapped);
     }
 
@@ -1165,7 +1249,7 @@ impl crate::Context for Context {
 
     fn device_features(&self, _device: &Self::DeviceId) -> wgt::Features {
         // TODO
-        wgt::Features::empty()
+        wgt::Features::TEXTURE_FORMAT_STENCIL8
     }
 
     fn device_limits(&self, _device: &Self::DeviceId) -> wgt::Limits {
@@ -1178,6 +1262,10 @@ impl crate::Context for Context {
         wgt::DownlevelCapabilities::default()
     }
 
+    fn device_memory_report(&self, _device: &Self::DeviceId) -> wgt::MemoryReport {
+        wgt::MemoryReport::default()
+    }
+
     fn device_create_shader_module(
         &self,
         device: &Self::DeviceId,
@@ -1308,6 +1396,15 @@ impl crate::Context for Context {
         Sendable(device.0.create_bind_group_layout(&mapped_desc))
     }
 
+    fn device_try_create_shader_module(
+        &self,
+        device: &Self::DeviceId,
+        desc: &crate::ShaderModuleDescriptor,
+    ) -> Result<Self::ShaderModuleId, crate::Error> {
+        // Shader errors are only reported asynchronously by the compilation info.
+        Ok(self.device_create_shader_module(device, desc))
+    }
+
     unsafe fn device_create_shader_module_spirv(
         &self,
         _device: &Self::DeviceId,
@@ -1481,6 +1578,14 @@ impl crate::Context for Context {
         Sendable(device.0.create_compute_pipeline(&mapped_desc))
     }
 
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        _device: &Self::DeviceId,
+        _desc: &crate::PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId {
+        panic!("PIPELINE_CACHE feature must be enabled to call create_pipeline_cache")
+    }
+
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -1511,6 +1616,7 @@ impl crate::Context for Context {
         mapped_desc.dimension(map_texture_dimension(desc.dimension));
         mapped_desc.mip_level_count(desc.mip_level_count);
         mapped_desc.sample_count(desc.sample_count);
+        set_view_formats(&mapped_desc, desc.view_formats);
         Sendable(device.0.create_texture(&mapped_desc))
     }
 
@@ -1541,9 +1647,23 @@ impl crate::Context for Context {
 
     fn device_create_query_set(
         &self,
-        _device: &Self::DeviceId,
-        _desc: &wgt::QuerySetDescriptor<crate::Label>,
+        device: &Self::DeviceId,
+        desc: &wgt::QuerySetDescriptor<crate::Label>,
     ) -> Self::QuerySetId {
+        let ty = match desc.ty {
+            wgt::QueryType::Occlusion => web_sys::GpuQueryType::Occlusion,
+            wgt::QueryType::PipelineStatistics(_) => {
+                panic!("PIPELINE_STATISTICS_QUERY feature must be enabled to create pipeline statistics query sets")
+            }
+            wgt::QueryType::Timestamp => {
+                panic!("TIMESTAMP_QUERY feature must be enabled to create timestamp query sets")
+            }
+        };
+        let mut mapped_desc = web_sys::GpuQuerySetDescriptor::new(desc.count, ty);
+        if let Some(label) = desc.label {
+            mapped_desc.label(label);
+        }
+        Sendable(device.0.create_query_set(&mapped_desc))
     }
 
     fn device_create_command_encoder(
@@ -1597,6 +1717,63 @@ impl crate::Context for Context {
         // TODO:
     }
 
+    fn device_set_device_lost_callback(
+        &self,
+        device: &Self::DeviceId,
+        callback: Box<dyn FnOnce(wgt::DeviceLostReason, String) + Send + 'static>,
+    ) {
+        // Only the latest callback is called: each one bumps a generation stored on the
+        // device object, and checks that it's still the latest once the device is lost.
+        let key = JsValue::from_str("__wgpuDeviceLostGeneration");
+        let generation = js_sys::Reflect::get(&device.0, &key)
+            .ok()
+            .and_then(|value| value.as_f64())
+            .unwrap_or(0.0)
+            + 1.0;
+        js_sys::Reflect::set(&device.0, &key, &JsValue::from_f64(generation)).unwrap();
+
+        let device = device.0.clone();
+        let lost = wasm_bindgen_futures::JsFuture::from(device.lost());
+        wasm_bindgen_futures::spawn_local(async move {
+            if let Ok(js_value) = lost.await {
+                let latest = js_sys::Reflect::get(&device, &key)
+                    .ok()
+                    .and_then(|value| value.as_f64());
+                if latest != Some(generation) {
+                    return;
+                }
+                let info = web_sys::GpuDeviceLostInfo::from(js_value);
+                // The browser doesn't tell other reasons apart.
+                let reason = match web_sys::GpuDeviceLostReason::from_js_value(&info.reason()) {
+                    Some(web_sys::GpuDeviceLostReason::Destroyed) => {
+                        wgt::DeviceLostReason::Destroyed
+                    }
+                    _ => wgt::DeviceLostReason::DriverReset,
+                };
+                callback(reason, info.message());
+            }
+        });
+    }
+
+    fn device_destroy(&self, device: &Self::DeviceId) {
+        device.0.destroy();
+    }
+
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: crate::ErrorFilter) {
+        device.0.push_error_scope(match filter {
+            crate::ErrorFilter::OutOfMemory => web_sys::GpuErrorFilter::OutOfMemory,
+            crate::ErrorFilter::Validation => web_sys::GpuErrorFilter::Validation,
+        });
+    }
+
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture {
+        let error_promise = device.0.pop_error_scope();
+        MakeSendFuture::new(
+            wasm_bindgen_futures::JsFuture::from(error_promise),
+            future_pop_error_scope,
+        )
+    }
+
     fn buffer_map_async(
         &self,
         buffer: &Self::BufferId,
@@ -1735,6 +1912,14 @@ impl crate::Context for Context {
         // Dropped automatically
     }
 
+    fn pipeline_cache_drop(&self, _cache: &Self::PipelineCacheId) {
+        // Dropped automatically
+    }
+
+    fn pipeline_cache_get_data(&self, _cache: &Self::PipelineCacheId) -> Option<Vec<u8>> {
+        None
+    }
+
     fn compute_pipeline_get_bind_group_layout(
         &self,
         pipeline: &Self::ComputePipelineId,
@@ -1909,6 +2094,10 @@ impl crate::Context for Context {
             mapped_desc.depth_stencil_attachment(&mapped_depth_stencil_attachment);
         }
 
+        if let Some(query_set) = desc.occlusion_query_set {
+            mapped_desc.occlusion_query_set(&query_set.id.0);
+        }
+
         RenderPass(encoder.begin_render_pass(&mapped_desc))
     }
 
@@ -1974,14 +2163,20 @@ impl crate::Context for Context {
 
     fn command_encoder_resolve_query_set(
         &self,
-        _encoder: &Self::CommandEncoderId,
-        _query_set: &Self::QuerySetId,
-        _first_query: u32,
-        _query_count: u32,
-        _destination: &Self::BufferId,
-        _destination_offset: wgt::BufferAddress,
+        encoder: &Self::CommandEncoderId,
+        query_set: &Self::QuerySetId,
+        first_query: u32,
+        query_count: u32,
+        destination: &Self::BufferId,
+        destination_offset: wgt::BufferAddress,
     ) {
-        unimplemented!();
+        encoder.resolve_query_set_with_f64(
+            &query_set.0,
+            first_query,
+            query_count,
+            &destination.0,
+            destination_offset as f64,
+        );
     }
 
     fn render_bundle_encoder_finish(
@@ -25,20 +25,22 @@ use std::{
 use parking_lot::Mutex;
 
 pub use wgt::{
-    AdapterInfo, AddressMode, Backend, Backends, BindGroupLayoutEntry, BindingType, BlendComponent,
-    BlendFactor, BlendOperation, BlendState, BufferAddress, BufferBindingType, BufferSize,
-    BufferUsages, Color, ColorTargetState, ColorWrites, CommandBufferDescriptor, CompareFunction,
-    DepthBiasState, DepthStencilState, DeviceType, DownlevelCapabilities, DownlevelFlags,
-    DynamicOffset, Extent3d, Face, Features, FilterMode, FrontFace, ImageDataLayout,
-    ImageSubresourceRange, IndexFormat, Limits, MultisampleState, Origin3d,
+    AdapterInfo, AddressMode, AllocatorReport, Backend, Backends, BindGroupLayoutEntry,
+    BindingType, BlendComponent, BlendFactor, BlendOperation, BlendState, BufferAddress,
+    BufferBindingType, BufferSize, BufferUsages, Color, ColorTargetState, ColorWrites,
+    CommandBufferDescriptor, CompareFunction, DepthBiasState, DepthStencilState, DeviceLostReason,
+    DeviceType, DownlevelCapabilities, DownlevelFlags, DynamicOffset, Extent3d, Face, Features,
+    FilterMode, FrontFace, ImageDataLayout, ImageSubresourceRange, IndexFormat, Limits,
+    MemoryReport, MemoryTypeReport, MemoryUsage, MultisampleState, Origin3d,
     PipelineStatisticsTypes, PolygonMode, PowerPreference, PresentMode, PrimitiveState,
-    PrimitiveTopology, PushConstantRange, QueryType, RenderBundleDepthStencil, SamplerBorderColor,
-    ShaderLocation, ShaderModel, ShaderStages, StencilFaceState, StencilOperation, StencilState,
-    StorageTextureAccess, SurfaceConfiguration, SurfaceStatus, TextureAspect, TextureDimension,
-    TextureFormat, TextureFormatFeatureFlags, TextureFormatFeatures, TextureSampleType,
-    TextureUsages, TextureViewDimension, VertexAttribute, VertexFormat, VertexStepMode,
-    COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT, MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT,
-    QUERY_SET_MAX_QUERIES, QUERY_SIZE, VERTEX_STRIDE_ALIGNMENT,
+    PrimitiveTopology, PushConstantRange, QueryType, RenderBundleDepthStencil,
+    ResourceMemoryReport, SamplerBorderColor, ShaderLocation, ShaderModel, ShaderStages,
+    StencilFaceState, StencilOperation, StencilState, StorageTextureAccess, SurfaceConfiguration,
+    SurfaceStatus, TextureAspect, TextureDimension, TextureFormat, TextureFormatFeatureFlags,
+    TextureFormatFeatures, TextureSampleType, TextureUsages, TextureViewDimension, VertexAttribute,
+    VertexFormat, VertexStepMode, COPY_BUFFER_ALIGNMENT, COPY_BYTES_PER_ROW_ALIGNMENT,
+    MAP_ALIGNMENT, PUSH_CONSTANT_ALIGNMENT, QUERY_SET_MAX_QUERIES, QUERY_SIZE,
+    VERTEX_STRIDE_ALIGNMENT,
 };
 
 use backend::{BufferMappedRange, Context as C};
@@ -146,6 +148,8 @@ trait RenderPassInner<Ctx: Context>: RenderInner<Ctx> {
     fn write_timestamp(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
     fn begin_pipeline_statistics_query(&mut self, query_set: &Ctx::QuerySetId, query_index: u32);
     fn end_pipeline_statistics_query(&mut self);
+    fn begin_occlusion_query(&mut self, query_index: u32);
+    fn end_occlusion_query(&mut self);
     fn execute_bundles<'a, I: Iterator<Item = &'a Ctx::RenderBundleId>>(
         &mut self,
         render_bundles: I,
@@ -167,6 +171,7 @@ trait Context: Debug + Send + Sized + Sync {
     type PipelineLayoutId: Debug + Send + Sync + 'static;
     type RenderPipelineId: Debug + Send + Sync + 'static;
     type ComputePipelineId: Debug + Send + Sync + 'static;
+    type PipelineCacheId: Debug + Send + Sync + 'static;
     type CommandEncoderId: Debug;
     type ComputePassId: Debug + ComputePassInner<Self>;
     type RenderPassId: Debug + RenderPassInner<Self>;
@@ -182,6 +187,7 @@ trait Context: Debug + Send + Sized + Sync {
         + Send;
     type MapAsyncFuture: Future<Output = Result<(), BufferAsyncError>> + Send;
     type OnSubmittedWorkDoneFuture: Future<Output = ()> + Send;
+    type PopErrorScopeFuture: Future<Output = Option<Error>> + Send;
 
     fn init(backends: Backends) -> Self;
     fn instance_create_surface(
@@ -238,11 +244,17 @@ trait Context: Debug + Send + Sized + Sync {
     fn device_features(&self, device: &Self::DeviceId) -> Features;
     fn device_limits(&self, device: &Self::DeviceId) -> Limits;
     fn device_downlevel_properties(&self, device: &Self::DeviceId) -> DownlevelCapabilities;
+    fn device_memory_report(&self, device: &Self::DeviceId) -> MemoryReport;
     fn device_create_shader_module(
         &self,
         device: &Self::DeviceId,
         desc: &ShaderModuleDescriptor,
     ) -> Self::ShaderModuleId;
+    fn device_try_create_shader_module(
+        &self,
+        device: &Self::DeviceId,
+        desc: &ShaderModuleDescriptor,
+    ) -> Result<Self::ShaderModuleId, Error>;
     unsafe fn device_create_shader_module_spirv(
         &self,
         device: &Self::DeviceId,
@@ -273,6 +285,11 @@ trait Context: Debug + Send + Sized + Sync {
         device: &Self::DeviceId,
         desc: &ComputePipelineDescriptor,
     ) -> Self::ComputePipelineId;
+    unsafe fn device_create_pipeline_cache(
+        &self,
+        device: &Self::DeviceId,
+        desc: &PipelineCacheDescriptor,
+    ) -> Self::PipelineCacheId;
     fn device_create_buffer(
         &self,
         device: &Self::DeviceId,
@@ -310,6 +327,14 @@ trait Context: Debug + Send + Sized + Sync {
         device: &Self::DeviceId,
         handler: impl UncapturedErrorHandler,
     );
+    fn device_push_error_scope(&self, device: &Self::DeviceId, filter: ErrorFilter);
+    fn device_pop_error_scope(&self, device: &Self::DeviceId) -> Self::PopErrorScopeFuture;
+    fn device_set_device_lost_callback(
+        &self,
+        device: &Self::DeviceId,
+        callback: Box<dyn FnOnce(DeviceLostReason, String) + Send + 'static>,
+    );
+    fn device_destroy(&self, device: &Self::DeviceId);
 
     fn buffer_map_async(
         &self,
@@ -347,6 +372,8 @@ trait Context: Debug + Send + Sized + Sync {
     fn render_bundle_drop(&self, render_bundle: &Self::RenderBundleId);
     fn compute_pipeline_drop(&self, pipeline: &Self::ComputePipelineId);
     fn render_pipeline_drop(&self, pipeline: &Self::RenderPipelineId);
+    fn pipeline_cache_drop(&self, cache: &Self::PipelineCacheId);
+    fn pipeline_cache_get_data(&self, cache: &Self::PipelineCacheId) -> Option<Vec<u8>>;
 
     fn compute_pipeline_get_bind_group_layout(
         &self,
@@ -845,6 +872,36 @@ impl ComputePipeline {
     }
 }
 
+/// Handle to a pipeline cache.
+///
+/// A `PipelineCache` is used to speed up the creation of render and compute pipelines,
+/// both within a run and across runs, by serializing it with [`PipelineCache::get_data`]
+/// and passing the data to [`Device::create_pipeline_cache`] on the next run.
+/// It may be created with [`Device::create_pipeline_cache`].
+#[derive(Debug)]
+pub struct PipelineCache {
+    context: Arc<C>,
+    id: <C as Context>::PipelineCacheId,
+}
+
+impl Drop for PipelineCache {
+    fn drop(&mut self) {
+        if !thread::panicking() {
+            self.context.pipeline_cache_drop(&self.id);
+        }
+    }
+}
+
+impl PipelineCache {
+    /// Returns the data of this cache, to be stored and passed to
+    /// [`Device::create_pipeline_cache`] on the next run.
+    ///
+    /// Returns `None` if the backend doesn't support pipeline caches.
+    pub fn get_data(&self) -> Option<Vec<u8>> {
+        self.context.pipeline_cache_get_data(&self.id)
+    }
+}
+
 /// Handle to a command buffer on the GPU.
 ///
 /// A `CommandBuffer` represents a complete sequence of commands that may be submitted to a command
@@ -943,6 +1000,7 @@ impl Drop for RenderBundle {
 }
 
 /// Handle to a query set.
+#[derive(Debug)]
 pub struct QuerySet {
     context: Arc<C>,
     id: <C as Context>::QuerySetId,
@@ -1092,7 +1150,7 @@ pub type CommandEncoderDescriptor<'a> = wgt::CommandEncoderDescriptor<Label<'a>>
 /// Describes a [`RenderBundle`].
 pub type RenderBundleDescriptor<'a> = wgt::RenderBundleDescriptor<Label<'a>>;
 /// Describes a [`Texture`].
-pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>>;
+pub type TextureDescriptor<'a> = wgt::TextureDescriptor<Label<'a>, &'a [TextureFormat]>;
 /// Describes a [`QuerySet`].
 pub type QuerySetDescriptor<'a> = wgt::QuerySetDescriptor<Label<'a>>;
 
@@ -1221,6 +1279,9 @@ pub struct RenderPassDescriptor<'a, 'b> {
     pub color_attachments: &'b [RenderPassColorAttachment<'a>],
     /// The depth and stencil attachment of the render pass, if any.
     pub depth_stencil_attachment: Option<RenderPassDepthStencilAttachment<'a>>,
+    /// The query set of type [`QueryType::Occlusion`] that occlusion queries
+    /// of the render pass write to, if any.
+    pub occlusion_query_set: Option<&'a QuerySet>,
 }
 
 /// Describes how the vertex buffer is interpreted.
@@ -1275,6 +1336,8 @@ pub struct RenderPipelineDescriptor<'a> {
     pub multisample: MultisampleState,
     /// The compiled fragment stage, its entry point, and the color targets.
     pub fragment: Option<FragmentState<'a>>,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<&'a PipelineCache>,
 }
 
 /// Describes the attachments of a compute pass.
@@ -1296,6 +1359,20 @@ pub struct ComputePipelineDescriptor<'a> {
     /// The name of the entry point in the compiled shader. There must be a function that returns
     /// void with this name in the shader.
     pub entry_point: &'a str,
+    /// The pipeline cache to use when creating this pipeline.
+    pub cache: Option<&'a PipelineCache>,
+}
+
+/// Describes a [`PipelineCache`].
+#[derive(Clone, Debug)]
+pub struct PipelineCacheDescriptor<'a> {
+    /// Debug label of the pipeline cache. This will show up in graphics debuggers for easy identification.
+    pub label: Label<'a>,
+    /// Data previously returned by [`PipelineCache::get_data`], if any.
+    pub data: Option<&'a [u8]>,
+    /// If the data is rejected because it's invalid or was produced for another adapter,
+    /// driver, or version of wgpu, create an empty cache instead of an invalid one.
+    pub fallback: bool,
 }
 
 pub use wgt::ImageCopyBuffer as ImageCopyBufferBase;
@@ -1537,7 +1614,9 @@ impl Adapter {
     ///
     /// - `desc` - Description of the features and limits requested from the given device.
     /// - `trace_path` - Can be used for API call tracing, if that feature is
-    ///   enabled in `wgpu-core`.
+    ///   enabled in `wgpu-core`. A path to a file with the `.trace` extension
+    ///   produces a single binary trace, any other path is used as a directory
+    ///   for a RON trace.
     ///
     /// # Panics
     ///
@@ -1660,6 +1739,15 @@ impl Device {
         Context::device_limits(&*self.context, &self.id)
     }
 
+    /// Reports the memory used by the buffers and textures of this device.
+    ///
+    /// The values are estimates, see [`MemoryReport`].
+    ///
+    /// On the web, the report is always empty.
+    pub fn memory_report(&self) -> MemoryReport {
+        Context::device_memory_report(&*self.context, &self.id)
+    }
+
     /// Creates a shader module from either SPIR-V or WGSL source code.
     pub fn create_shader_module(&self, desc: &ShaderModuleDescriptor) -> ShaderModule {
         ShaderModule {
@@ -1668,6 +1756,22 @@ impl Device {
         }
     }
 
+    /// Creates a shader module from either SPIR-V or WGSL source code,
+    /// returning the parsing and validation errors instead of reporting them
+    /// to the uncaptured error handler.
+    ///
+    /// On the web, the shader errors are only reported asynchronously,
+    /// so this always succeeds and the errors go to the uncaptured error handler.
+    pub fn try_create_shader_module(
+        &self,
+        desc: &ShaderModuleDescriptor,
+    ) -> Result<ShaderModule, Error> {
+        Ok(ShaderModule {
+            context: Arc::clone(&self.context),
+            id: Context::device_try_create_shader_module(&*self.context, &self.id, desc)?,
+        })
+    }
+
     /// Creates a shader module from SPIR-V binary directly.
     ///
     /// # Safety
@@ -1752,6 +1856,22 @@ impl Device {
         }
     }
 
+    /// Creates a [`PipelineCache`].
+    ///
+    /// [`Features::PIPELINE_CACHE`] must be enabled on the device.
+    ///
+    /// # Safety
+    ///
+    /// The `data` of the descriptor must have been returned by [`PipelineCache::get_data`].
+    /// It is checked to be intact and to match this adapter and driver, but its contents
+    /// are passed to the driver as-is.
+    pub unsafe fn create_pipeline_cache(&self, desc: &PipelineCacheDescriptor) -> PipelineCache {
+        PipelineCache {
+            context: Arc::clone(&self.context),
+            id: Context::device_create_pipeline_cache(&*self.context, &self.id, desc),
+        }
+    }
+
     /// Creates a [`Buffer`].
     pub fn create_buffer(&self, desc: &BufferDescriptor) -> Buffer {
         let mut map_context = MapContext::new(desc.size);
@@ -1821,6 +1941,40 @@ impl Device {
         self.context.device_on_uncaptured_error(&self.id, handler);
     }
 
+    /// Pushes an error scope, which captures the errors matching `filter`
+    /// until it is popped with [`Device::pop_error_scope`].
+    pub fn push_error_scope(&self, filter: ErrorFilter) {
+        Context::device_push_error_scope(&*self.context, &self.id, filter)
+    }
+
+    /// Pops the innermost error scope, returning a future that resolves
+    /// with the first error captured by it, if any.
+    ///
+    /// Resolves with a validation error if there is no error scope to pop.
+    pub fn pop_error_scope(&self) -> impl Future<Output = Option<Error>> + Send {
+        Context::device_pop_error_scope(&*self.context, &self.id)
+    }
+
+    /// Sets the callback called once the device is lost, with the reason and a message.
+    ///
+    /// It replaces the previous callback, and is called right away if the device is already lost.
+    /// Once the device is lost, operations on it and its resources fail without
+    /// reporting errors, and buffer mappings resolve with an error.
+    pub fn set_device_lost_callback(
+        &self,
+        callback: impl FnOnce(DeviceLostReason, String) + Send + 'static,
+    ) {
+        Context::device_set_device_lost_callback(&*self.context, &self.id, Box::new(callback))
+    }
+
+    /// Destroys the device, which is then lost with [`DeviceLostReason::Destroyed`].
+    ///
+    /// The work already submitted runs to completion, and the resources are freed
+    /// once they are dropped.
+    pub fn destroy(&self) {
+        Context::device_destroy(&*self.context, &self.id)
+    }
+
     /// Starts frame capture.
     pub fn start_capture(&self) {
         Context::device_start_capture(&*self.context, &self.id)
@@ -2788,6 +2942,24 @@ impl<'a> RenderPass<'a> {
     }
 }
 
+impl<'a> RenderPass<'a> {
+    /// Start an occlusion query on this render pass. It can be ended with
+    /// `end_occlusion_query`. Occlusion queries may not be nested.
+    ///
+    /// The query is written to the `query_index` of the occlusion query set
+    /// of the pass, as non-zero if any sample passed the depth and stencil tests.
+    /// Occlusion queries are not supported in render bundles.
+    pub fn begin_occlusion_query(&mut self, query_index: u32) {
+        self.id.begin_occlusion_query(query_index);
+    }
+
+    /// End the occlusion query on this render pass. It can be started with
+    /// `begin_occlusion_query`. Occlusion queries may not be nested.
+    pub fn end_occlusion_query(&mut self) {
+        self.id.end_occlusion_query();
+    }
+}
+
 impl<'a> Drop for RenderPass<'a> {
     fn drop(&mut self) {
         if !thread::panicking() {
@@ -3181,6 +3353,15 @@ impl Surface {
 pub trait UncapturedErrorHandler: Fn(Error) + Send + 'static {}
 impl<T> UncapturedErrorHandler for T where T: Fn(Error) + Send + 'static {}
 
+/// Filter for the errors captured by an error scope.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum ErrorFilter {
+    /// Catch only out-of-memory errors.
+    OutOfMemory,
+    /// Catch only validation errors.
+    Validation,
+}
+
 /// Error type
 #[derive(Debug)]
 pub enum Error {
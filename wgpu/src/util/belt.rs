@@ -1,7 +1,9 @@
 use crate::{
     Buffer, BufferAddress, BufferDescriptor, BufferSize, BufferUsages, BufferViewMut,
-    CommandEncoder, Device, MapMode,
+    CommandEncoder, Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, MapMode,
+    TextureAspect, TextureFormat, COPY_BYTES_PER_ROW_ALIGNMENT, MAP_ALIGNMENT,
 };
+use std::num::NonZeroU32;
 use std::pin::Pin;
 use std::task::{self, Poll};
 use std::{future::Future, sync::mpsc};
@@ -38,20 +40,153 @@ impl<F: Future<Output = ()>> Future for Join<F> {
     }
 }
 
+fn align_to(value: BufferAddress, alignment: BufferAddress) -> BufferAddress {
+    match value % alignment {
+        0 => value,
+        remainder => value + alignment - remainder,
+    }
+}
+
 struct Chunk {
     buffer: Buffer,
     size: BufferAddress,
     offset: BufferAddress,
 }
 
+impl Chunk {
+    /// Returns the offset at which `size` bytes with the given alignment fit into the chunk.
+    fn fit(&self, size: BufferAddress, alignment: BufferAddress) -> Option<BufferAddress> {
+        let offset = align_to(self.offset, alignment);
+        if offset + size <= self.size {
+            Some(offset)
+        } else {
+            None
+        }
+    }
+}
+
+/// Statistics of the chunks of a [`StagingBelt`] or [`DownloadBelt`].
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct BeltStats {
+    /// Number of chunks allocated as new buffers.
+    pub allocated_chunks: u64,
+    /// Number of times a recalled chunk was reused instead of allocating a new one.
+    pub reused_chunks: u64,
+    /// Total size of all the allocated chunks, in bytes.
+    pub allocated_bytes: BufferAddress,
+}
+
+/// Layout of a texture copy in a staging chunk, with rows padded to
+/// [`COPY_BYTES_PER_ROW_ALIGNMENT`].
+#[derive(Clone, Copy, Debug)]
+struct TextureCopyLayout {
+    bytes_per_row: u32,
+    padded_bytes_per_row: u32,
+    rows_per_image: u32,
+    row_count: u32,
+    /// Alignment of the offset of the copy in the buffer.
+    alignment: BufferAddress,
+}
+
+impl TextureCopyLayout {
+    fn new(format: TextureFormat, aspect: TextureAspect, size: Extent3d) -> Self {
+        let info = format.describe();
+        let (block_width, block_height) = info.block_dimensions;
+        // The stencil aspect of depth-stencil formats is always copied as 8 bits.
+        let block_size = match aspect {
+            TextureAspect::StencilOnly => 1,
+            _ => info.block_size as u32,
+        };
+        let bytes_per_row = size.width / block_width as u32 * block_size;
+        let rows_per_image = size.height / block_height as u32;
+        Self {
+            bytes_per_row,
+            padded_bytes_per_row: align_to(
+                bytes_per_row as BufferAddress,
+                COPY_BYTES_PER_ROW_ALIGNMENT as BufferAddress,
+            ) as u32,
+            rows_per_image,
+            row_count: rows_per_image * size.depth_or_array_layers,
+            // Block sizes are powers of two, so this is a multiple of both.
+            alignment: MAP_ALIGNMENT.max(block_size as BufferAddress),
+        }
+    }
+
+    fn size(&self) -> BufferAddress {
+        self.padded_bytes_per_row as BufferAddress * self.row_count as BufferAddress
+    }
+
+    fn image_copy_buffer<'a>(
+        &self,
+        buffer: &'a Buffer,
+        offset: BufferAddress,
+    ) -> ImageCopyBuffer<'a> {
+        ImageCopyBuffer {
+            buffer,
+            layout: ImageDataLayout {
+                offset,
+                bytes_per_row: NonZeroU32::new(self.padded_bytes_per_row),
+                rows_per_image: NonZeroU32::new(self.rows_per_image),
+            },
+        }
+    }
+}
+
+/// Staging memory of a texture upload, returned by [`StagingBelt::write_texture`].
+///
+/// Rows are padded to [`COPY_BYTES_PER_ROW_ALIGNMENT`] bytes, as required for
+/// buffer to texture copies. Rows of block-compressed formats contain a row of blocks,
+/// and the rows of all the depth slices or array layers follow each other.
+pub struct TextureWriteView<'a> {
+    view: BufferViewMut<'a>,
+    layout: TextureCopyLayout,
+}
+
+impl TextureWriteView<'_> {
+    /// Number of bytes of a row that are copied to the texture.
+    pub fn bytes_per_row(&self) -> u32 {
+        self.layout.bytes_per_row
+    }
+
+    /// Distance between the starts of two consecutive rows, in bytes.
+    pub fn padded_bytes_per_row(&self) -> u32 {
+        self.layout.padded_bytes_per_row
+    }
+
+    /// Number of rows in each depth slice or array layer.
+    pub fn rows_per_image(&self) -> u32 {
+        self.layout.rows_per_image
+    }
+
+    /// Returns the bytes of the given row, counting the rows of all the images.
+    pub fn row_mut(&mut self, row: u32) -> &mut [u8] {
+        assert!(row < self.layout.row_count, "row {} is out of range", row);
+        let start = row as usize * self.layout.padded_bytes_per_row as usize;
+        &mut self.view[start..start + self.layout.bytes_per_row as usize]
+    }
+
+    /// Copies tightly packed rows from `data`, which has to contain all the rows.
+    pub fn copy_from_slice(&mut self, data: &[u8]) {
+        let bytes_per_row = self.layout.bytes_per_row as usize;
+        assert_eq!(
+            data.len(),
+            bytes_per_row * self.layout.row_count as usize,
+            "data size doesn't match the texture copy"
+        );
+        for (row, data_row) in data.chunks(bytes_per_row).enumerate() {
+            self.row_mut(row as u32).copy_from_slice(data_row);
+        }
+    }
+}
+
 /// Staging belt is a machine that uploads data.
 ///
 /// Internally it uses a ring-buffer of staging buffers that are sub-allocated.
-/// It has an advantage over `Queue.write_buffer` in a way that it returns a mutable slice,
-/// which you can fill to avoid an extra data copy.
+/// It has an advantage over `Queue.write_buffer` and `Queue.write_texture` in a way
+/// that it returns a mutable slice, which you can fill to avoid an extra data copy.
 ///
 /// Using a staging belt is slightly complicated, and generally goes as follows:
-/// - Write to buffers that need writing to using `write_buffer`.
+/// - Write to buffers and textures that need writing to using `write_buffer` and `write_texture`.
 /// - Call `finish`.
 /// - Submit all command encoders used with `write_buffer`.
 /// - Call `recall`
@@ -65,6 +200,7 @@ pub struct StagingBelt {
     free_chunks: Vec<Chunk>,
     sender: mpsc::Sender<Chunk>,
     receiver: mpsc::Receiver<Chunk>,
+    stats: BeltStats,
 }
 
 impl StagingBelt {
@@ -82,9 +218,57 @@ impl StagingBelt {
             free_chunks: Vec::new(),
             sender,
             receiver,
+            stats: BeltStats::default(),
         }
     }
 
+    /// Returns the statistics of chunk allocation and reuse.
+    pub fn stats(&self) -> BeltStats {
+        self.stats
+    }
+
+    /// Allocates `size` bytes in an active chunk, returning the index of the chunk
+    /// and the offset of the allocation.
+    fn allocate(
+        &mut self,
+        size: BufferAddress,
+        alignment: BufferAddress,
+        device: &Device,
+    ) -> (usize, BufferAddress) {
+        if let Some((index, offset)) = self
+            .active_chunks
+            .iter()
+            .enumerate()
+            .find_map(|(index, chunk)| Some((index, chunk.fit(size, alignment)?)))
+        {
+            self.active_chunks[index].offset = offset + size;
+            return (index, offset);
+        }
+
+        let mut chunk =
+            if let Some(index) = self.free_chunks.iter().position(|chunk| size <= chunk.size) {
+                self.stats.reused_chunks += 1;
+                self.free_chunks.swap_remove(index)
+            } else {
+                let size = self.chunk_size.max(size);
+                self.stats.allocated_chunks += 1;
+                self.stats.allocated_bytes += size;
+                Chunk {
+                    buffer: device.create_buffer(&BufferDescriptor {
+                        label: Some("staging"),
+                        size,
+                        usage: BufferUsages::MAP_WRITE | BufferUsages::COPY_SRC,
+                        mapped_at_creation: true,
+                    }),
+                    size,
+                    offset: 0,
+                }
+            };
+        chunk.offset = size;
+        self.active_chunks.push(chunk);
+        (self.active_chunks.len() - 1, 0)
+    }
+
     /// Allocate the staging belt slice of `size` to be uploaded into the `target` buffer
     /// at the specified offset.
     ///
@@ -98,49 +282,47 @@ impl StagingBelt {
         size: BufferSize,
         device: &Device,
     ) -> BufferViewMut {
-        let mut chunk = if let Some(index) = self
-            .active_chunks
-            .iter()
-            .position(|chunk| chunk.offset + size.get() <= chunk.size)
-        {
-            self.active_chunks.swap_remove(index)
-        } else if let Some(index) = self
-            .free_chunks
-            .iter()
-            .position(|chunk| size.get() <= chunk.size)
-        {
-            self.free_chunks.swap_remove(index)
-        } else {
-            let size = self.chunk_size.max(size.get());
-            Chunk {
-                buffer: device.create_buffer(&BufferDescriptor {
-                    label: Some("staging"),
-                    size,
-                    usage: BufferUsages::MAP_WRITE | BufferUsages::COPY_SRC,
-                    mapped_at_creation: true,
-                }),
-                size,
-                offset: 0,
-            }
-        };
-
-        encoder.copy_buffer_to_buffer(&chunk.buffer, chunk.offset, target, offset, size.get());
-        let old_offset = chunk.offset;
-        chunk.offset += size.get();
-        let remainder = chunk.offset % crate::MAP_ALIGNMENT;
-        if remainder != 0 {
-            chunk.offset += crate::MAP_ALIGNMENT - remainder;
-        }
-
-        self.active_chunks.push(chunk);
-        self.active_chunks
-            .last()
-            .unwrap()
+        let (index, chunk_offset) = self.allocate(size.get(), MAP_ALIGNMENT, device);
+        let chunk = &self.active_chunks[index];
+        encoder.copy_buffer_to_buffer(&chunk.buffer, chunk_offset, target, offset, size.get());
+        chunk
             .buffer
-            .slice(old_offset..old_offset + size.get())
+            .slice(chunk_offset..chunk_offset + size.get())
             .get_mapped_range_mut()
     }
 
+    /// Allocate the staging belt memory for a copy of `size` texels into the `target` texture.
+    ///
+    /// `format` is the format of the texture. The returned view has to be filled with
+    /// the rows of the copy, at the pitch it specifies.
+    ///
+    /// The upload will be placed into the provided command encoder. This encoder
+    /// must be submitted after `finish` is called and before `recall` is called.
+    pub fn write_texture(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        target: ImageCopyTexture,
+        format: TextureFormat,
+        size: Extent3d,
+        device: &Device,
+    ) -> TextureWriteView {
+        let layout = TextureCopyLayout::new(format, target.aspect, size);
+        let (index, offset) = self.allocate(layout.size(), layout.alignment, device);
+        let chunk = &self.active_chunks[index];
+        encoder.copy_buffer_to_texture(
+            layout.image_copy_buffer(&chunk.buffer, offset),
+            target,
+            size,
+        );
+        TextureWriteView {
+            view: chunk
+                .buffer
+                .slice(offset..offset + layout.size())
+                .get_mapped_range_mut(),
+            layout,
+        }
+    }
+
     /// Prepare currently mapped buffers for use in a submission.
     ///
     /// At this point, all the partially used staging buffers are closed until
@@ -182,3 +364,244 @@ impl StagingBelt {
         Join { futures }
     }
 }
+
+/// Read back from a chunk of a [`DownloadBelt`].
+struct PendingRead {
+    offset: BufferAddress,
+    bytes_per_row: usize,
+    padded_bytes_per_row: usize,
+    row_count: usize,
+    callback: Box<dyn FnOnce(&[u8]) + Send>,
+}
+
+impl PendingRead {
+    fn complete(self, mapped: &[u8]) {
+        let start = self.offset as usize;
+        if self.bytes_per_row == self.padded_bytes_per_row {
+            (self.callback)(&mapped[start..start + self.bytes_per_row * self.row_count]);
+        } else {
+            let mut data = Vec::with_capacity(self.bytes_per_row * self.row_count);
+            for row in mapped[start..]
+                .chunks(self.padded_bytes_per_row)
+                .take(self.row_count)
+            {
+                data.extend_from_slice(&row[..self.bytes_per_row]);
+            }
+            (self.callback)(&data);
+        }
+    }
+}
+
+struct DownloadChunk {
+    chunk: Chunk,
+    reads: Vec<PendingRead>,
+}
+
+/// Download belt is a machine that reads data back from the GPU.
+///
+/// It is the counterpart of [`StagingBelt`], and recycles a ring-buffer of
+/// `MAP_READ` buffers for readbacks that happen every frame, like GPU picking.
+/// Instead of returning the data, the reads take callbacks that are called
+/// with it once the GPU is done.
+///
+/// Using a download belt generally goes as follows:
+/// - Read from buffers and textures using `read_buffer` and `read_texture`.
+/// - Call `finish`.
+/// - Submit all command encoders used with `read_buffer` and `read_texture`.
+/// - Call `recall`, and poll the device until the returned future is ready.
+pub struct DownloadBelt {
+    chunk_size: BufferAddress,
+    /// Chunks that we are actively using for pending transfers at this moment.
+    active_chunks: Vec<DownloadChunk>,
+    /// Chunks that have scheduled transfers already.
+    closed_chunks: Vec<DownloadChunk>,
+    /// Chunks that are back from the GPU and ready to be used.
+    free_chunks: Vec<Chunk>,
+    sender: mpsc::Sender<Chunk>,
+    receiver: mpsc::Receiver<Chunk>,
+    stats: BeltStats,
+}
+
+impl DownloadBelt {
+    /// Create a new download belt.
+    ///
+    /// The `chunk_size` is the unit of internal buffer allocation.
+    /// It's better when it's big, but ideally still 1-4 times less than
+    /// the total amount of data read back per submission.
+    pub fn new(chunk_size: BufferAddress) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        DownloadBelt {
+            chunk_size,
+            active_chunks: Vec::new(),
+            closed_chunks: Vec::new(),
+            free_chunks: Vec::new(),
+            sender,
+            receiver,
+            stats: BeltStats::default(),
+        }
+    }
+
+    /// Returns the statistics of chunk allocation and reuse.
+    pub fn stats(&self) -> BeltStats {
+        self.stats
+    }
+
+    /// Allocates `size` bytes in an active chunk, returning the chunk
+    /// and the offset of the allocation.
+    fn allocate(
+        &mut self,
+        size: BufferAddress,
+        alignment: BufferAddress,
+        device: &Device,
+    ) -> (&mut DownloadChunk, BufferAddress) {
+        if let Some((index, offset)) = self
+            .active_chunks
+            .iter()
+            .enumerate()
+            .find_map(|(index, active)| Some((index, active.chunk.fit(size, alignment)?)))
+        {
+            let active = &mut self.active_chunks[index];
+            active.chunk.offset = offset + size;
+            return (active, offset);
+        }
+
+        let mut chunk =
+            if let Some(index) = self.free_chunks.iter().position(|chunk| size <= chunk.size) {
+                self.stats.reused_chunks += 1;
+                self.free_chunks.swap_remove(index)
+            } else {
+                let size = self.chunk_size.max(size);
+                self.stats.allocated_chunks += 1;
+                self.stats.allocated_bytes += size;
+                Chunk {
+                    buffer: device.create_buffer(&BufferDescriptor {
+                        label: Some("download"),
+                        size,
+                        usage: BufferUsages::MAP_READ | BufferUsages::COPY_DST,
+                        mapped_at_creation: false,
+                    }),
+                    size,
+                    offset: 0,
+                }
+            };
+        chunk.offset = size;
+        self.active_chunks.push(DownloadChunk {
+            chunk,
+            reads: Vec::new(),
+        });
+        (self.active_chunks.last_mut().unwrap(), 0)
+    }
+
+    /// Schedule a read of `size` bytes from the `source` buffer at the specified offset.
+    ///
+    /// `callback` is called with the data once `recall` is done with the chunk it was read into.
+    /// It isn't called at all if the chunk can't be mapped.
+    ///
+    /// The download will be placed into the provided command encoder. This encoder
+    /// must be submitted after `finish` is called and before `recall` is called.
+    pub fn read_buffer(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        source: &Buffer,
+        offset: BufferAddress,
+        size: BufferSize,
+        device: &Device,
+        callback: impl FnOnce(&[u8]) + Send + 'static,
+    ) {
+        let (active, chunk_offset) = self.allocate(size.get(), MAP_ALIGNMENT, device);
+        encoder.copy_buffer_to_buffer(
+            source,
+            offset,
+            &active.chunk.buffer,
+            chunk_offset,
+            size.get(),
+        );
+        active.reads.push(PendingRead {
+            offset: chunk_offset,
+            bytes_per_row: size.get() as usize,
+            padded_bytes_per_row: size.get() as usize,
+            row_count: 1,
+            callback: Box::new(callback),
+        });
+    }
+
+    /// Schedule a read of `size` texels from the `source` texture.
+    ///
+    /// `format` is the format of the texture. `callback` is called with the tightly packed rows
+    /// of the copy once `recall` is done with the chunk it was read into, and isn't called
+    /// at all if the chunk can't be mapped.
+    ///
+    /// The download will be placed into the provided command encoder. This encoder
+    /// must be submitted after `finish` is called and before `recall` is called.
+    pub fn read_texture(
+        &mut self,
+        encoder: &mut CommandEncoder,
+        source: ImageCopyTexture,
+        format: TextureFormat,
+        size: Extent3d,
+        device: &Device,
+        callback: impl FnOnce(&[u8]) + Send + 'static,
+    ) {
+        let layout = TextureCopyLayout::new(format, source.aspect, size);
+        let (active, chunk_offset) = self.allocate(layout.size(), layout.alignment, device);
+        encoder.copy_texture_to_buffer(
+            source,
+            layout.image_copy_buffer(&active.chunk.buffer, chunk_offset),
+            size,
+        );
+        active.reads.push(PendingRead {
+            offset: chunk_offset,
+            bytes_per_row: layout.bytes_per_row as usize,
+            padded_bytes_per_row: layout.padded_bytes_per_row as usize,
+            row_count: layout.row_count as usize,
+            callback: Box::new(callback),
+        });
+    }
+
+    /// Close the chunks used by the reads so far.
+    ///
+    /// At this point, all the partially used buffers are closed until
+    /// the GPU is done copying the data into them.
+    pub fn finish(&mut self) {
+        self.closed_chunks.append(&mut self.active_chunks);
+    }
+
+    /// Map all of the closed buffers, call the callbacks of their reads,
+    /// and recall the buffers back to be reused.
+    ///
+    /// This has to be called after the command encoders written to by `read_buffer`
+    /// and `read_texture` are submitted!
+    pub fn recall(&mut self) -> impl Future<Output = ()> + Send {
+        while let Ok(mut chunk) = self.receiver.try_recv() {
+            chunk.offset = 0;
+            self.free_chunks.push(chunk);
+        }
+
+        let sender = &self.sender;
+        let futures = self
+            .closed_chunks
+            .drain(..)
+            .map(|DownloadChunk { chunk, reads }| {
+                let sender = sender.clone();
+                let async_buffer = chunk.buffer.slice(..).map_async(MapMode::Read);
+
+                Some(async move {
+                    if async_buffer.await.is_ok() {
+                        {
+                            let mapped = chunk.buffer.slice(..).get_mapped_range();
+                            for read in reads {
+                                read.complete(&mapped);
+                            }
+                        }
+                        chunk.buffer.unmap();
+                    }
+
+                    // The only possible error is the other side disconnecting, which is fine
+                    let _ = sender.send(chunk);
+                })
+            })
+            .collect::<Vec<_>>();
+
+        Join { futures }
+    }
+}
@@ -0,0 +1,279 @@
+use std::{error, fmt, future::Future, num::NonZeroU32, ops::Range};
+
+use crate::{
+    BufferAddress, BufferAsyncError, BufferDescriptor, BufferUsages, CommandEncoderDescriptor,
+    Device, Extent3d, ImageCopyBuffer, ImageCopyTexture, ImageDataLayout, ImageSubresourceRange,
+    MapMode, Origin3d, Queue, Texture, TextureAspect, TextureDescriptor, TextureDimension,
+    COPY_BYTES_PER_ROW_ALIGNMENT,
+};
+
+/// Error returned by [`DownloadTexture::read_texture`].
+#[derive(Clone, Debug, PartialEq)]
+pub enum DownloadTextureError {
+    /// The mip levels of the range don't exist in the texture.
+    InvalidMipLevels {
+        /// Requested mip levels.
+        range: Range<u32>,
+        /// Number of mip levels of the texture.
+        count: u32,
+    },
+    /// The array layers of the range don't exist in the texture.
+    InvalidArrayLayers {
+        /// Requested array layers.
+        range: Range<u32>,
+        /// Number of array layers of the texture.
+        count: u32,
+    },
+    /// Mapping the staging buffer failed.
+    Map(BufferAsyncError),
+}
+
+impl fmt::Display for DownloadTextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::InvalidMipLevels { ref range, count } => write!(
+                f,
+                "Mip levels {:?} are out of bounds of a texture with {} mip levels",
+                range, count
+            ),
+            Self::InvalidArrayLayers { ref range, count } => write!(
+                f,
+                "Array layers {:?} are out of bounds of a texture with {} array layers",
+                range, count
+            ),
+            Self::Map(ref error) => error.fmt(f),
+        }
+    }
+}
+
+impl error::Error for DownloadTextureError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::Map(ref error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+impl From<BufferAsyncError> for DownloadTextureError {
+    fn from(error: BufferAsyncError) -> Self {
+        Self::Map(error)
+    }
+}
+
+/// Layout of a subresource in the staging buffer.
+#[derive(Clone, Debug)]
+struct StagingLayout {
+    array_layer: u32,
+    mip_level: u32,
+    offset: BufferAddress,
+    padded_bytes_per_row: u32,
+    bytes_per_row: u32,
+    rows_per_image: u32,
+    copy_size: Extent3d,
+}
+
+/// Location of a subresource in the data of a [`DownloadTexture`].
+#[derive(Clone, Debug)]
+struct DownloadedSubresource {
+    array_layer: u32,
+    mip_level: u32,
+    range: Range<usize>,
+}
+
+/// CPU copy of texture subresources downloaded from the GPU.
+///
+/// The data is tightly packed in the same layout [`DeviceExt::create_texture_with_data`] expects:
+/// each array layer in order, with all of its mip levels.
+/// Rows of block-compressed formats contain a row of blocks,
+/// and mip levels are stored in their physical size, rounded up to whole blocks.
+///
+/// [`DeviceExt::create_texture_with_data`]: super::DeviceExt::create_texture_with_data
+#[derive(Clone, Debug)]
+pub struct DownloadTexture {
+    data: Vec<u8>,
+    subresources: Vec<DownloadedSubresource>,
+}
+
+impl DownloadTexture {
+    /// Asynchronously read the subresources of a texture within `range`.
+    ///
+    /// `desc` is the descriptor `texture` was created with, and `texture` needs the `COPY_SRC` usage.
+    /// For 3D textures, the array layer range is ignored and all depth slices are read.
+    ///
+    /// Returns an error if `range` selects mip levels or array layers the texture doesn't have.
+    pub fn read_texture(
+        device: &Device,
+        queue: &Queue,
+        texture: &Texture,
+        desc: &TextureDescriptor,
+        range: &ImageSubresourceRange,
+    ) -> impl Future<Output = Result<Self, DownloadTextureError>> + Send {
+        let staged = Self::stage(device, queue, texture, desc, range).map(|(download, layouts)| {
+            let fut = download.slice(..).map_async(MapMode::Read);
+            (download, layouts, fut)
+        });
+        async move {
+            let (download, layouts, fut) = staged?;
+            fut.await?;
+
+            let mut data = Vec::new();
+            let mut subresources = Vec::with_capacity(layouts.len());
+            {
+                let mapped = download.slice(..).get_mapped_range();
+                for layout in layouts {
+                    let start = data.len();
+                    let row_count = layout.rows_per_image * layout.copy_size.depth_or_array_layers;
+                    let padded_rows = mapped[layout.offset as usize..]
+                        .chunks(layout.padded_bytes_per_row as usize)
+                        .take(row_count as usize);
+                    for row in padded_rows {
+                        data.extend_from_slice(&row[..layout.bytes_per_row as usize]);
+                    }
+                    subresources.push(DownloadedSubresource {
+                        array_layer: layout.array_layer,
+                        mip_level: layout.mip_level,
+                        range: start..data.len(),
+                    });
+                }
+            }
+            download.unmap();
+
+            Ok(Self { data, subresources })
+        }
+    }
+
+    /// Validates `range` and records the copies of its subresources into a staging buffer.
+    fn stage(
+        device: &Device,
+        queue: &Queue,
+        texture: &Texture,
+        desc: &TextureDescriptor,
+        range: &ImageSubresourceRange,
+    ) -> Result<(crate::Buffer, Vec<StagingLayout>), DownloadTextureError> {
+        let format_info = desc.format.describe();
+        let (block_width, block_height) = format_info.block_dimensions;
+        // The stencil aspect of depth-stencil formats is always copied as 8 bits.
+        let block_size = match range.aspect {
+            TextureAspect::StencilOnly => 1,
+            _ => format_info.block_size as u32,
+        };
+
+        let mip_levels = range.base_mip_level..match range.mip_level_count {
+            Some(count) => range.base_mip_level.saturating_add(count.get()),
+            None => desc.mip_level_count.max(range.base_mip_level),
+        };
+        if mip_levels.is_empty() || mip_levels.end > desc.mip_level_count {
+            return Err(DownloadTextureError::InvalidMipLevels {
+                range: mip_levels,
+                count: desc.mip_level_count,
+            });
+        }
+        let array_layers = match desc.dimension {
+            TextureDimension::D3 => 0..1,
+            _ => {
+                let count = desc.array_layer_count();
+                let array_layers = range.base_array_layer..match range.array_layer_count {
+                    Some(count) => range.base_array_layer.saturating_add(count.get()),
+                    None => count.max(range.base_array_layer),
+                };
+                if array_layers.is_empty() || array_layers.end > count {
+                    return Err(DownloadTextureError::InvalidArrayLayers {
+                        range: array_layers,
+                        count,
+                    });
+                }
+                array_layers
+            }
+        };
+
+        let mut layouts = Vec::new();
+        let mut staging_size = 0;
+        for array_layer in array_layers {
+            for mip_level in mip_levels.clone() {
+                // The range was checked against the mip level count above.
+                let mut mip_size = desc.mip_level_size(mip_level).unwrap();
+                if desc.dimension != TextureDimension::D3 {
+                    mip_size.depth_or_array_layers = 1;
+                }
+                let mip_physical = mip_size.physical_size(desc.format);
+                let bytes_per_row = mip_physical.width / block_width as u32 * block_size;
+                let rows_per_image = mip_physical.height / block_height as u32;
+                let padded_bytes_per_row = (bytes_per_row + COPY_BYTES_PER_ROW_ALIGNMENT - 1)
+                    / COPY_BYTES_PER_ROW_ALIGNMENT
+                    * COPY_BYTES_PER_ROW_ALIGNMENT;
+
+                layouts.push(StagingLayout {
+                    array_layer,
+                    mip_level,
+                    offset: staging_size,
+                    padded_bytes_per_row,
+                    bytes_per_row,
+                    rows_per_image,
+                    copy_size: mip_physical,
+                });
+                staging_size += padded_bytes_per_row as BufferAddress
+                    * rows_per_image as BufferAddress
+                    * mip_physical.depth_or_array_layers as BufferAddress;
+            }
+        }
+
+        let download = device.create_buffer(&BufferDescriptor {
+            label: Some("download texture"),
+            size: staging_size,
+            usage: BufferUsages::COPY_DST | BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("download texture"),
+        });
+        for layout in layouts.iter() {
+            encoder.copy_texture_to_buffer(
+                ImageCopyTexture {
+                    texture,
+                    mip_level: layout.mip_level,
+                    origin: Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layout.array_layer,
+                    },
+                    aspect: range.aspect,
+                },
+                ImageCopyBuffer {
+                    buffer: &download,
+                    layout: ImageDataLayout {
+                        offset: layout.offset,
+                        bytes_per_row: NonZeroU32::new(layout.padded_bytes_per_row),
+                        rows_per_image: NonZeroU32::new(layout.rows_per_image),
+                    },
+                },
+                layout.copy_size,
+            );
+        }
+        queue.submit(Some(encoder.finish()));
+
+        Ok((download, layouts))
+    }
+
+    /// Returns the data of a single subresource, or `None` if it wasn't downloaded.
+    ///
+    /// For 3D textures, `array_layer` has to be 0, and the data contains all depth slices.
+    pub fn subresource(&self, array_layer: u32, mip_level: u32) -> Option<&[u8]> {
+        self.subresources
+            .iter()
+            .find(|sub| sub.array_layer == array_layer && sub.mip_level == mip_level)
+            .map(|sub| &self.data[sub.range.clone()])
+    }
+
+    /// Consumes the download, returning the data of all subresources.
+    pub fn into_data(self) -> Vec<u8> {
+        self.data
+    }
+}
+
+impl std::ops::Deref for DownloadTexture {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        &self.data
+    }
+}
@@ -0,0 +1,756 @@
+use std::{
+    cmp::Reverse,
+    collections::BinaryHeap,
+    error,
+    fmt::{self, Display, Write as _},
+};
+
+use crate::{
+    Buffer, BufferDescriptor, CommandEncoder, Device, Texture, TextureDescriptor, TextureView,
+    TextureViewDescriptor,
+};
+
+/// Version of a resource of a [`RenderGraph`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+struct ResourceVersion {
+    index: usize,
+    version: u32,
+}
+
+/// Handle to a version of a texture of a [`RenderGraph`].
+///
+/// Every write of a texture by a pass creates a new version, and a pass reading
+/// a version depends on the pass that wrote it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct TextureHandle(ResourceVersion);
+
+/// Handle to a version of a buffer of a [`RenderGraph`].
+///
+/// Every write of a buffer by a pass creates a new version, and a pass reading
+/// a version depends on the pass that wrote it.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct BufferHandle(ResourceVersion);
+
+enum ResourceSource<'a> {
+    ImportedTexture(&'a Texture),
+    TransientTexture(TextureDescriptor<'a>),
+    ImportedBuffer(&'a Buffer),
+    TransientBuffer(BufferDescriptor<'static>),
+}
+
+impl ResourceSource<'_> {
+    fn is_imported(&self) -> bool {
+        matches!(*self, Self::ImportedTexture(_) | Self::ImportedBuffer(_))
+    }
+}
+
+struct ResourceNode<'a> {
+    name: String,
+    source: ResourceSource<'a>,
+    /// Pass writing each version of the resource, the initial version has no writer.
+    writers: Vec<Option<usize>>,
+}
+
+type PassCallback<'a> = Box<dyn FnOnce(&mut CommandEncoder, &GraphResources) + 'a>;
+
+struct PassNode<'a> {
+    name: String,
+    reads: Vec<ResourceVersion>,
+    /// Versions created by the pass.
+    writes: Vec<ResourceVersion>,
+    side_effect: bool,
+    callback: Option<PassCallback<'a>>,
+}
+
+impl PassNode<'_> {
+    fn accesses(&self) -> impl Iterator<Item = ResourceVersion> + '_ {
+        self.reads.iter().chain(self.writes.iter()).copied()
+    }
+}
+
+/// Error recording a [`RenderGraph`].
+#[derive(Clone, PartialEq, Eq, Debug)]
+pub enum RenderGraphError {
+    /// The dependencies between passes are cyclic, so the passes can't be ordered.
+    ///
+    /// This happens when a pass reads an old version of a resource, and the pass
+    /// writing the next version depends on it.
+    Cycle {
+        /// Names of the passes that couldn't be ordered.
+        passes: Vec<String>,
+    },
+}
+
+impl Display for RenderGraphError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Cycle { ref passes } => write!(
+                f,
+                "The dependencies between the passes {:?} are cyclic",
+                passes
+            ),
+        }
+    }
+}
+
+impl error::Error for RenderGraphError {}
+
+/// Frame graph scheduling passes recorded into a command encoder.
+///
+/// Passes declare the textures and buffers they read and write, and the graph:
+/// - culls the passes whose results are never used, keeping the ones that write imported
+///   resources or that are marked with [`PassBuilder::side_effect`],
+/// - orders the passes after the ones they depend on, preserving the declaration order
+///   between independent passes,
+/// - creates the transient resources, sharing a texture or buffer between transient
+///   resources with the same descriptor and disjoint lifetimes,
+/// - and records the passes into a command encoder.
+///
+/// A graph is meant to be built for every frame. The content of transient resources is undefined
+/// before their first write, as their memory may be aliased with the ones of other resources.
+///
+/// [`RenderGraph::to_dot`] dumps the graph in the DOT format of Graphviz for debugging.
+#[derive(Default)]
+pub struct RenderGraph<'a> {
+    resources: Vec<ResourceNode<'a>>,
+    passes: Vec<PassNode<'a>>,
+}
+
+impl<'a> RenderGraph<'a> {
+    /// Creates an empty render graph.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn add_resource(&mut self, name: &str, source: ResourceSource<'a>) -> ResourceVersion {
+        self.resources.push(ResourceNode {
+            name: name.to_string(),
+            source,
+            writers: vec![None],
+        });
+        ResourceVersion {
+            index: self.resources.len() - 1,
+            version: 0,
+        }
+    }
+
+    /// Imports a texture created outside of the graph, like the texture of a surface frame.
+    ///
+    /// Passes writing an imported texture are never culled.
+    pub fn import_texture(&mut self, name: &str, texture: &'a Texture) -> TextureHandle {
+        TextureHandle(self.add_resource(name, ResourceSource::ImportedTexture(texture)))
+    }
+
+    /// Declares a transient texture, created by the graph when it is recorded.
+    ///
+    /// The label of `desc` is ignored, the texture is labelled with `name`.
+    pub fn create_texture(&mut self, name: &str, desc: &TextureDescriptor<'a>) -> TextureHandle {
+        let desc = TextureDescriptor {
+            label: None,
+            ..desc.clone()
+        };
+        TextureHandle(self.add_resource(name, ResourceSource::TransientTexture(desc)))
+    }
+
+    /// Imports a buffer created outside of the graph.
+    ///
+    /// Passes writing an imported buffer are never culled.
+    pub fn import_buffer(&mut self, name: &str, buffer: &'a Buffer) -> BufferHandle {
+        BufferHandle(self.add_resource(name, ResourceSource::ImportedBuffer(buffer)))
+    }
+
+    /// Declares a transient buffer, created by the graph when it is recorded.
+    ///
+    /// The label of `desc` is ignored, the buffer is labelled with `name`.
+    ///
+    /// # Panics
+    ///
+    /// - `desc.mapped_at_creation` is `true`.
+    pub fn create_buffer(&mut self, name: &str, desc: &BufferDescriptor) -> BufferHandle {
+        assert!(
+            !desc.mapped_at_creation,
+            "transient buffers can't be mapped at creation"
+        );
+        let desc = BufferDescriptor {
+            label: None,
+            ..desc.clone()
+        };
+        BufferHandle(self.add_resource(name, ResourceSource::TransientBuffer(desc)))
+    }
+
+    /// Adds a pass to the graph, declaring its resources with the returned builder.
+    pub fn add_pass(&mut self, name: &str) -> PassBuilder<'_, 'a> {
+        self.passes.push(PassNode {
+            name: name.to_string(),
+            reads: Vec::new(),
+            writes: Vec::new(),
+            side_effect: false,
+            callback: None,
+        });
+        PassBuilder {
+            index: self.passes.len() - 1,
+            graph: self,
+        }
+    }
+
+    fn writer(&self, resource: ResourceVersion) -> Option<usize> {
+        self.resources[resource.index].writers[resource.version as usize]
+    }
+
+    /// Returns the passes each pass reads the results of.
+    fn data_dependencies(&self) -> Vec<Vec<usize>> {
+        self.passes
+            .iter()
+            .enumerate()
+            .map(|(index, pass)| {
+                let mut dependencies = pass
+                    .reads
+                    .iter()
+                    .filter_map(|&read| self.writer(read))
+                    .filter(|&writer| writer != index)
+                    .collect::<Vec<_>>();
+                dependencies.sort_unstable();
+                dependencies.dedup();
+                dependencies
+            })
+            .collect()
+    }
+
+    /// Returns which passes are kept after culling.
+    fn kept_passes(&self, data_dependencies: &[Vec<usize>]) -> Vec<bool> {
+        let mut kept = vec![false; self.passes.len()];
+        let mut stack = self
+            .passes
+            .iter()
+            .enumerate()
+            .filter(|&(_, pass)| {
+                pass.side_effect
+                    || pass
+                        .writes
+                        .iter()
+                        .any(|write| self.resources[write.index].source.is_imported())
+            })
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        while let Some(index) = stack.pop() {
+            if !kept[index] {
+                kept[index] = true;
+                stack.extend_from_slice(&data_dependencies[index]);
+            }
+        }
+        kept
+    }
+
+    /// Orders the kept passes, returning their indices in execution order.
+    fn schedule(
+        &self,
+        data_dependencies: &[Vec<usize>],
+        kept: &[bool],
+    ) -> Result<Vec<usize>, RenderGraphError> {
+        let mut successors = vec![Vec::new(); self.passes.len()];
+        for (index, dependencies) in data_dependencies.iter().enumerate() {
+            for &dependency in dependencies {
+                successors[dependency].push(index);
+            }
+        }
+        // A pass writing a version has to run after the pass writing the previous one.
+        for (index, pass) in self.passes.iter().enumerate() {
+            for write in pass.writes.iter() {
+                let previous = ResourceVersion {
+                    version: write.version - 1,
+                    ..*write
+                };
+                if let Some(writer) = self.writer(previous) {
+                    if writer != index {
+                        successors[writer].push(index);
+                    }
+                }
+            }
+        }
+        // A pass reading a version has to run before the pass writing the next one.
+        for (reader, pass) in self.passes.iter().enumerate() {
+            for read in pass.reads.iter() {
+                let next = ResourceVersion {
+                    version: read.version + 1,
+                    ..*read
+                };
+                if let Some(writer) = self.resources[read.index]
+                    .writers
+                    .get(next.version as usize)
+                    .copied()
+                    .flatten()
+                {
+                    if writer != reader {
+                        successors[reader].push(writer);
+                    }
+                }
+            }
+        }
+
+        let mut predecessor_counts = vec![0; self.passes.len()];
+        for (index, successors) in successors.iter().enumerate() {
+            if kept[index] {
+                for &successor in successors.iter() {
+                    predecessor_counts[successor] += 1;
+                }
+            }
+        }
+
+        let mut ready = (0..self.passes.len())
+            .filter(|&index| kept[index] && predecessor_counts[index] == 0)
+            .map(Reverse)
+            .collect::<BinaryHeap<_>>();
+        let mut order = Vec::new();
+        while let Some(Reverse(index)) = ready.pop() {
+            order.push(index);
+            for &successor in successors[index].iter() {
+                predecessor_counts[successor] -= 1;
+                if kept[successor] && predecessor_counts[successor] == 0 {
+                    ready.push(Reverse(successor));
+                }
+            }
+        }
+
+        if order.len() != kept.iter().filter(|&&kept| kept).count() {
+            let passes = (0..self.passes.len())
+                .filter(|&index| kept[index] && predecessor_counts[index] != 0)
+                .map(|index| self.passes[index].name.clone())
+                .collect();
+            return Err(RenderGraphError::Cycle { passes });
+        }
+        Ok(order)
+    }
+
+    /// Assigns the transient resources to physical resources, returning the index
+    /// of the physical resource of each resource used by the scheduled passes,
+    /// and the resource standing for each physical resource.
+    fn alias_resources(&self, order: &[usize]) -> (Vec<Option<usize>>, Vec<usize>) {
+        let mut lifetimes = vec![None; self.resources.len()];
+        for (position, &pass) in order.iter().enumerate() {
+            for access in self.passes[pass].accesses() {
+                let lifetime = lifetimes[access.index].get_or_insert(position..position);
+                lifetime.end = position;
+            }
+        }
+
+        let mut used = (0..self.resources.len())
+            .filter_map(|index| Some((lifetimes[index].clone()?, index)))
+            .collect::<Vec<_>>();
+        used.sort_by_key(|&(ref lifetime, index)| (lifetime.start, index));
+
+        // Resource standing for each physical resource, and the end of its last lifetime.
+        let mut physical: Vec<(usize, usize)> = Vec::new();
+        let mut assignments = vec![None; self.resources.len()];
+        for (lifetime, index) in used {
+            let source = &self.resources[index].source;
+            let reusable = physical.iter().position(|&(owner, end)| {
+                end < lifetime.start
+                    && match (source, &self.resources[owner].source) {
+                        (
+                            &ResourceSource::TransientTexture(ref desc),
+                            &ResourceSource::TransientTexture(ref other),
+                        ) => desc == other,
+                        (
+                            &ResourceSource::TransientBuffer(ref desc),
+                            &ResourceSource::TransientBuffer(ref other),
+                        ) => desc == other,
+                        _ => false,
+                    }
+            });
+            assignments[index] = Some(match reusable {
+                Some(slot) => {
+                    physical[slot].1 = lifetime.end;
+                    slot
+                }
+                None => {
+                    physical.push((index, lifetime.end));
+                    physical.len() - 1
+                }
+            });
+        }
+        let owners = physical.into_iter().map(|(owner, _)| owner).collect();
+        (assignments, owners)
+    }
+
+    /// Culls, orders and records the passes into `encoder`.
+    ///
+    /// Every pass is recorded within a debug group named after it.
+    pub fn record(
+        self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+    ) -> Result<(), RenderGraphError> {
+        let data_dependencies = self.data_dependencies();
+        let kept = self.kept_passes(&data_dependencies);
+        let order = self.schedule(&data_dependencies, &kept)?;
+        let (assignments, owners) = self.alias_resources(&order);
+        let physical = owners
+            .iter()
+            .map(|&owner| {
+                let resource = &self.resources[owner];
+                let label = Some(resource.name.as_str());
+                match resource.source {
+                    ResourceSource::ImportedTexture(texture) => PhysicalResource::Texture(
+                        None,
+                        texture.create_view(&TextureViewDescriptor::default()),
+                    ),
+                    ResourceSource::TransientTexture(ref desc) => {
+                        let texture = device.create_texture(&TextureDescriptor {
+                            label,
+                            ..desc.clone()
+                        });
+                        let view = texture.create_view(&TextureViewDescriptor::default());
+                        PhysicalResource::Texture(Some(texture), view)
+                    }
+                    ResourceSource::ImportedBuffer(_) => PhysicalResource::ImportedBuffer,
+                    ResourceSource::TransientBuffer(ref desc) => {
+                        PhysicalResource::Buffer(device.create_buffer(&BufferDescriptor {
+                            label,
+                            ..desc.clone()
+                        }))
+                    }
+                }
+            })
+            .collect::<Vec<_>>();
+
+        let mut passes = self.passes;
+        for index in order {
+            let pass = &mut passes[index];
+            let resources = GraphResources {
+                pass: &pass.name,
+                accessible: pass.accesses().map(|access| access.index).collect(),
+                resources: &self.resources,
+                assignments: &assignments,
+                physical: &physical,
+            };
+            if let Some(callback) = pass.callback.take() {
+                encoder.push_debug_group(&pass.name);
+                callback(encoder, &resources);
+                encoder.pop_debug_group();
+            }
+        }
+        Ok(())
+    }
+
+    /// Dumps the graph in the DOT format of Graphviz.
+    ///
+    /// Passes are boxes, and resource versions are ellipses, with a double border for
+    /// imported resources. Culled passes are dashed, and an edge goes from each pass to the
+    /// versions it writes, and from each version to the passes reading it.
+    pub fn to_dot(&self) -> String {
+        let data_dependencies = self.data_dependencies();
+        let kept = self.kept_passes(&data_dependencies);
+
+        let mut dot = String::from("digraph \"render graph\" {\n");
+        for (index, pass) in self.passes.iter().enumerate() {
+            let style = if kept[index] {
+                ""
+            } else {
+                ", style=dashed, color=gray"
+            };
+            writeln!(
+                dot,
+                "    p{} [label=\"{}\", shape=box{}];",
+                index,
+                escape(&pass.name),
+                style
+            )
+            .unwrap();
+        }
+
+        let mut versions = self
+            .passes
+            .iter()
+            .flat_map(|pass| pass.accesses())
+            .collect::<Vec<_>>();
+        versions.sort_by_key(|version| (version.index, version.version));
+        versions.dedup();
+        for version in versions {
+            let resource = &self.resources[version.index];
+            let peripheries = if resource.source.is_imported() { 2 } else { 1 };
+            writeln!(
+                dot,
+                "    r{}_{} [label=\"{} v{}\", shape=ellipse, peripheries={}];",
+                version.index,
+                version.version,
+                escape(&resource.name),
+                version.version,
+                peripheries
+            )
+            .unwrap();
+        }
+
+        for (index, pass) in self.passes.iter().enumerate() {
+            for read in pass.reads.iter() {
+                writeln!(dot, "    r{}_{} -> p{};", read.index, read.version, index).unwrap();
+            }
+            for write in pass.writes.iter() {
+                writeln!(dot, "    p{} -> r{}_{};", index, write.index, write.version).unwrap();
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+fn escape(name: &str) -> String {
+    name.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+/// Declares the resources of a pass of a [`RenderGraph`].
+pub struct PassBuilder<'g, 'a> {
+    graph: &'g mut RenderGraph<'a>,
+    index: usize,
+}
+
+impl<'a> PassBuilder<'_, 'a> {
+    fn read(&mut self, resource: ResourceVersion) {
+        self.graph.passes[self.index].reads.push(resource);
+    }
+
+    fn write(&mut self, resource: ResourceVersion) -> ResourceVersion {
+        let node = &mut self.graph.resources[resource.index];
+        assert_eq!(
+            resource.version as usize + 1,
+            node.writers.len(),
+            "{:?} has already been written to, write to its latest version instead",
+            node.name
+        );
+        node.writers.push(Some(self.index));
+        let written = ResourceVersion {
+            version: resource.version + 1,
+            ..resource
+        };
+        self.graph.passes[self.index].writes.push(written);
+        written
+    }
+
+    /// Declares that the pass reads the given version of a texture.
+    pub fn read_texture(&mut self, texture: TextureHandle) {
+        self.read(texture.0);
+    }
+
+    /// Declares that the pass writes a texture, returning the new version.
+    ///
+    /// The written version has to be the latest one. The pass doesn't depend on
+    /// its previous content unless it also reads it.
+    ///
+    /// # Panics
+    ///
+    /// - A newer version of the texture has already been written.
+    pub fn write_texture(&mut self, texture: TextureHandle) -> TextureHandle {
+        TextureHandle(self.write(texture.0))
+    }
+
+    /// Declares that the pass reads the given version of a buffer.
+    pub fn read_buffer(&mut self, buffer: BufferHandle) {
+        self.read(buffer.0);
+    }
+
+    /// Declares that the pass writes a buffer, returning the new version.
+    ///
+    /// The written version has to be the latest one. The pass doesn't depend on
+    /// its previous content unless it also reads it.
+    ///
+    /// # Panics
+    ///
+    /// - A newer version of the buffer has already been written.
+    pub fn write_buffer(&mut self, buffer: BufferHandle) -> BufferHandle {
+        BufferHandle(self.write(buffer.0))
+    }
+
+    /// Marks the pass as having effects outside of the graph, so that it is never culled.
+    pub fn side_effect(&mut self) {
+        self.graph.passes[self.index].side_effect = true;
+    }
+
+    /// Sets the function recording the commands of the pass.
+    ///
+    /// It is called with the resources of the graph when the graph is recorded.
+    pub fn execute(self, callback: impl FnOnce(&mut CommandEncoder, &GraphResources) + 'a) {
+        self.graph.passes[self.index].callback = Some(Box::new(callback));
+    }
+}
+
+enum PhysicalResource {
+    /// A texture created by the graph or an imported one, with its default view.
+    Texture(Option<Texture>, TextureView),
+    Buffer(Buffer),
+    ImportedBuffer,
+}
+
+/// Resources of a [`RenderGraph`] accessible by the pass being recorded.
+pub struct GraphResources<'r> {
+    pass: &'r str,
+    accessible: Vec<usize>,
+    resources: &'r [ResourceNode<'r>],
+    assignments: &'r [Option<usize>],
+    physical: &'r [PhysicalResource],
+}
+
+impl GraphResources<'_> {
+    fn physical(&self, resource: ResourceVersion) -> &PhysicalResource {
+        assert!(
+            self.accessible.contains(&resource.index),
+            "pass {:?} didn't declare an access to {:?}",
+            self.pass,
+            self.resources[resource.index].name
+        );
+        &self.physical[self.assignments[resource.index].unwrap()]
+    }
+
+    /// Returns a texture declared by the pass.
+    ///
+    /// # Panics
+    ///
+    /// - The pass didn't declare that it reads or writes the texture.
+    pub fn texture(&self, texture: TextureHandle) -> &Texture {
+        let physical = self.physical(texture.0);
+        match (&self.resources[texture.0.index].source, physical) {
+            (&ResourceSource::ImportedTexture(texture), _) => texture,
+            (_, &PhysicalResource::Texture(Some(ref texture), _)) => texture,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns the default view of a texture declared by the pass.
+    ///
+    /// # Panics
+    ///
+    /// - The pass didn't declare that it reads or writes the texture.
+    pub fn texture_view(&self, texture: TextureHandle) -> &TextureView {
+        match *self.physical(texture.0) {
+            PhysicalResource::Texture(_, ref view) => view,
+            _ => unreachable!(),
+        }
+    }
+
+    /// Returns a buffer declared by the pass.
+    ///
+    /// # Panics
+    ///
+    /// - The pass didn't declare that it reads or writes the buffer.
+    pub fn buffer(&self, buffer: BufferHandle) -> &Buffer {
+        let physical = self.physical(buffer.0);
+        match (&self.resources[buffer.0.index].source, physical) {
+            (&ResourceSource::ImportedBuffer(buffer), _) => buffer,
+            (_, &PhysicalResource::Buffer(ref buffer)) => buffer,
+            _ => unreachable!(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Extent3d, TextureDimension, TextureFormat, TextureUsages};
+
+    const DESC: TextureDescriptor = TextureDescriptor {
+        label: None,
+        size: Extent3d {
+            width: 64,
+            height: 64,
+            depth_or_array_layers: 1,
+        },
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: TextureDimension::D2,
+        format: TextureFormat::Rgba8Unorm,
+        usage: TextureUsages::RENDER_ATTACHMENT,
+        view_formats: &[],
+    };
+
+    fn schedule(graph: &RenderGraph) -> Result<Vec<usize>, RenderGraphError> {
+        let data_dependencies = graph.data_dependencies();
+        let kept = graph.kept_passes(&data_dependencies);
+        graph.schedule(&data_dependencies, &kept)
+    }
+
+    #[test]
+    fn culls_and_orders_passes() {
+        let mut graph = RenderGraph::new();
+        let color = graph.create_texture("color", &DESC);
+        let unused = graph.create_texture("unused", &DESC);
+
+        let color = graph.add_pass("draw").write_texture(color);
+        graph.add_pass("unused").write_texture(unused);
+        let mut pass = graph.add_pass("overwrite");
+        pass.write_texture(color);
+        pass.side_effect();
+        let mut pass = graph.add_pass("read");
+        pass.read_texture(color);
+        pass.side_effect();
+
+        // Reading the first version has to happen before it's overwritten.
+        assert_eq!(schedule(&graph), Ok(vec![0, 3, 2]));
+        let dot = graph.to_dot();
+        assert!(dot.contains("p1 [label=\"unused\", shape=box, style=dashed, color=gray];"));
+        assert!(dot.contains("r0_1 -> p3;"));
+    }
+
+    #[test]
+    fn aliases_transient_textures() {
+        let mut graph = RenderGraph::new();
+        let first = graph.create_texture("first", &DESC);
+        let second = graph.create_texture("second", &DESC);
+        let third = graph.create_texture("third", &DESC);
+
+        let first = graph.add_pass("first").write_texture(first);
+        let mut pass = graph.add_pass("second");
+        pass.read_texture(first);
+        let second = pass.write_texture(second);
+        let mut pass = graph.add_pass("third");
+        pass.read_texture(second);
+        pass.write_texture(third);
+        pass.side_effect();
+
+        let order = schedule(&graph).unwrap();
+        assert_eq!(order, vec![0, 1, 2]);
+        let (assignments, owners) = graph.alias_resources(&order);
+        assert_eq!(assignments[0], assignments[2]);
+        assert_ne!(assignments[0], assignments[1]);
+        assert_eq!(owners, vec![0, 1]);
+    }
+
+    #[test]
+    fn owns_physical_resources_in_lifetime_order() {
+        let mut graph = RenderGraph::new();
+        // Declared before the resource that is used first.
+        let late = graph.create_texture("late", &DESC);
+        let early = graph.create_texture("early", &DESC);
+
+        let early = graph.add_pass("early").write_texture(early);
+        let mut pass = graph.add_pass("late");
+        pass.read_texture(early);
+        pass.write_texture(late);
+        pass.side_effect();
+
+        let order = schedule(&graph).unwrap();
+        let (assignments, owners) = graph.alias_resources(&order);
+        assert_eq!(assignments, vec![Some(1), Some(0)]);
+        assert_eq!(owners, vec![1, 0]);
+    }
+
+    #[test]
+    fn detects_cycles() {
+        let mut graph = RenderGraph::new();
+        let x = graph.create_texture("x", &DESC);
+        let y = graph.create_texture("y", &DESC);
+
+        let x = graph.add_pass("write x").write_texture(x);
+        let mut pass = graph.add_pass("overwrite x");
+        pass.write_texture(x);
+        let y = pass.write_texture(y);
+        let mut pass = graph.add_pass("read both");
+        pass.read_texture(x);
+        pass.read_texture(y);
+        pass.side_effect();
+
+        assert_eq!(
+            schedule(&graph),
+            Err(RenderGraphError::Cycle {
+                passes: vec!["overwrite x".to_string(), "read both".to_string()]
+            })
+        );
+    }
+}
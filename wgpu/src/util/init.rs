@@ -16,6 +16,7 @@ pub fn backend_bits_from_env() -> Option<Backends> {
             Ok("metal") => Backends::METAL,
             Ok("gl") => Backends::GL,
             Ok("webgpu") => Backends::BROWSER_WEBGPU,
+            Ok("cpu") => Backends::CPU,
             _ => return None,
         },
     )
@@ -0,0 +1,307 @@
+use std::{borrow::Cow, collections::HashMap, num::NonZeroU32};
+
+use crate::{
+    Adapter, BindGroupDescriptor, BindGroupEntry, BindGroupLayout, BindGroupLayoutDescriptor,
+    BindGroupLayoutEntry, BindingResource, BindingType, Color, CommandEncoder,
+    CommandEncoderDescriptor, Device, Features, FilterMode, FragmentState, ImageCopyTexture,
+    ImageDataLayout, LoadOp, MultisampleState, Operations, Origin3d, PipelineLayout,
+    PipelineLayoutDescriptor, PrimitiveState, Queue, RenderPassColorAttachment,
+    RenderPassDescriptor, RenderPipeline, RenderPipelineDescriptor, Sampler, SamplerDescriptor,
+    ShaderModule, ShaderModuleDescriptor, ShaderSource, ShaderStages, Texture, TextureAspect,
+    TextureDescriptor, TextureDimension, TextureFormat, TextureSampleType, TextureUsages,
+    TextureViewDescriptor, TextureViewDimension, VertexState,
+};
+
+/// Generates the mip chain of textures on the GPU.
+///
+/// Every mip level is rendered from the previous one with a linear filter.
+/// sRGB textures are sampled and rendered through sRGB views, so the filtering
+/// happens in linear space.
+///
+/// The generator keeps the render pipelines it creates for each configuration,
+/// and is meant to be reused for all the textures of a device.
+#[derive(Debug)]
+pub struct MipmapGenerator {
+    shader: ShaderModule,
+    sampler: Sampler,
+    /// Pipelines by the format of the rendered mip levels.
+    pipelines: HashMap<TextureFormat, MipmapPipeline>,
+}
+
+#[derive(Debug)]
+struct MipmapPipeline {
+    bind_group_layout: BindGroupLayout,
+    // Kept alive alongside the pipeline that uses it.
+    _pipeline_layout: PipelineLayout,
+    pipeline: RenderPipeline,
+}
+
+impl MipmapGenerator {
+    /// Creates a mipmap generator for `device`.
+    pub fn new(device: &Device) -> Self {
+        let shader = device.create_shader_module(&ShaderModuleDescriptor {
+            label: Some("mipmap"),
+            source: ShaderSource::Wgsl(Cow::Borrowed(include_str!("mipmap.wgsl"))),
+        });
+        let sampler = device.create_sampler(&SamplerDescriptor {
+            label: Some("mipmap"),
+            mag_filter: FilterMode::Linear,
+            min_filter: FilterMode::Linear,
+            ..Default::default()
+        });
+
+        Self {
+            shader,
+            sampler,
+            pipelines: HashMap::new(),
+        }
+    }
+
+    /// Returns `true` if mipmaps can be generated for textures of `format` on `device`,
+    /// which was requested from `adapter`.
+    ///
+    /// The format has to be filterable and renderable. The adapter-specific format features
+    /// are taken into account when the device has
+    /// [`Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES`].
+    pub fn supports_format(adapter: &Adapter, device: &Device, format: TextureFormat) -> bool {
+        let info = format.describe();
+        let features = if device
+            .features()
+            .contains(Features::TEXTURE_ADAPTER_SPECIFIC_FORMAT_FEATURES)
+        {
+            adapter.get_texture_format_features(format)
+        } else {
+            info.guaranteed_format_features
+        };
+        matches!(info.sample_type, TextureSampleType::Float { .. })
+            && features.filterable
+            && features
+                .allowed_usages
+                .contains(TextureUsages::RENDER_ATTACHMENT)
+    }
+
+    fn create_pipeline(
+        device: &Device,
+        shader: &ShaderModule,
+        format: TextureFormat,
+    ) -> MipmapPipeline {
+        let bind_group_layout = device.create_bind_group_layout(&BindGroupLayoutDescriptor {
+            label: Some("mipmap"),
+            entries: &[
+                BindGroupLayoutEntry {
+                    binding: 0,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Texture {
+                        // Only filterable formats are supported.
+                        sample_type: TextureSampleType::Float { filterable: true },
+                        view_dimension: TextureViewDimension::D2,
+                        multisampled: false,
+                    },
+                    count: None,
+                },
+                BindGroupLayoutEntry {
+                    binding: 1,
+                    visibility: ShaderStages::FRAGMENT,
+                    ty: BindingType::Sampler {
+                        filtering: true,
+                        comparison: false,
+                    },
+                    count: None,
+                },
+            ],
+        });
+        let pipeline_layout = device.create_pipeline_layout(&PipelineLayoutDescriptor {
+            label: Some("mipmap"),
+            bind_group_layouts: &[&bind_group_layout],
+            push_constant_ranges: &[],
+        });
+        let pipeline = device.create_render_pipeline(&RenderPipelineDescriptor {
+            label: Some("mipmap"),
+            layout: Some(&pipeline_layout),
+            vertex: VertexState {
+                module: shader,
+                entry_point: "vs_main",
+                buffers: &[],
+            },
+            fragment: Some(FragmentState {
+                module: shader,
+                entry_point: "fs_main",
+                targets: &[format.into()],
+            }),
+            primitive: PrimitiveState::default(),
+            depth_stencil: None,
+            multisample: MultisampleState::default(),
+            cache: None,
+        });
+        MipmapPipeline {
+            bind_group_layout,
+            _pipeline_layout: pipeline_layout,
+            pipeline,
+        }
+    }
+
+    /// Records the generation of mip levels `1..` of `texture` into `encoder`,
+    /// from the contents of mip level 0.
+    ///
+    /// `desc` is the descriptor `texture` was created with. All the array layers
+    /// are processed, which includes the faces of cube textures.
+    ///
+    /// # Panics
+    ///
+    /// - The texture is not 2D, or is multisampled.
+    /// - The format is not a float format.
+    /// - The texture usages don't contain `RENDER_ATTACHMENT` and `TEXTURE_BINDING`.
+    ///
+    /// Formats which are not supported by the device, see [`MipmapGenerator::supports_format`],
+    /// cause validation errors.
+    pub fn generate_mipmaps(
+        &mut self,
+        device: &Device,
+        encoder: &mut CommandEncoder,
+        texture: &Texture,
+        desc: &TextureDescriptor,
+    ) {
+        assert_eq!(
+            desc.dimension,
+            TextureDimension::D2,
+            "mipmaps can only be generated for 2D textures"
+        );
+        assert_eq!(
+            desc.sample_count, 1,
+            "mipmaps can't be generated for multisampled textures"
+        );
+        assert!(
+            matches!(
+                desc.format.describe().sample_type,
+                TextureSampleType::Float { .. }
+            ),
+            "mipmaps can't be generated for {:?} textures",
+            desc.format
+        );
+        assert!(
+            desc.usage
+                .contains(TextureUsages::RENDER_ATTACHMENT | TextureUsages::TEXTURE_BINDING),
+            "mipmap generation requires RENDER_ATTACHMENT and TEXTURE_BINDING usages"
+        );
+
+        let shader = &self.shader;
+        let pipeline = self
+            .pipelines
+            .entry(desc.format)
+            .or_insert_with(|| Self::create_pipeline(device, shader, desc.format));
+        let sampler = &self.sampler;
+        for layer in 0..desc.array_layer_count() {
+            let views = (0..desc.mip_level_count)
+                .map(|mip| {
+                    texture.create_view(&TextureViewDescriptor {
+                        label: Some("mipmap"),
+                        format: None,
+                        dimension: Some(TextureViewDimension::D2),
+                        aspect: TextureAspect::All,
+                        base_mip_level: mip,
+                        mip_level_count: NonZeroU32::new(1),
+                        base_array_layer: layer,
+                        array_layer_count: NonZeroU32::new(1),
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            for target_mip in 1..views.len() {
+                let bind_group = device.create_bind_group(&BindGroupDescriptor {
+                    label: Some("mipmap"),
+                    layout: &pipeline.bind_group_layout,
+                    entries: &[
+                        BindGroupEntry {
+                            binding: 0,
+                            resource: BindingResource::TextureView(&views[target_mip - 1]),
+                        },
+                        BindGroupEntry {
+                            binding: 1,
+                            resource: BindingResource::Sampler(sampler),
+                        },
+                    ],
+                });
+
+                let mut pass = encoder.begin_render_pass(&RenderPassDescriptor {
+                    label: Some("mipmap"),
+                    color_attachments: &[RenderPassColorAttachment {
+                        view: &views[target_mip],
+                        resolve_target: None,
+                        ops: Operations {
+                            load: LoadOp::Clear(Color::TRANSPARENT),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                    occlusion_query_set: None,
+                });
+                pass.set_pipeline(&pipeline.pipeline);
+                pass.set_bind_group(0, &bind_group, &[]);
+                pass.draw(0..3, 0..1);
+            }
+        }
+    }
+
+    /// Creates a texture, uploads the first mip level of each array layer,
+    /// and generates the other mip levels.
+    ///
+    /// Expects the array layers to be tightly packed in `data`.
+    ///
+    /// Implicitly adds the `COPY_DST`, `RENDER_ATTACHMENT` and `TEXTURE_BINDING`
+    /// usages, which are required to upload the data and generate the mipmaps.
+    /// The work is submitted to `queue` before returning.
+    ///
+    /// # Panics
+    ///
+    /// Same as [`MipmapGenerator::generate_mipmaps`].
+    pub fn create_texture_with_data(
+        &mut self,
+        device: &Device,
+        queue: &Queue,
+        desc: &TextureDescriptor,
+        data: &[u8],
+    ) -> Texture {
+        let mut desc = desc.to_owned();
+        desc.usage |= TextureUsages::COPY_DST
+            | TextureUsages::RENDER_ATTACHMENT
+            | TextureUsages::TEXTURE_BINDING;
+        let texture = device.create_texture(&desc);
+
+        let block_size = desc.format.describe().block_size as u32;
+        let bytes_per_row = desc.size.width * block_size;
+        let layer_size = (bytes_per_row * desc.size.height) as usize;
+        for layer in 0..desc.array_layer_count() {
+            let offset = layer as usize * layer_size;
+            queue.write_texture(
+                ImageCopyTexture {
+                    texture: &texture,
+                    mip_level: 0,
+                    origin: Origin3d {
+                        x: 0,
+                        y: 0,
+                        z: layer,
+                    },
+                    aspect: TextureAspect::All,
+                },
+                &data[offset..offset + layer_size],
+                ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(bytes_per_row),
+                    rows_per_image: NonZeroU32::new(desc.size.height),
+                },
+                crate::Extent3d {
+                    depth_or_array_layers: 1,
+                    ..desc.size
+                },
+            );
+        }
+
+        let mut encoder = device.create_command_encoder(&CommandEncoderDescriptor {
+            label: Some("mipmap"),
+        });
+        self.generate_mipmaps(device, &mut encoder, &texture, &desc);
+        queue.submit(Some(encoder.finish()));
+
+        texture
+    }
+}
@@ -2,8 +2,13 @@
 
 mod belt;
 mod device;
+mod download;
 mod encoder;
+mod graph;
 mod init;
+mod mipmap;
+#[cfg(not(target_arch = "wasm32"))]
+mod watcher;
 
 use std::future::Future;
 use std::{
@@ -12,13 +17,22 @@ use std::{
     ptr::copy_nonoverlapping,
 };
 
-pub use belt::StagingBelt;
+pub use belt::{BeltStats, DownloadBelt, StagingBelt, TextureWriteView};
 pub use device::{BufferInitDescriptor, DeviceExt};
+pub use download::{DownloadTexture, DownloadTextureError};
 pub use encoder::RenderEncoder;
+pub use graph::{
+    BufferHandle, GraphResources, PassBuilder, RenderGraph, RenderGraphError, TextureHandle,
+};
 pub use init::{
     backend_bits_from_env, initialize_adapter_from_env, initialize_adapter_from_env_or_default,
     power_preference_from_env,
 };
+pub use mipmap::MipmapGenerator;
+#[cfg(not(target_arch = "wasm32"))]
+pub use watcher::{
+    ComputePipelineHandle, RenderPipelineHandle, ShaderHandle, ShaderReloadError, ShaderWatcher,
+};
 
 /// Treat the given byte slice as a SPIR-V module.
 ///
@@ -70,6 +84,21 @@ pub fn make_spirv_raw(data: &[u8]) -> Cow<[u32]> {
     words
 }
 
+/// Returns a file name for storing the data of a [`PipelineCache`] for this adapter.
+///
+/// Returns `None` if the backend doesn't support pipeline caches.
+///
+/// [`PipelineCache`]: crate::PipelineCache
+pub fn pipeline_cache_key(adapter_info: &super::AdapterInfo) -> Option<String> {
+    match adapter_info.backend {
+        super::Backend::Vulkan => Some(format!(
+            "wgpu_pipeline_cache_vulkan_{:x}_{:x}",
+            adapter_info.vendor, adapter_info.device
+        )),
+        _ => None,
+    }
+}
+
 /// CPU accessible buffer used to download data back from the GPU.
 pub struct DownloadBuffer(super::Buffer, super::BufferMappedRange);
 
@@ -0,0 +1,262 @@
+use std::{
+    borrow::Cow,
+    error,
+    fmt::{self, Display},
+    fs, io,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use crate::{
+    ComputePipeline, Device, Error, ErrorFilter, RenderPipeline, ShaderModule,
+    ShaderModuleDescriptor, ShaderSource,
+};
+
+/// Handle to a shader file of a [`ShaderWatcher`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ShaderHandle(usize);
+
+/// Handle to a render pipeline of a [`ShaderWatcher`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct RenderPipelineHandle(usize);
+
+/// Handle to a compute pipeline of a [`ShaderWatcher`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct ComputePipelineHandle(usize);
+
+/// Error reloading a shader file.
+#[derive(Debug)]
+pub enum ShaderReloadError {
+    /// The shader file couldn't be read.
+    Io {
+        /// Path of the shader file.
+        path: PathBuf,
+        /// The error reading the file.
+        source: io::Error,
+    },
+    /// The shader module, or a pipeline depending on it, couldn't be created.
+    Shader {
+        /// Path of the shader file.
+        path: PathBuf,
+        /// The error creating the shader module or the pipeline.
+        source: Error,
+    },
+}
+
+impl Display for ShaderReloadError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Io {
+                ref path,
+                ref source,
+            } => write!(f, "Failed to read {}: {}", path.display(), source),
+            Self::Shader {
+                ref path,
+                ref source,
+            } => write!(f, "Failed to reload {}: {}", path.display(), source),
+        }
+    }
+}
+
+impl error::Error for ShaderReloadError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            Self::Io { ref source, .. } => Some(source),
+            Self::Shader { ref source, .. } => Some(source),
+        }
+    }
+}
+
+struct WatchedShader {
+    path: PathBuf,
+    modified: Option<SystemTime>,
+    module: ShaderModule,
+}
+
+/// Creates a pipeline from the shader modules of a watcher.
+type BuildPipeline<P> = Box<dyn Fn(&Device, &ShaderWatcher) -> P>;
+
+struct WatchedPipeline<P> {
+    shaders: Vec<ShaderHandle>,
+    build: BuildPipeline<P>,
+    pipeline: P,
+}
+
+/// Reloads WGSL shader files when they change, and rebuilds the pipelines using them.
+///
+/// Files are watched by polling their modification time with [`ShaderWatcher::reload`].
+/// When the new source of a shader, or one of the pipelines rebuilt from it, fails to compile,
+/// the previous shader module and pipelines are kept, so that editing a shader never
+/// interrupts the application.
+///
+/// Pipelines are registered with a function creating them from the shader modules of
+/// the watcher, which is called again each time one of their shaders is reloaded.
+#[derive(Default)]
+pub struct ShaderWatcher {
+    shaders: Vec<WatchedShader>,
+    render_pipelines: Vec<WatchedPipeline<RenderPipeline>>,
+    compute_pipelines: Vec<WatchedPipeline<ComputePipeline>>,
+}
+
+fn modified_time(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok()
+}
+
+fn load_shader(device: &Device, path: &Path) -> Result<ShaderModule, ShaderReloadError> {
+    let source = fs::read_to_string(path).map_err(|source| ShaderReloadError::Io {
+        path: path.to_path_buf(),
+        source,
+    })?;
+    let label = path.to_string_lossy();
+    device
+        .try_create_shader_module(&ShaderModuleDescriptor {
+            label: Some(&label),
+            source: ShaderSource::Wgsl(Cow::Owned(source)),
+        })
+        .map_err(|source| ShaderReloadError::Shader {
+            path: path.to_path_buf(),
+            source,
+        })
+}
+
+impl ShaderWatcher {
+    /// Creates a watcher without any shader.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Loads a WGSL shader file and starts watching it.
+    pub fn watch(
+        &mut self,
+        device: &Device,
+        path: impl AsRef<Path>,
+    ) -> Result<ShaderHandle, ShaderReloadError> {
+        let path = path.as_ref();
+        let modified = modified_time(path);
+        let module = load_shader(device, path)?;
+        self.shaders.push(WatchedShader {
+            path: path.to_path_buf(),
+            modified,
+            module,
+        });
+        Ok(ShaderHandle(self.shaders.len() - 1))
+    }
+
+    /// Returns the latest valid module of a shader file.
+    pub fn module(&self, shader: ShaderHandle) -> &ShaderModule {
+        &self.shaders[shader.0].module
+    }
+
+    /// Creates a render pipeline with `build`, which is called again
+    /// when any of the `shaders` it uses is reloaded.
+    pub fn add_render_pipeline(
+        &mut self,
+        device: &Device,
+        shaders: &[ShaderHandle],
+        build: impl Fn(&Device, &ShaderWatcher) -> RenderPipeline + 'static,
+    ) -> RenderPipelineHandle {
+        let pipeline = build(device, self);
+        self.render_pipelines.push(WatchedPipeline {
+            shaders: shaders.to_vec(),
+            build: Box::new(build),
+            pipeline,
+        });
+        RenderPipelineHandle(self.render_pipelines.len() - 1)
+    }
+
+    /// Returns the latest valid version of a render pipeline.
+    pub fn render_pipeline(&self, pipeline: RenderPipelineHandle) -> &RenderPipeline {
+        &self.render_pipelines[pipeline.0].pipeline
+    }
+
+    /// Creates a compute pipeline with `build`, which is called again
+    /// when any of the `shaders` it uses is reloaded.
+    pub fn add_compute_pipeline(
+        &mut self,
+        device: &Device,
+        shaders: &[ShaderHandle],
+        build: impl Fn(&Device, &ShaderWatcher) -> ComputePipeline + 'static,
+    ) -> ComputePipelineHandle {
+        let pipeline = build(device, self);
+        self.compute_pipelines.push(WatchedPipeline {
+            shaders: shaders.to_vec(),
+            build: Box::new(build),
+            pipeline,
+        });
+        ComputePipelineHandle(self.compute_pipelines.len() - 1)
+    }
+
+    /// Returns the latest valid version of a compute pipeline.
+    pub fn compute_pipeline(&self, pipeline: ComputePipelineHandle) -> &ComputePipeline {
+        &self.compute_pipelines[pipeline.0].pipeline
+    }
+
+    /// Reloads the shader files modified since they were last loaded,
+    /// and rebuilds the pipelines using them.
+    ///
+    /// Pipelines are rebuilt within a validation error scope, which is popped
+    /// before returning, so the returned future has to be awaited before the
+    /// next reload. It resolves with the errors of the shaders that failed to reload.
+    pub async fn reload(&mut self, device: &Device) -> Vec<ShaderReloadError> {
+        let mut errors = Vec::new();
+        for index in 0..self.shaders.len() {
+            let modified = modified_time(&self.shaders[index].path);
+            if modified == self.shaders[index].modified {
+                continue;
+            }
+            let result = self.reload_shader(device, ShaderHandle(index)).await;
+            // A file that couldn't be read, for example while it's being saved,
+            // is read again on the next reload.
+            if !matches!(result, Err(ShaderReloadError::Io { .. })) {
+                self.shaders[index].modified = modified;
+            }
+            if let Err(error) = result {
+                errors.push(error);
+            }
+        }
+        errors
+    }
+
+    async fn reload_shader(
+        &mut self,
+        device: &Device,
+        shader: ShaderHandle,
+    ) -> Result<(), ShaderReloadError> {
+        let path = self.shaders[shader.0].path.clone();
+        let module = load_shader(device, &path)?;
+        let old_module = std::mem::replace(&mut self.shaders[shader.0].module, module);
+
+        device.push_error_scope(ErrorFilter::Validation);
+        let render_pipelines = self
+            .render_pipelines
+            .iter()
+            .enumerate()
+            .filter(|&(_, watched)| watched.shaders.contains(&shader))
+            .map(|(index, watched)| (index, (watched.build)(device, self)))
+            .collect::<Vec<_>>();
+        let compute_pipelines = self
+            .compute_pipelines
+            .iter()
+            .enumerate()
+            .filter(|&(_, watched)| watched.shaders.contains(&shader))
+            .map(|(index, watched)| (index, (watched.build)(device, self)))
+            .collect::<Vec<_>>();
+
+        if let Some(error) = device.pop_error_scope().await {
+            self.shaders[shader.0].module = old_module;
+            return Err(ShaderReloadError::Shader {
+                path,
+                source: error,
+            });
+        }
+        for (index, pipeline) in render_pipelines {
+            self.render_pipelines[index].pipeline = pipeline;
+        }
+        for (index, pipeline) in compute_pipelines {
+            self.compute_pipelines[index].pipeline = pipeline;
+        }
+        Ok(())
+    }
+}
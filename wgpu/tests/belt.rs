@@ -0,0 +1,181 @@
+use std::{
+    num::NonZeroU64,
+    sync::{Arc, Mutex},
+};
+
+use wgpu::util::{BeltStats, DeviceExt, DownloadBelt, DownloadTexture, StagingBelt};
+
+use crate::common::{initialize_test, TestParameters};
+
+// 3 texels of 4 bytes are less than a row alignment, so rows are padded in the belts.
+const TEXTURE_SIZE: wgpu::Extent3d = wgpu::Extent3d {
+    width: 3,
+    height: 2,
+    depth_or_array_layers: 1,
+};
+
+fn texture_desc(usage: wgpu::TextureUsages) -> wgpu::TextureDescriptor<'static> {
+    wgpu::TextureDescriptor {
+        label: None,
+        size: TEXTURE_SIZE,
+        mip_level_count: 1,
+        sample_count: 1,
+        dimension: wgpu::TextureDimension::D2,
+        format: wgpu::TextureFormat::Rgba8Unorm,
+        usage,
+        view_formats: &[],
+    }
+}
+
+fn texture_data(seed: u8) -> Vec<u8> {
+    (0..3 * 2 * 4).map(|i| seed.wrapping_add(i)).collect()
+}
+
+#[test]
+fn staging_belt_write_texture() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let desc = texture_desc(wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC);
+        let texture = ctx.device.create_texture(&desc);
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 8,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let mut belt = StagingBelt::new(1024);
+
+        for round in 0..3u8 {
+            let data = texture_data(round * 50);
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut view = belt.write_texture(
+                    &mut encoder,
+                    texture.as_image_copy(),
+                    desc.format,
+                    TEXTURE_SIZE,
+                    &ctx.device,
+                );
+                assert_eq!(view.bytes_per_row(), 12);
+                assert_eq!(
+                    view.padded_bytes_per_row(),
+                    wgpu::COPY_BYTES_PER_ROW_ALIGNMENT
+                );
+                assert_eq!(view.rows_per_image(), 2);
+                view.copy_from_slice(&data);
+            }
+            belt.write_buffer(
+                &mut encoder,
+                &buffer,
+                0,
+                NonZeroU64::new(8).unwrap(),
+                &ctx.device,
+            )
+            .copy_from_slice(&[round; 8]);
+            belt.finish();
+            ctx.queue.submit(Some(encoder.finish()));
+            let recall = belt.recall();
+            ctx.device.poll(wgpu::Maintain::Wait);
+            pollster::block_on(recall);
+
+            let download = DownloadTexture::read_texture(
+                &ctx.device,
+                &ctx.queue,
+                &texture,
+                &desc,
+                &wgpu::ImageSubresourceRange::default(),
+            );
+            ctx.device.poll(wgpu::Maintain::Wait);
+            assert_eq!(&pollster::block_on(download).unwrap()[..], &data[..]);
+
+            let slice = buffer.slice(..);
+            let map = slice.map_async(wgpu::MapMode::Read);
+            ctx.device.poll(wgpu::Maintain::Wait);
+            pollster::block_on(map).unwrap();
+            assert_eq!(&slice.get_mapped_range()[..], &[round; 8]);
+            buffer.unmap();
+        }
+
+        // Both writes of a round fit in one chunk, which is only recalled at the end
+        // of the next round, so the first two rounds allocate.
+        assert_eq!(
+            belt.stats(),
+            BeltStats {
+                allocated_chunks: 2,
+                reused_chunks: 1,
+                allocated_bytes: 2 * 1024,
+            }
+        );
+    })
+}
+
+#[test]
+fn download_belt_read_back() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let desc = texture_desc(wgpu::TextureUsages::COPY_SRC);
+        let texel_data = texture_data(7);
+        let texture = ctx
+            .device
+            .create_texture_with_data(&ctx.queue, &desc, &texel_data);
+        let buffer_data = (0..16).collect::<Vec<u8>>();
+        let buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &buffer_data,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            });
+        let mut belt = DownloadBelt::new(1024);
+
+        for _ in 0..3 {
+            let texture_read = Arc::new(Mutex::new(None));
+            let buffer_read = Arc::new(Mutex::new(None));
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            let result = Arc::clone(&texture_read);
+            belt.read_texture(
+                &mut encoder,
+                texture.as_image_copy(),
+                desc.format,
+                TEXTURE_SIZE,
+                &ctx.device,
+                move |data| *result.lock().unwrap() = Some(data.to_vec()),
+            );
+            let result = Arc::clone(&buffer_read);
+            belt.read_buffer(
+                &mut encoder,
+                &buffer,
+                4,
+                NonZeroU64::new(8).unwrap(),
+                &ctx.device,
+                move |data| *result.lock().unwrap() = Some(data.to_vec()),
+            );
+            belt.finish();
+            ctx.queue.submit(Some(encoder.finish()));
+            let recall = belt.recall();
+            ctx.device.poll(wgpu::Maintain::Wait);
+            pollster::block_on(recall);
+
+            // Texture rows are read back without their padding.
+            assert_eq!(
+                texture_read.lock().unwrap().as_deref(),
+                Some(&texel_data[..])
+            );
+            assert_eq!(
+                buffer_read.lock().unwrap().as_deref(),
+                Some(&buffer_data[4..12])
+            );
+        }
+
+        assert_eq!(
+            belt.stats(),
+            BeltStats {
+                allocated_chunks: 2,
+                reused_chunks: 1,
+                allocated_bytes: 2 * 1024,
+            }
+        );
+    })
+}
@@ -0,0 +1,130 @@
+use std::num::NonZeroU32;
+
+use wgpu::util::DeviceExt;
+
+use crate::common::{initialize_test, TestParameters};
+
+const ROW_PITCH: u32 = wgpu::COPY_BYTES_PER_ROW_ALIGNMENT;
+
+#[test]
+fn copy_through_buffers_and_textures() {
+    initialize_test(TestParameters::default(), |ctx| {
+        // 4x2 texels, tightly packed
+        let data = (1..=32).collect::<Vec<u8>>();
+        let staging = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: None,
+                contents: &data,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            });
+        let upload = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 2 * ROW_PITCH as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::COPY_SRC | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let src_desc = wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 2,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+            view_formats: &[],
+        };
+        let src = ctx.device.create_texture(&src_desc);
+        let dst = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: 8,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+            usage: wgpu::TextureUsages::COPY_SRC | wgpu::TextureUsages::COPY_DST,
+            ..src_desc
+        });
+        let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 4 * ROW_PITCH as wgpu::BufferAddress,
+            usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        // Spread the rows to the pitch required by the buffer to texture copies.
+        for row in 0..2 {
+            encoder.copy_buffer_to_buffer(
+                &staging,
+                row * 16,
+                &upload,
+                row * ROW_PITCH as wgpu::BufferAddress,
+                16,
+            );
+        }
+        encoder.copy_buffer_to_texture(
+            wgpu::ImageCopyBuffer {
+                buffer: &upload,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(ROW_PITCH),
+                    rows_per_image: None,
+                },
+            },
+            src.as_image_copy(),
+            src_desc.size,
+        );
+        encoder.copy_texture_to_texture(
+            src.as_image_copy(),
+            wgpu::ImageCopyTexture {
+                texture: &dst,
+                mip_level: 0,
+                origin: wgpu::Origin3d { x: 2, y: 1, z: 0 },
+                aspect: wgpu::TextureAspect::All,
+            },
+            src_desc.size,
+        );
+        encoder.copy_texture_to_buffer(
+            dst.as_image_copy(),
+            wgpu::ImageCopyBuffer {
+                buffer: &readback,
+                layout: wgpu::ImageDataLayout {
+                    offset: 0,
+                    bytes_per_row: NonZeroU32::new(ROW_PITCH),
+                    rows_per_image: None,
+                },
+            },
+            wgpu::Extent3d {
+                width: 8,
+                height: 4,
+                depth_or_array_layers: 1,
+            },
+        );
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = readback.slice(..);
+        let map = slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(map).unwrap();
+        let mapped = slice.get_mapped_range();
+        for y in 0..4 {
+            let row = &mapped[y * ROW_PITCH as usize..][..8 * 4];
+            for x in 0..8 {
+                let expected = if (2..6).contains(&x) && (1..3).contains(&y) {
+                    let start = ((y - 1) * 4 + x - 2) * 4;
+                    &data[start..start + 4]
+                } else {
+                    // the rest of the destination is zero initialized
+                    &[0; 4][..]
+                };
+                assert_eq!(&row[x * 4..x * 4 + 4], expected, "texel ({}, {})", x, y);
+            }
+        }
+    })
+}
@@ -6,3 +6,171 @@ fn device_initialization() {
         // intentionally empty
     })
 }
+
+#[test]
+fn try_create_shader_module() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let result = ctx
+            .device
+            .try_create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("valid"),
+                source: wgpu::ShaderSource::Wgsl(
+                    "[[stage(compute), workgroup_size(1)]] fn main() {}".into(),
+                ),
+            });
+        assert!(result.is_ok());
+
+        let result = ctx
+            .device
+            .try_create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: Some("invalid"),
+                source: wgpu::ShaderSource::Wgsl("fn main( {}".into()),
+            });
+        assert!(matches!(result, Err(wgpu::Error::ValidationError { .. })));
+    })
+}
+
+#[test]
+fn device_destroy_calls_lost_callback() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        ctx.device
+            .set_device_lost_callback(move |reason, _message| {
+                sender.send(reason).unwrap();
+            });
+
+        ctx.device.destroy();
+        assert_eq!(receiver.try_recv(), Ok(wgpu::DeviceLostReason::Destroyed));
+
+        // Operations on the lost device fail without reporting errors.
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let map = buffer.slice(..).map_async(wgpu::MapMode::Read);
+        ctx.queue.submit(None);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        assert!(pollster::block_on(map).is_err());
+    })
+}
+
+fn create_invalid_buffer(device: &wgpu::Device, label: &str) {
+    // Buffers need at least one usage.
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: Some(label),
+        size: 16,
+        usage: wgpu::BufferUsages::empty(),
+        mapped_at_creation: false,
+    });
+}
+
+#[test]
+fn error_scopes_capture_errors() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let (sender, receiver) = std::sync::mpsc::channel();
+        ctx.device.on_uncaptured_error(move |error| {
+            sender.send(error.to_string()).unwrap();
+        });
+
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+
+        // The scope keeps the first error.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        create_invalid_buffer(&ctx.device, "first");
+        create_invalid_buffer(&ctx.device, "second");
+        match pollster::block_on(ctx.device.pop_error_scope()) {
+            Some(wgpu::Error::ValidationError { description, .. }) => {
+                assert!(description.contains("first"), "{}", description)
+            }
+            other => panic!("expected a validation error, got {:?}", other),
+        }
+
+        // Errors not matching the filter go to the enclosing scope.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        ctx.device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        create_invalid_buffer(&ctx.device, "nested");
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+        assert!(matches!(
+            pollster::block_on(ctx.device.pop_error_scope()),
+            Some(wgpu::Error::ValidationError { .. })
+        ));
+        assert!(receiver.try_recv().is_err());
+
+        // Errors outside of any matching scope are uncaptured.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::OutOfMemory);
+        create_invalid_buffer(&ctx.device, "uncaptured");
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+        assert!(receiver.try_recv().unwrap().contains("uncaptured"));
+    })
+}
+
+#[test]
+fn pop_error_scope_without_push() {
+    initialize_test(TestParameters::default(), |ctx| {
+        assert!(matches!(
+            pollster::block_on(ctx.device.pop_error_scope()),
+            Some(wgpu::Error::ValidationError { .. })
+        ));
+        // The scopes pushed afterwards still work.
+        ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+        assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+    })
+}
+
+#[test]
+fn failed_submit_loses_device_without_polling() {
+    // The CPU backend loses the device on a loop that doesn't finish,
+    // which is the only way to make a submission fail on purpose.
+    let instance = wgpu::Instance::new(wgpu::Backends::CPU);
+    let adapter =
+        match pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        {
+            Some(adapter) => adapter,
+            None => {
+                println!("TEST SKIPPED: NO CPU ADAPTER");
+                return;
+            }
+        };
+    let (device, queue) =
+        pollster::block_on(adapter.request_device(&wgpu::DeviceDescriptor::default(), None))
+            .unwrap();
+    let (sender, receiver) = std::sync::mpsc::channel();
+    device.set_device_lost_callback(move |reason, _message| {
+        sender.send(reason).unwrap();
+    });
+
+    let module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(
+            "[[stage(compute), workgroup_size(1)]] fn main() { loop {} }".into(),
+        ),
+    });
+    let pipeline = device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: None,
+        layout: None,
+        module: &module,
+        entry_point: "main",
+        cache: None,
+    });
+    let mut encoder =
+        device.create_command_encoder(&wgpu::CommandEncoderDescriptor { label: None });
+    {
+        let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+        pass.set_pipeline(&pipeline);
+        pass.dispatch(1, 1, 1);
+    }
+    queue.submit(Some(encoder.finish()));
+    assert_eq!(receiver.try_recv(), Ok(wgpu::DeviceLostReason::DriverReset));
+
+    // Without polling, the loss is known already, so the error of the lost device
+    // doesn't reach the uncaptured error handler, which would panic.
+    device.create_buffer(&wgpu::BufferDescriptor {
+        label: None,
+        size: 16,
+        usage: wgpu::BufferUsages::COPY_DST,
+        mapped_at_creation: false,
+    });
+}
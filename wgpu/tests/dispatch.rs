@@ -0,0 +1,131 @@
+use crate::common::{initialize_test, TestParameters};
+
+const SHADER: &str = "
+[[block]]
+struct Data {
+    values: array<u32>;
+};
+
+[[group(0), binding(0)]]
+var<storage, read_write> data: Data;
+
+var<workgroup> scratch: array<u32, 64>;
+
+[[stage(compute), workgroup_size(64)]]
+fn mirror(
+    [[builtin(local_invocation_index)]] local_index: u32,
+    [[builtin(global_invocation_id)]] global_id: vec3<u32>
+) {
+    scratch[local_index] = global_id.x;
+    workgroupBarrier();
+    data.values[global_id.x] = scratch[63u - local_index];
+}
+
+[[stage(compute), workgroup_size(64)]]
+fn scale([[builtin(global_invocation_id)]] global_id: vec3<u32>) {
+    data.values[global_id.x] = data.values[global_id.x] * 2u;
+}
+";
+
+const GROUP_COUNT: u32 = 3;
+
+#[test]
+fn dispatch_with_workgroup_barriers() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::COMPUTE_SHADERS),
+        |ctx| {
+            let size = (GROUP_COUNT * 64 * 4) as wgpu::BufferAddress;
+            let storage = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size,
+                usage: wgpu::BufferUsages::STORAGE | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            });
+            let readback = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+                label: None,
+                size,
+                usage: wgpu::BufferUsages::MAP_READ | wgpu::BufferUsages::COPY_DST,
+                mapped_at_creation: false,
+            });
+
+            let bind_group_layout =
+                ctx.device
+                    .create_bind_group_layout(&wgpu::BindGroupLayoutDescriptor {
+                        label: None,
+                        entries: &[wgpu::BindGroupLayoutEntry {
+                            binding: 0,
+                            visibility: wgpu::ShaderStages::COMPUTE,
+                            ty: wgpu::BindingType::Buffer {
+                                ty: wgpu::BufferBindingType::Storage { read_only: false },
+                                has_dynamic_offset: false,
+                                min_binding_size: None,
+                            },
+                            count: None,
+                        }],
+                    });
+            let bind_group = ctx.device.create_bind_group(&wgpu::BindGroupDescriptor {
+                label: None,
+                layout: &bind_group_layout,
+                entries: &[wgpu::BindGroupEntry {
+                    binding: 0,
+                    resource: storage.as_entire_binding(),
+                }],
+            });
+            let layout = ctx
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: None,
+                    bind_group_layouts: &[&bind_group_layout],
+                    push_constant_ranges: &[],
+                });
+            let module = ctx
+                .device
+                .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+                });
+            let pipelines = ["mirror", "scale"]
+                .iter()
+                .map(|entry_point| {
+                    ctx.device
+                        .create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                            label: None,
+                            layout: Some(&layout),
+                            module: &module,
+                            entry_point,
+                            cache: None,
+                        })
+                })
+                .collect::<Vec<_>>();
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_compute_pass(&wgpu::ComputePassDescriptor::default());
+                pass.set_bind_group(0, &bind_group, &[]);
+                for pipeline in pipelines.iter() {
+                    pass.set_pipeline(pipeline);
+                    pass.dispatch(GROUP_COUNT, 1, 1);
+                }
+            }
+            encoder.copy_buffer_to_buffer(&storage, 0, &readback, 0, size);
+            ctx.queue.submit(Some(encoder.finish()));
+
+            let slice = readback.slice(..);
+            let map = slice.map_async(wgpu::MapMode::Read);
+            ctx.device.poll(wgpu::Maintain::Wait);
+            pollster::block_on(map).unwrap();
+            let values = slice
+                .get_mapped_range()
+                .chunks_exact(4)
+                .map(|bytes| u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]))
+                .collect::<Vec<_>>();
+            // Each workgroup reverses its own range of the values.
+            let expected = (0..GROUP_COUNT * 64)
+                .map(|i| 2 * (i / 64 * 64 + 63 - i % 64))
+                .collect::<Vec<_>>();
+            assert_eq!(values, expected);
+        },
+    )
+}
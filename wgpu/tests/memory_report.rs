@@ -0,0 +1,51 @@
+use std::panic::{self, AssertUnwindSafe};
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn buffer_memory_report() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let before = ctx.device.memory_report().buffers;
+
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("reported"),
+            size: 1024,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let during = ctx.device.memory_report().buffers;
+        assert_eq!(during.total.count, before.total.count + 1);
+        assert_eq!(during.total.bytes, before.total.bytes + 1024);
+        let usage = during.by_usage[&wgpu::BufferUsages::COPY_DST];
+        assert!(usage.count >= 1 && usage.bytes >= 1024);
+        if cfg!(debug_assertions) {
+            assert_eq!(during.by_label["reported"].bytes, 1024);
+        }
+
+        drop(buffer);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let after = ctx.device.memory_report().buffers;
+        assert_eq!(after.total, before.total);
+        assert!(!after.by_label.contains_key("reported"));
+    })
+}
+
+#[test]
+fn invalid_write_takes_no_staging_memory() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: None,
+            size: 16,
+            usage: wgpu::BufferUsages::COPY_DST,
+            mapped_at_creation: false,
+        });
+        let before = ctx.device.memory_report().staging;
+
+        // Overruns the buffer. Queue errors are fatal.
+        let result = panic::catch_unwind(AssertUnwindSafe(|| {
+            ctx.queue.write_buffer(&buffer, 8, &[0; 16]);
+        }));
+        assert!(result.is_err());
+        assert_eq!(ctx.device.memory_report().staging, before);
+    })
+}
@@ -0,0 +1,122 @@
+use wgpu::util::{DownloadTexture, MipmapGenerator};
+
+use crate::common::{initialize_test, TestParameters};
+
+fn assert_texels_near(actual: &[u8], expected: &[u8]) {
+    assert_eq!(actual.len(), expected.len());
+    for (i, (&a, &e)) in actual.iter().zip(expected).enumerate() {
+        assert!(
+            (a as i32 - e as i32).abs() <= 2,
+            "byte {}: got {}, expected {}",
+            i,
+            a,
+            e
+        );
+    }
+}
+
+fn generate_and_read(
+    ctx: &crate::common::TestingContext,
+    generator: &mut MipmapGenerator,
+    desc: &wgpu::TextureDescriptor,
+    data: &[u8],
+) -> DownloadTexture {
+    let texture = generator.create_texture_with_data(&ctx.device, &ctx.queue, desc, data);
+    let download = DownloadTexture::read_texture(
+        &ctx.device,
+        &ctx.queue,
+        &texture,
+        desc,
+        &wgpu::ImageSubresourceRange::default(),
+    );
+    ctx.device.poll(wgpu::Maintain::Wait);
+    pollster::block_on(download).unwrap()
+}
+
+#[test]
+fn generate_mipmaps_read_back() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let mut generator = MipmapGenerator::new(&ctx.device);
+
+        // Layer 0 has a different color in each 2x2 quadrant, layer 1 is uniform gray.
+        let desc = wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 2,
+            },
+            mip_level_count: 3,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        };
+        let quadrants: [[u8; 4]; 4] = [
+            [255, 0, 0, 255],
+            [0, 255, 0, 255],
+            [0, 0, 255, 255],
+            [255, 255, 255, 255],
+        ];
+        let mut data = Vec::new();
+        for y in 0..4 {
+            for x in 0..4 {
+                data.extend_from_slice(&quadrants[y / 2 * 2 + x / 2]);
+            }
+        }
+        data.extend_from_slice(&[64; 4 * 4 * 4]);
+
+        let download = generate_and_read(&ctx, &mut generator, &desc, &data);
+        assert_eq!(download.subresource(0, 0).unwrap(), &data[..4 * 4 * 4]);
+        // Each texel of mip 1 is sampled between the 4 texels of a quadrant.
+        assert_texels_near(download.subresource(0, 1).unwrap(), &quadrants.concat());
+        assert_texels_near(download.subresource(0, 2).unwrap(), &[128, 128, 128, 255]);
+        assert_texels_near(download.subresource(1, 1).unwrap(), &[64; 2 * 2 * 4]);
+        assert_texels_near(download.subresource(1, 2).unwrap(), &[64; 4]);
+
+        // The same generator filters sRGB textures in linear space with its own pipeline.
+        let srgb_desc = wgpu::TextureDescriptor {
+            size: wgpu::Extent3d {
+                width: 2,
+                height: 1,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 2,
+            format: wgpu::TextureFormat::Rgba8UnormSrgb,
+            ..desc
+        };
+        let srgb_data = [0, 0, 0, 255, 255, 255, 255, 255];
+        let download = generate_and_read(&ctx, &mut generator, &srgb_desc, &srgb_data);
+        // Linear 0.5 is encoded as 188 in sRGB.
+        assert_texels_near(download.subresource(0, 1).unwrap(), &[188, 188, 188, 255]);
+    })
+}
+
+#[test]
+fn supported_formats() {
+    initialize_test(TestParameters::default(), |ctx| {
+        for &format in &[
+            wgpu::TextureFormat::Rgba8Unorm,
+            wgpu::TextureFormat::Rgba8UnormSrgb,
+            wgpu::TextureFormat::Rgba16Float,
+        ] {
+            assert!(
+                MipmapGenerator::supports_format(&ctx.adapter, &ctx.device, format),
+                "{:?}",
+                format
+            );
+        }
+        for &format in &[
+            wgpu::TextureFormat::R32Uint,
+            wgpu::TextureFormat::Depth32Float,
+            wgpu::TextureFormat::Bc1RgbaUnorm,
+        ] {
+            assert!(
+                !MipmapGenerator::supports_format(&ctx.adapter, &ctx.device, format),
+                "{:?}",
+                format
+            );
+        }
+    })
+}
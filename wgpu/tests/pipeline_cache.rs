@@ -0,0 +1,74 @@
+use crate::common::{initialize_test, TestParameters};
+
+fn create_pipeline(device: &wgpu::Device, cache: &wgpu::PipelineCache) {
+    let module = device.create_shader_module(&wgpu::ShaderModuleDescriptor {
+        label: None,
+        source: wgpu::ShaderSource::Wgsl(
+            "[[stage(compute), workgroup_size(1)]] fn main() {}".into(),
+        ),
+    });
+    device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+        label: None,
+        layout: None,
+        module: &module,
+        entry_point: "main",
+        cache: Some(cache),
+    });
+}
+
+#[test]
+fn pipeline_cache_round_trip() {
+    initialize_test(
+        TestParameters::default().features(wgpu::Features::PIPELINE_CACHE),
+        |ctx| {
+            let cache = unsafe {
+                ctx.device
+                    .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                        label: Some("empty"),
+                        data: None,
+                        fallback: false,
+                    })
+            };
+            create_pipeline(&ctx.device, &cache);
+            let data = match cache.get_data() {
+                Some(data) => data,
+                None => {
+                    println!("TEST SKIPPED: NO PIPELINE CACHE DATA");
+                    return;
+                }
+            };
+
+            // The data is accepted by the same device.
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let cache = unsafe {
+                ctx.device
+                    .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                        label: Some("loaded"),
+                        data: Some(&data),
+                        fallback: false,
+                    })
+            };
+            create_pipeline(&ctx.device, &cache);
+            assert!(pollster::block_on(ctx.device.pop_error_scope()).is_none());
+            assert!(cache.get_data().is_some());
+
+            // Corrupt data is rejected, unless an empty cache may be created instead.
+            let mut corrupt = data;
+            let last = corrupt.len() - 1;
+            corrupt[last] ^= 0xFF;
+            for &fallback in [false, true].iter() {
+                ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+                let _cache = unsafe {
+                    ctx.device
+                        .create_pipeline_cache(&wgpu::PipelineCacheDescriptor {
+                            label: Some("corrupt"),
+                            data: Some(&corrupt),
+                            fallback,
+                        })
+                };
+                let error = pollster::block_on(ctx.device.pop_error_scope());
+                assert_eq!(error.is_some(), !fallback);
+            }
+        },
+    )
+}
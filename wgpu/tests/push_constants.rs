@@ -0,0 +1,170 @@
+use wgpu::util::DownloadTexture;
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn gl_supports_downlevel_default_limits() {
+    // The uniform block emulating push constants must not take the uniform buffer
+    // limit below the downlevel defaults on drivers at the GLES 3.0 minimum.
+    let instance = wgpu::Instance::new(wgpu::Backends::GL);
+    let adapter =
+        match pollster::block_on(instance.request_adapter(&wgpu::RequestAdapterOptions::default()))
+        {
+            Some(adapter) => adapter,
+            None => {
+                println!("TEST SKIPPED: NO GL ADAPTER");
+                return;
+            }
+        };
+
+    let limits = wgpu::Limits::downlevel_defaults();
+    assert!(
+        adapter.limits().max_uniform_buffers_per_shader_stage
+            >= limits.max_uniform_buffers_per_shader_stage
+    );
+    pollster::block_on(adapter.request_device(
+        &wgpu::DeviceDescriptor {
+            label: None,
+            features: wgpu::Features::empty(),
+            limits,
+        },
+        None,
+    ))
+    .unwrap();
+}
+
+const SHADER: &str = "
+[[block]]
+struct PushConstants {
+    color: vec4<f32>;
+};
+var<push_constant> pc: PushConstants;
+
+[[stage(vertex)]]
+fn vs_main([[builtin(vertex_index)]] index: u32) -> [[builtin(position)]] vec4<f32> {
+    let uv = vec2<f32>(f32((index << 1u) & 2u), f32(index & 2u));
+    return vec4<f32>(uv * 2.0 - 1.0, 0.0, 1.0);
+}
+
+[[stage(fragment)]]
+fn fs_main() -> [[location(0)]] vec4<f32> {
+    return pc.color;
+}
+";
+
+#[test]
+fn draw_with_push_constants() {
+    initialize_test(
+        TestParameters::default()
+            .features(wgpu::Features::PUSH_CONSTANTS)
+            .limits(wgpu::Limits {
+                max_push_constant_size: 16,
+                ..wgpu::Limits::downlevel_defaults()
+            }),
+        |ctx| {
+            let module = ctx
+                .device
+                .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                    label: None,
+                    source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+                });
+            let layout = ctx
+                .device
+                .create_pipeline_layout(&wgpu::PipelineLayoutDescriptor {
+                    label: None,
+                    bind_group_layouts: &[],
+                    push_constant_ranges: &[wgpu::PushConstantRange {
+                        stages: wgpu::ShaderStages::FRAGMENT,
+                        range: 0..16,
+                    }],
+                });
+            let pipeline = ctx
+                .device
+                .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                    label: None,
+                    layout: Some(&layout),
+                    vertex: wgpu::VertexState {
+                        module: &module,
+                        entry_point: "vs_main",
+                        buffers: &[],
+                    },
+                    fragment: Some(wgpu::FragmentState {
+                        module: &module,
+                        entry_point: "fs_main",
+                        targets: &[wgpu::TextureFormat::Rgba8Unorm.into()],
+                    }),
+                    primitive: wgpu::PrimitiveState::default(),
+                    depth_stencil: None,
+                    multisample: wgpu::MultisampleState::default(),
+                    cache: None,
+                });
+
+            let desc = wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: 4,
+                    height: 1,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            };
+            let texture = ctx.device.create_texture(&desc);
+            let view = texture.create_view(&wgpu::TextureViewDescriptor::default());
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            {
+                let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                    label: None,
+                    color_attachments: &[wgpu::RenderPassColorAttachment {
+                        view: &view,
+                        resolve_target: None,
+                        ops: wgpu::Operations {
+                            load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                            store: true,
+                        },
+                    }],
+                    depth_stencil_attachment: None,
+                    occlusion_query_set: None,
+                });
+                pass.set_pipeline(&pipeline);
+                // Each half is drawn with different push constants, which have to be
+                // uploaded again between the draws.
+                let colors: [[f32; 4]; 2] = [[1.0, 0.0, 0.0, 1.0], [0.0, 0.0, 1.0, 1.0]];
+                for (i, color) in colors.iter().enumerate() {
+                    pass.set_scissor_rect(i as u32 * 2, 0, 2, 1);
+                    pass.set_push_constants(
+                        wgpu::ShaderStages::FRAGMENT,
+                        0,
+                        bytemuck::cast_slice(color),
+                    );
+                    pass.draw(0..3, 0..1);
+                }
+            }
+            ctx.queue.submit(Some(encoder.finish()));
+
+            let download = DownloadTexture::read_texture(
+                &ctx.device,
+                &ctx.queue,
+                &texture,
+                &desc,
+                &wgpu::ImageSubresourceRange::default(),
+            );
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let download = pollster::block_on(download).unwrap();
+            assert_eq!(
+                download.subresource(0, 0).unwrap(),
+                &[
+                    255, 0, 0, 255, 255, 0, 0, 255, //
+                    0, 0, 255, 255, 0, 0, 255, 255,
+                ]
+            );
+        },
+    )
+}
@@ -0,0 +1,152 @@
+use wgpu::util::DownloadTexture;
+
+use crate::common::{initialize_test, TestParameters};
+
+const SHADER: &str = "
+struct VertexOutput {
+    [[builtin(position)]] position: vec4<f32>;
+    [[location(0)]] color: vec4<f32>;
+};
+
+[[stage(vertex)]]
+fn vs_main(
+    [[builtin(vertex_index)]] vertex_index: u32,
+    [[builtin(instance_index)]] instance_index: u32
+) -> VertexOutput {
+    let uv = vec2<f32>(f32((vertex_index << 1u) & 2u), f32(vertex_index & 2u));
+    var out: VertexOutput;
+    out.position = vec4<f32>(uv * 2.0 - 1.0, 0.5, 1.0);
+    out.color = vec4<f32>(1.0, 0.0, 0.0, 1.0);
+    if (instance_index == 1u) {
+        // behind the first instance
+        out.position = vec4<f32>(uv * 2.0 - 1.0, 0.75, 1.0);
+        out.color = vec4<f32>(0.0, 1.0, 0.0, 1.0);
+    }
+    if (instance_index == 2u) {
+        // in front of the first instance, over the lower left half
+        out.position = vec4<f32>(uv - 1.0, 0.25, 1.0);
+        out.color = vec4<f32>(0.0, 0.0, 1.0, 1.0);
+    }
+    return out;
+}
+
+[[stage(fragment)]]
+fn fs_main(input: VertexOutput) -> [[location(0)]] vec4<f32> {
+    return input.color;
+}
+";
+
+const SIZE: u32 = 8;
+
+#[test]
+fn draw_triangles_with_depth_test() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let module = ctx
+            .device
+            .create_shader_module(&wgpu::ShaderModuleDescriptor {
+                label: None,
+                source: wgpu::ShaderSource::Wgsl(SHADER.into()),
+            });
+        let pipeline = ctx
+            .device
+            .create_render_pipeline(&wgpu::RenderPipelineDescriptor {
+                label: None,
+                layout: None,
+                vertex: wgpu::VertexState {
+                    module: &module,
+                    entry_point: "vs_main",
+                    buffers: &[],
+                },
+                fragment: Some(wgpu::FragmentState {
+                    module: &module,
+                    entry_point: "fs_main",
+                    targets: &[wgpu::TextureFormat::Rgba8Unorm.into()],
+                }),
+                primitive: wgpu::PrimitiveState::default(),
+                depth_stencil: Some(wgpu::DepthStencilState {
+                    format: wgpu::TextureFormat::Depth32Float,
+                    depth_write_enabled: true,
+                    depth_compare: wgpu::CompareFunction::Less,
+                    stencil: wgpu::StencilState::default(),
+                    bias: wgpu::DepthBiasState::default(),
+                }),
+                multisample: wgpu::MultisampleState::default(),
+                cache: None,
+            });
+
+        let desc = wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: SIZE,
+                height: SIZE,
+                depth_or_array_layers: 1,
+            },
+            mip_level_count: 1,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        };
+        let color = ctx.device.create_texture(&desc);
+        let depth = ctx.device.create_texture(&wgpu::TextureDescriptor {
+            format: wgpu::TextureFormat::Depth32Float,
+            usage: wgpu::TextureUsages::RENDER_ATTACHMENT,
+            ..desc.clone()
+        });
+        let color_view = color.create_view(&wgpu::TextureViewDescriptor::default());
+        let depth_view = depth.create_view(&wgpu::TextureViewDescriptor::default());
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        {
+            let mut pass = encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &color_view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color::BLACK),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: Some(wgpu::RenderPassDepthStencilAttachment {
+                    view: &depth_view,
+                    depth_ops: Some(wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(1.0),
+                        store: false,
+                    }),
+                    stencil_ops: None,
+                }),
+                occlusion_query_set: None,
+            });
+            pass.set_pipeline(&pipeline);
+            pass.draw(0..3, 0..3);
+        }
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let download = DownloadTexture::read_texture(
+            &ctx.device,
+            &ctx.queue,
+            &color,
+            &desc,
+            &wgpu::ImageSubresourceRange::default(),
+        );
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let download = pollster::block_on(download).unwrap();
+        let texels = download.subresource(0, 0).unwrap();
+        for y in 0..SIZE as usize {
+            for x in 0..SIZE as usize {
+                // The diagonal is left to the fill rule.
+                let expected: [u8; 4] = match x.cmp(&y) {
+                    std::cmp::Ordering::Less => [0, 0, 255, 255],
+                    std::cmp::Ordering::Greater => [255, 0, 0, 255],
+                    std::cmp::Ordering::Equal => continue,
+                };
+                let start = (y * SIZE as usize + x) * 4;
+                assert_eq!(texels[start..start + 4], expected, "texel ({}, {})", x, y);
+            }
+        }
+    })
+}
@@ -0,0 +1,118 @@
+use std::num::NonZeroU32;
+
+use wgpu::util::{DeviceExt, RenderGraph};
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn record_out_of_order_declarations() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let data = (0..=255).collect::<Vec<u8>>();
+        let source_buffer = ctx
+            .device
+            .create_buffer_init(&wgpu::util::BufferInitDescriptor {
+                label: Some("source"),
+                contents: &data,
+                usage: wgpu::BufferUsages::COPY_SRC,
+            });
+        let readback_buffer = ctx.device.create_buffer(&wgpu::BufferDescriptor {
+            label: Some("readback"),
+            size: 256,
+            usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::MAP_READ,
+            mapped_at_creation: false,
+        });
+        let size = wgpu::Extent3d {
+            width: 64,
+            height: 1,
+            depth_or_array_layers: 1,
+        };
+
+        let mut graph = RenderGraph::new();
+        // The texture is declared first but only used by the second pass,
+        // so the physical resources are created in a different order.
+        let texture = graph.create_texture(
+            "texture",
+            &wgpu::TextureDescriptor {
+                label: None,
+                size,
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::COPY_DST | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[],
+            },
+        );
+        let staging = graph.create_buffer(
+            "staging",
+            &wgpu::BufferDescriptor {
+                label: None,
+                size: 256,
+                usage: wgpu::BufferUsages::COPY_DST | wgpu::BufferUsages::COPY_SRC,
+                mapped_at_creation: false,
+            },
+        );
+        let source = graph.import_buffer("source", &source_buffer);
+        let readback = graph.import_buffer("readback", &readback_buffer);
+
+        let mut pass = graph.add_pass("upload");
+        pass.read_buffer(source);
+        let staging = pass.write_buffer(staging);
+        pass.execute(move |encoder, resources| {
+            encoder.copy_buffer_to_buffer(
+                resources.buffer(source),
+                0,
+                resources.buffer(staging),
+                0,
+                256,
+            );
+        });
+        let mut pass = graph.add_pass("to texture");
+        pass.read_buffer(staging);
+        let texture = pass.write_texture(texture);
+        pass.execute(move |encoder, resources| {
+            encoder.copy_buffer_to_texture(
+                wgpu::ImageCopyBuffer {
+                    buffer: resources.buffer(staging),
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(256),
+                        rows_per_image: None,
+                    },
+                },
+                resources.texture(texture).as_image_copy(),
+                size,
+            );
+        });
+        let mut pass = graph.add_pass("readback");
+        pass.read_texture(texture);
+        let readback = pass.write_buffer(readback);
+        pass.side_effect();
+        pass.execute(move |encoder, resources| {
+            encoder.copy_texture_to_buffer(
+                resources.texture(texture).as_image_copy(),
+                wgpu::ImageCopyBuffer {
+                    buffer: resources.buffer(readback),
+                    layout: wgpu::ImageDataLayout {
+                        offset: 0,
+                        bytes_per_row: NonZeroU32::new(256),
+                        rows_per_image: None,
+                    },
+                },
+                size,
+            );
+        });
+
+        let mut encoder = ctx
+            .device
+            .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+        graph.record(&ctx.device, &mut encoder).unwrap();
+        ctx.queue.submit(Some(encoder.finish()));
+
+        let slice = readback_buffer.slice(..);
+        let map = slice.map_async(wgpu::MapMode::Read);
+        ctx.device.poll(wgpu::Maintain::Wait);
+        pollster::block_on(map).unwrap();
+        assert_eq!(&slice.get_mapped_range()[..], &data[..]);
+    })
+}
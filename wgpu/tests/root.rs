@@ -1,7 +1,19 @@
 // All files containing tests
 mod common;
 
+mod belt;
+mod copy;
 mod device;
+mod dispatch;
 mod example_wgsl;
 mod instance;
+mod memory_report;
+mod mipmap;
+mod pipeline_cache;
+mod push_constants;
+mod rasterization;
+mod render_graph;
+mod shader_watcher;
+mod texture_download;
+mod texture_view_formats;
 mod vertex_indices;
@@ -0,0 +1,64 @@
+use std::{fs, path::Path, thread, time::Duration};
+
+use crate::common::{initialize_test, TestParameters};
+
+/// Writes the file, making sure its modification time changes.
+fn write_modified(path: &Path, contents: &[u8]) {
+    let modified = fs::metadata(path)
+        .and_then(|metadata| metadata.modified())
+        .ok();
+    loop {
+        fs::write(path, contents).unwrap();
+        if fs::metadata(path).unwrap().modified().ok() != modified {
+            break;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+#[test]
+fn shader_watcher_reload() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let shader = "[[stage(compute), workgroup_size(1)]] fn main() {}";
+        let path = std::env::temp_dir().join(format!("wgpu-watcher-{}.wgsl", std::process::id()));
+        fs::write(&path, shader).unwrap();
+
+        let mut watcher = wgpu::util::ShaderWatcher::new();
+        let handle = watcher.watch(&ctx.device, &path).unwrap();
+        watcher.add_compute_pipeline(&ctx.device, &[handle], move |device, watcher| {
+            device.create_compute_pipeline(&wgpu::ComputePipelineDescriptor {
+                label: None,
+                layout: None,
+                module: watcher.module(handle),
+                entry_point: "main",
+                cache: None,
+            })
+        });
+        let mut reload = || pollster::block_on(watcher.reload(&ctx.device));
+        assert!(reload().is_empty());
+
+        // A failed read is retried on the next reload.
+        write_modified(&path, &[0xFF, 0xFE]);
+        for _ in 0..2 {
+            let errors = reload();
+            assert!(matches!(
+                errors[..],
+                [wgpu::util::ShaderReloadError::Io { .. }]
+            ));
+        }
+
+        // Invalid source is reported once, until the file changes again.
+        write_modified(&path, b"fn main( {}");
+        let errors = reload();
+        assert!(matches!(
+            errors[..],
+            [wgpu::util::ShaderReloadError::Shader { .. }]
+        ));
+        assert!(reload().is_empty());
+
+        write_modified(&path, shader.as_bytes());
+        assert!(reload().is_empty());
+
+        fs::remove_file(&path).unwrap();
+    })
+}
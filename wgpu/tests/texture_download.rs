@@ -0,0 +1,124 @@
+use std::num::NonZeroU32;
+
+use wgpu::util::{DeviceExt, DownloadTexture, DownloadTextureError};
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn download_texture_round_trip() {
+    initialize_test(TestParameters::default(), |ctx| {
+        // The width isn't a multiple of the row alignment, so the rows have to be de-padded.
+        let desc = wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 10,
+                height: 6,
+                depth_or_array_layers: 2,
+            },
+            mip_level_count: 3,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        };
+        // Each layer holds mips of 10x6, 5x3 and 2x1 texels.
+        let layer_size = (10 * 6 + 5 * 3 + 2) * 4;
+        let data = (0..2 * layer_size)
+            .map(|i| (i % 251) as u8)
+            .collect::<Vec<_>>();
+        let texture = ctx
+            .device
+            .create_texture_with_data(&ctx.queue, &desc, &data);
+
+        let download = DownloadTexture::read_texture(
+            &ctx.device,
+            &ctx.queue,
+            &texture,
+            &desc,
+            &wgpu::ImageSubresourceRange::default(),
+        );
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let download = pollster::block_on(download).unwrap();
+        assert_eq!(&download[..], &data[..]);
+
+        let partial = DownloadTexture::read_texture(
+            &ctx.device,
+            &ctx.queue,
+            &texture,
+            &desc,
+            &wgpu::ImageSubresourceRange {
+                base_mip_level: 1,
+                mip_level_count: NonZeroU32::new(1),
+                base_array_layer: 1,
+                ..Default::default()
+            },
+        );
+        ctx.device.poll(wgpu::Maintain::Wait);
+        let partial = pollster::block_on(partial).unwrap();
+        let mip_offset = layer_size + 10 * 6 * 4;
+        assert_eq!(
+            partial.subresource(1, 1).unwrap(),
+            &data[mip_offset..mip_offset + 5 * 3 * 4]
+        );
+        assert!(partial.subresource(0, 1).is_none());
+    })
+}
+
+#[test]
+fn download_texture_invalid_range() {
+    initialize_test(TestParameters::default(), |ctx| {
+        let desc = wgpu::TextureDescriptor {
+            label: None,
+            size: wgpu::Extent3d {
+                width: 4,
+                height: 4,
+                depth_or_array_layers: 2,
+            },
+            mip_level_count: 2,
+            sample_count: 1,
+            dimension: wgpu::TextureDimension::D2,
+            format: wgpu::TextureFormat::Rgba8Unorm,
+            usage: wgpu::TextureUsages::COPY_SRC,
+            view_formats: &[],
+        };
+        let texture = ctx.device.create_texture(&desc);
+
+        let mips = DownloadTexture::read_texture(
+            &ctx.device,
+            &ctx.queue,
+            &texture,
+            &desc,
+            &wgpu::ImageSubresourceRange {
+                base_mip_level: 1,
+                mip_level_count: NonZeroU32::new(2),
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            pollster::block_on(mips).unwrap_err(),
+            DownloadTextureError::InvalidMipLevels {
+                range: 1..3,
+                count: 2
+            }
+        );
+
+        let layers = DownloadTexture::read_texture(
+            &ctx.device,
+            &ctx.queue,
+            &texture,
+            &desc,
+            &wgpu::ImageSubresourceRange {
+                base_array_layer: 2,
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            pollster::block_on(layers).unwrap_err(),
+            DownloadTextureError::InvalidArrayLayers {
+                range: 2..2,
+                count: 2
+            }
+        );
+    })
+}
@@ -0,0 +1,126 @@
+use wgpu::util::DownloadTexture;
+
+use crate::common::{initialize_test, TestParameters};
+
+#[test]
+fn srgb_view_of_unorm_texture() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::VIEW_FORMATS),
+        |ctx| {
+            let desc = wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_SRC,
+                view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+            };
+            let texture = ctx.device.create_texture(&desc);
+            let view = texture.create_view(&wgpu::TextureViewDescriptor {
+                format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+                ..Default::default()
+            });
+
+            let mut encoder = ctx
+                .device
+                .create_command_encoder(&wgpu::CommandEncoderDescriptor::default());
+            encoder.begin_render_pass(&wgpu::RenderPassDescriptor {
+                label: None,
+                color_attachments: &[wgpu::RenderPassColorAttachment {
+                    view: &view,
+                    resolve_target: None,
+                    ops: wgpu::Operations {
+                        load: wgpu::LoadOp::Clear(wgpu::Color {
+                            r: 0.5,
+                            g: 0.0,
+                            b: 1.0,
+                            a: 1.0,
+                        }),
+                        store: true,
+                    },
+                }],
+                depth_stencil_attachment: None,
+                occlusion_query_set: None,
+            });
+            ctx.queue.submit(Some(encoder.finish()));
+
+            let download = DownloadTexture::read_texture(
+                &ctx.device,
+                &ctx.queue,
+                &texture,
+                &desc,
+                &wgpu::ImageSubresourceRange::default(),
+            );
+            ctx.device.poll(wgpu::Maintain::Wait);
+            let download = pollster::block_on(download).unwrap();
+            // The clear color is encoded as sRGB, linear 0.5 being stored as 188.
+            for texel in download.chunks(4) {
+                assert!((187..=188).contains(&texel[0]), "{:?}", texel);
+                assert_eq!(&texel[1..], &[0, 255, 255]);
+            }
+        },
+    )
+}
+
+#[test]
+fn incompatible_view_format() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::VIEW_FORMATS),
+        |ctx| {
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            let _ = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[wgpu::TextureFormat::R32Float],
+            });
+            let error = pollster::block_on(ctx.device.pop_error_scope());
+            assert!(error.is_some());
+        },
+    )
+}
+
+#[test]
+fn storage_texture_with_srgb_view_format() {
+    initialize_test(
+        TestParameters::default().downlevel_flags(wgpu::DownlevelFlags::VIEW_FORMATS),
+        |ctx| {
+            ctx.device.push_error_scope(wgpu::ErrorFilter::Validation);
+            // `Rgba8UnormSrgb` doesn't support storage, which only limits the views in that format.
+            let texture = ctx.device.create_texture(&wgpu::TextureDescriptor {
+                label: None,
+                size: wgpu::Extent3d {
+                    width: 4,
+                    height: 4,
+                    depth_or_array_layers: 1,
+                },
+                mip_level_count: 1,
+                sample_count: 1,
+                dimension: wgpu::TextureDimension::D2,
+                format: wgpu::TextureFormat::Rgba8Unorm,
+                usage: wgpu::TextureUsages::STORAGE_BINDING | wgpu::TextureUsages::TEXTURE_BINDING,
+                view_formats: &[wgpu::TextureFormat::Rgba8UnormSrgb],
+            });
+            let _ = texture.create_view(&wgpu::TextureViewDescriptor {
+                format: Some(wgpu::TextureFormat::Rgba8UnormSrgb),
+                ..Default::default()
+            });
+            let error = pollster::block_on(ctx.device.pop_error_scope());
+            assert!(error.is_none(), "{:?}", error);
+        },
+    )
+}
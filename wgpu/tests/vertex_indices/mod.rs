@@ -77,6 +77,7 @@ fn pulling_common(
                     write_mask: wgpu::ColorWrites::ALL,
                 }],
             }),
+            cache: None,
         });
 
     let dummy = ctx
@@ -95,6 +96,7 @@ fn pulling_common(
                 dimension: wgpu::TextureDimension::D2,
                 format: wgpu::TextureFormat::Rgba8Unorm,
                 usage: wgpu::TextureUsages::RENDER_ATTACHMENT | wgpu::TextureUsages::COPY_DST,
+                view_formats: &[],
             },
             &[0, 0, 0, 1],
         )
@@ -112,6 +114,7 @@ fn pulling_common(
         }],
         depth_stencil_attachment: None,
         label: None,
+        occlusion_query_set: None,
     });
 
     rpass.set_pipeline(&pipeline);